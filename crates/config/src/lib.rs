@@ -6,7 +6,7 @@ use std::io::{Error as IOError, Write};
 
 use config::{Config as ConfigLib, ConfigError as ConfigErrorLib, File as ConfigFile, ValueKind};
 use serde::de::Error as SerdeError;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use spherix_macro::config;
 
@@ -38,10 +38,17 @@ config!(
             session_host: PathBuf PathBuf::from("http://127.0.0.1:25585/authlib-injector/sessionserver")
         },
         world: struct World {
-            seed: i32 1,
+            seed: Seed Seed::from(1),
             strategy: WorldStrategy WorldStrategy::GENERATE,
             path: PathBuf PathBuf::from("./world"),
-            view_distance: u8 8
+            view_distance: u8 8,
+            data_packs: Vec<String> Vec::<String>::new(),
+            generator: struct WorldGenerator {
+                overworld: GeneratorKind GeneratorKind::NOISE,
+                nether: GeneratorKind GeneratorKind::NOISE,
+                end: GeneratorKind GeneratorKind::NOISE,
+                flat_preset: String "minecraft:classic_flat"
+            }
         },
         chat: struct Chat {
             secure: bool true
@@ -80,6 +87,14 @@ config_enum_case_ignore!(
     }
 );
 
+config_enum_case_ignore!(
+    pub enum GeneratorKind {
+        NOISE,
+        FLAT,
+        VOID
+    }
+);
+
 pub enum ConfigResult {
     Presented(Config),
     Created(Config),
@@ -232,3 +247,112 @@ impl From<PathBuf> for ValueKind {
         ValueKind::String(value.0.to_str().unwrap().to_string())
     }
 }
+
+/// World seed. Besides plain numbers, it accepts text seeds the same way as
+/// vanilla does: trimmed text which is not a number is turned into the seed by
+/// Java's `String.hashCode()`, and empty text means a random seed.
+///
+/// Random seed is chosen anew on each start, so the server replaces it with the
+/// seed persisted in the world directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seed(pub i64, bool);
+
+impl Seed {
+    pub fn from_text(text: &str) -> Self {
+        let text = text.trim();
+        if text.is_empty() {
+            return Self(rand::random(), true)
+        }
+
+        match text.parse::<i64>() {
+            Ok(seed) => Self::from(seed),
+            Err(_) => Self::from(java_string_hash(text) as i64)
+        }
+    }
+
+    /// Whether the seed was chosen randomly instead of being configured.
+    pub fn is_random(&self) -> bool {
+        self.1
+    }
+}
+
+/// Java's `String.hashCode()`: polynomial hash over UTF-16 code units.
+fn java_string_hash(s: &str) -> i32 {
+    s.encode_utf16().fold(0i32, |hash, unit| hash.wrapping_mul(31).wrapping_add(unit as i32))
+}
+
+impl From<i64> for Seed {
+    fn from(value: i64) -> Self {
+        Self(value, false)
+    }
+}
+
+impl From<Seed> for ValueKind {
+    fn from(value: Seed) -> Self {
+        if value.is_random() {
+            ValueKind::String(String::new())
+        } else {
+            ValueKind::I64(value.0)
+        }
+    }
+}
+
+impl Serialize for Seed {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        if self.is_random() {
+            serializer.serialize_str("")
+        } else {
+            serializer.serialize_i64(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Seed {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        struct SeedVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for SeedVisitor {
+            type Value = Seed;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("integer or string seed")
+            }
+
+            fn visit_i64<E: SerdeError>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(Seed::from(v))
+            }
+
+            fn visit_u64<E: SerdeError>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(Seed::from(v as i64))
+            }
+
+            fn visit_str<E: SerdeError>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(Seed::from_text(v))
+            }
+        }
+
+        deserializer.deserialize_any(SeedVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Seed;
+
+    #[test]
+    fn seed_from_text() {
+        assert_eq!(Seed::from(-4172144997902289642), Seed::from_text("-4172144997902289642"));
+        assert_eq!(Seed::from(99162322), Seed::from_text("hello"));
+        assert_eq!(Seed::from(99162322), Seed::from_text("  hello\t"));
+        assert_eq!(Seed::from(-727589544), Seed::from_text("Spherix world seed"));
+        assert!(!Seed::from_text("hello").is_random());
+    }
+
+    #[test]
+    fn empty_seed_is_random() {
+        let seeds = (0..4).map(|_| Seed::from_text(" ")).collect::<Vec<_>>();
+
+        assert!(seeds.iter().all(Seed::is_random));
+        assert!(seeds.iter().any(|seed| *seed != seeds[0]));
+    }
+}
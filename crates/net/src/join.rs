@@ -4,6 +4,9 @@ use flume::{Receiver, Sender};
 use nbt::{Blob, Value};
 use uuid::Uuid;
 
+use sha2::{Digest, Sha256};
+
+use spherix_config::Seed;
 use spherix_proto::io::VarInt;
 use spherix_proto::packet::clientbound::PlayMapping as ClientboundPlayMapping;
 use spherix_proto::packet::clientbound::{Login, PlayMapping};
@@ -47,7 +50,7 @@ impl<'a> JoinContext<'a> {
     }
 }
 
-pub async fn join(ctx: JoinContext<'_>) {
+pub async fn join(ctx: JoinContext<'_>, seed: Seed) {
     let mut dimension_type = HashMap::new();
 
     {
@@ -268,7 +271,7 @@ pub async fn join(ctx: JoinContext<'_>) {
         registry_codec: blob,
         dimension_type: "overworld".to_string(),
         dimension_name: "overworld".to_string(),
-        hashed_seed: 0,
+        hashed_seed: hashed_seed(seed),
         max_players: VarInt(100),
         view_distance: VarInt(8),
         simulation_distance: VarInt(8),
@@ -292,3 +295,11 @@ pub async fn join(ctx: JoinContext<'_>) {
     //
     // println!("{:?}", a);
 }
+
+/// Client uses the hashed seed for biome noise of the rendering. As in vanilla,
+/// it is the first 8 bytes of SHA-256 of the seed (both are little-endian).
+fn hashed_seed(seed: Seed) -> i64 {
+    let digest = Sha256::digest(seed.0.to_le_bytes());
+
+    i64::from_le_bytes(digest[..8].try_into().unwrap())
+}
@@ -51,7 +51,7 @@ impl Worker {
         tokio::task::spawn(async move {
             let preamble = Preamble::new(&mut self.conn, &mut self.reader, &mut self.writer, self.biomes);
 
-            let join_ctx = handle_preamble(preamble, config).await;
+            let join_ctx = handle_preamble(preamble, config.clone()).await;
 
             if join_ctx.is_err() {
                 let err = join_ctx.err().unwrap();
@@ -87,7 +87,7 @@ impl Worker {
             let join_ctx = join_ctx_val.unwrap();
             let client = join_ctx.to_client(self.received.clone(), self.to_send.clone());
 
-            join(join_ctx).await;
+            join(join_ctx, config.world.seed).await;
 
             self
                 .players
@@ -49,6 +49,8 @@ use crate::systems::player::{on_spawn, poll_packets, spawn_player_entities};
 use crate::ticker::TickerPlugin;
 use crate::world::dimension::{last_sent_set_center_chunk, load_chunks, on_chunk_data_sent, on_load_event, on_player_movement, poll_chunks, poll_unload_chunks_events};
 use crate::world::player::worker::{LoadPropertiesTaskHandler, LoadPropertiesTaskResultReceiver};
+use crate::world::region::pregen;
+use crate::world::seed;
 use crate::world::world::World;
 use spherix_math::vector::{Vector3, Vector3f};
 use spherix_world::chunk::biome::Biome;
@@ -68,6 +70,7 @@ use spherix_worldgen::noise::json::{deserializers_with_markers, Resolver};
 use spherix_worldgen::noise::perlin::octave::MultiOctaveNoiseFactory;
 use spherix_worldgen::noise::perlin::{DefaultNoise, LegacyNoise};
 use spherix_worldgen::noise::settings::NoiseSettings;
+use spherix_worldgen::pack::DataPacks;
 use spherix_worldgen::rng::{Rng, RngForkable, XoroShiro};
 use spherix_worldgen::surface::json::{condition_deserializers, rule_deserializers};
 
@@ -113,12 +116,34 @@ async fn main() {
 
     drop(tmp_logger);
 
-    let config = config.unwrap().unwrap();
+    let mut config = config.unwrap().unwrap();
 
     let _guard = configure_logger(&config.log);
 
     info!("{}", BANNER);
 
+    // Both the server and the offline pre-generation use the persisted seed.
+    config.world.seed = match seed::resolve(config.world.seed, &config.world.path.inner()) {
+        Ok(seed) => seed,
+        Err(e) => {
+            error!("Error resolving seed of the world: {}", e);
+            exit(1);
+        }
+    };
+
+    let matches = clap::Command::new("spherix-server")
+        .subcommand(pregen::cli_command())
+        .get_matches();
+
+    if let Some(("pregen", matches)) = matches.subcommand() {
+        if let Err(e) = pregen::run_offline(&config, matches) {
+            error!("Pre-generation interrupted by error: {}", e);
+            exit(1);
+        }
+
+        return;
+    }
+
     if config.auth.enabled {
         let chat = if config.chat.secure {
             owo_colors::OwoColorize::green(&"with secure").to_string()
@@ -238,7 +263,7 @@ async fn main() {
     // // let now = Instant::now();
     // let r = df_resolver.resolve(&final_density).unwrap();
 
-    let mut rng = XoroShiro::new(1);
+    let mut rng = XoroShiro::new(config.world.seed.0 as u64);
 
     let forked = rng.fork_pos();
 
@@ -294,7 +319,18 @@ async fn main() {
 
     let now = Instant::now();
 
-    let mut world_mc = World::new(PathBuf::from("world"), palette, Arc::new(biome_palette));
+    let data_pack_paths = config.world.data_packs.iter().map(PathBuf::from).collect::<Vec<_>>();
+    let packs = Arc::new(DataPacks::load(PathBuf::from("./generated"), &data_pack_paths).unwrap());
+
+    info!(
+        "{} data packs were successfully loaded {}",
+        owo_colors::OwoColorize::blue(&packs.packs().len()),
+        owo_colors::OwoColorize::bright_black(&format!("({:.0?} elapsed)", now.elapsed()))
+    );
+
+    let now = Instant::now();
+
+    let mut world_mc = World::new(w, config.world.strategy, config.world.seed.0, &config.world.generator, packs, palette, Arc::new(biome_palette));
     let overworld = world_mc.dimension_mut(DimensionKind::Overworld);
 
     // println!("{:?}", overworld.block_at(Vector3::new(0, 0, 97)));
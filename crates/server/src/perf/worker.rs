@@ -1,9 +1,10 @@
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::thread;
 use std::time::Duration;
 
-use flume::Receiver;
+use flume::{Receiver, Sender};
 use rayon::{ThreadPool, ThreadPoolBuilder};
 
 /// Task handler with statically-dispatched tasks.
@@ -11,6 +12,13 @@ pub trait StaticTaskHandle<T, S> {
     fn handle(&self, task: T, non_shared_state: S);
 }
 
+/// Task of the [`StaticWorker`]. Tasks of the same partition are handled one
+/// by one in the order they were received, tasks without a partition are
+/// handled in any order.
+pub trait Partitioned {
+    fn partition(&self) -> Option<u64>;
+}
+
 /// Task handler with dynamically-dispatched tasks. Much more convenient for usage
 /// than [`StaticTaskHandle`], but with an additional indirection overhead.
 pub trait DynamicTaskHandler {
@@ -30,6 +38,7 @@ impl DynamicTaskHandler for DynamicTaskHandlerDelegate {
 
 pub struct StaticWorker<T, H, S> {
     pool: ThreadPool,
+    num_threads: usize,
     handler: Arc<H>,
     thread_local_state: S,
     task_rx: Receiver<T>,
@@ -37,7 +46,7 @@ pub struct StaticWorker<T, H, S> {
 
 impl<T, H, S> StaticWorker<T, H, S>
     where
-        T: Send + 'static,
+        T: Partitioned + Send + 'static,
         H: StaticTaskHandle<T, S> + Send + Sync + 'static,
         S: Send + Clone + 'static
 {
@@ -54,6 +63,7 @@ impl<T, H, S> StaticWorker<T, H, S>
                 .num_threads(num_threads)
                 .build()
                 .unwrap(),
+            num_threads,
             thread_local_state,
             handler: Arc::new(handler),
             task_rx
@@ -61,19 +71,42 @@ impl<T, H, S> StaticWorker<T, H, S>
     }
 
     pub fn run(mut self) {
+        // Each partition is handled by a single thread, so its tasks never
+        // run at the same time.
+        let lanes = (0..self.num_threads)
+            .map(|_| self.spawn_lane())
+            .collect::<Vec<_>>();
+
         loop {
             match self.task_rx.recv_timeout(Self::TIMEOUT) {
-                Ok(task ) => {
-                    let handler = self.handler.clone();
-                    let thread_local_state = self.thread_local_state.clone();
-                    self.pool.spawn(move || {
-                        handler.handle(task, thread_local_state);
-                    })
+                Ok(task ) => match task.partition() {
+                    Some(partition) => lanes[(partition % lanes.len() as u64) as usize].send(task).unwrap(),
+                    None => {
+                        let handler = self.handler.clone();
+                        let thread_local_state = self.thread_local_state.clone();
+                        self.pool.spawn(move || {
+                            handler.handle(task, thread_local_state);
+                        })
+                    }
                 },
                 Err(_) => {}
             }
         }
     }
+
+    fn spawn_lane(&self) -> Sender<T> {
+        let (tx, rx) = flume::unbounded::<T>();
+        let handler = self.handler.clone();
+        let thread_local_state = self.thread_local_state.clone();
+
+        thread::spawn(move || {
+            for task in rx.iter() {
+                handler.handle(task, thread_local_state.clone());
+            }
+        });
+
+        tx
+    }
 }
 
 pub struct DynamicWorker<H: DynamicTaskHandler + Sync + 'static> {
@@ -117,27 +150,55 @@ impl<H> DynamicWorker<H>
     }
 }
 
-/// Unsafe wrapper for [`T`] that forcely implement [`Send`] trait.
-/// Can be used to move a structure that does not implement [`Send`] trait to another
-/// thread (provided that there is a guarantee that this structure will only be used
-/// by the new thread (so, be a thread-local) and that there are no thread-unsafe
-/// references to this structure left on other threads).
-#[derive(Clone)]
-pub struct ForceSend<T>(T);
-
-/// All methods of the wrapper structure are marked as unsafe in order to draw attention
-/// to the nature of the wrapper in a code that calls them.
-impl<T> ForceSend<T> {
-    #[inline]
-    pub unsafe fn new(val: T) -> Self {
-        Self(val)
+#[cfg(test)]
+mod tests {
+    use crate::perf::worker::{Partitioned, StaticTaskHandle, StaticWorker};
+    use flume::Sender;
+    use std::thread;
+    use std::time::Duration;
+
+    struct Task(u64, usize);
+
+    impl Partitioned for Task {
+        fn partition(&self) -> Option<u64> {
+            Some(self.0)
+        }
     }
 
-    /// Just move the inner value out of the wrapper.
-    #[inline]
-    pub unsafe fn into_inner(self) -> T {
-        self.0
+    struct Handler(Sender<(u64, usize)>);
+
+    impl StaticTaskHandle<Task, ()> for Handler {
+        fn handle(&self, task: Task, _: ()) {
+            // Earlier tasks take longer, so they would be overtaken by the
+            // later ones on other threads.
+            if task.1 % 2 == 0 {
+                thread::sleep(Duration::from_millis(2));
+            }
+
+            self.0.send((task.0, task.1)).unwrap();
+        }
     }
-}
 
-unsafe impl<T> Send for ForceSend<T> {}
+    #[test]
+    fn tasks_of_a_partition_run_in_order() {
+        let (task_tx, task_rx) = flume::unbounded();
+        let (done_tx, done_rx) = flume::unbounded();
+
+        let worker = StaticWorker::new(Handler(done_tx), (), task_rx, 4);
+        thread::spawn(|| worker.run());
+
+        for i in 0..40 {
+            task_tx.send(Task(i as u64 % 3, i)).unwrap();
+        }
+
+        let mut done = vec![Vec::new(); 3];
+        for _ in 0..40 {
+            let (partition, i) = done_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            done[partition as usize].push(i);
+        }
+
+        for (partition, done) in done.into_iter().enumerate() {
+            assert_eq!((0..40).filter(|i| i % 3 == partition).collect::<Vec<_>>(), done);
+        }
+    }
+}
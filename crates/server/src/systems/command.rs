@@ -1,8 +1,11 @@
 use bevy_ecs::prelude::{Event, EventReader, EventWriter, Res};
-use tracing::debug;
+use tracing::{debug, error};
 
 use crate::console::msg::{Command, CommandReceiver, CommandSource};
 use crate::systems::packet::ChatCommandPacketEvent;
+use crate::world::dimension::forceload;
+use crate::world::region::pregen;
+use crate::world::world::World;
 
 #[derive(Event)]
 pub struct ChatCommandEvent(pub Command);
@@ -29,9 +32,25 @@ pub fn on_command(
 }
 
 pub fn poll_commands(
+    world: Res<World>,
     mut rx: EventReader<ChatCommandEvent>
 ) {
     for event in rx.read() {
         debug!("{}", event.0.text);
+
+        let Command { source, text } = &event.0;
+        if !matches!(source, CommandSource::Console) {
+            continue
+        }
+
+        match text.split_whitespace().next() {
+            Some("pregen") => if let Err(e) = pregen::run_console(&world, text) {
+                error!("Unable to pre-generate: {}", e);
+            },
+            Some("forceload") => if let Err(e) = forceload::run_console(&world, text) {
+                error!("Unable to force load: {}", e);
+            },
+            _ => {}
+        }
     }
 }
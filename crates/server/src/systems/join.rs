@@ -22,6 +22,7 @@ use crate::player::{Angle, Position, Rotation};
 use crate::server::ClientReceiver;
 use crate::systems::{schedule_entity_despawn, spawn_entity};
 use crate::world::player::worker::LoadPropertiesTaskResultReceiver;
+use crate::world::world::World;
 
 pub fn on_join(
     clients: Res<ClientReceiver>,
@@ -229,6 +230,7 @@ pub fn handle_disconnect(
 }
 
 pub fn despawn_player(
+    world: Res<World>,
     mut removals: RemovedComponents<PlayerType>,
     query: Query<(Entity, &Id, &ToSend)>,
     mut commands: Commands
@@ -236,6 +238,10 @@ pub fn despawn_player(
     for removed_player in removals.read() {
         let (_, removed_id, _) = query.get(removed_player).unwrap();
 
+        for dim in world.dimensions().values() {
+            dim.remove_player_tickets(removed_player);
+        }
+
         for (existing_entity, _, to_send) in query.iter() {
             if existing_entity == removed_player {
                 continue;
@@ -57,7 +57,7 @@ macro_rules! ok_or_skip {
             }
 
             res.unwrap()
-        };
+        }
     };
 }
 
@@ -0,0 +1,100 @@
+use clap::{Arg, ArgMatches, Command};
+use owo_colors::OwoColorize;
+use spherix_world::chunk::pos::ChunkPos;
+use tracing::info;
+
+use crate::world::region::pregen::{parse_dimension, parse_pos};
+use crate::world::world::World;
+
+/// Definition of the `forceload` command of the console, which keeps chunks
+/// loaded without players around (see [`crate::world::dimension::ticket::TicketKind::Forced`]).
+pub fn console_command() -> Command {
+    let pos = || Arg::new("pos")
+        .allow_hyphen_values(true)
+        .help("Chunk coordinates x,z");
+
+    Command::new("forceload")
+        .about("Keeps chunks loaded, even if no player is around")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("dimension")
+                .long("dimension")
+                .short('d')
+                .global(true)
+                .default_value("overworld")
+                .help("overworld, the_nether or the_end")
+        )
+        .subcommand(Command::new("add").about("Forces the chunk to stay loaded").arg(pos().required(true)))
+        .subcommand(Command::new("remove").about("Lets the chunk be unloaded").arg(pos().required(true)))
+        .subcommand(
+            Command::new("query")
+                .about("Shows whether the chunk is force loaded, or how many chunks are loaded if no chunk is given")
+                .arg(pos())
+        )
+}
+
+/// Runs the `forceload` command of the console.
+pub fn run_console(world: &World, input: &str) -> anyhow::Result<()> {
+    let words = shellwords::split(input)?;
+    let matches = match console_command().try_get_matches_from(words) {
+        Ok(matches) => matches,
+        Err(e) => {
+            e.print()?;
+
+            return Ok(());
+        }
+    };
+
+    let (name, matches) = matches.subcommand().unwrap();
+    let kind = parse_dimension(matches.get_one::<String>("dimension").unwrap())?;
+    let dimension = world.dimension(kind);
+
+    let Some(pos) = pos(matches)? else {
+        info!("{} chunks of {:?} are loaded", dimension.chunks_loaded().bright_blue(), kind);
+
+        return Ok(());
+    };
+
+    let at = format!("{},{}", pos.x(), pos.z());
+
+    match name {
+        "add" => {
+            dimension.force_load(pos.clone());
+            info!("Chunk {} of {:?} is force loaded", at.green(), kind);
+        }
+        "remove" => {
+            dimension.unforce_load(&pos);
+            info!("Chunk {} of {:?} is no longer force loaded", at.green(), kind);
+        }
+        _ => match dimension.is_force_loaded(&pos) {
+            true => info!("Chunk {} of {:?} is force loaded", at.green(), kind),
+            false => info!("Chunk {} of {:?} is not force loaded", at.green(), kind),
+        },
+    }
+
+    Ok(())
+}
+
+fn pos(matches: &ArgMatches) -> anyhow::Result<Option<ChunkPos>> {
+    matches.get_one::<String>("pos").map(|pos| parse_pos(pos)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::dimension::forceload::console_command;
+
+    #[test]
+    fn console_command_accepts_negative_coordinates() {
+        let matches = console_command()
+            .try_get_matches_from(["forceload", "add", "-3,-7", "-d", "the_nether"])
+            .unwrap();
+        let (name, matches) = matches.subcommand().unwrap();
+
+        assert_eq!("add", name);
+        assert_eq!("-3,-7", matches.get_one::<String>("pos").unwrap());
+        assert_eq!("the_nether", matches.get_one::<String>("dimension").unwrap());
+
+        assert!(console_command().try_get_matches_from(["forceload", "remove"]).is_err());
+        assert!(console_command().try_get_matches_from(["forceload", "query"]).is_ok());
+    }
+}
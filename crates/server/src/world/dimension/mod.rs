@@ -1,17 +1,17 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 
 use bevy_ecs::event::EventReader;
 use bevy_ecs::prelude::{Changed, Commands, Component, Entity, EventWriter, Res, Without};
 use bevy_ecs::query::With;
 use bevy_ecs::system::Query;
+use anyhow::anyhow;
 use flume::{unbounded, Receiver, Sender};
-use gxhash::GxBuildHasher;
 
-use spherix_config::Config;
-use spherix_math::vector::{OrderedSquareIter, RadialIter};
+use spherix_config::{Config, GeneratorKind, WorldGenerator, WorldStrategy};
+use spherix_math::vector::{OrderedSquareIter, RadialIter, Vector3};
 use spherix_proto::io::VarInt;
 use spherix_proto::packet::clientbound::{PlayMapping, SetCenterChunk, SetDefaultSpawnPosition, UnloadChunk};
 use spherix_world::chunk::pos::{ChunkPos, GlobalChunkPos};
@@ -19,15 +19,26 @@ use spherix_world::dimension::DimensionKind;
 
 use crate::entities::living::player::{ChunkDidLoadedEvent, DimensionKindPosPair, KnownChunks, LastSentSetCenterChunkPacket, LoadedChunksCounter, PlayerNeedChunksEvent, PlayerType, PlayerUnloadChunksEvent, Spawned, ToSend};
 use crate::entities::Uuid;
-use crate::perf::worker::{ForceSend, StaticWorker};
+use crate::perf::worker::StaticWorker;
 use crate::player::Position;
-use crate::world::region::generator::RegionGeneratorWorkerHandler;
-use crate::world::region::worker::{ChunkTask, LoadChunkTask};
+use crate::world::region::pregen::{PregenJob, PregenOptions};
+use crate::world::region::provider::ChunkProviderWorkerHandler;
+use crate::world::dimension::ticket::{TicketKind, Tickets};
+use crate::world::region::worker::{ChunkTask, LoadChunkTask, RegionLoadWorkerHandler, UnloadChunkTask};
 use crate::world::world::World;
 use spherix_world::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
 use spherix_worldgen::chunk::column::ChunkColumn;
+use spherix_worldgen::pack::DataPacks;
+use spherix_worldgen::world as worldgen;
 
+pub mod forceload;
 pub mod layout;
+pub mod ticket;
+
+/// Position where players appear in the world by default.
+pub const SPAWN_POSITION: Vector3 = Vector3::new(20, 70, -10);
+/// Radius (in chunks) of the square around the spawn, which is always loaded.
+pub const SPAWN_CHUNKS_RADIUS: i32 = 2;
 
 pub struct Chunks(pub RwLock<HashMap<ChunkPos, Option<Arc<ChunkColumn>>>>);
 
@@ -42,36 +53,50 @@ pub struct Dimension {
 
     chunks: Chunks,
 
-    player_chunks: Arc<RwLock<HashMap<ChunkPos, HashMap<Entity, ()>>>>,
+    tickets: RwLock<Tickets>,
+
+    pregen: Mutex<Option<Arc<PregenJob>>>,
 }
 
 impl Dimension {
-    pub fn new(dim: DimensionKind, dir: PathBuf, palette: Arc<BlockGlobalPalette>, biomes_palette: Arc<BiomeGlobalPalette>) -> Self {
+    pub fn new(
+        dim: DimensionKind,
+        world_dir: PathBuf,
+        strategy: WorldStrategy,
+        seed: i64,
+        generator_config: &WorldGenerator,
+        packs: Arc<DataPacks>,
+        palette: Arc<BlockGlobalPalette>,
+        biomes_palette: Arc<BiomeGlobalPalette>
+    ) -> Self {
+        let dir = dim.dir(&world_dir);
         let (chunk_tasks_tx, chunk_tasks_rx) = unbounded();
         let (chunk_tx, chunk_rx) = unbounded();
-        // let worker = StaticWorker::new(
-        //     RegionLoadWorkerHandler::new(
-        //         dir.clone(),
-        //         palette.clone(),
-        //         biomes_palette.clone(),
-        //         chunk_tx,
-        //     ),
-        //     chunk_tasks_rx,
-        //     4,
-        // );
-
-        let generator_chunk_cache = Arc::new(RwLock::new(HashMap::with_hasher(GxBuildHasher::default())));
-
-        let (handler, noise_settings, entropy_bag, rule_factory) = RegionGeneratorWorkerHandler::new(
-            palette.clone(),
-            biomes_palette.clone(),
-            generator_chunk_cache,
-            chunk_tx,
-        );
+
+        let loader = match strategy {
+            WorldStrategy::GENERATE => None,
+            WorldStrategy::LOAD => Some(RegionLoadWorkerHandler::new(
+                dir.clone(),
+                palette.clone(),
+                biomes_palette.clone()
+            ))
+        };
+
+        let generator = worldgen::WorldGenerator::builder()
+            .data_dir("./generated")
+            .seed(seed)
+            .dimension(dim)
+            .kind(generator_kind(dim, generator_config))
+            .palettes(palette.clone(), biomes_palette.clone())
+            .packs(packs)
+            .build()
+            .unwrap_or_else(|e| panic!("Unable to set up the generator of {:?}: {}", dim, e));
+
+        warn_unsupported(&generator);
 
         let worker = StaticWorker::new(
-            handler,
-            (unsafe { ForceSend::new(noise_settings) }, entropy_bag, rule_factory),
+            ChunkProviderWorkerHandler::new(loader, generator, 4, chunk_tx),
+            (),
             chunk_tasks_rx,
             4,
         );
@@ -83,24 +108,118 @@ impl Dimension {
             dir,
             palette,
             biomes_palette,
-            // worker,
             chunk_tasks_tx,
             chunk_rx,
             chunks: Chunks(Default::default()),
-            player_chunks: Arc::default(),
+            tickets: RwLock::default(),
+            pregen: Mutex::default(),
         }
     }
 
-    fn insert_to_player_chunks(&self, pos: ChunkPos, player: Entity) {
-        let mut guard = self.player_chunks.write().unwrap();
-        if !guard.contains_key(&pos) {
-            guard.insert(pos.clone(), HashMap::new());
+    /// Adds the ticket to the chunk. The chunk starts loading if it is the first
+    /// ticket of the chunk.
+    pub fn add_ticket(&self, pos: ChunkPos, ticket: TicketKind) {
+        let first = self.tickets.write().unwrap().add(pos.clone(), ticket);
+        if !first {
+            return;
         }
 
-        let players = guard.get_mut(&pos).unwrap();
-        players.insert(player, ());
+        let mut guard = self.chunks.0.write().unwrap();
+        if guard.contains_key(&pos) {
+            return;
+        }
+
+        guard.insert(pos.clone(), None);
         // drop the guard as early as possible
         drop(guard);
+
+        self.submit_chunk_task(ChunkTask::Load(LoadChunkTask(pos)));
+    }
+
+    /// Removes the ticket from the chunk. The chunk is saved and evicted from
+    /// memory if it was the last ticket of the chunk.
+    pub fn remove_ticket(&self, pos: &ChunkPos, ticket: &TicketKind) {
+        let last = self.tickets.write().unwrap().remove(pos, ticket);
+        if last {
+            self.unload(pos);
+        }
+    }
+
+    /// Removes all tickets of the player, for example, when the player leaves.
+    pub fn remove_player_tickets(&self, player: Entity) {
+        let released = self.tickets.write().unwrap().remove_player(player);
+        for pos in released {
+            self.unload(&pos);
+        }
+    }
+
+    /// Keeps chunks around the given spawn chunk always loaded.
+    pub fn add_spawn_tickets(&self, spawn: ChunkPos) {
+        for x in -SPAWN_CHUNKS_RADIUS..=SPAWN_CHUNKS_RADIUS {
+            for z in -SPAWN_CHUNKS_RADIUS..=SPAWN_CHUNKS_RADIUS {
+                self.add_ticket(ChunkPos::new(spawn.x() + x, spawn.z() + z), TicketKind::Spawn);
+            }
+        }
+    }
+
+    /// Keeps the chunk loaded until [`Self::unforce_load()`] is called for it.
+    pub fn force_load(&self, pos: ChunkPos) {
+        self.add_ticket(pos, TicketKind::Forced);
+    }
+
+    pub fn unforce_load(&self, pos: &ChunkPos) {
+        self.remove_ticket(pos, &TicketKind::Forced);
+    }
+
+    pub fn is_force_loaded(&self, pos: &ChunkPos) -> bool {
+        self.tickets.read().unwrap().contains(pos, &TicketKind::Forced)
+    }
+
+    fn unload(&self, pos: &ChunkPos) {
+        let chunk = self.chunks.0.write().unwrap().remove(pos);
+
+        // Chunk which is still being loaded is unloaded as soon as it arrives
+        // (see poll_chunks()).
+        if let Some(Some(chunk)) = chunk {
+            self.submit_chunk_task(ChunkTask::Unload(UnloadChunkTask(chunk)));
+        }
+    }
+
+    fn is_loaded(&self, pos: &ChunkPos) -> bool {
+        self.chunks.0.read().unwrap().get(pos).is_some_and(|chunk| chunk.is_some())
+    }
+
+    /// Count of chunks which are kept in memory by tickets.
+    pub fn chunks_loaded(&self) -> usize {
+        self.tickets.read().unwrap().len()
+    }
+
+    /// Starts the pre-generation of the area. It runs on a chunk worker in
+    /// the background, one job per dimension at a time.
+    pub fn pregen(&self, options: PregenOptions) -> anyhow::Result<()> {
+        let mut guard = self.pregen.lock().unwrap();
+        if guard.as_ref().is_some_and(|job| !job.is_finished()) {
+            return Err(anyhow!("Pre-generation of {:?} is already running", self.dim));
+        }
+
+        let job = PregenJob::new(options);
+        *guard = Some(job.clone());
+        drop(guard);
+
+        self.submit_chunk_task(ChunkTask::Pregen(job));
+
+        Ok(())
+    }
+
+    /// Stops the running pre-generation. Returns false if there is none.
+    pub fn stop_pregen(&self) -> bool {
+        match self.pregen.lock().unwrap().take() {
+            Some(job) if !job.is_finished() => {
+                job.stop();
+                true
+            }
+            _ => false
+        }
     }
 
     pub fn submit_chunk_task(&self, task: ChunkTask) {
@@ -108,6 +227,41 @@ impl Dimension {
     }
 }
 
+/// Generator kind of the dimension in the config.
+pub(crate) fn generator_kind(dim: DimensionKind, config: &WorldGenerator) -> worldgen::GeneratorKind {
+    let kind = match dim {
+        DimensionKind::Overworld => config.overworld,
+        DimensionKind::TheNether => config.nether,
+        DimensionKind::TheEnd => config.end,
+    };
+
+    match kind {
+        GeneratorKind::NOISE => worldgen::GeneratorKind::Noise,
+        GeneratorKind::FLAT => worldgen::GeneratorKind::Flat { preset: config.flat_preset.clone() },
+        GeneratorKind::VOID => worldgen::GeneratorKind::Void,
+    }
+}
+
+fn warn_unsupported(generator: &worldgen::WorldGenerator) {
+    let features = generator.features().failed();
+    if !features.is_empty() {
+        tracing::warn!(
+            "{} placed features are not supported and will be skipped: {}",
+            features.len(),
+            features.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    let structures = generator.structures().failed();
+    if !structures.is_empty() {
+        tracing::warn!(
+            "{} structures are not supported and will be skipped: {}",
+            structures.len(),
+            structures.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+}
+
 
 pub fn load_chunks(
     world: Res<World>,
@@ -121,33 +275,23 @@ pub fn load_chunks(
         for chunk in s.chunks.clone() {
             let dim = world.dimension(chunk.dim.clone());
 
-            let mut guard = dim.chunks.0.write().unwrap();
+            dim.add_ticket(chunk.vec.clone(), TicketKind::Player(s.entity));
 
-            if guard.contains_key(&chunk.vec) && guard.get(&chunk.vec).unwrap().is_some() {
-                dim.insert_to_player_chunks(chunk.vec.clone(), s.entity);
-
-                // Drop guard to prevent deadlock in send_chunk()
-                drop(guard);
-
-                let (_, mut known_chunks, mut counter, to_send) = query.get_mut(s.entity).unwrap();
-                send_chunk(chunk.vec.clone(), dim, to_send);
-                if known_chunks.0.contains_key(&chunk.vec) {
-                    let known_chunk = known_chunks.0.get_mut(&chunk.vec).unwrap();
-                    *known_chunk = true;
-                } else {
-                    known_chunks.0.insert(chunk.vec, true);
-                }
-
-                counter.0 += 1;
+            // Chunk which is not loaded yet will be sent by on_load_event().
+            if !dim.is_loaded(&chunk.vec) {
                 continue;
             }
 
-            guard.insert(chunk.vec.clone(), None);
-            // drop the guard as early as possible
-            drop(guard);
+            let (_, mut known_chunks, mut counter, to_send) = query.get_mut(s.entity).unwrap();
+            send_chunk(chunk.vec.clone(), dim, to_send);
+            if known_chunks.0.contains_key(&chunk.vec) {
+                let known_chunk = known_chunks.0.get_mut(&chunk.vec).unwrap();
+                *known_chunk = true;
+            } else {
+                known_chunks.0.insert(chunk.vec, true);
+            }
 
-            dim.insert_to_player_chunks(chunk.vec.clone(), s.entity);
-            dim.submit_chunk_task(ChunkTask::Load(LoadChunkTask(chunk.vec)));
+            counter.0 += 1;
         }
     }
 }
@@ -161,10 +305,15 @@ pub fn poll_chunks(
             let pos = chunk.pos();
             let mut guard_chunk_col = dim.chunks.0.write().unwrap();
 
-            if guard_chunk_col.contains_key(&chunk.pos()) {
-                guard_chunk_col.insert(chunk.pos(), Some(chunk));
+            if !guard_chunk_col.contains_key(&pos) {
+                drop(guard_chunk_col);
+
+                // All tickets of the chunk were removed while it was loading.
+                dim.submit_chunk_task(ChunkTask::Unload(UnloadChunkTask(chunk)));
+                continue;
             }
 
+            guard_chunk_col.insert(pos.clone(), Some(chunk));
             drop(guard_chunk_col);
 
             tx.send(ChunkDidLoadedEvent {
@@ -185,15 +334,10 @@ pub fn on_load_event(
     for event in rx.read() {
         let dim = world.dimension(event.chunk.dim.clone());
 
-        let guard = dim.player_chunks.read().unwrap();
-        if !guard.contains_key(&event.chunk.vec) {
-            continue;
-        }
-
-        let players = guard.get(&event.chunk.vec).unwrap();
+        let players = dim.tickets.read().unwrap().players(&event.chunk.vec);
 
-        for (player, _) in players {
-            let r = query.get_mut(player.clone());
+        for player in players {
+            let r = query.get_mut(player);
             if r.is_err() {
                 continue;
             }
@@ -237,7 +381,7 @@ pub fn on_chunk_data_sent(
             // SynchronizePlayerPosition packet does not affect this screen anyhow (despite the
             // fact that the documentation says so).
             to_send.send(PlayMapping::SetDefaultSpawnPosition(SetDefaultSpawnPosition {
-                location: spherix_proto::io::Position::new(20, 70, -10),
+                location: spherix_proto::io::Position::new(SPAWN_POSITION.x, SPAWN_POSITION.y, SPAWN_POSITION.z),
                 angle: 0.0,
             })).unwrap();
 
@@ -395,6 +539,7 @@ fn send_set_center_chunk_packet(
 }
 
 pub fn poll_unload_chunks_events(
+    world: Res<World>,
     mut rx: EventReader<PlayerUnloadChunksEvent>,
     query: Query<&ToSend, (With<PlayerType>, With<Spawned>)>
 ) {
@@ -402,6 +547,10 @@ pub fn poll_unload_chunks_events(
         let to_send = query.get(event.entity).unwrap();
 
         for chunk in &event.chunks {
+            world
+                .dimension(chunk.dim())
+                .remove_ticket(&chunk.vec, &TicketKind::Player(event.entity));
+
             to_send.send(
                 PlayMapping::UnloadChunk(UnloadChunk {
                     x: chunk.x(),
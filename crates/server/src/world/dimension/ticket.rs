@@ -0,0 +1,136 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy_ecs::prelude::Entity;
+
+use spherix_world::chunk::pos::ChunkPos;
+
+/// Reason for a chunk to stay loaded. A chunk is kept in memory as long as it
+/// has at least one ticket, and is saved and evicted once the last one is gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TicketKind {
+    /// The chunk is within view distance of the player.
+    Player(Entity),
+    /// The chunk is near the world spawn, so it is always loaded.
+    Spawn,
+    /// The chunk was explicitly requested to stay loaded (for example, by a command).
+    Forced,
+}
+
+/// Tickets of all chunks of a dimension.
+#[derive(Default)]
+pub struct Tickets(HashMap<ChunkPos, HashSet<TicketKind>>);
+
+impl Tickets {
+    /// Returns `true` if the chunk had no tickets before, so it has to be loaded.
+    pub fn add(&mut self, pos: ChunkPos, ticket: TicketKind) -> bool {
+        let tickets = self.0.entry(pos).or_default();
+        let first = tickets.is_empty();
+        tickets.insert(ticket);
+
+        first
+    }
+
+    /// Returns `true` if the removed ticket was the last one of the chunk, so it
+    /// has to be unloaded.
+    pub fn remove(&mut self, pos: &ChunkPos, ticket: &TicketKind) -> bool {
+        let Some(tickets) = self.0.get_mut(pos) else {
+            return false
+        };
+
+        if !tickets.remove(ticket) || !tickets.is_empty() {
+            return false
+        }
+
+        self.0.remove(pos);
+
+        true
+    }
+
+    /// Removes all tickets of the player and returns chunks which have no tickets
+    /// left.
+    pub fn remove_player(&mut self, player: Entity) -> Vec<ChunkPos> {
+        let ticket = TicketKind::Player(player);
+        let mut released = Vec::new();
+
+        self.0.retain(|pos, tickets| {
+            if tickets.remove(&ticket) && tickets.is_empty() {
+                released.push(pos.clone());
+
+                return false
+            }
+
+            true
+        });
+
+        released
+    }
+
+    pub fn contains(&self, pos: &ChunkPos, ticket: &TicketKind) -> bool {
+        self.0.get(pos).is_some_and(|tickets| tickets.contains(ticket))
+    }
+
+    /// Players whose view distance covers the chunk.
+    pub fn players(&self, pos: &ChunkPos) -> Vec<Entity> {
+        let Some(tickets) = self.0.get(pos) else {
+            return Vec::new()
+        };
+
+        tickets
+            .iter()
+            .filter_map(|ticket| match ticket {
+                TicketKind::Player(player) => Some(*player),
+                _ => None
+            })
+            .collect()
+    }
+
+    /// Count of chunks which have at least one ticket.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::Entity;
+
+    use spherix_world::chunk::pos::ChunkPos;
+
+    use crate::world::dimension::ticket::{TicketKind, Tickets};
+
+    #[test]
+    fn last_ticket_releases_chunk() {
+        let mut tickets = Tickets::default();
+        let player = Entity::from_raw(1);
+
+        assert!(tickets.add(ChunkPos::new(0, 0), TicketKind::Player(player)));
+        assert!(!tickets.add(ChunkPos::new(0, 0), TicketKind::Forced));
+        assert!(!tickets.add(ChunkPos::new(0, 0), TicketKind::Player(player)));
+
+        assert!(!tickets.remove(&ChunkPos::new(0, 0), &TicketKind::Player(player)));
+        assert!(tickets.contains(&ChunkPos::new(0, 0), &TicketKind::Forced));
+        // Absent ticket does not release the chunk.
+        assert!(!tickets.remove(&ChunkPos::new(0, 0), &TicketKind::Spawn));
+        assert!(tickets.remove(&ChunkPos::new(0, 0), &TicketKind::Forced));
+        assert!(!tickets.contains(&ChunkPos::new(0, 0), &TicketKind::Forced));
+        assert!(!tickets.remove(&ChunkPos::new(0, 0), &TicketKind::Forced));
+    }
+
+    #[test]
+    fn remove_player() {
+        let mut tickets = Tickets::default();
+        let first = Entity::from_raw(1);
+        let second = Entity::from_raw(2);
+
+        tickets.add(ChunkPos::new(0, 0), TicketKind::Player(first));
+        tickets.add(ChunkPos::new(0, 0), TicketKind::Player(second));
+        tickets.add(ChunkPos::new(1, 0), TicketKind::Player(first));
+        tickets.add(ChunkPos::new(1, 0), TicketKind::Spawn);
+        tickets.add(ChunkPos::new(2, 0), TicketKind::Player(first));
+
+        assert_eq!(vec![ChunkPos::new(2, 0)], tickets.remove_player(first));
+        assert_eq!(vec![second], tickets.players(&ChunkPos::new(0, 0)));
+        assert!(tickets.players(&ChunkPos::new(1, 0)).is_empty());
+        assert_eq!(2, tickets.len());
+    }
+}
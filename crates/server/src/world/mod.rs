@@ -3,3 +3,4 @@ pub mod region;
 pub mod dimension;
 pub mod world;
 pub mod player;
+pub mod seed;
@@ -139,7 +139,7 @@ impl Properties {
         let result = match compression {
             None => Self::deserialize(&mut Decoder::new(buf_read)),
             Some(compression) => {
-                let buf = compression.decode(buf_read);
+                let buf = compression.decode(buf_read)?;
                 Self::deserialize(&mut Decoder::new(Cursor::new(buf)))
             }
         };
@@ -1,4 +1,6 @@
 use std::io::{BufRead, Read};
 
 pub mod worker;
-pub mod generator;
+pub mod provider;
+pub mod scheduler;
+pub mod pregen;
@@ -0,0 +1,599 @@
+use crate::world::dimension::generator_kind;
+use crate::world::region::provider::ChunkProviderWorkerHandler;
+use crate::world::region::scheduler::{ChunkScheduler, Priority};
+use crate::world::region::worker::RegionLoadWorkerHandler;
+use crate::world::world::World;
+use anyhow::anyhow;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use flume::{Receiver, Sender};
+use owo_colors::OwoColorize;
+use spherix_config::Config;
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::status::ChunkStatus;
+use spherix_world::dimension::DimensionKind;
+use spherix_worldgen::chunk::column::ChunkColumn as WorldgenChunkColumn;
+use spherix_worldgen::chunk::step::ChunkStep;
+use spherix_worldgen::world::WorldGenerator;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// Chunks of an area are generated in strips of the width of a region, so the
+/// chunks around a chunk are mostly generated already and region files are
+/// written one at a time.
+const STRIP_WIDTH: i32 = 32;
+
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Chunks generated per second by the pre-generation of the running server,
+/// unless `--rate` is given, so the players are left some headroom.
+const CONSOLE_RATE: &str = "100";
+
+/// Area of the pre-generation in chunk coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PregenArea {
+    /// Square of `2 * radius + 1` chunks on a side around the center.
+    Radius { center: ChunkPos, radius: i32 },
+    /// Rectangle between the corners, both inclusive.
+    Rect { from: ChunkPos, to: ChunkPos },
+}
+
+impl PregenArea {
+    /// Lowest and highest chunk coordinates of the area.
+    fn bounds(&self) -> (ChunkPos, ChunkPos) {
+        match self {
+            PregenArea::Radius { center, radius } => (
+                ChunkPos::new(center.x() - radius, center.z() - radius),
+                ChunkPos::new(center.x() + radius, center.z() + radius),
+            ),
+            PregenArea::Rect { from, to } => (
+                ChunkPos::new(from.x().min(to.x()), from.z().min(to.z())),
+                ChunkPos::new(from.x().max(to.x()), from.z().max(to.z())),
+            ),
+        }
+    }
+
+    pub fn contains(&self, pos: &ChunkPos) -> bool {
+        let (min, max) = self.bounds();
+
+        (min.x()..=max.x()).contains(&pos.x()) && (min.z()..=max.z()).contains(&pos.z())
+    }
+
+    pub fn len(&self) -> usize {
+        let (min, max) = self.bounds();
+
+        (max.x() - min.x() + 1) as usize * (max.z() - min.z() + 1) as usize
+    }
+
+    /// Chunks of the area in the order they are generated.
+    pub fn chunks(&self) -> Vec<ChunkPos> {
+        let (min, max) = self.bounds();
+        let mut chunks = Vec::with_capacity(self.len());
+
+        for strip in (min.x()..=max.x()).step_by(STRIP_WIDTH as usize) {
+            for z in min.z()..=max.z() {
+                for x in strip..=(strip + STRIP_WIDTH - 1).min(max.x()) {
+                    chunks.push(ChunkPos::new(x, z));
+                }
+            }
+        }
+
+        chunks
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PregenOptions {
+    pub dimension: DimensionKind,
+    pub area: PregenArea,
+    /// How many chunks are requested from the scheduler at a time.
+    pub in_flight: usize,
+    /// Upper bound of the chunks requested per second.
+    pub rate: Option<f64>,
+}
+
+impl PregenOptions {
+    pub fn from_matches(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let area = match (matches.get_one::<i32>("radius"), matches.get_one::<String>("from"), matches.get_one::<String>("to")) {
+            (Some(radius), None, None) => PregenArea::Radius {
+                center: matches
+                    .get_one::<String>("center")
+                    .map(|center| parse_pos(center))
+                    .transpose()?
+                    .unwrap_or(ChunkPos::new(0, 0)),
+                radius: *radius,
+            },
+            (None, Some(from), Some(to)) => PregenArea::Rect {
+                from: parse_pos(from)?,
+                to: parse_pos(to)?,
+            },
+            _ => return Err(anyhow!("Either --radius or both --from and --to are required"))
+        };
+
+        Ok(Self {
+            dimension: parse_dimension(matches.get_one::<String>("dimension").unwrap())?,
+            area,
+            in_flight: *matches.get_one::<usize>("in-flight").unwrap(),
+            rate: matches.get_one::<f64>("rate").copied(),
+        })
+    }
+}
+
+/// Definition of the `pregen` command, which is shared by the console and the
+/// command line.
+pub fn command() -> Command {
+    Command::new("pregen")
+        .about("Generates the chunks of the area and saves them to the region files. Chunks, which are saved already, are skipped, so an interrupted run is resumed by running it again")
+        .arg(
+            Arg::new("dimension")
+                .long("dimension")
+                .short('d')
+                .default_value("overworld")
+                .help("overworld, the_nether or the_end")
+        )
+        .arg(
+            Arg::new("radius")
+                .long("radius")
+                .short('r')
+                .value_parser(value_parser!(i32).range(0..))
+                .conflicts_with_all(["from", "to"])
+                .help("Radius of the square around the center in chunks")
+        )
+        .arg(
+            Arg::new("center")
+                .long("center")
+                .allow_hyphen_values(true)
+                .requires("radius")
+                .help("Center of the square as chunk coordinates x,z (0,0 by default)")
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .allow_hyphen_values(true)
+                .requires("to")
+                .help("Corner of the rectangle as chunk coordinates x,z")
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .allow_hyphen_values(true)
+                .requires("from")
+                .help("Opposite corner of the rectangle as chunk coordinates x,z")
+        )
+        .arg(
+            Arg::new("in-flight")
+                .long("in-flight")
+                .value_parser(value_parser!(usize).range(1..))
+                .default_value("16")
+                .help("How many chunks are generated at a time")
+        )
+        .arg(
+            Arg::new("rate")
+                .long("rate")
+                .value_parser(parse_rate)
+                .help("Upper bound of the chunks generated per second")
+        )
+}
+
+/// Definition of the `pregen` command of the console, which can also stop the
+/// running pre-generation.
+pub fn console_command() -> Command {
+    command()
+        .mut_arg("rate", |arg| arg.default_value(CONSOLE_RATE))
+        .arg(
+            Arg::new("stop")
+                .long("stop")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["radius", "from"])
+                .help("Stops the pre-generation of the dimension")
+        )
+}
+
+/// Definition of the `pregen` subcommand of the binary, which generates chunks
+/// without starting the server.
+pub fn cli_command() -> Command {
+    command()
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .value_parser(value_parser!(usize).range(1..))
+                .help("Threads of the generation (all cores by default)")
+        )
+}
+
+pub(crate) fn parse_pos(s: &str) -> anyhow::Result<ChunkPos> {
+    let (x, z) = s
+        .split_once(',')
+        .ok_or_else(|| anyhow!("Invalid chunk coordinates {}, x,z expected", s))?;
+
+    Ok(ChunkPos::new(x.trim().parse()?, z.trim().parse()?))
+}
+
+fn parse_rate(s: &str) -> anyhow::Result<f64> {
+    let rate: f64 = s.trim().parse()?;
+    if !rate.is_finite() || rate <= 0.0 {
+        return Err(anyhow!("Rate must be a positive number, {} given", s))
+    }
+
+    Ok(rate)
+}
+
+pub(crate) fn parse_dimension(s: &str) -> anyhow::Result<DimensionKind> {
+    match s.strip_prefix("minecraft:").unwrap_or(s) {
+        "overworld" => Ok(DimensionKind::Overworld),
+        "the_nether" | "nether" => Ok(DimensionKind::TheNether),
+        "the_end" | "end" => Ok(DimensionKind::TheEnd),
+        _ => Err(anyhow!("Unknown dimension {}", s))
+    }
+}
+
+/// Pre-generation of an area. The chunks it has requested are delivered to
+/// it instead of the players (see [`ChunkProviderWorkerHandler`]).
+pub struct PregenJob {
+    pub options: PregenOptions,
+    claimed: Mutex<HashSet<ChunkPos>>,
+    generated_tx: Sender<Arc<WorldgenChunkColumn>>,
+    generated_rx: Receiver<Arc<WorldgenChunkColumn>>,
+    stopped: AtomicBool,
+    finished: AtomicBool,
+}
+
+impl PregenJob {
+    pub fn new(options: PregenOptions) -> Arc<Self> {
+        let (generated_tx, generated_rx) = flume::unbounded();
+
+        Arc::new(Self {
+            options,
+            claimed: Mutex::default(),
+            generated_tx,
+            generated_rx,
+            stopped: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        })
+    }
+
+    /// Stops requesting chunks. The chunks in generation are still saved.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// Takes the generated chunk, if the job has requested it.
+    pub(crate) fn deliver(&self, chunk: &Arc<WorldgenChunkColumn>) -> bool {
+        if !self.claimed.lock().unwrap().remove(&chunk.pos()) {
+            return false
+        }
+
+        self.generated_tx.send(chunk.clone()).unwrap();
+
+        true
+    }
+
+    /// Requests the chunks of the area, which are not saved yet, keeping the
+    /// number of the chunks in generation within the bound. Generated chunks
+    /// are saved by the provider, they are forgotten by the scheduler once
+    /// no chunk in generation needs them anymore.
+    pub(crate) fn run(&self, scheduler: &Arc<ChunkScheduler>, loader: &RegionLoadWorkerHandler) {
+        let options = &self.options;
+        let chunks = options.area.chunks();
+        let mut progress = Progress::new(options.dimension, chunks.len());
+        let mut tracker = Tracker::new(&options.area);
+
+        info!(
+            "Pre-generating {} chunks of {:?}",
+            chunks.len().bright_blue(),
+            options.dimension
+        );
+
+        let mut next = 0;
+        let mut in_flight = 0;
+
+        while progress.done < chunks.len() {
+            while in_flight < options.in_flight && next < chunks.len() && !self.stopped.load(Ordering::Relaxed) {
+                let throttled = options
+                    .rate
+                    .is_some_and(|rate| progress.generated as f64 + in_flight as f64 >= rate * progress.elapsed());
+                if throttled {
+                    break
+                }
+
+                let pos = chunks[next].clone();
+                next += 1;
+
+                match loader.exists(pos.clone()) {
+                    Ok(true) => {
+                        progress.skipped += 1;
+                        progress.done += 1;
+                        tracker.finish(scheduler, pos, None);
+                        continue
+                    }
+                    Ok(false) => {}
+                    Err(e) => error!("Unable to look up chunk ({}, {}): {}", pos.x().bright_red(), pos.z().bright_blue(), e),
+                }
+
+                self.claimed.lock().unwrap().insert(pos.clone());
+                scheduler.request(pos, Priority::Background);
+                in_flight += 1;
+            }
+
+            if in_flight == 0 && (next == chunks.len() || self.stopped.load(Ordering::Relaxed)) {
+                break
+            }
+
+            if let Ok(chunk) = self.generated_rx.recv_timeout(Duration::from_millis(100)) {
+                in_flight -= 1;
+                progress.generated += 1;
+                progress.done += 1;
+                tracker.finish(scheduler, chunk.pos(), Some(chunk));
+            }
+
+            progress.report_periodically();
+        }
+
+        tracker.release(scheduler);
+        progress.report();
+
+        if progress.done < chunks.len() {
+            info!("Pre-generation of {:?} is stopped, run it again to resume", options.dimension);
+        } else {
+            info!("Pre-generation of {:?} is finished", options.dimension);
+        }
+    }
+}
+
+/// Keeps the generated chunks of the area in the scheduler, until the chunks
+/// around them are generated as well, so they are not generated again as the
+/// neighbours of the chunks still to be generated.
+struct Tracker<'a> {
+    area: &'a PregenArea,
+    radius: i32,
+    finished: HashSet<ChunkPos>,
+    held: HashMap<ChunkPos, Arc<WorldgenChunkColumn>>,
+}
+
+impl<'a> Tracker<'a> {
+    fn new(area: &'a PregenArea) -> Self {
+        Self {
+            area,
+            radius: ChunkStep::dependency_radius(ChunkStatus::Full),
+            finished: HashSet::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    /// Marks the chunk as generated or skipped, then forgets the chunks no
+    /// chunk left to generate depends on.
+    fn finish(&mut self, scheduler: &ChunkScheduler, pos: ChunkPos, chunk: Option<Arc<WorldgenChunkColumn>>) {
+        self.finished.insert(pos.clone());
+        if let Some(chunk) = chunk {
+            self.held.insert(pos.clone(), chunk);
+        }
+
+        for near in around(&pos, self.radius) {
+            let unneeded = self.held.contains_key(&near)
+                && around(&near, self.radius)
+                    .filter(|other| self.area.contains(other))
+                    .all(|other| self.finished.contains(&other));
+
+            if unneeded {
+                scheduler.evict(&self.held.remove(&near).unwrap());
+            }
+        }
+    }
+
+    fn release(&mut self, scheduler: &ChunkScheduler) {
+        for (_, chunk) in self.held.drain() {
+            scheduler.evict(&chunk);
+        }
+    }
+}
+
+/// Chunks of the square around the chunk, the chunk included.
+fn around(pos: &ChunkPos, radius: i32) -> impl Iterator<Item = ChunkPos> {
+    let (x, z) = (pos.x(), pos.z());
+
+    (x - radius..=x + radius).flat_map(move |x| (z - radius..=z + radius).map(move |z| ChunkPos::new(x, z)))
+}
+
+struct Progress {
+    dimension: DimensionKind,
+    total: usize,
+    done: usize,
+    generated: usize,
+    skipped: usize,
+    started_at: Instant,
+    reported_at: Instant,
+}
+
+impl Progress {
+    fn new(dimension: DimensionKind, total: usize) -> Self {
+        Self {
+            dimension,
+            total,
+            done: 0,
+            generated: 0,
+            skipped: 0,
+            started_at: Instant::now(),
+            reported_at: Instant::now(),
+        }
+    }
+
+    /// Seconds since the start.
+    fn elapsed(&self) -> f64 {
+        self.started_at.elapsed().as_secs_f64()
+    }
+
+    fn report_periodically(&mut self) {
+        if self.reported_at.elapsed() >= REPORT_INTERVAL {
+            self.report();
+        }
+    }
+
+    fn report(&mut self) {
+        self.reported_at = Instant::now();
+
+        let rate = self.generated as f64 / self.elapsed().max(f64::EPSILON);
+        let eta = if rate > 0.0 {
+            format_duration(Duration::from_secs_f64((self.total - self.done) as f64 / rate))
+        } else {
+            "unknown".to_owned()
+        };
+
+        info!(
+            "Pre-generated {}/{} chunks of {:?} ({:.1}%, {} skipped), {:.1} chunks/s, ETA {}",
+            self.done.bright_blue(),
+            self.total,
+            self.dimension,
+            self.done as f64 * 100.0 / self.total.max(1) as f64,
+            self.skipped,
+            rate,
+            eta.green()
+        );
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Runs the `pregen` command of the console: starts the pre-generation in the
+/// background or stops it.
+pub fn run_console(world: &World, input: &str) -> anyhow::Result<()> {
+    let words = shellwords::split(input)?;
+    let matches = match console_command().try_get_matches_from(words) {
+        Ok(matches) => matches,
+        Err(e) => {
+            e.print()?;
+
+            return Ok(());
+        }
+    };
+
+    if matches.get_flag("stop") {
+        let dimension = parse_dimension(matches.get_one::<String>("dimension").unwrap())?;
+        if world.dimension(dimension).stop_pregen() {
+            info!("Stopping pre-generation of {:?}...", dimension);
+        } else {
+            warn!("Pre-generation of {:?} is not running", dimension);
+        }
+
+        return Ok(());
+    }
+
+    let options = PregenOptions::from_matches(&matches)?;
+
+    world.dimension(options.dimension).pregen(options)
+}
+
+/// Runs the `pregen` subcommand: generates the chunks into the region files of
+/// the world without starting the server.
+pub fn run_offline(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let options = PregenOptions::from_matches(matches)?;
+    let threads = matches
+        .get_one::<usize>("threads")
+        .copied()
+        .unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(4));
+
+    let now = Instant::now();
+
+    let generator = WorldGenerator::builder()
+        .data_dir("./generated")
+        .data_packs(config.world.data_packs.iter().map(PathBuf::from).collect())
+        .seed(config.world.seed.0)
+        .dimension(options.dimension)
+        .kind(generator_kind(options.dimension, &config.world.generator))
+        .build()?;
+
+    info!(
+        "Generator of {:?} was set up {}",
+        options.dimension,
+        format!("({:.0?} elapsed)", now.elapsed()).bright_black()
+    );
+
+    let loader = RegionLoadWorkerHandler::new(
+        options.dimension.dir(&config.world.path.inner()),
+        generator.block_global_palette().clone(),
+        generator.biome_global_palette().clone()
+    );
+
+    // Nothing but the job receives the chunks.
+    let (chunk_tx, _chunk_rx) = flume::unbounded();
+    let provider = ChunkProviderWorkerHandler::new(Some(loader), generator, threads, chunk_tx);
+
+    provider.pregen(PregenJob::new(options));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::region::pregen::{cli_command, console_command, PregenArea, PregenOptions};
+    use spherix_world::chunk::pos::ChunkPos;
+    use spherix_world::dimension::DimensionKind;
+    use std::collections::HashSet;
+
+    #[test]
+    fn area_chunks_are_unique_and_within_area() {
+        let areas = [
+            PregenArea::Radius { center: ChunkPos::new(5, -3), radius: 20 },
+            PregenArea::Rect { from: ChunkPos::new(40, 7), to: ChunkPos::new(-30, -2) },
+        ];
+
+        for area in areas {
+            let chunks = area.chunks();
+            let unique = chunks.iter().cloned().collect::<HashSet<_>>();
+
+            assert_eq!(area.len(), chunks.len());
+            assert_eq!(chunks.len(), unique.len());
+            assert!(chunks.iter().all(|pos| area.contains(pos)));
+        }
+
+        assert_eq!(41 * 41, PregenArea::Radius { center: ChunkPos::new(0, 0), radius: 20 }.len());
+        assert_eq!(71 * 10, PregenArea::Rect { from: ChunkPos::new(40, 7), to: ChunkPos::new(-30, -2) }.len());
+    }
+
+    #[test]
+    fn options_from_args() {
+        let matches = cli_command()
+            .try_get_matches_from(["pregen", "-d", "the_nether", "--radius", "10", "--center", "3,-4", "--rate", "50"])
+            .unwrap();
+        let options = PregenOptions::from_matches(&matches).unwrap();
+
+        assert_eq!(DimensionKind::TheNether, options.dimension);
+        assert_eq!(PregenArea::Radius { center: ChunkPos::new(3, -4), radius: 10 }, options.area);
+        assert_eq!(16, options.in_flight);
+        assert_eq!(Some(50.0), options.rate);
+
+        let matches = console_command()
+            .try_get_matches_from(["pregen", "--from", "-1,-1", "--to", "1,2"])
+            .unwrap();
+        let options = PregenOptions::from_matches(&matches).unwrap();
+
+        assert_eq!(DimensionKind::Overworld, options.dimension);
+        assert_eq!(PregenArea::Rect { from: ChunkPos::new(-1, -1), to: ChunkPos::new(1, 2) }, options.area);
+        assert_eq!(Some(100.0), options.rate);
+
+        let matches = cli_command().try_get_matches_from(["pregen", "--radius", "1"]).unwrap();
+        assert_eq!(None, PregenOptions::from_matches(&matches).unwrap().rate);
+
+        for rate in ["0", "-5", "NaN", "inf", "fast"] {
+            assert!(cli_command().try_get_matches_from(["pregen", "--radius", "1", "--rate", rate]).is_err());
+        }
+
+        assert!(console_command().try_get_matches_from(["pregen", "--radius", "1", "--from", "0,0", "--to", "1,1"]).is_err());
+        assert!(PregenOptions::from_matches(&console_command().try_get_matches_from(["pregen"]).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::perf::worker::StaticTaskHandle;
+use crate::world::region::pregen::PregenJob;
+use crate::world::region::scheduler::{ChunkScheduler, Priority};
+use crate::world::region::worker::{ChunkTask, RegionLoadWorkerHandler};
+use flume::Sender;
+use owo_colors::OwoColorize;
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_worldgen::chunk::column::ChunkColumn as WorldgenChunkColumn;
+use spherix_worldgen::world::WorldGenerator;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+/// Provides chunks of a dimension. If the region loader is present, chunks are
+/// looked up in region files first. Chunks missing there are generated and
+/// saved, so the world grows as it is explored. Without the loader all chunks
+/// are just generated.
+///
+/// Chunks generated for the running pre-generation are delivered to it
+/// instead, unless they are wanted by the dimension as well.
+pub struct ChunkProviderWorkerHandler {
+    loader: Option<Arc<RegionLoadWorkerHandler>>,
+    scheduler: Arc<ChunkScheduler>,
+    /// Chunks, which failed to load. They must not be overwritten by the
+    /// generated ones.
+    unsaved: Arc<Mutex<HashSet<ChunkPos>>>,
+    /// Chunks requested by the dimension, which are not generated yet.
+    wanted: Arc<Mutex<HashSet<ChunkPos>>>,
+    pregen: Arc<Mutex<Option<Arc<PregenJob>>>>,
+    chunk_tx: Sender<Arc<WorldgenChunkColumn>>,
+}
+
+impl ChunkProviderWorkerHandler {
+    pub fn new(
+        loader: Option<RegionLoadWorkerHandler>,
+        generator: WorldGenerator,
+        num_threads: usize,
+        chunk_tx: Sender<Arc<WorldgenChunkColumn>>
+    ) -> Self {
+        let loader = loader.map(Arc::new);
+        let unsaved = Arc::new(Mutex::new(HashSet::new()));
+        let wanted = Arc::new(Mutex::new(HashSet::new()));
+        let pregen = Arc::new(Mutex::new(None::<Arc<PregenJob>>));
+
+        let generated = {
+            let loader = loader.clone();
+            let unsaved = unsaved.clone();
+            let wanted = wanted.clone();
+            let pregen = pregen.clone();
+            let chunk_tx = chunk_tx.clone();
+
+            move |chunk: Arc<WorldgenChunkColumn>| {
+                let pos = chunk.pos();
+                let save = !unsaved.lock().unwrap().remove(&pos);
+
+                if let Some(loader) = loader.as_ref().filter(|_| save) {
+                    if let Err(e) = loader.save(chunk.inner()) {
+                        error!("Unable to save chunk ({}, {}): {}", pos.x().bright_red(), pos.z().bright_blue(), e);
+                    }
+                }
+
+                let wanted = wanted.lock().unwrap().remove(&pos);
+                let delivered = pregen
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .is_some_and(|job| job.deliver(&chunk));
+
+                if wanted || !delivered {
+                    chunk_tx.send(chunk).unwrap();
+                }
+            }
+        };
+
+        Self {
+            scheduler: ChunkScheduler::new(generator, num_threads, generated),
+            loader,
+            unsaved,
+            wanted,
+            pregen,
+            chunk_tx,
+        }
+    }
+
+    /// Runs the pre-generation until it is finished or stopped. Only one job
+    /// runs at a time.
+    pub fn pregen(&self, job: Arc<PregenJob>) {
+        let Some(loader) = &self.loader else {
+            warn!("Chunks of {:?} are not saved with the current world strategy, nothing to pre-generate", job.options.dimension);
+            job.finish();
+
+            return;
+        };
+
+        {
+            let mut active = self.pregen.lock().unwrap();
+            if active.as_ref().is_some_and(|active| !active.is_finished()) {
+                warn!("Pre-generation of {:?} is already running", job.options.dimension);
+                job.finish();
+
+                return;
+            }
+
+            *active = Some(job.clone());
+        }
+
+        job.run(&self.scheduler, loader);
+        job.finish();
+
+        *self.pregen.lock().unwrap() = None;
+    }
+
+    fn provide(&self, pos: ChunkPos) {
+        if let Some(loader) = &self.loader {
+            match loader.load(pos.clone()) {
+                Ok(Some(chunk)) => {
+                    let chunk = Arc::new(WorldgenChunkColumn::new(chunk));
+                    self.scheduler.insert(chunk.clone());
+                    self.chunk_tx.send(chunk).unwrap();
+
+                    return;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!("Unable to load chunk ({}, {}): {}", pos.x().bright_red(), pos.z().bright_blue(), e);
+                    self.unsaved.lock().unwrap().insert(pos.clone());
+                }
+            }
+        }
+
+        // The chunk is sent once the scheduler has generated it.
+        self.wanted.lock().unwrap().insert(pos.clone());
+        self.scheduler.request(pos, Priority::Player);
+    }
+
+    fn unload(&self, chunk: Arc<WorldgenChunkColumn>) {
+        if let Some(loader) = &self.loader {
+            if let Err(e) = loader.save(chunk.inner()) {
+                let pos = chunk.pos();
+                error!("Unable to save chunk ({}, {}): {}", pos.x().bright_red(), pos.z().bright_blue(), e);
+            }
+        }
+
+        self.scheduler.evict(&chunk);
+    }
+}
+
+impl StaticTaskHandle<ChunkTask, ()> for ChunkProviderWorkerHandler {
+    fn handle(&self, task: ChunkTask, _: ()) {
+        match task {
+            ChunkTask::Load(task) => self.provide(task.0),
+            ChunkTask::Unload(task) => self.unload(task.0),
+            ChunkTask::Pregen(job) => self.pregen(job),
+        }
+
+        if let Some(loader) = &self.loader {
+            loader.close_unused_descriptors();
+        }
+    }
+}
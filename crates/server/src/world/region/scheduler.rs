@@ -0,0 +1,418 @@
+use gxhash::GxBuildHasher;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::status::ChunkStatus;
+use spherix_worldgen::chunk::column::{ChunkColumn as WorldgenChunkColumn, Neighbour};
+use spherix_worldgen::chunk::step::ChunkStep;
+use spherix_worldgen::world::WorldGenerator;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Promotes chunks step by step (see [`ChunkStep`]) on the thread pool. A step
+/// starts once the chunks within its radius have reached the neighbour status,
+/// which are generated on demand as well. A step writes its own chunk (features
+/// write the chunks around as well) and reads the rest of its neighbours. Steps,
+/// one of which would write a chunk the other touches, never run at the same
+/// time, so the locks of the chunks are never contended.
+pub struct ChunkScheduler {
+    generator: WorldGenerator,
+    state: Mutex<State>,
+    pool: ThreadPool,
+    generated: Box<dyn Fn(Arc<WorldgenChunkColumn>) + Send + Sync>,
+}
+
+impl ChunkScheduler {
+    /// The callback receives the requested chunks, once they are generated.
+    pub fn new<F>(
+        generator: WorldGenerator,
+        num_threads: usize,
+        generated: F,
+    ) -> Arc<Self>
+    where
+        F: Fn(Arc<WorldgenChunkColumn>) + Send + Sync + 'static
+    {
+        Arc::new(Self {
+            generator,
+            state: Mutex::default(),
+            pool: ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap(),
+            generated: Box::new(generated),
+        })
+    }
+
+    /// Generates the chunk in the background, unless it is known already.
+    pub fn request(self: &Arc<Self>, pos: ChunkPos, priority: Priority) {
+        let mut state = self.state.lock().unwrap();
+
+        let slot = state.slot(&self.generator, &pos);
+        slot.requested = true;
+
+        let generated = match &slot.chunk {
+            Held::Frozen(chunk) => Some(chunk.clone()),
+            Held::Proto(_) => None,
+        };
+
+        if generated.is_none() {
+            let known = state.pending.get(&pos).copied().unwrap_or(Priority::Background);
+            state.pending.insert(pos.clone(), priority.max(known));
+            state.dirty.insert(pos);
+        }
+
+        let started = state.schedule(&self.generator, self.pool.current_num_threads());
+        drop(state);
+
+        self.spawn(started);
+
+        if let Some(chunk) = generated {
+            (self.generated)(chunk);
+        }
+    }
+
+    /// Makes a chunk, which was not generated by the scheduler (for example,
+    /// loaded from a region file), visible to the steps of its neighbours.
+    pub fn insert(self: &Arc<Self>, chunk: Arc<WorldgenChunkColumn>) {
+        let pos = chunk.pos();
+        let mut state = self.state.lock().unwrap();
+
+        // The chunk is already generated for its neighbours, and a step is
+        // writing it right now.
+        if state.slots.get(&pos).is_some_and(|slot| slot.running.is_some()) {
+            return
+        }
+
+        state.pending.remove(&pos);
+        state.slots.insert(pos.clone(), Slot {
+            chunk: Held::Frozen(chunk),
+            status: ChunkStatus::Full,
+            running: None,
+            requested: true,
+        });
+        state.touch(&pos);
+
+        let started = state.schedule(&self.generator, self.pool.current_num_threads());
+        drop(state);
+
+        self.spawn(started);
+    }
+
+    /// Forgets the chunk, unless it has already been replaced by a newer
+    /// instance of the same chunk, along with the chunks around, which were
+    /// generated only for it.
+    pub fn evict(&self, chunk: &Arc<WorldgenChunkColumn>) {
+        let pos = chunk.pos();
+        let mut state = self.state.lock().unwrap();
+
+        let known = state
+            .slots
+            .get(&pos)
+            .is_some_and(|slot| matches!(&slot.chunk, Held::Frozen(frozen) if Arc::ptr_eq(frozen, chunk)));
+
+        if known {
+            state.slots.remove(&pos);
+            state.prune(&pos);
+        }
+    }
+
+    fn spawn(self: &Arc<Self>, started: Vec<(ChunkPos, &'static ChunkStep)>) {
+        for (pos, step) in started {
+            let scheduler = self.clone();
+            self.pool.spawn(move || scheduler.run(pos, step));
+        }
+    }
+
+    fn run(self: &Arc<Self>, pos: ChunkPos, step: &'static ChunkStep) {
+        let (chunk, neighbours) = {
+            let state = self.state.lock().unwrap();
+
+            let Held::Proto(chunk) = &state.slots[&pos].chunk else {
+                unreachable!("Steps run only on chunks in generation")
+            };
+
+            let neighbours = around(&pos, step.radius)
+                .filter(|neighbour| *neighbour != pos)
+                .filter_map(|neighbour| {
+                    let held = state.slots.get(&neighbour)?.chunk.clone();
+
+                    Some((neighbour, held))
+                })
+                .collect::<Vec<_>>();
+
+            (chunk.clone(), neighbours)
+        };
+
+        {
+            let write = step.write_radius() > 0;
+            let mut guards = neighbours
+                .iter()
+                .map(|(pos, held)| (pos.clone(), held.lock(write)))
+                .collect::<Vec<_>>();
+
+            self.generator.promote(
+                step,
+                guards.iter_mut().map(|(pos, guard)| (pos.clone(), guard.neighbour())).collect(),
+                &mut chunk.write().unwrap(),
+            );
+        }
+
+        drop(neighbours);
+        drop(chunk);
+
+        self.finish(pos, step);
+    }
+
+    fn finish(self: &Arc<Self>, pos: ChunkPos, step: &'static ChunkStep) {
+        let mut state = self.state.lock().unwrap();
+
+        let mut slot = state.slots.remove(&pos).unwrap();
+        slot.status = step.status;
+        slot.running = None;
+        state.steps -= 1;
+
+        let mut generated = None;
+
+        if ChunkStep::after(step.status).is_none() {
+            slot.chunk = slot.chunk.freeze();
+
+            if slot.requested && state.pending.remove(&pos).is_some() {
+                generated = Some(slot.chunk.clone());
+            }
+        }
+
+        state.slots.insert(pos.clone(), slot);
+        state.touch(&pos);
+
+        let started = state.schedule(&self.generator, self.pool.current_num_threads());
+        drop(state);
+
+        self.spawn(started);
+
+        if let Some(Held::Frozen(chunk)) = generated {
+            (self.generated)(chunk);
+        }
+    }
+}
+
+/// Whom a chunk is requested for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Pre-generated chunks. Their steps start only while threads are idle.
+    Background,
+    /// Chunks players wait for. Their steps start right away.
+    Player,
+}
+
+/// Chunk known to the scheduler.
+#[derive(Clone)]
+enum Held {
+    /// Chunk in generation. Only the step running on the chunk, or the
+    /// features around, write it.
+    Proto(Arc<RwLock<WorldgenChunkColumn>>),
+    /// Generated or loaded chunk, which is not written anymore.
+    Frozen(Arc<WorldgenChunkColumn>),
+}
+
+impl Held {
+    /// Locks the chunk for a step around. Frozen chunks are never written.
+    fn lock(&self, write: bool) -> Guard<'_> {
+        match self {
+            Held::Proto(chunk) if write => Guard::Write(chunk.write().unwrap()),
+            Held::Proto(chunk) => Guard::Read(chunk.read().unwrap()),
+            Held::Frozen(chunk) => Guard::Frozen(chunk),
+        }
+    }
+
+    /// Stops the generation of the chunk. No step may hold the chunk.
+    fn freeze(self) -> Self {
+        match self {
+            Held::Proto(chunk) => {
+                let chunk = Arc::try_unwrap(chunk)
+                    .unwrap_or_else(|_| unreachable!("Generated chunk is still held by a step"));
+
+                Held::Frozen(Arc::new(chunk.into_inner().unwrap()))
+            }
+            frozen => frozen,
+        }
+    }
+}
+
+enum Guard<'a> {
+    Read(RwLockReadGuard<'a, WorldgenChunkColumn>),
+    Write(RwLockWriteGuard<'a, WorldgenChunkColumn>),
+    Frozen(&'a WorldgenChunkColumn),
+}
+
+impl Guard<'_> {
+    fn neighbour(&mut self) -> Neighbour<'_> {
+        match self {
+            Guard::Read(guard) => Neighbour::ReadOnly(&**guard),
+            Guard::Write(guard) => Neighbour::Writable(&mut **guard),
+            Guard::Frozen(chunk) => Neighbour::ReadOnly(*chunk),
+        }
+    }
+}
+
+struct Slot {
+    chunk: Held,
+    /// Status the chunk has reached. Loaded chunks are never promoted, so
+    /// they are considered full.
+    status: ChunkStatus,
+    running: Option<&'static ChunkStep>,
+    /// Whether the chunk is requested, or only generated for its neighbours.
+    requested: bool,
+}
+
+#[derive(Default)]
+struct State {
+    slots: HashMap<ChunkPos, Slot, GxBuildHasher>,
+    /// Requested chunks, which are not generated yet.
+    pending: HashMap<ChunkPos, Priority, GxBuildHasher>,
+    /// Pending chunks, which something around has changed for since they were
+    /// last advanced.
+    dirty: HashSet<ChunkPos, GxBuildHasher>,
+    /// Steps started and not finished yet.
+    steps: usize,
+}
+
+impl State {
+    fn slot(&mut self, generator: &WorldGenerator, pos: &ChunkPos) -> &mut Slot {
+        self.slots.entry(pos.clone()).or_insert_with(|| Slot {
+            chunk: Held::Proto(Arc::new(RwLock::new(generator.empty_chunk(pos.clone())))),
+            status: ChunkStatus::Empty,
+            running: None,
+            requested: false,
+        })
+    }
+
+    /// Marks the pending chunks dirty, whose steps (or the steps of their
+    /// dependencies) may be able to start after the chunk has changed.
+    fn touch(&mut self, pos: &ChunkPos) {
+        let radius = ChunkStep::dependency_radius(ChunkStatus::Full) + 2 * ChunkStep::max_radius();
+
+        for near in around(pos, radius) {
+            if self.pending.contains_key(&near) {
+                self.dirty.insert(near);
+            }
+        }
+    }
+
+    /// Starts the steps, which the dirty pending chunks wait for. Chunks of
+    /// the players go first, the steps of the other chunks start only as long
+    /// as fewer steps than threads are running, so they never queue up in
+    /// front of the players. Returns the started steps.
+    fn schedule(&mut self, generator: &WorldGenerator, num_threads: usize) -> Vec<(ChunkPos, &'static ChunkStep)> {
+        let mut started = Vec::new();
+        let mut dirty = std::mem::take(&mut self.dirty)
+            .into_iter()
+            .filter_map(|pos| Some((self.pending.get(&pos).copied()?, pos)))
+            .collect::<Vec<_>>();
+        dirty.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+
+        for (priority, pos) in dirty {
+            let limit = match priority {
+                Priority::Player => None,
+                Priority::Background => Some(num_threads),
+            };
+
+            if !self.advance(generator, pos.clone(), ChunkStatus::Full, limit, &mut started) && self.is_saturated(limit) {
+                // Advanced again, once a step has finished.
+                self.dirty.insert(pos);
+            }
+        }
+
+        started
+    }
+
+    #[inline]
+    fn is_saturated(&self, limit: Option<usize>) -> bool {
+        limit.is_some_and(|limit| self.steps >= limit)
+    }
+
+    /// Starts the next step of the chunk towards the status, if its
+    /// neighbours are ready, no step around is in the way and fewer steps
+    /// than the limit are running. Neighbours are advanced as well. Returns
+    /// whether the chunk has reached the status.
+    fn advance(
+        &mut self,
+        generator: &WorldGenerator,
+        pos: ChunkPos,
+        status: ChunkStatus,
+        limit: Option<usize>,
+        started: &mut Vec<(ChunkPos, &'static ChunkStep)>,
+    ) -> bool {
+        let slot = self.slot(generator, &pos);
+        if slot.status.ordinal() >= status.ordinal() {
+            return true
+        }
+
+        if slot.running.is_some() {
+            return false
+        }
+
+        let step = ChunkStep::after(slot.status).unwrap();
+        let mut ready = true;
+
+        for neighbour in around(&pos, step.radius) {
+            if neighbour != pos {
+                ready &= self.advance(generator, neighbour, step.neighbour_status, limit, started);
+            }
+        }
+
+        if !ready || self.is_blocked(&pos, step) || self.is_saturated(limit) {
+            return false
+        }
+
+        self.slots.get_mut(&pos).unwrap().running = Some(step);
+        self.steps += 1;
+        started.push((pos, step));
+
+        false
+    }
+
+    /// Whether a step running around writes a chunk the step would touch, or
+    /// touches a chunk the step would write.
+    fn is_blocked(&self, pos: &ChunkPos, step: &ChunkStep) -> bool {
+        around(pos, 2 * ChunkStep::max_radius()).any(|other| {
+            self.slots
+                .get(&other)
+                .and_then(|slot| slot.running)
+                .is_some_and(|running| {
+                    let reach = (step.write_radius() + running.radius).max(running.write_radius() + step.radius);
+
+                    distance(pos, &other) <= reach
+                })
+        })
+    }
+
+    /// Removes the chunks around, which are neither requested nor needed by
+    /// a requested chunk anymore.
+    fn prune(&mut self, pos: &ChunkPos) {
+        let radius = ChunkStep::dependency_radius(ChunkStatus::Full);
+
+        for other in around(pos, radius) {
+            let unused = self
+                .slots
+                .get(&other)
+                .is_some_and(|slot| !slot.requested && slot.running.is_none());
+
+            let needed = around(&other, radius)
+                .any(|near| self.slots.get(&near).is_some_and(|slot| slot.requested));
+
+            if unused && !needed {
+                self.slots.remove(&other);
+            }
+        }
+    }
+}
+
+/// Chunks of the square around the chunk, the chunk included.
+fn around(pos: &ChunkPos, radius: i32) -> impl Iterator<Item = ChunkPos> {
+    let (x, z) = (pos.x(), pos.z());
+
+    (x - radius..=x + radius).flat_map(move |x| (z - radius..=z + radius).map(move |z| ChunkPos::new(x, z)))
+}
+
+fn distance(a: &ChunkPos, b: &ChunkPos) -> i32 {
+    (a.x() - b.x()).abs().max((a.z() - b.z()).abs())
+}
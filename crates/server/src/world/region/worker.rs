@@ -1,25 +1,56 @@
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use flume::{Receiver, Sender};
+use anyhow::anyhow;
 use owo_colors::OwoColorize;
 use tracing::trace;
 
 use spherix_world::chunk::pos::ChunkPos;
 use spherix_world::region::pos::{ChunkWithinRegionPos, RegionPos};
 
-use crate::perf::worker::StaticTaskHandle;
 use spherix_world::chunk::column::ChunkColumn;
 use spherix_world::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
+use spherix_world::chunk::status::ChunkStatus;
 use spherix_world::region::anvil::Anvil;
 use spherix_world::region::RegionFile;
+use spherix_worldgen::chunk::column::ChunkColumn as WorldgenChunkColumn;
+use spherix_worldgen::chunk::step::ChunkStep;
+
+use crate::perf::worker::Partitioned;
+use crate::world::region::pregen::PregenJob;
 
 pub struct LoadChunkTask(pub ChunkPos);
 
+/// Saves the chunk which has no tickets anymore and forgets about it.
+pub struct UnloadChunkTask(pub Arc<WorldgenChunkColumn>);
+
 pub enum ChunkTask {
-    Load(LoadChunkTask)
+    Load(LoadChunkTask),
+    Unload(UnloadChunkTask),
+    /// Generates the chunks of the area, which blocks a thread of the worker
+    /// until the job is finished.
+    Pregen(Arc<PregenJob>),
+}
+
+impl Partitioned for ChunkTask {
+    /// Tasks of the same chunk run in order, so the chunk is never loaded
+    /// while it is still being saved.
+    fn partition(&self) -> Option<u64> {
+        let pos = match self {
+            ChunkTask::Load(task) => task.0.clone(),
+            ChunkTask::Unload(task) => task.0.pos(),
+            ChunkTask::Pregen(_) => return None,
+        };
+
+        let mut hasher = DefaultHasher::new();
+        pos.hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
 }
 
 struct RegionDescriptor {
@@ -54,62 +85,114 @@ fn filename_fancy(region_pos: &RegionPos) -> String {
     s.green().to_string()
 }
 
+/// Reads chunks from region files of a dimension and writes them back. Region
+/// file descriptors are cached and closed after some time of inactivity.
 pub struct RegionLoadWorkerHandler {
     dir: PathBuf,
     palette: Arc<BlockGlobalPalette>,
     biomes_palette: Arc<BiomeGlobalPalette>,
 
     regions: Mutex<HashMap<RegionPos, RegionDescriptor>>,
-    chunk_tx: Sender<ChunkColumn>,
 }
 
 impl RegionLoadWorkerHandler {
     pub fn new(
         dir: PathBuf,
         palette: Arc<BlockGlobalPalette>,
-        biomes_palette: Arc<BiomeGlobalPalette>,
-        chunk_tx: Sender<ChunkColumn>
+        biomes_palette: Arc<BiomeGlobalPalette>
     ) -> Self {
         Self {
             dir,
             palette,
             biomes_palette,
             regions: Mutex::default(),
-            chunk_tx,
         }
     }
 
-    fn load(&self, pos: ChunkPos) {
+    /// Returns [`None`] if the generated chunk has never been saved to the
+    /// region file. Chunks saved in the middle of the generation are
+    /// generated again, so they are not loaded either.
+    pub fn load(&self, pos: ChunkPos) -> anyhow::Result<Option<ChunkColumn>> {
         let region_pos: RegionPos = pos.clone().into();
+        let chunk_pos: ChunkWithinRegionPos = pos.clone().into();
 
-        let mut regions_guard = self.regions.lock().unwrap();
-        let region = if regions_guard.contains_key(&region_pos) {
-            let region = regions_guard.get_mut(&region_pos).unwrap();
-            region.touched_at = Instant::now();
+        let now = Instant::now();
+        let chunk_blob = self.with_region(region_pos.clone(), false, |region| region.read(chunk_pos))?
+            .transpose()?
+            .flatten();
+        let io_elapsed = now.elapsed();
 
-            region
-        } else {
-            regions_guard.insert(
-                region_pos.clone(),
-                RegionDescriptor::new(Anvil::at(region_pos.clone(), self.dir.join("region"))),
+        let Some(chunk_blob) = chunk_blob else {
+            return Ok(None)
+        };
+
+        let now = Instant::now();
+        let chunk = ChunkColumn::from_nbt(chunk_blob, self.palette.clone(), self.biomes_palette.clone())?;
+        let handle_elapsed = now.elapsed();
+
+        if !ChunkStep::is_generated(chunk.status) {
+            trace!(
+                "Chunk ({}, {}) was saved with status {}, it will be generated again",
+                pos.x().bright_red(),
+                pos.z().bright_blue(),
+                chunk.status.name()
             );
-            trace!("Opening a region descriptor for file {}", filename_fancy(&region_pos));
 
-            regions_guard.get_mut(&region_pos.clone()).unwrap()
+            return Ok(None)
+        }
+
+        trace!(
+            "Chunk ({}, {}) was loaded for the region ({}, {}) {}",
+            pos.x().bright_red(),
+            pos.z().bright_blue(),
+            region_pos.x().bright_red(),
+            region_pos.z().bright_blue(),
+            format!("({:.0?} I/O + {:.0?} elapsed)", io_elapsed, handle_elapsed).bright_black()
+        );
+
+        Ok(Some(chunk))
+    }
+
+    /// Whether the generated chunk has been saved to the region file (see
+    /// [`Self::load`]).
+    pub fn exists(&self, pos: ChunkPos) -> anyhow::Result<bool> {
+        let region_pos: RegionPos = pos.clone().into();
+        let chunk_pos: ChunkWithinRegionPos = pos.clone().into();
+
+        let saved = self.with_region(region_pos.clone(), false, |region| region.does_chunk_exist(chunk_pos.clone()))?;
+        if !saved.unwrap_or(false) {
+            return Ok(false)
+        }
+
+        // Only the status is needed, so the chunk itself is not parsed.
+        let chunk_blob = self.with_region(region_pos, false, |region| region.read(chunk_pos))?
+            .transpose()?
+            .flatten();
+        let status = match chunk_blob.as_ref().and_then(|blob| blob.get("Status")) {
+            Some(nbt::Value::String(name)) => ChunkStatus::from_name(name)
+                .ok_or_else(|| anyhow!("Unknown chunk status {}", name))?,
+            Some(x) => return Err(anyhow!("Chunk status must be a string, {:?} given", x)),
+            None => ChunkStatus::Empty
         };
 
+        Ok(ChunkStep::is_generated(status))
+    }
+
+    pub fn save(&self, chunk: &ChunkColumn) -> anyhow::Result<()> {
+        let pos = chunk.pos();
+        let region_pos: RegionPos = pos.clone().into();
         let chunk_pos: ChunkWithinRegionPos = pos.clone().into();
 
         let now = Instant::now();
-        let chunk_blob = region.region.read(chunk_pos.clone()).unwrap();
-        let io_elapsed = now.elapsed();
+        let chunk_blob = chunk.to_nbt();
+        let handle_elapsed = now.elapsed();
 
         let now = Instant::now();
-        let mut chunk = ChunkColumn::from_nbt(chunk_blob, self.palette.clone(), self.biomes_palette.clone());
-        let handle_elapsed = now.elapsed();
+        self.with_region(region_pos.clone(), true, |region| region.write(chunk_pos, &chunk_blob))?.transpose()?;
+        let io_elapsed = now.elapsed();
 
         trace!(
-            "Chunk ({}, {}) was loaded for the region ({}, {}) {}",
+            "Chunk ({}, {}) was saved to the region ({}, {}) {}",
             pos.x().bright_red(),
             pos.z().bright_blue(),
             region_pos.x().bright_red(),
@@ -117,20 +200,151 @@ impl RegionLoadWorkerHandler {
             format!("({:.0?} I/O + {:.0?} elapsed)", io_elapsed, handle_elapsed).bright_black()
         );
 
-        self.chunk_tx.send(chunk).unwrap();
+        Ok(())
     }
 
-    fn close_unused_descriptors(&self) {
+    /// Calls the function with the region file. Region files are created only
+    /// to be written, so [`None`] is returned if the file to be read does not
+    /// exist.
+    fn with_region<R, F>(&self, region_pos: RegionPos, write: bool, f: F) -> anyhow::Result<Option<R>>
+    where
+        F: FnOnce(&mut Anvil) -> R
+    {
+        let mut regions_guard = self.regions.lock().unwrap();
+
+        // The file was opened for reading, but now it is written.
+        if write && regions_guard.get(&region_pos).is_some_and(|region| !region.region.is_writable()) {
+            regions_guard.remove(&region_pos);
+        }
+
+        let region = if regions_guard.contains_key(&region_pos) {
+            let region = regions_guard.get_mut(&region_pos).unwrap();
+            region.touched_at = Instant::now();
+
+            region
+        } else {
+            let dir = self.dir.join("region");
+            let region = if write {
+                Anvil::at(region_pos.clone(), dir)?
+            } else {
+                let Some(region) = Anvil::open(region_pos.clone(), dir)? else {
+                    return Ok(None)
+                };
+
+                region
+            };
+
+            regions_guard.insert(region_pos.clone(), RegionDescriptor::new(region));
+            trace!("Opening a region descriptor for file {}", filename_fancy(&region_pos));
+
+            regions_guard.get_mut(&region_pos).unwrap()
+        };
+
+        Ok(Some(f(&mut region.region)))
+    }
+
+    pub fn close_unused_descriptors(&self) {
         self.regions.lock().unwrap().retain(|_, descriptor| !descriptor.old_enough());
     }
 }
 
-impl StaticTaskHandle<ChunkTask, ()> for RegionLoadWorkerHandler {
-    fn handle(&self, task: ChunkTask, _: ()) {
-        match task {
-            ChunkTask::Load(task) => self.load(task.0)
+#[cfg(test)]
+mod tests {
+    use crate::world::region::worker::RegionLoadWorkerHandler;
+    use spherix_world::chunk::column::ChunkColumn;
+    use spherix_world::chunk::palette::{create_biome_global_palette_from_json, create_block_global_palette_from_json};
+    use spherix_world::chunk::pos::ChunkPos;
+    use spherix_world::chunk::status::ChunkStatus;
+    use spherix_world::region::pos::{ChunkWithinRegionPos, RegionPos};
+    use spherix_world::region::RegionFile;
+    use std::sync::Arc;
+
+    fn loader(dir: &std::path::Path) -> RegionLoadWorkerHandler {
+        let blocks = serde_json::json!({
+            "minecraft:air": {"states": [{"id": 0, "default": true}]},
+            "minecraft:stone": {"states": [{"id": 1, "default": true}]}
+        });
+        let biomes = serde_json::json!([{
+            "id": 0,
+            "name": "minecraft:plains",
+            "element": {
+                "downfall": 0.4,
+                "temperature": 0.8,
+                "precipitation": "rain",
+                "effects": {
+                    "sky_color": 0,
+                    "water_fog_color": 0,
+                    "water_color": 0,
+                    "fog_color": 0,
+                    "mood_sound": {
+                        "tick_delay": 6000,
+                        "offset": 2.0,
+                        "sound": "minecraft:ambient.cave",
+                        "block_search_extent": 8
+                    }
+                }
+            }
+        }]);
+
+        RegionLoadWorkerHandler::new(
+            dir.to_path_buf(),
+            Arc::new(create_block_global_palette_from_json(blocks)),
+            Arc::new(create_biome_global_palette_from_json(&biomes))
+        )
+    }
+
+    #[test]
+    fn proto_chunks_are_generated_again() {
+        let dir = std::env::temp_dir().join(format!("spherix-worker-proto-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let loader = loader(&dir);
+
+        for (pos, status) in [(ChunkPos::new(1, 2), ChunkStatus::Noise), (ChunkPos::new(1, 3), ChunkStatus::Full)] {
+            let mut chunk = ChunkColumn::empty(pos, loader.palette.clone(), loader.biomes_palette.clone());
+            chunk.status = status;
+            loader.save(&chunk).unwrap();
+        }
+
+        assert!(loader.load(ChunkPos::new(1, 2)).unwrap().is_none());
+        assert!(!loader.exists(ChunkPos::new(1, 2)).unwrap());
+
+        assert_eq!(ChunkStatus::Full, loader.load(ChunkPos::new(1, 3)).unwrap().unwrap().status);
+        assert!(loader.exists(ChunkPos::new(1, 3)).unwrap());
+
+        // Lookups of the chunks, which were never saved, create no region files.
+        assert!(loader.load(ChunkPos::new(100, 100)).unwrap().is_none());
+        assert_eq!(1, std::fs::read_dir(dir.join("region")).unwrap().count());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn damaged_chunk_is_error() {
+        let dir = std::env::temp_dir().join(format!("spherix-worker-damaged-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let loader = loader(&dir);
+
+        let mut damaged = nbt::Blob::new();
+        damaged.insert("xPos", "garbage").unwrap();
+        loader
+            .with_region(RegionPos::new(0, 0), true, |region| region.write(ChunkWithinRegionPos::new(4, 5), &damaged))
+            .unwrap()
+            .unwrap()
+            .unwrap();
+
+        assert!(loader.load(ChunkPos::new(4, 5)).is_err());
+        // Only the status is read, and there is none.
+        assert!(!loader.exists(ChunkPos::new(4, 5)).unwrap());
+
+        // The region is still usable, and the damaged chunk can be overwritten.
+        for pos in [ChunkPos::new(4, 6), ChunkPos::new(4, 5)] {
+            let mut chunk = ChunkColumn::empty(pos.clone(), loader.palette.clone(), loader.biomes_palette.clone());
+            chunk.status = ChunkStatus::Full;
+            loader.save(&chunk).unwrap();
+
+            assert_eq!(ChunkStatus::Full, loader.load(pos).unwrap().unwrap().status);
         }
 
-        self.close_unused_descriptors();
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,113 @@
+use anyhow::anyhow;
+use owo_colors::OwoColorize;
+use spherix_config::Seed;
+use spherix_world::dimension::DimensionKind;
+use std::io::ErrorKind;
+use std::path::Path;
+use tracing::info;
+
+/// File of the world directory, which keeps the seed the world is generated
+/// with.
+const SEED_FILENAME: &str = "seed.txt";
+
+/// Returns the seed the world at the given directory is generated with. The
+/// seed is persisted on the first start, so a random seed is chosen only once
+/// and the server and the offline pre-generation always agree on it.
+pub fn resolve(seed: Seed, world_dir: &Path) -> anyhow::Result<Seed> {
+    let path = world_dir.join(SEED_FILENAME);
+
+    let persisted = match std::fs::read_to_string(&path) {
+        Ok(text) => Some(
+            text
+                .trim()
+                .parse::<i64>()
+                .map_err(|e| anyhow!("Invalid seed in {}: {}", path.display(), e))?
+        ),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    if let Some(persisted) = persisted {
+        if !seed.is_random() && seed.0 != persisted {
+            return Err(anyhow!(
+                "World {} is generated with seed {}, but seed {} is configured",
+                world_dir.display(),
+                persisted,
+                seed.0
+            ))
+        }
+
+        return Ok(Seed::from(persisted))
+    }
+
+    // Chunks of the world were generated with a seed, which is unknown now.
+    if seed.is_random() && has_regions(world_dir)? {
+        return Err(anyhow!(
+            "World {} is generated already, but its seed is unknown. Configure the seed explicitly",
+            world_dir.display()
+        ))
+    }
+
+    std::fs::create_dir_all(world_dir)?;
+    std::fs::write(&path, seed.0.to_string())?;
+
+    if seed.is_random() {
+        info!("Random seed {} was chosen for the world", seed.0.blue());
+    }
+
+    Ok(Seed::from(seed.0))
+}
+
+fn has_regions(world_dir: &Path) -> anyhow::Result<bool> {
+    for dimension in [DimensionKind::Overworld, DimensionKind::TheNether, DimensionKind::TheEnd] {
+        match std::fs::read_dir(dimension.dir(world_dir).join("region")) {
+            Ok(mut entries) => if entries.next().is_some() {
+                return Ok(true)
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => {},
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::seed::resolve;
+    use spherix_config::Seed;
+    use std::path::PathBuf;
+
+    fn world_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("spherix-seed-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    #[test]
+    fn random_seed_is_persisted() {
+        let dir = world_dir("random");
+
+        let seed = resolve(Seed::from_text(""), &dir).unwrap();
+        assert!(!seed.is_random());
+        assert_eq!(seed, resolve(Seed::from_text(""), &dir).unwrap());
+        assert_eq!(seed, resolve(seed, &dir).unwrap());
+        assert!(resolve(Seed::from(seed.0.wrapping_add(1)), &dir).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn random_seed_is_refused_for_generated_world() {
+        let dir = world_dir("generated");
+        std::fs::create_dir_all(dir.join("DIM-1").join("region")).unwrap();
+        std::fs::write(dir.join("DIM-1").join("region").join("r.0.0.mca"), b"").unwrap();
+
+        assert!(resolve(Seed::from_text(""), &dir).is_err());
+        assert_eq!(Seed::from(5), resolve(Seed::from(5), &dir).unwrap());
+        assert_eq!(Seed::from(5), resolve(Seed::from_text(""), &dir).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,10 +4,13 @@ use std::sync::Arc;
 
 use bevy_ecs::prelude::Resource;
 
+use spherix_config::{WorldGenerator, WorldStrategy};
 use spherix_world::dimension::DimensionKind;
+use spherix_worldgen::pack::DataPacks;
 
-use crate::world::dimension::Dimension;
+use crate::world::dimension::{Dimension, SPAWN_POSITION};
 use spherix_world::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
+use spherix_world::chunk::pos::ChunkPos;
 
 #[allow(dead_code)]
 #[derive(Resource)]
@@ -18,8 +21,13 @@ pub struct World {
 }
 
 impl World {
-    pub fn new(dir: PathBuf, palette: Arc<BlockGlobalPalette>, biomes_palette: Arc<BiomeGlobalPalette>) -> Self {
-        let dimensions = Self::create_dimensions(dir.clone(), palette.clone(), biomes_palette);
+    pub fn new(dir: PathBuf, strategy: WorldStrategy, seed: i64, generator_config: &WorldGenerator, packs: Arc<DataPacks>, palette: Arc<BlockGlobalPalette>, biomes_palette: Arc<BiomeGlobalPalette>) -> Self {
+        let dimensions = Self::create_dimensions(dir.clone(), strategy, seed, generator_config, packs, palette.clone(), biomes_palette);
+
+        dimensions
+            .get(&DimensionKind::Overworld)
+            .unwrap()
+            .add_spawn_tickets(ChunkPos::new(SPAWN_POSITION.x >> 4, SPAWN_POSITION.z >> 4));
 
         Self {
             dir,
@@ -28,11 +36,11 @@ impl World {
         }
     }
 
-    fn create_dimensions(dir: PathBuf, palette: Arc<BlockGlobalPalette>, biomes_palette: Arc<BiomeGlobalPalette>) -> HashMap<DimensionKind, Dimension> {
+    fn create_dimensions(dir: PathBuf, strategy: WorldStrategy, seed: i64, generator_config: &WorldGenerator, packs: Arc<DataPacks>, palette: Arc<BlockGlobalPalette>, biomes_palette: Arc<BiomeGlobalPalette>) -> HashMap<DimensionKind, Dimension> {
         HashMap::from([
-            (DimensionKind::Overworld, Dimension::new(DimensionKind::Overworld, dir.clone(), palette.clone(), biomes_palette.clone())),
-            (DimensionKind::TheNether, Dimension::new(DimensionKind::TheNether, dir.clone(), palette.clone(), biomes_palette.clone())),
-            (DimensionKind::TheEnd, Dimension::new(DimensionKind::TheEnd, dir, palette, biomes_palette))
+            (DimensionKind::Overworld, Dimension::new(DimensionKind::Overworld, dir.clone(), strategy, seed, generator_config, packs.clone(), palette.clone(), biomes_palette.clone())),
+            (DimensionKind::TheNether, Dimension::new(DimensionKind::TheNether, dir.clone(), strategy, seed, generator_config, packs.clone(), palette.clone(), biomes_palette.clone())),
+            (DimensionKind::TheEnd, Dimension::new(DimensionKind::TheEnd, dir, strategy, seed, generator_config, packs, palette, biomes_palette))
         ])
     }
 
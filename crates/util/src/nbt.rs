@@ -113,3 +113,19 @@ impl NbtExt for Value {
         }
     }
 }
+
+/// Extracts content of the root compound of the blob. [`nbt::Blob`] does not
+/// expose its entries, so the blob is passed through its binary representation.
+pub fn blob_to_compound(blob: &nbt::Blob) -> HashMap<String, Value> {
+    let mut buf = Vec::with_capacity(blob.len_bytes());
+    blob.to_writer(&mut buf).unwrap();
+
+    // Skip the header of the root tag: tag id (1 byte), title length (2 bytes)
+    // and the title itself.
+    let title_len = u16::from_be_bytes([buf[1], buf[2]]) as usize;
+
+    match Value::from_reader(0x0a, &mut &buf[3 + title_len..]).unwrap() {
+        Value::Compound(map) => map,
+        _ => unreachable!()
+    }
+}
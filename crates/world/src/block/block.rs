@@ -268,14 +268,14 @@ impl Block {
     pub const MANGROVE_PLANKS: &'static Block = &Block::new("minecraft:mangrove_planks", PropertiesBuilder::new(Material::STONE).build());
     pub const BAMBOO_PLANKS: &'static Block = &Block::new("minecraft:bamboo_planks", PropertiesBuilder::new(Material::STONE).build());
     pub const BAMBOO_MOSAIC: &'static Block = &Block::new("minecraft:bamboo_mosaic", PropertiesBuilder::new(Material::STONE).build());
-    pub const OAK_SAPLING: &'static Block = &Block::new("minecraft:oak_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const SPRUCE_SAPLING: &'static Block = &Block::new("minecraft:spruce_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const BIRCH_SAPLING: &'static Block = &Block::new("minecraft:birch_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const JUNGLE_SAPLING: &'static Block = &Block::new("minecraft:jungle_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const ACACIA_SAPLING: &'static Block = &Block::new("minecraft:acacia_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const CHERRY_SAPLING: &'static Block = &Block::new("minecraft:cherry_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const DARK_OAK_SAPLING: &'static Block = &Block::new("minecraft:dark_oak_sapling", PropertiesBuilder::new(Material::STONE).build());
-    pub const MANGROVE_PROPAGULE: &'static Block = &Block::new("minecraft:mangrove_propagule", PropertiesBuilder::new(Material::STONE).build());
+    pub const OAK_SAPLING: &'static Block = &Block::new("minecraft:oak_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const SPRUCE_SAPLING: &'static Block = &Block::new("minecraft:spruce_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const BIRCH_SAPLING: &'static Block = &Block::new("minecraft:birch_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const JUNGLE_SAPLING: &'static Block = &Block::new("minecraft:jungle_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const ACACIA_SAPLING: &'static Block = &Block::new("minecraft:acacia_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const CHERRY_SAPLING: &'static Block = &Block::new("minecraft:cherry_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const DARK_OAK_SAPLING: &'static Block = &Block::new("minecraft:dark_oak_sapling", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const MANGROVE_PROPAGULE: &'static Block = &Block::new("minecraft:mangrove_propagule", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const BEDROCK: &'static Block = &Block::new("minecraft:bedrock", PropertiesBuilder::new(Material::STONE).build());
     pub const WATER: &'static Block = &Block::new("minecraft:water", PropertiesBuilder::new(Material::WATER).fluid().no_collision().strength(100.0, 100.0).build());
     pub const LAVA: &'static Block = &Block::new("minecraft:lava", PropertiesBuilder::new(Material::LAVA).fluid().no_collision().random_ticks().strength(100.0, 100.0).build());
@@ -290,52 +290,52 @@ impl Block {
     pub const COAL_ORE: &'static Block = &Block::new("minecraft:coal_ore", PropertiesBuilder::new(Material::STONE).build());
     pub const DEEPSLATE_COAL_ORE: &'static Block = &Block::new("minecraft:deepslate_coal_ore", PropertiesBuilder::new(Material::STONE).build());
     pub const NETHER_GOLD_ORE: &'static Block = &Block::new("minecraft:nether_gold_ore", PropertiesBuilder::new(Material::STONE).build());
-    pub const OAK_LOG: &'static Block = &Block::new("minecraft:oak_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const SPRUCE_LOG: &'static Block = &Block::new("minecraft:spruce_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const BIRCH_LOG: &'static Block = &Block::new("minecraft:birch_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const JUNGLE_LOG: &'static Block = &Block::new("minecraft:jungle_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const ACACIA_LOG: &'static Block = &Block::new("minecraft:acacia_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const CHERRY_LOG: &'static Block = &Block::new("minecraft:cherry_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const DARK_OAK_LOG: &'static Block = &Block::new("minecraft:dark_oak_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const MANGROVE_LOG: &'static Block = &Block::new("minecraft:mangrove_log", PropertiesBuilder::new(Material::STONE).build());
+    pub const OAK_LOG: &'static Block = &Block::new("minecraft:oak_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const SPRUCE_LOG: &'static Block = &Block::new("minecraft:spruce_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const BIRCH_LOG: &'static Block = &Block::new("minecraft:birch_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const JUNGLE_LOG: &'static Block = &Block::new("minecraft:jungle_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const ACACIA_LOG: &'static Block = &Block::new("minecraft:acacia_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const CHERRY_LOG: &'static Block = &Block::new("minecraft:cherry_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const DARK_OAK_LOG: &'static Block = &Block::new("minecraft:dark_oak_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const MANGROVE_LOG: &'static Block = &Block::new("minecraft:mangrove_log", PropertiesBuilder::new(Material::WOOD).build());
     pub const MANGROVE_ROOTS: &'static Block = &Block::new("minecraft:mangrove_roots", PropertiesBuilder::new(Material::STONE).build());
     pub const MUDDY_MANGROVE_ROOTS: &'static Block = &Block::new("minecraft:muddy_mangrove_roots", PropertiesBuilder::new(Material::STONE).build());
     pub const BAMBOO_BLOCK: &'static Block = &Block::new("minecraft:bamboo_block", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_SPRUCE_LOG: &'static Block = &Block::new("minecraft:stripped_spruce_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_BIRCH_LOG: &'static Block = &Block::new("minecraft:stripped_birch_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_JUNGLE_LOG: &'static Block = &Block::new("minecraft:stripped_jungle_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_ACACIA_LOG: &'static Block = &Block::new("minecraft:stripped_acacia_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_CHERRY_LOG: &'static Block = &Block::new("minecraft:stripped_cherry_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_DARK_OAK_LOG: &'static Block = &Block::new("minecraft:stripped_dark_oak_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_OAK_LOG: &'static Block = &Block::new("minecraft:stripped_oak_log", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_MANGROVE_LOG: &'static Block = &Block::new("minecraft:stripped_mangrove_log", PropertiesBuilder::new(Material::STONE).build());
+    pub const STRIPPED_SPRUCE_LOG: &'static Block = &Block::new("minecraft:stripped_spruce_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_BIRCH_LOG: &'static Block = &Block::new("minecraft:stripped_birch_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_JUNGLE_LOG: &'static Block = &Block::new("minecraft:stripped_jungle_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_ACACIA_LOG: &'static Block = &Block::new("minecraft:stripped_acacia_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_CHERRY_LOG: &'static Block = &Block::new("minecraft:stripped_cherry_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_DARK_OAK_LOG: &'static Block = &Block::new("minecraft:stripped_dark_oak_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_OAK_LOG: &'static Block = &Block::new("minecraft:stripped_oak_log", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_MANGROVE_LOG: &'static Block = &Block::new("minecraft:stripped_mangrove_log", PropertiesBuilder::new(Material::WOOD).build());
     pub const STRIPPED_BAMBOO_BLOCK: &'static Block = &Block::new("minecraft:stripped_bamboo_block", PropertiesBuilder::new(Material::STONE).build());
-    pub const OAK_WOOD: &'static Block = &Block::new("minecraft:oak_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const SPRUCE_WOOD: &'static Block = &Block::new("minecraft:spruce_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const BIRCH_WOOD: &'static Block = &Block::new("minecraft:birch_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const JUNGLE_WOOD: &'static Block = &Block::new("minecraft:jungle_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const ACACIA_WOOD: &'static Block = &Block::new("minecraft:acacia_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const CHERRY_WOOD: &'static Block = &Block::new("minecraft:cherry_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const DARK_OAK_WOOD: &'static Block = &Block::new("minecraft:dark_oak_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const MANGROVE_WOOD: &'static Block = &Block::new("minecraft:mangrove_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_OAK_WOOD: &'static Block = &Block::new("minecraft:stripped_oak_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_SPRUCE_WOOD: &'static Block = &Block::new("minecraft:stripped_spruce_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_BIRCH_WOOD: &'static Block = &Block::new("minecraft:stripped_birch_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_JUNGLE_WOOD: &'static Block = &Block::new("minecraft:stripped_jungle_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_ACACIA_WOOD: &'static Block = &Block::new("minecraft:stripped_acacia_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_CHERRY_WOOD: &'static Block = &Block::new("minecraft:stripped_cherry_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_DARK_OAK_WOOD: &'static Block = &Block::new("minecraft:stripped_dark_oak_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const STRIPPED_MANGROVE_WOOD: &'static Block = &Block::new("minecraft:stripped_mangrove_wood", PropertiesBuilder::new(Material::STONE).build());
-    pub const OAK_LEAVES: &'static Block = &Block::new("minecraft:oak_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const SPRUCE_LEAVES: &'static Block = &Block::new("minecraft:spruce_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const BIRCH_LEAVES: &'static Block = &Block::new("minecraft:birch_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const JUNGLE_LEAVES: &'static Block = &Block::new("minecraft:jungle_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const ACACIA_LEAVES: &'static Block = &Block::new("minecraft:acacia_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const CHERRY_LEAVES: &'static Block = &Block::new("minecraft:cherry_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const DARK_OAK_LEAVES: &'static Block = &Block::new("minecraft:dark_oak_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const MANGROVE_LEAVES: &'static Block = &Block::new("minecraft:mangrove_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const AZALEA_LEAVES: &'static Block = &Block::new("minecraft:azalea_leaves", PropertiesBuilder::new(Material::STONE).build());
-    pub const FLOWERING_AZALEA_LEAVES: &'static Block = &Block::new("minecraft:flowering_azalea_leaves", PropertiesBuilder::new(Material::STONE).build());
+    pub const OAK_WOOD: &'static Block = &Block::new("minecraft:oak_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const SPRUCE_WOOD: &'static Block = &Block::new("minecraft:spruce_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const BIRCH_WOOD: &'static Block = &Block::new("minecraft:birch_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const JUNGLE_WOOD: &'static Block = &Block::new("minecraft:jungle_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const ACACIA_WOOD: &'static Block = &Block::new("minecraft:acacia_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const CHERRY_WOOD: &'static Block = &Block::new("minecraft:cherry_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const DARK_OAK_WOOD: &'static Block = &Block::new("minecraft:dark_oak_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const MANGROVE_WOOD: &'static Block = &Block::new("minecraft:mangrove_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_OAK_WOOD: &'static Block = &Block::new("minecraft:stripped_oak_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_SPRUCE_WOOD: &'static Block = &Block::new("minecraft:stripped_spruce_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_BIRCH_WOOD: &'static Block = &Block::new("minecraft:stripped_birch_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_JUNGLE_WOOD: &'static Block = &Block::new("minecraft:stripped_jungle_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_ACACIA_WOOD: &'static Block = &Block::new("minecraft:stripped_acacia_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_CHERRY_WOOD: &'static Block = &Block::new("minecraft:stripped_cherry_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_DARK_OAK_WOOD: &'static Block = &Block::new("minecraft:stripped_dark_oak_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const STRIPPED_MANGROVE_WOOD: &'static Block = &Block::new("minecraft:stripped_mangrove_wood", PropertiesBuilder::new(Material::WOOD).build());
+    pub const OAK_LEAVES: &'static Block = &Block::new("minecraft:oak_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const SPRUCE_LEAVES: &'static Block = &Block::new("minecraft:spruce_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const BIRCH_LEAVES: &'static Block = &Block::new("minecraft:birch_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const JUNGLE_LEAVES: &'static Block = &Block::new("minecraft:jungle_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const ACACIA_LEAVES: &'static Block = &Block::new("minecraft:acacia_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const CHERRY_LEAVES: &'static Block = &Block::new("minecraft:cherry_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const DARK_OAK_LEAVES: &'static Block = &Block::new("minecraft:dark_oak_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const MANGROVE_LEAVES: &'static Block = &Block::new("minecraft:mangrove_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const AZALEA_LEAVES: &'static Block = &Block::new("minecraft:azalea_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
+    pub const FLOWERING_AZALEA_LEAVES: &'static Block = &Block::new("minecraft:flowering_azalea_leaves", PropertiesBuilder::new(Material::LEAVES).no_occlusion().build());
     pub const SPONGE: &'static Block = &Block::new("minecraft:sponge", PropertiesBuilder::new(Material::STONE).build());
     pub const WET_SPONGE: &'static Block = &Block::new("minecraft:wet_sponge", PropertiesBuilder::new(Material::STONE).build());
     pub const GLASS: &'static Block = &Block::new("minecraft:glass", PropertiesBuilder::new(Material::STONE).build());
@@ -367,11 +367,11 @@ impl Block {
     pub const DETECTOR_RAIL: &'static Block = &Block::new("minecraft:detector_rail", PropertiesBuilder::new(Material::STONE).build());
     pub const STICKY_PISTON: &'static Block = &Block::new("minecraft:sticky_piston", PropertiesBuilder::new(Material::STONE).build());
     pub const COBWEB: &'static Block = &Block::new("minecraft:cobweb", PropertiesBuilder::new(Material::STONE).build());
-    pub const GRASS: &'static Block = &Block::new("minecraft:grass", PropertiesBuilder::new(Material::STONE).build());
-    pub const FERN: &'static Block = &Block::new("minecraft:fern", PropertiesBuilder::new(Material::STONE).build());
-    pub const DEAD_BUSH: &'static Block = &Block::new("minecraft:dead_bush", PropertiesBuilder::new(Material::STONE).build());
-    pub const SEAGRASS: &'static Block = &Block::new("minecraft:seagrass", PropertiesBuilder::new(Material::STONE).build());
-    pub const TALL_SEAGRASS: &'static Block = &Block::new("minecraft:tall_seagrass", PropertiesBuilder::new(Material::STONE).build());
+    pub const GRASS: &'static Block = &Block::new("minecraft:grass", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const FERN: &'static Block = &Block::new("minecraft:fern", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const DEAD_BUSH: &'static Block = &Block::new("minecraft:dead_bush", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const SEAGRASS: &'static Block = &Block::new("minecraft:seagrass", PropertiesBuilder::new(Material::REPLACEABLE_WATER_PLANT).no_collision().build());
+    pub const TALL_SEAGRASS: &'static Block = &Block::new("minecraft:tall_seagrass", PropertiesBuilder::new(Material::REPLACEABLE_WATER_PLANT).no_collision().build());
     pub const PISTON: &'static Block = &Block::new("minecraft:piston", PropertiesBuilder::new(Material::STONE).build());
     pub const PISTON_HEAD: &'static Block = &Block::new("minecraft:piston_head", PropertiesBuilder::new(Material::STONE).build());
     pub const WHITE_WOOL: &'static Block = &Block::new("minecraft:white_wool", PropertiesBuilder::new(Material::STONE).build());
@@ -391,22 +391,22 @@ impl Block {
     pub const RED_WOOL: &'static Block = &Block::new("minecraft:red_wool", PropertiesBuilder::new(Material::STONE).build());
     pub const BLACK_WOOL: &'static Block = &Block::new("minecraft:black_wool", PropertiesBuilder::new(Material::STONE).build());
     pub const MOVING_PISTON: &'static Block = &Block::new("minecraft:moving_piston", PropertiesBuilder::new(Material::STONE).build());
-    pub const DANDELION: &'static Block = &Block::new("minecraft:dandelion", PropertiesBuilder::new(Material::STONE).build());
-    pub const TORCHFLOWER: &'static Block = &Block::new("minecraft:torchflower", PropertiesBuilder::new(Material::STONE).build());
-    pub const POPPY: &'static Block = &Block::new("minecraft:poppy", PropertiesBuilder::new(Material::STONE).build());
-    pub const BLUE_ORCHID: &'static Block = &Block::new("minecraft:blue_orchid", PropertiesBuilder::new(Material::STONE).build());
-    pub const ALLIUM: &'static Block = &Block::new("minecraft:allium", PropertiesBuilder::new(Material::STONE).build());
-    pub const AZURE_BLUET: &'static Block = &Block::new("minecraft:azure_bluet", PropertiesBuilder::new(Material::STONE).build());
-    pub const RED_TULIP: &'static Block = &Block::new("minecraft:red_tulip", PropertiesBuilder::new(Material::STONE).build());
-    pub const ORANGE_TULIP: &'static Block = &Block::new("minecraft:orange_tulip", PropertiesBuilder::new(Material::STONE).build());
-    pub const WHITE_TULIP: &'static Block = &Block::new("minecraft:white_tulip", PropertiesBuilder::new(Material::STONE).build());
-    pub const PINK_TULIP: &'static Block = &Block::new("minecraft:pink_tulip", PropertiesBuilder::new(Material::STONE).build());
-    pub const OXEYE_DAISY: &'static Block = &Block::new("minecraft:oxeye_daisy", PropertiesBuilder::new(Material::STONE).build());
-    pub const CORNFLOWER: &'static Block = &Block::new("minecraft:cornflower", PropertiesBuilder::new(Material::STONE).build());
-    pub const WITHER_ROSE: &'static Block = &Block::new("minecraft:wither_rose", PropertiesBuilder::new(Material::STONE).build());
-    pub const LILY_OF_THE_VALLEY: &'static Block = &Block::new("minecraft:lily_of_the_valley", PropertiesBuilder::new(Material::STONE).build());
-    pub const BROWN_MUSHROOM: &'static Block = &Block::new("minecraft:brown_mushroom", PropertiesBuilder::new(Material::STONE).build());
-    pub const RED_MUSHROOM : &'static Block = &Block::new("minecraft:red_mushroom", PropertiesBuilder::new(Material::STONE).build());
+    pub const DANDELION: &'static Block = &Block::new("minecraft:dandelion", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const TORCHFLOWER: &'static Block = &Block::new("minecraft:torchflower", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const POPPY: &'static Block = &Block::new("minecraft:poppy", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const BLUE_ORCHID: &'static Block = &Block::new("minecraft:blue_orchid", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const ALLIUM: &'static Block = &Block::new("minecraft:allium", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const AZURE_BLUET: &'static Block = &Block::new("minecraft:azure_bluet", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const RED_TULIP: &'static Block = &Block::new("minecraft:red_tulip", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const ORANGE_TULIP: &'static Block = &Block::new("minecraft:orange_tulip", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const WHITE_TULIP: &'static Block = &Block::new("minecraft:white_tulip", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const PINK_TULIP: &'static Block = &Block::new("minecraft:pink_tulip", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const OXEYE_DAISY: &'static Block = &Block::new("minecraft:oxeye_daisy", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const CORNFLOWER: &'static Block = &Block::new("minecraft:cornflower", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const WITHER_ROSE: &'static Block = &Block::new("minecraft:wither_rose", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const LILY_OF_THE_VALLEY: &'static Block = &Block::new("minecraft:lily_of_the_valley", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const BROWN_MUSHROOM: &'static Block = &Block::new("minecraft:brown_mushroom", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const RED_MUSHROOM : &'static Block = &Block::new("minecraft:red_mushroom", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const GOLD_BLOCK : &'static Block = &Block::new("minecraft:gold_block", PropertiesBuilder::new(Material::STONE).build());
     pub const IRON_BLOCK : &'static Block = &Block::new("minecraft:iron_block", PropertiesBuilder::new(Material::STONE).build());
     pub const BRICKS : &'static Block = &Block::new("minecraft:bricks", PropertiesBuilder::new(Material::STONE).build());
@@ -494,12 +494,12 @@ impl Block {
     pub const SNOW : &'static Block = &Block::new("minecraft:snow", PropertiesBuilder::new(Material::STONE).build());
     pub const ICE : &'static Block = &Block::new("minecraft:ice", PropertiesBuilder::new(Material::STONE).build());
     pub const SNOW_BLOCK : &'static Block = &Block::new("minecraft:snow_block", PropertiesBuilder::new(Material::STONE).build());
-    pub const CACTUS : &'static Block = &Block::new("minecraft:cactus", PropertiesBuilder::new(Material::STONE).build());
+    pub const CACTUS : &'static Block = &Block::new("minecraft:cactus", PropertiesBuilder::new(Material::CACTUS).build());
     pub const CLAY : &'static Block = &Block::new("minecraft:clay", PropertiesBuilder::new(Material::STONE).build());
-    pub const SUGAR_CANE : &'static Block = &Block::new("minecraft:sugar_cane", PropertiesBuilder::new(Material::STONE).build());
+    pub const SUGAR_CANE : &'static Block = &Block::new("minecraft:sugar_cane", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const JUKEBOX : &'static Block = &Block::new("minecraft:jukebox", PropertiesBuilder::new(Material::STONE).build());
     pub const OAK_FENCE : &'static Block = &Block::new("minecraft:oak_fence", PropertiesBuilder::new(Material::STONE).build());
-    pub const PUMPKIN : &'static Block = &Block::new("minecraft:pumpkin", PropertiesBuilder::new(Material::STONE).build());
+    pub const PUMPKIN : &'static Block = &Block::new("minecraft:pumpkin", PropertiesBuilder::new(Material::VEGETABLE).build());
     pub const NETHERRACK : &'static Block = &Block::new("minecraft:netherrack", PropertiesBuilder::new(Material::STONE).build());
     pub const SOUL_SAND : &'static Block = &Block::new("minecraft:soul_sand", PropertiesBuilder::new(Material::STONE).build());
     pub const SOUL_SOIL : &'static Block = &Block::new("minecraft:soul_soil", PropertiesBuilder::new(Material::STONE).build());
@@ -556,19 +556,19 @@ impl Block {
     pub const IRON_BARS : &'static Block = &Block::new("minecraft:iron_bars", PropertiesBuilder::new(Material::STONE).build());
     pub const CHAIN : &'static Block = &Block::new("minecraft:chain", PropertiesBuilder::new(Material::STONE).build());
     pub const GLASS_PANE : &'static Block = &Block::new("minecraft:glass_pane", PropertiesBuilder::new(Material::STONE).build());
-    pub const MELON : &'static Block = &Block::new("minecraft:melon", PropertiesBuilder::new(Material::STONE).build());
+    pub const MELON : &'static Block = &Block::new("minecraft:melon", PropertiesBuilder::new(Material::VEGETABLE).build());
     pub const ATTACHED_PUMPKIN_STEM : &'static Block = &Block::new("minecraft:attached_pumpkin_stem", PropertiesBuilder::new(Material::STONE).build());
     pub const ATTACHED_MELON_STEM : &'static Block = &Block::new("minecraft:attached_melon_stem", PropertiesBuilder::new(Material::STONE).build());
     pub const PUMPKIN_STEM : &'static Block = &Block::new("minecraft:pumpkin_stem", PropertiesBuilder::new(Material::STONE).build());
     pub const MELON_STEM : &'static Block = &Block::new("minecraft:melon_stem", PropertiesBuilder::new(Material::STONE).build());
-    pub const VINE : &'static Block = &Block::new("minecraft:vine", PropertiesBuilder::new(Material::STONE).build());
-    pub const GLOW_LICHEN : &'static Block = &Block::new("minecraft:glow_lichen", PropertiesBuilder::new(Material::STONE).build());
+    pub const VINE : &'static Block = &Block::new("minecraft:vine", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const GLOW_LICHEN : &'static Block = &Block::new("minecraft:glow_lichen", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
     pub const OAK_FENCE_GATE : &'static Block = &Block::new("minecraft:oak_fence_gate", PropertiesBuilder::new(Material::STONE).build());
     pub const BRICK_STAIRS : &'static Block = &Block::new("minecraft:brick_stairs", PropertiesBuilder::new(Material::STONE).build());
     pub const STONE_BRICK_STAIRS : &'static Block = &Block::new("minecraft:stone_brick_stairs", PropertiesBuilder::new(Material::STONE).build());
     pub const MUD_BRICK_STAIRS : &'static Block = &Block::new("minecraft:mud_brick_stairs", PropertiesBuilder::new(Material::STONE).build());
     pub const MYCELIUM : &'static Block = &Block::new("minecraft:mycelium", PropertiesBuilder::new(Material::STONE).build());
-    pub const LILY_PAD : &'static Block = &Block::new("minecraft:lily_pad", PropertiesBuilder::new(Material::STONE).build());
+    pub const LILY_PAD : &'static Block = &Block::new("minecraft:lily_pad", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const NETHER_BRICKS : &'static Block = &Block::new("minecraft:nether_bricks", PropertiesBuilder::new(Material::STONE).build());
     pub const NETHER_BRICK_FENCE : &'static Block = &Block::new("minecraft:nether_brick_fence", PropertiesBuilder::new(Material::STONE).build());
     pub const NETHER_BRICK_STAIRS : &'static Block = &Block::new("minecraft:nether_brick_stairs", PropertiesBuilder::new(Material::STONE).build());
@@ -584,7 +584,7 @@ impl Block {
     pub const END_STONE : &'static Block = &Block::new("minecraft:end_stone", PropertiesBuilder::new(Material::STONE).build());
     pub const DRAGON_EGG : &'static Block = &Block::new("minecraft:dragon_egg", PropertiesBuilder::new(Material::STONE).build());
     pub const REDSTONE_LAMP : &'static Block = &Block::new("minecraft:redstone_lamp", PropertiesBuilder::new(Material::STONE).build());
-    pub const COCOA : &'static Block = &Block::new("minecraft:cocoa", PropertiesBuilder::new(Material::STONE).build());
+    pub const COCOA : &'static Block = &Block::new("minecraft:cocoa", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const SANDSTONE_STAIRS : &'static Block = &Block::new("minecraft:sandstone_stairs", PropertiesBuilder::new(Material::STONE).build());
     pub const EMERALD_ORE : &'static Block = &Block::new("minecraft:emerald_ore", PropertiesBuilder::new(Material::STONE).build());
     pub const DEEPSLATE_EMERALD_ORE : &'static Block = &Block::new("minecraft:deepslate_emerald_ore", PropertiesBuilder::new(Material::STONE).build());
@@ -741,12 +741,12 @@ impl Block {
     pub const TERRACOTTA : &'static Block = &Block::new("minecraft:terracotta", PropertiesBuilder::new(Material::STONE).build());
     pub const COAL_BLOCK : &'static Block = &Block::new("minecraft:coal_block", PropertiesBuilder::new(Material::STONE).build());
     pub const PACKED_ICE : &'static Block = &Block::new("minecraft:packed_ice", PropertiesBuilder::new(Material::STONE).build());
-    pub const SUNFLOWER : &'static Block = &Block::new("minecraft:sunflower", PropertiesBuilder::new(Material::STONE).build());
-    pub const LILAC : &'static Block = &Block::new("minecraft:lilac", PropertiesBuilder::new(Material::STONE).build());
-    pub const ROSE_BUSH : &'static Block = &Block::new("minecraft:rose_bush", PropertiesBuilder::new(Material::STONE).build());
-    pub const PEONY : &'static Block = &Block::new("minecraft:peony", PropertiesBuilder::new(Material::STONE).build());
-    pub const TALL_GRASS : &'static Block = &Block::new("minecraft:tall_grass", PropertiesBuilder::new(Material::STONE).build());
-    pub const LARGE_FERN : &'static Block = &Block::new("minecraft:large_fern", PropertiesBuilder::new(Material::STONE).build());
+    pub const SUNFLOWER : &'static Block = &Block::new("minecraft:sunflower", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const LILAC : &'static Block = &Block::new("minecraft:lilac", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const ROSE_BUSH : &'static Block = &Block::new("minecraft:rose_bush", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const PEONY : &'static Block = &Block::new("minecraft:peony", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const TALL_GRASS : &'static Block = &Block::new("minecraft:tall_grass", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
+    pub const LARGE_FERN : &'static Block = &Block::new("minecraft:large_fern", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
     pub const WHITE_BANNER : &'static Block = &Block::new("minecraft:white_banner", PropertiesBuilder::new(Material::STONE).build());
     pub const ORANGE_BANNER : &'static Block = &Block::new("minecraft:orange_banner", PropertiesBuilder::new(Material::STONE).build());
     pub const MAGENTA_BANNER : &'static Block = &Block::new("minecraft:magenta_banner", PropertiesBuilder::new(Material::STONE).build());
@@ -920,8 +920,8 @@ impl Block {
     pub const GREEN_CONCRETE_POWDER : &'static Block = &Block::new("minecraft:green_concrete_powder", PropertiesBuilder::new(Material::STONE).build());
     pub const RED_CONCRETE_POWDER : &'static Block = &Block::new("minecraft:red_concrete_powder", PropertiesBuilder::new(Material::STONE).build());
     pub const BLACK_CONCRETE_POWDER : &'static Block = &Block::new("minecraft:black_concrete_powder", PropertiesBuilder::new(Material::STONE).build());
-    pub const KELP : &'static Block = &Block::new("minecraft:kelp", PropertiesBuilder::new(Material::STONE).build());
-    pub const KELP_PLANT : &'static Block = &Block::new("minecraft:kelp_plant", PropertiesBuilder::new(Material::STONE).build());
+    pub const KELP : &'static Block = &Block::new("minecraft:kelp", PropertiesBuilder::new(Material::WATER_PLANT).no_collision().build());
+    pub const KELP_PLANT : &'static Block = &Block::new("minecraft:kelp_plant", PropertiesBuilder::new(Material::WATER_PLANT).no_collision().build());
     pub const DRIED_KELP_BLOCK : &'static Block = &Block::new("minecraft:dried_kelp_block", PropertiesBuilder::new(Material::STONE).build());
     pub const TURTLE_EGG : &'static Block = &Block::new("minecraft:turtle_egg", PropertiesBuilder::new(Material::STONE).build());
     pub const DEAD_TUBE_CORAL_BLOCK : &'static Block = &Block::new("minecraft:dead_tube_coral_block", PropertiesBuilder::new(Material::STONE).build());
@@ -970,8 +970,8 @@ impl Block {
     pub const BAMBOO_SAPLING : &'static Block = &Block::new("minecraft:bamboo_sapling", PropertiesBuilder::new(Material::STONE).build());
     pub const BAMBOO : &'static Block = &Block::new("minecraft:bamboo", PropertiesBuilder::new(Material::STONE).build());
     pub const POTTED_BAMBOO : &'static Block = &Block::new("minecraft:potted_bamboo", PropertiesBuilder::new(Material::STONE).build());
-    pub const VOID_AIR : &'static Block = &Block::new("minecraft:void_air", PropertiesBuilder::new(Material::STONE).air().build());
-    pub const CAVE_AIR : &'static Block = &Block::new("minecraft:cave_air", PropertiesBuilder::new(Material::STONE).air().build());
+    pub const VOID_AIR : &'static Block = &Block::new("minecraft:void_air", PropertiesBuilder::new(Material::AIR).no_collision().air().build());
+    pub const CAVE_AIR : &'static Block = &Block::new("minecraft:cave_air", PropertiesBuilder::new(Material::AIR).no_collision().air().build());
     pub const BUBBLE_COLUMN : &'static Block = &Block::new("minecraft:bubble_column", PropertiesBuilder::new(Material::STONE).build());
     pub const POLISHED_GRANITE_STAIRS : &'static Block = &Block::new("minecraft:polished_granite_stairs", PropertiesBuilder::new(Material::STONE).build());
     pub const SMOOTH_RED_SANDSTONE_STAIRS : &'static Block = &Block::new("minecraft:smooth_red_sandstone_stairs", PropertiesBuilder::new(Material::STONE).build());
@@ -1029,7 +1029,7 @@ impl Block {
     pub const SOUL_LANTERN : &'static Block = &Block::new("minecraft:soul_lantern", PropertiesBuilder::new(Material::STONE).build());
     pub const CAMPFIRE : &'static Block = &Block::new("minecraft:campfire", PropertiesBuilder::new(Material::STONE).build());
     pub const SOUL_CAMPFIRE : &'static Block = &Block::new("minecraft:soul_campfire", PropertiesBuilder::new(Material::STONE).build());
-    pub const SWEET_BERRY_BUSH : &'static Block = &Block::new("minecraft:sweet_berry_bush", PropertiesBuilder::new(Material::STONE).build());
+    pub const SWEET_BERRY_BUSH : &'static Block = &Block::new("minecraft:sweet_berry_bush", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const WARPED_STEM : &'static Block = &Block::new("minecraft:warped_stem", PropertiesBuilder::new(Material::STONE).build());
     pub const STRIPPED_WARPED_STEM : &'static Block = &Block::new("minecraft:stripped_warped_stem", PropertiesBuilder::new(Material::STONE).build());
     pub const WARPED_HYPHAE : &'static Block = &Block::new("minecraft:warped_hyphae", PropertiesBuilder::new(Material::STONE).build());
@@ -1077,7 +1077,7 @@ impl Block {
     pub const JIGSAW : &'static Block = &Block::new("minecraft:jigsaw", PropertiesBuilder::new(Material::STONE).build());
     pub const COMPOSTER : &'static Block = &Block::new("minecraft:composter", PropertiesBuilder::new(Material::STONE).build());
     pub const TARGET : &'static Block = &Block::new("minecraft:target", PropertiesBuilder::new(Material::STONE).build());
-    pub const BEE_NEST : &'static Block = &Block::new("minecraft:bee_nest", PropertiesBuilder::new(Material::STONE).build());
+    pub const BEE_NEST : &'static Block = &Block::new("minecraft:bee_nest", PropertiesBuilder::new(Material::WOOD).build());
     pub const BEEHIVE : &'static Block = &Block::new("minecraft:beehive", PropertiesBuilder::new(Material::STONE).build());
     pub const HONEY_BLOCK : &'static Block = &Block::new("minecraft:honey_block", PropertiesBuilder::new(Material::STONE).build());
     pub const HONEYCOMB_BLOCK : &'static Block = &Block::new("minecraft:honeycomb_block", PropertiesBuilder::new(Material::STONE).build());
@@ -1196,18 +1196,18 @@ impl Block {
     pub const LIGHTNING_ROD : &'static Block = &Block::new("minecraft:lightning_rod", PropertiesBuilder::new(Material::STONE).build());
     pub const POINTED_DRIPSTONE : &'static Block = &Block::new("minecraft:pointed_dripstone", PropertiesBuilder::new(Material::STONE).build());
     pub const DRIPSTONE_BLOCK : &'static Block = &Block::new("minecraft:dripstone_block", PropertiesBuilder::new(Material::STONE).build());
-    pub const CAVE_VINES : &'static Block = &Block::new("minecraft:cave_vines", PropertiesBuilder::new(Material::STONE).build());
-    pub const CAVE_VINES_PLANT : &'static Block = &Block::new("minecraft:cave_vines_plant", PropertiesBuilder::new(Material::STONE).build());
-    pub const SPORE_BLOSSOM : &'static Block = &Block::new("minecraft:spore_blossom", PropertiesBuilder::new(Material::STONE).build());
-    pub const AZALEA : &'static Block = &Block::new("minecraft:azalea", PropertiesBuilder::new(Material::STONE).build());
-    pub const FLOWERING_AZALEA : &'static Block = &Block::new("minecraft:flowering_azalea", PropertiesBuilder::new(Material::STONE).build());
-    pub const MOSS_CARPET : &'static Block = &Block::new("minecraft:moss_carpet", PropertiesBuilder::new(Material::STONE).build());
-    pub const PINK_PETALS : &'static Block = &Block::new("minecraft:pink_petals", PropertiesBuilder::new(Material::STONE).build());
+    pub const CAVE_VINES : &'static Block = &Block::new("minecraft:cave_vines", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const CAVE_VINES_PLANT : &'static Block = &Block::new("minecraft:cave_vines_plant", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const SPORE_BLOSSOM : &'static Block = &Block::new("minecraft:spore_blossom", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const AZALEA : &'static Block = &Block::new("minecraft:azalea", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const FLOWERING_AZALEA : &'static Block = &Block::new("minecraft:flowering_azalea", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const MOSS_CARPET : &'static Block = &Block::new("minecraft:moss_carpet", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const PINK_PETALS : &'static Block = &Block::new("minecraft:pink_petals", PropertiesBuilder::new(Material::PLANT).no_collision().build());
     pub const MOSS_BLOCK : &'static Block = &Block::new("minecraft:moss_block", PropertiesBuilder::new(Material::STONE).build());
-    pub const BIG_DRIPLEAF : &'static Block = &Block::new("minecraft:big_dripleaf", PropertiesBuilder::new(Material::STONE).build());
-    pub const BIG_DRIPLEAF_STEM : &'static Block = &Block::new("minecraft:big_dripleaf_stem", PropertiesBuilder::new(Material::STONE).build());
-    pub const SMALL_DRIPLEAF : &'static Block = &Block::new("minecraft:small_dripleaf", PropertiesBuilder::new(Material::STONE).build());
-    pub const HANGING_ROOTS : &'static Block = &Block::new("minecraft:hanging_roots", PropertiesBuilder::new(Material::STONE).build());
+    pub const BIG_DRIPLEAF : &'static Block = &Block::new("minecraft:big_dripleaf", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const BIG_DRIPLEAF_STEM : &'static Block = &Block::new("minecraft:big_dripleaf_stem", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const SMALL_DRIPLEAF : &'static Block = &Block::new("minecraft:small_dripleaf", PropertiesBuilder::new(Material::PLANT).no_collision().build());
+    pub const HANGING_ROOTS : &'static Block = &Block::new("minecraft:hanging_roots", PropertiesBuilder::new(Material::REPLACEABLE_PLANT).no_collision().build());
     pub const ROOTED_DIRT : &'static Block = &Block::new("minecraft:rooted_dirt", PropertiesBuilder::new(Material::STONE).build());
     pub const MUD : &'static Block = &Block::new("minecraft:mud", PropertiesBuilder::new(Material::STONE).build());
     pub const DEEPSLATE : &'static Block = &Block::new("minecraft:deepslate", PropertiesBuilder::new(Material::STONE).build());
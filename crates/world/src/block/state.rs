@@ -2,6 +2,7 @@ use crate::block::block::{Block, BLOCKS};
 use crate::block::variant::{Variant, VariantVec};
 use crate::chunk::palette::global::AsAltPaletteIndex;
 use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
@@ -100,6 +101,28 @@ impl BlockState {
 
         true
     }
+
+    /// Block state entry of the section palette as it is stored in the
+    /// chunk NBT: `{Name: "minecraft:...", Properties: {...}}`.
+    pub fn to_nbt(&self) -> nbt::Value {
+        let mut compound = HashMap::from([
+            ("Name".to_owned(), nbt::Value::String(self.name().to_owned()))
+        ]);
+
+        if !self.variants.is_empty() {
+            compound.insert(
+                "Properties".to_owned(),
+                nbt::Value::Compound(
+                    self.variants
+                        .iter()
+                        .map(|variant| (variant.prop_name().to_owned(), nbt::Value::String(variant.prop_value())))
+                        .collect()
+                )
+            );
+        }
+
+        nbt::Value::Compound(compound)
+    }
 }
 
 pub trait VariantPermutations {
@@ -81,6 +81,16 @@ macro_rules! variant_enum {
                 }
             }
 
+            impl std::fmt::Display for $variant {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(match self {
+                        $(
+                            $variant::$case => spherix_macro::to_snake!($case),
+                        )*
+                    })
+                }
+            }
+
             impl std::str::FromStr for $variant {
                 type Err = ();
 
@@ -109,6 +119,16 @@ macro_rules! variant_u8_from_str_impl {
     }
 }
 
+macro_rules! variant_display_impl {
+    ($variant:ident) => {
+        impl std::fmt::Display for $variant {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+    }
+}
+
 macro_rules! variant_u8_is_covers_all_values_impl {
     ($min:literal, $max:literal) => {
         pub fn is_covers_all_values(list: &serde_json::Value) -> bool {
@@ -157,6 +177,7 @@ macro_rules! variant_u8 {
             }
 
             variant_u8_from_str_impl!($variant);
+            variant_display_impl!($variant);
         )*
     }
 }
@@ -184,6 +205,7 @@ macro_rules! variant_u8_max {
             }
 
             variant_u8_from_str_impl!($variant);
+            variant_display_impl!($variant);
         )*
     }
 }
@@ -231,6 +253,8 @@ macro_rules! variant_bool {
                     Ok($variant(bool::from_str(input)?))
                 }
             }
+
+            variant_display_impl!($variant);
         )*
     }
 }
@@ -689,6 +713,15 @@ macro_rules! variant {
                     )*
                 }
             }
+
+            /// Value of the property as it is represented in JSON reports and NBT.
+            pub fn prop_value(&self) -> String {
+                match self {
+                    $(
+                        Variant::$variant(x) => x.to_string(),
+                    )*
+                }
+            }
         }
 
         impl core::cmp::PartialOrd for Variant {
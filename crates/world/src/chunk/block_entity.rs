@@ -0,0 +1,54 @@
+use anyhow::anyhow;
+use spherix_math::vector::Vector3;
+use std::collections::HashMap;
+
+/// Block entity as it is stored in the "block_entities" list of the chunk NBT.
+/// Only identifier and position are modelled, the rest of the tags is specific
+/// for each kind of block entity and kept as is.
+pub struct BlockEntity {
+    pub id: String,
+    /// Absolute position of the block.
+    pub pos: Vector3,
+    pub data: HashMap<String, nbt::Value>,
+}
+
+impl BlockEntity {
+    pub fn from_nbt(nbt: &nbt::Value) -> anyhow::Result<Self> {
+        let nbt::Value::Compound(data) = nbt else {
+            return Err(anyhow!("Block entity must be a compound, {:?} given", nbt))
+        };
+        let mut data = data.clone();
+
+        let id = match data.remove("id") {
+            Some(nbt::Value::String(id)) => id,
+            x => return Err(anyhow!("Block entity id must be a string, {:?} given", x))
+        };
+        let x = coordinate_from_nbt(&mut data, "x")?;
+        let y = coordinate_from_nbt(&mut data, "y")?;
+        let z = coordinate_from_nbt(&mut data, "z")?;
+
+        Ok(Self {
+            id,
+            pos: Vector3::new(x, y, z),
+            data,
+        })
+    }
+
+    pub fn to_nbt(&self) -> nbt::Value {
+        let mut compound = self.data.clone();
+
+        compound.insert("id".to_owned(), nbt::Value::String(self.id.clone()));
+        compound.insert("x".to_owned(), nbt::Value::Int(self.pos.x));
+        compound.insert("y".to_owned(), nbt::Value::Int(self.pos.y));
+        compound.insert("z".to_owned(), nbt::Value::Int(self.pos.z));
+
+        nbt::Value::Compound(compound)
+    }
+}
+
+fn coordinate_from_nbt(data: &mut HashMap<String, nbt::Value>, name: &str) -> anyhow::Result<i32> {
+    match data.remove(name) {
+        Some(nbt::Value::Int(x)) => Ok(x),
+        x => Err(anyhow!("Block entity {} must be an int, {:?} given", name, x))
+    }
+}
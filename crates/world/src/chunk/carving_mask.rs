@@ -0,0 +1,122 @@
+/// Blocks of a chunk column which were already carved by carvers. Carvers of
+/// the neighbour chunks overlap, so the mask prevents carving the same block
+/// twice. It is stored in the "CarvingMasks" tag of the chunk NBT until the
+/// chunk is fully generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CarvingMask {
+    min_y: i32,
+    /// Bits in the same order as vanilla `BitSet` stores them.
+    words: Vec<u64>,
+}
+
+impl CarvingMask {
+    pub fn new(height: i32, min_y: i32) -> Self {
+        let bits = 256 * height as usize;
+
+        Self {
+            min_y,
+            words: vec![0; bits.div_ceil(64)],
+        }
+    }
+
+    /// Inverse of [`Self::to_long_array()`].
+    pub fn from_long_array(height: i32, min_y: i32, data: &[i64]) -> Self {
+        let mut mask = Self::new(height, min_y);
+
+        for (word, value) in mask.words.iter_mut().zip(data) {
+            *word = *value as u64;
+        }
+
+        mask
+    }
+
+    /// Coordinates are relative to the chunk horizontally and absolute vertically.
+    #[inline]
+    pub fn get(&self, x: i32, y: i32, z: i32) -> bool {
+        let index = self.index(x, y, z);
+
+        self.words[index >> 6] & (1 << (index & 63)) != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, x: i32, y: i32, z: i32) {
+        let index = self.index(x, y, z);
+
+        self.words[index >> 6] |= 1 << (index & 63);
+    }
+
+    /// Carved blocks in the order of their bit indices, with coordinates as in
+    /// [`Self::get()`].
+    pub fn positions(&self) -> impl Iterator<Item = (i32, i32, i32)> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .flat_map(|(i, word)| {
+                (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| (i * 64 + bit) as i32)
+            })
+            .map(|index| (index & 15, (index >> 8) + self.min_y, index >> 4 & 15))
+    }
+
+    /// Words of the mask without trailing empty ones, as vanilla writes them.
+    pub fn to_long_array(&self) -> Vec<i64> {
+        let len = self.words.iter().rposition(|word| *word != 0).map_or(0, |i| i + 1);
+
+        self.words[..len].iter().map(|word| *word as i64).collect()
+    }
+
+    #[inline]
+    fn index(&self, x: i32, y: i32, z: i32) -> usize {
+        (x & 15 | (z & 15) << 4 | (y - self.min_y) << 8) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::carving_mask::CarvingMask;
+
+    #[test]
+    fn set_and_get() {
+        let mut mask = CarvingMask::new(384, -64);
+
+        assert!(!mask.get(3, -64, 7));
+        mask.set(3, -64, 7);
+        mask.set(15, 319, 15);
+
+        assert!(mask.get(3, -64, 7));
+        assert!(mask.get(15, 319, 15));
+        assert!(!mask.get(7, -64, 3));
+        assert!(!mask.get(3, -63, 7));
+    }
+
+    #[test]
+    fn long_array_round_trip() {
+        let mut mask = CarvingMask::new(384, -64);
+        assert!(mask.to_long_array().is_empty());
+
+        mask.set(1, -64, 0);
+        mask.set(0, -60, 0);
+
+        // Bit 1 of the first word and bit 0 of the 17th word (index 4 << 8).
+        let data = mask.to_long_array();
+        assert_eq!(17, data.len());
+        assert_eq!(2, data[0]);
+        assert_eq!(1, data[16]);
+
+        assert_eq!(mask, CarvingMask::from_long_array(384, -64, &data));
+    }
+
+    #[test]
+    fn positions() {
+        let mut mask = CarvingMask::new(384, -64);
+        mask.set(15, 319, 15);
+        mask.set(3, -64, 7);
+        mask.set(1, 10, 2);
+
+        assert_eq!(
+            vec![(3, -64, 7), (1, 10, 2), (15, 319, 15)],
+            mask.positions().collect::<Vec<_>>()
+        );
+    }
+}
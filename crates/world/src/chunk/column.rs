@@ -1,18 +1,22 @@
 use crate::block::state::BlockState;
 use crate::chunk::biome::Biome;
-use crate::chunk::handle::{ChunkSectionHandle, RwLockReadGuard, RwLockWriteGuard};
+use crate::chunk::block_entity::BlockEntity;
+use crate::chunk::carving_mask::CarvingMask;
+use crate::chunk::handle::ChunkSectionHandle;
 use crate::chunk::heightmap::Heightmaps;
 use crate::chunk::palette::container::{create_empty_biome_paletted_container, create_empty_block_paletted_container};
 use crate::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
 use crate::chunk::pos::ChunkPos;
-use crate::chunk::section::ChunkSection;
+use crate::chunk::section::{section_y_from_nbt, ChunkSection, SectionLight};
 use crate::chunk::status::ChunkStatus;
 use crate::chunk::vector::{Vector3BlockColumn, Vector3BlockSection};
+use anyhow::anyhow;
 use spherix_math::vector::vec3::Vector3u;
 use spherix_math::vector::Vector3;
 use spherix_proto::io::BitSet;
 use spherix_proto::packet::clientbound::ChunkData;
-use spherix_util::nbt::NbtExt;
+use spherix_util::nbt::blob_to_compound;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Chunk column as described [`here`].
@@ -23,13 +27,37 @@ pub struct ChunkColumn {
     pos: ChunkPos,
     pub status: ChunkStatus,
     sections: Vec<ChunkSectionHandle>,
-    pub heightmaps: Heightmaps
+    pub heightmaps: Heightmaps,
+    /// Version of the game data the chunk was saved with.
+    pub data_version: i32,
+    /// Cumulative number of ticks players have been in this chunk.
+    pub inhabited_time: i64,
+    /// Tick when the chunk was last saved.
+    pub last_update: i64,
+    pub block_entities: Vec<BlockEntity>,
+    /// Blocks carved by air carvers. Present only for chunks which have passed
+    /// the carvers stage, but are not fully generated yet.
+    pub carving_mask: Option<CarvingMask>,
+    /// Light of the sections right below and above the build height.
+    pub outer_light: Vec<SectionLight>,
+    /// Starts of the structures originating in this chunk by the structure
+    /// name. They are kept in the vanilla NBT form.
+    pub structure_starts: HashMap<String, nbt::Value>,
+    /// Chunks with the starts of the structures, whose pieces intersect this
+    /// chunk, by the structure name.
+    pub structure_references: HashMap<String, Vec<ChunkPos>>,
+    /// Tags of the chunk NBT which are not modelled yet (ticks,
+    /// post-processing, etc.). They are written back as is.
+    pub extra: HashMap<String, nbt::Value>
 }
 
 impl ChunkColumn {
     const Y_MIN: i32 = -64;
     const Y_MAX: i32 = 319;
 
+    /// Data version of Minecraft 1.19.4.
+    pub const DATA_VERSION: i32 = 3337;
+
     pub fn empty(pos: ChunkPos, block_global_palette: Arc<BlockGlobalPalette>, biome_global_palette: Arc<BiomeGlobalPalette>) -> Self {
         let mut sections = Vec::new();
         for i in 0..24 {
@@ -46,12 +74,7 @@ impl ChunkColumn {
             );
         }
 
-        Self {
-            pos,
-            status: ChunkStatus::Empty,
-            sections,
-            heightmaps: Heightmaps::empty(),
-        }
+        Self::new(pos, sections, Heightmaps::empty())
     }
 
     #[inline]
@@ -60,29 +83,226 @@ impl ChunkColumn {
             pos,
             status: ChunkStatus::Empty,
             sections,
-            heightmaps
+            heightmaps,
+            data_version: Self::DATA_VERSION,
+            inhabited_time: 0,
+            last_update: 0,
+            block_entities: Vec::new(),
+            carving_mask: None,
+            outer_light: Vec::new(),
+            structure_starts: HashMap::new(),
+            structure_references: HashMap::new(),
+            extra: HashMap::new(),
         }
     }
 
-    pub fn from_nbt(nbt: nbt::Blob, block_palette: Arc<BlockGlobalPalette>, biome_palette: Arc<BiomeGlobalPalette>) -> ChunkColumn {
-        let pos_x = *nbt.get("xPos").unwrap().as_byte() as i32;
-        let pos_z = *nbt.get("zPos").unwrap().as_byte() as i32;
-        let list = nbt.get("sections").unwrap();
-        let nbt_sections = list.as_list();
-
-        let mut sections = Vec::with_capacity(24);
-
-        for nbt_section in nbt_sections {
-            sections.push(
-                ChunkSection::from_nbt(
+    /// https://minecraft.fandom.com/wiki/Chunk_format#NBT_structure
+    pub fn from_nbt(
+        nbt: nbt::Blob,
+        block_palette: Arc<BlockGlobalPalette>,
+        biome_palette: Arc<BiomeGlobalPalette>
+    ) -> anyhow::Result<ChunkColumn> {
+        let mut nbt = blob_to_compound(&nbt);
+
+        let pos_x = match nbt.remove("xPos") {
+            Some(nbt::Value::Int(x)) => x,
+            x => return Err(anyhow!("Chunk xPos must be an int, {:?} given", x))
+        };
+        let pos_z = match nbt.remove("zPos") {
+            Some(nbt::Value::Int(z)) => z,
+            x => return Err(anyhow!("Chunk zPos must be an int, {:?} given", x))
+        };
+        // Always equals to the lowest section of the dimension.
+        nbt.remove("yPos");
+
+        let mut chunk = Self::empty(ChunkPos::new(pos_x, pos_z), block_palette.clone(), biome_palette.clone());
+        let min_section = (chunk.min_build_height() >> 4) as i8;
+
+        let nbt_sections = match nbt.remove("sections") {
+            Some(nbt::Value::List(sections)) => sections,
+            x => return Err(anyhow!("Chunk sections must be a list, {:?} given", x))
+        };
+
+        for nbt_section in nbt_sections.iter() {
+            let y = section_y_from_nbt(nbt_section)?;
+            let idx = y - min_section;
+
+            if idx >= 0 && (idx as usize) < chunk.sections.len() {
+                chunk.sections[idx as usize] = ChunkSection::from_nbt(
                     nbt_section,
+                    idx,
                     block_palette.clone(),
                     biome_palette.clone()
-                ).into()
-            );
+                )?.into();
+            } else {
+                chunk.outer_light.push(SectionLight::from_nbt(nbt_section)?);
+            }
+        }
+
+        if let Some(status) = nbt.remove("Status") {
+            chunk.status = match &status {
+                nbt::Value::String(name) => ChunkStatus::from_name(name),
+                _ => None
+            }.ok_or_else(|| anyhow!("Unknown chunk status {:?}", status))?;
+        }
+
+        if let Some(heightmaps) = nbt.remove("Heightmaps") {
+            let nbt::Value::Compound(heightmaps) = heightmaps else {
+                return Err(anyhow!("Chunk heightmaps must be a compound, {:?} given", heightmaps))
+            };
+
+            chunk.heightmaps = Heightmaps::from_nbt(
+                &heightmaps,
+                Self::Y_MAX - Self::Y_MIN + 1,
+                chunk.min_build_height()
+            )?;
         }
 
-        Self::new(ChunkPos::new(pos_x, pos_z), sections, Heightmaps::empty())
+        if let Some(block_entities) = nbt.remove("block_entities") {
+            let nbt::Value::List(block_entities) = block_entities else {
+                return Err(anyhow!("Chunk block entities must be a list, {:?} given", block_entities))
+            };
+
+            chunk.block_entities = block_entities.iter().map(BlockEntity::from_nbt).collect::<anyhow::Result<_>>()?;
+        }
+
+        if let Some(carving_masks) = nbt.remove("CarvingMasks") {
+            match carving_masks {
+                nbt::Value::Compound(carving_masks) => match carving_masks.get("AIR") {
+                    Some(nbt::Value::LongArray(air)) => {
+                        chunk.carving_mask = Some(CarvingMask::from_long_array(
+                            Self::Y_MAX - Self::Y_MIN + 1,
+                            chunk.min_build_height(),
+                            air
+                        ));
+                    },
+                    None => {},
+                    Some(x) => return Err(anyhow!("Chunk carving mask must be a long array, {:?} given", x))
+                },
+                x => return Err(anyhow!("Chunk carving masks must be a compound, {:?} given", x))
+            }
+        }
+
+        if let Some(structures) = nbt.remove("structures") {
+            let nbt::Value::Compound(structures) = structures else {
+                return Err(anyhow!("Chunk structures must be a compound, {:?} given", structures))
+            };
+
+            match structures.get("starts") {
+                Some(nbt::Value::Compound(starts)) => chunk.structure_starts = starts.clone(),
+                None => {},
+                Some(x) => return Err(anyhow!("Structure starts must be a compound, {:?} given", x))
+            }
+
+            match structures.get("References") {
+                Some(nbt::Value::Compound(references)) => {
+                    chunk.structure_references = references
+                        .iter()
+                        .map(|(name, positions)| {
+                            let nbt::Value::LongArray(positions) = positions else {
+                                return Err(anyhow!("References of {} must be a long array, {:?} given", name, positions))
+                            };
+
+                            let positions = positions
+                                .iter()
+                                .map(|pos| ChunkPos::new(ChunkPos::extract_x(*pos), ChunkPos::extract_z(*pos)))
+                                .collect();
+
+                            Ok((name.clone(), positions))
+                        })
+                        .collect::<anyhow::Result<_>>()?;
+                },
+                None => {},
+                Some(x) => return Err(anyhow!("Structure references must be a compound, {:?} given", x))
+            }
+        }
+
+        if let Some(data_version) = nbt.remove("DataVersion") {
+            chunk.data_version = match data_version {
+                nbt::Value::Int(data_version) => data_version,
+                x => return Err(anyhow!("Chunk DataVersion must be an int, {:?} given", x))
+            };
+        }
+
+        if let Some(inhabited_time) = nbt.remove("InhabitedTime") {
+            chunk.inhabited_time = match inhabited_time {
+                nbt::Value::Long(inhabited_time) => inhabited_time,
+                x => return Err(anyhow!("Chunk InhabitedTime must be a long, {:?} given", x))
+            };
+        }
+
+        if let Some(last_update) = nbt.remove("LastUpdate") {
+            chunk.last_update = match last_update {
+                nbt::Value::Long(last_update) => last_update,
+                x => return Err(anyhow!("Chunk LastUpdate must be a long, {:?} given", x))
+            };
+        }
+
+        chunk.extra = nbt;
+
+        Ok(chunk)
+    }
+
+    /// Inverse of [`Self::from_nbt()`]. The result is written in the same form
+    /// as vanilla does, so a chunk read from a vanilla world and written back
+    /// yields equivalent NBT.
+    pub fn to_nbt(&self) -> nbt::Blob {
+        let min_section = (self.min_build_height() >> 4) as i8;
+
+        let mut sections = Vec::with_capacity(self.sections.len() + self.outer_light.len());
+        sections.extend(self.outer_light.iter().filter(|light| light.y < min_section).map(|light| light.to_nbt()));
+        for (i, section) in self.sections.iter().enumerate() {
+            sections.push(section.read().to_nbt(min_section + i as i8));
+        }
+        sections.extend(self.outer_light.iter().filter(|light| light.y >= min_section).map(|light| light.to_nbt()));
+
+        let mut blob = nbt::Blob::new();
+
+        blob.insert("DataVersion", self.data_version).unwrap();
+        blob.insert("xPos", self.pos.x()).unwrap();
+        blob.insert("yPos", min_section as i32).unwrap();
+        blob.insert("zPos", self.pos.z()).unwrap();
+        blob.insert("Status", self.status.name()).unwrap();
+        blob.insert("LastUpdate", self.last_update).unwrap();
+        blob.insert("InhabitedTime", self.inhabited_time).unwrap();
+        blob.insert("sections", nbt::Value::List(sections)).unwrap();
+        blob.insert("Heightmaps", self.heightmaps.to_storage_nbt()).unwrap();
+        blob.insert(
+            "block_entities",
+            nbt::Value::List(self.block_entities.iter().map(|block_entity| block_entity.to_nbt()).collect())
+        ).unwrap();
+
+        if let Some(carving_mask) = &self.carving_mask {
+            blob.insert(
+                "CarvingMasks",
+                nbt::Value::Compound(HashMap::from([
+                    ("AIR".to_owned(), nbt::Value::LongArray(carving_mask.to_long_array()))
+                ]))
+            ).unwrap();
+        }
+
+        let references = self.structure_references
+            .iter()
+            .map(|(name, positions)| {
+                let positions = positions.iter().map(|pos| i64::from(pos.clone())).collect();
+
+                (name.clone(), nbt::Value::LongArray(positions))
+            })
+            .collect();
+
+        blob.insert(
+            "structures",
+            nbt::Value::Compound(HashMap::from([
+                ("References".to_owned(), nbt::Value::Compound(references)),
+                ("starts".to_owned(), nbt::Value::Compound(self.structure_starts.clone()))
+            ]))
+        ).unwrap();
+
+        for (name, value) in self.extra.iter() {
+            blob.insert(name.clone(), value.clone()).unwrap();
+        }
+
+        blob
     }
 
     pub fn to_load_packet(&self) -> ChunkData {
@@ -103,7 +323,7 @@ impl ChunkColumn {
         let mut vec = Vec::new();
 
         for (i, section) in self.sections.iter().enumerate() {
-            let guard = section.guarded.read().unwrap();
+            let guard = section.read();
 
             vec.extend(guard.to_packet_bytes());
 
@@ -168,6 +388,12 @@ impl ChunkColumn {
         &self.sections[index]
     }
 
+    /// The section of the exclusively owned chunk, which is written without
+    /// locking.
+    pub fn section_mut(&mut self, index: usize) -> &mut ChunkSection {
+        self.sections[index].get_mut()
+    }
+
     pub fn pos(&self) -> ChunkPos {
         self.pos.clone()
     }
@@ -183,20 +409,7 @@ impl ChunkColumn {
 
         let pos = Vector3BlockSection::new(pos.x(), (pos.y() & 0xF) as u32, pos.z() as u32);
 
-        section.guarded.read().unwrap().block_state(pos)
-    }
-
-    pub unsafe fn block_state_unguarded(&self, pos: Vector3BlockColumn) -> Option<Arc<BlockState>> {
-        #[cfg(debug_assertions)]
-        if pos.y() < Self::Y_MIN || pos.y() > Self::Y_MAX {
-            panic!("Y {} is out of range. Valid range is [{}; {}].", pos.y(), Self::Y_MIN, Self::Y_MAX);
-        }
-
-        let (section_index, section_vector) = pos.to_section_index_and_vector(self.min_build_height());
-
-        let section = self.sections.get(section_index).unwrap();
-
-        section.unguarded.as_mut().unwrap().block_state(section_vector)
+        section.read().block_state(pos)
     }
 
     pub fn set_block_state(&mut self, pos: Vector3BlockColumn, state: Arc<BlockState>) {
@@ -209,20 +422,7 @@ impl ChunkColumn {
 
         let section = self.sections.get_mut(section_index).unwrap();
 
-        section.guarded.write().unwrap().set_block_state(section_vector, state);
-    }
-
-    pub unsafe fn set_block_state_unguarded(&mut self, pos: Vector3BlockColumn, state: Arc<BlockState>) {
-        #[cfg(debug_assertions)]
-        if pos.y() < Self::Y_MIN || pos.y() > Self::Y_MAX {
-            panic!("Y out of range");
-        }
-
-        let (section_index, section_vector) = pos.to_section_index_and_vector(self.min_build_height());
-
-        let section = self.sections.get_mut(section_index).unwrap();
-
-        section.unguarded.as_mut().unwrap().set_block_state(section_vector, state);
+        section.get_mut().set_block_state(section_vector, state);
     }
 
     pub fn biome(&self, pos: Vector3) -> Arc<Biome> {
@@ -231,9 +431,7 @@ impl ChunkColumn {
 
         self
             .sections[section_index as usize]
-            .guarded
             .read()
-            .unwrap()
             .biome(Vector3u::new(pos.x as u32, local_y, pos.z as u32))
             .unwrap()
     }
@@ -246,24 +444,7 @@ impl ChunkColumn {
 
         self
             .sections[j]
-            .guarded
             .read()
-            .unwrap()
-            .biome(Vector3u::new((pos.x & 3) as u32, (l & 3) as u32, (pos.z & 3) as u32))
-            .unwrap()
-    }
-
-    pub unsafe fn biome2_unguarded(&self, pos: Vector3) -> Arc<Biome> {
-        let i = quart_pos_from_block(self.min_build_height());
-        let k = i + quart_pos_from_block(384) - 1;
-        let l = pos.y.clamp(i, k);
-        let j = Self::section_index(quart_pos_to_block(l));
-
-        self
-            .sections[j]
-            .unguarded
-            .as_ref()
-            .unwrap()
             .biome(Vector3u::new((pos.x & 3) as u32, (l & 3) as u32, (pos.z & 3) as u32))
             .unwrap()
     }
@@ -283,43 +464,20 @@ impl ChunkColumn {
         -64
     }
 
+    /// View of the chunk, which reads the sections under their locks.
     #[inline]
-    pub fn with_safe(&self) -> Ref<RwLockReadGuard> {
+    pub fn with_safe(&self) -> Ref {
         Ref {
             col: self,
-            extractor: &|h| {
-                RwLockReadGuard(h.guarded.read().unwrap())
-            },
         }
     }
 
+    /// View of the exclusively owned chunk, which writes the sections without
+    /// locking.
     #[inline]
-    pub unsafe fn with_unsafe(&self) -> Ref<&ChunkSection> {
-        Ref {
-            col: self,
-            extractor: &|h| {
-                unsafe { h.unguarded.as_ref().unwrap() }
-            }
-        }
-    }
-
-    #[inline]
-    pub fn with_safe_mut(&mut self) -> RefMut<RwLockWriteGuard> {
-        RefMut {
-            col: self,
-            extractor: &|h| {
-                RwLockWriteGuard(h.guarded.write().unwrap())
-            },
-        }
-    }
-
-    #[inline]
-    pub fn with_unsafe_mut(&mut self) -> RefMut<&mut ChunkSection> {
+    pub fn with_safe_mut(&mut self) -> RefMut {
         RefMut {
             col: self,
-            extractor: &|h| {
-                unsafe { h.unguarded.as_mut().unwrap() }
-            },
         }
     }
 }
@@ -343,63 +501,301 @@ pub trait ChunkColumnRefMut<'a> {
     fn set_block_state(&'a mut self, at: Vector3BlockColumn, state: Arc<BlockState>);
 }
 
-pub struct Ref<'a, R>
-where
-    R: AsRef<ChunkSection>
-{
+pub struct Ref<'a> {
     col: &'a ChunkColumn,
-    extractor: &'a dyn Fn(&'a ChunkSectionHandle) -> R,
 }
 
-impl<'a, R> ChunkColumnRef for Ref<'a, R>
-where
-    R: AsRef<ChunkSection>
-{
+impl<'a> ChunkColumnRef for Ref<'a> {
     fn block_state(&self, at: Vector3BlockColumn) -> Arc<BlockState> {
-        let (section_index, section_vector) = at.to_section_index_and_vector(self.col.min_build_height());
-        let section = self.col.sections.get(section_index).unwrap();
-
-        let handle = (self.extractor)(section);
-
-        handle.as_ref().block_state(section_vector).unwrap()
+        self.col.block_state(at).unwrap()
     }
 
     fn biome(&self, at: Vector3) -> Arc<Biome> {
-        let i = quart_pos_from_block(self.col.min_build_height());
-        let k = i + quart_pos_from_block(384) - 1;
-        let l = at.y.clamp(i, k);
-        let j = ChunkColumn::section_index(quart_pos_to_block(l));
-
-        self
-            .col
-            .sections[j]
-            .guarded
-            .read()
-            .unwrap()
-            .biome(Vector3u::new((at.x & 3) as u32, (l & 3) as u32, (at.z & 3) as u32))
-            .unwrap()
+        self.col.biome2(at)
     }
 }
 
-pub struct RefMut<'a, R>
-where
-    R: AsMut<ChunkSection>
-{
+pub struct RefMut<'a> {
     col: &'a mut ChunkColumn,
-    extractor: &'a dyn Fn(&'a ChunkSectionHandle) -> R,
 }
 
-impl<'a, R> ChunkColumnRefMut<'a> for RefMut<'a, R>
-where
-    R: AsMut<ChunkSection>
-{
+impl<'a> ChunkColumnRefMut<'a> for RefMut<'a> {
     fn set_block_state(&'a mut self, at: Vector3BlockColumn, state: Arc<BlockState>) {
-        let (section_index, section_vector) = at.to_section_index_and_vector(self.col.min_build_height());
-        let section = self.col.sections.get_mut(section_index).unwrap();
+        self.col.set_block_state(at, state);
+    }
+}
 
-        let mut handle = (self.extractor)(section);
 
-        handle.as_mut().set_block_state(section_vector, state);
+#[cfg(test)]
+mod tests {
+    use crate::block::packed::PackedArray;
+    use crate::chunk::column::ChunkColumn;
+    use crate::chunk::palette::global::GlobalId;
+    use crate::chunk::palette::{create_biome_global_palette_from_json, create_block_global_palette_from_json};
+    use crate::chunk::pos::ChunkPos;
+    use crate::chunk::status::ChunkStatus;
+    use crate::chunk::vector::{Vector3BlockColumn, Vector3BlockSection};
+    use std::sync::Arc;
+
+    const BLOCKS: &str = r#"
+    {
+        "minecraft:air": {"states": [{"id": 0, "default": true}]},
+        "minecraft:stone": {"states": [{"id": 1, "default": true}]},
+        "minecraft:oak_log": {
+            "properties": {"axis": ["x", "y", "z"]},
+            "states": [
+                {"id": 2, "properties": {"axis": "x"}},
+                {"id": 3, "default": true, "properties": {"axis": "y"}},
+                {"id": 4, "properties": {"axis": "z"}}
+            ]
+        }
+    }
+    "#;
+
+    const BIOMES: [&str; 10] = [
+        "minecraft:forest",
+        "minecraft:plains",
+        "minecraft:desert",
+        "minecraft:taiga",
+        "minecraft:swamp",
+        "minecraft:jungle",
+        "minecraft:savanna",
+        "minecraft:badlands",
+        "minecraft:beach",
+        "minecraft:river"
+    ];
+
+    fn biomes_json() -> serde_json::Value {
+        serde_json::Value::Array(
+            BIOMES
+                .iter()
+                .enumerate()
+                .map(|(id, name)| serde_json::json!({
+                    "id": id,
+                    "name": name,
+                    "element": {
+                        "downfall": 0.5,
+                        "temperature": 0.5,
+                        "precipitation": "rain",
+                        "effects": {
+                            "sky_color": 0,
+                            "water_fog_color": 0,
+                            "water_color": 0,
+                            "fog_color": 0,
+                            "mood_sound": {
+                                "tick_delay": 6000,
+                                "offset": 2.0,
+                                "sound": "minecraft:ambient.cave",
+                                "block_search_extent": 8
+                            }
+                        }
+                    }
+                }))
+                .collect()
+        )
+    }
+
+    fn packed(bits: usize, len: usize, f: impl Fn(usize) -> u16) -> nbt::Value {
+        let mut data = PackedArray::zeros(bits, len);
+        for i in 0..len {
+            data.set(i, f(i));
+        }
+
+        nbt::Value::LongArray(data.entries().iter().map(|x| *x as i64).collect())
+    }
+
+    fn compound(entries: Vec<(&str, nbt::Value)>) -> nbt::Value {
+        nbt::Value::Compound(entries.into_iter().map(|(k, v)| (k.to_owned(), v)).collect())
+    }
+
+    fn light(seed: usize) -> nbt::Value {
+        nbt::Value::ByteArray((0..2048).map(|i| ((i * 31 + seed) % 256) as u8 as i8).collect())
+    }
+
+    fn block(name: &str, properties: Option<Vec<(&str, &str)>>) -> nbt::Value {
+        let mut entries = vec![("Name", nbt::Value::String(name.to_owned()))];
+        if let Some(properties) = properties {
+            entries.push((
+                "Properties",
+                compound(properties.into_iter().map(|(k, v)| (k, nbt::Value::String(v.to_owned()))).collect())
+            ));
+        }
+
+        compound(entries)
+    }
+
+    /// Chunk in the form vanilla 1.19.4 writes it.
+    fn vanilla_chunk() -> nbt::Blob {
+        let mut sections = vec![compound(vec![("Y", nbt::Value::Byte(-5)), ("SkyLight", light(0))])];
+
+        for y in -4i8..20 {
+            let mut section = vec![("Y", nbt::Value::Byte(y))];
+
+            let block_states = match y {
+                -4 => compound(vec![
+                    ("palette", nbt::Value::List(vec![
+                        block("minecraft:stone", None),
+                        block("minecraft:air", None),
+                        block("minecraft:oak_log", Some(vec![("axis", "x")]))
+                    ])),
+                    ("data", packed(4, 4096, |i| (i % 3) as u16))
+                ]),
+                -3 => compound(vec![("palette", nbt::Value::List(vec![block("minecraft:stone", None)]))]),
+                _ => compound(vec![("palette", nbt::Value::List(vec![block("minecraft:air", None)]))])
+            };
+            section.push(("block_states", block_states));
+
+            let biomes = match y {
+                // More biomes than in-memory local palette is able to hold
+                -4 => compound(vec![
+                    ("palette", nbt::Value::List(BIOMES.iter().map(|name| nbt::Value::String(name.to_string())).collect())),
+                    ("data", packed(4, 64, |i| (i % 10) as u16))
+                ]),
+                -3 => compound(vec![
+                    ("palette", nbt::Value::List(vec![
+                        nbt::Value::String("minecraft:river".to_owned()),
+                        nbt::Value::String("minecraft:beach".to_owned())
+                    ])),
+                    ("data", packed(1, 64, |i| (i / 32) as u16))
+                ]),
+                _ => compound(vec![("palette", nbt::Value::List(vec![nbt::Value::String("minecraft:plains".to_owned())]))])
+            };
+            section.push(("biomes", biomes));
+
+            if y < 0 {
+                section.push(("BlockLight", light((y + 10) as usize)));
+            }
+            section.push(("SkyLight", light((y + 100) as usize)));
+
+            sections.push(compound(section));
+        }
+
+        sections.push(compound(vec![("Y", nbt::Value::Byte(20)), ("SkyLight", light(1))]));
+
+        let heightmap = nbt::Value::LongArray((0..37).map(|i| i * 0x1234_5678_9abc).collect());
+
+        let mut blob = nbt::Blob::new();
+        blob.insert("DataVersion", 3337i32).unwrap();
+        blob.insert("xPos", 2i32).unwrap();
+        blob.insert("yPos", -4i32).unwrap();
+        blob.insert("zPos", -3i32).unwrap();
+        blob.insert("Status", "minecraft:full").unwrap();
+        blob.insert("LastUpdate", 1234i64).unwrap();
+        blob.insert("InhabitedTime", 567i64).unwrap();
+        blob.insert("isLightOn", 1i8).unwrap();
+        blob.insert("sections", nbt::Value::List(sections)).unwrap();
+        blob.insert("Heightmaps", compound(vec![
+            ("MOTION_BLOCKING", heightmap.clone()),
+            ("MOTION_BLOCKING_NO_LEAVES", heightmap.clone()),
+            ("OCEAN_FLOOR", heightmap.clone()),
+            ("WORLD_SURFACE", heightmap)
+        ])).unwrap();
+        blob.insert("block_entities", nbt::Value::List(vec![compound(vec![
+            ("id", nbt::Value::String("minecraft:chest".to_owned())),
+            ("x", nbt::Value::Int(35)),
+            ("y", nbt::Value::Int(-60)),
+            ("z", nbt::Value::Int(-41)),
+            ("keepPacked", nbt::Value::Byte(0)),
+            ("Items", nbt::Value::List(Vec::new()))
+        ])])).unwrap();
+        blob.insert("structures", compound(vec![
+            ("References", compound(vec![
+                ("minecraft:village_plains", nbt::Value::LongArray(vec![(-4i64 & 0xFFFF_FFFF) << 32 | 3, 2]))
+            ])),
+            ("starts", compound(vec![
+                ("minecraft:village_plains", compound(vec![
+                    ("id", nbt::Value::String("minecraft:village_plains".to_owned())),
+                    ("ChunkX", nbt::Value::Int(2)),
+                    ("ChunkZ", nbt::Value::Int(-3)),
+                    ("references", nbt::Value::Int(0)),
+                    ("Children", nbt::Value::List(Vec::new()))
+                ]))
+            ]))
+        ])).unwrap();
+        blob.insert("fluid_ticks", nbt::Value::List(Vec::new())).unwrap();
+        blob.insert("block_ticks", nbt::Value::List(Vec::new())).unwrap();
+        blob.insert("PostProcessing", nbt::Value::List((0..24).map(|_| nbt::Value::List(Vec::new())).collect())).unwrap();
+
+        blob
+    }
+
+    #[test]
+    fn nbt_round_trip() {
+        let block_palette = Arc::new(create_block_global_palette_from_json(serde_json::from_str(BLOCKS).unwrap()));
+        let biome_palette = Arc::new(create_biome_global_palette_from_json(&biomes_json()));
+
+        let blob = vanilla_chunk();
+        let chunk = ChunkColumn::from_nbt(blob.clone(), block_palette, biome_palette).unwrap();
+
+        assert_eq!(2, chunk.pos().x());
+        assert_eq!(-3, chunk.pos().z());
+        assert_eq!(ChunkStatus::Full, chunk.status);
+        assert_eq!(567, chunk.inhabited_time);
+        assert_eq!(1, chunk.block_entities.len());
+        assert_eq!(2, chunk.outer_light.len());
+        assert_eq!(1, chunk.structure_starts.len());
+        assert_eq!(
+            vec![ChunkPos::new(3, -4), ChunkPos::new(2, 0)],
+            chunk.structure_references["minecraft:village_plains"]
+        );
+
+        assert_eq!("minecraft:stone", chunk.block_state(Vector3BlockColumn::new(0, -64, 0)).unwrap().name());
+        assert_eq!("minecraft:oak_log", chunk.block_state(Vector3BlockColumn::new(2, -64, 0)).unwrap().name());
+        assert_eq!("minecraft:stone", chunk.block_state(Vector3BlockColumn::new(5, -48, 7)).unwrap().name());
+        assert_eq!(4096 - 4096 / 3, chunk.section(0).read().non_empty_block_count);
+
+        assert_eq!(blob, chunk.to_nbt());
     }
-}
 
+    #[test]
+    fn damaged_nbt_is_error() {
+        let block_palette = Arc::new(create_block_global_palette_from_json(serde_json::from_str(BLOCKS).unwrap()));
+        let biome_palette = Arc::new(create_biome_global_palette_from_json(&biomes_json()));
+        let from_nbt = |blob| ChunkColumn::from_nbt(blob, block_palette.clone(), biome_palette.clone());
+
+        let mut blob = vanilla_chunk();
+        blob.insert("Status", "minecraft:unknown").unwrap();
+        assert!(from_nbt(blob).is_err());
+
+        let mut blob = vanilla_chunk();
+        blob.insert("sections", 1i32).unwrap();
+        assert!(from_nbt(blob).is_err());
+
+        // Data is shorter than the palette requires.
+        let mut blob = vanilla_chunk();
+        blob.insert("sections", nbt::Value::List(vec![compound(vec![
+            ("Y", nbt::Value::Byte(0)),
+            ("block_states", compound(vec![
+                ("palette", nbt::Value::List(vec![block("minecraft:stone", None), block("minecraft:air", None)])),
+                ("data", nbt::Value::LongArray(vec![0; 10]))
+            ]))
+        ])])).unwrap();
+        assert!(from_nbt(blob).is_err());
+
+        let mut blob = vanilla_chunk();
+        blob.insert("sections", nbt::Value::List(vec![compound(vec![
+            ("Y", nbt::Value::Byte(0)),
+            ("block_states", compound(vec![("palette", nbt::Value::List(vec![block("minecraft:unknown", None)]))]))
+        ])])).unwrap();
+        assert!(from_nbt(blob).is_err());
+    }
+
+    #[test]
+    fn owned_writes_are_seen_by_shared_readers() {
+        fn assert_shared<T: Send + Sync>() {}
+        assert_shared::<ChunkColumn>();
+
+        let block_palette = Arc::new(create_block_global_palette_from_json(serde_json::from_str(BLOCKS).unwrap()));
+        let biome_palette = Arc::new(create_biome_global_palette_from_json(&biomes_json()));
+        let stone = block_palette.get_obj_by_id(GlobalId(1)).unwrap();
+
+        let mut chunk = ChunkColumn::empty(ChunkPos::new(0, 0), block_palette, biome_palette);
+        chunk.set_block_state(Vector3BlockColumn::new(3, 10, 4), stone.clone());
+        chunk.section_mut(0).set_block_state(Vector3BlockSection::new(1, 2, 3), stone);
+
+        let chunk = Arc::new(chunk);
+
+        assert_eq!("minecraft:stone", chunk.block_state(Vector3BlockColumn::new(3, 10, 4)).unwrap().name());
+        assert_eq!("minecraft:stone", chunk.section(0).read().block_state(Vector3BlockSection::new(1, 2, 3)).unwrap().name());
+    }
+}
@@ -1,26 +1,33 @@
 use crate::chunk::section::ChunkSection;
 use std::sync::RwLock;
 
-/// Pair of safe (guarded) and unsafe (unguarded) pointers to the [`ChunkSection`].
-/// The unsafe one is always valid until this [`ChunkSectionHandle`] is not dropped.
-pub struct ChunkSectionHandle {
-    pub guarded: RwLock<Box<ChunkSection>>,
-    pub unguarded: *mut ChunkSection,
-}
+/// Lock around the [`ChunkSection`]. A chunk in generation is owned by a
+/// single step, which writes its sections through [`Self::get_mut`] without
+/// locking. Once the chunk is shared, the sections are reached only through
+/// the lock.
+pub struct ChunkSectionHandle(RwLock<ChunkSection>);
 
-unsafe impl Send for ChunkSectionHandle {}
+impl ChunkSectionHandle {
+    #[inline]
+    pub fn new(section: ChunkSection) -> Self {
+        Self(RwLock::new(section))
+    }
 
-unsafe impl Sync for ChunkSectionHandle {}
+    #[inline]
+    pub fn read(&self) -> RwLockReadGuard<'_> {
+        RwLockReadGuard(self.0.read().unwrap())
+    }
 
-impl ChunkSectionHandle {
     #[inline]
-    pub fn new(chunk: ChunkSection) -> Self {
-        let mut b = Box::new(chunk);
+    pub fn write(&self) -> RwLockWriteGuard<'_> {
+        RwLockWriteGuard(self.0.write().unwrap())
+    }
 
-        Self {
-            unguarded: &mut *b as *mut ChunkSection,
-            guarded: RwLock::new(b),
-        }
+    /// The section of the exclusively owned chunk. The borrow checker proves
+    /// there are no other readers, so the lock is not taken.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut ChunkSection {
+        self.0.get_mut().unwrap()
     }
 }
 
@@ -30,18 +37,40 @@ impl From<ChunkSection> for ChunkSectionHandle {
     }
 }
 
-pub struct RwLockReadGuard<'a> (pub std::sync::RwLockReadGuard<'a, Box<ChunkSection>>);
+pub struct RwLockReadGuard<'a> (pub std::sync::RwLockReadGuard<'a, ChunkSection>);
 
 impl<'a> AsRef<ChunkSection> for RwLockReadGuard<'a> {
     fn as_ref(&self) -> &ChunkSection {
-        &**self.0
+        &self.0
+    }
+}
+
+impl<'a> std::ops::Deref for RwLockReadGuard<'a> {
+    type Target = ChunkSection;
+
+    fn deref(&self) -> &ChunkSection {
+        &self.0
     }
 }
 
-pub struct RwLockWriteGuard<'a> (pub std::sync::RwLockWriteGuard<'a, Box<ChunkSection>>);
+pub struct RwLockWriteGuard<'a> (pub std::sync::RwLockWriteGuard<'a, ChunkSection>);
 
 impl<'a> AsMut<ChunkSection> for RwLockWriteGuard<'a> {
     fn as_mut(&mut self) -> &mut ChunkSection {
-        &mut **self.0
+        &mut self.0
+    }
+}
+
+impl<'a> std::ops::Deref for RwLockWriteGuard<'a> {
+    type Target = ChunkSection;
+
+    fn deref(&self) -> &ChunkSection {
+        &self.0
+    }
+}
+
+impl<'a> std::ops::DerefMut for RwLockWriteGuard<'a> {
+    fn deref_mut(&mut self) -> &mut ChunkSection {
+        &mut self.0
     }
 }
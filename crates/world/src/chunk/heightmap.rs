@@ -1,12 +1,16 @@
+use crate::block::material::Material;
 use crate::block::packed::PackedArray;
 use crate::block::state::BlockState;
+use crate::block::variant::{Variant, Waterlogged};
 use crate::chunk::column::ChunkColumnRef;
 use crate::chunk::vector::block::Vector2BlockSection;
 use crate::chunk::vector::Vector3BlockColumn;
+use anyhow::anyhow;
 use spherix_math::vector::Vector2;
 use spherix_util::math::smallest_encompassing_log2;
 use std::collections::HashMap;
 
+#[derive(Clone)]
 pub struct Heightmap
 {
     ty: HeightmapType,
@@ -24,6 +28,52 @@ impl Heightmap
         }
     }
 
+    /// Creates heightmap from the packed data as it is stored in the chunk NBT.
+    pub fn from_nbt(ty: HeightmapType, world_height: i32, min_build_height: i32, data: &[i64]) -> anyhow::Result<Self> {
+        let bits = smallest_encompassing_log2((world_height + 1) as u32) as usize;
+        let expected_len = 256usize.div_ceil(64 / bits);
+        if data.len() != expected_len {
+            return Err(anyhow!("Heightmap {} must have {} longs, {} given", ty.name(), expected_len, data.len()))
+        }
+
+        Ok(Self {
+            ty,
+            min_build_height,
+            data: PackedArray::new(data.iter().map(|x| *x as u64).collect(), bits, 256),
+        })
+    }
+
+    pub fn to_nbt(&self) -> nbt::Value {
+        nbt::Value::LongArray(self.data.entries().iter().map(|x| *x as i64).collect())
+    }
+
+    #[inline]
+    pub fn ty(&self) -> HeightmapType {
+        self.ty
+    }
+
+    /// Computes the heightmap from scratch, scanning columns of the chunk down
+    /// from `max_y`.
+    pub fn prime<C>(ty: HeightmapType, chunk: &C, world_height: i32, min_build_height: i32, max_y: i32) -> Self
+    where
+        C: ChunkColumnRef
+    {
+        let mut heightmap = Self::new(ty, world_height, min_build_height);
+
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in (min_build_height..=max_y).rev() {
+                    if ty.is_opaque(chunk.block_state(Vector3BlockColumn::new(x, y, z)).as_ref()) {
+                        heightmap.set_height(Vector2BlockSection::new(x, z), (y + 1 - min_build_height) as u16);
+                        break
+                    }
+                }
+            }
+        }
+
+        heightmap
+    }
+
     pub fn update<C>(&mut self, chunk: &C, at: Vector3BlockColumn, block: &BlockState) -> bool
     where
         C: ChunkColumnRef
@@ -82,6 +132,7 @@ impl Heightmap
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HeightmapType {
     WorldSurfaceWg,
     WorldSurface,
@@ -92,21 +143,52 @@ pub enum HeightmapType {
 }
 
 impl HeightmapType {
-    fn is_opaque(&self, block: &BlockState) -> bool {
+    pub const ALL: [HeightmapType; 6] = [
+        HeightmapType::WorldSurfaceWg,
+        HeightmapType::WorldSurface,
+        HeightmapType::OceanFloorWg,
+        HeightmapType::OceanFloor,
+        HeightmapType::MotionBlocking,
+        HeightmapType::MotionBlockingNoLeaves
+    ];
+
+    /// Key of the heightmap within "Heightmaps" compound of the chunk NBT.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HeightmapType::WorldSurfaceWg => "WORLD_SURFACE_WG",
+            HeightmapType::WorldSurface => "WORLD_SURFACE",
+            HeightmapType::OceanFloorWg => "OCEAN_FLOOR_WG",
+            HeightmapType::OceanFloor => "OCEAN_FLOOR",
+            HeightmapType::MotionBlocking => "MOTION_BLOCKING",
+            HeightmapType::MotionBlockingNoLeaves => "MOTION_BLOCKING_NO_LEAVES"
+        }
+    }
+
+    pub fn is_opaque(&self, block: &BlockState) -> bool {
         match self {
             HeightmapType::WorldSurfaceWg => Self::is_not_air(block),
             HeightmapType::WorldSurface => Self::is_not_air(block),
             HeightmapType::OceanFloorWg => Self::is_material_motion_blocking(block),
             HeightmapType::OceanFloor => Self::is_material_motion_blocking(block),
-            HeightmapType::MotionBlocking => {
-                todo!()
-            },
+            HeightmapType::MotionBlocking => Self::is_material_motion_blocking(block) || Self::has_fluid(block),
             HeightmapType::MotionBlockingNoLeaves => {
-                todo!()
+                (Self::is_material_motion_blocking(block) || Self::has_fluid(block))
+                    && block.block().properties().material() != &Material::LEAVES
             }
         }
     }
 
+    /// Whether the block is a fluid or contains one. Plants, which always grow
+    /// under water, are treated as waterlogged.
+    fn has_fluid(block: &BlockState) -> bool {
+        let material = block.block().properties().material();
+
+        material.liquid
+            || material == &Material::WATER_PLANT
+            || material == &Material::REPLACEABLE_WATER_PLANT
+            || block.variants().contains(&Variant::Waterlogged(Waterlogged(true)))
+    }
+
     #[inline]
     fn is_not_air(block: &BlockState) -> bool {
         !block.block().properties().is_air
@@ -118,6 +200,7 @@ impl HeightmapType {
     }
 }
 
+#[derive(Clone)]
 pub struct Heightmaps {
     pub world_surface_wg: Option<Heightmap>,
     pub world_surface: Option<Heightmap>,
@@ -139,6 +222,58 @@ impl Heightmaps {
         }
     }
 
+    pub fn get(&self, ty: HeightmapType) -> Option<&Heightmap> {
+        match ty {
+            HeightmapType::WorldSurfaceWg => self.world_surface_wg.as_ref(),
+            HeightmapType::WorldSurface => self.world_surface.as_ref(),
+            HeightmapType::OceanFloorWg => self.ocean_floor_wg.as_ref(),
+            HeightmapType::OceanFloor => self.ocean_floor.as_ref(),
+            HeightmapType::MotionBlocking => self.motion_blocking.as_ref(),
+            HeightmapType::MotionBlockingNoLeaves => self.motion_blocking_no_leaves.as_ref()
+        }
+    }
+
+    pub fn set(&mut self, heightmap: Heightmap) {
+        let slot = match heightmap.ty {
+            HeightmapType::WorldSurfaceWg => &mut self.world_surface_wg,
+            HeightmapType::WorldSurface => &mut self.world_surface,
+            HeightmapType::OceanFloorWg => &mut self.ocean_floor_wg,
+            HeightmapType::OceanFloor => &mut self.ocean_floor,
+            HeightmapType::MotionBlocking => &mut self.motion_blocking,
+            HeightmapType::MotionBlockingNoLeaves => &mut self.motion_blocking_no_leaves
+        };
+
+        *slot = Some(heightmap);
+    }
+
+    /// Reads "Heightmaps" compound of the chunk NBT. Unknown keys are ignored.
+    pub fn from_nbt(nbt: &HashMap<String, nbt::Value>, world_height: i32, min_build_height: i32) -> anyhow::Result<Self> {
+        let mut heightmaps = Self::empty();
+
+        for ty in HeightmapType::ALL {
+            match nbt.get(ty.name()) {
+                Some(nbt::Value::LongArray(data)) => {
+                    heightmaps.set(Heightmap::from_nbt(ty, world_height, min_build_height, data)?);
+                },
+                Some(x) => return Err(anyhow!("Heightmap {} must be a long array, {:?} given", ty.name(), x)),
+                None => {}
+            }
+        }
+
+        Ok(heightmaps)
+    }
+
+    /// Representation of the heightmaps for the chunk NBT. Unlike [`Self::to_nbt()`],
+    /// which is used for the network, all present heightmaps are written.
+    pub fn to_storage_nbt(&self) -> nbt::Value {
+        nbt::Value::Compound(
+            HeightmapType::ALL
+                .iter()
+                .filter_map(|ty| self.get(*ty).map(|heightmap| (ty.name().to_owned(), heightmap.to_nbt())))
+                .collect()
+        )
+    }
+
     pub fn to_nbt(&self) -> nbt::Blob {
         let mut blob = nbt::Blob::new();
 
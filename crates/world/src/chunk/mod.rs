@@ -2,6 +2,8 @@ pub mod pos;
 pub mod palette;
 pub mod biome;
 pub mod heightmap;
+pub mod block_entity;
+pub mod carving_mask;
 pub mod handle;
 pub mod column;
 pub mod section;
@@ -3,11 +3,14 @@ use crate::block::packed::PackedArray;
 use crate::block::state::BlockState;
 use crate::chunk::biome::Biome;
 use crate::chunk::palette::global::{GlobalId, GlobalPalette};
-use crate::chunk::palette::local::{create_biome_palette_from_nbt, create_block_palette_from_nbt, LocalId, LocalPalette, LocalPalettes, PutStatus, SingleValuedLocalPalette};
+use crate::chunk::palette::local::{biome_palette_entries_from_nbt, block_palette_entries_from_nbt, GlobalLocalPalette, HashMapLocalPalette, LocalId, LocalPalette, LocalPalettes, PutStatus, SingleValuedLocalPalette};
 use crate::chunk::palette::resize::LocalPaletteResizer;
+use anyhow::anyhow;
 use spherix_math::vector::Vector3u;
 use spherix_proto::io::{Error, Writable};
-use spherix_util::nbt::NbtExt;
+use log::warn;
+use spherix_util::math::smallest_encompassing_log2;
+use std::collections::HashMap;
 use std::io::Write;
 
 pub struct PalettedContainer {
@@ -55,6 +58,69 @@ impl PalettedContainer {
         previous
     }
 
+    /// Counts entries whose value satisfies the predicate. The predicate is
+    /// evaluated only once per distinct value.
+    pub fn count<F>(&self, predicate: F) -> usize
+    where
+        F: Fn(GlobalId) -> bool
+    {
+        let total = 1 << (3 * self.size_bits);
+        let mut matches = HashMap::new();
+
+        (0..total)
+            .filter(|i| {
+                let local = self.data.get(*i);
+
+                *matches.entry(local).or_insert_with(|| {
+                    predicate(self.palette.global_by_local(LocalId(local)).unwrap())
+                })
+            })
+            .count()
+    }
+
+    /// Serializes the container into the "block_states" (or "biomes") compound
+    /// of the chunk NBT. Like vanilla, the palette is rebuilt from values which
+    /// are actually present, in order of their first occurrence.
+    pub fn to_nbt<F>(&self, entry_to_nbt: F) -> nbt::Value
+    where
+        F: Fn(GlobalId) -> nbt::Value
+    {
+        let total = 1 << (3 * self.size_bits);
+
+        let mut entries = Vec::new();
+        let mut locals = HashMap::new();
+        let mut indices = Vec::with_capacity(total);
+
+        for i in 0..total {
+            let global = self.get_by_index(i).unwrap();
+            let local = *locals.entry(global).or_insert_with(|| {
+                entries.push(global);
+                (entries.len() - 1) as u16
+            });
+
+            indices.push(local);
+        }
+
+        let mut compound = HashMap::from([
+            ("palette".to_owned(), nbt::Value::List(entries.iter().map(|global| entry_to_nbt(*global)).collect()))
+        ]);
+
+        let bits = serialization_bits(entries.len(), self.resizer.min_fit_size);
+        if bits != 0 {
+            let mut data = PackedArray::zeros(bits as usize, total);
+            for (i, local) in indices.into_iter().enumerate() {
+                data.set(i, local);
+            }
+
+            compound.insert(
+                "data".to_owned(),
+                nbt::Value::LongArray(data.entries().iter().map(|x| *x as i64).collect())
+            );
+        }
+
+        nbt::Value::Compound(compound)
+    }
+
     #[inline]
     fn set_by_index(&mut self, index: usize, global: GlobalId) {
         let local = self.palette.local_by_global(global);
@@ -95,32 +161,6 @@ impl PalettedContainer {
     }
 }
 
-macro_rules! from_nbt_mixed {
-    ($nbt:ident, $palette:ident, $fit_size:ident, $total:literal, $resizer:ident, $size_bits:literal) => {
-        {
-            let mut vec = Vec::new();
-
-            for value in $nbt {
-                vec.push(match value {
-                    nbt::Value::Byte(x) => *x as u64,
-                    nbt::Value::Short(x) => *x as u64,
-                    nbt::Value::Int(x) => *x as u64,
-                    nbt::Value::Long(x) => *x as u64,
-                    x => panic!("Expected nbt::Value::Byte, nbt::Value::Short, nbt::Value::Int or nbt::Value::Long, {:?} given", x)
-                });
-            }
-
-            PalettedContainer::new($palette, PackedArray::new(vec, $fit_size as usize, $total), $resizer, $size_bits)
-        }
-    }
-}
-
-macro_rules! from_nbt {
-    ($data:ident, $palette:ident, $fit_size:ident, $total:literal, $resizer:ident, $size_bits:literal) => {
-        PalettedContainer::new($palette, PackedArray::new($data.iter().map(|x| *x as u64).collect(), $fit_size as usize, $total), $resizer, $size_bits)
-    }
-}
-
 pub fn create_empty_block_paletted_container(global_palette: &GlobalPalette<BlockState>) -> PalettedContainer {
     let resizer = LocalPaletteResizer::new(4, 15, 9);
     let palette = SingleValuedLocalPalette::new(
@@ -132,30 +172,14 @@ pub fn create_empty_block_paletted_container(global_palette: &GlobalPalette<Bloc
     PalettedContainer::new(LocalPalettes::SingleValued(palette), PackedArray::zeros(resizer.min_fit_size as usize, 4096), resizer, 4)
 }
 
-pub fn create_block_paletted_container_from_nbt(nbt: &nbt::Value, global_palette: &GlobalPalette<BlockState>) -> PalettedContainer {
+pub fn create_block_paletted_container_from_nbt(nbt: &nbt::Value, global_palette: &GlobalPalette<BlockState>) -> anyhow::Result<PalettedContainer> {
     let resizer = LocalPaletteResizer::new(4, 15, 9);
 
-    let nbt = nbt.as_compound();
-    let block_states = nbt.get("block_states").unwrap().as_compound();
-    let palette = create_block_palette_from_nbt(
-        block_states.get("palette").unwrap(),
-        &resizer,
-        global_palette,
-    );
-
-    let fit_size = palette.bits();
+    let block_states = container_from_nbt(nbt, "block_states")?;
+    let palette = block_states.get("palette").ok_or_else(|| anyhow!("Block states have no palette"))?;
+    let entries = block_palette_entries_from_nbt(palette, global_palette)?;
 
-    if palette.bits() == 0 {
-        PalettedContainer::new(palette, PackedArray::zeros(resizer.min_fit_size as usize, 4096), resizer, 4)
-    } else {
-        match block_states.get("data").unwrap() {
-            nbt::Value::List(data) => from_nbt_mixed!(data, palette, fit_size, 4096, resizer, 4),
-            nbt::Value::ByteArray(data) => from_nbt!(data, palette, fit_size, 4096, resizer, 4),
-            nbt::Value::IntArray(data) => from_nbt!(data, palette, fit_size, 4096, resizer, 4),
-            nbt::Value::LongArray(data) => from_nbt!(data, palette, fit_size, 4096, resizer, 4),
-            _ => panic!()
-        }
-    }
+    create_paletted_container_from_nbt(block_states, entries, resizer, 4)
 }
 
 pub fn create_empty_biome_paletted_container(global_palette: &GlobalPalette<Biome>) -> PalettedContainer {
@@ -169,29 +193,112 @@ pub fn create_empty_biome_paletted_container(global_palette: &GlobalPalette<Biom
     PalettedContainer::new(LocalPalettes::SingleValued(palette), PackedArray::zeros(resizer.min_fit_size as usize, 64), resizer, 2)
 }
 
-
-pub fn create_biome_paletted_container_from_nbt(nbt: &nbt::Value, global_palette: &GlobalPalette<Biome>) -> PalettedContainer {
+pub fn create_biome_paletted_container_from_nbt(nbt: &nbt::Value, global_palette: &GlobalPalette<Biome>) -> anyhow::Result<PalettedContainer> {
     let resizer = LocalPaletteResizer::new(1, 6, 4);
 
-    let nbt = nbt.as_compound();
-    let block_states = nbt.get("biomes").unwrap().as_compound();
-    let palette = create_biome_palette_from_nbt(
-        block_states.get("palette").unwrap(),
-        &resizer,
-        global_palette,
-    );
+    let biomes = container_from_nbt(nbt, "biomes")?;
+    let palette = biomes.get("palette").ok_or_else(|| anyhow!("Biomes have no palette"))?;
+    let entries = biome_palette_entries_from_nbt(palette, global_palette)?;
+
+    create_paletted_container_from_nbt(biomes, entries, resizer, 2)
+}
 
-    let fit_size = palette.bits();
+fn container_from_nbt<'a>(section: &'a nbt::Value, name: &str) -> anyhow::Result<&'a HashMap<String, nbt::Value>> {
+    match section {
+        nbt::Value::Compound(section) => match section.get(name) {
+            Some(nbt::Value::Compound(container)) => Ok(container),
+            x => Err(anyhow!("Section must have {} compound, {:?} given", name, x))
+        },
+        x => Err(anyhow!("Section must be a compound, {:?} given", x))
+    }
+}
+
+/// On disk the palette always lists only values which are present in the
+/// container, and "data" refers to it even if the palette is large enough to
+/// switch to the global one. So, in the latter case, local indices are
+/// replaced with global ones.
+fn create_paletted_container_from_nbt(
+    nbt: &HashMap<String, nbt::Value>,
+    entries: Vec<GlobalId>,
+    resizer: LocalPaletteResizer,
+    size_bits: u8
+) -> anyhow::Result<PalettedContainer> {
+    let total: usize = 1 << (3 * size_bits);
+    if entries.is_empty() {
+        return Err(anyhow!("Palette is empty"))
+    }
+
+    let bits = serialization_bits(entries.len(), resizer.min_fit_size);
+
+    if bits == 0 {
+        return Ok(PalettedContainer::new(
+            LocalPalettes::SingleValued(SingleValuedLocalPalette::new(entries[0])),
+            PackedArray::zeros(resizer.min_fit_size as usize, total),
+            resizer,
+            size_bits
+        ));
+    }
+
+    let data = nbt.get("data").ok_or_else(|| anyhow!("Palette has {} entries, but there is no data", entries.len()))?;
+    let data = packed_data_from_nbt(data)?;
+    let expected_len = total.div_ceil(64 / bits as usize);
+    if data.len() != expected_len {
+        return Err(anyhow!("Expected {} longs of data with {} bits per entry, {} given", expected_len, bits, data.len()))
+    }
 
-    if palette.bits() == 0 {
-        PalettedContainer::new(palette, PackedArray::zeros(resizer.min_fit_size as usize, 64), resizer, 2)
+    let stored = PackedArray::new(data, bits as usize, total);
+    // Entries refer to the palette, so an index out of it is a damaged data.
+    if let Some(local) = (0..total).map(|i| stored.get(i)).find(|local| *local as usize >= entries.len()) {
+        return Err(anyhow!("Data refers to entry {}, but palette has only {} entries", local, entries.len()))
+    }
+
+    if bits < resizer.threshold {
+        let mut palette = HashMapLocalPalette::with_capacity(bits, entries.len());
+        for global in entries {
+            if let PutStatus::NeedResize { .. } = palette.put(global) {
+                warn!("Resizing attempt during palette creation is a marker of bug!")
+            }
+        }
+
+        Ok(PalettedContainer::new(LocalPalettes::HashMap(palette), stored, resizer, size_bits))
     } else {
-        match block_states.get("data").unwrap() {
-            nbt::Value::List(data) => from_nbt_mixed!(data, palette, fit_size, 64, resizer, 2),
-            nbt::Value::ByteArray(data) => from_nbt!(data, palette, fit_size, 64, resizer, 2),
-            nbt::Value::IntArray(data) => from_nbt!(data, palette, fit_size, 64, resizer, 2),
-            nbt::Value::LongArray(data) => from_nbt!(data, palette, fit_size, 64, resizer, 2),
-            _ => panic!()
+        let mut data = PackedArray::zeros(resizer.max_fit_size as usize, total);
+        for i in 0..total {
+            data.set(i, entries[stored.get(i) as usize].0);
         }
+
+        Ok(PalettedContainer::new(LocalPalettes::Global(GlobalLocalPalette::new(resizer.max_fit_size)), data, resizer, size_bits))
+    }
+}
+
+fn packed_data_from_nbt(nbt: &nbt::Value) -> anyhow::Result<Vec<u64>> {
+    match nbt {
+        nbt::Value::LongArray(data) => Ok(data.iter().map(|x| *x as u64).collect()),
+        nbt::Value::IntArray(data) => Ok(data.iter().map(|x| *x as u64).collect()),
+        nbt::Value::ByteArray(data) => Ok(data.iter().map(|x| *x as u64).collect()),
+        nbt::Value::List(data) => data
+            .iter()
+            .map(|value| match value {
+                nbt::Value::Byte(x) => Ok(*x as u64),
+                nbt::Value::Short(x) => Ok(*x as u64),
+                nbt::Value::Int(x) => Ok(*x as u64),
+                nbt::Value::Long(x) => Ok(*x as u64),
+                x => Err(anyhow!("Expected nbt::Value::Byte, nbt::Value::Short, nbt::Value::Int or nbt::Value::Long, {:?} given", x))
+            })
+            .collect(),
+        x => Err(anyhow!("Expected array of integers, {:?} given", x))
+    }
+}
+
+/// Bits per entry of the "data" array on disk. It depends only on the size of
+/// the palette: there is no "data" at all for a single value, and the minimal
+/// size is the same as in memory.
+fn serialization_bits(palette_len: usize, min_fit_size: u8) -> u8 {
+    let bits = smallest_encompassing_log2(palette_len as u32) as u8;
+
+    if bits == 0 {
+        0
+    } else {
+        bits.max(min_fit_size)
     }
 }
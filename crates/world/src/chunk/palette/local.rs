@@ -3,16 +3,14 @@ use crate::block::state::{BlockState, VariantPermutations};
 use crate::block::variant::Variant;
 use crate::chunk::biome::Biome;
 use crate::chunk::palette::global::{GlobalId, GlobalPalette};
-use crate::chunk::palette::resize::LocalPaletteResizer;
+use anyhow::anyhow;
 use bimap::BiHashMap;
 use gxhash::{GxBuildHasher, GxHasher};
-use log::warn;
-use nalgebra::max;
 use spherix_proto::io::{Byte, Error, VarInt, Writable};
-use spherix_util::nbt::NbtExt;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::io::Write;
+use std::sync::Arc;
 
 /// Represents object ID within local palette
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -318,86 +316,72 @@ impl LocalPalette for GlobalLocalPalette {
     }
 }
 
-pub fn create_block_palette_from_nbt(nbt: &nbt::Value, resizer: &LocalPaletteResizer, global_palette: &GlobalPalette<BlockState>) -> LocalPalettes {
-    let items = nbt.as_list();
-    let bits = (items.len() as f64).log2().ceil() as u8;
-
-    if bits == 0 {
-        let item = items[0].as_compound();
-        let global = global_id_of_block(item, &global_palette);
-
-        LocalPalettes::SingleValued(SingleValuedLocalPalette::new(global))
-    } else if bits < resizer.threshold {
-        let bits = max(bits, resizer.min_fit_size);
-
-        // Init with capacity?
-        let mut palette = HashMapLocalPalette::new(bits);
-        for item in items {
-            let item = item.as_compound();
-            let global = global_id_of_block(item, &global_palette);
-            match palette.put(global) {
-                PutStatus::NeedResize { .. } => {
-                    warn!("Resizing attempt during palette creation is a marker of bug!")
-                },
-                _ => {}
-            }
-        }
-
-        LocalPalettes::HashMap(palette)
-    } else {
-        LocalPalettes::Global(GlobalLocalPalette::new(resizer.max_fit_size))
-    }
+/// Resolves entries of the section block palette as it is stored in the chunk
+/// NBT. Order of the entries is kept, so indices of the resulting vector are
+/// the local identifiers used by the "data" array.
+pub fn block_palette_entries_from_nbt(nbt: &nbt::Value, global_palette: &GlobalPalette<BlockState>) -> anyhow::Result<Vec<GlobalId>> {
+    let nbt::Value::List(items) = nbt else {
+        return Err(anyhow!("Block palette must be a list, {:?} given", nbt))
+    };
+
+    items
+        .iter()
+        .map(|item| match item {
+            nbt::Value::Compound(item) => global_id_of_block(item, global_palette),
+            x => Err(anyhow!("Block palette entry must be a compound, {:?} given", x))
+        })
+        .collect()
 }
 
-fn global_id_of_block(item: &HashMap<String, nbt::Value>, global_palette: &GlobalPalette<BlockState>) -> GlobalId {
-    let name = item.get("Name").unwrap().as_string();
-    let block = BLOCKS.get(name).unwrap();
-    let states = global_palette.get_objs_by_index(block).unwrap();
-    let mut state = states[0].clone();
+fn global_id_of_block(item: &HashMap<String, nbt::Value>, global_palette: &GlobalPalette<BlockState>) -> anyhow::Result<GlobalId> {
+    let name = match item.get("Name") {
+        Some(nbt::Value::String(name)) => name,
+        x => return Err(anyhow!("Block palette entry must have a name, {:?} given", x))
+    };
+    let block = BLOCKS.get(name).ok_or_else(|| anyhow!("Unknown block {}", name))?;
+    let states = global_palette.get_objs_by_index(block).unwrap_or_default();
+    let mut state = Arc::clone(states.first().ok_or_else(|| anyhow!("No states of block {}", name))?);
 
-    if item.contains_key("Properties") {
-        let props = item.get("Properties").unwrap().as_compound();
+    if let Some(props) = item.get("Properties") {
+        let nbt::Value::Compound(props) = props else {
+            return Err(anyhow!("Properties of block {} must be a compound, {:?} given", name, props))
+        };
 
         let variants = Variant::from_nbt(props, state.variants())
-            .expect(&format!("Variant::from_nbt: {:?} {:?} {:?}", name, props, state.variants()));
+            .ok_or_else(|| anyhow!("Invalid properties of block {}: {:?}, expected {:?}", name, props, state.variants()))?;
 
-        state = states.get_state_by_variants(variants).unwrap().clone();
-    } else {
-        if states.len() != 1 {
-            panic!("Illegal state")
-        }
+        state = states
+            .get_state_by_variants(variants)
+            .ok_or_else(|| anyhow!("No state of block {} with properties {:?}", name, props))?
+            .clone();
+    } else if states.len() != 1 {
+        return Err(anyhow!("Block {} has {} states, but no properties are given", name, states.len()))
     }
 
-    global_palette.get_id_by_obj(&state).unwrap()
+    global_palette.get_id_by_obj(&state).ok_or_else(|| anyhow!("Block state of {} is not in the global palette", name))
 }
 
-pub fn create_biome_palette_from_nbt(nbt: &nbt::Value, resizer: &LocalPaletteResizer, global_palette: &GlobalPalette<Biome>) -> LocalPalettes {
-    let items = nbt.as_list();
-    let bits = (items.len() as f64).log2().ceil() as u8;
-
-    if bits == 0 {
-        let item = items[0].as_string();
-        let global = global_id_of_biome(item, &global_palette);
-
-        LocalPalettes::SingleValued(SingleValuedLocalPalette::new(global))
-    } else if bits < resizer.threshold {
-        // Init with capacity?
-        let mut palette = HashMapLocalPalette::new(bits);
-        for item in items {
-            let item = item.as_string();
-            let global = global_id_of_biome(item, &global_palette);
-            palette.put(global);
-        }
-
-        LocalPalettes::HashMap(palette)
-    } else {
-        LocalPalettes::Global(GlobalLocalPalette::new(resizer.max_fit_size))
-    }
+/// The same as [`block_palette_entries_from_nbt`], but for biomes.
+pub fn biome_palette_entries_from_nbt(nbt: &nbt::Value, global_palette: &GlobalPalette<Biome>) -> anyhow::Result<Vec<GlobalId>> {
+    let nbt::Value::List(items) = nbt else {
+        return Err(anyhow!("Biome palette must be a list, {:?} given", nbt))
+    };
+
+    items
+        .iter()
+        .map(|item| match item {
+            nbt::Value::String(item) => global_id_of_biome(item, global_palette),
+            x => Err(anyhow!("Biome palette entry must be a string, {:?} given", x))
+        })
+        .collect()
 }
 
-fn global_id_of_biome(item: &String, global_palette: &GlobalPalette<Biome>) -> GlobalId {
+fn global_id_of_biome(item: &String, global_palette: &GlobalPalette<Biome>) -> anyhow::Result<GlobalId> {
     // todo: get id be name directly
-    let biome = global_palette.get_objs_by_index(item).unwrap();
-    let biome = biome.first().unwrap();
-    global_palette.get_id_by_obj(biome).unwrap()
+    let biome = global_palette
+        .get_objs_by_index(item)
+        .and_then(|biomes| biomes.first().copied())
+        .ok_or_else(|| anyhow!("Unknown biome {}", item))?;
+
+    global_palette.get_id_by_obj(biome).ok_or_else(|| anyhow!("Biome {} is not in the global palette", item))
 }
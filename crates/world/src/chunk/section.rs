@@ -1,11 +1,12 @@
 use crate::block::state::BlockState;
 use crate::chunk::biome::Biome;
-use crate::chunk::palette::container::{create_biome_paletted_container_from_nbt, create_block_paletted_container_from_nbt, PalettedContainer};
+use crate::chunk::palette::container::{create_biome_paletted_container_from_nbt, create_block_paletted_container_from_nbt, create_empty_biome_paletted_container, create_empty_block_paletted_container, PalettedContainer};
 use crate::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
 use crate::chunk::vector::Vector3BlockSection;
+use anyhow::anyhow;
 use spherix_math::vector::Vector3u;
 use spherix_proto::io::{Short, Writable};
-use spherix_util::nbt::NbtExt;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Chunk section as described [`here`].
@@ -47,39 +48,51 @@ impl ChunkSection {
     }
 
     /// https://minecraft.fandom.com/wiki/Chunk_format#NBT_structure
-    pub fn from_nbt(nbt: &nbt::Value, blocks_global_palette: Arc<BlockGlobalPalette>, biomes_global_palette: Arc<BiomeGlobalPalette>) -> Self {
-        let section = nbt.as_compound();
-
-        let y = match section.get("Y").unwrap() {
-            nbt::Value::Byte(x) => *x,
-            nbt::Value::Short(x) => *x as i8,
-            nbt::Value::Int(x) => *x as i8,
-            nbt::Value::Long(x) => *x as i8,
-            _ => panic!()
-        };
-
-        let blocks = create_block_paletted_container_from_nbt(nbt, blocks_global_palette.as_ref());
-        let biomes = create_biome_paletted_container_from_nbt(nbt, biomes_global_palette.as_ref());
+    ///
+    /// Missing containers are replaced with empty ones as vanilla does.
+    pub fn from_nbt(nbt: &nbt::Value, idx: i8, blocks_global_palette: Arc<BlockGlobalPalette>, biomes_global_palette: Arc<BiomeGlobalPalette>) -> anyhow::Result<Self> {
+        let section = section_from_nbt(nbt)?;
 
-        let block_light_packed;
-        let block_light = section.get("BlockLight");
-        if block_light.is_some() {
-            block_light_packed = Some(block_light.unwrap().as_byte_array().iter().map(|x| *x as u8).collect::<Vec<u8>>().try_into().unwrap());
+        let blocks = if section.contains_key("block_states") {
+            create_block_paletted_container_from_nbt(nbt, blocks_global_palette.as_ref())?
         } else {
-            block_light_packed = None
-        }
+            create_empty_block_paletted_container(blocks_global_palette.as_ref())
+        };
 
-        let sky_light_packed;
-        let sky_light = section.get("SkyLight");
-        if sky_light.is_some() {
-            sky_light_packed = Some(sky_light.unwrap().as_byte_array().iter().map(|x| *x as u8).collect::<Vec<u8>>().try_into().unwrap());
+        let biomes = if section.contains_key("biomes") {
+            create_biome_paletted_container_from_nbt(nbt, biomes_global_palette.as_ref())?
         } else {
-            sky_light_packed = None
-        }
+            create_empty_biome_paletted_container(biomes_global_palette.as_ref())
+        };
+
+        let light = SectionLight::from_nbt(nbt)?;
 
-        let mut chunk = Self::new(y, blocks_global_palette, biomes_global_palette, blocks, biomes, block_light_packed, sky_light_packed);
+        let mut chunk = Self::new(idx, blocks_global_palette, biomes_global_palette, blocks, biomes, light.block_light, light.sky_light);
+        chunk.non_empty_block_count = chunk.blocks.count(|global| {
+            !chunk.block_global_palette.get_obj_by_id(global).unwrap().block().properties.is_air
+        });
+
+        Ok(chunk)
+    }
 
-        chunk
+    /// Inverse of [`Self::from_nbt()`]. Y coordinate of the section is not known
+    /// to the section itself, so it is passed by the column.
+    pub fn to_nbt(&self, y: i8) -> nbt::Value {
+        let mut section = HashMap::from([
+            ("Y".to_owned(), nbt::Value::Byte(y)),
+            (
+                "block_states".to_owned(),
+                self.blocks.to_nbt(|global| self.block_global_palette.get_obj_by_id(global).unwrap().to_nbt())
+            ),
+            (
+                "biomes".to_owned(),
+                self.biomes.to_nbt(|global| nbt::Value::String(self.biome_global_palette.get_obj_by_id(global).unwrap().name()))
+            )
+        ]);
+
+        light_to_nbt(&mut section, &self.block_light, &self.sky_light);
+
+        nbt::Value::Compound(section)
     }
 
     pub fn to_packet_bytes(&self) -> Vec<u8> {
@@ -149,3 +162,73 @@ impl AsMut<ChunkSection> for ChunkSection {
         self
     }
 }
+
+/// Light of the section which has no blocks. Vanilla keeps such sections right
+/// below and above the build height, because light propagates through them.
+pub struct SectionLight {
+    pub y: i8,
+    pub block_light: Option<[u8; 2048]>,
+    pub sky_light: Option<[u8; 2048]>
+}
+
+impl SectionLight {
+    pub fn from_nbt(nbt: &nbt::Value) -> anyhow::Result<Self> {
+        let section = section_from_nbt(nbt)?;
+
+        Ok(Self {
+            y: section_y_from_nbt(nbt)?,
+            block_light: section.get("BlockLight").map(light_from_nbt).transpose()?,
+            sky_light: section.get("SkyLight").map(light_from_nbt).transpose()?,
+        })
+    }
+
+    pub fn to_nbt(&self) -> nbt::Value {
+        let mut section = HashMap::from([
+            ("Y".to_owned(), nbt::Value::Byte(self.y))
+        ]);
+
+        light_to_nbt(&mut section, &self.block_light, &self.sky_light);
+
+        nbt::Value::Compound(section)
+    }
+}
+
+pub fn section_y_from_nbt(nbt: &nbt::Value) -> anyhow::Result<i8> {
+    match section_from_nbt(nbt)?.get("Y") {
+        Some(nbt::Value::Byte(x)) => Ok(*x),
+        Some(nbt::Value::Short(x)) => Ok(*x as i8),
+        Some(nbt::Value::Int(x)) => Ok(*x as i8),
+        Some(nbt::Value::Long(x)) => Ok(*x as i8),
+        x => Err(anyhow!("Section Y must be an integer, {:?} given", x))
+    }
+}
+
+fn section_from_nbt(nbt: &nbt::Value) -> anyhow::Result<&HashMap<String, nbt::Value>> {
+    match nbt {
+        nbt::Value::Compound(section) => Ok(section),
+        x => Err(anyhow!("Section must be a compound, {:?} given", x))
+    }
+}
+
+fn light_from_nbt(nbt: &nbt::Value) -> anyhow::Result<[u8; 2048]> {
+    let nbt::Value::ByteArray(light) = nbt else {
+        return Err(anyhow!("Light must be a byte array, {:?} given", nbt))
+    };
+
+    light
+        .iter()
+        .map(|x| *x as u8)
+        .collect::<Vec<u8>>()
+        .try_into()
+        .map_err(|light: Vec<u8>| anyhow!("Light must have 2048 bytes, {} given", light.len()))
+}
+
+fn light_to_nbt(section: &mut HashMap<String, nbt::Value>, block_light: &Option<[u8; 2048]>, sky_light: &Option<[u8; 2048]>) {
+    if let Some(block_light) = block_light {
+        section.insert("BlockLight".to_owned(), nbt::Value::ByteArray(block_light.iter().map(|x| *x as i8).collect()));
+    }
+
+    if let Some(sky_light) = sky_light {
+        section.insert("SkyLight".to_owned(), nbt::Value::ByteArray(sky_light.iter().map(|x| *x as i8).collect()));
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChunkStatus {
     Empty,
     StructureStarts,
@@ -32,4 +33,47 @@ impl ChunkStatus {
             ChunkStatus::Full => 12
         }
     }
+
+    /// Identifier of the status as it is stored in the "Status" tag of the
+    /// chunk NBT.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChunkStatus::Empty => "minecraft:empty",
+            ChunkStatus::StructureStarts => "minecraft:structure_starts",
+            ChunkStatus::StructureReferences => "minecraft:structure_references",
+            ChunkStatus::Biomes => "minecraft:biomes",
+            ChunkStatus::Noise => "minecraft:noise",
+            ChunkStatus::Surface => "minecraft:surface",
+            ChunkStatus::Carvers => "minecraft:carvers",
+            ChunkStatus::LiquidCarvers => "minecraft:liquid_carvers",
+            ChunkStatus::Features => "minecraft:features",
+            ChunkStatus::Light => "minecraft:light",
+            ChunkStatus::Spawn => "minecraft:spawn",
+            ChunkStatus::Heightmaps => "minecraft:heightmaps",
+            ChunkStatus::Full => "minecraft:full"
+        }
+    }
+
+    /// Inverse of [`Self::name()`]. Identifiers without namespace (as they
+    /// were written by older versions) are accepted too.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+
+        Some(match name {
+            "empty" => ChunkStatus::Empty,
+            "structure_starts" => ChunkStatus::StructureStarts,
+            "structure_references" => ChunkStatus::StructureReferences,
+            "biomes" => ChunkStatus::Biomes,
+            "noise" => ChunkStatus::Noise,
+            "surface" => ChunkStatus::Surface,
+            "carvers" => ChunkStatus::Carvers,
+            "liquid_carvers" => ChunkStatus::LiquidCarvers,
+            "features" => ChunkStatus::Features,
+            "light" => ChunkStatus::Light,
+            "spawn" => ChunkStatus::Spawn,
+            "heightmaps" => ChunkStatus::Heightmaps,
+            "full" => ChunkStatus::Full,
+            _ => return None
+        })
+    }
 }
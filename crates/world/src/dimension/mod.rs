@@ -1,4 +1,5 @@
 use bevy_ecs::prelude::Component;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Component)]
 pub enum DimensionKind {
@@ -11,7 +12,21 @@ impl From<String> for DimensionKind {
     fn from(value: String) -> Self {
         match value.as_str() {
             "minecraft:overworld" => DimensionKind::Overworld,
+            "minecraft:the_nether" => DimensionKind::TheNether,
+            "minecraft:the_end" => DimensionKind::TheEnd,
             _ => panic!("invalid dimension name")
         }
     }
 }
+
+impl DimensionKind {
+    /// Directory of the dimension within the world directory as vanilla lays
+    /// it out.
+    pub fn dir(&self, world_dir: &Path) -> PathBuf {
+        match self {
+            DimensionKind::Overworld => world_dir.to_path_buf(),
+            DimensionKind::TheNether => world_dir.join("DIM-1"),
+            DimensionKind::TheEnd => world_dir.join("DIM1")
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::io::{BufRead, Read, Write};
 use flate2::bufread::{GzDecoder, ZlibDecoder};
 use flate2::write::{GzEncoder, ZlibEncoder};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Compression {
     Gzip,
     Zlib
@@ -12,21 +13,30 @@ impl Compression {
     const TYPE_GZIP: u8 = 1;
     const TYPE_ZLIB: u8 = 2;
 
-    pub fn decode<R: BufRead>(self, buf_read: &mut R) -> Vec<u8> {
+    /// Identifier of the compression scheme as it is stored in the chunk header
+    /// of a region file.
+    pub fn id(&self) -> u8 {
+        match self {
+            Compression::Gzip => Self::TYPE_GZIP,
+            Compression::Zlib => Self::TYPE_ZLIB,
+        }
+    }
+
+    pub fn decode<R: BufRead>(self, buf_read: &mut R) -> anyhow::Result<Vec<u8>> {
         let mut buf = Vec::<u8>::new();
 
         match self {
             Compression::Gzip => {
                 let mut decoder = GzDecoder::new(buf_read);
-                decoder.read_to_end(&mut buf).unwrap();
+                decoder.read_to_end(&mut buf)?;
             },
             Compression::Zlib => {
                 let mut decoder = ZlibDecoder::new(buf_read);
-                decoder.read_to_end(&mut buf).unwrap();
+                decoder.read_to_end(&mut buf)?;
             }
         };
 
-        buf
+        Ok(buf)
     }
 
     pub fn encode<W: Write>(&self, data: &Vec<u8>, buf: &mut W, level: flate2::Compression) -> anyhow::Result<usize> {
@@ -41,12 +51,14 @@ impl Compression {
     }
 }
 
-impl From<u8> for Compression {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for Compression {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            Self::TYPE_GZIP => Self::Gzip,
-            Self::TYPE_ZLIB => Self::Zlib,
-            x => panic!("{}", format!("Unexpected compression type {} given", x))
+            Self::TYPE_GZIP => Ok(Self::Gzip),
+            Self::TYPE_ZLIB => Ok(Self::Zlib),
+            x => Err(anyhow::anyhow!("Unexpected compression type {} given", x))
         }
     }
 }
@@ -1,9 +1,12 @@
-use std::fs::File;
-use std::io::{BufReader, Seek, SeekFrom};
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::anyhow;
 
 use crate::region::pos::{ChunkWithinRegionPos, RegionPos};
-use spherix_proto::io::Readable;
+use spherix_proto::io::{Readable, Writable};
 
 use crate::io::Compression;
 use crate::region::RegionFile;
@@ -50,57 +53,175 @@ use crate::region::RegionFile;
 ///         </tr>
 ///     </tbody>
 /// </table>
+///
+/// Each chunk starts with a 4 bytes length (including the compression type byte),
+/// followed by 1 byte of compression type and the compressed NBT data itself.
+/// Chunk data is always padded up to the sector boundary.
 pub struct Anvil {
     file: BufReader<File>,
-    size: u64,
-    pos: RegionPos
+    pos: RegionPos,
+    compression: Compression,
+    locations: [u32; Self::ENTRIES],
+    timestamps: [u32; Self::ENTRIES],
+    sectors: SectorBitmap,
+    writable: bool,
 }
 
 impl Anvil {
     const SECTOR_SIZE_BYTES: u64 = 4096;
+    const ENTRIES: usize = 1024;
+    /// Locations and timestamps tables occupy first 2 sectors of the file.
+    const HEADER_SECTORS: usize = 2;
+    /// Sectors count is stored in a single byte of a location entry.
+    const MAX_CHUNK_SECTORS: usize = 255;
+    /// 4 bytes of length and 1 byte of compression type.
+    const CHUNK_HEADER_SIZE_BYTES: usize = 5;
+
+    pub fn new(file: File, pos: RegionPos) -> anyhow::Result<Self> {
+        Self::from_file(file, pos, true)
+    }
+
+    fn from_file(file: File, pos: RegionPos, writable: bool) -> anyhow::Result<Self> {
+        let mut file = BufReader::new(file);
+        let size = file.get_ref().metadata()?.len();
+
+        let mut locations = [0; Self::ENTRIES];
+        let mut timestamps = [0; Self::ENTRIES];
+
+        if size < Self::HEADER_SECTORS as u64 * Self::SECTOR_SIZE_BYTES {
+            // Brand-new (or truncated) file has no chunks. Once it is opened for
+            // writing, an empty header is written, so the file is a valid region
+            // from now on.
+            if writable {
+                let header = [0u8; Self::HEADER_SECTORS * Self::SECTOR_SIZE_BYTES as usize];
+                file.seek(SeekFrom::Start(0))?;
+                file.get_mut().write_all(&header)?;
+            }
+        } else {
+            file.seek(SeekFrom::Start(0))?;
+            for location in locations.iter_mut() {
+                *location = i32::read(&mut file)? as u32;
+            }
 
-    pub fn new(file: File, pos: RegionPos) -> Self {
-        let size = file.metadata().unwrap().len();
+            for timestamp in timestamps.iter_mut() {
+                *timestamp = i32::read(&mut file)? as u32;
+            }
+        }
+
+        let mut sectors = SectorBitmap::new();
+        sectors.force(0, Self::HEADER_SECTORS);
 
-        Self {
-            file: BufReader::new(file),
-            size,
-            pos
+        for location in locations.iter() {
+            let offset = Self::offset_from_location_value(*location);
+            let count = Self::sectors_from_location_value(*location);
+
+            if count != 0 {
+                sectors.force(offset, count);
+            }
         }
+
+        Ok(Self {
+            file,
+            pos,
+            compression: Compression::Zlib,
+            locations,
+            timestamps,
+            sectors,
+            writable,
+        })
+    }
+
+    /// Opens the region file at the given position for reading and writing. The
+    /// file (and the directory) will be created if it does not exist yet.
+    pub fn at(region_pos: RegionPos, base_path: PathBuf) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&base_path)?;
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(base_path.join(Self::filename(&region_pos)))?;
+
+        Self::new(file, region_pos)
     }
 
-    pub fn at(region_pos: RegionPos, base_path: PathBuf) -> Self {
-        Self::new(
-            File::open(base_path.join(Self::filename(&region_pos))).map_err(|e| {
-                println!("{}", e);
+    /// Opens the existing region file at the given position for reading only.
+    /// Returns [`None`] if there is no such file, nothing is created.
+    pub fn open(region_pos: RegionPos, base_path: PathBuf) -> anyhow::Result<Option<Self>> {
+        let file = match File::open(base_path.join(Self::filename(&region_pos))) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        Self::from_file(file, region_pos, false).map(Some)
+    }
 
-                return e
-            }).unwrap(),
-            region_pos
-        )
+    /// Whether chunks can be written to the file (see [`Self::open`]).
+    pub fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    /// Compression scheme that will be used to write chunks. Zlib is used by default
+    /// as vanilla does.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+
+        self
     }
 
     pub fn filename(region_pos: &RegionPos) -> String {
         format!("r.{}.{}.mca", region_pos.x(), region_pos.z())
     }
 
-    /// Calculate position of location entry in the file.
-    fn location_offset(chunk_pos: ChunkWithinRegionPos) -> u64 {
-        (4 * ((chunk_pos.x() & 31) + (chunk_pos.z() & 31) * 32)) as u64
+    /// Calculate index of location (and timestamp) entry in the header tables.
+    #[inline]
+    fn index(chunk_pos: &ChunkWithinRegionPos) -> usize {
+        ((chunk_pos.x() & 31) + (chunk_pos.z() & 31) * 32) as usize
     }
 
     /// Reads 3 high bytes of location entry. The resulting value will be offset.
-    fn offset_from_location_value(location: u64) -> u64 {
-        let b1 = (location & 0xFF000000) >> 24;
-        let b2 = (location & 0x00FF0000) >> 16;
-        let b3 = (location & 0x0000FF00) >> 8;
-
-        (b3 & 0xFF) | ((b2 & 0xFF) << 8) | ((b1 & 0x0F) << 16)
+    #[inline]
+    fn offset_from_location_value(location: u32) -> usize {
+        (location >> 8) as usize
     }
 
     /// Reads 1 low bytes of location entry. The resulting value will be sectors count.
-    fn sectors_from_location_value(location: u64) -> u64 {
-        location & 0x000000FF
+    #[inline]
+    fn sectors_from_location_value(location: u32) -> usize {
+        (location & 0xFF) as usize
+    }
+
+    #[inline]
+    fn location_value(offset: usize, sectors: usize) -> u32 {
+        ((offset as u32) << 8) | (sectors as u32 & 0xFF)
+    }
+
+    /// Write location and timestamp entries of the chunk to the header tables.
+    fn write_header_entry(&mut self, index: usize) -> anyhow::Result<()> {
+        let file = self.file.get_mut();
+
+        file.seek(SeekFrom::Start((index * 4) as u64))?;
+        (self.locations[index] as i32).write(file)?;
+
+        file.seek(SeekFrom::Start(Self::SECTOR_SIZE_BYTES + (index * 4) as u64))?;
+        (self.timestamps[index] as i32).write(file)?;
+
+        Ok(())
+    }
+
+    fn now() -> u32 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .unwrap_or(0)
+    }
+
+    /// Unix timestamp (in seconds) of the last chunk modification or 0 if the
+    /// chunk has never been written.
+    pub fn timestamp(&self, chunk_pos: ChunkWithinRegionPos) -> u32 {
+        self.timestamps[Self::index(&chunk_pos)]
     }
 
     pub fn pos(&self) -> &RegionPos {
@@ -109,49 +230,312 @@ impl Anvil {
 }
 
 impl RegionFile for Anvil {
-    fn read(&mut self, chunk_pos: ChunkWithinRegionPos) -> Option<nbt::Blob> {
-        // Go to necessary location entry.
-        let new_pos = self.file.seek(SeekFrom::Start(Self::location_offset(chunk_pos))).unwrap();
-
-        if new_pos > self.size {
-            return None
-        }
-
-        // Read location entry.
-        let location = i32::read(&mut self.file).unwrap() as u64;
+    fn read(&mut self, chunk_pos: ChunkWithinRegionPos) -> anyhow::Result<Option<nbt::Blob>> {
+        let location = self.locations[Self::index(&chunk_pos)];
         let offset = Self::offset_from_location_value(location);
         let sectors = Self::sectors_from_location_value(location);
         if offset == 0 && sectors == 0 {
-            return None
+            return Ok(None)
         }
 
         // Go to necessary chunk section.
-        self.file.seek(SeekFrom::Start(Self::SECTOR_SIZE_BYTES * offset)).unwrap();
-        // Skip length.
-        let _ = i32::read(&mut self.file);
-        let compression_type = u8::read(&mut self.file).unwrap();
-        let compression = Compression::from(compression_type);
+        self.file.seek(SeekFrom::Start(Self::SECTOR_SIZE_BYTES * offset as u64))?;
+        // Length includes compression type byte.
+        let length = i32::read(&mut self.file)?;
+        // Length is checked before allocating, so a damaged header can't request
+        // more memory than the chunk sectors hold.
+        if length <= 0 || length as u64 + 4 > sectors as u64 * Self::SECTOR_SIZE_BYTES {
+            return Err(anyhow!(
+                "Chunk ({}, {}) of region ({}, {}) has invalid length {} for {} sectors",
+                chunk_pos.x(),
+                chunk_pos.z(),
+                self.pos.x(),
+                self.pos.z(),
+                length,
+                sectors
+            ))
+        }
+
+        let compression_type = u8::read(&mut self.file)?;
+        let compression = Compression::try_from(compression_type)?;
 
-        // Read NBT with chunk data
-        let buf = compression.decode(&mut self.file);
-        let level = nbt::from_reader(&mut buf.as_slice()).unwrap();
+        let mut compressed = vec![0u8; (length - 1) as usize];
+        self.file.read_exact(&mut compressed)?;
 
-        Some(level)
+        // Read NBT with chunk data. Blob reader keeps exact tag types, unlike
+        // the serde-based one.
+        let buf = compression.decode(&mut compressed.as_slice())?;
+        let level = nbt::Blob::from_reader(&mut buf.as_slice())?;
+
+        Ok(Some(level))
     }
 
-    fn does_chunk_exist(&mut self, chunk_pos: ChunkWithinRegionPos) -> bool {
-        // Go to necessary location entry
-        let new_pos = self.file.seek(SeekFrom::Start(Self::location_offset(chunk_pos))).unwrap();
+    fn write(&mut self, chunk_pos: ChunkWithinRegionPos, nbt: &nbt::Blob) -> anyhow::Result<()> {
+        if !self.writable {
+            return Err(anyhow!("Region ({}, {}) is opened for reading only", self.pos.x(), self.pos.z()))
+        }
+
+        let mut compressed = Vec::new();
+        {
+            // Encoder finishes the stream on drop.
+            let mut encoder = self.compression.wrap_encoder(&mut compressed, flate2::Compression::default());
+            nbt.to_writer(&mut encoder)?;
+            encoder.flush()?;
+        }
 
-        if new_pos > self.size {
-            return false
+        let total = Self::CHUNK_HEADER_SIZE_BYTES + compressed.len();
+        let sectors = total.div_ceil(Self::SECTOR_SIZE_BYTES as usize);
+        if sectors > Self::MAX_CHUNK_SECTORS {
+            return Err(anyhow!(
+                "Chunk ({}, {}) of region ({}, {}) requires {} sectors, but at most {} is allowed",
+                chunk_pos.x(),
+                chunk_pos.z(),
+                self.pos.x(),
+                self.pos.z(),
+                sectors,
+                Self::MAX_CHUNK_SECTORS
+            ))
         }
 
-        // Read location entry.
-        let location = i32::read(&mut self.file).unwrap() as u64;
+        let index = Self::index(&chunk_pos);
+        let previous = self.locations[index];
+
+        // Sectors of the previous chunk version are still reserved, so a crash in
+        // the middle of writing never damages the previous version of the chunk.
+        let offset = self.sectors.allocate(sectors);
+
+        let mut buf = Vec::with_capacity(sectors * Self::SECTOR_SIZE_BYTES as usize);
+        ((compressed.len() + 1) as i32).write(&mut buf)?;
+        self.compression.id().write(&mut buf)?;
+        buf.extend_from_slice(&compressed);
+        // Pad chunk data up to the sector boundary.
+        buf.resize(sectors * Self::SECTOR_SIZE_BYTES as usize, 0);
+
+        let file = self.file.get_mut();
+        file.seek(SeekFrom::Start(offset as u64 * Self::SECTOR_SIZE_BYTES))?;
+        file.write_all(&buf)?;
+
+        self.locations[index] = Self::location_value(offset, sectors);
+        self.timestamps[index] = Self::now();
+        self.write_header_entry(index)?;
+
+        // Now the previous sectors can be reused by the following writes.
+        let previous_sectors = Self::sectors_from_location_value(previous);
+        if previous_sectors != 0 {
+            self.sectors.free(Self::offset_from_location_value(previous), previous_sectors);
+        }
+
+        Ok(())
+    }
+
+    fn does_chunk_exist(&mut self, chunk_pos: ChunkWithinRegionPos) -> bool {
+        let location = self.locations[Self::index(&chunk_pos)];
         let offset = Self::offset_from_location_value(location);
         let sectors = Self::sectors_from_location_value(location);
 
         !(offset == 0 && sectors == 0)
     }
 }
+
+/// Keeps track of used sectors of the region file.
+struct SectorBitmap(Vec<bool>);
+
+impl SectorBitmap {
+    fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Mark the given range of sectors as used.
+    fn force(&mut self, start: usize, len: usize) {
+        if self.0.len() < start + len {
+            self.0.resize(start + len, false);
+        }
+
+        self.0[start..start + len].fill(true);
+    }
+
+    /// Mark the given range of sectors as free.
+    fn free(&mut self, start: usize, len: usize) {
+        let end = (start + len).min(self.0.len());
+        if start < end {
+            self.0[start..end].fill(false);
+        }
+    }
+
+    /// Find the first run of free sectors of the given length, mark it as used and
+    /// return its start. If there is no such run, the file grows.
+    fn allocate(&mut self, len: usize) -> usize {
+        let mut run_start = 0;
+        let mut run_len = 0;
+
+        for (i, used) in self.0.iter().enumerate() {
+            if *used {
+                run_len = 0;
+                run_start = i + 1;
+            } else {
+                run_len += 1;
+                if run_len == len {
+                    break;
+                }
+            }
+        }
+
+        // Either a free run was found, or the run (possibly empty) at the end of
+        // the file is extended.
+        self.force(run_start, len);
+
+        run_start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::region::anvil::{Anvil, SectorBitmap};
+    use crate::region::pos::{ChunkWithinRegionPos, RegionPos};
+    use crate::region::RegionFile;
+    use std::path::PathBuf;
+
+    fn region_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("spherix-anvil-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    fn blob(payload_len: usize) -> nbt::Blob {
+        let mut blob = nbt::Blob::new();
+        blob.insert("xPos", 3i32).unwrap();
+        // Pseudo-random bytes to make the payload incompressible.
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let payload: Vec<i8> = (0..payload_len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+
+                (state >> 56) as i8
+            })
+            .collect();
+        blob.insert("payload", nbt::Value::ByteArray(payload)).unwrap();
+
+        blob
+    }
+
+    #[test]
+    fn allocate() {
+        let mut bitmap = SectorBitmap::new();
+        bitmap.force(0, 2);
+
+        assert_eq!(2, bitmap.allocate(3));
+        assert_eq!(5, bitmap.allocate(1));
+
+        bitmap.free(2, 3);
+        assert_eq!(2, bitmap.allocate(2));
+        assert_eq!(4, bitmap.allocate(1));
+        assert_eq!(6, bitmap.allocate(2));
+    }
+
+    #[test]
+    fn write_and_read() {
+        let dir = region_dir("write-and-read");
+        let mut anvil = Anvil::at(RegionPos::new(0, -1), dir.clone()).unwrap();
+
+        assert!(!anvil.does_chunk_exist(ChunkWithinRegionPos::new(3, 7)));
+
+        let expected = blob(100);
+        anvil.write(ChunkWithinRegionPos::new(3, 7), &expected).unwrap();
+
+        assert!(anvil.does_chunk_exist(ChunkWithinRegionPos::new(3, 7)));
+        assert!(anvil.timestamp(ChunkWithinRegionPos::new(3, 7)) > 0);
+        assert_eq!(Some(expected.clone()), anvil.read(ChunkWithinRegionPos::new(3, 7)).unwrap());
+
+        // Header must be persisted, so a reopened file contains the chunk.
+        drop(anvil);
+        let mut anvil = Anvil::at(RegionPos::new(0, -1), dir.clone()).unwrap();
+        assert_eq!(Some(expected), anvil.read(ChunkWithinRegionPos::new(3, 7)).unwrap());
+        assert_eq!(None, anvil.read(ChunkWithinRegionPos::new(7, 3)).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn open_does_not_create_files() {
+        let dir = region_dir("open-does-not-create-files");
+
+        assert!(Anvil::open(RegionPos::new(2, 3), dir.clone()).unwrap().is_none());
+        assert!(!dir.join(Anvil::filename(&RegionPos::new(2, 3))).exists());
+
+        let expected = blob(100);
+        Anvil::at(RegionPos::new(2, 3), dir.clone())
+            .unwrap()
+            .write(ChunkWithinRegionPos::new(1, 2), &expected)
+            .unwrap();
+
+        let mut anvil = Anvil::open(RegionPos::new(2, 3), dir.clone()).unwrap().unwrap();
+        assert!(!anvil.is_writable());
+        assert_eq!(Some(expected.clone()), anvil.read(ChunkWithinRegionPos::new(1, 2)).unwrap());
+        assert!(anvil.write(ChunkWithinRegionPos::new(1, 2), &expected).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn damaged_chunk_is_error() {
+        let dir = region_dir("damaged-chunk-is-error");
+        let mut anvil = Anvil::at(RegionPos::new(4, 4), dir.clone()).unwrap();
+        anvil.write(ChunkWithinRegionPos::new(0, 0), &blob(100)).unwrap();
+        anvil.write(ChunkWithinRegionPos::new(1, 0), &blob(100)).unwrap();
+        drop(anvil);
+
+        // Chunk at sector 2 claims to be longer than its single sector.
+        let path = dir.join(Anvil::filename(&RegionPos::new(4, 4)));
+        let mut data = std::fs::read(&path).unwrap();
+        data[2 * 4096..2 * 4096 + 4].copy_from_slice(&i32::MAX.to_be_bytes());
+        // Chunk at sector 3 has garbage instead of compressed data.
+        data[3 * 4096 + 5..3 * 4096 + 50].fill(0xAB);
+        std::fs::write(&path, data).unwrap();
+
+        let mut anvil = Anvil::at(RegionPos::new(4, 4), dir.clone()).unwrap();
+        assert!(anvil.read(ChunkWithinRegionPos::new(0, 0)).is_err());
+        assert!(anvil.read(ChunkWithinRegionPos::new(1, 0)).is_err());
+
+        // Damaged chunks can be overwritten.
+        let expected = blob(10);
+        anvil.write(ChunkWithinRegionPos::new(0, 0), &expected).unwrap();
+        assert_eq!(Some(expected), anvil.read(ChunkWithinRegionPos::new(0, 0)).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reuse_sectors() {
+        let dir = region_dir("reuse-sectors");
+        let mut anvil = Anvil::at(RegionPos::new(1, 1), dir.clone()).unwrap();
+
+        // 3 sectors: 2..5
+        anvil.write(ChunkWithinRegionPos::new(0, 0), &blob(10_000)).unwrap();
+        // 1 sector: 5..6
+        anvil.write(ChunkWithinRegionPos::new(1, 0), &blob(10)).unwrap();
+        // Chunk grows and moves to 6..10. Sectors 2..5 are free now.
+        anvil.write(ChunkWithinRegionPos::new(0, 0), &blob(14_000)).unwrap();
+        // Chunk shrinks and takes the first free sector.
+        let shrunk = blob(20);
+        anvil.write(ChunkWithinRegionPos::new(0, 0), &shrunk).unwrap();
+
+        assert_eq!(2, Anvil::offset_from_location_value(anvil.locations[0]));
+        assert_eq!(1, Anvil::sectors_from_location_value(anvil.locations[0]));
+
+        // A new chunk fits into the hole which was left by the grown chunk.
+        let other = blob(5_000);
+        anvil.write(ChunkWithinRegionPos::new(2, 0), &other).unwrap();
+        assert_eq!(3, Anvil::offset_from_location_value(anvil.locations[2]));
+
+        let len = std::fs::metadata(dir.join(Anvil::filename(&RegionPos::new(1, 1)))).unwrap().len();
+        assert_eq!(10 * 4096, len);
+
+        assert_eq!(Some(shrunk), anvil.read(ChunkWithinRegionPos::new(0, 0)).unwrap());
+        assert_eq!(Some(other), anvil.read(ChunkWithinRegionPos::new(2, 0)).unwrap());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,7 +4,13 @@ pub mod pos;
 pub mod anvil;
 
 pub trait RegionFile {
-    fn read(&mut self, chunk_pos: ChunkWithinRegionPos) -> Option<nbt::Blob>;
+    /// Read the chunk or [`None`] if it has never been written. Damaged chunk
+    /// data results in an error.
+    fn read(&mut self, chunk_pos: ChunkWithinRegionPos) -> anyhow::Result<Option<nbt::Blob>>;
+
+    /// Write (or overwrite) the chunk. Location and timestamp of the chunk are
+    /// updated as well.
+    fn write(&mut self, chunk_pos: ChunkWithinRegionPos, nbt: &nbt::Blob) -> anyhow::Result<()>;
 
     fn does_chunk_exist(&mut self, chunk_pos: ChunkWithinRegionPos) -> bool;
 }
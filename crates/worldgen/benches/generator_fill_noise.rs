@@ -6,9 +6,11 @@ use crate::shared::{create_biome_global_palette, create_biome_index, create_bloc
 use criterion::{criterion_group, criterion_main, Criterion};
 use spherix_world::chunk::column::ChunkColumn;
 use spherix_world::chunk::pos::ChunkPos;
+use spherix_worldgen::biome::source::BiomeSource;
 use spherix_worldgen::chunk::column::ChunkColumn as WorldgenColumnColumn;
-use spherix_worldgen::chunk::generator::NoiseBasedChunkGenerator;
-use spherix_worldgen::rng::{RngForkable, XoroShiro};
+use spherix_worldgen::chunk::generator::{ChunkGenerator, NoiseBasedChunkGenerator};
+use spherix_worldgen::noise::density::beardifier::Beardifier;
+use spherix_worldgen::rng::{RngForkable, WorldgenRng};
 use std::sync::Arc;
 
 pub fn benchmark(c: &mut Criterion) {
@@ -26,20 +28,21 @@ pub fn benchmark(c: &mut Criterion) {
 
     let mut worldgen_chunk = WorldgenColumnColumn::new(chunk);
 
-    let mut rng = XoroShiro::new(200);
+    let mut rng = WorldgenRng::new(200, noise_settings.use_legacy_random_source);
     let forked = Arc::new(rng.fork_pos());
 
     let (gen, noise_settings) = NoiseBasedChunkGenerator::new(
         noise_settings,
         block_palette,
         biome_palette,
-        biome_index,
+        BiomeSource::MultiNoise(biome_index),
+        200,
         forked.clone()
     );
 
     c.bench_function("generator_fill_noise", |b| {
         b.iter(|| {
-            gen.do_fill_noise(&noise_settings, &mut worldgen_chunk, -8, 48);
+            gen.fill_noise(&noise_settings, Beardifier::default(), &mut worldgen_chunk);
         })
     });
 }
@@ -2,7 +2,7 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use spherix_math::vector::{Vector2f, Vector3f};
 use spherix_worldgen::noise::perlin::octave::MultiOctaveNoiseFactory;
 use spherix_worldgen::noise::perlin::simplex::MultiOctaveNoise as SimplexMultiOctaveNoise;
-use spherix_worldgen::noise::perlin::{GridNoise, MultiOctaveNoise, Noise, SimplexNoise};
+use spherix_worldgen::noise::perlin::{BatchNoise, DefaultNoise, GridNoise, MultiOctaveNoise, Noise, SimplexNoise};
 use spherix_worldgen::rng::XoroShiro;
 use std::hint::black_box;
 
@@ -84,10 +84,37 @@ pub fn multi_octave(c: &mut Criterion) {
     });
 }
 
+/// A column of cell corners, sampled one position at a time and as a batch.
+pub fn batched(c: &mut Criterion) {
+    let noise = DefaultNoise::create(
+        &mut XoroShiro::new(0xAA78B4),
+        &vec![1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.013333333333333334],
+        -8
+    );
+    let column = (0..49).map(vector).collect::<Vec<_>>();
+
+    c.bench_function("double_noise_column", |b| {
+        b.iter(|| {
+            for at in &column {
+                black_box(Noise::sample(&noise, *at));
+            }
+        })
+    });
+
+    c.bench_function("double_noise_column_batch", |b| {
+        let mut out = vec![0.0; column.len()];
+
+        b.iter(|| {
+            noise.sample_batch(&column, 0.0, 0.0, &mut out);
+            black_box(&out);
+        })
+    });
+}
+
 #[inline]
 fn vector(i: i32) -> Vector3f {
     Vector3f::new(49.5, i as f64, -102.18)
 }
 
-criterion_group!(benches, single_octave, multi_octave);
+criterion_group!(benches, single_octave, multi_octave, batched);
 criterion_main!(benches);
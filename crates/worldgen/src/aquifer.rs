@@ -1,11 +1,18 @@
-use crate::noise::density::density::DensityFunctionContext;
+use crate::noise::density::cache::{quart_pos_from_block, quart_pos_to_block};
+use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions};
+use crate::noise::math::{clamped_map, floor, floor_div, map};
+use crate::noise::router::NoiseRouter;
 use crate::noise::settings::NoiseSettings;
+use crate::rng::{Rng, RngPos, WorldgenRngPos};
 use spherix_math::vector::Vector3;
 use spherix_world::block::block::Block;
 use spherix_world::block::state::BlockState;
 use spherix_world::chunk::palette::BlockGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct FluidStatus {
     level: i32,
     ty: Arc<BlockState>,
@@ -58,7 +65,7 @@ impl FluidPicker {
 }
 
 pub trait Aquifer {
-    fn compute(&self, ctx: &DensityFunctionContext, noise_value: f64) -> Option<Arc<BlockState>>;
+    fn compute(&mut self, ctx: &mut DensityFunctionContext, noise_value: f64) -> Option<Arc<BlockState>>;
 }
 
 pub struct DisabledAquifer {
@@ -75,7 +82,7 @@ impl DisabledAquifer {
 }
 
 impl Aquifer for DisabledAquifer {
-    fn compute(&self, ctx: &DensityFunctionContext, noise_value: f64) -> Option<Arc<BlockState>> {
+    fn compute(&mut self, ctx: &mut DensityFunctionContext, noise_value: f64) -> Option<Arc<BlockState>> {
         if noise_value > 0.0 {
             None
         } else {
@@ -83,3 +90,636 @@ impl Aquifer for DisabledAquifer {
         }
     }
 }
+
+/// Aquifer which fills underground cavities with local water and lava lakes.
+///
+/// The world is split into a grid of cells (16x12x16 blocks). Each cell has an
+/// aquifer center at a random position within the cell, and each aquifer has its
+/// own fluid level and type. A block takes the fluid of the nearest aquifer, and
+/// a barrier is placed between aquifers with different fluid levels, so they
+/// don't mix.
+pub struct NoiseBasedAquifer {
+    picker: FluidPicker,
+    barrier: DensityFunctions,
+    fluid_level_floodedness: DensityFunctions,
+    fluid_level_spread: DensityFunctions,
+    lava: DensityFunctions,
+    erosion: DensityFunctions,
+    depth: DensityFunctions,
+    initial_density_without_jaggedness: DensityFunctions,
+    rng: Arc<WorldgenRngPos>,
+    /// Context for sampling density functions at arbitrary points, outside of
+    /// the cell, which is being filled by the noise chunk.
+    point_ctx: DensityFunctionContext,
+    air: Arc<BlockState>,
+    lava_state: Arc<BlockState>,
+    noise_min_y: i32,
+    noise_height: i32,
+    cell_height: i32,
+    min_grid_x: i32,
+    min_grid_y: i32,
+    min_grid_z: i32,
+    grid_size_x: i32,
+    grid_size_z: i32,
+    aquifer_cache: Vec<Option<FluidStatus>>,
+    location_cache: Vec<Option<Vector3>>,
+    preliminary_surface_levels: HashMap<(i32, i32), i32>,
+}
+
+impl NoiseBasedAquifer {
+    const X_RANGE: u32 = 10;
+    const Y_RANGE: u32 = 9;
+    const Z_RANGE: u32 = 10;
+    const X_SPACING: i32 = 16;
+    const Y_SPACING: i32 = 12;
+    const Z_SPACING: i32 = 16;
+    /// Fluid level of aquifers, which are completely dry.
+    const WAY_BELOW_MIN_Y: i32 = -2032 << 4;
+    const SURFACE_SAMPLING_OFFSETS_IN_CHUNKS: [(i32, i32); 13] = [
+        (0, 0), (-2, -1), (-1, -1), (0, -1), (1, -1), (-3, 0), (-2, 0),
+        (-1, 0), (1, 0), (-2, 1), (-1, 1), (0, 1), (1, 1)
+    ];
+    /// Thresholds of the deep dark region, which never contains aquifers.
+    const EROSION_DEEP_DARK_DRYNESS_THRESHOLD: f64 = -0.225f32 as f64;
+    const DEPTH_DEEP_DARK_DRYNESS_THRESHOLD: f64 = 0.9f32 as f64;
+
+    pub fn new(
+        picker: FluidPicker,
+        router: &NoiseRouter,
+        rng: Arc<WorldgenRngPos>,
+        chunk_pos: ChunkPos,
+        noise_settings: &NoiseSettings,
+    ) -> Self {
+        let min_block_x = chunk_pos.get_min_block_x();
+        let min_block_z = chunk_pos.get_min_block_z();
+        let noise_min_y = noise_settings.noise_min_y;
+        let noise_height = noise_settings.noise_height as i32;
+
+        let min_grid_x = Self::grid_x(min_block_x) - 1;
+        let grid_size_x = Self::grid_x(min_block_x + 15) + 1 - min_grid_x + 1;
+        let min_grid_y = Self::grid_y(noise_min_y) - 1;
+        let grid_size_y = Self::grid_y(noise_min_y + noise_height) + 1 - min_grid_y + 1;
+        let min_grid_z = Self::grid_z(min_block_z) - 1;
+        let grid_size_z = Self::grid_z(min_block_z + 15) + 1 - min_grid_z + 1;
+
+        let cache_size = (grid_size_x * grid_size_y * grid_size_z) as usize;
+
+        let point_ctx = DensityFunctionContext {
+            // Makes cache markers pass the sampling through, as vanilla does for
+            // contexts other than the noise chunk.
+            filler: ContextFiller::Slice,
+            first_noise_x: quart_pos_from_block(min_block_x),
+            first_noise_z: quart_pos_from_block(min_block_z),
+            ..Default::default()
+        };
+
+        let air = picker.palette.get_default_obj_by_index(&Block::AIR).unwrap();
+        let lava_state = picker.palette.get_default_obj_by_index(&Block::LAVA).unwrap();
+
+        Self {
+            picker,
+            barrier: router.barrier.clone(),
+            fluid_level_floodedness: router.fluid_level_floodedness.clone(),
+            fluid_level_spread: router.fluid_level_spread.clone(),
+            lava: router.lava.clone(),
+            erosion: router.erosion.clone(),
+            depth: router.depth.clone(),
+            initial_density_without_jaggedness: router.initial_density_without_jaggedness.clone(),
+            rng,
+            point_ctx,
+            air,
+            lava_state,
+            noise_min_y,
+            noise_height,
+            cell_height: noise_settings.cell_height() as i32,
+            min_grid_x,
+            min_grid_y,
+            min_grid_z,
+            grid_size_x,
+            grid_size_z,
+            aquifer_cache: vec![None; cache_size],
+            location_cache: vec![None; cache_size],
+            preliminary_surface_levels: HashMap::new(),
+        }
+    }
+
+    #[inline]
+    fn grid_x(x: i32) -> i32 {
+        floor_div(x, Self::X_SPACING)
+    }
+
+    #[inline]
+    fn grid_y(y: i32) -> i32 {
+        floor_div(y, Self::Y_SPACING)
+    }
+
+    #[inline]
+    fn grid_z(z: i32) -> i32 {
+        floor_div(z, Self::Z_SPACING)
+    }
+
+    #[inline]
+    fn index(&self, x: i32, y: i32, z: i32) -> usize {
+        let i = x - self.min_grid_x;
+        let j = y - self.min_grid_y;
+        let k = z - self.min_grid_z;
+
+        ((j * self.grid_size_z + k) * self.grid_size_x + i) as usize
+    }
+
+    /// Closer the distances to two aquifer centers, the higher the similarity.
+    #[inline]
+    fn similarity(first_distance: i32, second_distance: i32) -> f64 {
+        1.0 - (second_distance - first_distance).abs() as f64 / 25.0
+    }
+
+    #[inline]
+    fn is(state: &BlockState, block: &Block) -> bool {
+        state.block() == block
+    }
+
+    fn sample_at(&mut self, df: &DensityFunctions, at: Vector3) -> f64 {
+        df.sample(at, &mut self.point_ctx)
+    }
+
+    /// Center of the aquifer of the given grid cell.
+    fn location(&mut self, x: i32, y: i32, z: i32) -> Vector3 {
+        let index = self.index(x, y, z);
+        if let Some(location) = self.location_cache[index] {
+            return location
+        }
+
+        let mut rng = self.rng.at(Vector3::new(x, y, z));
+        let location = Vector3::new(
+            x * Self::X_SPACING + rng.next_u32(Self::X_RANGE) as i32,
+            y * Self::Y_SPACING + rng.next_u32(Self::Y_RANGE) as i32,
+            z * Self::Z_SPACING + rng.next_u32(Self::Z_RANGE) as i32,
+        );
+        self.location_cache[index] = Some(location);
+
+        location
+    }
+
+    fn aquifer_status(&mut self, location: Vector3) -> FluidStatus {
+        let index = self.index(
+            Self::grid_x(location.x),
+            Self::grid_y(location.y),
+            Self::grid_z(location.z)
+        );
+
+        if let Some(status) = &self.aquifer_cache[index] {
+            return status.clone()
+        }
+
+        let status = self.compute_fluid(location);
+        self.aquifer_cache[index] = Some(status.clone());
+
+        status
+    }
+
+    /// Highest Y (aligned to the cell height), where the terrain is definitely
+    /// solid, or [`i32::MAX`] if there is no such Y.
+    fn preliminary_surface_level(&mut self, x: i32, z: i32) -> i32 {
+        let x = quart_pos_to_block(quart_pos_from_block(x));
+        let z = quart_pos_to_block(quart_pos_from_block(z));
+
+        if let Some(level) = self.preliminary_surface_levels.get(&(x, z)) {
+            return *level
+        }
+
+        let mut level = i32::MAX;
+        let mut y = self.noise_min_y + self.noise_height;
+        while y >= self.noise_min_y {
+            let df = self.initial_density_without_jaggedness.clone();
+            if self.sample_at(&df, Vector3::new(x, y, z)) > 0.390625 {
+                level = y;
+                break;
+            }
+
+            y -= self.cell_height;
+        }
+
+        self.preliminary_surface_levels.insert((x, z), level);
+
+        level
+    }
+
+    fn compute_fluid(&mut self, at: Vector3) -> FluidStatus {
+        let global_status = self.picker.pick(&at).clone();
+        let mut min_surface = i32::MAX;
+        let top_y = at.y + 12;
+        let bottom_y = at.y - 12;
+        let mut near_surface_fluid = false;
+
+        for (offset_x, offset_z) in Self::SURFACE_SAMPLING_OFFSETS_IN_CHUNKS {
+            let x = at.x + (offset_x << 4);
+            let z = at.z + (offset_z << 4);
+            let surface = self.preliminary_surface_level(x, z);
+            let fluid_y = surface + 8;
+            let center = offset_x == 0 && offset_z == 0;
+
+            if center && bottom_y > fluid_y {
+                return global_status
+            }
+
+            let above_surface = top_y > fluid_y;
+            if above_surface || center {
+                let status = self.picker.pick(&Vector3::new(x, fluid_y, z));
+                if !Self::is(&status.at(fluid_y), Block::AIR) {
+                    if center {
+                        near_surface_fluid = true;
+                    }
+
+                    if above_surface {
+                        return status.clone()
+                    }
+                }
+            }
+
+            min_surface = min_surface.min(surface);
+        }
+
+        let level = self.compute_surface_level(at, &global_status, min_surface, near_surface_fluid);
+
+        FluidStatus {
+            level,
+            ty: self.compute_fluid_type(at, &global_status, level),
+            default: self.air.clone(),
+        }
+    }
+
+    fn compute_surface_level(&mut self, at: Vector3, status: &FluidStatus, min_surface: i32, near_surface_fluid: bool) -> i32 {
+        let (flooded, spread) = if self.is_deep_dark_region(at) {
+            (-1.0, -1.0)
+        } else {
+            let distance_below_surface = min_surface + 8 - at.y;
+            let surface_proximity = if near_surface_fluid {
+                clamped_map(distance_below_surface as f64, 0.0, 64.0, 1.0, 0.0)
+            } else {
+                0.0
+            };
+
+            let df = self.fluid_level_floodedness.clone();
+            let floodedness = self.sample_at(&df, at).clamp(-1.0, 1.0);
+            let flooded_threshold = map(surface_proximity, 1.0, 0.0, -0.3, 0.8);
+            let spread_threshold = map(surface_proximity, 1.0, 0.0, -0.8, 0.4);
+
+            (floodedness - flooded_threshold, floodedness - spread_threshold)
+        };
+
+        if flooded > 0.0 {
+            status.level
+        } else if spread > 0.0 {
+            self.compute_randomized_fluid_surface_level(at, min_surface)
+        } else {
+            Self::WAY_BELOW_MIN_Y
+        }
+    }
+
+    fn is_deep_dark_region(&mut self, at: Vector3) -> bool {
+        let erosion = self.erosion.clone();
+        let depth = self.depth.clone();
+
+        self.sample_at(&erosion, at) < Self::EROSION_DEEP_DARK_DRYNESS_THRESHOLD
+            && self.sample_at(&depth, at) > Self::DEPTH_DEEP_DARK_DRYNESS_THRESHOLD
+    }
+
+    fn compute_randomized_fluid_surface_level(&mut self, at: Vector3, min_surface: i32) -> i32 {
+        let x = floor_div(at.x, 16);
+        let y = floor_div(at.y, 40);
+        let z = floor_div(at.z, 16);
+        let middle_y = y * 40 + 20;
+
+        let df = self.fluid_level_spread.clone();
+        let spread = self.sample_at(&df, Vector3::new(x, y, z)) * 10.0;
+        let quantized = floor(spread / 3.0) * 3;
+
+        min_surface.min(middle_y + quantized)
+    }
+
+    fn compute_fluid_type(&mut self, at: Vector3, status: &FluidStatus, level: i32) -> Arc<BlockState> {
+        if level <= -10 && level != Self::WAY_BELOW_MIN_Y && !Self::is(&status.ty, Block::LAVA) {
+            let x = floor_div(at.x, 64);
+            let y = floor_div(at.y, 40);
+            let z = floor_div(at.z, 64);
+
+            let df = self.lava.clone();
+            if self.sample_at(&df, Vector3::new(x, y, z)).abs() > 0.3 {
+                return self.lava_state.clone()
+            }
+        }
+
+        status.ty.clone()
+    }
+
+    fn calculate_pressure(
+        &self,
+        ctx: &mut DensityFunctionContext,
+        barrier: &mut Option<f64>,
+        first: &FluidStatus,
+        second: &FluidStatus
+    ) -> f64 {
+        let y = ctx.pos().y;
+        let first_state = first.at(y);
+        let second_state = second.at(y);
+
+        if (Self::is(&first_state, Block::LAVA) && Self::is(&second_state, Block::WATER))
+            || (Self::is(&first_state, Block::WATER) && Self::is(&second_state, Block::LAVA)) {
+            return 2.0
+        }
+
+        let level_diff = (first.level - second.level).abs();
+        if level_diff == 0 {
+            return 0.0
+        }
+
+        let middle_level = 0.5 * (first.level + second.level) as f64;
+        let offset_from_middle = y as f64 + 0.5 - middle_level;
+        let half_diff = level_diff as f64 / 2.0;
+        let distance = half_diff - offset_from_middle.abs();
+
+        let pressure = if offset_from_middle > 0.0 {
+            if distance > 0.0 {
+                distance / 1.5
+            } else {
+                distance / 2.5
+            }
+        } else {
+            let distance = 3.0 + distance;
+            if distance > 0.0 {
+                distance / 3.0
+            } else {
+                distance / 10.0
+            }
+        };
+
+        let barrier_value = if (-2.0..=2.0).contains(&pressure) {
+            *barrier.get_or_insert_with(|| self.barrier.sample(ctx.pos(), ctx))
+        } else {
+            0.0
+        };
+
+        2.0 * (barrier_value + pressure)
+    }
+}
+
+impl Aquifer for NoiseBasedAquifer {
+    fn compute(&mut self, ctx: &mut DensityFunctionContext, noise_value: f64) -> Option<Arc<BlockState>> {
+        if noise_value > 0.0 {
+            return None
+        }
+
+        let pos = ctx.pos();
+
+        if Self::is(&self.picker.pick(&pos).at(pos.y), Block::LAVA) {
+            return Some(self.lava_state.clone())
+        }
+
+        let grid_x = floor_div(pos.x - 5, Self::X_SPACING);
+        let grid_y = floor_div(pos.y + 1, Self::Y_SPACING);
+        let grid_z = floor_div(pos.z - 5, Self::Z_SPACING);
+
+        // Three nearest aquifer centers and squared distances to them.
+        let mut first_distance = i32::MAX;
+        let mut second_distance = i32::MAX;
+        let mut third_distance = i32::MAX;
+        let mut first = Vector3::new(0, 0, 0);
+        let mut second = Vector3::new(0, 0, 0);
+        let mut third = Vector3::new(0, 0, 0);
+
+        for offset_x in 0..=1 {
+            for offset_y in -1..=1 {
+                for offset_z in 0..=1 {
+                    let location = self.location(grid_x + offset_x, grid_y + offset_y, grid_z + offset_z);
+
+                    let dx = location.x - pos.x;
+                    let dy = location.y - pos.y;
+                    let dz = location.z - pos.z;
+                    let distance = dx * dx + dy * dy + dz * dz;
+
+                    if first_distance >= distance {
+                        third = second;
+                        second = first;
+                        first = location;
+                        third_distance = second_distance;
+                        second_distance = first_distance;
+                        first_distance = distance;
+                    } else if second_distance >= distance {
+                        third = second;
+                        second = location;
+                        third_distance = second_distance;
+                        second_distance = distance;
+                    } else if third_distance >= distance {
+                        third = location;
+                        third_distance = distance;
+                    }
+                }
+            }
+        }
+
+        let first_status = self.aquifer_status(first);
+        let first_second_similarity = Self::similarity(first_distance, second_distance);
+        let state = first_status.at(pos.y);
+
+        if first_second_similarity <= 0.0 {
+            return Some(state)
+        }
+
+        let below = Vector3::new(pos.x, pos.y - 1, pos.z);
+        if Self::is(&state, Block::WATER) && Self::is(&self.picker.pick(&below).at(below.y), Block::LAVA) {
+            return Some(state)
+        }
+
+        let mut barrier = None;
+        let second_status = self.aquifer_status(second);
+        let pressure = first_second_similarity * self.calculate_pressure(ctx, &mut barrier, &first_status, &second_status);
+        if noise_value + pressure > 0.0 {
+            return None
+        }
+
+        let third_status = self.aquifer_status(third);
+
+        let first_third_similarity = Self::similarity(first_distance, third_distance);
+        if first_third_similarity > 0.0 {
+            let pressure = first_second_similarity
+                * first_third_similarity
+                * self.calculate_pressure(ctx, &mut barrier, &first_status, &third_status);
+
+            if noise_value + pressure > 0.0 {
+                return None
+            }
+        }
+
+        let second_third_similarity = Self::similarity(second_distance, third_distance);
+        if second_third_similarity > 0.0 {
+            let pressure = first_second_similarity
+                * second_third_similarity
+                * self.calculate_pressure(ctx, &mut barrier, &second_status, &third_status);
+
+            if noise_value + pressure > 0.0 {
+                return None
+            }
+        }
+
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aquifer::{Aquifer, FluidPicker, FluidStatus, NoiseBasedAquifer};
+    use crate::noise::density::density::{DensityFunctionContext, DensityFunctions};
+    use crate::noise::density::misc::Const;
+    use crate::noise::router::NoiseRouter;
+    use crate::noise::settings::NoiseSettings;
+    use crate::rng::{RngForkable, WorldgenRng};
+    use spherix_world::block::block::Block;
+    use spherix_world::block::state::BlockState;
+    use spherix_world::block::variant::VariantVec;
+    use spherix_world::chunk::palette::global::GlobalId;
+    use spherix_world::chunk::palette::BlockGlobalPalette;
+    use spherix_world::chunk::pos::ChunkPos;
+    use std::sync::Arc;
+
+    fn palette() -> Arc<BlockGlobalPalette> {
+        let mut palette = BlockGlobalPalette::new(4);
+        for (i, block) in [Block::AIR, Block::STONE, Block::WATER, Block::LAVA].into_iter().enumerate() {
+            palette.insert(GlobalId(i as u16), BlockState::new(block, true, VariantVec::empty()));
+        }
+
+        Arc::new(palette)
+    }
+
+    fn constant(value: f64) -> DensityFunctions {
+        DensityFunctions::Const(Const::new(value))
+    }
+
+    /// Overworld-like settings, where the terrain is solid up to the top of
+    /// the world, so every aquifer is far below the surface.
+    fn settings(palette: &Arc<BlockGlobalPalette>, floodedness: f64) -> NoiseSettings {
+        NoiseSettings {
+            router: NoiseRouter {
+                barrier: constant(0.25),
+                fluid_level_floodedness: constant(floodedness),
+                fluid_level_spread: constant(0.0),
+                lava: constant(0.0),
+                temperature: constant(0.0),
+                vegetation: constant(0.0),
+                continents: constant(0.0),
+                erosion: constant(0.0),
+                depth: constant(0.0),
+                ridges: constant(0.0),
+                initial_density_without_jaggedness: constant(1.0),
+                final_density: constant(1.0),
+                vein_toggle: constant(0.0),
+                vein_ridged: constant(0.0),
+                vein_gap: constant(0.0),
+            },
+            use_legacy_random_source: false,
+            default_block: palette.get_default_obj_by_index(&Block::STONE).unwrap(),
+            default_fluid: palette.get_default_obj_by_index(&Block::WATER).unwrap(),
+            sea_level: 63,
+            aquifers_enabled: true,
+            ore_veins_enabled: false,
+            noise_height: 384,
+            noise_min_y: -64,
+            noise_size_horizontal: 1,
+            noise_size_vertical: 2,
+        }
+    }
+
+    fn aquifer(floodedness: f64) -> NoiseBasedAquifer {
+        let palette = palette();
+        let settings = settings(&palette, floodedness);
+
+        NoiseBasedAquifer::new(
+            FluidPicker::create(&settings, &palette),
+            &settings.router,
+            Arc::new(WorldgenRng::new(42, false).fork_pos()),
+            ChunkPos::new(3, -2),
+            &settings
+        )
+    }
+
+    /// Block of the cavity at the given position.
+    fn cavity(aquifer: &mut NoiseBasedAquifer, x: i32, y: i32, z: i32) -> Option<&'static Block> {
+        let mut ctx = DensityFunctionContext {
+            cell_start_block_x: x,
+            cell_start_block_y: y,
+            cell_start_block_z: z,
+            ..Default::default()
+        };
+
+        aquifer.compute(&mut ctx, -1.0).map(|state| state.block())
+    }
+
+    #[test]
+    fn flooded_aquifers_take_the_sea_level() {
+        let mut aquifer = aquifer(1.0);
+
+        for (x, z) in [(48, -32), (55, -21), (63, -17)] {
+            // Aquifers centered below the lava floor take it as their level, so
+            // the cavities stay clear of them.
+            for y in -30..63 {
+                assert_eq!(Some(Block::WATER), cavity(&mut aquifer, x, y, z), "{} {} {}", x, y, z);
+            }
+
+            for y in 63..100 {
+                assert_eq!(Some(Block::AIR), cavity(&mut aquifer, x, y, z), "{} {} {}", x, y, z);
+            }
+
+            // The lava floor is the same for all the aquifers.
+            assert_eq!(Some(Block::LAVA), cavity(&mut aquifer, x, -60, z));
+        }
+
+        let mut ctx = DensityFunctionContext::default();
+        assert!(aquifer.compute(&mut ctx, 0.5).is_none());
+    }
+
+    #[test]
+    fn dry_aquifers_are_empty() {
+        let mut aquifer = aquifer(-1.0);
+
+        for y in [0, 40, 62, 90] {
+            assert_eq!(Some(Block::AIR), cavity(&mut aquifer, 50, y, -30));
+        }
+
+        assert_eq!(Some(Block::LAVA), cavity(&mut aquifer, 50, -55, -30));
+    }
+
+    #[test]
+    fn pressure_between_aquifers() {
+        let aquifer = aquifer(1.0);
+        let water = aquifer.picker.default_fluid.ty.clone();
+
+        let status = |level, ty: &Arc<BlockState>| FluidStatus {
+            level,
+            ty: ty.clone(),
+            default: aquifer.air.clone(),
+        };
+        let pressure = |y, first: &FluidStatus, second: &FluidStatus, barrier: &mut Option<f64>| {
+            let mut ctx = DensityFunctionContext {
+                cell_start_block_y: y,
+                ..Default::default()
+            };
+
+            aquifer.calculate_pressure(&mut ctx, barrier, first, second)
+        };
+
+        let high = status(10, &water);
+        let low = status(0, &water);
+        let lava = status(0, &aquifer.lava_state);
+
+        // Values of vanilla NoiseBasedAquifer.calculatePressure().
+        let mut barrier = None;
+        assert_eq!(6.0, pressure(5, &high, &low, &mut barrier));
+        assert_eq!(-8.4, pressure(20, &high, &low, &mut barrier));
+        assert_eq!(0.0, pressure(5, &high, &high, &mut barrier));
+        assert_eq!(2.0, pressure(-5, &high, &lava, &mut barrier));
+        // Barrier noise is sampled only near the border of the aquifers.
+        assert_eq!(None, barrier);
+
+        assert!((pressure(-3, &high, &low, &mut barrier) - 2.0 * (0.25 + 0.5 / 3.0)).abs() < 1e-9);
+        assert_eq!(Some(0.25), barrier);
+    }
+}
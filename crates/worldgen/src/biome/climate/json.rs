@@ -129,3 +129,18 @@ pub fn create_biome_index_from_json(s: String) -> anyhow::Result<BiomeIndex> {
 
     Ok(rtree)
 }
+
+/// Distinct biomes of the parameter list in the order of their first occurrence.
+/// Vanilla enumerates possible biomes of the source in this order.
+pub fn possible_biomes_from_json(s: &str) -> anyhow::Result<Vec<String>> {
+    let biomes: Biomes = serde_json::from_str(s)?;
+
+    let mut possible = Vec::new();
+    for biome in biomes.biomes {
+        if !possible.contains(&biome.biome) {
+            possible.push(biome.biome);
+        }
+    }
+
+    Ok(possible)
+}
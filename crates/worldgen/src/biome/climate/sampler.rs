@@ -3,6 +3,7 @@ use crate::noise::density::cache::quart_pos_to_block;
 use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions};
 use spherix_math::vector::Vector3;
 
+#[derive(Clone)]
 pub struct ClimateSampler {
     temperature: DensityFunctions,
     humidity: DensityFunctions,
@@ -51,6 +52,14 @@ impl ClimateSampler {
         }
     }
     
+    /// Erosion at the block position, which the End biomes are picked by.
+    pub fn erosion(&self, pos: Vector3) -> f64 {
+        let mut ctx = DensityFunctionContext::default();
+        ctx.filler = ContextFiller::Slice;
+
+        self.erosion.sample(pos, &mut ctx)
+    }
+
     #[inline]
     fn f64_to_i64(val: f64) -> i64 {
         (val * 10000.0) as i64
@@ -11,12 +11,12 @@ use std::sync::Arc;
 /// natural transitions between different biomes.
 pub struct BiomeGradient<'a> {
     seed: i64,
-    biome_accessor: &'a BiomeAccessor
+    biome_accessor: &'a BiomeAccessor<'a>
 }
 
 impl<'a> BiomeGradient<'a> {
     #[inline]
-    pub fn new(seed: i64, biome_accessor: &'a BiomeAccessor) -> Self {
+    pub fn new(seed: i64, biome_accessor: &'a BiomeAccessor<'a>) -> Self {
         Self {
             seed,
             biome_accessor
@@ -24,7 +24,7 @@ impl<'a> BiomeGradient<'a> {
     }
 
     #[inline]
-    pub fn with_hashed_seed(seed: i64, biome_accessor: &'a BiomeAccessor) -> Self {
+    pub fn with_hashed_seed(seed: i64, biome_accessor: &'a BiomeAccessor<'a>) -> Self {
         Self::new(Self::hash_seed(seed), biome_accessor)
     }
 
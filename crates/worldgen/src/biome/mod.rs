@@ -4,3 +4,4 @@ pub mod noise;
 pub mod temperature;
 pub mod gradient;
 pub mod accessor;
+pub mod source;
@@ -1,29 +1,46 @@
-use crate::biome::climate::json::BiomeIndex;
 use crate::biome::climate::sampler::ClimateSampler;
+use crate::biome::source::BiomeSource;
+use crate::noise::density::cache::quart_pos_to_block;
 use spherix_math::vector::Vector3;
 use spherix_world::chunk::biome::Biome;
 use spherix_world::chunk::palette::BiomeGlobalPalette;
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct BiomeSampler {
     palette: Arc<BiomeGlobalPalette>,
-    index: Arc<BiomeIndex>,
+    source: BiomeSource,
     climate: ClimateSampler
 }
 
 impl BiomeSampler {
-    pub fn new(palette: Arc<BiomeGlobalPalette>, index: Arc<BiomeIndex>, climate: ClimateSampler) -> Self {
+    pub fn new(palette: Arc<BiomeGlobalPalette>, source: BiomeSource, climate: ClimateSampler) -> Self {
         Self {
             palette,
-            index,
+            source,
             climate,
         }
     }
 
     pub fn sample(&self, pos: &Vector3) -> Arc<Biome> {
-        let point = self.climate.sample(pos);
-        let biome = self.index.nearest_neighbor(&point).unwrap();
+        match &self.source {
+            BiomeSource::MultiNoise(index) => {
+                let point = self.climate.sample(pos);
+                let biome = index.nearest_neighbor(&point).unwrap();
 
-        self.palette.get_default_obj_by_index(&biome.data).unwrap()
+                self.palette.get_default_obj_by_index(&biome.data).unwrap()
+            }
+            BiomeSource::TheEnd => {
+                let biome = BiomeSource::end_biome(
+                    quart_pos_to_block(pos.x),
+                    quart_pos_to_block(pos.y),
+                    quart_pos_to_block(pos.z),
+                    |x, y, z| self.climate.erosion(Vector3::new(x, y, z)),
+                );
+
+                self.palette.get_default_obj_by_index(&biome.to_owned()).unwrap()
+            }
+            BiomeSource::Fixed(biome) => self.palette.get_default_obj_by_index(biome).unwrap(),
+        }
     }
 }
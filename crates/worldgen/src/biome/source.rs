@@ -0,0 +1,69 @@
+use crate::biome::climate::json::BiomeIndex;
+use std::sync::Arc;
+
+/// Biomes of the End in the order vanilla `TheEndBiomeSource` lists them.
+pub const END_BIOMES: [&str; 5] = [
+    "minecraft:the_end",
+    "minecraft:end_highlands",
+    "minecraft:end_midlands",
+    "minecraft:small_end_islands",
+    "minecraft:end_barrens",
+];
+
+/// Where the biomes of a dimension come from.
+#[derive(Clone)]
+pub enum BiomeSource {
+    /// Biome nearest to the climate point, as in the overworld and the nether.
+    MultiNoise(Arc<BiomeIndex>),
+    /// Equivalent of vanilla `TheEndBiomeSource`. The biome depends on the
+    /// distance to the origin and the erosion, which is the island falloff.
+    TheEnd,
+    /// Single biome everywhere, as in superflat and void worlds.
+    Fixed(String),
+}
+
+impl BiomeSource {
+    /// Equivalent of vanilla `TheEndBiomeSource::getNoiseBiome()`. `erosion`
+    /// samples the erosion at the block position.
+    pub fn end_biome<F>(x: i32, y: i32, z: i32, erosion: F) -> &'static str
+    where
+        F: Fn(i32, i32, i32) -> f64
+    {
+        let section_x = x >> 4;
+        let section_z = z >> 4;
+
+        if (section_x as i64) * (section_x as i64) + (section_z as i64) * (section_z as i64) <= 4096 {
+            return END_BIOMES[0]
+        }
+
+        let erosion = erosion((section_x * 2 + 1) * 8, y, (section_z * 2 + 1) * 8);
+
+        if erosion > 0.25 {
+            END_BIOMES[1]
+        } else if erosion >= -0.0625 {
+            END_BIOMES[2]
+        } else if erosion < -0.21875 {
+            END_BIOMES[3]
+        } else {
+            END_BIOMES[4]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::biome::source::BiomeSource;
+
+    #[test]
+    fn end_biome() {
+        assert_eq!("minecraft:the_end", BiomeSource::end_biome(1000, 64, -100, |_, _, _| -1.0));
+        assert_eq!("minecraft:end_highlands", BiomeSource::end_biome(1100, 64, 0, |_, _, _| 0.3));
+        assert_eq!("minecraft:end_midlands", BiomeSource::end_biome(1100, 64, 0, |_, _, _| -0.0625));
+        assert_eq!("minecraft:end_barrens", BiomeSource::end_biome(1100, 64, 0, |_, _, _| -0.1));
+        assert_eq!("minecraft:small_end_islands", BiomeSource::end_biome(1100, 64, 0, |_, _, _| -0.5));
+        assert_eq!("minecraft:end_highlands", BiomeSource::end_biome(1040, 64, -1, |x, y, z| {
+            assert_eq!((1048, 64, -8), (x, y, z));
+            0.3
+        }));
+    }
+}
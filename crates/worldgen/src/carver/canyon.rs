@@ -0,0 +1,160 @@
+use crate::carver::{Carver, CarverConfig, CarverRng, CarveMode, CarvingContext};
+use crate::noise::math::{cos, sin};
+use crate::provider::{random_between, FloatProvider};
+use crate::rng::{LcgEntropySrc, Rng, U32EntropySrc};
+use crate::tag::BlockTags;
+use serde::Deserialize;
+use serde_json::Value;
+use spherix_world::chunk::pos::ChunkPos;
+use std::f32::consts::PI;
+
+#[derive(Deserialize)]
+struct CanyonShape {
+    distance_factor: FloatProvider,
+    thickness: FloatProvider,
+    /// Width of the canyon changes once in this many blocks (on average).
+    width_smoothness: u32,
+    horizontal_radius_factor: FloatProvider,
+    vertical_radius_default_factor: f32,
+    vertical_radius_center_factor: f32,
+}
+
+/// Single long and narrow ravine.
+pub struct CanyonCarver {
+    config: CarverConfig,
+    vertical_rotation: FloatProvider,
+    shape: CanyonShape,
+}
+
+impl CanyonCarver {
+    /// Horizontal reach of canyons in chunks.
+    const RANGE: i32 = 4;
+
+    pub fn from_json(json: &Value, tags: &BlockTags) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Raw {
+            vertical_rotation: FloatProvider,
+            shape: CanyonShape,
+        }
+
+        let raw: Raw = serde_json::from_value(json.clone())?;
+
+        Ok(Self {
+            config: CarverConfig::from_json(json, tags)?,
+            vertical_rotation: raw.vertical_rotation,
+            shape: raw.shape,
+        })
+    }
+
+    /// Random width multipliers for each level of the dimension, which make
+    /// the canyon walls uneven.
+    fn width_factors(&self, ctx: &CarvingContext, rng: &mut CarverRng) -> Vec<f32> {
+        let mut factors = Vec::with_capacity(ctx.gen_ctx.height as usize);
+        let mut factor = 1.0f32;
+
+        for i in 0..ctx.gen_ctx.height {
+            if i == 0 || rng.next_u32(self.shape.width_smoothness) == 0 {
+                factor = 1.0 + rng.next_f32() * rng.next_f32();
+            }
+
+            factors.push(factor * factor);
+        }
+
+        factors
+    }
+
+    fn vertical_radius(&self, rng: &mut CarverRng, vertical_radius: f64, branch_count: f32, branch: f32) -> f64 {
+        let centrality = 1.0 - (0.5 - branch / branch_count).abs() * 2.0;
+        let factor = self.shape.vertical_radius_default_factor + self.shape.vertical_radius_center_factor * centrality;
+
+        factor as f64 * vertical_radius * random_between(rng, 0.75, 1.0) as f64
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn do_carve(
+        &self,
+        ctx: &mut CarvingContext,
+        seed: i64,
+        mut x: f64,
+        mut y: f64,
+        mut z: f64,
+        thickness: f32,
+        mut yaw: f32,
+        mut pitch: f32,
+        branch_count: i32,
+        y_scale: f64,
+    ) {
+        let mut rng = CarverRng::new(LcgEntropySrc::new(seed as u64));
+        let width_factors = self.width_factors(ctx, &mut rng);
+        let min_y = ctx.gen_ctx.min_y;
+
+        let mut yaw_change = 0.0f32;
+        let mut pitch_change = 0.0f32;
+
+        for branch in 0..branch_count {
+            let horizontal_radius = 1.5 + (sin(branch as f32 * PI / branch_count as f32) * thickness) as f64;
+            let vertical_radius = horizontal_radius * y_scale;
+            let horizontal_radius = horizontal_radius * self.shape.horizontal_radius_factor.sample(&mut rng) as f64;
+            let vertical_radius = self.vertical_radius(&mut rng, vertical_radius, branch_count as f32, branch as f32);
+
+            let horizontal_pitch = cos(pitch);
+            let vertical_pitch = sin(pitch);
+            x += (cos(yaw) * horizontal_pitch) as f64;
+            y += vertical_pitch as f64;
+            z += (sin(yaw) * horizontal_pitch) as f64;
+
+            pitch *= 0.7;
+            pitch += pitch_change * 0.05;
+            yaw += yaw_change * 0.05;
+            pitch_change *= 0.8;
+            yaw_change *= 0.5;
+            pitch_change += (rng.next_f32() - rng.next_f32()) * rng.next_f32() * 2.0;
+            yaw_change += (rng.next_f32() - rng.next_f32()) * rng.next_f32() * 4.0;
+
+            if rng.next_u32(4) == 0 {
+                continue
+            }
+
+            if !ctx.can_reach(x, z, branch, branch_count, thickness) {
+                return
+            }
+
+            ctx.carve_ellipsoid(
+                &self.config,
+                CarveMode::Aquifer,
+                x,
+                y,
+                z,
+                horizontal_radius,
+                vertical_radius,
+                |rel_x, rel_y, rel_z, block_y| {
+                    let width_factor = width_factors[(block_y - min_y - 1) as usize] as f64;
+
+                    (rel_x * rel_x + rel_z * rel_z) * width_factor + rel_y * rel_y / 6.0 >= 1.0
+                }
+            );
+        }
+    }
+}
+
+impl Carver for CanyonCarver {
+    fn config(&self) -> &CarverConfig {
+        &self.config
+    }
+
+    fn carve(&self, ctx: &mut CarvingContext, rng: &mut CarverRng, start: &ChunkPos) {
+        let max_branch_count = (Self::RANGE * 2 - 1) << 4;
+
+        let x = (start.get_min_block_x() + rng.next_u32(16) as i32) as f64;
+        let y = self.config.y.sample(rng, &ctx.gen_ctx) as f64;
+        let z = (start.get_min_block_z() + rng.next_u32(16) as i32) as f64;
+        let yaw = rng.next_f32() * (PI * 2.0);
+        let pitch = self.vertical_rotation.sample(rng);
+        let y_scale = self.config.y_scale.sample(rng) as f64;
+        let thickness = self.shape.thickness.sample(rng);
+        let branch_count = (max_branch_count as f32 * self.shape.distance_factor.sample(rng)) as i32;
+        let seed = rng.next_u64() as i64;
+
+        self.do_carve(ctx, seed, x, y, z, thickness, yaw, pitch, branch_count, y_scale);
+    }
+}
@@ -0,0 +1,247 @@
+use crate::carver::{Carver, CarverConfig, CarverRng, CarveMode, CarvingContext};
+use crate::noise::math::{cos, sin};
+use crate::provider::FloatProvider;
+use crate::rng::{LcgEntropySrc, Rng, U32EntropySrc};
+use crate::tag::BlockTags;
+use serde::Deserialize;
+use serde_json::Value;
+use spherix_world::chunk::pos::ChunkPos;
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CaveKind {
+    Overworld,
+    /// Fewer, but wider and taller tunnels, which ignore aquifers.
+    Nether,
+}
+
+/// Tunnels with occasional rooms, which branch into two smaller tunnels.
+pub struct CaveCarver {
+    config: CarverConfig,
+    kind: CaveKind,
+    horizontal_radius_multiplier: FloatProvider,
+    vertical_radius_multiplier: FloatProvider,
+    /// Relative height of the tunnel floor, so tunnels have flat bottoms.
+    floor_level: FloatProvider,
+}
+
+/// Tunnel parameters, which are the same for all its branches.
+struct Tunnel {
+    horizontal_radius_multiplier: f64,
+    vertical_radius_multiplier: f64,
+    floor_level: f64,
+}
+
+impl CaveCarver {
+    /// Horizontal reach of tunnels in chunks.
+    const RANGE: i32 = 4;
+
+    pub fn from_json(json: &Value, tags: &BlockTags, kind: CaveKind) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Raw {
+            horizontal_radius_multiplier: FloatProvider,
+            vertical_radius_multiplier: FloatProvider,
+            floor_level: FloatProvider,
+        }
+
+        let raw: Raw = serde_json::from_value(json.clone())?;
+
+        Ok(Self {
+            config: CarverConfig::from_json(json, tags)?,
+            kind,
+            horizontal_radius_multiplier: raw.horizontal_radius_multiplier,
+            vertical_radius_multiplier: raw.vertical_radius_multiplier,
+            floor_level: raw.floor_level,
+        })
+    }
+
+    fn cave_bound(&self) -> u32 {
+        match self.kind {
+            CaveKind::Overworld => 15,
+            CaveKind::Nether => 10,
+        }
+    }
+
+    fn thickness(&self, rng: &mut CarverRng) -> f32 {
+        match self.kind {
+            CaveKind::Overworld => {
+                let mut thickness = rng.next_f32() * 2.0 + rng.next_f32();
+                if rng.next_u32(10) == 0 {
+                    thickness *= rng.next_f32() * rng.next_f32() * 3.0 + 1.0;
+                }
+
+                thickness
+            }
+            CaveKind::Nether => (rng.next_f32() * 2.0 + rng.next_f32()) * 2.0,
+        }
+    }
+
+    fn y_scale(&self) -> f64 {
+        match self.kind {
+            CaveKind::Overworld => 1.0,
+            CaveKind::Nether => 5.0,
+        }
+    }
+
+    fn mode(&self) -> CarveMode {
+        match self.kind {
+            CaveKind::Overworld => CarveMode::Aquifer,
+            CaveKind::Nether => CarveMode::Nether,
+        }
+    }
+
+    fn should_skip(rel_x: f64, rel_y: f64, rel_z: f64, floor_level: f64) -> bool {
+        rel_y <= floor_level || rel_x * rel_x + rel_y * rel_y + rel_z * rel_z >= 1.0
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_room(
+        &self,
+        ctx: &mut CarvingContext,
+        tunnel: &Tunnel,
+        x: f64,
+        y: f64,
+        z: f64,
+        radius: f32,
+        y_scale: f64,
+    ) {
+        let horizontal_radius = 1.5 + (sin(PI / 2.0) * radius) as f64;
+        let vertical_radius = horizontal_radius * y_scale;
+
+        ctx.carve_ellipsoid(
+            &self.config,
+            self.mode(),
+            x + 1.0,
+            y,
+            z,
+            horizontal_radius,
+            vertical_radius,
+            |rel_x, rel_y, rel_z, _| Self::should_skip(rel_x, rel_y, rel_z, tunnel.floor_level)
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_tunnel(
+        &self,
+        ctx: &mut CarvingContext,
+        tunnel: &Tunnel,
+        seed: i64,
+        mut x: f64,
+        mut y: f64,
+        mut z: f64,
+        thickness: f32,
+        mut yaw: f32,
+        mut pitch: f32,
+        branch_index: i32,
+        branch_count: i32,
+        y_scale: f64,
+    ) {
+        let mut rng = CarverRng::new(LcgEntropySrc::new(seed as u64));
+
+        let split_index = rng.next_u32((branch_count / 2) as u32) as i32 + branch_count / 4;
+        let steep = rng.next_u32(6) == 0;
+
+        let mut yaw_change = 0.0f32;
+        let mut pitch_change = 0.0f32;
+
+        for branch in branch_index..branch_count {
+            let horizontal_radius = 1.5 + (sin(PI * branch as f32 / branch_count as f32) * thickness) as f64;
+            let vertical_radius = horizontal_radius * y_scale;
+
+            let horizontal_pitch = cos(pitch);
+            x += (cos(yaw) * horizontal_pitch) as f64;
+            y += sin(pitch) as f64;
+            z += (sin(yaw) * horizontal_pitch) as f64;
+
+            pitch *= if steep { 0.92 } else { 0.7 };
+            pitch += pitch_change * 0.1;
+            yaw += yaw_change * 0.1;
+            pitch_change *= 0.9;
+            yaw_change *= 0.75;
+            pitch_change += (rng.next_f32() - rng.next_f32()) * rng.next_f32() * 2.0;
+            yaw_change += (rng.next_f32() - rng.next_f32()) * rng.next_f32() * 4.0;
+
+            if branch == split_index && thickness > 1.0 {
+                let seed = rng.next_u64() as i64;
+                let thickness = rng.next_f32() * 0.5 + 0.5;
+                self.create_tunnel(
+                    ctx, tunnel, seed, x, y, z, thickness, yaw - PI / 2.0, pitch / 3.0, branch, branch_count, 1.0
+                );
+
+                let seed = rng.next_u64() as i64;
+                let thickness = rng.next_f32() * 0.5 + 0.5;
+                self.create_tunnel(
+                    ctx, tunnel, seed, x, y, z, thickness, yaw + PI / 2.0, pitch / 3.0, branch, branch_count, 1.0
+                );
+
+                return
+            }
+
+            if rng.next_u32(4) == 0 {
+                continue
+            }
+
+            if !ctx.can_reach(x, z, branch, branch_count, thickness) {
+                return
+            }
+
+            ctx.carve_ellipsoid(
+                &self.config,
+                self.mode(),
+                x,
+                y,
+                z,
+                horizontal_radius * tunnel.horizontal_radius_multiplier,
+                vertical_radius * tunnel.vertical_radius_multiplier,
+                |rel_x, rel_y, rel_z, _| Self::should_skip(rel_x, rel_y, rel_z, tunnel.floor_level)
+            );
+        }
+    }
+}
+
+impl Carver for CaveCarver {
+    fn config(&self) -> &CarverConfig {
+        &self.config
+    }
+
+    fn carve(&self, ctx: &mut CarvingContext, rng: &mut CarverRng, start: &ChunkPos) {
+        let max_branch_count = (Self::RANGE * 2 - 1) << 4;
+
+        let bound = rng.next_u32(self.cave_bound());
+        let bound = rng.next_u32(bound + 1);
+        let count = rng.next_u32(bound + 1);
+
+        for _ in 0..count {
+            let x = (start.get_min_block_x() + rng.next_u32(16) as i32) as f64;
+            let y = self.config.y.sample(rng, &ctx.gen_ctx) as f64;
+            let z = (start.get_min_block_z() + rng.next_u32(16) as i32) as f64;
+
+            let tunnel = Tunnel {
+                horizontal_radius_multiplier: self.horizontal_radius_multiplier.sample(rng) as f64,
+                vertical_radius_multiplier: self.vertical_radius_multiplier.sample(rng) as f64,
+                floor_level: self.floor_level.sample(rng) as f64,
+            };
+
+            let mut tunnels = 1;
+            if rng.next_u32(4) == 0 {
+                let y_scale = self.config.y_scale.sample(rng) as f64;
+                let radius = 1.0 + rng.next_f32() * 6.0;
+                self.create_room(ctx, &tunnel, x, y, z, radius, y_scale);
+
+                tunnels += rng.next_u32(4);
+            }
+
+            for _ in 0..tunnels {
+                let yaw = rng.next_f32() * (PI * 2.0);
+                let pitch = (rng.next_f32() - 0.5) / 4.0;
+                let thickness = self.thickness(rng);
+                let branch_count = max_branch_count - rng.next_u32((max_branch_count / 4) as u32) as i32;
+                let seed = rng.next_u64() as i64;
+
+                self.create_tunnel(
+                    ctx, &tunnel, seed, x, y, z, thickness, yaw, pitch, 0, branch_count, self.y_scale()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+pub mod cave;
+pub mod canyon;
+pub mod registry;
+
+use crate::aquifer::Aquifer;
+use crate::carver::canyon::CanyonCarver;
+use crate::carver::cave::{CaveCarver, CaveKind};
+use crate::chunk::column::ChunkColumn;
+use crate::noise::density::cache::quart_pos_from_block;
+use crate::noise::density::density::{ContextFiller, DensityFunctionContext};
+use crate::noise::math::floor;
+use crate::provider::{FloatProvider, HeightProvider, VerticalAnchor};
+use crate::rng::{LcgEntropySrc, Rng, U32EntropySrc, U32EntropySrcRng};
+use crate::surface::context::WorldGenerationContext;
+use crate::surface::materializer::TopMaterial;
+use crate::tag::{BlockSet, BlockTags};
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::carving_mask::CarvingMask;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::vector::Vector3BlockColumn;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Carvers still use the legacy (Java) random, seeded per chunk.
+pub type CarverRng = U32EntropySrcRng<LcgEntropySrc>;
+
+/// Equivalent of vanilla `WorldgenRandom::setLargeFeatureSeed()`.
+pub fn large_feature_rng(seed: i64, x: i32, z: i32) -> CarverRng {
+    let mut rng = CarverRng::new(LcgEntropySrc::new(seed as u64));
+    let a = rng.next_u64() as i64;
+    let b = rng.next_u64() as i64;
+
+    let seed = (x as i64).wrapping_mul(a) ^ (z as i64).wrapping_mul(b) ^ seed;
+
+    CarverRng::new(LcgEntropySrc::new(seed as u64))
+}
+
+/// Configured carver, i.e. the carver algorithm together with its parameters
+/// from `worldgen/configured_carver/*.json`.
+pub trait Carver: Send + Sync {
+    fn config(&self) -> &CarverConfig;
+
+    fn is_start_chunk(&self, rng: &mut CarverRng) -> bool {
+        rng.next_f32() <= self.config().probability
+    }
+
+    /// Carves the part of the tunnels, which start in the chunk `start`, that
+    /// falls into the chunk of the context.
+    fn carve(&self, ctx: &mut CarvingContext, rng: &mut CarverRng, start: &ChunkPos);
+}
+
+pub fn carver_from_json(json: &Value, tags: &BlockTags) -> anyhow::Result<Arc<dyn Carver>> {
+    let Some(Value::String(ty)) = json.get("type") else {
+        return Err(anyhow!("No \"type\" key"))
+    };
+    let Some(config) = json.get("config") else {
+        return Err(anyhow!("No \"config\" key"))
+    };
+
+    let carver: Arc<dyn Carver> = match ty.as_str() {
+        "minecraft:cave" => Arc::new(CaveCarver::from_json(config, tags, CaveKind::Overworld)?),
+        "minecraft:nether_cave" => Arc::new(CaveCarver::from_json(config, tags, CaveKind::Nether)?),
+        "minecraft:canyon" => Arc::new(CanyonCarver::from_json(config, tags)?),
+        _ => return Err(anyhow!("Unknown carver type {}", ty))
+    };
+
+    Ok(carver)
+}
+
+/// Parameters common to all carvers.
+pub struct CarverConfig {
+    pub probability: f32,
+    pub y: HeightProvider,
+    pub y_scale: FloatProvider,
+    /// Carved blocks at or below this level are replaced with lava.
+    pub lava_level: VerticalAnchor,
+    pub replaceable: BlockSet,
+}
+
+impl CarverConfig {
+    pub fn from_json(json: &Value, tags: &BlockTags) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Raw {
+            probability: f32,
+            y: HeightProvider,
+            #[serde(rename = "yScale")]
+            y_scale: FloatProvider,
+            lava_level: VerticalAnchor,
+        }
+
+        let raw: Raw = serde_json::from_value(json.clone())?;
+
+        let Some(replaceable) = json.get("replaceable") else {
+            return Err(anyhow!("No \"replaceable\" key"))
+        };
+
+        Ok(Self {
+            probability: raw.probability,
+            y: raw.y,
+            y_scale: raw.y_scale,
+            lava_level: raw.lava_level,
+            replaceable: tags.resolve_holder_set(replaceable)?,
+        })
+    }
+}
+
+/// How carved blocks are replaced.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CarveMode {
+    /// Takes the fluid from the aquifer, so caves are flooded consistently with
+    /// the noise stage.
+    Aquifer,
+    /// Ignores the aquifer and fills the bottom of the dimension with lava.
+    Nether,
+}
+
+/// State of the chunk being carved.
+pub struct CarvingContext<'a> {
+    pub gen_ctx: WorldGenerationContext,
+    pos: ChunkPos,
+    chunk: &'a mut ChunkColumn,
+    aquifer: Rc<RefCell<Box<dyn Aquifer>>>,
+    /// Context for sampling the aquifer at single blocks.
+    point_ctx: DensityFunctionContext,
+    top_material: TopMaterial<'a>,
+    mask: CarvingMask,
+    lava: Arc<BlockState>,
+    cave_air: Arc<BlockState>,
+}
+
+impl<'a> CarvingContext<'a> {
+    pub fn new(
+        gen_ctx: WorldGenerationContext,
+        chunk: &'a mut ChunkColumn,
+        aquifer: Rc<RefCell<Box<dyn Aquifer>>>,
+        top_material: TopMaterial<'a>,
+        mask: CarvingMask,
+        palette: &BlockGlobalPalette,
+    ) -> Self {
+        let pos = chunk.pos();
+
+        let point_ctx = DensityFunctionContext {
+            filler: ContextFiller::Slice,
+            first_noise_x: quart_pos_from_block(pos.get_min_block_x()),
+            first_noise_z: quart_pos_from_block(pos.get_min_block_z()),
+            ..Default::default()
+        };
+
+        Self {
+            gen_ctx,
+            pos,
+            chunk,
+            aquifer,
+            point_ctx,
+            top_material,
+            mask,
+            lava: palette.get_default_obj_by_index(&Block::LAVA).unwrap(),
+            cave_air: palette.get_default_obj_by_index(&Block::CAVE_AIR).unwrap(),
+        }
+    }
+
+    pub fn into_mask(self) -> CarvingMask {
+        self.mask
+    }
+
+    /// Whether a tunnel at (x, z) is still able to reach the chunk with its
+    /// remaining branches.
+    fn can_reach(&self, x: f64, z: f64, branch_index: i32, branch_count: i32, width: f32) -> bool {
+        let dx = x - (self.pos.get_min_block_x() + 8) as f64;
+        let dz = z - (self.pos.get_min_block_z() + 8) as f64;
+        let remaining = (branch_count - branch_index) as f64;
+        let max_distance = (width + 2.0 + 16.0) as f64;
+
+        dx * dx + dz * dz - remaining * remaining <= max_distance * max_distance
+    }
+
+    /// Carves blocks of the chunk within the ellipsoid, which are not excluded
+    /// by `skip`. The latter receives coordinates relative to the ellipsoid
+    /// center and radii, and the absolute y.
+    #[allow(clippy::too_many_arguments)]
+    fn carve_ellipsoid<S>(
+        &mut self,
+        config: &CarverConfig,
+        mode: CarveMode,
+        x: f64,
+        y: f64,
+        z: f64,
+        horizontal_radius: f64,
+        vertical_radius: f64,
+        skip: S,
+    ) -> bool
+    where
+        S: Fn(f64, f64, f64, i32) -> bool
+    {
+        let min_block_x = self.pos.get_min_block_x();
+        let min_block_z = self.pos.get_min_block_z();
+
+        let max_distance = 16.0 + horizontal_radius * 2.0;
+        if (x - (min_block_x + 8) as f64).abs() > max_distance || (z - (min_block_z + 8) as f64).abs() > max_distance {
+            return false
+        }
+
+        let min_x = (floor(x - horizontal_radius) - min_block_x - 1).max(0);
+        let max_x = (floor(x + horizontal_radius) - min_block_x).min(15);
+        let min_y = (floor(y - vertical_radius) - 1).max(self.gen_ctx.min_y + 1);
+        // Keeps a few blocks at the top of the dimension untouched.
+        let max_y = (floor(y + vertical_radius) + 1).min(self.gen_ctx.min_y + self.gen_ctx.height - 1 - 7);
+        let min_z = (floor(z - horizontal_radius) - min_block_z - 1).max(0);
+        let max_z = (floor(z + horizontal_radius) - min_block_z).min(15);
+
+        let mut carved = false;
+
+        for local_x in min_x..=max_x {
+            let rel_x = ((min_block_x + local_x) as f64 + 0.5 - x) / horizontal_radius;
+
+            for local_z in min_z..=max_z {
+                let rel_z = ((min_block_z + local_z) as f64 + 0.5 - z) / horizontal_radius;
+
+                if rel_x * rel_x + rel_z * rel_z >= 1.0 {
+                    continue
+                }
+
+                let mut surface_reached = false;
+
+                for block_y in (min_y + 1..=max_y).rev() {
+                    let rel_y = (block_y as f64 - 0.5 - y) / vertical_radius;
+
+                    if skip(rel_x, rel_y, rel_z, block_y) || self.mask.get(local_x, block_y, local_z) {
+                        continue
+                    }
+
+                    self.mask.set(local_x, block_y, local_z);
+
+                    let at = Vector3::new(min_block_x + local_x, block_y, min_block_z + local_z);
+                    carved |= match mode {
+                        CarveMode::Aquifer => self.carve_block(config, at, &mut surface_reached),
+                        CarveMode::Nether => self.carve_nether_block(config, at),
+                    };
+                }
+            }
+        }
+
+        carved
+    }
+
+    /// `surface_reached` remembers whether the tunnel has passed through grass
+    /// or mycelium on its way down in the current column.
+    fn carve_block(&mut self, config: &CarverConfig, at: Vector3, surface_reached: &mut bool) -> bool {
+        let state = self.block_state(at);
+        if state.block() == Block::GRASS_BLOCK || state.block() == Block::MYCELIUM {
+            *surface_reached = true;
+        }
+
+        if !config.replaceable.contains(state.block()) {
+            return false
+        }
+
+        let Some(carved) = self.carve_state(config, at) else {
+            return false
+        };
+
+        let under_fluid = carved.block().properties.is_fluid;
+        self.set_block_state(at, carved);
+
+        // The dirt exposed below the surface becomes the top layer again, e.g.
+        // grass in the open and dirt or sand under water.
+        if *surface_reached {
+            let below = Vector3::new(at.x, at.y - 1, at.z);
+            if self.block_state(below).block() == Block::DIRT {
+                if let Some(top) = self.top_material.at(below, under_fluid) {
+                    self.set_block_state(below, top);
+                }
+            }
+        }
+
+        true
+    }
+
+    fn carve_nether_block(&mut self, config: &CarverConfig, at: Vector3) -> bool {
+        if !config.replaceable.contains(self.block_state(at).block()) {
+            return false
+        }
+
+        let state = if at.y <= self.gen_ctx.min_y + 31 {
+            self.lava.clone()
+        } else {
+            self.cave_air.clone()
+        };
+
+        self.set_block_state(at, state);
+
+        true
+    }
+
+    fn carve_state(&mut self, config: &CarverConfig, at: Vector3) -> Option<Arc<BlockState>> {
+        if at.y <= config.lava_level.resolve_y(&self.gen_ctx) {
+            return Some(self.lava.clone())
+        }
+
+        self.point_ctx.cell_start_block_x = at.x;
+        self.point_ctx.cell_start_block_y = at.y;
+        self.point_ctx.cell_start_block_z = at.z;
+
+        self.aquifer.borrow_mut().compute(&mut self.point_ctx, 0.0)
+    }
+
+    #[inline]
+    fn block_state(&self, at: Vector3) -> Arc<BlockState> {
+        self.chunk
+            .inner()
+            .block_state(Vector3BlockColumn::new((at.x & 15) as u32, at.y, (at.z & 15) as u32))
+            .unwrap()
+    }
+
+    #[inline]
+    fn set_block_state(&mut self, at: Vector3, state: Arc<BlockState>) {
+        self.chunk
+            .inner_mut()
+            .set_block_state(Vector3BlockColumn::new((at.x & 15) as u32, at.y, (at.z & 15) as u32), state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::carver::large_feature_rng;
+    use crate::rng::Rng;
+
+    #[test]
+    fn large_feature_seed() {
+        let mut rng = large_feature_rng(12345, 3, -7);
+        assert_eq!(8624464025789168634, rng.next_u64() as i64);
+
+        let mut rng = large_feature_rng(-42, 0, 0);
+        assert_eq!(65, rng.next_u32(100));
+    }
+}
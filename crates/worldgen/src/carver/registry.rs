@@ -0,0 +1,61 @@
+use crate::carver::{carver_from_json, Carver};
+use crate::pack::DataPacks;
+use crate::tag::BlockTags;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::chunk::biome::Biome;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Air carvers of each biome, in the order they are listed in the biome. The
+/// order matters, since the index of a carver is mixed into its seed.
+pub struct CarverRegistry {
+    biome_carvers: HashMap<String, Vec<Arc<dyn Carver>>>,
+}
+
+impl CarverRegistry {
+    /// Loads carver lists of all biomes of the data packs along with the
+    /// configured carvers they refer to.
+    pub fn load(packs: &DataPacks, tags: &BlockTags) -> anyhow::Result<Self> {
+        let mut carvers: HashMap<String, Arc<dyn Carver>> = HashMap::new();
+        let mut biome_carvers = HashMap::new();
+
+        for name in packs.ids("worldgen/biome", "json")? {
+            let biome = packs.json("worldgen/biome", &name)?;
+            let names = match biome.get("carvers").and_then(|carvers| carvers.get("air")) {
+                None => Vec::new(),
+                Some(Value::String(name)) => vec![name.clone()],
+                Some(Value::Array(names)) => names
+                    .iter()
+                    .map(|name| name.as_str().map(str::to_owned).ok_or_else(|| anyhow!("Expected string, found {:?}", name)))
+                    .collect::<anyhow::Result<_>>()?,
+                Some(value) => return Err(anyhow!("Expected string or array, found {:?}", value))
+            };
+
+            let mut list = Vec::with_capacity(names.len());
+            for carver_name in names {
+                if !carvers.contains_key(&carver_name) {
+                    let carver = carver_from_json(&packs.json("worldgen/configured_carver", &carver_name)?, tags)
+                        .map_err(|e| anyhow!("Unable to load carver {}: {}", carver_name, e))?;
+
+                    carvers.insert(carver_name.clone(), carver);
+                }
+
+                list.push(carvers.get(&carver_name).unwrap().clone());
+            }
+
+            biome_carvers.insert(name, list);
+        }
+
+        Ok(Self {
+            biome_carvers,
+        })
+    }
+
+    pub fn carvers(&self, biome: &Biome) -> &[Arc<dyn Carver>] {
+        self.biome_carvers
+            .get(biome.name_ref())
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+}
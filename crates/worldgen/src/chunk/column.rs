@@ -4,6 +4,7 @@ use spherix_world::chunk::biome::Biome;
 use spherix_world::chunk::column::{ChunkColumnRef, ChunkColumnRefMut};
 use spherix_world::chunk::handle::ChunkSectionHandle;
 use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::section::ChunkSection;
 use spherix_world::chunk::vector::Vector3BlockColumn;
 use std::sync::Arc;
 
@@ -22,6 +23,10 @@ impl ChunkColumn {
     pub fn inner_mut(&mut self) -> &mut spherix_world::chunk::column::ChunkColumn {
         &mut self.0
     }
+
+    pub fn into_inner(self) -> spherix_world::chunk::column::ChunkColumn {
+        self.0
+    }
     
     #[inline]
     pub fn pos(&self) -> ChunkPos {
@@ -39,14 +44,12 @@ impl ChunkColumn {
     }
 
     #[inline]
-    pub fn with_safe(&self) -> Ref {
-        Ref {
-            col: self
-        }
+    pub fn section_mut(&mut self, index: usize) -> &mut ChunkSection {
+        self.0.section_mut(index)
     }
 
     #[inline]
-    pub unsafe fn with_unsafe(&self) -> Ref {
+    pub fn with_safe(&self) -> Ref {
         Ref {
             col: self
         }
@@ -59,13 +62,6 @@ impl ChunkColumn {
         }
     }
 
-    #[inline]
-    pub fn with_unsafe_mut(&mut self) -> RefMut {
-        RefMut {
-            col: self
-        }
-    }
-
     #[inline]
     pub fn min_build_height(&self) -> i32 {
         -64
@@ -77,6 +73,31 @@ impl ChunkColumn {
     }
 }
 
+/// Chunk within the radius of a step. Steps write into the neighbours, which
+/// are still in generation, and only read the others.
+pub enum Neighbour<'a> {
+    Writable(&'a mut ChunkColumn),
+    ReadOnly(&'a ChunkColumn),
+}
+
+impl Neighbour<'_> {
+    #[inline]
+    pub fn get(&self) -> &ChunkColumn {
+        match self {
+            Neighbour::Writable(chunk) => &**chunk,
+            Neighbour::ReadOnly(chunk) => *chunk,
+        }
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut ChunkColumn> {
+        match self {
+            Neighbour::Writable(chunk) => Some(&mut **chunk),
+            Neighbour::ReadOnly(_) => None,
+        }
+    }
+}
+
 pub struct Ref<'a> {
     col: &'a ChunkColumn
 }
@@ -0,0 +1,252 @@
+use crate::biome::sampler::BiomeSampler;
+use crate::biome::source::BiomeSource;
+use crate::chunk::column::{ChunkColumn, Neighbour};
+use crate::chunk::generator::{climate_sampler, finish_noise, ChunkGenerator, Decoration};
+use crate::chunk::noise::NoiseChunk;
+use crate::feature::registry::FeatureRegistry;
+use crate::noise::density::beardifier::Beardifier;
+use crate::noise::settings::NoiseSettings;
+use crate::structure::registry::StructureRegistry;
+use crate::structure::{StructureStarts, DECORATION_STEPS};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::block::block::{Block, BLOCKS};
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::heightmap::{Heightmap, HeightmapType};
+use spherix_world::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::vector::{Vector3BlockColumn, Vector3BlockSection};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Settings of a superflat world, as in `worldgen/flat_level_generator_preset/*.json`.
+pub struct FlatSettings {
+    /// Block of each layer from the bottom of the world up.
+    pub layers: Vec<Arc<BlockState>>,
+    pub biome: String,
+    /// Features of the biome are placed.
+    pub features: bool,
+    /// Lakes of the biome are placed, even without the other features.
+    pub lakes: bool,
+    /// Structure sets, which are placed. All of them are, if absent.
+    pub structure_overrides: Option<Vec<String>>,
+}
+
+impl FlatSettings {
+    /// Reads the `settings` of the preset.
+    pub fn from_json(json: &Value, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let Some(Value::Array(layers)) = json.get("layers") else {
+            return Err(anyhow!("No \"layers\" key in {:?}", json))
+        };
+
+        let mut states = Vec::new();
+
+        for layer in layers {
+            let (Some(Value::String(name)), Some(height)) = (layer.get("block"), layer.get("height").and_then(Value::as_u64)) else {
+                return Err(anyhow!("Invalid flat layer {:?}", layer))
+            };
+
+            let block = BLOCKS
+                .get(name.as_str())
+                .copied()
+                .ok_or_else(|| anyhow!("Unknown block {}", name))?;
+            let state = palette
+                .get_default_obj_by_index(&block)
+                .ok_or_else(|| anyhow!("No default state of block {}", name))?;
+
+            states.extend(std::iter::repeat(state).take(height as usize));
+        }
+
+        let Some(Value::String(biome)) = json.get("biome") else {
+            return Err(anyhow!("No \"biome\" key in {:?}", json))
+        };
+
+        let structure_overrides = match json.get("structure_overrides") {
+            None => None,
+            Some(Value::String(tag)) if tag.starts_with('#') => {
+                return Err(anyhow!("Structure set tags are not supported: {}", tag))
+            }
+            Some(Value::String(set)) => Some(vec![set.clone()]),
+            Some(Value::Array(sets)) => Some(
+                sets.iter()
+                    .map(|set| set.as_str().map(str::to_owned).ok_or_else(|| anyhow!("Invalid structure set {:?}", set)))
+                    .collect::<anyhow::Result<_>>()?
+            ),
+            Some(overrides) => return Err(anyhow!("Invalid structure overrides {:?}", overrides)),
+        };
+
+        Ok(Self {
+            layers: states,
+            biome: biome.clone(),
+            features: json.get("features").and_then(Value::as_bool).unwrap_or(false),
+            lakes: json.get("lakes").and_then(Value::as_bool).unwrap_or(false),
+            structure_overrides,
+        })
+    }
+
+    /// Equivalent of vanilla `FlatLevelSource::getBaseHeight()`: the first y
+    /// above the top layer of the heightmap type.
+    pub fn base_height(&self, min_y: i32, ty: HeightmapType) -> i32 {
+        self.layers
+            .iter()
+            .rposition(|state| ty.is_opaque(state))
+            .map_or(min_y, |i| min_y + i as i32 + 1)
+    }
+
+    /// Vanilla places no features in a void world, unless its biome is the
+    /// void, which has the starting platform.
+    fn places_features(&self) -> bool {
+        let void = self.layers.iter().all(|state| state.block() == Block::AIR);
+
+        !void || self.biome == "minecraft:the_void"
+    }
+}
+
+/// Equivalent of vanilla `FlatLevelSource`. The world is made of the layers of
+/// the settings in a single biome, without a surface and carvers.
+#[derive(Clone)]
+pub struct FlatChunkGenerator {
+    palette: Arc<BlockGlobalPalette>,
+    biome_palette: Arc<BiomeGlobalPalette>,
+    settings: Arc<FlatSettings>,
+}
+
+impl FlatChunkGenerator {
+    pub fn new(
+        settings: FlatSettings,
+        palette: Arc<BlockGlobalPalette>,
+        biome_palette: Arc<BiomeGlobalPalette>,
+    ) -> anyhow::Result<Self> {
+        if biome_palette.get_default_obj_by_index(&settings.biome).is_none() {
+            return Err(anyhow!("Unknown biome {}", settings.biome))
+        }
+
+        Ok(Self {
+            palette,
+            biome_palette,
+            settings: Arc::new(settings),
+        })
+    }
+
+    pub fn settings(&self) -> &FlatSettings {
+        &self.settings
+    }
+}
+
+impl ChunkGenerator for FlatChunkGenerator {
+    fn biome_sampler(&self, noise_settings: &NoiseSettings) -> BiomeSampler {
+        BiomeSampler::new(
+            self.biome_palette.clone(),
+            BiomeSource::Fixed(self.settings.biome.clone()),
+            climate_sampler(noise_settings),
+        )
+    }
+
+    fn base_height(&self, _: &NoiseSettings, _: i32, _: i32, ty: HeightmapType) -> i32 {
+        self.settings.base_height(-64, ty)
+    }
+
+    fn fill_noise(&self, _: &NoiseSettings, _: Beardifier, chunk_column: &mut ChunkColumn) -> Option<NoiseChunk> {
+        let min_y = chunk_column.min_build_height();
+
+        let mut ocean_floor_heightmap = Heightmap::new(HeightmapType::OceanFloorWg, 384, -64);
+        let mut world_surface_heightmap = Heightmap::new(HeightmapType::WorldSurfaceWg, 384, -64);
+
+        for (i, state) in self.settings.layers.iter().take(384).enumerate() {
+            if state.block() == Block::AIR {
+                continue
+            }
+
+            let y = min_y + i as i32;
+            let section_index = ((y - min_y) >> 4) as usize;
+
+            for x in 0..16 {
+                for z in 0..16 {
+                    chunk_column
+                        .section_mut(section_index)
+                        .set_block_state(Vector3BlockSection::new(x, (y & 0xF) as u32, z), state.clone());
+
+                    let heightmap_pos = Vector3BlockColumn::new(x, y, z);
+
+                    let chunk_column_ref = chunk_column.with_safe();
+                    ocean_floor_heightmap.update(&chunk_column_ref, heightmap_pos, state.as_ref());
+                    world_surface_heightmap.update(&chunk_column_ref, heightmap_pos, state.as_ref());
+                }
+            }
+        }
+
+        finish_noise(chunk_column, ocean_floor_heightmap, world_surface_heightmap);
+
+        None
+    }
+
+    /// Equivalent of vanilla `FlatLevelGeneratorSettings::adjustGenerationSettings()`.
+    /// Features of the structure steps are never placed, lakes are placed if
+    /// either lakes or features are enabled.
+    fn decorate(
+        &self,
+        seed: i64,
+        noise_settings: &NoiseSettings,
+        features: &FeatureRegistry,
+        structures: &StructureRegistry,
+        starts: &dyn Fn(&ChunkPos) -> StructureStarts,
+        biome_sampler: &BiomeSampler,
+        neighbours: HashMap<ChunkPos, Neighbour>,
+        chunk_column: &mut ChunkColumn,
+    ) {
+        let places_features = self.settings.places_features();
+
+        Decoration {
+            seed,
+            noise_settings,
+            palette: &self.palette,
+            features,
+            structures,
+            starts,
+            biome_sampler,
+        }
+            .decorate(neighbours, chunk_column, |step| match DECORATION_STEPS.get(step) {
+                Some(&"lakes") => self.settings.lakes || (places_features && self.settings.features),
+                Some(&"underground_structures") | Some(&"surface_structures") => false,
+                _ => places_features && self.settings.features,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::flat::FlatSettings;
+    use serde_json::json;
+    use spherix_world::block::block::Block;
+    use spherix_world::block::state::BlockState;
+    use spherix_world::block::variant::VariantVec;
+    use spherix_world::chunk::heightmap::HeightmapType;
+    use spherix_world::chunk::palette::global::GlobalId;
+    use spherix_world::chunk::palette::BlockGlobalPalette;
+
+    #[test]
+    fn classic_flat() {
+        let mut palette = BlockGlobalPalette::new(2);
+        palette.insert(GlobalId(0), BlockState::new(Block::BEDROCK, true, VariantVec::empty()));
+        palette.insert(GlobalId(1), BlockState::new(Block::DIRT, true, VariantVec::empty()));
+        palette.insert(GlobalId(2), BlockState::new(Block::GRASS_BLOCK, true, VariantVec::empty()));
+
+        let settings = FlatSettings::from_json(&json!({
+            "biome": "minecraft:plains",
+            "features": false,
+            "lakes": false,
+            "layers": [
+                {"block": "minecraft:bedrock", "height": 1},
+                {"block": "minecraft:dirt", "height": 2},
+                {"block": "minecraft:grass_block", "height": 1}
+            ],
+            "structure_overrides": "minecraft:villages"
+        }), &palette).unwrap();
+
+        let blocks = settings.layers.iter().map(|state| state.block()).collect::<Vec<_>>();
+        assert_eq!(vec![Block::BEDROCK, Block::DIRT, Block::DIRT, Block::GRASS_BLOCK], blocks);
+        assert_eq!(Some(vec!["minecraft:villages".to_owned()]), settings.structure_overrides);
+        assert_eq!(-60, settings.base_height(-64, HeightmapType::WorldSurfaceWg));
+        assert!(settings.places_features());
+    }
+}
@@ -1,32 +1,170 @@
-use crate::aquifer::{DisabledAquifer, FluidPicker};
-use crate::biome::climate::json::BiomeIndex;
+use crate::aquifer::{Aquifer, DisabledAquifer, FluidPicker, NoiseBasedAquifer};
+use crate::biome::accessor::BiomeAccessor;
 use crate::biome::climate::sampler::ClimateSampler;
+use crate::biome::gradient::{BiomeGradient, LazyCachedBiomeGradient};
 use crate::biome::sampler::BiomeSampler;
-use crate::chunk::column::ChunkColumn;
-use crate::chunk::noise::NoiseChunk;
+use crate::biome::source::BiomeSource;
+use crate::carver::registry::CarverRegistry;
+use crate::carver::{large_feature_rng, CarvingContext};
+use crate::chunk::column::{ChunkColumn, Neighbour};
+use crate::chunk::noise::{NoiseChunk, NoiseTapes};
+use crate::feature::level::WorldGenLevel;
+use crate::feature::registry::FeatureRegistry;
+use crate::feature::{decoration_seed, feature_rng};
 use crate::noise::density::cache::{block_to_section_coord, quart_pos_from_block};
-use crate::noise::density::density::{ChainMapper, DensityFunctionContext, InterpolatedCollector, SetupInterpolatedMapper, SetupNoiseMapper};
-use crate::noise::math::floor_div;
+use crate::noise::density::beardifier::{Beardifier, BeardifierMapper};
+use crate::noise::density::binary::Add;
+use crate::noise::density::density::{ChainMapper, DensityFunctionContext, DensityFunctions, InterpolatedCollector, SetupEndIslandsMapper, SetupInterpolatedMapper, SetupNoiseMapper};
+use crate::noise::density::maker::InterpolatedInner;
+use crate::noise::math::{floor_div, floor_mod};
+use crate::noise::router::NoiseRouter;
 use crate::noise::settings::NoiseSettings;
-use crate::rng::XoroShiroPos;
+use crate::ore_vein::OreVeinifier;
+use crate::rng::{RngForkable, RngPos, WorldgenRngPos};
+use crate::structure::bounding_box::BoundingBox;
+use crate::structure::registry::StructureRegistry;
+use crate::structure::{StructureStarts, DECORATION_STEPS};
+use crate::surface::context::{EntropyBag, WorldGenerationContext};
+use crate::surface::materializer::{SurfaceMaterializer, TopMaterial};
+use crate::surface::rule_factory::RuleFactories;
 use spherix_math::vector::vec3::Vector3u;
 use spherix_math::vector::Vector3;
 use spherix_world::block::block::Block;
+use spherix_world::chunk::carving_mask::CarvingMask;
 use spherix_world::chunk::heightmap::{Heightmap, HeightmapType};
 use spherix_world::chunk::palette::{BiomeGlobalPalette, BlockGlobalPalette};
+use spherix_world::chunk::pos::ChunkPos;
 use spherix_world::chunk::status::ChunkStatus;
 use spherix_world::chunk::vector::{Vector3BlockColumn, Vector3BlockSection};
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
+/// Steps of the generation of a chunk, in the order they run. Noise settings
+/// of the dimension define its height, even for generators, which do not
+/// sample the noise.
 pub trait ChunkGenerator {
-    //
+    /// Sampler of the biomes of the dimension. Structures and carvers look up
+    /// the biomes of chunks, which are not generated yet, through it.
+    fn biome_sampler(&self, noise_settings: &NoiseSettings) -> BiomeSampler;
+
+    fn fill_biomes(&self, biome_sampler: &BiomeSampler, chunk_column: &mut ChunkColumn) {
+        fill_biomes_from(biome_sampler, chunk_column);
+    }
+
+    /// Equivalent of vanilla `ChunkGenerator::getBaseHeight()`: the first y
+    /// above the top block of the heightmap type. Structures use it before
+    /// their chunks are generated.
+    fn base_height(&self, noise_settings: &NoiseSettings, x: i32, z: i32, ty: HeightmapType) -> i32;
+
+    /// Stores the structures starting at the chunk.
+    fn create_structures(&self, starts: &StructureStarts, chunk_column: &mut ChunkColumn) {
+        let inner = chunk_column.inner_mut();
+
+        for start in starts.iter() {
+            inner.structure_starts.insert(start.structure.name.clone(), start.to_nbt());
+        }
+
+        inner.status = ChunkStatus::StructureStarts;
+    }
+
+    /// Stores references to the structures of the chunks around, whose pieces
+    /// may reach into this chunk. Only referenced structures are placed during
+    /// the decoration.
+    fn create_references(&self, starts: &dyn Fn(&ChunkPos) -> StructureStarts, chunk_column: &mut ChunkColumn) {
+        let pos = chunk_column.pos();
+        let min_x = pos.get_min_block_x();
+        let min_z = pos.get_min_block_z();
+        let inner = chunk_column.inner_mut();
+
+        for x in pos.x() - 8..=pos.x() + 8 {
+            for z in pos.z() - 8..=pos.z() + 8 {
+                let start_pos = ChunkPos::new(x, z);
+
+                for start in starts(&start_pos).iter() {
+                    if start.bounding_box().intersects_xz(min_x, min_z, min_x + 15, min_z + 15) {
+                        inner
+                            .structure_references
+                            .entry(start.structure.name.clone())
+                            .or_default()
+                            .push(start_pos.clone());
+                    }
+                }
+            }
+        }
+
+        inner.status = ChunkStatus::StructureReferences;
+    }
+
+    /// Beardifier of the structures referenced by the chunk, i.e. of the
+    /// pieces around it, which adapt the terrain.
+    fn beardifier(&self, starts: &dyn Fn(&ChunkPos) -> StructureStarts, chunk_column: &ChunkColumn) -> Beardifier {
+        let mut referenced = Vec::new();
+
+        for (name, positions) in &chunk_column.inner().structure_references {
+            for start_pos in positions {
+                referenced.extend(starts(start_pos).iter().filter(|start| start.structure.name == *name).cloned());
+            }
+        }
+
+        Beardifier::for_structures_in_chunk(&chunk_column.pos(), referenced.iter().map(|start| &**start))
+    }
+
+    /// Places the blocks of the terrain. The noise chunk is returned, if the
+    /// terrain comes from the noise, so the surface and carvers are able to
+    /// work on it. Otherwise they are skipped.
+    fn fill_noise(&self, noise_settings: &NoiseSettings, beardifier: Beardifier, chunk_column: &mut ChunkColumn) -> Option<NoiseChunk>;
+
+    /// Applies the surface rules of the dimension.
+    fn build_surface(
+        &self,
+        _noise_settings: &NoiseSettings,
+        _entropy_bag: Arc<EntropyBag>,
+        _rule_factory: Arc<RuleFactories>,
+        _noise_chunk: &mut NoiseChunk,
+        _biome_accessor: BiomeAccessor,
+        _chunk_column: &mut ChunkColumn,
+    ) {}
+
+    /// Carvers evaluate the surface rules for the blocks they expose, so they
+    /// take the same inputs as the surface.
+    #[allow(clippy::too_many_arguments)]
+    fn carve(
+        &self,
+        _seed: i64,
+        _noise_settings: &NoiseSettings,
+        _entropy_bag: Arc<EntropyBag>,
+        _rule_factory: Arc<RuleFactories>,
+        _carvers: &CarverRegistry,
+        _noise_chunk: &mut NoiseChunk,
+        _biome_accessor: BiomeAccessor,
+        _chunk_column: &mut ChunkColumn,
+    ) {}
+
+    /// Places pieces of the referenced structures and features of the biomes
+    /// around the chunk.
+    #[allow(clippy::too_many_arguments)]
+    fn decorate(
+        &self,
+        seed: i64,
+        noise_settings: &NoiseSettings,
+        features: &FeatureRegistry,
+        structures: &StructureRegistry,
+        starts: &dyn Fn(&ChunkPos) -> StructureStarts,
+        biome_sampler: &BiomeSampler,
+        neighbours: HashMap<ChunkPos, Neighbour>,
+        chunk_column: &mut ChunkColumn,
+    );
 }
 
 #[derive(Clone)]
 pub struct NoiseBasedChunkGenerator {
     palette: Arc<BlockGlobalPalette>,
     pub biome_palette: Arc<BiomeGlobalPalette>,
-    biome_index: Arc<BiomeIndex>,
+    biome_source: BiomeSource,
+    surface_materializer: SurfaceMaterializer,
+    aquifer_rng: Arc<WorldgenRngPos>,
+    ore_rng: Arc<WorldgenRngPos>,
+    tapes: Arc<NoiseTapes>,
 }
 
 impl NoiseBasedChunkGenerator {
@@ -34,8 +172,9 @@ impl NoiseBasedChunkGenerator {
         mut noise_settings: NoiseSettings,
         palette: Arc<BlockGlobalPalette>,
         biome_palette: Arc<BiomeGlobalPalette>,
-        biome_index: Arc<BiomeIndex>,
-        forked: Arc<XoroShiroPos>,
+        biome_source: BiomeSource,
+        seed: i64,
+        forked: Arc<WorldgenRngPos>,
     ) -> (Self, NoiseSettings) {
         let cell_width = noise_settings.cell_width() as usize;
         let cell_count_xz = 16 / cell_width;
@@ -44,75 +183,180 @@ impl NoiseBasedChunkGenerator {
             noise_settings.cell_height() as i32,
         ) as usize;
 
+        let aquifer_rng = Arc::new(forked.by_hash("minecraft:aquifer".to_owned()).fork_pos());
+        let ore_rng = Arc::new(forked.by_hash("minecraft:ore".to_owned()).fork_pos());
+
+        let noise_mapper = if noise_settings.use_legacy_random_source {
+            SetupNoiseMapper::new_legacy(forked, seed)
+        } else {
+            SetupNoiseMapper::new(forked)
+        };
+
         let chain_mapper = ChainMapper::new(vec![
-            Box::new(SetupNoiseMapper::new(forked)),
+            Box::new(noise_mapper),
+            Box::new(SetupEndIslandsMapper::new(seed)),
             Box::new(SetupInterpolatedMapper::new(cell_count_y, cell_count_xz)),
         ]);
 
         noise_settings.router = noise_settings.router.map(&chain_mapper);
 
+        let (router, interpolators) = chunk_router(&noise_settings.router, Beardifier::default());
+        let tapes = Arc::new(NoiseTapes::compile(&router.final_density, &interpolators));
+
         let gen = Self {
+            surface_materializer: SurfaceMaterializer::new(palette.clone()),
             palette,
             biome_palette,
-            biome_index,
+            biome_source,
+            aquifer_rng,
+            ore_rng,
+            tapes,
         };
 
         (gen, noise_settings)
     }
 
-    pub fn do_fill_biomes<'a>(&self, noise_settings: &NoiseSettings, chunk_column: &'a mut ChunkColumn) -> BiomeSampler {
-        let chunkpos = chunk_column.pos();
-        let p_188006_ = quart_pos_from_block(chunkpos.get_min_block_x());
-        let p_188007_ = quart_pos_from_block(chunkpos.get_min_block_z());
+    /// Noise of `cell_count_xz` cells in both horizontal directions, starting
+    /// at the given block. The aquifer is the one of the chunk at `pos`. The
+    /// beardifier is added to the final density, as vanilla does.
+    fn noise_chunk(
+        &self,
+        noise_settings: &NoiseSettings,
+        beardifier: Beardifier,
+        pos: ChunkPos,
+        start_x: i32,
+        start_z: i32,
+        cell_count_xz: u32,
+    ) -> NoiseChunk {
+        let cell_width = noise_settings.cell_width() as i32;
+        let cell_height = noise_settings.cell_height() as i32;
 
-        let biome_sampler = BiomeSampler::new(
+        let mut ctx = DensityFunctionContext::default();
+
+        ctx.cell_width = noise_settings.cell_width();
+        ctx.cell_height = noise_settings.cell_height();
+        ctx.cell_count_xz = cell_count_xz;
+        ctx.cell_count_y = floor_div(
+            noise_settings.noise_height as i32,
+            noise_settings.cell_height() as i32,
+        ) as u32;
+        ctx.cell_noise_min_y = floor_div(noise_settings.noise_min_y, cell_height);
+        ctx.first_cell_x = floor_div(start_x, cell_width);
+        ctx.first_cell_z = floor_div(start_z, cell_width);
+        ctx.first_noise_x = quart_pos_from_block(start_x);
+        ctx.first_noise_z = quart_pos_from_block(start_z);
+        ctx.noise_size_xz = quart_pos_from_block(ctx.cell_count_xz as i32 * cell_width) as u32;
+
+        let (router, interpolators) = chunk_router(&noise_settings.router, beardifier.clone());
+
+        let picker = FluidPicker::create(noise_settings, &self.palette);
+        let aquifer: Box<dyn Aquifer> = if noise_settings.aquifers_enabled {
+            Box::new(NoiseBasedAquifer::new(picker, &router, self.aquifer_rng.clone(), pos, noise_settings))
+        } else {
+            Box::new(DisabledAquifer::new(picker))
+        };
+
+        let ore_veinifier = if noise_settings.ore_veins_enabled {
+            Some(OreVeinifier::new(
+                router.vein_toggle.clone(),
+                router.vein_ridged.clone(),
+                router.vein_gap.clone(),
+                self.ore_rng.clone(),
+                &self.palette
+            ))
+        } else {
+            None
+        };
+
+        NoiseChunk::new(
+            ctx,
+            router,
+            aquifer,
+            ore_veinifier,
+            interpolators,
+            &self.tapes,
+            beardifier,
+        )
+    }
+}
+
+/// Router of a noise chunk with the beardifier added to the final density, as
+/// vanilla does, and the interpolators it has.
+fn chunk_router(router: &NoiseRouter, beardifier: Beardifier) -> (NoiseRouter, Vec<InterpolatedInner>) {
+    let interpolated_collector = InterpolatedCollector::new();
+
+    let mut router = router.clone();
+    router.final_density = DensityFunctions::Add(Box::new(Add::new(
+        router.final_density,
+        DensityFunctions::Beardifier(Beardifier::default()),
+    )));
+
+    let router = router
+        .map(&BeardifierMapper::new(beardifier))
+        .map(&interpolated_collector);
+
+    (router, interpolated_collector.collected.into_inner())
+}
+
+impl ChunkGenerator for NoiseBasedChunkGenerator {
+    fn biome_sampler(&self, noise_settings: &NoiseSettings) -> BiomeSampler {
+        BiomeSampler::new(
             self.biome_palette.clone(),
-            self.biome_index.clone(),
-            ClimateSampler::new(
-                noise_settings.router.temperature.clone(),
-                noise_settings.router.vegetation.clone(),
-                noise_settings.router.continents.clone(),
-                noise_settings.router.erosion.clone(),
-                noise_settings.router.depth.clone(),
-                noise_settings.router.ridges.clone(),
-            ),
-        );
+            self.biome_source.clone(),
+            climate_sampler(noise_settings),
+        )
+    }
 
-        // let now = Instant::now();
-        // for i in 0..100000 {
-        //     let _ = black_box(biome_sampler.sample(&Vector3::new(0, 67, i)));
-        // }
-        // println!("NOW TIME: {:?}", now.elapsed());
+    /// Takes the first y above the top block, as the noise of the column has
+    /// it.
+    fn base_height(&self, noise_settings: &NoiseSettings, x: i32, z: i32, ty: HeightmapType) -> i32 {
+        let cell_width = noise_settings.cell_width() as i32;
+        let cell_height = noise_settings.cell_height() as i32;
+        let lowest_cell_y = floor_div(noise_settings.noise_min_y, cell_height);
+        let cell_count_y = floor_div(noise_settings.noise_height as i32, cell_height);
+
+        let start_x = floor_div(x, cell_width) * cell_width;
+        let start_z = floor_div(z, cell_width) * cell_width;
+        let x_fraction = floor_mod(x, cell_width) as f64 / cell_width as f64;
+        let z_fraction = floor_mod(z, cell_width) as f64 / cell_width as f64;
+
+        let mut chunk = self.noise_chunk(
+            noise_settings,
+            Beardifier::default(),
+            ChunkPos::new(start_x >> 4, start_z >> 4),
+            start_x,
+            start_z,
+            1,
+        );
+        chunk.advance_cell_x(0);
 
-        for section in chunk_column.sections() {
-            let section = unsafe { section.unguarded.as_mut().unwrap() };
+        for cell_y in (0..cell_count_y).rev() {
+            chunk.select_cell_yz(cell_y, 0);
 
-            let bottom = (section.idx() as i32) - 4;
+            for in_cell_y in (0..cell_height).rev() {
+                let y = (lowest_cell_y + cell_y) * cell_height + in_cell_y;
 
-            let i = quart_pos_from_block(bottom << 4);
+                chunk.update_for_y(y, in_cell_y as f64 / cell_height as f64);
+                chunk.update_for_x(x, x_fraction);
+                chunk.update_for_z(z, z_fraction);
 
-            for k in 0..4 {
-                for l in 0..4 {
-                    for i1 in 0..4 {
-                        let x = biome_sampler.sample(
-                            &Vector3::new(p_188006_ + k as i32, i + l as i32, p_188007_ + i1 as i32)
-                        );
+                let state = chunk
+                    .calculate_interpolated_state()
+                    .unwrap_or_else(|| noise_settings.default_block.clone());
 
-                        section.set_biome(
-                            Vector3u::new(k, l, i1),
-                            x,
-                        );
-                    }
+                if ty.is_opaque(&state) {
+                    chunk.stop_interpolation();
+                    return y + 1
                 }
             }
         }
 
-        chunk_column.inner_mut().status = ChunkStatus::Biomes;
+        chunk.stop_interpolation();
 
-        biome_sampler
+        noise_settings.noise_min_y
     }
 
-    pub fn do_fill_noise<'a>(&self, noise_settings: &NoiseSettings, chunk_column: &'a mut ChunkColumn, lowest_cell_y: i32, cells_per_chunk_y: i32) -> NoiseChunk {
+    fn fill_noise(&self, noise_settings: &NoiseSettings, beardifier: Beardifier, chunk_column: &mut ChunkColumn) -> Option<NoiseChunk> {
         let pos = chunk_column.pos();
 
         let min_chunk_x = pos.get_min_block_x();
@@ -120,37 +364,10 @@ impl NoiseBasedChunkGenerator {
 
         let cell_width = noise_settings.cell_width() as i32;
         let cell_height = noise_settings.cell_height() as i32;
+        let lowest_cell_y = floor_div(noise_settings.noise_min_y, cell_height);
+        let cells_per_chunk_y = floor_div(noise_settings.noise_height as i32, cell_height);
 
-        let mut ctx = DensityFunctionContext::default();
-
-        ctx.cell_width = noise_settings.cell_width();
-        ctx.cell_height = noise_settings.cell_height();
-        ctx.cell_count_xz = (16 / cell_width) as u32;
-        ctx.cell_count_y = floor_div(
-            noise_settings.noise_height as i32,
-            noise_settings.cell_height() as i32,
-        ) as u32;
-        ctx.cell_noise_min_y = floor_div(noise_settings.noise_min_y, cell_height);
-        ctx.first_cell_x = floor_div(min_chunk_x, cell_width);
-        ctx.first_cell_z = floor_div(min_chunk_z, cell_width);
-        ctx.first_noise_x = quart_pos_from_block(min_chunk_x);
-        ctx.first_noise_z = quart_pos_from_block(min_chunk_z);
-        ctx.noise_size_xz = quart_pos_from_block(ctx.cell_count_xz as i32 * cell_width) as u32;
-
-        let interpolated_collector = InterpolatedCollector::new();
-
-        let router = noise_settings.router.clone().map(&interpolated_collector);
-
-        let interpolators = interpolated_collector.collected.borrow().clone();
-
-        let aquifer = DisabledAquifer::new(FluidPicker::create(noise_settings, &self.palette));
-
-        let mut chunk = NoiseChunk::new(
-            ctx,
-            router,
-            Box::new(aquifer),
-            interpolators,
-        );
+        let mut chunk = self.noise_chunk(noise_settings, beardifier, pos, min_chunk_x, min_chunk_z, (16 / cell_width) as u32);
 
         let cells_per_chunk_xz = 16 / cell_width;
 
@@ -202,22 +419,14 @@ impl NoiseBasedChunkGenerator {
                                 };
 
                                 if block_state.block() != Block::AIR {
-                                    let section = unsafe {
-                                        chunk_column
-                                            .section(section_index as usize)
-                                            .unguarded
-                                            .as_mut()
-                                            .unwrap()
-                                    };
-
-                                    section.set_block_state(
+                                    chunk_column.section_mut(section_index as usize).set_block_state(
                                         Vector3BlockSection::new(in_section_x, in_section_y, in_section_z),
                                         block_state.clone(),
                                     );
 
                                     let heightmap_pos = Vector3BlockColumn::new(in_section_x, y, in_section_z);
 
-                                    let chunk_column_ref = unsafe { chunk_column.with_unsafe() };
+                                    let chunk_column_ref = chunk_column.with_safe();
                                     ocean_floor_heightmap.update(&chunk_column_ref, heightmap_pos, block_state.as_ref());
                                     world_surface_heightmap.update(&chunk_column_ref, heightmap_pos, block_state.as_ref());
                                 }
@@ -232,17 +441,397 @@ impl NoiseBasedChunkGenerator {
 
         chunk.stop_interpolation();
 
-        chunk_column.inner_mut().heightmaps.ocean_floor_wg = Some(ocean_floor_heightmap);
-        chunk_column.inner_mut().heightmaps.world_surface_wg = Some(world_surface_heightmap);
+        finish_noise(chunk_column, ocean_floor_heightmap, world_surface_heightmap);
 
-        for i in 0..24 {
-            let section = unsafe { chunk_column.section(i).unguarded.as_mut().unwrap() };
+        Some(chunk)
+    }
+
+    fn build_surface(
+        &self,
+        noise_settings: &NoiseSettings,
+        entropy_bag: Arc<EntropyBag>,
+        rule_factory: Arc<RuleFactories>,
+        noise_chunk: &mut NoiseChunk,
+        biome_accessor: BiomeAccessor,
+        chunk_column: &mut ChunkColumn,
+    ) {
+        self.surface_materializer.materialize(
+            noise_settings,
+            entropy_bag,
+            rule_factory,
+            noise_chunk,
+            biome_accessor,
+            chunk_column,
+        );
+    }
+
+    /// Runs air carvers of all chunks within the carver range, which reach into
+    /// this chunk. Carvers are taken from the biome at the corner of the chunk
+    /// they start in, and each of them is seeded by its index in the biome list.
+    fn carve(
+        &self,
+        seed: i64,
+        noise_settings: &NoiseSettings,
+        entropy_bag: Arc<EntropyBag>,
+        rule_factory: Arc<RuleFactories>,
+        carvers: &CarverRegistry,
+        noise_chunk: &mut NoiseChunk,
+        biome_accessor: BiomeAccessor,
+        chunk_column: &mut ChunkColumn,
+    ) {
+        let pos = chunk_column.pos();
+        let mask = chunk_column
+            .inner_mut()
+            .carving_mask
+            .take()
+            .unwrap_or_else(|| CarvingMask::new(noise_settings.noise_height as i32, noise_settings.noise_min_y));
+
+        let biome_gradient = BiomeGradient::with_hashed_seed(1, &biome_accessor);
+        let cached_biome_gradient = LazyCachedBiomeGradient::new(&biome_gradient);
+        let heightmaps = chunk_column.inner().heightmaps.clone();
+        let aquifer = noise_chunk.aquifer();
+
+        let mut ctx = CarvingContext::new(
+            WorldGenerationContext {
+                height: noise_settings.noise_height as i32,
+                min_y: noise_settings.noise_min_y,
+            },
+            chunk_column,
+            aquifer,
+            TopMaterial::new(
+                noise_settings,
+                entropy_bag,
+                &rule_factory,
+                noise_chunk,
+                &heightmaps,
+                &cached_biome_gradient,
+            ),
+            mask,
+            &self.palette,
+        );
+
+        for offset_x in -8..=8 {
+            for offset_z in -8..=8 {
+                let start = ChunkPos::new(pos.x() + offset_x, pos.z() + offset_z);
+                let biome = biome_accessor.sampler.sample(&Vector3::new(
+                    quart_pos_from_block(start.get_min_block_x()),
+                    0,
+                    quart_pos_from_block(start.get_min_block_z()),
+                ));
+
+                for (i, carver) in carvers.carvers(&biome).iter().enumerate() {
+                    let mut rng = large_feature_rng(seed.wrapping_add(i as i64), start.x(), start.z());
+
+                    if carver.is_start_chunk(&mut rng) {
+                        carver.carve(&mut ctx, &mut rng, &start);
+                    }
+                }
+            }
+        }
+
+        let mask = ctx.into_mask();
+
+        chunk_column.inner_mut().carving_mask = Some(mask);
+        chunk_column.inner_mut().status = ChunkStatus::Carvers;
+    }
+
+    fn decorate(
+        &self,
+        seed: i64,
+        noise_settings: &NoiseSettings,
+        features: &FeatureRegistry,
+        structures: &StructureRegistry,
+        starts: &dyn Fn(&ChunkPos) -> StructureStarts,
+        biome_sampler: &BiomeSampler,
+        neighbours: HashMap<ChunkPos, Neighbour>,
+        chunk_column: &mut ChunkColumn,
+    ) {
+        Decoration {
+            seed,
+            noise_settings,
+            palette: &self.palette,
+            features,
+            structures,
+            starts,
+            biome_sampler,
+        }
+            .decorate(neighbours, chunk_column, |_| true);
+    }
+}
+
+/// Climate of the noise router. Sampled only by the multi noise and the End
+/// biome sources.
+pub(crate) fn climate_sampler(noise_settings: &NoiseSettings) -> ClimateSampler {
+    ClimateSampler::new(
+        noise_settings.router.temperature.clone(),
+        noise_settings.router.vegetation.clone(),
+        noise_settings.router.continents.clone(),
+        noise_settings.router.erosion.clone(),
+        noise_settings.router.depth.clone(),
+        noise_settings.router.ridges.clone(),
+    )
+}
+
+/// Sets the biome of every quart of the chunk from the sampler.
+pub(crate) fn fill_biomes_from(biome_sampler: &BiomeSampler, chunk_column: &mut ChunkColumn) {
+    let chunkpos = chunk_column.pos();
+    let p_188006_ = quart_pos_from_block(chunkpos.get_min_block_x());
+    let p_188007_ = quart_pos_from_block(chunkpos.get_min_block_z());
+
+    for section_index in 0..chunk_column.sections().len() {
+        let section = chunk_column.section_mut(section_index);
+
+        let bottom = (section.idx() as i32) - 4;
 
-            section.sky_light = Some([u8::MAX; 2048]);
+        let i = quart_pos_from_block(bottom << 4);
+
+        for k in 0..4 {
+            for l in 0..4 {
+                for i1 in 0..4 {
+                    let x = biome_sampler.sample(
+                        &Vector3::new(p_188006_ + k as i32, i + l as i32, p_188007_ + i1 as i32)
+                    );
+
+                    section.set_biome(
+                        Vector3u::new(k, l, i1),
+                        x,
+                    );
+                }
+            }
         }
+    }
 
-        chunk_column.inner_mut().status = ChunkStatus::Noise;
+    chunk_column.inner_mut().status = ChunkStatus::Biomes;
+}
+
+/// Stores the heightmaps of the placed terrain and lights up the chunk.
+pub(crate) fn finish_noise(chunk_column: &mut ChunkColumn, ocean_floor: Heightmap, world_surface: Heightmap) {
+    chunk_column.inner_mut().heightmaps.ocean_floor_wg = Some(ocean_floor);
+    chunk_column.inner_mut().heightmaps.world_surface_wg = Some(world_surface);
+
+    for i in 0..24 {
+        chunk_column.section_mut(i).sky_light = Some([u8::MAX; 2048]);
+    }
+
+    chunk_column.inner_mut().status = ChunkStatus::Noise;
+}
+
+/// What the decoration of a chunk reads from.
+pub(crate) struct Decoration<'a> {
+    pub seed: i64,
+    pub noise_settings: &'a NoiseSettings,
+    pub palette: &'a Arc<BlockGlobalPalette>,
+    pub features: &'a FeatureRegistry,
+    pub structures: &'a StructureRegistry,
+    pub starts: &'a dyn Fn(&ChunkPos) -> StructureStarts,
+    pub biome_sampler: &'a BiomeSampler,
+}
 
-        chunk
+impl Decoration<'_> {
+    /// Places pieces of the referenced structures and features of the biomes
+    /// around the chunk, step by step. Within a step, structures go first, then
+    /// features run in their global order, each seeded by its index and the
+    /// decoration seed of the chunk. Features are placed only at the steps
+    /// `feature_step` accepts. The parts of features reaching into the
+    /// neighbours are placed, as long as the neighbours are writable.
+    pub fn decorate<F>(&self, neighbours: HashMap<ChunkPos, Neighbour>, chunk_column: &mut ChunkColumn, feature_step: F)
+    where
+        F: Fn(usize) -> bool
+    {
+        let pos = chunk_column.pos();
+        let origin = Vector3::new(pos.get_min_block_x(), chunk_column.min_build_height(), pos.get_min_block_z());
+        let references = chunk_column.inner().structure_references.clone();
+        let chunk_box = BoundingBox::for_chunk(
+            &pos,
+            self.noise_settings.noise_min_y + 1,
+            self.noise_settings.noise_min_y + self.noise_settings.noise_height as i32 - 1,
+        );
+
+        let mut level = WorldGenLevel::new(
+            self.seed,
+            WorldGenerationContext {
+                height: self.noise_settings.noise_height as i32,
+                min_y: self.noise_settings.noise_min_y,
+            },
+            chunk_column,
+            neighbours,
+            self.biome_sampler,
+            self.features,
+            self.palette,
+        );
+
+        let biomes = level.biomes_around();
+        let decoration_seed = decoration_seed(self.seed, origin.x, origin.z);
+
+        for step in 0..DECORATION_STEPS.len().max(self.features.step_count()) {
+            for (index, name) in self.structures.structures_in_step(step).iter().enumerate() {
+                let Some(references) = references.get(name) else {
+                    continue
+                };
+
+                let mut rng = feature_rng(decoration_seed, index, step);
+
+                for start_pos in references {
+                    for start in (self.starts)(start_pos).iter().filter(|start| start.structure.name == *name) {
+                        start.place_in_chunk(&mut level, &mut rng, self.palette, &chunk_box);
+                    }
+                }
+            }
+
+            if step >= self.features.step_count() || !feature_step(step) {
+                continue
+            }
+
+            let indices = biomes
+                .iter()
+                .flat_map(|biome| self.features.biome_step(biome, step))
+                .copied()
+                .collect::<BTreeSet<_>>();
+
+            for index in indices {
+                let feature = self.features.feature(step, index);
+                let Some(placed) = &feature.feature else {
+                    continue
+                };
+
+                let mut rng = feature_rng(decoration_seed, index, step);
+                placed.place_with_biome_check(&feature.name, &mut level, &mut rng, origin);
+            }
+        }
+
+        chunk_column.inner_mut().status = ChunkStatus::Features;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::generator::chunk_router;
+    use crate::noise::density::beardifier::Beardifier;
+    use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions};
+    use crate::noise::density::maker::InterpolatedInner;
+    use crate::noise::density::tape::{Program, Stage, Tape};
+    use crate::noise::math::floor_div;
+    use crate::noise::settings::NoiseSettings;
+    use crate::world::WorldGenerator;
+    use spherix_world::dimension::DimensionKind;
+    use std::mem;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    /// Values of the slices and of the cells of the chunk at the origin,
+    /// sampled through the tapes if given, through the tree otherwise.
+    fn sample_chunk(
+        noise_settings: &NoiseSettings,
+        final_density: &DensityFunctions,
+        interpolators: &[InterpolatedInner],
+        mut tapes: Option<(&mut [Program], &mut Program)>,
+    ) -> Vec<f64> {
+        let mut ctx = DensityFunctionContext {
+            cell_width: noise_settings.cell_width(),
+            cell_height: noise_settings.cell_height(),
+            cell_count_xz: 16 / noise_settings.cell_width(),
+            cell_count_y: noise_settings.noise_height / noise_settings.cell_height(),
+            cell_noise_min_y: floor_div(noise_settings.noise_min_y, noise_settings.cell_height() as i32),
+            interpolating: true,
+            interpolators: interpolators.iter().map(InterpolatedInner::state).collect(),
+            ..Default::default()
+        };
+        let cell_width = ctx.cell_width as i32;
+        let cell_height = ctx.cell_height as i32;
+
+        let mut values = Vec::new();
+
+        for x in 0..ctx.cell_count_xz as i32 {
+            for (slice_x, use_first_slice) in [(x, true), (x + 1, false)] {
+                ctx.cell_start_block_x = slice_x * cell_width;
+                ctx.in_cell_x = 0;
+
+                for z in 0..=ctx.cell_count_xz as usize {
+                    ctx.cell_start_block_z = z as i32 * cell_width;
+                    ctx.in_cell_z = 0;
+                    ctx.array_interpolation_counter += 1;
+
+                    for (i, interpolator) in interpolators.iter().enumerate() {
+                        let mut state = mem::take(&mut ctx.interpolators[interpolator.index]);
+                        let slice = match use_first_slice {
+                            true => &mut state.slice0[z],
+                            false => &mut state.slice1[z],
+                        };
+
+                        ctx.filler = ContextFiller::Slice;
+                        match &mut tapes {
+                            Some((slices, _)) => slices[i].fill(slice, &mut ctx),
+                            None => interpolator.fill_slice(slice, &mut ctx),
+                        }
+                        ctx.filler = ContextFiller::Default;
+
+                        values.extend_from_slice(slice);
+                        ctx.interpolators[interpolator.index] = state;
+                    }
+                }
+
+                ctx.array_interpolation_counter += 1;
+            }
+
+            ctx.cell_start_block_x = x * cell_width;
+
+            for z in 0..ctx.cell_count_xz as usize {
+                for y in 0..ctx.cell_count_y as usize {
+                    ctx.interpolators.iter_mut().for_each(|state| state.select_cell_yz(y, z));
+
+                    ctx.filling_cell = true;
+                    ctx.cell_start_block_y = (y as i32 + ctx.cell_noise_min_y) * cell_height;
+                    ctx.cell_start_block_z = z as i32 * cell_width;
+                    ctx.array_interpolation_counter += 1;
+
+                    let mut cell = vec![0.0; (cell_width * cell_width * cell_height) as usize];
+                    match &mut tapes {
+                        Some((_, program)) => program.fill(&mut cell, &mut ctx),
+                        None => final_density.fill_array(&mut cell, &mut ctx),
+                    }
+
+                    ctx.array_interpolation_counter += 1;
+                    ctx.filling_cell = false;
+
+                    values.extend(cell);
+                }
+            }
+        }
+
+        values
+    }
+
+    #[test]
+    #[ignore = "needs the vanilla data generated into ./generated"]
+    fn tapes_match_tree_over_a_chunk() {
+        for dimension in [DimensionKind::Overworld, DimensionKind::TheNether, DimensionKind::TheEnd] {
+            let generator = WorldGenerator::builder()
+                .data_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../generated"))
+                .seed(1)
+                .dimension(dimension)
+                .build()
+                .unwrap();
+
+            let noise_settings = generator.noise_settings();
+            let (router, interpolators) = chunk_router(&noise_settings.router, Beardifier::default());
+
+            let program = |df: &DensityFunctions, stage: Stage| Program::new(
+                Arc::new(Tape::compile(df, stage).unwrap()),
+                Beardifier::default(),
+            );
+            let mut slices = interpolators
+                .iter()
+                .map(|interpolator| program(&interpolator.argument, Stage::Slice))
+                .collect::<Vec<_>>();
+            let mut cell = program(&router.final_density, Stage::Cell);
+
+            let expected = sample_chunk(noise_settings, &router.final_density, &interpolators, None);
+            let actual = sample_chunk(noise_settings, &router.final_density, &interpolators, Some((slices.as_mut_slice(), &mut cell)));
+
+            assert_eq!(
+                expected.iter().map(|val| val.to_bits()).collect::<Vec<_>>(),
+                actual.iter().map(|val| val.to_bits()).collect::<Vec<_>>(),
+            );
+        }
     }
 }
@@ -1,3 +1,6 @@
 pub mod noise;
 pub mod generator;
 pub mod column;
+pub mod flat;
+pub mod void;
+pub mod step;
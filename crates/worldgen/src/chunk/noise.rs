@@ -1,41 +1,108 @@
 use crate::aquifer::Aquifer;
+use crate::material::{AquiferFiller, BlockStateFiller, MaterialRuleList};
+use crate::noise::density::beardifier::Beardifier;
 use crate::noise::density::cache::CacheAllInCell;
-use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions};
+use crate::noise::density::density::{ContextFiller, DensityFunctionContext, DensityFunctions};
 use crate::noise::density::maker::InterpolatedInner;
+use crate::noise::density::tape::{Program, Stage, Tape};
 use crate::noise::router::NoiseRouter;
-use spherix_math::vector::Vector3;
+use crate::ore_vein::OreVeinifier;
+use log::warn;
 use spherix_world::block::state::BlockState;
 use std::cell::RefCell;
+use std::mem;
 use std::rc::Rc;
 use std::sync::Arc;
 
+/// Tapes of the functions a noise chunk evaluates. The router of the noise
+/// chunks differs only by the beardifier, so they are compiled once per
+/// generator and shared.
+pub struct NoiseTapes {
+    /// Arguments of the interpolators, in the order
+    /// [`crate::noise::density::density::InterpolatedCollector`] numbers them.
+    slices: Vec<Option<Arc<Tape>>>,
+    final_density: Option<Arc<Tape>>,
+}
+
+impl NoiseTapes {
+    /// Functions, which can not be compiled, are evaluated through the tree.
+    pub fn compile(final_density: &DensityFunctions, interpolators: &[InterpolatedInner]) -> Self {
+        let compile = |df: &DensityFunctions, stage: Stage| match Tape::compile(df, stage) {
+            Ok(tape) => Some(Arc::new(tape)),
+            Err(e) => {
+                warn!("Density function is sampled through the tree: {}", e);
+
+                None
+            }
+        };
+
+        Self {
+            slices: interpolators
+                .iter()
+                .map(|interpolator| compile(&interpolator.argument, Stage::Slice))
+                .collect(),
+            final_density: compile(final_density, Stage::Cell),
+        }
+    }
+}
+
+/// Evaluation of the noise router over a chunk. Functions of the router are
+/// shared, the states of the interpolators and of the cell caches are in the
+/// context.
 pub struct NoiseChunk {
     pub ctx: DensityFunctionContext,
-    aquifer: Box<dyn Aquifer>,
-    interpolators: Vec<Rc<RefCell<InterpolatedInner>>>,
-    cell_caches: Vec<Rc<RefCell<CacheAllInCell>>>,
+    /// Aquifer followed by the ore veinifier (if ore veins are enabled).
+    block_state_rule: MaterialRuleList,
+    aquifer: Rc<RefCell<Box<dyn Aquifer>>>,
+    interpolators: Vec<InterpolatedInner>,
+    cell_caches: Vec<CacheAllInCell>,
+    /// Compiled arguments of the interpolators, if they could be compiled.
+    slice_programs: Vec<Option<Program>>,
+    /// Compiled functions of the cell caches, if they could be compiled.
+    cell_programs: Vec<Option<Program>>,
     pub df: DensityFunctions,
 }
 
 impl NoiseChunk {
+    /// The interpolators are the ones of the router, numbered by
+    /// [`crate::noise::density::density::InterpolatedCollector`]. The tapes
+    /// are evaluated with the beardifier of the chunk.
     pub fn new(
-        ctx: DensityFunctionContext,
+        mut ctx: DensityFunctionContext,
         router: NoiseRouter,
         aquifer: Box<dyn Aquifer>,
-        interpolators: Vec<Rc<RefCell<InterpolatedInner>>>,
+        ore_veinifier: Option<OreVeinifier>,
+        interpolators: Vec<InterpolatedInner>,
+        tapes: &NoiseTapes,
+        beardifier: Beardifier,
     ) -> Self {
-        let cache_cell = Rc::new(
-            RefCell::new(
-                CacheAllInCell::new(router.final_density, ctx.cell_width, ctx.cell_height)
-            )
-        );
+        let program = |tape: &Option<Arc<Tape>>| tape.clone().map(|tape| Program::new(tape, beardifier.clone()));
+        let slice_programs = tapes.slices.iter().map(program).collect();
+        let cell_programs = vec![program(&tapes.final_density)];
+
+        let cache_cell = CacheAllInCell::new(router.final_density, 0);
+
+        ctx.interpolators = interpolators.iter().map(InterpolatedInner::state).collect();
+        ctx.cell_caches = vec![vec![0.0; (ctx.cell_width * ctx.cell_width * ctx.cell_height) as usize]];
+
+        let aquifer = Rc::new(RefCell::new(aquifer));
+
+        let mut fillers: Vec<Box<dyn BlockStateFiller>> = vec![
+            Box::new(AquiferFiller::new(DensityFunctions::CacheAllInCell(Box::new(cache_cell.clone())), aquifer.clone()))
+        ];
+        if let Some(ore_veinifier) = ore_veinifier {
+            fillers.push(Box::new(ore_veinifier));
+        }
 
         let mut chunk = Self {
             ctx,
+            block_state_rule: MaterialRuleList::new(fillers),
             aquifer,
             interpolators,
             cell_caches: vec![cache_cell.clone()],
-            df: DensityFunctions::CacheAllInCell(cache_cell),
+            slice_programs,
+            cell_programs,
+            df: DensityFunctions::CacheAllInCell(Box::new(cache_cell)),
         };
 
         chunk.initialize_for_first_cell_x();
@@ -43,25 +110,14 @@ impl NoiseChunk {
         chunk
     }
     
-    fn vector(&self) -> Vector3 {
-        Vector3::new(
-            self.ctx.cell_start_block_x + self.ctx.in_cell_x as i32,
-            self.ctx.cell_start_block_y + self.ctx.in_cell_y as i32,
-            self.ctx.cell_start_block_z + self.ctx.in_cell_z as i32,
-        )
-    }
-
     pub fn calculate_interpolated_state(&mut self) -> Option<Arc<BlockState>> {
-        // self.ctx.debug_tree.reset();
-
-        let sampled = self.df.sample(
-            self.vector(),
-            &mut self.ctx,
-        );
-
-        // println!("{}", self.ctx.debug_tree);
+        self.block_state_rule.calculate(&mut self.ctx)
+    }
 
-        self.aquifer.compute(&self.ctx, sampled)
+    /// Aquifer which has filled the chunk. Carvers use it to flood carved
+    /// blocks consistently with the noise stage.
+    pub fn aquifer(&self) -> Rc<RefCell<Box<dyn Aquifer>>> {
+        self.aquifer.clone()
     }
 
     fn initialize_for_first_cell_x(&mut self) {
@@ -79,19 +135,25 @@ impl NoiseChunk {
             self.ctx.in_cell_z = 0;
             self.ctx.array_interpolation_counter += 1;
 
-            for interpolator in self.interpolators.iter_mut() {
-                let mut interpolator = interpolator.borrow_mut();
+            for (interpolator, program) in self.interpolators.iter().zip(self.slice_programs.iter_mut()) {
+                // Interpolators pass the sampling through to their arguments
+                // while the slices are filled, so the state is not read.
+                let mut state = mem::take(&mut self.ctx.interpolators[interpolator.index]);
+                let slice = match use_first_slice {
+                    true => &mut state.slice0[i as usize],
+                    false => &mut state.slice1[i as usize],
+                };
 
                 let prev_filler = self.ctx.filler.clone();
                 self.ctx.filler = ContextFiller::Slice;
 
-                if use_first_slice {
-                    interpolator.fill_slice0(i as usize, &mut self.ctx)
-                } else {
-                    interpolator.fill_slice1(i as usize, &mut self.ctx)
+                match program {
+                    Some(program) => program.fill(slice, &mut self.ctx),
+                    None => interpolator.fill_slice(slice, &mut self.ctx),
                 }
 
                 self.ctx.filler = prev_filler;
+                self.ctx.interpolators[interpolator.index] = state;
             }
         }
 
@@ -104,17 +166,23 @@ impl NoiseChunk {
     }
 
     pub fn select_cell_yz(&mut self, y: i32, z: i32) {
-        self.interpolators.iter_mut().for_each(|interpolator| {
-            interpolator.borrow_mut().select_cell_yz(y as usize, z as usize)
-        });
+        self.ctx.interpolators.iter_mut().for_each(|state| state.select_cell_yz(y as usize, z as usize));
 
         self.ctx.filling_cell = true;
         self.ctx.cell_start_block_y = (y + self.ctx.cell_noise_min_y) * self.ctx.cell_height as i32;
         self.ctx.cell_start_block_z = (self.ctx.first_cell_z + z) * self.ctx.cell_width as i32;
         self.ctx.array_interpolation_counter += 1;
 
-        for cell_cache in self.cell_caches.iter() {
-            cell_cache.borrow_mut().fill_array_inner(&mut self.ctx);
+        for (cell_cache, program) in self.cell_caches.iter().zip(self.cell_programs.iter_mut()) {
+            // The cached function does not read its own values.
+            let mut values = mem::take(&mut self.ctx.cell_caches[cell_cache.index]);
+
+            match program {
+                Some(program) => program.fill(&mut values, &mut self.ctx),
+                None => cell_cache.inner.fill_array(&mut values, &mut self.ctx),
+            }
+
+            self.ctx.cell_caches[cell_cache.index] = values;
         }
 
         self.ctx.array_interpolation_counter += 1;
@@ -123,30 +191,22 @@ impl NoiseChunk {
 
     pub fn update_for_y(&mut self, y: i32, interpolated: f64) {
         self.ctx.in_cell_y = (y - self.ctx.cell_start_block_y) as u32;
-        self.interpolators.iter_mut().for_each(|interpolator| {
-            interpolator.borrow_mut().update_for_y(interpolated);
-        });
+        self.ctx.interpolators.iter_mut().for_each(|state| state.update_for_y(interpolated));
     }
 
     pub fn update_for_x(&mut self, x: i32, interpolated: f64) {
         self.ctx.in_cell_x = (x - self.ctx.cell_start_block_x) as u32;
-        self.interpolators.iter_mut().for_each(|interpolator| {
-            interpolator.borrow_mut().update_for_x(interpolated);
-        });
+        self.ctx.interpolators.iter_mut().for_each(|state| state.update_for_x(interpolated));
     }
 
     pub fn update_for_z(&mut self, z: i32, interpolated: f64) {
         self.ctx.in_cell_z = (z - self.ctx.cell_start_block_z) as u32;
         self.ctx.interpolation_counter += 1;
-        self.interpolators.iter_mut().for_each(|interpolator| {
-            interpolator.borrow_mut().update_for_z(interpolated);
-        });
+        self.ctx.interpolators.iter_mut().for_each(|state| state.update_for_z(interpolated));
     }
 
     pub fn swap_slices(&mut self) {
-        self.interpolators
-            .iter()
-            .for_each(|interpolator| interpolator.borrow_mut().swap_slices())
+        self.ctx.interpolators.iter_mut().for_each(|state| state.swap_slices())
     }
 
     pub fn stop_interpolation(&mut self) {
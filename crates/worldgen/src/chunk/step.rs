@@ -0,0 +1,128 @@
+use spherix_world::chunk::status::ChunkStatus;
+
+/// Step of the generation, which promotes a chunk to the status. Steps read
+/// the chunks within the radius around (a square of `2 * radius + 1` chunks on
+/// a side), which must have reached the neighbour status first. Features write
+/// into them as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkStep {
+    pub status: ChunkStatus,
+    pub radius: i32,
+    pub neighbour_status: ChunkStatus,
+}
+
+/// Steps of the generation in the order they run. Stages, which share the
+/// state of the chunk (the noise chunk of the noise, surface and carvers), run
+/// as one step.
+pub const CHUNK_STEPS: [ChunkStep; 4] = [
+    // Structure starts and references, then the biomes. Starts of the chunks
+    // around come from the structure start cache, not from the chunks.
+    ChunkStep {
+        status: ChunkStatus::Biomes,
+        radius: 0,
+        neighbour_status: ChunkStatus::Empty,
+    },
+    // The surface blends the biomes of the neighbours.
+    ChunkStep {
+        status: ChunkStatus::Carvers,
+        radius: 1,
+        neighbour_status: ChunkStatus::Biomes,
+    },
+    // Features read and write the blocks of the carved neighbours.
+    ChunkStep {
+        status: ChunkStatus::Features,
+        radius: 1,
+        neighbour_status: ChunkStatus::Carvers,
+    },
+    // Nothing is left to do, but the features of the neighbours may still
+    // write into the chunk until they are placed.
+    ChunkStep {
+        status: ChunkStatus::Full,
+        radius: 1,
+        neighbour_status: ChunkStatus::Features,
+    },
+];
+
+impl ChunkStep {
+    /// The step, which follows the status, or `None` if the chunk is fully
+    /// generated.
+    pub fn after(status: ChunkStatus) -> Option<&'static ChunkStep> {
+        CHUNK_STEPS.iter().find(|step| step.status.ordinal() > status.ordinal())
+    }
+
+    /// Whether no step follows the status. Chunks saved in the middle of the
+    /// generation (vanilla keeps them at the border of the explored area) are
+    /// not generated yet.
+    pub fn is_generated(status: ChunkStatus) -> bool {
+        Self::after(status).is_none()
+    }
+
+    /// Radius of the chunks the step writes. Only features write into the
+    /// chunks around, the other steps write their own chunk only.
+    pub fn write_radius(&self) -> i32 {
+        match self.status {
+            ChunkStatus::Features => self.radius,
+            _ => 0,
+        }
+    }
+
+    /// Radius around a chunk, whose chunks are generated to some status before
+    /// the chunk reaches the status.
+    pub fn dependency_radius(status: ChunkStatus) -> i32 {
+        CHUNK_STEPS
+            .iter()
+            .filter(|step| step.status.ordinal() <= status.ordinal())
+            .map(|step| step.radius + Self::dependency_radius(step.neighbour_status))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The widest radius of the steps, i.e. how far apart two steps have to run
+    /// so that they never touch the same chunk.
+    pub fn max_radius() -> i32 {
+        CHUNK_STEPS.iter().map(|step| step.radius).max().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::step::{ChunkStep, CHUNK_STEPS};
+    use spherix_world::chunk::status::ChunkStatus;
+
+    #[test]
+    fn steps_depend_on_earlier_statuses() {
+        let mut previous = ChunkStatus::Empty;
+
+        for step in CHUNK_STEPS {
+            assert!(step.status.ordinal() > previous.ordinal());
+            assert!(step.neighbour_status.ordinal() < step.status.ordinal());
+
+            previous = step.status;
+        }
+    }
+
+    #[test]
+    fn steps_follow_statuses() {
+        assert_eq!(ChunkStep::after(ChunkStatus::Empty).unwrap().status, ChunkStatus::Biomes);
+        assert_eq!(ChunkStep::after(ChunkStatus::Biomes).unwrap().status, ChunkStatus::Carvers);
+        assert_eq!(ChunkStep::after(ChunkStatus::Noise).unwrap().status, ChunkStatus::Carvers);
+        assert_eq!(ChunkStep::after(ChunkStatus::Features).unwrap().status, ChunkStatus::Full);
+        assert!(ChunkStep::after(ChunkStatus::Full).is_none());
+
+        assert!(!ChunkStep::is_generated(ChunkStatus::Noise));
+        assert!(!ChunkStep::is_generated(ChunkStatus::Features));
+        assert!(ChunkStep::is_generated(ChunkStatus::Full));
+    }
+
+    #[test]
+    fn features_depend_on_two_chunks_around() {
+        assert_eq!(ChunkStep::dependency_radius(ChunkStatus::Biomes), 0);
+        assert_eq!(ChunkStep::dependency_radius(ChunkStatus::Carvers), 1);
+        assert_eq!(ChunkStep::dependency_radius(ChunkStatus::Features), 2);
+        assert_eq!(ChunkStep::dependency_radius(ChunkStatus::Full), 3);
+        assert_eq!(ChunkStep::max_radius(), 1);
+
+        assert_eq!(ChunkStep::after(ChunkStatus::Noise).unwrap().write_radius(), 0);
+        assert_eq!(ChunkStep::after(ChunkStatus::Carvers).unwrap().write_radius(), 1);
+    }
+}
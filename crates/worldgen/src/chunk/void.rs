@@ -0,0 +1,79 @@
+use crate::biome::sampler::BiomeSampler;
+use crate::biome::source::BiomeSource;
+use crate::chunk::column::{ChunkColumn, Neighbour};
+use crate::chunk::generator::{climate_sampler, finish_noise, ChunkGenerator};
+use crate::chunk::noise::NoiseChunk;
+use crate::feature::registry::FeatureRegistry;
+use crate::noise::density::beardifier::Beardifier;
+use crate::noise::settings::NoiseSettings;
+use crate::structure::registry::StructureRegistry;
+use crate::structure::StructureStarts;
+use spherix_world::chunk::heightmap::{Heightmap, HeightmapType};
+use spherix_world::chunk::palette::BiomeGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::status::ChunkStatus;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Generates empty chunks of the void biome. Unlike the vanilla void preset,
+/// there is neither the starting platform nor any structures.
+#[derive(Clone)]
+pub struct VoidChunkGenerator {
+    biome_palette: Arc<BiomeGlobalPalette>,
+}
+
+impl VoidChunkGenerator {
+    pub const BIOME: &'static str = "minecraft:the_void";
+
+    pub fn new(biome_palette: Arc<BiomeGlobalPalette>) -> Self {
+        Self {
+            biome_palette,
+        }
+    }
+}
+
+impl ChunkGenerator for VoidChunkGenerator {
+    fn biome_sampler(&self, noise_settings: &NoiseSettings) -> BiomeSampler {
+        BiomeSampler::new(
+            self.biome_palette.clone(),
+            BiomeSource::Fixed(Self::BIOME.to_owned()),
+            climate_sampler(noise_settings),
+        )
+    }
+
+    fn base_height(&self, _: &NoiseSettings, _: i32, _: i32, _: HeightmapType) -> i32 {
+        -64
+    }
+
+    fn create_structures(&self, _: &StructureStarts, chunk_column: &mut ChunkColumn) {
+        chunk_column.inner_mut().status = ChunkStatus::StructureStarts;
+    }
+
+    fn create_references(&self, _: &dyn Fn(&ChunkPos) -> StructureStarts, chunk_column: &mut ChunkColumn) {
+        chunk_column.inner_mut().status = ChunkStatus::StructureReferences;
+    }
+
+    fn fill_noise(&self, _: &NoiseSettings, _: Beardifier, chunk_column: &mut ChunkColumn) -> Option<NoiseChunk> {
+        finish_noise(
+            chunk_column,
+            Heightmap::new(HeightmapType::OceanFloorWg, 384, -64),
+            Heightmap::new(HeightmapType::WorldSurfaceWg, 384, -64),
+        );
+
+        None
+    }
+
+    fn decorate(
+        &self,
+        _: i64,
+        _: &NoiseSettings,
+        _: &FeatureRegistry,
+        _: &StructureRegistry,
+        _: &dyn Fn(&ChunkPos) -> StructureStarts,
+        _: &BiomeSampler,
+        _: HashMap<ChunkPos, Neighbour>,
+        chunk_column: &mut ChunkColumn,
+    ) {
+        chunk_column.inner_mut().status = ChunkStatus::Features;
+    }
+}
@@ -0,0 +1,76 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::predicate::{BlockPredicate, BlockPredicates};
+use crate::feature::state_provider::RuleBasedStateProvider;
+use crate::feature::{Feature, FeatureRng};
+use crate::noise::json::Resolver;
+use crate::provider::IntProvider;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+
+/// Flat disk of blocks, e.g. sand or clay under water. Columns of the disk
+/// replace the blocks matching the target around the origin.
+pub struct DiskFeature {
+    pub state_provider: RuleBasedStateProvider,
+    pub target: BlockPredicates,
+    pub radius: IntProvider,
+    pub half_height: i32,
+}
+
+impl DiskFeature {
+    pub fn from_json(
+        json: &Value,
+        palette: &BlockGlobalPalette,
+        predicates: &Resolver<BlockPredicates>,
+    ) -> anyhow::Result<Self> {
+        let field = |name: &str| json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json));
+
+        Ok(Self {
+            state_provider: RuleBasedStateProvider::from_json(field("state_provider")?, palette, predicates)?,
+            target: predicates.resolve(field("target")?)?,
+            radius: IntProvider::from_json(field("radius")?)?,
+            half_height: field("half_height")?
+                .as_i64()
+                .ok_or_else(|| anyhow!("Expected integer \"half_height\" in {:?}", json))? as i32,
+        })
+    }
+
+    fn place_column(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, top: i32, bottom: i32, x: i32, z: i32) -> bool {
+        let mut placed = false;
+
+        for y in (bottom + 1..=top).rev() {
+            let pos = Vector3::new(x, y, z);
+
+            if self.target.test(level, pos) {
+                let state = self.state_provider.state(level, rng, pos);
+                level.set_block_state(pos, state);
+                placed = true;
+            }
+        }
+
+        placed
+    }
+}
+
+impl Feature for DiskFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let top = origin.y + self.half_height;
+        let bottom = origin.y - self.half_height - 1;
+        let radius = self.radius.sample(rng);
+
+        let mut placed = false;
+        for z in origin.z - radius..=origin.z + radius {
+            for x in origin.x - radius..=origin.x + radius {
+                let dx = x - origin.x;
+                let dz = z - origin.z;
+
+                if dx * dx + dz * dz <= radius * radius {
+                    placed |= self.place_column(level, rng, top, bottom, x, z);
+                }
+            }
+        }
+
+        placed
+    }
+}
@@ -0,0 +1,127 @@
+use crate::feature::disk::DiskFeature;
+use crate::feature::ore::{OreConfig, OreFeature, ReplaceSingleBlockFeature, ScatteredOreFeature};
+use crate::feature::placement::PlacementModifiers;
+use crate::feature::predicate::BlockPredicates;
+use crate::feature::tree::TreeFeature;
+use crate::feature::vegetation::{RandomPatchFeature, RandomSelectorFeature, SimpleBlockFeature, VegetationPatchFeature};
+use crate::feature::{ConfiguredFeature, NoOpFeature};
+use crate::noise::json::deserializer::Deserializer;
+use crate::noise::json::Resolver;
+use crate::tag::BlockTags;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::rc::Rc;
+use std::sync::Arc;
+
+fn config(json: &Value) -> anyhow::Result<&Value> {
+    json.get("config").ok_or_else(|| anyhow!("No \"config\" field present"))
+}
+
+pub struct NoOpDeserializer;
+
+impl Deserializer<ConfiguredFeature> for NoOpDeserializer {
+    fn deserialize(&self, _: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(NoOpFeature))
+    }
+}
+
+pub struct OreDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl Deserializer<ConfiguredFeature> for OreDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(OreFeature { config: OreConfig::from_json(config(json)?, &self.tags, &self.palette)? }))
+    }
+}
+
+pub struct ScatteredOreDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl Deserializer<ConfiguredFeature> for ScatteredOreDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(ScatteredOreFeature { config: OreConfig::from_json(config(json)?, &self.tags, &self.palette)? }))
+    }
+}
+
+pub struct ReplaceSingleBlockDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl Deserializer<ConfiguredFeature> for ReplaceSingleBlockDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(ReplaceSingleBlockFeature::from_json(config(json)?, &self.tags, &self.palette)?))
+    }
+}
+
+pub struct DiskDeserializer {
+    pub palette: Arc<BlockGlobalPalette>,
+    pub predicates: Rc<Resolver<BlockPredicates>>,
+}
+
+impl Deserializer<ConfiguredFeature> for DiskDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(DiskFeature::from_json(config(json)?, &self.palette, &self.predicates)?))
+    }
+}
+
+pub struct TreeDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl Deserializer<ConfiguredFeature> for TreeDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(TreeFeature::from_json(config(json)?, &self.tags, self.palette.clone())?))
+    }
+}
+
+/// Covers `random_patch`, `flower` and `no_bonemeal_flower`, which share the
+/// config and the placement.
+pub struct RandomPatchDeserializer {
+    pub placements: Rc<Resolver<PlacementModifiers>>,
+}
+
+impl Deserializer<ConfiguredFeature> for RandomPatchDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(RandomPatchFeature::from_json(config(json)?, resolver, &self.placements)?))
+    }
+}
+
+pub struct SimpleBlockDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl Deserializer<ConfiguredFeature> for SimpleBlockDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(SimpleBlockFeature::from_json(config(json)?, &self.tags, self.palette.clone())?))
+    }
+}
+
+pub struct RandomSelectorDeserializer {
+    pub placements: Rc<Resolver<PlacementModifiers>>,
+}
+
+impl Deserializer<ConfiguredFeature> for RandomSelectorDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(RandomSelectorFeature::from_json(config(json)?, resolver, &self.placements)?))
+    }
+}
+
+pub struct VegetationPatchDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+    pub placements: Rc<Resolver<PlacementModifiers>>,
+}
+
+impl Deserializer<ConfiguredFeature> for VegetationPatchDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<ConfiguredFeature>) -> anyhow::Result<ConfiguredFeature> {
+        Ok(Arc::new(VegetationPatchFeature::from_json(config(json)?, &self.tags, &self.palette, resolver, &self.placements)?))
+    }
+}
@@ -0,0 +1,3 @@
+pub mod feature;
+pub mod placement;
+pub mod predicate;
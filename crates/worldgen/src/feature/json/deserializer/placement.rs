@@ -0,0 +1,190 @@
+use crate::feature::placement::{BiomeFilter, BlockPredicateFilter, CarvingMaskPlacement, CountOnEveryLayerPlacement, CountPlacement, EnvironmentScanPlacement, HeightRangePlacement, HeightmapPlacement, InSquarePlacement, NoiseBasedCountPlacement, NoiseThresholdCountPlacement, PlacementModifiers, RandomOffsetPlacement, RarityFilter, SurfaceRelativeThresholdFilter, SurfaceWaterDepthFilter};
+use crate::feature::predicate::BlockPredicates;
+use crate::noise::json::deserializer::Deserializer;
+use crate::noise::json::Resolver;
+use anyhow::anyhow;
+use serde_json::Value;
+use std::rc::Rc;
+
+fn predicate(
+    json: &Value,
+    name: &str,
+    predicates: &Resolver<BlockPredicates>,
+) -> anyhow::Result<Option<BlockPredicates>> {
+    json.get(name).map(|predicate| predicates.resolve(predicate)).transpose()
+}
+
+pub struct BiomeDeserializer;
+
+impl Deserializer<PlacementModifiers> for BiomeDeserializer {
+    fn deserialize(&self, _: &Value, _: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::Biome(BiomeFilter))
+    }
+}
+
+pub struct BlockPredicateFilterDeserializer {
+    pub predicates: Rc<Resolver<BlockPredicates>>,
+}
+
+impl Deserializer<PlacementModifiers> for BlockPredicateFilterDeserializer {
+    fn deserialize(&self, json: &Value, _: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        let predicate = predicate(json, "predicate", &self.predicates)?
+            .ok_or_else(|| anyhow!("No \"predicate\" field present"))?;
+
+        Ok(PlacementModifiers::BlockPredicateFilter(BlockPredicateFilter { predicate }))
+    }
+}
+
+pub struct RarityFilterDeserializer;
+
+impl Deserializer<PlacementModifiers> for RarityFilterDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::RarityFilter(RarityFilter { chance: resolver.resolve_field(json, "chance")? }))
+    }
+}
+
+pub struct SurfaceRelativeThresholdFilterDeserializer;
+
+impl Deserializer<PlacementModifiers> for SurfaceRelativeThresholdFilterDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(
+            PlacementModifiers::SurfaceRelativeThresholdFilter(
+                SurfaceRelativeThresholdFilter {
+                    heightmap: resolver.resolve_field(json, "heightmap")?,
+                    min_inclusive: resolver.resolve_field_or(json, "min_inclusive", i32::MIN)?,
+                    max_inclusive: resolver.resolve_field_or(json, "max_inclusive", i32::MAX)?,
+                }
+            )
+        )
+    }
+}
+
+pub struct SurfaceWaterDepthFilterDeserializer;
+
+impl Deserializer<PlacementModifiers> for SurfaceWaterDepthFilterDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(
+            PlacementModifiers::SurfaceWaterDepthFilter(
+                SurfaceWaterDepthFilter {
+                    max_water_depth: resolver.resolve_field(json, "max_water_depth")?,
+                }
+            )
+        )
+    }
+}
+
+pub struct CarvingMaskDeserializer;
+
+impl Deserializer<PlacementModifiers> for CarvingMaskDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::CarvingMask(CarvingMaskPlacement { step: resolver.resolve_field(json, "step")? }))
+    }
+}
+
+pub struct CountDeserializer;
+
+impl Deserializer<PlacementModifiers> for CountDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::Count(CountPlacement { count: resolver.resolve_field(json, "count")? }))
+    }
+}
+
+pub struct CountOnEveryLayerDeserializer;
+
+impl Deserializer<PlacementModifiers> for CountOnEveryLayerDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::CountOnEveryLayer(CountOnEveryLayerPlacement { count: resolver.resolve_field(json, "count")? }))
+    }
+}
+
+pub struct EnvironmentScanDeserializer {
+    pub predicates: Rc<Resolver<BlockPredicates>>,
+}
+
+impl Deserializer<PlacementModifiers> for EnvironmentScanDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(
+            PlacementModifiers::EnvironmentScan(
+                EnvironmentScanPlacement {
+                    direction_of_search: resolver.resolve_field(json, "direction_of_search")?,
+                    target_condition: predicate(json, "target_condition", &self.predicates)?
+                        .ok_or_else(|| anyhow!("No \"target_condition\" field present"))?,
+                    allowed_search_condition: predicate(json, "allowed_search_condition", &self.predicates)?
+                        .unwrap_or(BlockPredicates::True),
+                    max_steps: resolver.resolve_field(json, "max_steps")?,
+                }
+            )
+        )
+    }
+}
+
+pub struct HeightmapDeserializer;
+
+impl Deserializer<PlacementModifiers> for HeightmapDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::Heightmap(HeightmapPlacement { heightmap: resolver.resolve_field(json, "heightmap")? }))
+    }
+}
+
+pub struct HeightRangeDeserializer;
+
+impl Deserializer<PlacementModifiers> for HeightRangeDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::HeightRange(HeightRangePlacement { height: resolver.resolve_field(json, "height")? }))
+    }
+}
+
+pub struct InSquareDeserializer;
+
+impl Deserializer<PlacementModifiers> for InSquareDeserializer {
+    fn deserialize(&self, _: &Value, _: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(PlacementModifiers::InSquare(InSquarePlacement))
+    }
+}
+
+pub struct NoiseBasedCountDeserializer;
+
+impl Deserializer<PlacementModifiers> for NoiseBasedCountDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(
+            PlacementModifiers::NoiseBasedCount(
+                NoiseBasedCountPlacement {
+                    noise_to_count_ratio: resolver.resolve_field(json, "noise_to_count_ratio")?,
+                    noise_factor: resolver.resolve_field(json, "noise_factor")?,
+                    noise_offset: resolver.resolve_field_or(json, "noise_offset", 0.0)?,
+                }
+            )
+        )
+    }
+}
+
+pub struct NoiseThresholdCountDeserializer;
+
+impl Deserializer<PlacementModifiers> for NoiseThresholdCountDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(
+            PlacementModifiers::NoiseThresholdCount(
+                NoiseThresholdCountPlacement {
+                    noise_level: resolver.resolve_field(json, "noise_level")?,
+                    below_noise: resolver.resolve_field(json, "below_noise")?,
+                    above_noise: resolver.resolve_field(json, "above_noise")?,
+                }
+            )
+        )
+    }
+}
+
+pub struct RandomOffsetDeserializer;
+
+impl Deserializer<PlacementModifiers> for RandomOffsetDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<PlacementModifiers>) -> anyhow::Result<PlacementModifiers> {
+        Ok(
+            PlacementModifiers::RandomOffset(
+                RandomOffsetPlacement {
+                    xz_spread: resolver.resolve_field(json, "xz_spread")?,
+                    y_spread: resolver.resolve_field(json, "y_spread")?,
+                }
+            )
+        )
+    }
+}
@@ -0,0 +1,185 @@
+use crate::feature::predicate::{AllOfPredicate, AnyOfPredicate, BlockPredicates, HasSturdyFacePredicate, InsideWorldBoundsPredicate, MatchingBlocksPredicate, MatchingFluidsPredicate, NotPredicate, ReplaceablePredicate, SolidPredicate, WouldSurvivePredicate};
+use crate::feature::state::block_state_from_json;
+use crate::noise::json::deserializer::Deserializer;
+use crate::noise::json::resolvable::Resolvable;
+use crate::noise::json::Resolver;
+use crate::tag::BlockTags;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[inline]
+fn offset(json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<Vector3> {
+    resolver.resolve_field_or(json, "offset", Vector3::new(0, 0, 0))
+}
+
+fn field<'a>(json: &'a Value, name: &str) -> anyhow::Result<&'a Value> {
+    json.get(name).ok_or_else(|| anyhow!("No \"{}\" field present", name))
+}
+
+pub struct MatchingBlocksDeserializer {
+    pub tags: Rc<BlockTags>,
+}
+
+impl Deserializer<BlockPredicates> for MatchingBlocksDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(
+            BlockPredicates::MatchingBlocks(
+                MatchingBlocksPredicate {
+                    offset: offset(json, resolver)?,
+                    blocks: self.tags.resolve_holder_set(field(json, "blocks")?)?,
+                }
+            )
+        )
+    }
+}
+
+pub struct MatchingBlockTagDeserializer {
+    pub tags: Rc<BlockTags>,
+}
+
+impl Deserializer<BlockPredicates> for MatchingBlockTagDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        let tag: String = resolver.resolve_field(json, "tag")?;
+
+        Ok(
+            BlockPredicates::MatchingBlocks(
+                MatchingBlocksPredicate {
+                    offset: offset(json, resolver)?,
+                    blocks: self.tags.tag(&tag)?,
+                }
+            )
+        )
+    }
+}
+
+pub struct MatchingFluidsDeserializer;
+
+impl MatchingFluidsDeserializer {
+    /// Fluid tags are not read from the data, there are only two of them.
+    fn fluids(name: &str) -> anyhow::Result<Vec<String>> {
+        match name {
+            "#minecraft:water" => Ok(vec!["minecraft:water".to_owned(), "minecraft:flowing_water".to_owned()]),
+            "#minecraft:lava" => Ok(vec!["minecraft:lava".to_owned(), "minecraft:flowing_lava".to_owned()]),
+            name if name.starts_with('#') => Err(anyhow!("Unknown fluid tag {}", name)),
+            name => Ok(vec![name.to_owned()]),
+        }
+    }
+}
+
+impl Deserializer<BlockPredicates> for MatchingFluidsDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        let names = match field(json, "fluids")? {
+            Value::String(name) => vec![name.clone()],
+            value => <Vec<String> as Resolvable<BlockPredicates>>::resolve(value, resolver)?,
+        };
+
+        let mut fluids = HashSet::new();
+        for name in names {
+            fluids.extend(Self::fluids(&name)?);
+        }
+
+        Ok(
+            BlockPredicates::MatchingFluids(
+                MatchingFluidsPredicate {
+                    offset: offset(json, resolver)?,
+                    fluids,
+                }
+            )
+        )
+    }
+}
+
+pub struct HasSturdyFaceDeserializer;
+
+impl Deserializer<BlockPredicates> for HasSturdyFaceDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(
+            BlockPredicates::HasSturdyFace(
+                HasSturdyFacePredicate {
+                    offset: offset(json, resolver)?,
+                    direction: resolver.resolve_field(json, "direction")?,
+                }
+            )
+        )
+    }
+}
+
+pub struct SolidDeserializer;
+
+impl Deserializer<BlockPredicates> for SolidDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::Solid(SolidPredicate { offset: offset(json, resolver)? }))
+    }
+}
+
+pub struct ReplaceableDeserializer;
+
+impl Deserializer<BlockPredicates> for ReplaceableDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::Replaceable(ReplaceablePredicate { offset: offset(json, resolver)? }))
+    }
+}
+
+pub struct WouldSurviveDeserializer {
+    pub tags: Rc<BlockTags>,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl Deserializer<BlockPredicates> for WouldSurviveDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(
+            BlockPredicates::WouldSurvive(
+                WouldSurvivePredicate {
+                    offset: offset(json, resolver)?,
+                    state: block_state_from_json(field(json, "state")?, &self.palette)?,
+                    dirt: self.tags.tag("minecraft:dirt")?,
+                }
+            )
+        )
+    }
+}
+
+pub struct InsideWorldBoundsDeserializer;
+
+impl Deserializer<BlockPredicates> for InsideWorldBoundsDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::InsideWorldBounds(InsideWorldBoundsPredicate { offset: offset(json, resolver)? }))
+    }
+}
+
+pub struct AnyOfDeserializer;
+
+impl Deserializer<BlockPredicates> for AnyOfDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::AnyOf(AnyOfPredicate { predicates: resolver.resolve_field(json, "predicates")? }))
+    }
+}
+
+pub struct AllOfDeserializer;
+
+impl Deserializer<BlockPredicates> for AllOfDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::AllOf(AllOfPredicate { predicates: resolver.resolve_field(json, "predicates")? }))
+    }
+}
+
+pub struct NotDeserializer;
+
+impl Deserializer<BlockPredicates> for NotDeserializer {
+    fn deserialize(&self, json: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::Not(Box::new(NotPredicate { predicate: resolver.resolve_field(json, "predicate")? })))
+    }
+}
+
+pub struct TrueDeserializer;
+
+impl Deserializer<BlockPredicates> for TrueDeserializer {
+    fn deserialize(&self, _: &Value, _: &Resolver<BlockPredicates>) -> anyhow::Result<BlockPredicates> {
+        Ok(BlockPredicates::True)
+    }
+}
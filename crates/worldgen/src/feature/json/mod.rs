@@ -0,0 +1,223 @@
+use crate::feature::json::deserializer::feature::{DiskDeserializer, NoOpDeserializer, OreDeserializer, RandomPatchDeserializer, RandomSelectorDeserializer, ReplaceSingleBlockDeserializer, ScatteredOreDeserializer, SimpleBlockDeserializer, TreeDeserializer, VegetationPatchDeserializer};
+use crate::feature::json::deserializer::placement::{BiomeDeserializer, BlockPredicateFilterDeserializer, CarvingMaskDeserializer, CountDeserializer, CountOnEveryLayerDeserializer, EnvironmentScanDeserializer, HeightRangeDeserializer, HeightmapDeserializer, InSquareDeserializer, NoiseBasedCountDeserializer, NoiseThresholdCountDeserializer, RandomOffsetDeserializer, RarityFilterDeserializer, SurfaceRelativeThresholdFilterDeserializer, SurfaceWaterDepthFilterDeserializer};
+use crate::feature::json::deserializer::predicate::{AllOfDeserializer, AnyOfDeserializer, HasSturdyFaceDeserializer, InsideWorldBoundsDeserializer, MatchingBlockTagDeserializer, MatchingBlocksDeserializer, MatchingFluidsDeserializer, NotDeserializer, ReplaceableDeserializer, SolidDeserializer, TrueDeserializer, WouldSurviveDeserializer};
+use crate::feature::placement::PlacementModifiers;
+use crate::feature::predicate::BlockPredicates;
+use crate::feature::ConfiguredFeature;
+use crate::noise::json::deserializer::Deserializer;
+use crate::noise::json::Resolver;
+use crate::tag::BlockTags;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub mod deserializer;
+pub mod resolvable;
+
+pub fn feature_deserializers(
+    tags: Rc<BlockTags>,
+    palette: Arc<BlockGlobalPalette>,
+    predicates: Rc<Resolver<BlockPredicates>>,
+    placements: Rc<Resolver<PlacementModifiers>>,
+) -> HashMap<String, Box<dyn Deserializer<ConfiguredFeature>>> {
+    HashMap::from([
+        ("minecraft:no_op".to_owned(), cast(NoOpDeserializer)),
+        ("minecraft:ore".to_owned(), cast(OreDeserializer {
+            tags: tags.clone(),
+            palette: palette.clone(),
+        })),
+        ("minecraft:scattered_ore".to_owned(), cast(ScatteredOreDeserializer {
+            tags: tags.clone(),
+            palette: palette.clone(),
+        })),
+        ("minecraft:replace_single_block".to_owned(), cast(ReplaceSingleBlockDeserializer {
+            tags: tags.clone(),
+            palette: palette.clone(),
+        })),
+        ("minecraft:disk".to_owned(), cast(DiskDeserializer {
+            palette: palette.clone(),
+            predicates,
+        })),
+        ("minecraft:tree".to_owned(), cast(TreeDeserializer {
+            tags: tags.clone(),
+            palette: palette.clone(),
+        })),
+        ("minecraft:random_patch".to_owned(), cast(RandomPatchDeserializer {
+            placements: placements.clone(),
+        })),
+        ("minecraft:flower".to_owned(), cast(RandomPatchDeserializer {
+            placements: placements.clone(),
+        })),
+        ("minecraft:no_bonemeal_flower".to_owned(), cast(RandomPatchDeserializer {
+            placements: placements.clone(),
+        })),
+        ("minecraft:simple_block".to_owned(), cast(SimpleBlockDeserializer {
+            tags: tags.clone(),
+            palette: palette.clone(),
+        })),
+        ("minecraft:random_selector".to_owned(), cast(RandomSelectorDeserializer {
+            placements: placements.clone(),
+        })),
+        ("minecraft:vegetation_patch".to_owned(), cast(VegetationPatchDeserializer {
+            tags,
+            palette,
+            placements,
+        })),
+    ])
+}
+
+pub fn placement_deserializers(
+    predicates: Rc<Resolver<BlockPredicates>>,
+) -> HashMap<String, Box<dyn Deserializer<PlacementModifiers>>> {
+    HashMap::from([
+        ("minecraft:biome".to_owned(), cast(BiomeDeserializer)),
+        ("minecraft:block_predicate_filter".to_owned(), cast(BlockPredicateFilterDeserializer {
+            predicates: predicates.clone(),
+        })),
+        ("minecraft:rarity_filter".to_owned(), cast(RarityFilterDeserializer)),
+        ("minecraft:surface_relative_threshold_filter".to_owned(), cast(SurfaceRelativeThresholdFilterDeserializer)),
+        ("minecraft:surface_water_depth_filter".to_owned(), cast(SurfaceWaterDepthFilterDeserializer)),
+        ("minecraft:carving_mask".to_owned(), cast(CarvingMaskDeserializer)),
+        ("minecraft:count".to_owned(), cast(CountDeserializer)),
+        ("minecraft:count_on_every_layer".to_owned(), cast(CountOnEveryLayerDeserializer)),
+        ("minecraft:environment_scan".to_owned(), cast(EnvironmentScanDeserializer {
+            predicates,
+        })),
+        ("minecraft:heightmap".to_owned(), cast(HeightmapDeserializer)),
+        ("minecraft:height_range".to_owned(), cast(HeightRangeDeserializer)),
+        ("minecraft:in_square".to_owned(), cast(InSquareDeserializer)),
+        ("minecraft:noise_based_count".to_owned(), cast(NoiseBasedCountDeserializer)),
+        ("minecraft:noise_threshold_count".to_owned(), cast(NoiseThresholdCountDeserializer)),
+        ("minecraft:random_offset".to_owned(), cast(RandomOffsetDeserializer)),
+    ])
+}
+
+pub fn predicate_deserializers(
+    tags: Rc<BlockTags>,
+    palette: Arc<BlockGlobalPalette>,
+) -> HashMap<String, Box<dyn Deserializer<BlockPredicates>>> {
+    HashMap::from([
+        ("minecraft:matching_blocks".to_owned(), cast(MatchingBlocksDeserializer {
+            tags: tags.clone(),
+        })),
+        ("minecraft:matching_block_tag".to_owned(), cast(MatchingBlockTagDeserializer {
+            tags: tags.clone(),
+        })),
+        ("minecraft:matching_fluids".to_owned(), cast(MatchingFluidsDeserializer)),
+        ("minecraft:has_sturdy_face".to_owned(), cast(HasSturdyFaceDeserializer)),
+        ("minecraft:solid".to_owned(), cast(SolidDeserializer)),
+        ("minecraft:replaceable".to_owned(), cast(ReplaceableDeserializer)),
+        ("minecraft:would_survive".to_owned(), cast(WouldSurviveDeserializer {
+            tags,
+            palette,
+        })),
+        ("minecraft:inside_world_bounds".to_owned(), cast(InsideWorldBoundsDeserializer)),
+        ("minecraft:any_of".to_owned(), cast(AnyOfDeserializer)),
+        ("minecraft:all_of".to_owned(), cast(AllOfDeserializer)),
+        ("minecraft:not".to_owned(), cast(NotDeserializer)),
+        ("minecraft:true".to_owned(), cast(TrueDeserializer)),
+    ])
+}
+
+#[inline]
+fn cast<T, D: Deserializer<T> + 'static>(
+    t: D,
+) -> Box<dyn Deserializer<T>> {
+    Box::new(t)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::json::{feature_deserializers, placement_deserializers, predicate_deserializers};
+    use crate::feature::placement::{CarvingStep, PlacementModifiers};
+    use crate::feature::predicate::BlockPredicates;
+    use crate::feature::{Direction, PlacedFeature};
+    use crate::noise::json::value_resolver::MockValueResolver;
+    use crate::noise::json::Resolver;
+    use crate::pack::DataPacks;
+    use crate::tag::BlockTags;
+    use serde_json::json;
+    use spherix_math::vector::Vector3;
+    use spherix_world::chunk::heightmap::HeightmapType;
+    use spherix_world::chunk::palette::BlockGlobalPalette;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn placed_feature_from_json() {
+        let tags = Rc::new(BlockTags::new(Arc::new(DataPacks::new(Vec::new()))));
+        let palette = Arc::new(BlockGlobalPalette::new(0));
+
+        let predicates = Rc::new(Resolver::new(
+            predicate_deserializers(tags.clone(), palette.clone()),
+            Box::new(MockValueResolver::new(HashMap::new())),
+        ));
+
+        let placements = Rc::new(Resolver::new(
+            placement_deserializers(predicates.clone()),
+            Box::new(MockValueResolver::new(HashMap::from([
+                ("minecraft:seagrass_simple".to_owned(), json!({
+                    "feature": "minecraft:seagrass_simple",
+                    "placement": [
+                        {"type": "minecraft:rarity_filter", "chance": 10},
+                        {"type": "minecraft:carving_mask", "step": "liquid"},
+                        {
+                            "type": "minecraft:count",
+                            "count": {"type": "minecraft:uniform", "value": {"min_inclusive": 0, "max_inclusive": 4}}
+                        },
+                        {"type": "minecraft:in_square"},
+                        {"type": "minecraft:surface_relative_threshold_filter", "heightmap": "OCEAN_FLOOR_WG", "max_inclusive": -2},
+                        {
+                            "type": "minecraft:environment_scan",
+                            "direction_of_search": "down",
+                            "max_steps": 12,
+                            "target_condition": {
+                                "type": "minecraft:matching_fluids",
+                                "offset": [0, -1, 0],
+                                "fluids": "minecraft:water"
+                            }
+                        },
+                        {"type": "minecraft:biome"}
+                    ]
+                })),
+            ]))),
+        ));
+
+        let features = Resolver::new(
+            feature_deserializers(tags, palette, predicates, placements.clone()),
+            Box::new(MockValueResolver::new(HashMap::from([
+                ("minecraft:seagrass_simple".to_owned(), json!({"type": "minecraft:no_op", "config": {}})),
+            ]))),
+        );
+
+        let placed = PlacedFeature::from_json(&json!("minecraft:seagrass_simple"), &features, &placements).unwrap();
+
+        assert_eq!(7, placed.placement.len());
+        assert!(matches!(&placed.placement[0], PlacementModifiers::RarityFilter(filter) if filter.chance == 10));
+        assert!(matches!(&placed.placement[1], PlacementModifiers::CarvingMask(mask) if mask.step == CarvingStep::Liquid));
+        assert!(matches!(&placed.placement[2], PlacementModifiers::Count(_)));
+        assert!(matches!(&placed.placement[3], PlacementModifiers::InSquare(_)));
+
+        let PlacementModifiers::SurfaceRelativeThresholdFilter(filter) = &placed.placement[4] else {
+            panic!("Expected surface_relative_threshold_filter");
+        };
+        assert_eq!(HeightmapType::OceanFloorWg, filter.heightmap);
+        assert_eq!(i32::MIN, filter.min_inclusive);
+        assert_eq!(-2, filter.max_inclusive);
+
+        let PlacementModifiers::EnvironmentScan(scan) = &placed.placement[5] else {
+            panic!("Expected environment_scan");
+        };
+        assert_eq!(Direction::Down, scan.direction_of_search);
+        assert_eq!(12, scan.max_steps);
+        assert!(matches!(scan.allowed_search_condition, BlockPredicates::True));
+        assert!(matches!(
+            &scan.target_condition,
+            BlockPredicates::MatchingFluids(fluids) if fluids.offset == Vector3::new(0, -1, 0) && fluids.fluids.contains("minecraft:water")
+        ));
+
+        assert!(matches!(&placed.placement[6], PlacementModifiers::Biome(_)));
+    }
+}
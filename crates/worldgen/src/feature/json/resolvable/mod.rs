@@ -0,0 +1,61 @@
+use crate::feature::placement::CarvingStep;
+use crate::feature::predicate::BlockPredicates;
+use crate::feature::Direction;
+use crate::noise::json::resolvable::Resolvable;
+use crate::noise::json::Resolver;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::heightmap::HeightmapType;
+
+impl<T> Resolvable<T> for HeightmapType {
+    fn resolve(val: &Value, _: &Resolver<T>) -> anyhow::Result<Self> {
+        let Value::String(name) = val else {
+            return Err(anyhow!("Expected String, but given: {:?}", val))
+        };
+
+        HeightmapType::ALL
+            .into_iter()
+            .find(|ty| ty.name() == name)
+            .ok_or_else(|| anyhow!("Unknown heightmap type {}", name))
+    }
+}
+
+impl<T> Resolvable<T> for Direction {
+    fn resolve(val: &Value, _: &Resolver<T>) -> anyhow::Result<Self> {
+        let Value::String(name) = val else {
+            return Err(anyhow!("Expected String, but given: {:?}", val))
+        };
+
+        Direction::from_name(name).ok_or_else(|| anyhow!("Unknown direction {}", name))
+    }
+}
+
+impl<T> Resolvable<T> for CarvingStep {
+    fn resolve(val: &Value, _: &Resolver<T>) -> anyhow::Result<Self> {
+        match val.as_str() {
+            Some("air") => Ok(CarvingStep::Air),
+            Some("liquid") => Ok(CarvingStep::Liquid),
+            _ => Err(anyhow!("Expected either \"air\" or \"liquid\", but given: {:?}", val))
+        }
+    }
+}
+
+/// Block position offsets written as `[x, y, z]`.
+impl<T> Resolvable<T> for Vector3 {
+    fn resolve(val: &Value, resolver: &Resolver<T>) -> anyhow::Result<Self> {
+        let coords: Vec<i32> = Resolvable::resolve(val, resolver)?;
+
+        let [x, y, z] = coords[..] else {
+            return Err(anyhow!("Expected 3 coordinates, but given: {:?}", val))
+        };
+
+        Ok(Vector3::new(x, y, z))
+    }
+}
+
+impl Resolvable<BlockPredicates> for BlockPredicates {
+    fn resolve(val: &Value, resolver: &Resolver<BlockPredicates>) -> anyhow::Result<Self> {
+        resolver.resolve(val)
+    }
+}
@@ -0,0 +1,380 @@
+use crate::biome::sampler::BiomeSampler;
+use crate::chunk::column::{ChunkColumn, Neighbour};
+use crate::feature::registry::FeatureRegistry;
+use crate::noise::density::cache::{quart_pos_from_block, quart_pos_to_section};
+use crate::surface::context::WorldGenerationContext;
+use spherix_math::vector::{Vector2, Vector3};
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::biome::Biome;
+use spherix_world::chunk::carving_mask::CarvingMask;
+use spherix_world::chunk::heightmap::{Heightmap, HeightmapType};
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::status::ChunkStatus;
+use spherix_world::chunk::vector::Vector3BlockColumn;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Part of the world, which is visible to features decorating a chunk. Already
+/// carved chunks around are readable, everything else reads as air. Blocks are
+/// written to the decorated chunk and its writable neighbours (see
+/// [`WorldGenLevel::ensure_can_write()`]), so features crossing the border of
+/// the chunk are cut only at frozen chunks.
+pub struct WorldGenLevel<'a> {
+    pub seed: i64,
+    pub gen_ctx: WorldGenerationContext,
+    pos: ChunkPos,
+    chunk: &'a mut ChunkColumn,
+    neighbours: HashMap<ChunkPos, Neighbour<'a>>,
+    biome_sampler: &'a BiomeSampler,
+    features: &'a FeatureRegistry,
+    air: Arc<BlockState>,
+}
+
+impl<'a> WorldGenLevel<'a> {
+    /// Heightmaps, which features read, but do not update. Vanilla computes them
+    /// before the decoration of the chunk.
+    pub const PRIMED_HEIGHTMAPS: [HeightmapType; 4] = [
+        HeightmapType::MotionBlocking,
+        HeightmapType::MotionBlockingNoLeaves,
+        HeightmapType::OceanFloor,
+        HeightmapType::WorldSurface,
+    ];
+
+    pub fn new(
+        seed: i64,
+        gen_ctx: WorldGenerationContext,
+        chunk: &'a mut ChunkColumn,
+        neighbours: HashMap<ChunkPos, Neighbour<'a>>,
+        biome_sampler: &'a BiomeSampler,
+        features: &'a FeatureRegistry,
+        palette: &BlockGlobalPalette,
+    ) -> Self {
+        let pos = chunk.pos();
+
+        let max_y = chunk
+            .inner()
+            .heightmaps
+            .get(HeightmapType::WorldSurfaceWg)
+            .map_or(gen_ctx.min_y + gen_ctx.height - 1, |heightmap| {
+                (0..16)
+                    .flat_map(|x| (0..16).map(move |z| (x, z)))
+                    .map(|(x, z)| heightmap.height(Vector2::new(x, z)))
+                    .max()
+                    .unwrap()
+            });
+
+        for ty in Self::PRIMED_HEIGHTMAPS {
+            let heightmap = Heightmap::prime(ty, &chunk.inner().with_safe(), gen_ctx.height, gen_ctx.min_y, max_y);
+            chunk.inner_mut().heightmaps.set(heightmap);
+        }
+
+        Self {
+            seed,
+            gen_ctx,
+            pos,
+            chunk,
+            neighbours,
+            biome_sampler,
+            features,
+            air: palette.get_default_obj_by_index(&Block::AIR).unwrap(),
+        }
+    }
+
+    #[inline]
+    pub fn pos(&self) -> ChunkPos {
+        self.pos.clone()
+    }
+
+    #[inline]
+    pub fn features(&self) -> &FeatureRegistry {
+        self.features
+    }
+
+    #[inline]
+    pub fn min_y(&self) -> i32 {
+        self.gen_ctx.min_y
+    }
+
+    /// The first y above the dimension.
+    #[inline]
+    pub fn max_y(&self) -> i32 {
+        self.gen_ctx.min_y + self.gen_ctx.height
+    }
+
+    #[inline]
+    pub fn is_outside_build_height(&self, y: i32) -> bool {
+        y < self.min_y() || y >= self.max_y()
+    }
+
+    pub fn block_state(&self, at: Vector3) -> Arc<BlockState> {
+        if self.is_outside_build_height(at.y) {
+            return self.air.clone()
+        }
+
+        self.chunk_at(at.x, at.z, ChunkStatus::Carvers)
+            .and_then(|chunk| chunk.inner().block_state(Self::column_vector(at)))
+            .unwrap_or_else(|| self.air.clone())
+    }
+
+    #[inline]
+    pub fn is_air(&self, at: Vector3) -> bool {
+        self.block_state(at).block().properties().is_air
+    }
+
+    /// Sets the block, if vanilla allows writing it and its chunk is writable.
+    /// Returns whether the block was set.
+    pub fn set_block_state(&mut self, at: Vector3, state: Arc<BlockState>) -> bool {
+        if !self.ensure_can_write(at) {
+            return false
+        }
+
+        let Some(chunk) = self.chunk_at_mut(at.x, at.z, ChunkStatus::Carvers) else {
+            return false
+        };
+
+        let at = Self::column_vector(at);
+        let column = chunk.inner_mut();
+        column.set_block_state(at, state.clone());
+
+        // Only the worldgen heightmaps are kept up to date during decoration.
+        let mut ocean_floor = column.heightmaps.ocean_floor_wg.take();
+        let mut world_surface = column.heightmaps.world_surface_wg.take();
+
+        for heightmap in [&mut ocean_floor, &mut world_surface].into_iter().flatten() {
+            heightmap.update(&column.with_safe(), at, state.as_ref());
+        }
+
+        column.heightmaps.ocean_floor_wg = ocean_floor;
+        column.heightmaps.world_surface_wg = world_surface;
+
+        true
+    }
+
+    /// Whether vanilla would allow writing the block, i.e. whether it is within
+    /// the decorated chunk or one of its neighbours.
+    pub fn ensure_can_write(&self, at: Vector3) -> bool {
+        !self.is_outside_build_height(at.y)
+            && (at.x >> 4).abs_diff(self.pos.x()) <= 1
+            && (at.z >> 4).abs_diff(self.pos.z()) <= 1
+    }
+
+    /// The first y above the top block of the heightmap type, as vanilla
+    /// `getHeight()` returns it.
+    pub fn height(&self, ty: HeightmapType, x: i32, z: i32) -> i32 {
+        let Some(chunk) = self.chunk_at(x, z, ChunkStatus::Carvers) else {
+            return self.min_y()
+        };
+
+        if let Some(heightmap) = chunk.inner().heightmaps.get(ty) {
+            return heightmap.height(Vector2::new(x & 15, z & 15)) + 1
+        }
+
+        (self.min_y()..self.max_y())
+            .rev()
+            .find(|y| ty.is_opaque(&chunk.inner().block_state(Self::column_vector(Vector3::new(x, *y, z))).unwrap()))
+            .map_or(self.min_y(), |y| y + 1)
+    }
+
+    pub fn biome(&self, at: Vector3) -> Arc<Biome> {
+        let quart = Vector3::new(quart_pos_from_block(at.x), quart_pos_from_block(at.y), quart_pos_from_block(at.z));
+
+        self.biome_at_quart(quart)
+    }
+
+    pub fn biome_at_quart(&self, quart: Vector3) -> Arc<Biome> {
+        match self.chunk_at(quart_pos_to_section(quart.x) << 4, quart_pos_to_section(quart.z) << 4, ChunkStatus::Biomes) {
+            Some(chunk) => chunk.inner().biome2(quart),
+            None => self.biome_sampler.sample(&quart),
+        }
+    }
+
+    /// Distinct biomes of the decorated chunk and its 8 neighbours. Their
+    /// feature lists make up the features run in each decoration step.
+    pub fn biomes_around(&self) -> Vec<Arc<Biome>> {
+        let mut names = HashSet::new();
+        let mut biomes = Vec::new();
+
+        let min_quart_x = quart_pos_from_block(self.pos.get_min_block_x()) - 4;
+        let min_quart_z = quart_pos_from_block(self.pos.get_min_block_z()) - 4;
+        let min_quart_y = quart_pos_from_block(self.min_y());
+        let max_quart_y = quart_pos_from_block(self.max_y());
+
+        for x in min_quart_x..min_quart_x + 12 {
+            for z in min_quart_z..min_quart_z + 12 {
+                for y in min_quart_y..max_quart_y {
+                    let biome = self.biome_at_quart(Vector3::new(x, y, z));
+
+                    if names.insert(biome.name_ref().to_owned()) {
+                        biomes.push(biome);
+                    }
+                }
+            }
+        }
+
+        biomes
+    }
+
+    /// Blocks of the decorated chunk carved by the air carvers.
+    pub fn carving_mask(&self) -> Option<&CarvingMask> {
+        self.chunk.inner().carving_mask.as_ref()
+    }
+
+    #[inline]
+    pub fn is_in_chunk(&self, x: i32, z: i32) -> bool {
+        x >> 4 == self.pos.x() && z >> 4 == self.pos.z()
+    }
+
+    /// The chunk containing the block column, if it has reached the status.
+    fn chunk_at(&self, x: i32, z: i32, status: ChunkStatus) -> Option<&ChunkColumn> {
+        if self.is_in_chunk(x, z) {
+            return Some(self.chunk)
+        }
+
+        self.neighbours
+            .get(&ChunkPos::new(x >> 4, z >> 4))
+            .map(Neighbour::get)
+            .filter(|chunk| chunk.inner().status.ordinal() >= status.ordinal())
+    }
+
+    /// The chunk containing the block column, if it has reached the status and
+    /// is writable.
+    fn chunk_at_mut(&mut self, x: i32, z: i32, status: ChunkStatus) -> Option<&mut ChunkColumn> {
+        if self.is_in_chunk(x, z) {
+            return Some(&mut *self.chunk)
+        }
+
+        self.neighbours
+            .get_mut(&ChunkPos::new(x >> 4, z >> 4))
+            .and_then(Neighbour::get_mut)
+            .filter(|chunk| chunk.inner().status.ordinal() >= status.ordinal())
+    }
+
+    #[inline]
+    fn column_vector(at: Vector3) -> Vector3BlockColumn {
+        Vector3BlockColumn::new((at.x & 15) as u32, at.y, (at.z & 15) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::biome::climate::sampler::ClimateSampler;
+    use crate::biome::sampler::BiomeSampler;
+    use crate::biome::source::BiomeSource;
+    use crate::chunk::column::{ChunkColumn, Neighbour};
+    use crate::feature::level::WorldGenLevel;
+    use crate::feature::registry::FeatureRegistry;
+    use crate::feature::{worldgen_rng, Feature, FeatureRng};
+    use crate::noise::density::density::DensityFunctions;
+    use crate::noise::density::misc::Const;
+    use crate::surface::context::WorldGenerationContext;
+    use serde_json::json;
+    use spherix_math::vector::Vector3;
+    use spherix_world::block::block::Block;
+    use spherix_world::block::state::BlockState;
+    use spherix_world::chunk::palette::{create_biome_global_palette_from_json, create_block_global_palette_from_json};
+    use spherix_world::chunk::pos::ChunkPos;
+    use spherix_world::chunk::status::ChunkStatus;
+    use spherix_world::chunk::vector::Vector3BlockColumn;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// Row of blocks along the x axis.
+    struct Row {
+        state: Arc<BlockState>,
+        length: i32,
+    }
+
+    impl Feature for Row {
+        fn place(&self, level: &mut WorldGenLevel, _: &mut FeatureRng, origin: Vector3) -> bool {
+            let mut placed = false;
+            for dx in 0..self.length {
+                placed |= level.set_block_state(Vector3::new(origin.x + dx, origin.y, origin.z), self.state.clone());
+            }
+
+            placed
+        }
+    }
+
+    #[test]
+    fn features_write_into_neighbours() {
+        let palette = Arc::new(create_block_global_palette_from_json(json!({
+            "minecraft:air": {"states": [{"id": 0, "default": true}]},
+            "minecraft:stone": {"states": [{"id": 1, "default": true}]}
+        })));
+        let biome_palette = Arc::new(create_biome_global_palette_from_json(&json!([{
+            "id": 0,
+            "name": "minecraft:plains",
+            "element": {
+                "downfall": 0.4,
+                "temperature": 0.8,
+                "precipitation": "rain",
+                "effects": {
+                    "sky_color": 0,
+                    "water_fog_color": 0,
+                    "water_color": 0,
+                    "fog_color": 0,
+                    "mood_sound": {
+                        "tick_delay": 6000,
+                        "offset": 2.0,
+                        "sound": "minecraft:ambient.cave",
+                        "block_search_extent": 8
+                    }
+                }
+            }
+        }])));
+
+        let carved = |x, z| {
+            let mut chunk = ChunkColumn::new(spherix_world::chunk::column::ChunkColumn::empty(
+                ChunkPos::new(x, z),
+                palette.clone(),
+                biome_palette.clone(),
+            ));
+            chunk.inner_mut().status = ChunkStatus::Carvers;
+
+            chunk
+        };
+
+        let mut decorated = carved(0, 0);
+        let mut east = carved(1, 0);
+        let south = carved(0, 1);
+
+        let zero = || DensityFunctions::Const(Const::new(0.0));
+        let sampler = BiomeSampler::new(
+            biome_palette.clone(),
+            BiomeSource::Fixed("minecraft:plains".to_owned()),
+            ClimateSampler::new(zero(), zero(), zero(), zero(), zero(), zero()),
+        );
+        let features = FeatureRegistry::new(Vec::new(), |_| unreachable!()).unwrap();
+        let stone = palette.get_default_obj_by_index(&Block::STONE).unwrap();
+
+        let mut level = WorldGenLevel::new(
+            0,
+            WorldGenerationContext { height: 384, min_y: -64 },
+            &mut decorated,
+            HashMap::from([
+                (ChunkPos::new(1, 0), Neighbour::Writable(&mut east)),
+                (ChunkPos::new(0, 1), Neighbour::ReadOnly(&south)),
+            ]),
+            &sampler,
+            &features,
+            &palette,
+        );
+
+        let row = Row { state: stone.clone(), length: 3 };
+        assert!(row.place(&mut level, &mut worldgen_rng(0), Vector3::new(15, 70, 3)));
+
+        // Read-only neighbours and chunks out of the reach of features are
+        // not written.
+        assert!(!level.set_block_state(Vector3::new(3, 70, 16), stone.clone()));
+        assert!(!level.set_block_state(Vector3::new(32, 70, 3), stone));
+        drop(level);
+
+        let block = |chunk: &ChunkColumn, x, z| chunk.inner().block_state(Vector3BlockColumn::new(x, 70, z)).unwrap().block();
+        assert_eq!(Block::STONE, block(&decorated, 15, 3));
+        assert_eq!(Block::STONE, block(&east, 0, 3));
+        assert_eq!(Block::STONE, block(&east, 1, 3));
+        assert_eq!(Block::AIR, block(&south, 3, 0));
+    }
+}
@@ -0,0 +1,251 @@
+pub mod disk;
+pub mod json;
+pub mod level;
+pub mod ore;
+pub mod placement;
+pub mod predicate;
+pub mod registry;
+pub mod rule_test;
+pub mod sorter;
+pub mod state;
+pub mod state_provider;
+pub mod tree;
+pub mod vegetation;
+
+use crate::feature::level::WorldGenLevel;
+use crate::feature::placement::{PlacementContext, PlacementModifier, PlacementModifiers};
+use crate::noise::json::Resolver;
+use crate::rng::{Rng, U32EntropySrcRng, XoroShiro, XoroShiroU32EntropySrc};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use std::sync::Arc;
+
+/// Vanilla `WorldgenRandom` backed by Xoroshiro, which is used for decoration.
+pub type FeatureRng = U32EntropySrcRng<XoroShiroU32EntropySrc>;
+
+pub fn worldgen_rng(seed: i64) -> FeatureRng {
+    FeatureRng::new(XoroShiroU32EntropySrc::new(XoroShiro::new(seed as u64)))
+}
+
+/// Equivalent of vanilla `WorldgenRandom::setDecorationSeed()`. The returned
+/// seed is shared by all features of the chunk with the minimal block (x, z).
+pub fn decoration_seed(seed: i64, x: i32, z: i32) -> i64 {
+    let mut rng = worldgen_rng(seed);
+    let a = rng.next_u64() as i64 | 1;
+    let b = rng.next_u64() as i64 | 1;
+
+    (x as i64).wrapping_mul(a).wrapping_add((z as i64).wrapping_mul(b)) ^ seed
+}
+
+/// Equivalent of vanilla `WorldgenRandom::setFeatureSeed()`.
+pub fn feature_rng(decoration_seed: i64, index: usize, step: usize) -> FeatureRng {
+    worldgen_rng(
+        decoration_seed
+            .wrapping_add(index as i64)
+            .wrapping_add(10000 * step as i64)
+    )
+}
+
+/// Configured feature, i.e. the feature algorithm together with its parameters
+/// from `worldgen/configured_feature/*.json`.
+pub trait Feature: Send + Sync {
+    /// Places the feature at `origin`. Returns whether anything was placed.
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool;
+}
+
+pub type ConfiguredFeature = Arc<dyn Feature>;
+
+pub struct NoOpFeature;
+
+impl Feature for NoOpFeature {
+    fn place(&self, _: &mut WorldGenLevel, _: &mut FeatureRng, _: Vector3) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 6] = [
+        Direction::Down,
+        Direction::Up,
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    /// Horizontal directions in the order of vanilla `Direction.Plane.HORIZONTAL`.
+    pub const HORIZONTAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "down" => Some(Direction::Down),
+            "up" => Some(Direction::Up),
+            "north" => Some(Direction::North),
+            "south" => Some(Direction::South),
+            "west" => Some(Direction::West),
+            "east" => Some(Direction::East),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Down => "down",
+            Direction::Up => "up",
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::West => "west",
+            Direction::East => "east",
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Down => Direction::Up,
+            Direction::Up => Direction::Down,
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        }
+    }
+
+    /// Horizontal direction rotated clockwise, as seen from above. Vertical
+    /// directions are kept.
+    pub fn clockwise(&self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+            vertical => *vertical,
+        }
+    }
+
+    /// Equivalent of vanilla `Direction.Plane.HORIZONTAL.getRandomDirection()`.
+    pub fn random_horizontal(rng: &mut FeatureRng) -> Direction {
+        Self::HORIZONTAL[rng.next_u32(4) as usize]
+    }
+
+    pub fn relative(&self, pos: Vector3) -> Vector3 {
+        match self {
+            Direction::Down => Vector3::new(pos.x, pos.y - 1, pos.z),
+            Direction::Up => Vector3::new(pos.x, pos.y + 1, pos.z),
+            Direction::North => Vector3::new(pos.x, pos.y, pos.z - 1),
+            Direction::South => Vector3::new(pos.x, pos.y, pos.z + 1),
+            Direction::West => Vector3::new(pos.x - 1, pos.y, pos.z),
+            Direction::East => Vector3::new(pos.x + 1, pos.y, pos.z),
+        }
+    }
+}
+
+/// Configured feature together with the modifiers, which turn the origin of
+/// the chunk into the positions the feature is placed at.
+pub struct PlacedFeature {
+    pub feature: ConfiguredFeature,
+    pub placement: Vec<PlacementModifiers>,
+}
+
+impl PlacedFeature {
+    /// Reads the placed feature, which is either inlined or referenced by name.
+    /// Configured features are resolved with `features`, and modifiers with
+    /// `placements`, whose value resolver looks up named placed features.
+    pub fn from_json(
+        json: &Value,
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> anyhow::Result<Self> {
+        if let Value::String(name) = json {
+            return Self::from_json(&placements.resolve_value(name.clone())?, features, placements)
+                .map_err(|e| anyhow!("Unable to resolve placed feature \"{}\": {}", name, e))
+        }
+
+        let Some(feature) = json.get("feature") else {
+            return Err(anyhow!("No \"feature\" key"))
+        };
+
+        let placement = match json.get("placement") {
+            Some(Value::Array(modifiers)) => modifiers
+                .iter()
+                .map(|modifier| placements.resolve(modifier))
+                .collect::<anyhow::Result<_>>()?,
+            Some(value) => return Err(anyhow!("Expected Array, but given: {:?}", value)),
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            feature: features.resolve(feature)?,
+            placement,
+        })
+    }
+
+    /// Places the feature, which is nested into another one.
+    pub fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        self.place_from(0, level, None, rng, origin)
+    }
+
+    /// Places the feature listed by biomes under `name`, so that the biome
+    /// filter is able to check, whether the biome at the position lists it.
+    pub fn place_with_biome_check(&self, name: &str, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        self.place_from(0, level, Some(name), rng, origin)
+    }
+
+    /// Vanilla chains modifiers with lazy streams, so all positions produced
+    /// from one position are processed (and the feature is placed at them)
+    /// before the next modifier call. The random is consumed in the same order.
+    fn place_from(
+        &self,
+        index: usize,
+        level: &mut WorldGenLevel,
+        top_feature: Option<&str>,
+        rng: &mut FeatureRng,
+        pos: Vector3,
+    ) -> bool {
+        let Some(modifier) = self.placement.get(index) else {
+            return self.feature.place(level, rng, pos)
+        };
+
+        let positions = modifier.positions(&PlacementContext { level: &*level, top_feature }, rng, pos);
+
+        let mut placed = false;
+        for pos in positions {
+            placed |= self.place_from(index + 1, level, top_feature, rng, pos);
+        }
+
+        placed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::{decoration_seed, feature_rng};
+    use crate::rng::Rng;
+
+    #[test]
+    fn decoration_and_feature_seed() {
+        let seed = decoration_seed(12345, 16, -32);
+        assert_eq!(-4241508331898708823, seed);
+        assert_eq!(-42, decoration_seed(-42, 0, 0));
+
+        let mut rng = feature_rng(seed, 3, 6);
+        assert_eq!(10, rng.next_u32(16));
+        assert_eq!(6, rng.next_u32(16));
+        assert_eq!(4366422182793243729, rng.next_u64() as i64);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::rule_test::{RuleTest, RuleTests};
+use crate::feature::state::block_state_from_json;
+use crate::feature::{Direction, Feature, FeatureRng};
+use crate::noise::math::{floor, lerp, sin};
+use crate::rng::Rng;
+use crate::tag::BlockTags;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::heightmap::HeightmapType;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+/// Block state placed in place of the blocks passing the rule test.
+pub struct OreTarget {
+    pub target: RuleTests,
+    pub state: Arc<BlockState>,
+}
+
+/// Configuration shared by `ore` and `scattered_ore`.
+pub struct OreConfig {
+    pub targets: Vec<OreTarget>,
+    pub size: i32,
+    pub discard_chance_on_air_exposure: f32,
+}
+
+impl OreConfig {
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let size = json
+            .get("size")
+            .and_then(Value::as_i64)
+            .ok_or_else(|| anyhow!("No \"size\" key in {:?}", json))?;
+
+        let discard_chance_on_air_exposure = json
+            .get("discard_chance_on_air_exposure")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| anyhow!("No \"discard_chance_on_air_exposure\" key in {:?}", json))?;
+
+        Ok(Self {
+            targets: targets_from_json(json, tags, palette)?,
+            size: size as i32,
+            discard_chance_on_air_exposure: discard_chance_on_air_exposure as f32,
+        })
+    }
+
+    /// Equivalent of vanilla `OreFeature::canPlaceOre()`.
+    fn can_place(&self, level: &WorldGenLevel, rng: &mut FeatureRng, state: &BlockState, target: &OreTarget, pos: Vector3) -> bool {
+        if !target.target.test(state, rng) {
+            return false
+        }
+
+        if self.should_skip_air_check(rng) {
+            return true
+        }
+
+        !is_adjacent_to_air(level, pos)
+    }
+
+    fn should_skip_air_check(&self, rng: &mut FeatureRng) -> bool {
+        if self.discard_chance_on_air_exposure <= 0.0 {
+            true
+        } else if self.discard_chance_on_air_exposure >= 1.0 {
+            false
+        } else {
+            rng.next_f32() >= self.discard_chance_on_air_exposure
+        }
+    }
+}
+
+fn targets_from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Vec<OreTarget>> {
+    let Some(Value::Array(targets)) = json.get("targets") else {
+        return Err(anyhow!("No \"targets\" key in {:?}", json))
+    };
+
+    targets
+        .iter()
+        .map(|target| {
+            let (Some(rule), Some(state)) = (target.get("target"), target.get("state")) else {
+                return Err(anyhow!("Expected \"target\" and \"state\" keys in {:?}", target))
+            };
+
+            Ok(OreTarget {
+                target: RuleTests::from_json(rule, tags, palette)?,
+                state: block_state_from_json(state, palette)?,
+            })
+        })
+        .collect()
+}
+
+fn is_adjacent_to_air(level: &WorldGenLevel, pos: Vector3) -> bool {
+    Direction::ALL
+        .iter()
+        .any(|direction| level.block_state(direction.relative(pos)).block().properties().is_air)
+}
+
+/// Blob of ore shaped by spheres along a random line.
+pub struct OreFeature {
+    pub config: OreConfig,
+}
+
+impl OreFeature {
+    #[allow(clippy::too_many_arguments)]
+    fn do_place(
+        &self,
+        level: &mut WorldGenLevel,
+        rng: &mut FeatureRng,
+        min_x: f64,
+        max_x: f64,
+        min_z: f64,
+        max_z: f64,
+        min_y: f64,
+        max_y: f64,
+        x: i32,
+        y: i32,
+        z: i32,
+        width: i32,
+        height: i32,
+    ) -> bool {
+        let size = self.config.size as usize;
+        let mut placed = 0;
+        let mut visited = vec![false; (width * height * width) as usize];
+
+        // Centres and radii of the spheres.
+        let mut spheres = Vec::with_capacity(size);
+        for i in 0..size {
+            let part = i as f32 / size as f32;
+            let sphere_x = lerp(part as f64, min_x, max_x);
+            let sphere_y = lerp(part as f64, min_y, max_y);
+            let sphere_z = lerp(part as f64, min_z, max_z);
+            let scale = rng.next_f64() * size as f64 / 16.0;
+            let radius = ((sin(std::f32::consts::PI * part) + 1.0) as f64 * scale + 1.0) / 2.0;
+
+            spheres.push([sphere_x, sphere_y, sphere_z, radius]);
+        }
+
+        // Spheres fully contained in others are discarded.
+        for i in 0..size.saturating_sub(1) {
+            if spheres[i][3] <= 0.0 {
+                continue
+            }
+
+            for j in i + 1..size {
+                if spheres[j][3] <= 0.0 {
+                    continue
+                }
+
+                let dx = spheres[i][0] - spheres[j][0];
+                let dy = spheres[i][1] - spheres[j][1];
+                let dz = spheres[i][2] - spheres[j][2];
+                let dr = spheres[i][3] - spheres[j][3];
+
+                if dr * dr > dx * dx + dy * dy + dz * dz {
+                    if dr > 0.0 {
+                        spheres[j][3] = -1.0;
+                    } else {
+                        spheres[i][3] = -1.0;
+                    }
+                }
+            }
+        }
+
+        for [sphere_x, sphere_y, sphere_z, radius] in spheres {
+            if radius < 0.0 {
+                continue
+            }
+
+            let from_x = floor(sphere_x - radius).max(x);
+            let from_y = floor(sphere_y - radius).max(y);
+            let from_z = floor(sphere_z - radius).max(z);
+            let to_x = floor(sphere_x + radius).max(from_x);
+            let to_y = floor(sphere_y + radius).max(from_y);
+            let to_z = floor(sphere_z + radius).max(from_z);
+
+            for block_x in from_x..=to_x {
+                let dx = (block_x as f64 + 0.5 - sphere_x) / radius;
+                if dx * dx >= 1.0 {
+                    continue
+                }
+
+                for block_y in from_y..=to_y {
+                    let dy = (block_y as f64 + 0.5 - sphere_y) / radius;
+                    if dx * dx + dy * dy >= 1.0 {
+                        continue
+                    }
+
+                    for block_z in from_z..=to_z {
+                        let dz = (block_z as f64 + 0.5 - sphere_z) / radius;
+                        if dx * dx + dy * dy + dz * dz >= 1.0 || level.is_outside_build_height(block_y) {
+                            continue
+                        }
+
+                        let index = (block_x - x + (block_y - y) * width + (block_z - z) * width * height) as usize;
+                        if visited[index] {
+                            continue
+                        }
+                        visited[index] = true;
+
+                        let pos = Vector3::new(block_x, block_y, block_z);
+                        if !level.ensure_can_write(pos) {
+                            continue
+                        }
+
+                        let state = level.block_state(pos);
+                        for target in &self.config.targets {
+                            if self.config.can_place(level, rng, &state, target, pos) {
+                                level.set_block_state(pos, target.state.clone());
+                                placed += 1;
+                                break
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        placed > 0
+    }
+}
+
+impl Feature for OreFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let angle = rng.next_f32() * std::f32::consts::PI;
+        let spread = self.config.size as f32 / 8.0;
+        let margin = ((self.config.size as f32 / 16.0 * 2.0 + 1.0) / 2.0).ceil() as i32;
+
+        let min_x = origin.x as f64 + (angle as f64).sin() * spread as f64;
+        let max_x = origin.x as f64 - (angle as f64).sin() * spread as f64;
+        let min_z = origin.z as f64 + (angle as f64).cos() * spread as f64;
+        let max_z = origin.z as f64 - (angle as f64).cos() * spread as f64;
+        let min_y = (origin.y + rng.next_u32(3) as i32 - 2) as f64;
+        let max_y = (origin.y + rng.next_u32(3) as i32 - 2) as f64;
+
+        let x = origin.x - spread.ceil() as i32 - margin;
+        let y = origin.y - 2 - margin;
+        let z = origin.z - spread.ceil() as i32 - margin;
+        let width = 2 * (spread.ceil() as i32 + margin);
+        let height = 2 * (2 + margin);
+
+        for block_x in x..=x + width {
+            for block_z in z..=z + width {
+                if y <= level.height(HeightmapType::OceanFloorWg, block_x, block_z) {
+                    return self.do_place(level, rng, min_x, max_x, min_z, max_z, min_y, max_y, x, y, z, width, height)
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// Single blocks of ore scattered around the origin.
+pub struct ScatteredOreFeature {
+    pub config: OreConfig,
+}
+
+impl ScatteredOreFeature {
+    const MAX_DIST_FROM_ORIGIN: i32 = 7;
+
+    fn offset(rng: &mut FeatureRng, distance: i32) -> i32 {
+        // Java Math.round(float).
+        (((rng.next_f32() - rng.next_f32()) * distance as f32) as f64 + 0.5).floor() as i32
+    }
+}
+
+impl Feature for ScatteredOreFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let count = rng.next_u32((self.config.size + 1) as u32);
+
+        for i in 0..count as i32 {
+            let distance = i.min(Self::MAX_DIST_FROM_ORIGIN);
+            let x = Self::offset(rng, distance);
+            let y = Self::offset(rng, distance);
+            let z = Self::offset(rng, distance);
+            let pos = Vector3::new(origin.x + x, origin.y + y, origin.z + z);
+
+            let state = level.block_state(pos);
+            for target in &self.config.targets {
+                if self.config.can_place(level, rng, &state, target, pos) {
+                    level.set_block_state(pos, target.state.clone());
+                    break
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Replaces the block at the origin with the state of the first target,
+/// whose rule test it passes.
+pub struct ReplaceSingleBlockFeature {
+    pub targets: Vec<OreTarget>,
+}
+
+impl ReplaceSingleBlockFeature {
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        Ok(Self {
+            targets: targets_from_json(json, tags, palette)?,
+        })
+    }
+}
+
+impl Feature for ReplaceSingleBlockFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let state = level.block_state(origin);
+
+        for target in &self.targets {
+            if target.target.test(&state, rng) {
+                level.set_block_state(origin, target.state.clone());
+                break
+            }
+        }
+
+        true
+    }
+}
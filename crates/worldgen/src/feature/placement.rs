@@ -0,0 +1,376 @@
+use crate::biome::noise::INFO_NOISE;
+use crate::feature::level::WorldGenLevel;
+use crate::feature::predicate::{BlockPredicate, BlockPredicates};
+use crate::feature::{Direction, FeatureRng};
+use crate::noise::perlin::noise::Noise;
+use crate::provider::{HeightProvider, IntProvider};
+use crate::rng::Rng;
+use spherix_math::vector::{Vector2f, Vector3};
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::heightmap::HeightmapType;
+
+pub enum PlacementModifiers {
+    Biome(BiomeFilter),
+    BlockPredicateFilter(BlockPredicateFilter),
+    RarityFilter(RarityFilter),
+    SurfaceRelativeThresholdFilter(SurfaceRelativeThresholdFilter),
+    SurfaceWaterDepthFilter(SurfaceWaterDepthFilter),
+    CarvingMask(CarvingMaskPlacement),
+    Count(CountPlacement),
+    CountOnEveryLayer(CountOnEveryLayerPlacement),
+    EnvironmentScan(EnvironmentScanPlacement),
+    Heightmap(HeightmapPlacement),
+    HeightRange(HeightRangePlacement),
+    InSquare(InSquarePlacement),
+    NoiseBasedCount(NoiseBasedCountPlacement),
+    NoiseThresholdCount(NoiseThresholdCountPlacement),
+    RandomOffset(RandomOffsetPlacement),
+}
+
+impl PlacementModifier for PlacementModifiers {
+    fn positions(&self, ctx: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        match self {
+            PlacementModifiers::Biome(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::BlockPredicateFilter(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::RarityFilter(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::SurfaceRelativeThresholdFilter(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::SurfaceWaterDepthFilter(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::CarvingMask(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::Count(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::CountOnEveryLayer(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::EnvironmentScan(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::Heightmap(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::HeightRange(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::InSquare(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::NoiseBasedCount(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::NoiseThresholdCount(x) => x.positions(ctx, rng, pos),
+            PlacementModifiers::RandomOffset(x) => x.positions(ctx, rng, pos),
+        }
+    }
+}
+
+pub struct PlacementContext<'a, 'b> {
+    pub level: &'a WorldGenLevel<'b>,
+    /// Name of the placed feature listed by biomes, if it is the one being
+    /// placed. Features nested into other features have none.
+    pub top_feature: Option<&'a str>,
+}
+
+pub trait PlacementModifier {
+    /// Turns the position into zero or more positions.
+    fn positions(&self, ctx: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3>;
+}
+
+#[inline]
+fn filter(should_place: bool, pos: Vector3) -> Vec<Vector3> {
+    if should_place {
+        vec![pos]
+    } else {
+        Vec::new()
+    }
+}
+
+#[inline]
+fn repeat(count: i32, pos: Vector3) -> Vec<Vector3> {
+    vec![pos; count.max(0) as usize]
+}
+
+/// Passes positions in biomes, which list the placed feature.
+pub struct BiomeFilter;
+
+impl PlacementModifier for BiomeFilter {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        // Vanilla fails on attempt to check the biome of a nested feature.
+        let Some(top_feature) = ctx.top_feature else {
+            return Vec::new()
+        };
+
+        let biome = ctx.level.biome(pos);
+
+        filter(ctx.level.features().biome_has_feature(&biome, top_feature), pos)
+    }
+}
+
+pub struct BlockPredicateFilter {
+    pub predicate: BlockPredicates,
+}
+
+impl PlacementModifier for BlockPredicateFilter {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        filter(self.predicate.test(ctx.level, pos), pos)
+    }
+}
+
+/// Passes positions with the chance of 1 / `chance`.
+pub struct RarityFilter {
+    pub chance: i32,
+}
+
+impl PlacementModifier for RarityFilter {
+    fn positions(&self, _: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        filter(rng.next_f32() < 1.0 / self.chance as f32, pos)
+    }
+}
+
+/// Passes positions within the range relative to the surface.
+pub struct SurfaceRelativeThresholdFilter {
+    pub heightmap: HeightmapType,
+    pub min_inclusive: i32,
+    pub max_inclusive: i32,
+}
+
+impl PlacementModifier for SurfaceRelativeThresholdFilter {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let height = ctx.level.height(self.heightmap, pos.x, pos.z) as i64;
+        let min = height + self.min_inclusive as i64;
+        let max = height + self.max_inclusive as i64;
+
+        filter(min <= pos.y as i64 && pos.y as i64 <= max, pos)
+    }
+}
+
+/// Passes positions, where the water above the ocean floor is not deeper than
+/// `max_water_depth`.
+pub struct SurfaceWaterDepthFilter {
+    pub max_water_depth: i32,
+}
+
+impl PlacementModifier for SurfaceWaterDepthFilter {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let ocean_floor = ctx.level.height(HeightmapType::OceanFloor, pos.x, pos.z);
+        let world_surface = ctx.level.height(HeightmapType::WorldSurface, pos.x, pos.z);
+
+        filter(world_surface - ocean_floor <= self.max_water_depth, pos)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarvingStep {
+    Air,
+    Liquid,
+}
+
+/// Turns the position into all blocks of its chunk carved during the step.
+pub struct CarvingMaskPlacement {
+    pub step: CarvingStep,
+}
+
+impl PlacementModifier for CarvingMaskPlacement {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        // There are no liquid carvers, so the liquid mask is always empty.
+        if self.step == CarvingStep::Liquid || !ctx.level.is_in_chunk(pos.x, pos.z) {
+            return Vec::new()
+        }
+
+        let Some(mask) = ctx.level.carving_mask() else {
+            return Vec::new()
+        };
+
+        let min_x = pos.x & !15;
+        let min_z = pos.z & !15;
+
+        mask
+            .positions()
+            .map(|(x, y, z)| Vector3::new(min_x + x, y, min_z + z))
+            .collect()
+    }
+}
+
+pub struct CountPlacement {
+    pub count: IntProvider,
+}
+
+impl PlacementModifier for CountPlacement {
+    fn positions(&self, _: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        repeat(self.count.sample(rng), pos)
+    }
+}
+
+/// Places the feature `count` times on each layer of the ground, e.g. on the
+/// floors of the nether caves.
+pub struct CountOnEveryLayerPlacement {
+    pub count: IntProvider,
+}
+
+impl CountOnEveryLayerPlacement {
+    fn find_on_ground_y(level: &WorldGenLevel, x: i32, y: i32, z: i32, layer: i32) -> Option<i32> {
+        let is_empty = |state: &BlockState| {
+            state.block().properties().is_air || state.block() == Block::WATER || state.block() == Block::LAVA
+        };
+
+        let mut current = 0;
+        let mut above = level.block_state(Vector3::new(x, y, z));
+
+        for y in (level.min_y() + 1..=y).rev() {
+            let state = level.block_state(Vector3::new(x, y - 1, z));
+
+            if !is_empty(&state) && is_empty(&above) && state.block() != Block::BEDROCK {
+                if current == layer {
+                    return Some(y)
+                }
+
+                current += 1;
+            }
+
+            above = state;
+        }
+
+        None
+    }
+}
+
+impl PlacementModifier for CountOnEveryLayerPlacement {
+    fn positions(&self, ctx: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let mut positions = Vec::new();
+        let mut layer = 0;
+
+        loop {
+            let mut found = false;
+
+            // Vanilla samples the count anew on every iteration.
+            let mut i = 0;
+            while i < self.count.sample(rng) {
+                let x = rng.next_u32(16) as i32 + pos.x;
+                let z = rng.next_u32(16) as i32 + pos.z;
+                let height = ctx.level.height(HeightmapType::MotionBlocking, x, z);
+
+                if let Some(y) = Self::find_on_ground_y(ctx.level, x, height, z, layer) {
+                    positions.push(Vector3::new(x, y, z));
+                    found = true;
+                }
+
+                i += 1;
+            }
+
+            if !found {
+                break
+            }
+
+            layer += 1;
+        }
+
+        positions
+    }
+}
+
+/// Moves the position up or down until the target condition is met.
+pub struct EnvironmentScanPlacement {
+    pub direction_of_search: Direction,
+    pub target_condition: BlockPredicates,
+    pub allowed_search_condition: BlockPredicates,
+    pub max_steps: i32,
+}
+
+impl PlacementModifier for EnvironmentScanPlacement {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, mut pos: Vector3) -> Vec<Vector3> {
+        if !self.allowed_search_condition.test(ctx.level, pos) {
+            return Vec::new()
+        }
+
+        for _ in 0..self.max_steps {
+            if self.target_condition.test(ctx.level, pos) {
+                return vec![pos]
+            }
+
+            pos = self.direction_of_search.relative(pos);
+
+            if ctx.level.is_outside_build_height(pos.y) {
+                return Vec::new()
+            }
+
+            if !self.allowed_search_condition.test(ctx.level, pos) {
+                break
+            }
+        }
+
+        filter(self.target_condition.test(ctx.level, pos), pos)
+    }
+}
+
+/// Moves the position to the surface.
+pub struct HeightmapPlacement {
+    pub heightmap: HeightmapType,
+}
+
+impl PlacementModifier for HeightmapPlacement {
+    fn positions(&self, ctx: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let y = ctx.level.height(self.heightmap, pos.x, pos.z);
+
+        filter(y > ctx.level.min_y(), Vector3::new(pos.x, y, pos.z))
+    }
+}
+
+pub struct HeightRangePlacement {
+    pub height: HeightProvider,
+}
+
+impl PlacementModifier for HeightRangePlacement {
+    fn positions(&self, ctx: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        vec![Vector3::new(pos.x, self.height.sample(rng, &ctx.level.gen_ctx), pos.z)]
+    }
+}
+
+/// Moves the position randomly within its chunk.
+pub struct InSquarePlacement;
+
+impl PlacementModifier for InSquarePlacement {
+    fn positions(&self, _: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let x = rng.next_u32(16) as i32 + pos.x;
+        let z = rng.next_u32(16) as i32 + pos.z;
+
+        vec![Vector3::new(x, pos.y, z)]
+    }
+}
+
+/// Count, which grows with the value of the biome info noise.
+pub struct NoiseBasedCountPlacement {
+    pub noise_to_count_ratio: i32,
+    pub noise_factor: f64,
+    pub noise_offset: f64,
+}
+
+impl PlacementModifier for NoiseBasedCountPlacement {
+    fn positions(&self, _: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let noise = INFO_NOISE.sample(Vector2f::new(pos.x as f64 / self.noise_factor, pos.z as f64 / self.noise_factor));
+        let count = ((noise + self.noise_offset) * self.noise_to_count_ratio as f64).ceil() as i32;
+
+        repeat(count, pos)
+    }
+}
+
+/// One of two counts, depending on whether the biome info noise is below the
+/// level.
+pub struct NoiseThresholdCountPlacement {
+    pub noise_level: f64,
+    pub below_noise: i32,
+    pub above_noise: i32,
+}
+
+impl PlacementModifier for NoiseThresholdCountPlacement {
+    fn positions(&self, _: &PlacementContext, _: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let noise = INFO_NOISE.sample(Vector2f::new(pos.x as f64 / 200.0, pos.z as f64 / 200.0));
+        let count = if noise < self.noise_level {
+            self.below_noise
+        } else {
+            self.above_noise
+        };
+
+        repeat(count, pos)
+    }
+}
+
+pub struct RandomOffsetPlacement {
+    pub xz_spread: IntProvider,
+    pub y_spread: IntProvider,
+}
+
+impl PlacementModifier for RandomOffsetPlacement {
+    fn positions(&self, _: &PlacementContext, rng: &mut FeatureRng, pos: Vector3) -> Vec<Vector3> {
+        let x = pos.x + self.xz_spread.sample(rng);
+        let y = pos.y + self.y_spread.sample(rng);
+        let z = pos.z + self.xz_spread.sample(rng);
+
+        vec![Vector3::new(x, y, z)]
+    }
+}
@@ -0,0 +1,200 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::Direction;
+use crate::tag::BlockSet;
+use spherix_math::vector::Vector3;
+use spherix_world::block::block::Block;
+use spherix_world::block::material::Material;
+use spherix_world::block::state::BlockState;
+use spherix_world::block::variant::{Variant, Waterlogged};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+pub enum BlockPredicates {
+    MatchingBlocks(MatchingBlocksPredicate),
+    MatchingFluids(MatchingFluidsPredicate),
+    HasSturdyFace(HasSturdyFacePredicate),
+    Solid(SolidPredicate),
+    Replaceable(ReplaceablePredicate),
+    WouldSurvive(WouldSurvivePredicate),
+    InsideWorldBounds(InsideWorldBoundsPredicate),
+    AnyOf(AnyOfPredicate),
+    AllOf(AllOfPredicate),
+    Not(Box<NotPredicate>),
+    True,
+}
+
+impl BlockPredicate for BlockPredicates {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        match self {
+            BlockPredicates::MatchingBlocks(x) => x.test(level, pos),
+            BlockPredicates::MatchingFluids(x) => x.test(level, pos),
+            BlockPredicates::HasSturdyFace(x) => x.test(level, pos),
+            BlockPredicates::Solid(x) => x.test(level, pos),
+            BlockPredicates::Replaceable(x) => x.test(level, pos),
+            BlockPredicates::WouldSurvive(x) => x.test(level, pos),
+            BlockPredicates::InsideWorldBounds(x) => x.test(level, pos),
+            BlockPredicates::AnyOf(x) => x.test(level, pos),
+            BlockPredicates::AllOf(x) => x.test(level, pos),
+            BlockPredicates::Not(x) => x.test(level, pos),
+            BlockPredicates::True => true,
+        }
+    }
+}
+
+pub trait BlockPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool;
+}
+
+#[inline]
+fn offset(pos: Vector3, offset: Vector3) -> Vector3 {
+    Vector3::new(pos.x + offset.x, pos.y + offset.y, pos.z + offset.z)
+}
+
+/// Fluid of the block state, as the id of its fluid type.
+pub fn fluid(state: &BlockState) -> Option<&'static str> {
+    let material = state.block().properties().material();
+
+    if state.block() == Block::LAVA {
+        Some("minecraft:lava")
+    } else if material.liquid
+        || material == &Material::WATER_PLANT
+        || material == &Material::REPLACEABLE_WATER_PLANT
+        || state.variants().contains(&Variant::Waterlogged(Waterlogged(true))) {
+        Some("minecraft:water")
+    } else {
+        None
+    }
+}
+
+/// Covers both `matching_blocks` and `matching_block_tag`.
+pub struct MatchingBlocksPredicate {
+    pub offset: Vector3,
+    pub blocks: BlockSet,
+}
+
+impl BlockPredicate for MatchingBlocksPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        self.blocks.contains(level.block_state(offset(pos, self.offset)).block())
+    }
+}
+
+pub struct MatchingFluidsPredicate {
+    pub offset: Vector3,
+    pub fluids: HashSet<String>,
+}
+
+impl BlockPredicate for MatchingFluidsPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        fluid(&level.block_state(offset(pos, self.offset))).is_some_and(|fluid| self.fluids.contains(fluid))
+    }
+}
+
+/// Shapes of blocks are not known, so full solid blocks are considered to be
+/// the only ones with sturdy faces.
+pub struct HasSturdyFacePredicate {
+    pub offset: Vector3,
+    pub direction: Direction,
+}
+
+impl BlockPredicate for HasSturdyFacePredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        is_face_sturdy(&level.block_state(offset(pos, self.offset)))
+    }
+}
+
+/// Whether the faces of the block are sturdy, see [`HasSturdyFacePredicate`].
+pub fn is_face_sturdy(state: &BlockState) -> bool {
+    let material = state.block().properties().material();
+
+    material.solid_blocking && material.blocks_motion
+}
+
+pub struct SolidPredicate {
+    pub offset: Vector3,
+}
+
+impl BlockPredicate for SolidPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        level.block_state(offset(pos, self.offset)).block().properties().material().solid
+    }
+}
+
+pub struct ReplaceablePredicate {
+    pub offset: Vector3,
+}
+
+impl BlockPredicate for ReplaceablePredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        level.block_state(offset(pos, self.offset)).block().properties().material().replaceable
+    }
+}
+
+/// Survival rules of individual blocks are not modelled. Plants are expected
+/// to stand on dirt, water plants to be under water, and the rest of blocks to
+/// survive anywhere.
+pub struct WouldSurvivePredicate {
+    pub offset: Vector3,
+    pub state: Arc<BlockState>,
+    /// Blocks of the `minecraft:dirt` tag.
+    pub dirt: BlockSet,
+}
+
+impl BlockPredicate for WouldSurvivePredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        can_survive(level, &self.state, offset(pos, self.offset), &self.dirt)
+    }
+}
+
+/// Approximation of vanilla `BlockState::canSurvive()`, see [`WouldSurvivePredicate`].
+pub fn can_survive(level: &WorldGenLevel, state: &BlockState, pos: Vector3, dirt: &BlockSet) -> bool {
+    let below = level.block_state(Vector3::new(pos.x, pos.y - 1, pos.z));
+    let material = state.block().properties().material();
+
+    if material == &Material::PLANT || material == &Material::REPLACEABLE_PLANT {
+        dirt.contains(below.block())
+    } else if material == &Material::WATER_PLANT || material == &Material::REPLACEABLE_WATER_PLANT {
+        level.block_state(pos).block() == Block::WATER && below.block().properties().material().solid_blocking
+    } else {
+        true
+    }
+}
+
+pub struct InsideWorldBoundsPredicate {
+    pub offset: Vector3,
+}
+
+impl BlockPredicate for InsideWorldBoundsPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        !level.is_outside_build_height(offset(pos, self.offset).y)
+    }
+}
+
+pub struct AnyOfPredicate {
+    pub predicates: Vec<BlockPredicates>,
+}
+
+impl BlockPredicate for AnyOfPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        self.predicates.iter().any(|predicate| predicate.test(level, pos))
+    }
+}
+
+pub struct AllOfPredicate {
+    pub predicates: Vec<BlockPredicates>,
+}
+
+impl BlockPredicate for AllOfPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        self.predicates.iter().all(|predicate| predicate.test(level, pos))
+    }
+}
+
+pub struct NotPredicate {
+    pub predicate: BlockPredicates,
+}
+
+impl BlockPredicate for NotPredicate {
+    fn test(&self, level: &WorldGenLevel, pos: Vector3) -> bool {
+        !self.predicate.test(level, pos)
+    }
+}
@@ -0,0 +1,212 @@
+use crate::feature::placement::PlacementModifiers;
+use crate::feature::sorter::features_per_step;
+use crate::feature::{ConfiguredFeature, PlacedFeature};
+use crate::noise::json::Resolver;
+use crate::pack::DataPacks;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::chunk::biome::Biome;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Placed feature at its index within the decoration step. The feature is
+/// absent, if it failed to load, e.g. because its type is not supported yet.
+/// The index is still reserved, since it is part of the seed of the features
+/// following it.
+pub struct StepFeature {
+    pub name: String,
+    pub feature: Option<Arc<PlacedFeature>>,
+}
+
+/// Placed features of all decoration steps, ordered the way vanilla
+/// `FeatureSorter` orders them, along with the features listed by each biome.
+pub struct FeatureRegistry {
+    steps: Vec<Vec<StepFeature>>,
+    /// Indices of the features each biome lists, per step, in ascending order.
+    biome_steps: HashMap<String, Vec<Vec<usize>>>,
+    biome_features: HashMap<String, HashSet<String>>,
+    failed: Vec<(String, String)>,
+}
+
+impl FeatureRegistry {
+    /// Builds the registry out of the feature lists of the biomes, given in the
+    /// order of the possible biomes of the biome source. Every placed feature
+    /// is resolved once by its name.
+    pub fn new<F>(biomes: Vec<(String, Vec<Vec<String>>)>, mut resolve: F) -> anyhow::Result<Self>
+    where
+        F: FnMut(&str) -> anyhow::Result<PlacedFeature>
+    {
+        let lists = biomes.iter().map(|(_, steps)| steps.clone()).collect::<Vec<_>>();
+        let sorted = features_per_step(&lists)?;
+
+        let mut failed = Vec::new();
+        let steps = sorted
+            .into_iter()
+            .map(|names| {
+                names
+                    .into_iter()
+                    .map(|name| {
+                        let feature = match resolve(&name) {
+                            Ok(feature) => Some(Arc::new(feature)),
+                            Err(e) => {
+                                failed.push((name.clone(), e.to_string()));
+                                None
+                            }
+                        };
+
+                        StepFeature { name, feature }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let indices = steps
+            .iter()
+            .map(|features| {
+                features
+                    .iter()
+                    .enumerate()
+                    .map(|(index, feature)| (feature.name.as_str(), index))
+                    .collect::<HashMap<_, _>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut biome_steps = HashMap::new();
+        let mut biome_features = HashMap::new();
+
+        for (biome, lists) in biomes {
+            let per_step = lists
+                .iter()
+                .enumerate()
+                .map(|(step, names)| {
+                    let mut list = names.iter().map(|name| indices[step][name.as_str()]).collect::<Vec<_>>();
+                    list.sort_unstable();
+                    list
+                })
+                .collect();
+
+            biome_features.insert(biome.clone(), lists.into_iter().flatten().collect());
+            biome_steps.insert(biome, per_step);
+        }
+
+        Ok(Self {
+            steps,
+            biome_steps,
+            biome_features,
+            failed,
+        })
+    }
+
+    /// Loads feature lists of the possible biomes from the data packs and
+    /// resolves the placed features they refer to.
+    pub fn load(
+        packs: &DataPacks,
+        possible_biomes: &[String],
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> anyhow::Result<Self> {
+        let biomes = possible_biomes
+            .iter()
+            .map(|biome| {
+                let json = packs.json("worldgen/biome", biome)?;
+
+                Ok((biome.clone(), biome_features_from_json(&json)?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Self::new(biomes, |name| {
+            PlacedFeature::from_json(&Value::String(name.to_owned()), features, placements)
+        })
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn feature(&self, step: usize, index: usize) -> &StepFeature {
+        &self.steps[step][index]
+    }
+
+    /// Indices of the features the biome lists in the step, in ascending order.
+    pub fn biome_step(&self, biome: &Biome, step: usize) -> &[usize] {
+        self.biome_steps
+            .get(biome.name_ref())
+            .and_then(|steps| steps.get(step))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn biome_has_feature(&self, biome: &Biome, name: &str) -> bool {
+        self.biome_features
+            .get(biome.name_ref())
+            .is_some_and(|features| features.contains(name))
+    }
+
+    /// Names of the placed features, which failed to load, with the reasons.
+    pub fn failed(&self) -> &[(String, String)] {
+        &self.failed
+    }
+}
+
+/// Reads the `features` list of the biome, i.e. the placed features of each
+/// decoration step.
+pub fn biome_features_from_json(json: &Value) -> anyhow::Result<Vec<Vec<String>>> {
+    let Some(Value::Array(steps)) = json.get("features") else {
+        return Ok(Vec::new())
+    };
+
+    steps
+        .iter()
+        .map(|step| match step {
+            Value::String(name) => Ok(vec![name.clone()]),
+            Value::Array(names) => names
+                .iter()
+                .map(|name| name.as_str().map(str::to_owned).ok_or_else(|| anyhow!("Expected string, found {:?}", name)))
+                .collect(),
+            value => Err(anyhow!("Expected string or array, found {:?}", value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::registry::FeatureRegistry;
+    use crate::feature::{NoOpFeature, PlacedFeature};
+    use anyhow::anyhow;
+    use std::sync::Arc;
+
+    fn steps(steps: &[&[&str]]) -> Vec<Vec<String>> {
+        steps
+            .iter()
+            .map(|features| features.iter().map(|name| name.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn registry_indices() {
+        let registry = FeatureRegistry::new(
+            vec![
+                ("minecraft:plains".to_owned(), steps(&[&["lake"], &["ore_coal", "ore_iron"]])),
+                ("minecraft:desert".to_owned(), steps(&[&[], &["ore_gold", "ore_iron"]])),
+            ],
+            |name| match name {
+                "ore_gold" => Err(anyhow!("Unsupported")),
+                _ => Ok(PlacedFeature { feature: Arc::new(NoOpFeature), placement: Vec::new() }),
+            },
+        ).unwrap();
+
+        assert_eq!(2, registry.step_count());
+        assert_eq!("ore_gold", registry.feature(1, 0).name);
+        assert_eq!("ore_coal", registry.feature(1, 1).name);
+        assert_eq!("ore_iron", registry.feature(1, 2).name);
+        assert!(registry.feature(1, 0).feature.is_none());
+        assert!(registry.feature(1, 1).feature.is_some());
+
+        assert_eq!(vec![vec![0], vec![1, 2]], registry.biome_steps["minecraft:plains"]);
+        assert_eq!(vec![vec![], vec![0, 2]], registry.biome_steps["minecraft:desert"]);
+        assert!(registry.biome_features["minecraft:desert"].contains("ore_iron"));
+
+        assert_eq!(1, registry.failed().len());
+        assert_eq!("ore_gold", registry.failed()[0].0);
+    }
+}
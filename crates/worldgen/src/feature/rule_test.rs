@@ -0,0 +1,204 @@
+use crate::feature::state::block_state_from_json;
+use crate::rng::Rng;
+use crate::tag::{BlockSet, BlockTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::block::block::{Block, BLOCKS};
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+/// Test of a single block state, e.g. whether an ore is able to replace it.
+/// Unlike block predicates, it does not look at the surroundings.
+pub enum RuleTests {
+    AlwaysTrue,
+    BlockMatch(BlockMatchTest),
+    BlockStateMatch(BlockStateMatchTest),
+    TagMatch(TagMatchTest),
+    RandomBlockMatch(RandomBlockMatchTest),
+    RandomBlockStateMatch(RandomBlockStateMatchTest),
+}
+
+impl RuleTests {
+    /// Rule tests are dispatched on `predicate_type` rather than `type`, so they
+    /// are read without the `Resolver`.
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("predicate_type") else {
+            return Err(anyhow!("No \"predicate_type\" key in {:?}", json))
+        };
+
+        let probability = || {
+            json.get("probability")
+                .and_then(Value::as_f64)
+                .map(|probability| probability as f32)
+                .ok_or_else(|| anyhow!("No \"probability\" key in {:?}", json))
+        };
+
+        let test = match ty.as_str() {
+            "minecraft:always_true" => RuleTests::AlwaysTrue,
+            "minecraft:block_match" => RuleTests::BlockMatch(BlockMatchTest {
+                block: block_from_json(json)?,
+            }),
+            "minecraft:blockstate_match" => RuleTests::BlockStateMatch(BlockStateMatchTest {
+                state: block_state_from_json(field(json, "block_state")?, palette)?,
+            }),
+            "minecraft:tag_match" => {
+                let Some(Value::String(tag)) = json.get("tag") else {
+                    return Err(anyhow!("No \"tag\" key in {:?}", json))
+                };
+
+                RuleTests::TagMatch(TagMatchTest { blocks: tags.tag(tag)? })
+            }
+            "minecraft:random_block_match" => RuleTests::RandomBlockMatch(RandomBlockMatchTest {
+                block: block_from_json(json)?,
+                probability: probability()?,
+            }),
+            "minecraft:random_blockstate_match" => RuleTests::RandomBlockStateMatch(RandomBlockStateMatchTest {
+                state: block_state_from_json(field(json, "block_state")?, palette)?,
+                probability: probability()?,
+            }),
+            _ => return Err(anyhow!("Unknown rule test type {}", ty))
+        };
+
+        Ok(test)
+    }
+}
+
+impl RuleTest for RuleTests {
+    fn test<R: Rng>(&self, state: &BlockState, rng: &mut R) -> bool {
+        match self {
+            RuleTests::AlwaysTrue => true,
+            RuleTests::BlockMatch(x) => x.test(state, rng),
+            RuleTests::BlockStateMatch(x) => x.test(state, rng),
+            RuleTests::TagMatch(x) => x.test(state, rng),
+            RuleTests::RandomBlockMatch(x) => x.test(state, rng),
+            RuleTests::RandomBlockStateMatch(x) => x.test(state, rng),
+        }
+    }
+}
+
+pub trait RuleTest {
+    fn test<R: Rng>(&self, state: &BlockState, rng: &mut R) -> bool;
+}
+
+fn field<'a>(json: &'a Value, name: &str) -> anyhow::Result<&'a Value> {
+    json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json))
+}
+
+fn block_from_json(json: &Value) -> anyhow::Result<&'static Block> {
+    let Some(Value::String(name)) = json.get("block") else {
+        return Err(anyhow!("No \"block\" key in {:?}", json))
+    };
+
+    BLOCKS
+        .get(name.as_str())
+        .copied()
+        .ok_or_else(|| anyhow!("Unknown block {}", name))
+}
+
+pub struct BlockMatchTest {
+    pub block: &'static Block,
+}
+
+impl RuleTest for BlockMatchTest {
+    fn test<R: Rng>(&self, state: &BlockState, _: &mut R) -> bool {
+        state.block() == self.block
+    }
+}
+
+pub struct BlockStateMatchTest {
+    pub state: Arc<BlockState>,
+}
+
+impl RuleTest for BlockStateMatchTest {
+    fn test<R: Rng>(&self, state: &BlockState, _: &mut R) -> bool {
+        state == self.state.as_ref()
+    }
+}
+
+pub struct TagMatchTest {
+    pub blocks: BlockSet,
+}
+
+impl RuleTest for TagMatchTest {
+    fn test<R: Rng>(&self, state: &BlockState, _: &mut R) -> bool {
+        self.blocks.contains(state.block())
+    }
+}
+
+pub struct RandomBlockMatchTest {
+    pub block: &'static Block,
+    pub probability: f32,
+}
+
+impl RuleTest for RandomBlockMatchTest {
+    fn test<R: Rng>(&self, state: &BlockState, rng: &mut R) -> bool {
+        state.block() == self.block && rng.next_f32() < self.probability
+    }
+}
+
+pub struct RandomBlockStateMatchTest {
+    pub state: Arc<BlockState>,
+    pub probability: f32,
+}
+
+impl RuleTest for RandomBlockStateMatchTest {
+    fn test<R: Rng>(&self, state: &BlockState, rng: &mut R) -> bool {
+        state == self.state.as_ref() && rng.next_f32() < self.probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::rule_test::{RuleTest, RuleTests};
+    use crate::feature::worldgen_rng;
+    use crate::pack::DataPacks;
+    use crate::tag::BlockTags;
+    use serde_json::json;
+    use spherix_world::block::block::Block;
+    use spherix_world::block::state::BlockState;
+    use spherix_world::block::variant::VariantVec;
+    use spherix_world::chunk::palette::global::GlobalId;
+    use spherix_world::chunk::palette::BlockGlobalPalette;
+    use std::sync::Arc;
+
+    #[test]
+    fn rule_tests() {
+        let tags = BlockTags::new(Arc::new(DataPacks::new(Vec::new())));
+        let mut palette = BlockGlobalPalette::new(1);
+        palette.insert(GlobalId(0), BlockState::new(Block::STONE, true, VariantVec::empty()));
+        palette.insert(GlobalId(1), BlockState::new(Block::GRAVEL, true, VariantVec::empty()));
+
+        let stone = BlockState::new(Block::STONE, true, VariantVec::empty());
+        let gravel = BlockState::new(Block::GRAVEL, true, VariantVec::empty());
+        let mut rng = worldgen_rng(0);
+
+        let always_true = RuleTests::from_json(&json!({"predicate_type": "minecraft:always_true"}), &tags, &palette).unwrap();
+        assert!(always_true.test(&gravel, &mut rng));
+
+        let block_match = RuleTests::from_json(
+            &json!({"predicate_type": "minecraft:block_match", "block": "minecraft:stone"}),
+            &tags,
+            &palette
+        ).unwrap();
+        assert!(block_match.test(&stone, &mut rng));
+        assert!(!block_match.test(&gravel, &mut rng));
+
+        let state_match = RuleTests::from_json(
+            &json!({"predicate_type": "minecraft:blockstate_match", "block_state": {"Name": "minecraft:gravel"}}),
+            &tags,
+            &palette
+        ).unwrap();
+        assert!(state_match.test(&gravel, &mut rng));
+        assert!(!state_match.test(&stone, &mut rng));
+
+        let never = RuleTests::from_json(
+            &json!({"predicate_type": "minecraft:random_block_match", "block": "minecraft:stone", "probability": 0.0}),
+            &tags,
+            &palette
+        ).unwrap();
+        assert!(!never.test(&stone, &mut rng));
+
+        assert!(RuleTests::from_json(&json!({"predicate_type": "minecraft:unknown"}), &tags, &palette).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::anyhow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Feature within a step. Ordered by step first and then by the order in which
+/// features are first met in the biome lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Node {
+    step: usize,
+    index: usize,
+}
+
+/// Equivalent of vanilla `FeatureSorter::buildFeaturesPerStep()`. Orders
+/// features of every decoration step, so that the order of each biome list is
+/// preserved. The position of a feature within its step becomes part of its
+/// seed. Biomes are given as lists of steps, each of which is a list of placed
+/// feature names, in the order of the possible biomes of the biome source.
+pub fn features_per_step(biomes: &[Vec<Vec<String>>]) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut indices: HashMap<&str, usize> = HashMap::new();
+    let mut names = Vec::new();
+    let mut edges: BTreeMap<Node, BTreeSet<Node>> = BTreeMap::new();
+    let mut step_count = 0;
+
+    for steps in biomes {
+        step_count = step_count.max(steps.len());
+
+        let mut list = Vec::new();
+        for (step, features) in steps.iter().enumerate() {
+            for name in features {
+                let index = *indices.entry(name).or_insert_with(|| {
+                    names.push(name.as_str());
+                    names.len() - 1
+                });
+
+                list.push(Node { step, index });
+            }
+        }
+
+        for (i, node) in list.iter().enumerate() {
+            let next = edges.entry(*node).or_default();
+            if let Some(following) = list.get(i + 1) {
+                next.insert(*following);
+            }
+        }
+    }
+
+    let mut visited = BTreeSet::new();
+    let mut on_stack = BTreeSet::new();
+    let mut sorted = Vec::new();
+
+    for node in edges.keys() {
+        if depth_first_search(&edges, &mut visited, &mut on_stack, &mut sorted, *node) {
+            return Err(anyhow!(
+                "Feature order cycle found, involving \"{}\" in step {}",
+                names[node.index],
+                node.step
+            ))
+        }
+    }
+
+    sorted.reverse();
+
+    Ok(
+        (0..step_count)
+            .map(|step| {
+                sorted
+                    .iter()
+                    .filter(|node| node.step == step)
+                    .map(|node| names[node.index].to_owned())
+                    .collect()
+            })
+            .collect()
+    )
+}
+
+/// Returns whether a cycle was found. Visited nodes are pushed to `sorted` in
+/// the post-order.
+fn depth_first_search(
+    edges: &BTreeMap<Node, BTreeSet<Node>>,
+    visited: &mut BTreeSet<Node>,
+    on_stack: &mut BTreeSet<Node>,
+    sorted: &mut Vec<Node>,
+    current: Node,
+) -> bool {
+    if visited.contains(&current) {
+        return false
+    }
+
+    if on_stack.contains(&current) {
+        return true
+    }
+
+    on_stack.insert(current);
+
+    for next in edges.get(&current).into_iter().flatten() {
+        if depth_first_search(edges, visited, on_stack, sorted, *next) {
+            return true
+        }
+    }
+
+    on_stack.remove(&current);
+    visited.insert(current);
+    sorted.push(current);
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::sorter::features_per_step;
+
+    fn biome(steps: &[&[&str]]) -> Vec<Vec<String>> {
+        steps
+            .iter()
+            .map(|features| features.iter().map(|name| name.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_features_per_step() {
+        let sorted = features_per_step(&[
+            biome(&[&["lake"], &["ore_coal", "ore_iron"]]),
+            biome(&[&[], &["ore_gold", "ore_iron"], &["trees"]]),
+            biome(&[&["lake"], &["ore_coal", "ore_gold"]]),
+        ]).unwrap();
+
+        assert_eq!(
+            vec![
+                vec!["lake".to_owned()],
+                vec!["ore_coal".to_owned(), "ore_gold".to_owned(), "ore_iron".to_owned()],
+                vec!["trees".to_owned()],
+            ],
+            sorted
+        );
+    }
+
+    #[test]
+    fn detects_cycles() {
+        assert!(features_per_step(&[
+            biome(&[&["a", "b"]]),
+            biome(&[&["b", "a"]]),
+        ]).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::block::block::BLOCKS;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+/// Reads block state as it is written in worldgen configs, e.g.
+/// `{"Name": "minecraft:oak_log", "Properties": {"axis": "y"}}`. Properties,
+/// which are not listed, take values of the default state.
+pub fn block_state_from_json(json: &Value, palette: &BlockGlobalPalette) -> anyhow::Result<Arc<BlockState>> {
+    let Some(Value::String(name)) = json.get("Name") else {
+        return Err(anyhow!("No \"Name\" key in {:?}", json))
+    };
+
+    let block = BLOCKS
+        .get(name.as_str())
+        .copied()
+        .ok_or_else(|| anyhow!("Unknown block {}", name))?;
+
+    let default = palette
+        .get_default_obj_by_index(&block)
+        .ok_or_else(|| anyhow!("No default state of block {}", name))?;
+
+    let Some(Value::Object(properties)) = json.get("Properties") else {
+        return Ok(default)
+    };
+
+    palette
+        .get_objs_by_index(&block)
+        .into_iter()
+        .flatten()
+        .find(|state| {
+            state.variants().iter().zip(default.variants().iter()).all(|(variant, default)| {
+                match properties.get(variant.prop_name()) {
+                    Some(Value::String(value)) => *value == variant.prop_value(),
+                    _ => variant == default,
+                }
+            })
+        })
+        .cloned()
+        .ok_or_else(|| anyhow!("No state of block {} with properties {:?}", name, properties))
+}
+
+/// State of the same block with the property set to the value, as vanilla
+/// `setValue()` does. Returns `None`, if the block has no such property.
+pub fn with_property(
+    state: &Arc<BlockState>,
+    name: &str,
+    value: &str,
+    palette: &BlockGlobalPalette,
+) -> Option<Arc<BlockState>> {
+    if !state.variants().iter().any(|variant| variant.prop_name() == name) {
+        return None
+    }
+
+    palette
+        .get_objs_by_index(&state.block())?
+        .into_iter()
+        .find(|candidate| {
+            candidate.variants().iter().zip(state.variants().iter()).all(|(variant, current)| {
+                if variant.prop_name() == name {
+                    variant.prop_value() == value
+                } else {
+                    variant == current
+                }
+            })
+        })
+        .cloned()
+}
+
+/// State of the same block with all the properties set at once. Returns
+/// `None`, if the block lacks any of them.
+pub fn with_properties(
+    state: &Arc<BlockState>,
+    properties: &[(&str, String)],
+    palette: &BlockGlobalPalette,
+) -> Option<Arc<BlockState>> {
+    let has_all = properties
+        .iter()
+        .all(|(name, _)| state.variants().iter().any(|variant| variant.prop_name() == *name));
+
+    if !has_all {
+        return None
+    }
+
+    palette
+        .get_objs_by_index(&state.block())?
+        .into_iter()
+        .find(|candidate| {
+            candidate.variants().iter().zip(state.variants().iter()).all(|(variant, current)| {
+                match properties.iter().find(|(name, _)| *name == variant.prop_name()) {
+                    Some((_, value)) => variant.prop_value() == *value,
+                    None => variant == current,
+                }
+            })
+        })
+        .cloned()
+}
+
+/// Value of the property of the block state, if the block has it.
+pub fn property(state: &BlockState, name: &str) -> Option<String> {
+    state
+        .variants()
+        .iter()
+        .find(|variant| variant.prop_name() == name)
+        .map(|variant| variant.prop_value())
+}
@@ -0,0 +1,369 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::predicate::{BlockPredicate, BlockPredicates};
+use crate::feature::state::{block_state_from_json, with_property};
+use crate::feature::FeatureRng;
+use crate::noise::json::Resolver;
+use crate::noise::math::clamped_map;
+use crate::noise::perlin::noise::Noise;
+use crate::noise::perlin::octave::MultiOctaveNoiseFactory;
+use crate::noise::perlin::DefaultNoise;
+use crate::rng::{LcgEntropySrc, Rng, U32EntropySrc, U32EntropySrcRng};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::{Vector3, Vector3f};
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+/// Source of the block states placed by features.
+pub enum BlockStateProviders {
+    Simple(SimpleStateProvider),
+    Weighted(WeightedStateProvider),
+    RotatedBlock(RotatedBlockProvider),
+    NoiseThreshold(NoiseThresholdProvider),
+    Noise(NoiseProvider),
+    DualNoise(DualNoiseProvider),
+}
+
+impl BlockStateProviders {
+    pub fn from_json(json: &Value, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let state = || block_state_from_json(json.get("state").ok_or_else(|| anyhow!("No \"state\" key"))?, palette);
+
+        let provider = match ty.as_str() {
+            "minecraft:simple_state_provider" => BlockStateProviders::Simple(SimpleStateProvider {
+                state: state()?,
+            }),
+            "minecraft:weighted_state_provider" => {
+                let Some(Value::Array(entries)) = json.get("entries") else {
+                    return Err(anyhow!("No \"entries\" key in {:?}", json))
+                };
+
+                let entries = entries
+                    .iter()
+                    .map(|entry| {
+                        let state = block_state_from_json(entry.get("data").ok_or_else(|| anyhow!("No \"data\" key"))?, palette)?;
+                        let weight = entry
+                            .get("weight")
+                            .and_then(Value::as_u64)
+                            .ok_or_else(|| anyhow!("No \"weight\" key in {:?}", entry))?;
+
+                        Ok((state, weight as u32))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                if entries.iter().all(|(_, weight)| *weight == 0) {
+                    return Err(anyhow!("No weighted entries in {:?}", json))
+                }
+
+                BlockStateProviders::Weighted(WeightedStateProvider::new(entries))
+            }
+            "minecraft:rotated_block_provider" => {
+                let state = state()?;
+                let axes = ["x", "y", "z"]
+                    .map(|axis| with_property(&state, "axis", axis, palette).unwrap_or_else(|| state.clone()));
+
+                BlockStateProviders::RotatedBlock(RotatedBlockProvider { axes })
+            }
+            "minecraft:noise_threshold_provider" => BlockStateProviders::NoiseThreshold(NoiseThresholdProvider {
+                noise: NoiseBasedProvider::from_json(json)?,
+                threshold: f64_field(json, "threshold")? as f32,
+                high_chance: f64_field(json, "high_chance")? as f32,
+                default_state: block_state_from_json(field(json, "default_state")?, palette)?,
+                low_states: states_from_json(field(json, "low_states")?, palette)?,
+                high_states: states_from_json(field(json, "high_states")?, palette)?,
+            }),
+            "minecraft:noise_provider" => BlockStateProviders::Noise(NoiseProvider {
+                noise: NoiseBasedProvider::from_json(json)?,
+                states: states_from_json(field(json, "states")?, palette)?,
+            }),
+            "minecraft:dual_noise_provider" => {
+                let variety: Vec<i32> = serde_json::from_value(field(json, "variety")?.clone())?;
+                let [min_variety, max_variety] = variety[..] else {
+                    return Err(anyhow!("Expected \"variety\" to be a range, but given: {:?}", variety))
+                };
+
+                BlockStateProviders::DualNoise(DualNoiseProvider {
+                    noise: NoiseBasedProvider::from_json(json)?,
+                    states: states_from_json(field(json, "states")?, palette)?,
+                    min_variety,
+                    max_variety,
+                    slow_noise: noise_from_json(field(json, "slow_noise")?, json)?,
+                    slow_scale: f64_field(json, "slow_scale")? as f32,
+                })
+            }
+            _ => return Err(anyhow!("Unknown block state provider type {}", ty))
+        };
+
+        Ok(provider)
+    }
+}
+
+impl BlockStateProvider for BlockStateProviders {
+    fn state(&self, rng: &mut FeatureRng, pos: Vector3) -> Arc<BlockState> {
+        match self {
+            BlockStateProviders::Simple(x) => x.state(rng, pos),
+            BlockStateProviders::Weighted(x) => x.state(rng, pos),
+            BlockStateProviders::RotatedBlock(x) => x.state(rng, pos),
+            BlockStateProviders::NoiseThreshold(x) => x.state(rng, pos),
+            BlockStateProviders::Noise(x) => x.state(rng, pos),
+            BlockStateProviders::DualNoise(x) => x.state(rng, pos),
+        }
+    }
+}
+
+pub trait BlockStateProvider {
+    fn state(&self, rng: &mut FeatureRng, pos: Vector3) -> Arc<BlockState>;
+}
+
+pub struct SimpleStateProvider {
+    pub state: Arc<BlockState>,
+}
+
+impl BlockStateProvider for SimpleStateProvider {
+    fn state(&self, _: &mut FeatureRng, _: Vector3) -> Arc<BlockState> {
+        self.state.clone()
+    }
+}
+
+pub struct WeightedStateProvider {
+    entries: Vec<(Arc<BlockState>, u32)>,
+    total_weight: u32,
+}
+
+impl WeightedStateProvider {
+    pub fn new(entries: Vec<(Arc<BlockState>, u32)>) -> Self {
+        let total_weight = entries.iter().map(|(_, weight)| weight).sum();
+
+        Self {
+            entries,
+            total_weight,
+        }
+    }
+}
+
+impl BlockStateProvider for WeightedStateProvider {
+    fn state(&self, rng: &mut FeatureRng, _: Vector3) -> Arc<BlockState> {
+        let mut index = rng.next_u32(self.total_weight) as i64;
+
+        for (state, weight) in &self.entries {
+            index -= *weight as i64;
+            if index < 0 {
+                return state.clone()
+            }
+        }
+
+        unreachable!()
+    }
+}
+
+/// Randomly rotated pillar block, e.g. a log.
+pub struct RotatedBlockProvider {
+    /// The block along the x, y and z axes.
+    pub axes: [Arc<BlockState>; 3],
+}
+
+impl BlockStateProvider for RotatedBlockProvider {
+    fn state(&self, rng: &mut FeatureRng, _: Vector3) -> Arc<BlockState> {
+        self.axes[rng.next_u32(3) as usize].clone()
+    }
+}
+
+fn field<'a>(json: &'a Value, name: &str) -> anyhow::Result<&'a Value> {
+    json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json))
+}
+
+fn f64_field(json: &Value, name: &str) -> anyhow::Result<f64> {
+    field(json, name)?
+        .as_f64()
+        .ok_or_else(|| anyhow!("Expected number \"{}\" in {:?}", name, json))
+}
+
+fn states_from_json(json: &Value, palette: &BlockGlobalPalette) -> anyhow::Result<Vec<Arc<BlockState>>> {
+    let Value::Array(states) = json else {
+        return Err(anyhow!("Expected Array, but given: {:?}", json))
+    };
+
+    if states.is_empty() {
+        return Err(anyhow!("Expected at least one state"))
+    }
+
+    states
+        .iter()
+        .map(|state| block_state_from_json(state, palette))
+        .collect()
+}
+
+/// Noise of the provider, seeded with its own `seed` by the legacy random, as
+/// vanilla `NoiseBasedStateProvider` does.
+fn noise_from_json(params: &Value, provider: &Value) -> anyhow::Result<DefaultNoise> {
+    let seed = field(provider, "seed")?
+        .as_i64()
+        .ok_or_else(|| anyhow!("Expected integer \"seed\" in {:?}", provider))?;
+    let first_octave = field(params, "firstOctave")?
+        .as_i64()
+        .ok_or_else(|| anyhow!("Expected integer \"firstOctave\" in {:?}", params))?;
+    let amplitudes: Vec<f64> = serde_json::from_value(field(params, "amplitudes")?.clone())?;
+
+    Ok(DefaultNoise::create(
+        &mut U32EntropySrcRng::new(LcgEntropySrc::new(seed as u64)),
+        &amplitudes,
+        first_octave as i32,
+    ))
+}
+
+/// Equivalent of vanilla `Util.getRandom()`.
+#[inline]
+fn random_state(states: &[Arc<BlockState>], rng: &mut FeatureRng) -> Arc<BlockState> {
+    states[rng.next_u32(states.len() as u32) as usize].clone()
+}
+
+/// Picks the state by the noise value in range [-1, 1].
+#[inline]
+fn state_by_noise(states: &[Arc<BlockState>], noise: f64) -> Arc<BlockState> {
+    let part = ((1.0 + noise) / 2.0).clamp(0.0, 0.9999);
+
+    states[(part * states.len() as f64) as usize].clone()
+}
+
+/// Noise and scale shared by the noise based providers.
+pub struct NoiseBasedProvider {
+    pub noise: DefaultNoise,
+    pub scale: f32,
+}
+
+impl NoiseBasedProvider {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        Ok(Self {
+            noise: noise_from_json(field(json, "noise")?, json)?,
+            scale: f64_field(json, "scale")? as f32,
+        })
+    }
+
+    #[inline]
+    fn value(&self, pos: Vector3, scale: f32) -> f64 {
+        sample(&self.noise, pos, scale)
+    }
+}
+
+#[inline]
+fn sample(noise: &DefaultNoise, pos: Vector3, scale: f32) -> f64 {
+    let scale = scale as f64;
+
+    noise.sample(Vector3f::new(pos.x as f64 * scale, pos.y as f64 * scale, pos.z as f64 * scale))
+}
+
+/// One of the low states below the noise threshold. Above the threshold, one
+/// of the high states with the chance, or the default state otherwise.
+pub struct NoiseThresholdProvider {
+    pub noise: NoiseBasedProvider,
+    pub threshold: f32,
+    pub high_chance: f32,
+    pub default_state: Arc<BlockState>,
+    pub low_states: Vec<Arc<BlockState>>,
+    pub high_states: Vec<Arc<BlockState>>,
+}
+
+impl BlockStateProvider for NoiseThresholdProvider {
+    fn state(&self, rng: &mut FeatureRng, pos: Vector3) -> Arc<BlockState> {
+        let noise = self.noise.value(pos, self.noise.scale);
+
+        if noise < self.threshold as f64 {
+            random_state(&self.low_states, rng)
+        } else if rng.next_f32() < self.high_chance {
+            random_state(&self.high_states, rng)
+        } else {
+            self.default_state.clone()
+        }
+    }
+}
+
+pub struct NoiseProvider {
+    pub noise: NoiseBasedProvider,
+    pub states: Vec<Arc<BlockState>>,
+}
+
+impl BlockStateProvider for NoiseProvider {
+    fn state(&self, _: &mut FeatureRng, pos: Vector3) -> Arc<BlockState> {
+        state_by_noise(&self.states, self.noise.value(pos, self.noise.scale))
+    }
+}
+
+/// Picks a few states by the slow noise, so that nearby positions share them,
+/// and then one of these by the regular noise.
+pub struct DualNoiseProvider {
+    pub noise: NoiseBasedProvider,
+    pub states: Vec<Arc<BlockState>>,
+    pub min_variety: i32,
+    pub max_variety: i32,
+    pub slow_noise: DefaultNoise,
+    pub slow_scale: f32,
+}
+
+impl BlockStateProvider for DualNoiseProvider {
+    fn state(&self, _: &mut FeatureRng, pos: Vector3) -> Arc<BlockState> {
+        let slow = sample(&self.slow_noise, pos, self.slow_scale);
+        let variety = clamped_map(slow, -1.0, 1.0, self.min_variety as f64, (self.max_variety + 1) as f64) as i32;
+
+        let states = (0..variety.max(1))
+            .map(|i| {
+                let at = Vector3::new(pos.x + i * 54545, pos.y, pos.z + i * 34234);
+
+                state_by_noise(&self.states, sample(&self.slow_noise, at, self.slow_scale))
+            })
+            .collect::<Vec<_>>();
+
+        state_by_noise(&states, self.noise.value(pos, self.noise.scale))
+    }
+}
+
+/// Provider of the first rule, whose predicate holds at the position, or the
+/// fallback one.
+pub struct RuleBasedStateProvider {
+    pub fallback: BlockStateProviders,
+    pub rules: Vec<(BlockPredicates, BlockStateProviders)>,
+}
+
+impl RuleBasedStateProvider {
+    pub fn from_json(
+        json: &Value,
+        palette: &BlockGlobalPalette,
+        predicates: &Resolver<BlockPredicates>,
+    ) -> anyhow::Result<Self> {
+        let Some(fallback) = json.get("fallback") else {
+            return Err(anyhow!("No \"fallback\" key in {:?}", json))
+        };
+
+        let rules = match json.get("rules") {
+            Some(Value::Array(rules)) => rules
+                .iter()
+                .map(|rule| {
+                    let (Some(if_true), Some(then)) = (rule.get("if_true"), rule.get("then")) else {
+                        return Err(anyhow!("Expected \"if_true\" and \"then\" keys in {:?}", rule))
+                    };
+
+                    Ok((predicates.resolve(if_true)?, BlockStateProviders::from_json(then, palette)?))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            fallback: BlockStateProviders::from_json(fallback, palette)?,
+            rules,
+        })
+    }
+
+    pub fn state(&self, level: &WorldGenLevel, rng: &mut FeatureRng, pos: Vector3) -> Arc<BlockState> {
+        for (predicate, provider) in &self.rules {
+            if predicate.test(level, pos) {
+                return provider.state(rng, pos)
+            }
+        }
+
+        self.fallback.state(rng, pos)
+    }
+}
@@ -0,0 +1,318 @@
+use crate::feature::state::with_property;
+use crate::feature::state_provider::{BlockStateProvider, BlockStateProviders};
+use crate::feature::tree::{above, offset, TreeContext};
+use crate::feature::{Direction, FeatureRng};
+use crate::rng::Rng;
+use crate::tag::{BlockSet, BlockTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+/// Decorators run after the tree is placed. They see logs and leaves of the
+/// tree sorted by y.
+pub enum TreeDecorators {
+    TrunkVine(VineStates),
+    LeaveVine(LeaveVineDecorator),
+    Cocoa(CocoaDecorator),
+    Beehive(BeehiveDecorator),
+    AlterGround(AlterGroundDecorator),
+}
+
+impl TreeDecorators {
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let probability = || {
+            json.get("probability")
+                .and_then(Value::as_f64)
+                .map(|probability| probability as f32)
+                .ok_or_else(|| anyhow!("No \"probability\" key in {:?}", json))
+        };
+
+        let decorator = match ty.as_str() {
+            "minecraft:trunk_vine" => TreeDecorators::TrunkVine(VineStates::new(palette)?),
+            "minecraft:leave_vine" => TreeDecorators::LeaveVine(LeaveVineDecorator {
+                probability: probability()?,
+                vines: VineStates::new(palette)?,
+            }),
+            "minecraft:cocoa" => TreeDecorators::Cocoa(CocoaDecorator::new(probability()?, palette)?),
+            "minecraft:beehive" => TreeDecorators::Beehive(BeehiveDecorator {
+                probability: probability()?,
+                nest: default_state(Block::BEE_NEST, palette)
+                    .and_then(|state| with_property(&state, "facing", BeehiveDecorator::FACING.name(), palette))
+                    .ok_or_else(|| anyhow!("No bee nest state"))?,
+            }),
+            "minecraft:alter_ground" => TreeDecorators::AlterGround(AlterGroundDecorator {
+                provider: BlockStateProviders::from_json(
+                    json.get("provider").ok_or_else(|| anyhow!("No \"provider\" key in {:?}", json))?,
+                    palette
+                )?,
+                dirt: tags.tag("minecraft:dirt")?,
+            }),
+            _ => return Err(anyhow!("Unknown tree decorator type {}", ty))
+        };
+
+        Ok(decorator)
+    }
+
+    pub fn place(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, logs: &[Vector3], leaves: &[Vector3]) {
+        match self {
+            TreeDecorators::TrunkVine(vines) => {
+                for log in logs {
+                    vines.place_around(ctx, rng, *log, |rng| rng.next_u32(3) > 0, false);
+                }
+            }
+            TreeDecorators::LeaveVine(x) => {
+                for leaf in leaves {
+                    x.vines.place_around(ctx, rng, *leaf, |rng| rng.next_f32() < x.probability, true);
+                }
+            }
+            TreeDecorators::Cocoa(x) => x.place(ctx, rng, logs),
+            TreeDecorators::Beehive(x) => x.place(ctx, rng, logs, leaves),
+            TreeDecorators::AlterGround(x) => x.place(ctx, rng, logs),
+        }
+    }
+}
+
+fn default_state(block: &Block, palette: &BlockGlobalPalette) -> Option<Arc<BlockState>> {
+    palette.get_default_obj_by_index(block)
+}
+
+/// Vines attached to the face of the neighbour block, which is in the given
+/// direction.
+pub struct VineStates {
+    north: Arc<BlockState>,
+    east: Arc<BlockState>,
+    south: Arc<BlockState>,
+    west: Arc<BlockState>,
+}
+
+impl VineStates {
+    fn new(palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let vine = default_state(Block::VINE, palette).ok_or_else(|| anyhow!("No vine state"))?;
+        let facing = |direction: Direction| {
+            with_property(&vine, direction.name(), "true", palette).ok_or_else(|| anyhow!("No vine state facing {}", direction.name()))
+        };
+
+        Ok(Self {
+            north: facing(Direction::North)?,
+            east: facing(Direction::East)?,
+            south: facing(Direction::South)?,
+            west: facing(Direction::West)?,
+        })
+    }
+
+    fn get(&self, direction: Direction) -> Arc<BlockState> {
+        match direction {
+            Direction::North => self.north.clone(),
+            Direction::East => self.east.clone(),
+            Direction::South => self.south.clone(),
+            _ => self.west.clone(),
+        }
+    }
+
+    /// Places vines west, east, north and south of `pos`, if `chance` holds
+    /// and there is air. Hanging vines grow down up to 4 more blocks.
+    fn place_around<F>(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, pos: Vector3, mut chance: F, hanging: bool)
+    where
+        F: FnMut(&mut FeatureRng) -> bool
+    {
+        for side in [Direction::West, Direction::East, Direction::North, Direction::South] {
+            let at = side.relative(pos);
+            if !chance(rng) || !ctx.is_air(at) {
+                continue
+            }
+
+            let state = self.get(side.opposite());
+            ctx.set_decoration(at, state.clone());
+
+            if hanging {
+                let mut at = above(at, -1);
+                let mut left = 4;
+
+                while ctx.is_air(at) && left > 0 {
+                    ctx.set_decoration(at, state.clone());
+                    at = above(at, -1);
+                    left -= 1;
+                }
+            }
+        }
+    }
+}
+
+pub struct LeaveVineDecorator {
+    pub probability: f32,
+    vines: VineStates,
+}
+
+/// Cocoa beans on the lower logs of jungle trees.
+pub struct CocoaDecorator {
+    pub probability: f32,
+    /// States by the horizontal facing and age.
+    states: Vec<[Arc<BlockState>; 3]>,
+}
+
+impl CocoaDecorator {
+    fn new(probability: f32, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let cocoa = default_state(Block::COCOA, palette).ok_or_else(|| anyhow!("No cocoa state"))?;
+
+        let states = Direction::HORIZONTAL
+            .iter()
+            .map(|facing| {
+                let state = |age: &str| {
+                    with_property(&cocoa, "facing", facing.name(), palette)
+                        .and_then(|state| with_property(&state, "age", age, palette))
+                        .ok_or_else(|| anyhow!("No cocoa state facing {} with age {}", facing.name(), age))
+                };
+
+                Ok([state("0")?, state("1")?, state("2")?])
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            probability,
+            states,
+        })
+    }
+
+    fn place(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, logs: &[Vector3]) {
+        if rng.next_f32() >= self.probability {
+            return
+        }
+
+        let Some(bottom) = logs.first().map(|log| log.y) else {
+            return
+        };
+
+        for log in logs.iter().filter(|log| log.y - bottom <= 2) {
+            for (i, facing) in Direction::HORIZONTAL.iter().enumerate() {
+                if rng.next_f32() > 0.25 {
+                    continue
+                }
+
+                let at = facing.opposite().relative(*log);
+                if !ctx.is_air(at) {
+                    continue
+                }
+
+                let age = rng.next_u32(3) as usize;
+                ctx.set_decoration(at, self.states[i][age].clone());
+            }
+        }
+    }
+}
+
+/// Bee nest under the leaves or on the trunk. Bees are not spawned, since
+/// block entities are not generated.
+pub struct BeehiveDecorator {
+    pub probability: f32,
+    nest: Arc<BlockState>,
+}
+
+impl BeehiveDecorator {
+    const FACING: Direction = Direction::South;
+    const SPAWN_DIRECTIONS: [Direction; 3] = [Direction::East, Direction::South, Direction::West];
+
+    fn place(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, logs: &[Vector3], leaves: &[Vector3]) {
+        if rng.next_f32() >= self.probability {
+            return
+        }
+
+        let (Some(first_log), Some(last_log)) = (logs.first(), logs.last()) else {
+            return
+        };
+
+        let y = match leaves.first() {
+            Some(leaf) => (leaf.y - 1).max(first_log.y + 1),
+            None => (first_log.y + 1 + rng.next_u32(3) as i32).min(last_log.y),
+        };
+
+        let mut candidates = logs
+            .iter()
+            .filter(|log| log.y == y)
+            .flat_map(|log| Self::SPAWN_DIRECTIONS.map(|direction| direction.relative(*log)))
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return
+        }
+
+        // Vanilla Util.shuffle().
+        for i in (2..=candidates.len()).rev() {
+            candidates.swap(i - 1, rng.next_u32(i as u32) as usize);
+        }
+
+        let nest = candidates
+            .into_iter()
+            .find(|pos| ctx.is_air(*pos) && ctx.is_air(Self::FACING.relative(*pos)));
+
+        if let Some(pos) = nest {
+            ctx.set_decoration(pos, self.nest.clone());
+        }
+    }
+}
+
+/// Replaces the ground around the trunk, e.g. with podzol.
+pub struct AlterGroundDecorator {
+    pub provider: BlockStateProviders,
+    dirt: BlockSet,
+}
+
+impl AlterGroundDecorator {
+    fn place(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, logs: &[Vector3]) {
+        let Some(bottom) = logs.first().map(|log| log.y) else {
+            return
+        };
+
+        for log in logs.iter().filter(|log| log.y == bottom) {
+            self.place_circle(ctx, rng, offset(*log, -1, 0, -1));
+            self.place_circle(ctx, rng, offset(*log, 2, 0, -1));
+            self.place_circle(ctx, rng, offset(*log, -1, 0, 2));
+            self.place_circle(ctx, rng, offset(*log, 2, 0, 2));
+
+            for _ in 0..5 {
+                let i = rng.next_u32(64) as i32;
+                let x = i % 8;
+                let z = i / 8;
+
+                if x == 0 || x == 7 || z == 0 || z == 7 {
+                    self.place_circle(ctx, rng, offset(*log, -3 + x, 0, -3 + z));
+                }
+            }
+        }
+    }
+
+    fn place_circle(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, center: Vector3) {
+        for x in -2..=2 {
+            for z in -2..=2 {
+                if x.abs() == 2 && z.abs() == 2 {
+                    continue
+                }
+
+                self.place_block_at(ctx, rng, offset(center, x, 0, z));
+            }
+        }
+    }
+
+    fn place_block_at(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, pos: Vector3) {
+        for y in (-3..=2).rev() {
+            let at = above(pos, y);
+
+            if self.dirt.contains(ctx.level.block_state(at).block()) {
+                let state = self.provider.state(rng, pos);
+                ctx.set_decoration(at, state);
+                break
+            }
+
+            if !ctx.is_air(at) && y < 0 {
+                break
+            }
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use crate::feature::tree::{above, offset, TreeContext};
+use crate::feature::FeatureRng;
+use crate::noise::math::floor;
+use crate::provider::IntProvider;
+use crate::rng::Rng;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+
+/// Position on the trunk, which the foliage grows around.
+pub struct FoliageAttachment {
+    pub pos: Vector3,
+    pub radius_offset: i32,
+    /// Whether the trunk is 2x2 blocks wide.
+    pub double_trunk: bool,
+}
+
+impl FoliageAttachment {
+    pub fn new(pos: Vector3, radius_offset: i32, double_trunk: bool) -> Self {
+        Self {
+            pos,
+            radius_offset,
+            double_trunk,
+        }
+    }
+}
+
+pub enum FoliagePlacerKind {
+    Blob { height: i32 },
+    Fancy { height: i32 },
+    Bush { height: i32 },
+    Spruce { trunk_height: IntProvider },
+    Pine { height: IntProvider },
+    Acacia,
+    DarkOak,
+    MegaJungle { height: i32 },
+    MegaPine { crown_height: IntProvider },
+    RandomSpread { foliage_height: IntProvider, leaf_placement_attempts: i32 },
+}
+
+pub struct FoliagePlacers {
+    pub radius: IntProvider,
+    pub offset: IntProvider,
+    pub kind: FoliagePlacerKind,
+}
+
+impl FoliagePlacers {
+    pub fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let field = |name: &str| json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json));
+        let int = |name: &str| {
+            field(name)?
+                .as_i64()
+                .map(|x| x as i32)
+                .ok_or_else(|| anyhow!("Expected integer \"{}\" in {:?}", name, json))
+        };
+        let provider = |name: &str| IntProvider::from_json(field(name)?);
+
+        let kind = match ty.as_str() {
+            "minecraft:blob_foliage_placer" => FoliagePlacerKind::Blob { height: int("height")? },
+            "minecraft:fancy_foliage_placer" => FoliagePlacerKind::Fancy { height: int("height")? },
+            "minecraft:bush_foliage_placer" => FoliagePlacerKind::Bush { height: int("height")? },
+            "minecraft:spruce_foliage_placer" => FoliagePlacerKind::Spruce { trunk_height: provider("trunk_height")? },
+            "minecraft:pine_foliage_placer" => FoliagePlacerKind::Pine { height: provider("height")? },
+            "minecraft:acacia_foliage_placer" => FoliagePlacerKind::Acacia,
+            "minecraft:dark_oak_foliage_placer" => FoliagePlacerKind::DarkOak,
+            "minecraft:jungle_foliage_placer" => FoliagePlacerKind::MegaJungle { height: int("height")? },
+            "minecraft:mega_pine_foliage_placer" => FoliagePlacerKind::MegaPine { crown_height: provider("crown_height")? },
+            "minecraft:random_spread_foliage_placer" => FoliagePlacerKind::RandomSpread {
+                foliage_height: provider("foliage_height")?,
+                leaf_placement_attempts: int("leaf_placement_attempts")?,
+            },
+            _ => return Err(anyhow!("Unknown foliage placer type {}", ty))
+        };
+
+        Ok(Self {
+            radius: provider("radius")?,
+            offset: provider("offset")?,
+            kind,
+        })
+    }
+
+    pub fn foliage_height(&self, rng: &mut FeatureRng, height: i32) -> i32 {
+        match &self.kind {
+            FoliagePlacerKind::Blob { height }
+            | FoliagePlacerKind::Fancy { height }
+            | FoliagePlacerKind::Bush { height }
+            | FoliagePlacerKind::MegaJungle { height } => *height,
+            FoliagePlacerKind::Spruce { trunk_height } => 4.max(height - trunk_height.sample(rng)),
+            FoliagePlacerKind::Pine { height } => height.sample(rng),
+            FoliagePlacerKind::Acacia => 0,
+            FoliagePlacerKind::DarkOak => 4,
+            FoliagePlacerKind::MegaPine { crown_height } => crown_height.sample(rng),
+            FoliagePlacerKind::RandomSpread { foliage_height, .. } => foliage_height.sample(rng),
+        }
+    }
+
+    pub fn foliage_radius(&self, rng: &mut FeatureRng, radius: i32) -> i32 {
+        let sampled = self.radius.sample(rng);
+
+        match self.kind {
+            FoliagePlacerKind::Pine { .. } => sampled + rng.next_u32((radius + 1).max(1) as u32) as i32,
+            _ => sampled,
+        }
+    }
+
+    pub fn create_foliage(
+        &self,
+        ctx: &mut TreeContext,
+        rng: &mut FeatureRng,
+        attachment: &FoliageAttachment,
+        foliage_height: i32,
+        foliage_radius: i32,
+    ) {
+        let foliage_offset = self.offset.sample(rng);
+        let pos = attachment.pos;
+        let double_trunk = attachment.double_trunk;
+        let radius_offset = attachment.radius_offset;
+
+        match &self.kind {
+            FoliagePlacerKind::Blob { .. } => {
+                for y in (foliage_offset - foliage_height..=foliage_offset).rev() {
+                    let range = (foliage_radius + radius_offset - 1 - y / 2).max(0);
+                    self.place_leaves_row(ctx, rng, pos, range, y, double_trunk);
+                }
+            }
+            FoliagePlacerKind::Fancy { .. } => {
+                for y in (foliage_offset - foliage_height..=foliage_offset).rev() {
+                    let edge = y == foliage_offset || y == foliage_offset - foliage_height;
+                    let range = foliage_radius + if edge { 0 } else { 1 };
+                    self.place_leaves_row(ctx, rng, pos, range, y, double_trunk);
+                }
+            }
+            FoliagePlacerKind::Bush { .. } => {
+                for y in (foliage_offset - foliage_height..=foliage_offset).rev() {
+                    let range = foliage_radius + radius_offset - 1 - y;
+                    self.place_leaves_row(ctx, rng, pos, range, y, double_trunk);
+                }
+            }
+            FoliagePlacerKind::Spruce { .. } => {
+                let mut range = rng.next_u32(2) as i32;
+                let mut max_range = 1;
+                let mut min_range = 0;
+
+                for y in (-foliage_height..=foliage_offset).rev() {
+                    self.place_leaves_row(ctx, rng, pos, range, y, double_trunk);
+
+                    if range >= max_range {
+                        range = min_range;
+                        min_range = 1;
+                        max_range = (max_range + 1).min(foliage_radius + radius_offset);
+                    } else {
+                        range += 1;
+                    }
+                }
+            }
+            FoliagePlacerKind::Pine { .. } => {
+                let mut range = 0;
+
+                for y in (foliage_offset - foliage_height..=foliage_offset).rev() {
+                    self.place_leaves_row(ctx, rng, pos, range, y, double_trunk);
+
+                    if range >= 1 && y == foliage_offset - foliage_height + 1 {
+                        range -= 1;
+                    } else if range < foliage_radius + radius_offset {
+                        range += 1;
+                    }
+                }
+            }
+            FoliagePlacerKind::Acacia => {
+                let pos = above(pos, foliage_offset);
+
+                self.place_leaves_row(ctx, rng, pos, foliage_radius + radius_offset, -1 - foliage_height, double_trunk);
+                self.place_leaves_row(ctx, rng, pos, foliage_radius - 1, -foliage_height, double_trunk);
+                self.place_leaves_row(ctx, rng, pos, foliage_radius + radius_offset - 1, 0, double_trunk);
+            }
+            FoliagePlacerKind::DarkOak => {
+                let pos = above(pos, foliage_offset);
+
+                if double_trunk {
+                    self.place_leaves_row(ctx, rng, pos, foliage_radius + 2, -1, double_trunk);
+                    self.place_leaves_row(ctx, rng, pos, foliage_radius + 3, 0, double_trunk);
+                    self.place_leaves_row(ctx, rng, pos, foliage_radius + 2, 1, double_trunk);
+
+                    if rng.next_bool() {
+                        self.place_leaves_row(ctx, rng, pos, foliage_radius, 2, double_trunk);
+                    }
+                } else {
+                    self.place_leaves_row(ctx, rng, pos, foliage_radius + 2, -1, double_trunk);
+                    self.place_leaves_row(ctx, rng, pos, foliage_radius + 1, 0, double_trunk);
+                }
+            }
+            FoliagePlacerKind::MegaJungle { .. } => {
+                let height = if double_trunk { foliage_height } else { 1 + rng.next_u32(2) as i32 };
+
+                for y in (foliage_offset - height..=foliage_offset).rev() {
+                    let range = foliage_radius + radius_offset + 1 - y;
+                    self.place_leaves_row(ctx, rng, pos, range, y, double_trunk);
+                }
+            }
+            FoliagePlacerKind::MegaPine { .. } => {
+                let mut previous = 0;
+
+                for y in pos.y - foliage_height + foliage_offset..=pos.y + foliage_offset {
+                    let dy = pos.y - y;
+                    let range = foliage_radius + radius_offset + floor((dy as f32 / foliage_height as f32 * 3.5) as f64);
+                    let row_range = if dy > 0 && range == previous && y & 1 == 0 { range + 1 } else { range };
+
+                    self.place_leaves_row(ctx, rng, Vector3::new(pos.x, y, pos.z), row_range, 0, double_trunk);
+                    previous = range;
+                }
+            }
+            FoliagePlacerKind::RandomSpread { leaf_placement_attempts, .. } => {
+                for _ in 0..*leaf_placement_attempts {
+                    let x = rng.next_u32(foliage_radius as u32) as i32 - rng.next_u32(foliage_radius as u32) as i32;
+                    let y = rng.next_u32(foliage_height as u32) as i32 - rng.next_u32(foliage_height as u32) as i32;
+                    let z = rng.next_u32(foliage_radius as u32) as i32 - rng.next_u32(foliage_radius as u32) as i32;
+
+                    ctx.try_place_leaf(rng, offset(pos, x, y, z));
+                }
+            }
+        }
+    }
+
+    /// Places the square layer of leaves at `y` relative to `pos`, skipping
+    /// some of its blocks (e.g. corners) depending on the placer.
+    fn place_leaves_row(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, pos: Vector3, range: i32, y: i32, double_trunk: bool) {
+        let extra = if double_trunk { 1 } else { 0 };
+
+        for x in -range..=range + extra {
+            for z in -range..=range + extra {
+                if self.should_skip_location_signed(rng, x, y, z, range, double_trunk) {
+                    continue
+                }
+
+                ctx.try_place_leaf(rng, offset(pos, x, y, z));
+            }
+        }
+    }
+
+    fn should_skip_location_signed(&self, rng: &mut FeatureRng, x: i32, y: i32, z: i32, range: i32, double_trunk: bool) -> bool {
+        if matches!(self.kind, FoliagePlacerKind::DarkOak)
+            && y == 0
+            && double_trunk
+            && (x == -range || x >= range)
+            && (z == -range || z >= range) {
+            return true
+        }
+
+        let (x, z) = if double_trunk {
+            (x.abs().min((x - 1).abs()), z.abs().min((z - 1).abs()))
+        } else {
+            (x.abs(), z.abs())
+        };
+
+        self.should_skip_location(rng, x, y, z, range, double_trunk)
+    }
+
+    fn should_skip_location(&self, rng: &mut FeatureRng, x: i32, y: i32, z: i32, range: i32, double_trunk: bool) -> bool {
+        match self.kind {
+            FoliagePlacerKind::Blob { .. } => x == range && z == range && (rng.next_u32(2) == 0 || y == 0),
+            FoliagePlacerKind::Fancy { .. } => {
+                (x as f32 + 0.5).powi(2) + (z as f32 + 0.5).powi(2) > (range * range) as f32
+            }
+            FoliagePlacerKind::Bush { .. } => x == range && z == range && rng.next_u32(2) == 0,
+            FoliagePlacerKind::Spruce { .. } | FoliagePlacerKind::Pine { .. } => x == range && z == range && range > 0,
+            FoliagePlacerKind::Acacia => {
+                if y == 0 {
+                    (x > 1 || z > 1) && x != 0 && z != 0
+                } else {
+                    x == range && z == range && range > 0
+                }
+            }
+            FoliagePlacerKind::DarkOak => {
+                if y == -1 && !double_trunk {
+                    x == range && z == range
+                } else if y == 1 {
+                    x + z > range * 2 - 2
+                } else {
+                    false
+                }
+            }
+            FoliagePlacerKind::MegaJungle { .. } | FoliagePlacerKind::MegaPine { .. } => {
+                x + z >= 7 || x * x + z * z > range * range
+            }
+            FoliagePlacerKind::RandomSpread { .. } => false,
+        }
+    }
+}
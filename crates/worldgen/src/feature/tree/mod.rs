@@ -0,0 +1,438 @@
+pub mod decorator;
+pub mod foliage;
+pub mod trunk;
+
+use crate::feature::level::WorldGenLevel;
+use crate::feature::state::{property, with_property};
+use crate::feature::state_provider::{BlockStateProvider, BlockStateProviders};
+use crate::feature::tree::decorator::TreeDecorators;
+use crate::feature::tree::foliage::FoliagePlacers;
+use crate::feature::tree::trunk::TrunkPlacers;
+use crate::feature::{Direction, Feature, FeatureRng};
+use crate::tag::{BlockSet, BlockTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::block::Block;
+use spherix_world::block::material::Material;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Set of positions, which remembers the order they were added in. Vanilla
+/// keeps the blocks of the tree in hash sets, whose iteration order can not be
+/// reproduced, so the insertion order is used instead.
+#[derive(Default)]
+pub struct PositionSet {
+    order: Vec<Vector3>,
+    set: HashSet<Vector3>,
+}
+
+impl PositionSet {
+    pub fn insert(&mut self, pos: Vector3) {
+        if self.set.insert(pos) {
+            self.order.push(pos);
+        }
+    }
+
+    #[inline]
+    pub fn contains(&self, pos: &Vector3) -> bool {
+        self.set.contains(pos)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Vector3> {
+        self.order.iter()
+    }
+
+    /// Positions sorted by y, as decorators see them.
+    pub fn sorted_by_y(&self) -> Vec<Vector3> {
+        let mut sorted = self.order.clone();
+        sorted.sort_by_key(|pos| pos.y);
+        sorted
+    }
+}
+
+/// Size of the space around the trunk, which has to be free for the tree to
+/// grow, at each height of the tree.
+pub enum FeatureSize {
+    TwoLayers {
+        limit: i32,
+        lower_size: i32,
+        upper_size: i32,
+        min_clipped_height: Option<i32>,
+    },
+    ThreeLayers {
+        limit: i32,
+        upper_limit: i32,
+        lower_size: i32,
+        middle_size: i32,
+        upper_size: i32,
+        min_clipped_height: Option<i32>,
+    },
+}
+
+impl FeatureSize {
+    pub fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let int = |name: &str, default: i32| json.get(name).and_then(Value::as_i64).map_or(default, |x| x as i32);
+        let min_clipped_height = json.get("min_clipped_height").and_then(Value::as_i64).map(|x| x as i32);
+
+        let size = match ty.as_str() {
+            "minecraft:two_layers_feature_size" => FeatureSize::TwoLayers {
+                limit: int("limit", 1),
+                lower_size: int("lower_size", 0),
+                upper_size: int("upper_size", 1),
+                min_clipped_height,
+            },
+            "minecraft:three_layers_feature_size" => FeatureSize::ThreeLayers {
+                limit: int("limit", 1),
+                upper_limit: int("upper_limit", 1),
+                lower_size: int("lower_size", 0),
+                middle_size: int("middle_size", 1),
+                upper_size: int("upper_size", 1),
+                min_clipped_height,
+            },
+            _ => return Err(anyhow!("Unknown feature size type {}", ty))
+        };
+
+        Ok(size)
+    }
+
+    pub fn size_at_height(&self, height: i32, y: i32) -> i32 {
+        match *self {
+            FeatureSize::TwoLayers { limit, lower_size, upper_size, .. } => {
+                if y < limit { lower_size } else { upper_size }
+            }
+            FeatureSize::ThreeLayers { limit, upper_limit, lower_size, middle_size, upper_size, .. } => {
+                if y < limit {
+                    lower_size
+                } else if y >= height - upper_limit {
+                    upper_size
+                } else {
+                    middle_size
+                }
+            }
+        }
+    }
+
+    pub fn min_clipped_height(&self) -> Option<i32> {
+        match *self {
+            FeatureSize::TwoLayers { min_clipped_height, .. } => min_clipped_height,
+            FeatureSize::ThreeLayers { min_clipped_height, .. } => min_clipped_height,
+        }
+    }
+}
+
+pub struct TreeConfig {
+    pub trunk_provider: BlockStateProviders,
+    pub trunk_placer: TrunkPlacers,
+    pub foliage_provider: BlockStateProviders,
+    pub foliage_placer: FoliagePlacers,
+    pub dirt_provider: BlockStateProviders,
+    pub minimum_size: FeatureSize,
+    pub decorators: Vec<TreeDecorators>,
+    pub ignore_vines: bool,
+    pub force_dirt: bool,
+}
+
+/// Blocks of the tags trees look at.
+pub struct TreeBlocks {
+    pub leaves: BlockSet,
+    pub logs: BlockSet,
+    pub dirt: BlockSet,
+}
+
+/// Tree, which consists of the trunk, foliage attached to it and decorations,
+/// e.g. vines or bee nests. Roots (of mangroves) are not supported.
+pub struct TreeFeature {
+    pub config: TreeConfig,
+    pub blocks: TreeBlocks,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl TreeFeature {
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: Arc<BlockGlobalPalette>) -> anyhow::Result<Self> {
+        let field = |name: &str| json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json));
+
+        if json.get("root_placer").is_some() {
+            return Err(anyhow!("Root placers are not supported"))
+        }
+
+        let decorators = match json.get("decorators") {
+            Some(Value::Array(decorators)) => decorators
+                .iter()
+                .map(|decorator| TreeDecorators::from_json(decorator, tags, &palette))
+                .collect::<anyhow::Result<_>>()?,
+            _ => Vec::new(),
+        };
+
+        let config = TreeConfig {
+            trunk_provider: BlockStateProviders::from_json(field("trunk_provider")?, &palette)?,
+            trunk_placer: TrunkPlacers::from_json(field("trunk_placer")?)?,
+            foliage_provider: BlockStateProviders::from_json(field("foliage_provider")?, &palette)?,
+            foliage_placer: FoliagePlacers::from_json(field("foliage_placer")?)?,
+            dirt_provider: BlockStateProviders::from_json(field("dirt_provider")?, &palette)?,
+            minimum_size: FeatureSize::from_json(field("minimum_size")?)?,
+            decorators,
+            ignore_vines: json.get("ignore_vines").and_then(Value::as_bool).unwrap_or(false),
+            force_dirt: json.get("force_dirt").and_then(Value::as_bool).unwrap_or(false),
+        };
+
+        Ok(Self {
+            config,
+            blocks: TreeBlocks {
+                leaves: tags.tag("minecraft:leaves")?,
+                logs: tags.tag("minecraft:logs")?,
+                dirt: tags.tag("minecraft:dirt")?,
+            },
+            palette,
+        })
+    }
+
+    /// Equivalent of vanilla `TreeFeature::getMaxFreeTreeHeight()`.
+    fn max_free_tree_height(&self, ctx: &TreeContext, trunk_height: i32, top: Vector3) -> i32 {
+        for y in 0..=trunk_height + 1 {
+            let size = self.config.minimum_size.size_at_height(trunk_height, y);
+
+            for x in -size..=size {
+                for z in -size..=size {
+                    let pos = Vector3::new(top.x + x, top.y + y, top.z + z);
+
+                    if !ctx.is_free(pos) || !self.config.ignore_vines && ctx.is_block(pos, Block::VINE) {
+                        return y - 2
+                    }
+                }
+            }
+        }
+
+        trunk_height
+    }
+
+    fn do_place(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let height = self.config.trunk_placer.tree_height(rng);
+        let foliage_height = self.config.foliage_placer.foliage_height(rng, height);
+        let foliage_radius = self.config.foliage_placer.foliage_radius(rng, height - foliage_height);
+
+        if origin.y < ctx.level.min_y() + 1 || origin.y + height + 1 > ctx.level.max_y() {
+            return false
+        }
+
+        let free_height = self.max_free_tree_height(ctx, height, origin);
+        let enough = free_height >= height
+            || self.config.minimum_size.min_clipped_height().is_some_and(|min| free_height >= min);
+
+        if !enough {
+            return false
+        }
+
+        let attachments = self.config.trunk_placer.place_trunk(ctx, rng, free_height, origin);
+        for attachment in attachments {
+            self.config.foliage_placer.create_foliage(ctx, rng, &attachment, foliage_height, foliage_radius);
+        }
+
+        true
+    }
+}
+
+impl Feature for TreeFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let mut ctx = TreeContext {
+            level,
+            config: &self.config,
+            blocks: &self.blocks,
+            palette: &self.palette,
+            logs: PositionSet::default(),
+            leaves: PositionSet::default(),
+            decorations: PositionSet::default(),
+        };
+
+        if !self.do_place(&mut ctx, rng, origin) || ctx.logs.is_empty() && ctx.leaves.is_empty() {
+            return false
+        }
+
+        let logs = ctx.logs.sorted_by_y();
+        let leaves = ctx.leaves.sorted_by_y();
+        for decorator in &self.config.decorators {
+            decorator.place(&mut ctx, rng, &logs, &leaves);
+        }
+
+        ctx.update_leaves();
+
+        true
+    }
+}
+
+/// State of the tree being placed, through which placers and decorators write
+/// blocks, so that the blocks of each kind are tracked.
+pub struct TreeContext<'a, 'b> {
+    pub level: &'a mut WorldGenLevel<'b>,
+    pub config: &'a TreeConfig,
+    pub blocks: &'a TreeBlocks,
+    pub palette: &'a BlockGlobalPalette,
+    pub logs: PositionSet,
+    pub leaves: PositionSet,
+    pub decorations: PositionSet,
+}
+
+impl TreeContext<'_, '_> {
+    #[inline]
+    pub fn is_block(&self, pos: Vector3, block: &Block) -> bool {
+        self.level.block_state(pos).block() == block
+    }
+
+    #[inline]
+    pub fn is_air(&self, pos: Vector3) -> bool {
+        self.level.is_air(pos)
+    }
+
+    pub fn is_air_or_leaves(&self, pos: Vector3) -> bool {
+        let state = self.level.block_state(pos);
+
+        state.block().properties().is_air || self.blocks.leaves.contains(state.block())
+    }
+
+    /// Equivalent of vanilla `TreeFeature::validTreePos()`.
+    pub fn valid_tree_pos(&self, pos: Vector3) -> bool {
+        let state = self.level.block_state(pos);
+        let block = state.block();
+
+        block.properties().is_air
+            || self.blocks.leaves.contains(block)
+            || block.properties().material() == &Material::REPLACEABLE_PLANT
+            || block == Block::WATER
+    }
+
+    /// Equivalent of vanilla `TreeFeature::isFree()`.
+    pub fn is_free(&self, pos: Vector3) -> bool {
+        self.valid_tree_pos(pos) || self.blocks.logs.contains(self.level.block_state(pos).block())
+    }
+
+    pub fn place_log(&mut self, rng: &mut FeatureRng, pos: Vector3) -> bool {
+        self.place_log_with(rng, pos, |state, _| state)
+    }
+
+    /// Places the log, whose state is adjusted by `f` (e.g. rotated).
+    pub fn place_log_with<F>(&mut self, rng: &mut FeatureRng, pos: Vector3, f: F) -> bool
+    where
+        F: FnOnce(Arc<BlockState>, &BlockGlobalPalette) -> Arc<BlockState>
+    {
+        if !self.valid_tree_pos(pos) {
+            return false
+        }
+
+        let state = f(self.config.trunk_provider.state(rng, pos), self.palette);
+        self.logs.insert(pos);
+        self.level.set_block_state(pos, state);
+
+        true
+    }
+
+    pub fn place_log_if_free(&mut self, rng: &mut FeatureRng, pos: Vector3) {
+        if self.is_free(pos) {
+            self.place_log(rng, pos);
+        }
+    }
+
+    /// Replaces the block under the trunk with dirt, unless it is already dirt.
+    pub fn set_dirt_at(&mut self, rng: &mut FeatureRng, pos: Vector3) {
+        let block = self.level.block_state(pos).block();
+        let is_dirt = self.blocks.dirt.contains(block) && block != Block::GRASS_BLOCK && block != Block::MYCELIUM;
+
+        if self.config.force_dirt || !is_dirt {
+            let state = self.config.dirt_provider.state(rng, pos);
+            self.level.set_block_state(pos, state);
+        }
+    }
+
+    /// Equivalent of vanilla `FoliagePlacer::tryPlaceLeaf()`.
+    pub fn try_place_leaf(&mut self, rng: &mut FeatureRng, pos: Vector3) -> bool {
+        if !self.valid_tree_pos(pos) {
+            return false
+        }
+
+        let mut state = self.config.foliage_provider.state(rng, pos);
+        if self.is_block(pos, Block::WATER) {
+            state = with_property(&state, "waterlogged", "true", self.palette).unwrap_or(state);
+        }
+
+        self.leaves.insert(pos);
+        self.level.set_block_state(pos, state);
+
+        true
+    }
+
+    pub fn set_decoration(&mut self, pos: Vector3, state: Arc<BlockState>) {
+        self.decorations.insert(pos);
+        self.level.set_block_state(pos, state);
+    }
+
+    /// Sets `distance` of the leaves around the logs, so that they do not
+    /// decay. Leaves farther than 6 blocks from the logs are left as they are.
+    fn update_leaves(&mut self) {
+        let mut previous = PositionSet::default();
+
+        for log in self.logs.iter() {
+            for direction in Direction::ALL {
+                let pos = direction.relative(*log);
+                if self.logs.contains(&pos) {
+                    continue
+                }
+
+                let state = self.level.block_state(pos);
+                if let Some(state) = with_property(&state, "distance", "1", self.palette) {
+                    previous.insert(pos);
+                    self.level.set_block_state(pos, state);
+                }
+            }
+        }
+
+        for distance in 2..=6 {
+            let mut current = PositionSet::default();
+
+            for leaf in previous.iter() {
+                for direction in Direction::ALL {
+                    let pos = direction.relative(*leaf);
+                    if previous.contains(&pos) || current.contains(&pos) {
+                        continue
+                    }
+
+                    let state = self.level.block_state(pos);
+                    let Some(old) = property(&state, "distance").and_then(|value| value.parse::<i32>().ok()) else {
+                        continue
+                    };
+
+                    if old <= distance {
+                        continue
+                    }
+
+                    if let Some(state) = with_property(&state, "distance", &distance.to_string(), self.palette) {
+                        current.insert(pos);
+                        self.level.set_block_state(pos, state);
+                    }
+                }
+            }
+
+            previous = current;
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn above(pos: Vector3, y: i32) -> Vector3 {
+    Vector3::new(pos.x, pos.y + y, pos.z)
+}
+
+#[inline]
+pub(crate) fn offset(pos: Vector3, x: i32, y: i32, z: i32) -> Vector3 {
+    Vector3::new(pos.x + x, pos.y + y, pos.z + z)
+}
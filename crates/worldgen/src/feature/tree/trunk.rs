@@ -0,0 +1,473 @@
+use crate::feature::state::with_property;
+use crate::feature::tree::foliage::FoliageAttachment;
+use crate::feature::tree::{above, offset, TreeContext};
+use crate::feature::{Direction, FeatureRng};
+use crate::noise::math::{cos, floor, sin};
+use crate::provider::IntProvider;
+use crate::rng::Rng;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+
+/// Height of the trunk shared by all trunk placers. The trunk is
+/// `base_height + [0, height_rand_a] + [0, height_rand_b]` blocks high.
+pub struct TrunkHeight {
+    pub base_height: i32,
+    pub height_rand_a: i32,
+    pub height_rand_b: i32,
+}
+
+impl TrunkHeight {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let int = |name: &str| {
+            json.get(name)
+                .and_then(Value::as_i64)
+                .map(|x| x as i32)
+                .ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json))
+        };
+
+        Ok(Self {
+            base_height: int("base_height")?,
+            height_rand_a: int("height_rand_a")?,
+            height_rand_b: int("height_rand_b")?,
+        })
+    }
+}
+
+pub enum TrunkPlacers {
+    Straight(TrunkHeight),
+    Forking(TrunkHeight),
+    Giant(TrunkHeight),
+    MegaJungle(TrunkHeight),
+    DarkOak(TrunkHeight),
+    Fancy(TrunkHeight),
+    Bending(BendingTrunkPlacer),
+}
+
+impl TrunkPlacers {
+    pub fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let height = TrunkHeight::from_json(json)?;
+
+        let placer = match ty.as_str() {
+            "minecraft:straight_trunk_placer" => TrunkPlacers::Straight(height),
+            "minecraft:forking_trunk_placer" => TrunkPlacers::Forking(height),
+            "minecraft:giant_trunk_placer" => TrunkPlacers::Giant(height),
+            "minecraft:mega_jungle_trunk_placer" => TrunkPlacers::MegaJungle(height),
+            "minecraft:dark_oak_trunk_placer" => TrunkPlacers::DarkOak(height),
+            "minecraft:fancy_trunk_placer" => TrunkPlacers::Fancy(height),
+            "minecraft:bending_trunk_placer" => TrunkPlacers::Bending(BendingTrunkPlacer {
+                min_height_for_leaves: json.get("min_height_for_leaves").and_then(Value::as_i64).unwrap_or(1) as i32,
+                bend_length: IntProvider::from_json(json.get("bend_length").ok_or_else(|| anyhow!("No \"bend_length\" key in {:?}", json))?)?,
+                height,
+            }),
+            _ => return Err(anyhow!("Unknown trunk placer type {}", ty))
+        };
+
+        Ok(placer)
+    }
+
+    fn height(&self) -> &TrunkHeight {
+        match self {
+            TrunkPlacers::Straight(height)
+            | TrunkPlacers::Forking(height)
+            | TrunkPlacers::Giant(height)
+            | TrunkPlacers::MegaJungle(height)
+            | TrunkPlacers::DarkOak(height)
+            | TrunkPlacers::Fancy(height) => height,
+            TrunkPlacers::Bending(x) => &x.height,
+        }
+    }
+
+    pub fn tree_height(&self, rng: &mut FeatureRng) -> i32 {
+        let height = self.height();
+
+        height.base_height
+            + rng.next_u32(height.height_rand_a as u32 + 1) as i32
+            + rng.next_u32(height.height_rand_b as u32 + 1) as i32
+    }
+
+    /// Places the trunk of `height` blocks at `origin` and returns the
+    /// positions the foliage is attached to.
+    pub fn place_trunk(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+        match self {
+            TrunkPlacers::Straight(_) => place_straight(ctx, rng, height, origin),
+            TrunkPlacers::Forking(_) => place_forking(ctx, rng, height, origin),
+            TrunkPlacers::Giant(_) => place_giant(ctx, rng, height, origin),
+            TrunkPlacers::MegaJungle(_) => place_mega_jungle(ctx, rng, height, origin),
+            TrunkPlacers::DarkOak(_) => place_dark_oak(ctx, rng, height, origin),
+            TrunkPlacers::Fancy(_) => FancyTrunk::place(ctx, rng, height, origin),
+            TrunkPlacers::Bending(x) => x.place(ctx, rng, height, origin),
+        }
+    }
+}
+
+#[inline]
+fn below(pos: Vector3) -> Vector3 {
+    above(pos, -1)
+}
+
+fn place_straight(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+    ctx.set_dirt_at(rng, below(origin));
+
+    for y in 0..height {
+        ctx.place_log(rng, above(origin, y));
+    }
+
+    vec![FoliageAttachment::new(above(origin, height), 0, false)]
+}
+
+/// Acacia trunk, which leans to one side and may fork into a second branch.
+fn place_forking(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+    ctx.set_dirt_at(rng, below(origin));
+
+    let mut attachments = Vec::new();
+    let direction = Direction::random_horizontal(rng);
+    let lean_from = height - rng.next_u32(4) as i32 - 1;
+    let mut lean = 3 - rng.next_u32(3) as i32;
+
+    let mut x = origin.x;
+    let mut z = origin.z;
+    let mut top = None;
+
+    for i in 0..height {
+        let y = origin.y + i;
+        if i >= lean_from && lean > 0 {
+            let step = direction.relative(Vector3::new(x, y, z));
+            x = step.x;
+            z = step.z;
+            lean -= 1;
+        }
+
+        if ctx.place_log(rng, Vector3::new(x, y, z)) {
+            top = Some(y + 1);
+        }
+    }
+
+    if let Some(top) = top {
+        attachments.push(FoliageAttachment::new(Vector3::new(x, top, z), 1, false));
+    }
+
+    x = origin.x;
+    z = origin.z;
+
+    let branch_direction = Direction::random_horizontal(rng);
+    if branch_direction != direction {
+        let branch_from = lean_from - rng.next_u32(2) as i32 - 1;
+        let mut length = 1 + rng.next_u32(3) as i32;
+        top = None;
+
+        let mut i = branch_from;
+        while i < height && length > 0 {
+            if i >= 1 {
+                let y = origin.y + i;
+                let step = branch_direction.relative(Vector3::new(x, y, z));
+                x = step.x;
+                z = step.z;
+
+                if ctx.place_log(rng, Vector3::new(x, y, z)) {
+                    top = Some(y + 1);
+                }
+            }
+
+            i += 1;
+            length -= 1;
+        }
+
+        if let Some(top) = top {
+            attachments.push(FoliageAttachment::new(Vector3::new(x, top, z), 0, false));
+        }
+    }
+
+    attachments
+}
+
+/// 2x2 trunk of mega spruces.
+fn place_giant(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+    let ground = below(origin);
+    for (x, z) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        ctx.set_dirt_at(rng, offset(ground, x, 0, z));
+    }
+
+    for y in 0..height {
+        ctx.place_log_if_free(rng, offset(origin, 0, y, 0));
+
+        if y < height - 1 {
+            ctx.place_log_if_free(rng, offset(origin, 1, y, 0));
+            ctx.place_log_if_free(rng, offset(origin, 1, y, 1));
+            ctx.place_log_if_free(rng, offset(origin, 0, y, 1));
+        }
+    }
+
+    vec![FoliageAttachment::new(above(origin, height), 0, true)]
+}
+
+/// Giant trunk with branches, each of which carries foliage.
+fn place_mega_jungle(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+    let mut attachments = place_giant(ctx, rng, height, origin);
+
+    let mut y = height - 2 - rng.next_u32(4) as i32;
+    while y > height / 2 {
+        let angle = rng.next_f32() * std::f32::consts::PI * 2.0;
+        let mut x = 0;
+        let mut z = 0;
+
+        for i in 0..5 {
+            x = (1.5 + cos(angle) * i as f32) as i32;
+            z = (1.5 + sin(angle) * i as f32) as i32;
+            ctx.place_log(rng, offset(origin, x, y - 3 + i / 2, z));
+        }
+
+        attachments.push(FoliageAttachment::new(offset(origin, x, y, z), -2, false));
+        y -= 2 + rng.next_u32(4) as i32;
+    }
+
+    attachments
+}
+
+/// 2x2 trunk, which leans to one side, with short branches around its top.
+fn place_dark_oak(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+    let ground = below(origin);
+    for (x, z) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        ctx.set_dirt_at(rng, offset(ground, x, 0, z));
+    }
+
+    let mut attachments = Vec::new();
+    let direction = Direction::random_horizontal(rng);
+    let lean_from = height - rng.next_u32(4) as i32;
+    let mut lean = 2 - rng.next_u32(3) as i32;
+
+    let mut x = origin.x;
+    let mut z = origin.z;
+    let top = origin.y + height - 1;
+
+    for i in 0..height {
+        if i >= lean_from && lean > 0 {
+            let step = direction.relative(Vector3::new(x, 0, z));
+            x = step.x;
+            z = step.z;
+            lean -= 1;
+        }
+
+        let pos = Vector3::new(x, origin.y + i, z);
+        if ctx.is_air_or_leaves(pos) {
+            ctx.place_log(rng, pos);
+            ctx.place_log(rng, offset(pos, 1, 0, 0));
+            ctx.place_log(rng, offset(pos, 0, 0, 1));
+            ctx.place_log(rng, offset(pos, 1, 0, 1));
+        }
+    }
+
+    attachments.push(FoliageAttachment::new(Vector3::new(x, top, z), 0, true));
+
+    for branch_x in -1..=2 {
+        for branch_z in -1..=2 {
+            let inside = (0..=1).contains(&branch_x) && (0..=1).contains(&branch_z);
+            if inside || rng.next_u32(3) > 0 {
+                continue
+            }
+
+            let length = rng.next_u32(3) as i32 + 2;
+            for i in 0..length {
+                ctx.place_log(rng, Vector3::new(origin.x + branch_x, top - i - 1, origin.z + branch_z));
+            }
+
+            attachments.push(FoliageAttachment::new(Vector3::new(x + branch_x, top, z + branch_z), 0, false));
+        }
+    }
+
+    attachments
+}
+
+/// Trunk of fancy oaks with branches going up and out of it.
+struct FancyTrunk;
+
+impl FancyTrunk {
+    fn place(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+        let height = height + 2;
+        let trunk_height = floor(height as f64 * 0.618);
+
+        ctx.set_dirt_at(rng, below(origin));
+
+        // Vanilla takes the minimum, so there is always a single branch per layer.
+        let branches_per_layer = 1.min(floor(1.382 + (height as f64 / 13.0).powi(2)));
+        let trunk_top = origin.y + trunk_height;
+
+        let mut layer = height - 5;
+        let mut foliage = vec![(above(origin, layer), trunk_top)];
+
+        while layer >= 0 {
+            let shape = Self::tree_shape(height, layer);
+            if shape < 0.0 {
+                layer -= 1;
+                continue
+            }
+
+            for _ in 0..branches_per_layer {
+                let radius = shape as f64 * (rng.next_f32() as f64 + 0.328);
+                let angle = (rng.next_f32() * 2.0) as f64 * std::f64::consts::PI;
+                let branch_x = radius * angle.sin() + 0.5;
+                let branch_z = radius * angle.cos() + 0.5;
+
+                let end = Vector3::new(
+                    floor(origin.x as f64 + branch_x),
+                    floor(origin.y as f64 + (layer - 1) as f64),
+                    floor(origin.z as f64 + branch_z),
+                );
+
+                if !Self::make_limb(ctx, rng, end, above(end, 5), false) {
+                    continue
+                }
+
+                let dx = origin.x - end.x;
+                let dz = origin.z - end.z;
+                let base = end.y as f64 - ((dx * dx + dz * dz) as f64).sqrt() * 0.381;
+                let base = if base > trunk_top as f64 { trunk_top } else { base as i32 };
+                let start = Vector3::new(origin.x, base, origin.z);
+
+                if Self::make_limb(ctx, rng, start, end, false) {
+                    foliage.push((end, start.y));
+                }
+            }
+
+            layer -= 1;
+        }
+
+        Self::make_limb(ctx, rng, origin, above(origin, trunk_height), true);
+        Self::make_branches(ctx, rng, height, origin, &foliage);
+
+        foliage
+            .into_iter()
+            .filter(|(_, base)| Self::trim_branches(height, base - origin.y))
+            .map(|(pos, _)| FoliageAttachment::new(pos, 0, false))
+            .collect()
+    }
+
+    /// Walks the line from `from` to `to` and either places logs along it, or
+    /// checks that it is free.
+    fn make_limb(ctx: &mut TreeContext, rng: &mut FeatureRng, from: Vector3, to: Vector3, place: bool) -> bool {
+        if !place && from == to {
+            return true
+        }
+
+        let delta = Vector3::new(to.x - from.x, to.y - from.y, to.z - from.z);
+        let steps = delta.x.abs().max(delta.y.abs()).max(delta.z.abs());
+        let step_x = delta.x as f32 / steps as f32;
+        let step_y = delta.y as f32 / steps as f32;
+        let step_z = delta.z as f32 / steps as f32;
+
+        for i in 0..=steps {
+            let pos = offset(
+                from,
+                floor((0.5 + i as f32 * step_x) as f64),
+                floor((0.5 + i as f32 * step_y) as f64),
+                floor((0.5 + i as f32 * step_z) as f64),
+            );
+
+            if place {
+                let axis = Self::log_axis(from, pos);
+                ctx.place_log_with(rng, pos, |state, palette| {
+                    with_property(&state, "axis", axis, palette).unwrap_or(state)
+                });
+            } else if !ctx.is_free(pos) {
+                return false
+            }
+        }
+
+        true
+    }
+
+    fn log_axis(from: Vector3, to: Vector3) -> &'static str {
+        let dx = (to.x - from.x).abs();
+        let dz = (to.z - from.z).abs();
+        let max = dx.max(dz);
+
+        if max == 0 {
+            "y"
+        } else if dx == max {
+            "x"
+        } else {
+            "z"
+        }
+    }
+
+    fn trim_branches(height: i32, y: i32) -> bool {
+        y as f64 >= height as f64 * 0.2
+    }
+
+    fn make_branches(ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3, foliage: &[(Vector3, i32)]) {
+        for (pos, base) in foliage {
+            let start = Vector3::new(origin.x, *base, origin.z);
+
+            if start != *pos && Self::trim_branches(height, base - origin.y) {
+                Self::make_limb(ctx, rng, start, *pos, true);
+            }
+        }
+    }
+
+    fn tree_shape(height: i32, y: i32) -> f32 {
+        if (y as f32) < height as f32 * 0.3 {
+            return -1.0
+        }
+
+        let radius = height as f32 / 2.0;
+        let dy = radius - y as f32;
+
+        if dy == 0.0 {
+            radius * 0.5
+        } else if dy.abs() >= radius {
+            0.0
+        } else {
+            (radius * radius - dy * dy).sqrt() * 0.5
+        }
+    }
+}
+
+/// Trunk of azaleas, which bends to one side at the top.
+pub struct BendingTrunkPlacer {
+    pub height: TrunkHeight,
+    pub min_height_for_leaves: i32,
+    pub bend_length: IntProvider,
+}
+
+impl BendingTrunkPlacer {
+    fn place(&self, ctx: &mut TreeContext, rng: &mut FeatureRng, height: i32, origin: Vector3) -> Vec<FoliageAttachment> {
+        let direction = Direction::random_horizontal(rng);
+        let top = height - 1;
+        let mut pos = origin;
+
+        ctx.set_dirt_at(rng, below(origin));
+
+        let mut attachments = Vec::new();
+        for i in 0..=top {
+            if i + 1 >= top + rng.next_u32(2) as i32 {
+                pos = direction.relative(pos);
+            }
+
+            if ctx.valid_tree_pos(pos) {
+                ctx.place_log(rng, pos);
+            }
+
+            if i >= self.min_height_for_leaves {
+                attachments.push(FoliageAttachment::new(pos, 0, false));
+            }
+
+            pos = above(pos, 1);
+        }
+
+        let bend_length = self.bend_length.sample(rng);
+        for _ in 0..=bend_length {
+            if ctx.valid_tree_pos(pos) {
+                ctx.place_log(rng, pos);
+            }
+
+            attachments.push(FoliageAttachment::new(pos, 0, false));
+            pos = direction.relative(pos);
+        }
+
+        attachments
+    }
+}
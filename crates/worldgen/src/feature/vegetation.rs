@@ -0,0 +1,369 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::placement::PlacementModifiers;
+use crate::feature::predicate::{can_survive, is_face_sturdy};
+use crate::feature::state::with_property;
+use crate::feature::state_provider::{BlockStateProvider, BlockStateProviders};
+use crate::feature::{ConfiguredFeature, Direction, Feature, FeatureRng, PlacedFeature};
+use crate::noise::json::Resolver;
+use crate::provider::IntProvider;
+use crate::rng::Rng;
+use crate::tag::{BlockSet, BlockTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+#[inline]
+fn field<'a>(json: &'a Value, name: &str) -> anyhow::Result<&'a Value> {
+    json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json))
+}
+
+#[inline]
+fn int_or(json: &Value, name: &str, default: i32) -> i32 {
+    json.get(name).and_then(Value::as_i64).map_or(default, |x| x as i32)
+}
+
+#[inline]
+fn f32_or(json: &Value, name: &str, default: f32) -> f32 {
+    json.get(name).and_then(Value::as_f64).map_or(default, |x| x as f32)
+}
+
+/// Places the nested feature at random positions around the origin. Covers
+/// `random_patch`, `flower` and `no_bonemeal_flower`.
+pub struct RandomPatchFeature {
+    pub tries: i32,
+    pub xz_spread: i32,
+    pub y_spread: i32,
+    pub feature: PlacedFeature,
+}
+
+impl RandomPatchFeature {
+    pub fn from_json(
+        json: &Value,
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            tries: int_or(json, "tries", 128),
+            xz_spread: int_or(json, "xz_spread", 7),
+            y_spread: int_or(json, "y_spread", 3),
+            feature: PlacedFeature::from_json(field(json, "feature")?, features, placements)?,
+        })
+    }
+}
+
+impl Feature for RandomPatchFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let xz_spread = (self.xz_spread + 1) as u32;
+        let y_spread = (self.y_spread + 1) as u32;
+
+        let mut placed = 0;
+        for _ in 0..self.tries {
+            let x = rng.next_u32(xz_spread) as i32 - rng.next_u32(xz_spread) as i32;
+            let y = rng.next_u32(y_spread) as i32 - rng.next_u32(y_spread) as i32;
+            let z = rng.next_u32(xz_spread) as i32 - rng.next_u32(xz_spread) as i32;
+
+            if self.feature.place(level, rng, Vector3::new(origin.x + x, origin.y + y, origin.z + z)) {
+                placed += 1;
+            }
+        }
+
+        placed > 0
+    }
+}
+
+/// Single block, e.g. grass or a flower, if it is able to survive at the
+/// origin. Double plants get their upper half placed above.
+pub struct SimpleBlockFeature {
+    pub to_place: BlockStateProviders,
+    /// Blocks of the `minecraft:dirt` tag.
+    pub dirt: BlockSet,
+    pub palette: Arc<BlockGlobalPalette>,
+}
+
+impl SimpleBlockFeature {
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: Arc<BlockGlobalPalette>) -> anyhow::Result<Self> {
+        Ok(Self {
+            to_place: BlockStateProviders::from_json(field(json, "to_place")?, &palette)?,
+            dirt: tags.tag("minecraft:dirt")?,
+            palette,
+        })
+    }
+}
+
+impl Feature for SimpleBlockFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let state = self.to_place.state(rng, origin);
+
+        if !can_survive(level, &state, origin, &self.dirt) {
+            return false
+        }
+
+        let upper = with_property(&state, "half", "upper", &self.palette);
+        let lower = with_property(&state, "half", "lower", &self.palette);
+
+        match (lower, upper) {
+            (Some(lower), Some(upper)) => {
+                let above = Vector3::new(origin.x, origin.y + 1, origin.z);
+                if !level.is_air(above) {
+                    return false
+                }
+
+                level.set_block_state(origin, lower);
+                level.set_block_state(above, upper);
+            }
+            _ => {
+                level.set_block_state(origin, state);
+            }
+        }
+
+        true
+    }
+}
+
+/// The first feature, whose chance holds, or the default one.
+pub struct RandomSelectorFeature {
+    pub features: Vec<(PlacedFeature, f32)>,
+    pub default: PlacedFeature,
+}
+
+impl RandomSelectorFeature {
+    pub fn from_json(
+        json: &Value,
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> anyhow::Result<Self> {
+        let Value::Array(entries) = field(json, "features")? else {
+            return Err(anyhow!("Expected \"features\" to be an array in {:?}", json))
+        };
+
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                let chance = field(entry, "chance")?
+                    .as_f64()
+                    .ok_or_else(|| anyhow!("Expected number \"chance\" in {:?}", entry))?;
+
+                Ok((PlacedFeature::from_json(field(entry, "feature")?, features, placements)?, chance as f32))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            features: entries,
+            default: PlacedFeature::from_json(field(json, "default")?, features, placements)?,
+        })
+    }
+}
+
+impl Feature for RandomSelectorFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        for (feature, chance) in &self.features {
+            if rng.next_f32() < *chance {
+                return feature.place(level, rng, origin)
+            }
+        }
+
+        self.default.place(level, rng, origin)
+    }
+}
+
+/// Patch of ground blocks (e.g. moss) on the floor or the ceiling, with the
+/// vegetation feature placed on some of them.
+pub struct VegetationPatchFeature {
+    pub replaceable: BlockSet,
+    pub ground_state: BlockStateProviders,
+    pub vegetation_feature: PlacedFeature,
+    /// `Down` for the floor, `Up` for the ceiling.
+    pub surface: Direction,
+    pub depth: IntProvider,
+    pub extra_bottom_block_chance: f32,
+    pub vertical_range: i32,
+    pub vegetation_chance: f32,
+    pub xz_radius: IntProvider,
+    pub extra_edge_column_chance: f32,
+}
+
+impl VegetationPatchFeature {
+    pub fn from_json(
+        json: &Value,
+        tags: &BlockTags,
+        palette: &BlockGlobalPalette,
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> anyhow::Result<Self> {
+        let Some(Value::String(replaceable)) = json.get("replaceable") else {
+            return Err(anyhow!("No \"replaceable\" key in {:?}", json))
+        };
+
+        let surface = match field(json, "surface")?.as_str() {
+            Some("floor") => Direction::Down,
+            Some("ceiling") => Direction::Up,
+            _ => return Err(anyhow!("Expected either \"floor\" or \"ceiling\" surface in {:?}", json))
+        };
+
+        Ok(Self {
+            replaceable: tags.tag(replaceable.strip_prefix('#').unwrap_or(replaceable))?,
+            ground_state: BlockStateProviders::from_json(field(json, "ground_state")?, palette)?,
+            vegetation_feature: PlacedFeature::from_json(field(json, "vegetation_feature")?, features, placements)?,
+            surface,
+            depth: IntProvider::from_json(field(json, "depth")?)?,
+            extra_bottom_block_chance: f32_or(json, "extra_bottom_block_chance", 0.0),
+            vertical_range: int_or(json, "vertical_range", 1),
+            vegetation_chance: f32_or(json, "vegetation_chance", 0.0),
+            xz_radius: IntProvider::from_json(field(json, "xz_radius")?)?,
+            extra_edge_column_chance: f32_or(json, "extra_edge_column_chance", 0.0),
+        })
+    }
+
+    /// Places the ground and returns the positions of the surface blocks.
+    fn place_ground_patch(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3, x_radius: i32, z_radius: i32) -> Vec<Vector3> {
+        let towards_surface = self.surface;
+        let away_from_surface = self.surface.opposite();
+
+        let mut surface = Vec::new();
+        for x in -x_radius..=x_radius {
+            let x_edge = x == -x_radius || x == x_radius;
+
+            for z in -z_radius..=z_radius {
+                let z_edge = z == -z_radius || z == z_radius;
+                let corner = x_edge && z_edge;
+                let edge = (x_edge || z_edge) && !corner;
+
+                if corner || edge && (self.extra_edge_column_chance == 0.0 || rng.next_f32() > self.extra_edge_column_chance) {
+                    continue
+                }
+
+                let mut pos = Vector3::new(origin.x + x, origin.y, origin.z + z);
+
+                let mut steps = 0;
+                while level.is_air(pos) && steps < self.vertical_range {
+                    pos = towards_surface.relative(pos);
+                    steps += 1;
+                }
+
+                steps = 0;
+                while !level.is_air(pos) && steps < self.vertical_range {
+                    pos = away_from_surface.relative(pos);
+                    steps += 1;
+                }
+
+                let ground = towards_surface.relative(pos);
+                if !level.is_air(pos) || !is_face_sturdy(&level.block_state(ground)) {
+                    continue
+                }
+
+                let extra = self.extra_bottom_block_chance > 0.0 && rng.next_f32() < self.extra_bottom_block_chance;
+                let depth = self.depth.sample(rng) + if extra { 1 } else { 0 };
+
+                if self.place_ground(level, rng, ground, depth) {
+                    surface.push(ground);
+                }
+            }
+        }
+
+        surface
+    }
+
+    fn place_ground(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, mut pos: Vector3, depth: i32) -> bool {
+        for i in 0..depth {
+            let state = self.ground_state.state(rng, pos);
+            let current = level.block_state(pos);
+
+            if state.block() == current.block() {
+                continue
+            }
+
+            if !self.replaceable.contains(current.block()) {
+                return i != 0
+            }
+
+            level.set_block_state(pos, state);
+            pos = self.surface.relative(pos);
+        }
+
+        true
+    }
+}
+
+impl Feature for VegetationPatchFeature {
+    fn place(&self, level: &mut WorldGenLevel, rng: &mut FeatureRng, origin: Vector3) -> bool {
+        let x_radius = self.xz_radius.sample(rng) + 1;
+        let z_radius = self.xz_radius.sample(rng) + 1;
+
+        let surface = self.place_ground_patch(level, rng, origin, x_radius, z_radius);
+
+        // Vanilla iterates a hash set of the positions, whose order can not be
+        // reproduced, so the vegetation is placed in the order of the ground.
+        for pos in &surface {
+            if self.vegetation_chance > 0.0 && rng.next_f32() < self.vegetation_chance {
+                self.vegetation_feature.place(level, rng, self.surface.opposite().relative(*pos));
+            }
+        }
+
+        !surface.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::json::{feature_deserializers, placement_deserializers, predicate_deserializers};
+    use crate::feature::vegetation::{RandomPatchFeature, RandomSelectorFeature};
+    use crate::noise::json::value_resolver::MockValueResolver;
+    use crate::noise::json::Resolver;
+    use crate::pack::DataPacks;
+    use crate::tag::BlockTags;
+    use serde_json::json;
+    use spherix_world::chunk::palette::BlockGlobalPalette;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[test]
+    fn nested_features_from_json() {
+        let tags = Rc::new(BlockTags::new(Arc::new(DataPacks::new(Vec::new()))));
+        let palette = Arc::new(BlockGlobalPalette::new(0));
+
+        let predicates = Rc::new(Resolver::new(
+            predicate_deserializers(tags.clone(), palette.clone()),
+            Box::new(MockValueResolver::new(HashMap::new())),
+        ));
+
+        let placements = Rc::new(Resolver::new(
+            placement_deserializers(predicates.clone()),
+            Box::new(MockValueResolver::new(HashMap::from([
+                ("minecraft:nothing".to_owned(), json!({"feature": {"type": "minecraft:no_op", "config": {}}, "placement": []})),
+            ]))),
+        ));
+
+        let features = Resolver::new(
+            feature_deserializers(tags, palette, predicates, placements.clone()),
+            Box::new(MockValueResolver::new(HashMap::new())),
+        );
+
+        let patch = RandomPatchFeature::from_json(
+            &json!({"feature": "minecraft:nothing", "tries": 96}),
+            &features,
+            &placements,
+        ).unwrap();
+
+        assert_eq!(96, patch.tries);
+        assert_eq!(7, patch.xz_spread);
+        assert_eq!(3, patch.y_spread);
+
+        let selector = RandomSelectorFeature::from_json(
+            &json!({
+                "features": [
+                    {"chance": 0.2, "feature": "minecraft:nothing"},
+                    {"chance": 0.5, "feature": {"feature": {"type": "minecraft:no_op", "config": {}}, "placement": []}}
+                ],
+                "default": "minecraft:nothing"
+            }),
+            &features,
+            &placements,
+        ).unwrap();
+
+        assert_eq!(vec![0.2, 0.5], selector.features.iter().map(|(_, chance)| *chance).collect::<Vec<_>>());
+        assert!(selector.default.placement.is_empty());
+    }
+}
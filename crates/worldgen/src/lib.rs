@@ -3,5 +3,13 @@ pub mod noise;
 pub mod chunk;
 pub mod aquifer;
 pub mod material;
+pub mod ore_vein;
 pub mod biome;
 pub mod surface;
+pub mod provider;
+pub mod tag;
+pub mod carver;
+pub mod feature;
+pub mod structure;
+pub mod pack;
+pub mod world;
@@ -1,9 +1,12 @@
-use spherix_math::vector::Vector3;
+use crate::aquifer::Aquifer;
+use crate::noise::density::density::{DensityFunction, DensityFunctionContext, DensityFunctions};
 use spherix_world::block::state::BlockState;
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 pub trait BlockStateFiller {
-    fn calculate(&self, pos: &Vector3) -> Option<Arc<BlockState>>;
+    fn calculate(&self, ctx: &mut DensityFunctionContext) -> Option<Arc<BlockState>>;
 }
 
 pub struct MaterialRuleList(Vec<Box<dyn BlockStateFiller>>);
@@ -16,9 +19,9 @@ impl MaterialRuleList {
 }
 
 impl BlockStateFiller for MaterialRuleList {
-    fn calculate(&self, pos: &Vector3) -> Option<Arc<BlockState>> {
+    fn calculate(&self, ctx: &mut DensityFunctionContext) -> Option<Arc<BlockState>> {
         for filler in self.0.iter() {
-            let mb_state = filler.calculate(pos);
+            let mb_state = filler.calculate(ctx);
             if mb_state.is_some() {
                 return Some(mb_state.unwrap())
             }
@@ -27,3 +30,28 @@ impl BlockStateFiller for MaterialRuleList {
         None
     }
 }
+
+/// Fills the terrain, which is shaped by the final density, with fluids of the
+/// aquifer. Solid blocks are left to the following fillers.
+pub struct AquiferFiller {
+    final_density: DensityFunctions,
+    aquifer: Rc<RefCell<Box<dyn Aquifer>>>,
+}
+
+impl AquiferFiller {
+    #[inline]
+    pub fn new(final_density: DensityFunctions, aquifer: Rc<RefCell<Box<dyn Aquifer>>>) -> Self {
+        Self {
+            final_density,
+            aquifer,
+        }
+    }
+}
+
+impl BlockStateFiller for AquiferFiller {
+    fn calculate(&self, ctx: &mut DensityFunctionContext) -> Option<Arc<BlockState>> {
+        let density = self.final_density.sample(ctx.pos(), ctx);
+
+        self.aquifer.borrow_mut().compute(ctx, density)
+    }
+}
@@ -0,0 +1,240 @@
+use crate::noise::density::density::{DensityFunction, DensityFunctionContext, DensityFunctions, Mapper};
+use crate::noise::math::{clamped_map, fast_inv_sqrt};
+use crate::structure::bounding_box::BoundingBox;
+use crate::structure::jigsaw::JigsawJunction;
+use crate::structure::pool::Projection;
+use crate::structure::{StructureStart, TerrainAdjustment};
+use lazy_static::lazy_static;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::pos::ChunkPos;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+const KERNEL_RADIUS: i32 = 12;
+const KERNEL_SIZE: i32 = 24;
+
+lazy_static! {
+    static ref BEARD_KERNEL: Vec<f32> = (0..KERNEL_SIZE * KERNEL_SIZE * KERNEL_SIZE)
+        .map(|i| {
+            let z = i / (KERNEL_SIZE * KERNEL_SIZE) - KERNEL_RADIUS;
+            let x = i / KERNEL_SIZE % KERNEL_SIZE - KERNEL_RADIUS;
+            let y = i % KERNEL_SIZE - KERNEL_RADIUS;
+            let y = y as f64 + 0.5;
+
+            (-((x * x) as f64 + y * y + (z * z) as f64) / 16.0).exp() as f32
+        })
+        .collect();
+}
+
+/// Piece whose terrain is adapted as a whole, i.e. a rigid one.
+#[derive(Debug, Clone)]
+pub struct Rigid {
+    pub bounding_box: BoundingBox,
+    pub adjustment: TerrainAdjustment,
+    pub ground_level_delta: i32,
+}
+
+/// Adds density around the structure pieces of the chunk, so the pieces
+/// sit on the ground and are not cut by it. Without pieces it is the marker
+/// `minecraft:beardifier`, which is always 0.
+#[derive(Clone, Default)]
+pub struct Beardifier {
+    rigids: Arc<[Rigid]>,
+    junctions: Arc<[JigsawJunction]>,
+}
+
+impl Beardifier {
+    pub fn new(rigids: Vec<Rigid>, junctions: Vec<JigsawJunction>) -> Self {
+        Self {
+            rigids: rigids.into(),
+            junctions: junctions.into(),
+        }
+    }
+
+    /// Equivalent of vanilla `Beardifier::forStructuresInChunk()`. Takes the
+    /// pieces of the starts, which are within the kernel radius of the chunk.
+    pub fn for_structures_in_chunk<'a, I>(pos: &ChunkPos, starts: I) -> Self
+    where
+        I: IntoIterator<Item = &'a StructureStart>
+    {
+        let min_x = pos.get_min_block_x();
+        let min_z = pos.get_min_block_z();
+
+        let mut rigids = Vec::new();
+        let mut junctions = Vec::new();
+
+        for start in starts {
+            let adjustment = start.structure.terrain_adaptation;
+            if adjustment == TerrainAdjustment::None {
+                continue
+            }
+
+            let close_pieces = start.pieces.iter().filter(|piece| {
+                piece.bounding_box.intersects_xz(
+                    min_x - KERNEL_RADIUS,
+                    min_z - KERNEL_RADIUS,
+                    min_x + 15 + KERNEL_RADIUS,
+                    min_z + 15 + KERNEL_RADIUS,
+                )
+            });
+
+            for piece in close_pieces {
+                if piece.element.projection() == Projection::Rigid {
+                    rigids.push(Rigid {
+                        bounding_box: piece.bounding_box,
+                        adjustment,
+                        ground_level_delta: piece.ground_level_delta,
+                    });
+                }
+
+                junctions.extend(
+                    piece
+                        .junctions
+                        .iter()
+                        .filter(|junction| {
+                            junction.source_x > min_x - KERNEL_RADIUS
+                                && junction.source_z > min_z - KERNEL_RADIUS
+                                && junction.source_x < min_x + 15 + KERNEL_RADIUS
+                                && junction.source_z < min_z + 15 + KERNEL_RADIUS
+                        })
+                        .cloned()
+                );
+            }
+        }
+
+        Self::new(rigids, junctions)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rigids.is_empty() && self.junctions.is_empty()
+    }
+
+    fn bury_contribution(x: i32, y: i32, z: i32) -> f64 {
+        let distance = ((x * x + y * y + z * z) as f64).sqrt();
+
+        clamped_map(distance, 0.0, 6.0, 1.0, 0.0)
+    }
+
+    fn beard_contribution(x: i32, y: i32, z: i32, y_to_ground: i32) -> f64 {
+        let i = x + KERNEL_RADIUS;
+        let j = y + KERNEL_RADIUS;
+        let k = z + KERNEL_RADIUS;
+
+        if !(0..KERNEL_SIZE).contains(&i) || !(0..KERNEL_SIZE).contains(&j) || !(0..KERNEL_SIZE).contains(&k) {
+            return 0.0
+        }
+
+        let y_to_ground = y_to_ground as f64 + 0.5;
+        let distance_squared = (x * x) as f64 + y_to_ground * y_to_ground + (z * z) as f64;
+        let weight = -y_to_ground * fast_inv_sqrt(distance_squared / 2.0) / 2.0;
+
+        weight * BEARD_KERNEL[(k * KERNEL_SIZE * KERNEL_SIZE + i * KERNEL_SIZE + j) as usize] as f64
+    }
+}
+
+impl Debug for Beardifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Beardifier (rigids: {}, junctions: {})", self.rigids.len(), self.junctions.len())
+    }
+}
+
+impl DensityFunction for Beardifier {
+    fn sample(&self, at: Vector3, _: &mut DensityFunctionContext) -> f64 {
+        let mut density = 0.0;
+
+        for rigid in self.rigids.iter() {
+            let bounding_box = &rigid.bounding_box;
+            let dx = 0.max((bounding_box.min_x - at.x).max(at.x - bounding_box.max_x));
+            let dz = 0.max((bounding_box.min_z - at.z).max(at.z - bounding_box.max_z));
+            let ground_y = bounding_box.min_y + rigid.ground_level_delta;
+            let y_to_ground = at.y - ground_y;
+
+            density += match rigid.adjustment {
+                TerrainAdjustment::None | TerrainAdjustment::Encapsulate => 0.0,
+                TerrainAdjustment::Bury => Self::bury_contribution(dx, y_to_ground, dz),
+                TerrainAdjustment::BeardThin => Self::beard_contribution(dx, y_to_ground, dz, y_to_ground) * 0.8,
+                TerrainAdjustment::BeardBox => {
+                    let dy = 0.max((ground_y - at.y).max(at.y - bounding_box.max_y));
+
+                    Self::beard_contribution(dx, dy, dz, y_to_ground) * 0.8
+                }
+            };
+        }
+
+        for junction in self.junctions.iter() {
+            let y_to_ground = at.y - junction.source_ground_y;
+
+            density += Self::beard_contribution(
+                at.x - junction.source_x,
+                y_to_ground,
+                at.z - junction.source_z,
+                y_to_ground,
+            ) * 0.4;
+        }
+
+        density
+    }
+
+    fn min_value(&self) -> f64 {
+        if self.is_empty() { 0.0 } else { f64::NEG_INFINITY }
+    }
+
+    fn max_value(&self) -> f64 {
+        if self.is_empty() { 0.0 } else { f64::INFINITY }
+    }
+
+    fn map<M: Mapper>(self, mapper: &M) -> DensityFunctions {
+        mapper.map(DensityFunctions::Beardifier(self))
+    }
+}
+
+/// Replaces the `minecraft:beardifier` markers with the beardifier of the
+/// chunk.
+pub struct BeardifierMapper {
+    beardifier: Beardifier,
+}
+
+impl BeardifierMapper {
+    pub fn new(beardifier: Beardifier) -> Self {
+        Self {
+            beardifier,
+        }
+    }
+}
+
+impl Mapper for BeardifierMapper {
+    fn map(&self, df: DensityFunctions) -> DensityFunctions {
+        match df {
+            DensityFunctions::Beardifier(_) => DensityFunctions::Beardifier(self.beardifier.clone()),
+            _ => df
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::density::beardifier::{Beardifier, Rigid};
+    use crate::noise::density::density::{DensityFunction, DensityFunctionContext};
+    use crate::structure::bounding_box::BoundingBox;
+    use crate::structure::TerrainAdjustment;
+    use spherix_math::vector::Vector3;
+
+    #[test]
+    fn beard_thin_fills_below_and_clears_above() {
+        let beardifier = Beardifier::new(
+            vec![Rigid {
+                bounding_box: BoundingBox::new(0, 64, 0, 8, 72, 8),
+                adjustment: TerrainAdjustment::BeardThin,
+                ground_level_delta: 1,
+            }],
+            Vec::new(),
+        );
+        let mut ctx = DensityFunctionContext::default();
+
+        assert!(beardifier.sample(Vector3::new(4, 63, 4), &mut ctx) > 0.0);
+        assert!(beardifier.sample(Vector3::new(4, 67, 4), &mut ctx) < 0.0);
+        assert_eq!(0.0, beardifier.sample(Vector3::new(4, 40, 4), &mut ctx));
+        assert_eq!(0.0, beardifier.sample(Vector3::new(30, 65, 4), &mut ctx));
+        assert_eq!(0.0, Beardifier::default().sample(Vector3::new(4, 63, 4), &mut ctx));
+    }
+}
@@ -4,8 +4,8 @@ use std::fmt::{Debug, Formatter};
 
 #[derive(Clone)]
 pub struct AddConst {
-    input: DensityFunctions,
-    argument: f64,
+    pub(crate) input: DensityFunctions,
+    pub(crate) argument: f64,
     min_value: f64,
     max_value: f64,
 }
@@ -64,8 +64,8 @@ impl DensityFunction for AddConst {
 
 #[derive(Clone)]
 pub struct Add {
-    argument1: DensityFunctions,
-    argument2: DensityFunctions,
+    pub(crate) argument1: DensityFunctions,
+    pub(crate) argument2: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -140,8 +140,8 @@ impl DensityFunction for Add {
 
 #[derive(Clone)]
 pub struct MulConst {
-    input: DensityFunctions,
-    argument: f64,
+    pub(crate) input: DensityFunctions,
+    pub(crate) argument: f64,
     min_value: f64,
     max_value: f64,
 }
@@ -325,8 +325,8 @@ impl DensityFunction for Mul {
 
 #[derive(Clone)]
 pub struct Min {
-    argument1: DensityFunctions,
-    argument2: DensityFunctions,
+    pub(crate) argument1: DensityFunctions,
+    pub(crate) argument2: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -412,8 +412,8 @@ impl DensityFunction for Min {
 
 #[derive(Clone)]
 pub struct Max {
-    argument1: DensityFunctions,
-    argument2: DensityFunctions,
+    pub(crate) argument1: DensityFunctions,
+    pub(crate) argument2: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
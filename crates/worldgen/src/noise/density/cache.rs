@@ -1,9 +1,8 @@
 use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions, Mapper};
 use spherix_math::vector::Vector3;
 use spherix_util::slice::slice_copy;
-use spherix_world::chunk::pos::ChunkPos;
-use std::cell::{Cell, RefCell};
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Clone)]
 pub struct FlatCache {
@@ -42,6 +41,12 @@ impl FlatCache {
             empty: true
         }
     }
+
+    /// Whether the cache passes the samples through to its argument.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
 }
 
 impl Debug for FlatCache {
@@ -154,48 +159,31 @@ pub fn length(x: f64, y: f64, z: f64) -> f64 {
     length_squared(x, y, z).sqrt()
 }
 
+/// Marker `minecraft:cache_2d`. Vanilla caches the last column only within a
+/// noise chunk, where it saves nothing over the flat caches, so it passes the
+/// samples through.
 #[derive(Clone)]
 pub struct Cache2D {
-    inner: DensityFunctions,
-    last_pos_2d: Cell<i64>,
-    last_value: Cell<f64>,
-    empty: bool
+    pub(crate) inner: DensityFunctions,
 }
 
 impl Cache2D {
     pub fn new(inner: DensityFunctions) -> Self {
         Self {
             inner,
-            last_pos_2d: Cell::new(ChunkPos::INVALID.into()),
-            last_value: Cell::new(0.0),
-            empty: true
         }
     }
 }
 
 impl Debug for Cache2D {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Cache2D (last_pos_2d: {}, last_value: {})", self.last_pos_2d.get(), self.last_value.get())
+        write!(f, "Cache2D")
     }
 }
 
 impl DensityFunction for Cache2D {
     fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
-        if self.empty {
-            return self.inner.sample(at, ctx);
-        }
-
-        let i = at.x;
-        let j = at.z;
-        let k = ChunkPos::new(i, j).into();
-        if self.last_pos_2d.get() == k {
-            self.last_value.get()
-        } else {
-            self.last_pos_2d.set(k);
-            let value = self.inner.sample(at, ctx);
-            self.last_value.set(value);
-            value
-        }
+        self.inner.sample(at, ctx)
     }
 
     fn fill_array(&self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
@@ -223,23 +211,23 @@ impl DensityFunction for Cache2D {
     }
 }
 
+/// Ids of the `cache_once` functions. Copies of a function keep its id, since
+/// they sample the same values.
+static NEXT_CACHE_ONCE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Marker `minecraft:cache_once`. The cached values live in the context (see
+/// [`CacheOnceState`]), so the function itself is immutable.
 #[derive(Clone)]
 pub struct CacheOnce {
-    inner: DensityFunctions,
-    last_counter: Cell<usize>,
-    last_array_counter: Cell<usize>,
-    last_value: Cell<f64>,
-    last_array: RefCell<Option<Vec<f64>>>,
+    pub(crate) inner: DensityFunctions,
+    id: usize,
 }
 
 impl CacheOnce {
     pub fn new(inner: DensityFunctions) -> Self {
         Self {
             inner,
-            last_counter: Cell::new(0),
-            last_array_counter: Cell::new(0),
-            last_value: Cell::new(0.0),
-            last_array: RefCell::new(None),
+            id: NEXT_CACHE_ONCE_ID.fetch_add(1, Ordering::Relaxed),
         }
     }
 }
@@ -250,36 +238,75 @@ impl Debug for CacheOnce {
     }
 }
 
+/// Last value and array of a [`CacheOnce`] in a context, along with the
+/// counters of the context they were computed at.
+pub struct CacheOnceState {
+    last_counter: usize,
+    last_array_counter: usize,
+    last_value: f64,
+    last_array: Option<Vec<f64>>,
+}
+
+impl Default for CacheOnceState {
+    /// Nothing is cached yet, even at the initial counters of a context.
+    fn default() -> Self {
+        Self {
+            last_counter: usize::MAX,
+            last_array_counter: usize::MAX,
+            last_value: 0.0,
+            last_array: None,
+        }
+    }
+}
+
 impl DensityFunction for CacheOnce {
     fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
         if ctx.filler != ContextFiller::Default {
-            self.inner.sample(at, ctx )
-        } else if self.last_array.borrow().is_some() && self.last_array_counter.get() == ctx.array_interpolation_counter {
-            self.last_array.borrow().as_ref().unwrap()[ctx.array_index]
-        } else if self.last_counter.get() == ctx.interpolation_counter {
-            self.last_value.get()
-        } else {
-            self.last_counter.set(ctx.interpolation_counter);
-            let d0 = self.inner.sample(at, ctx);
-            self.last_value.set(d0);
+            return self.inner.sample(at, ctx)
+        }
+
+        let array_index = ctx.array_index;
+        let array_interpolation_counter = ctx.array_interpolation_counter;
+        let interpolation_counter = ctx.interpolation_counter;
+        let state = ctx.cache_once(self.id);
 
-            d0
+        if let Some(last_array) = &state.last_array {
+            if state.last_array_counter == array_interpolation_counter {
+                return last_array[array_index]
+            }
+        }
+
+        if state.last_counter == interpolation_counter {
+            return state.last_value
         }
+
+        state.last_counter = interpolation_counter;
+        let d0 = self.inner.sample(at, ctx);
+        ctx.cache_once(self.id).last_value = d0;
+
+        d0
     }
 
     fn fill_array(&self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
-        if self.last_array.borrow().is_some() && self.last_array_counter.get() == ctx.array_interpolation_counter {
-            slice_copy(self.last_array.borrow().as_ref().unwrap(), 0, arr, 0, arr.len());
-        } else {
-            self.inner.fill_array(arr, ctx);
-            if self.last_array.borrow().is_some() && self.last_array.borrow().as_ref().unwrap().len() == arr.len() {
-                slice_copy(arr, 0, self.last_array.borrow_mut().as_mut().unwrap(), 0, arr.len());
-            } else {
-                self.last_array.replace(Some(arr.into()));
+        let array_interpolation_counter = ctx.array_interpolation_counter;
+        let state = ctx.cache_once(self.id);
+
+        if let Some(last_array) = &state.last_array {
+            if state.last_array_counter == array_interpolation_counter {
+                slice_copy(last_array, 0, arr, 0, arr.len());
+                return
             }
+        }
 
-            self.last_array_counter.set(ctx.array_interpolation_counter)
+        self.inner.fill_array(arr, ctx);
+
+        let state = ctx.cache_once(self.id);
+        match &mut state.last_array {
+            Some(last_array) if last_array.len() == arr.len() => slice_copy(arr, 0, last_array, 0, arr.len()),
+            last_array => *last_array = Some(arr.into()),
         }
+
+        state.last_array_counter = array_interpolation_counter;
     }
 
     fn min_value(&self) -> f64 {
@@ -294,32 +321,31 @@ impl DensityFunction for CacheOnce {
         mapper.map(
             DensityFunctions::CacheOnce(
                 Box::new(
-                    Self::new(
-                        self.inner.map(mapper)
-                    )
+                    Self {
+                        inner: self.inner.map(mapper),
+                        id: self.id,
+                    }
                 )
             )
         )
     }
 }
 
+/// Values of the function at all positions of the cell the noise chunk fills.
+/// The values live in [`DensityFunctionContext::cell_caches`].
 #[derive(Clone)]
 pub struct CacheAllInCell {
-    inner: DensityFunctions,
-    values: Vec<f64>,
+    pub(crate) inner: DensityFunctions,
+    pub(crate) index: usize,
 }
 
 impl CacheAllInCell {
-    pub fn new(inner: DensityFunctions, cell_width: u32, cell_height: u32) -> Self {
+    pub fn new(inner: DensityFunctions, index: usize) -> Self {
         Self {
             inner,
-            values: vec![0.0; (cell_width * cell_width * cell_height) as usize],
+            index,
         }
     }
-
-    pub fn fill_array_inner(&mut self, ctx: &mut DensityFunctionContext) {
-        self.inner.fill_array(&mut self.values, ctx);
-    }
 }
 
 impl Debug for CacheAllInCell {
@@ -338,10 +364,11 @@ impl DensityFunction for CacheAllInCell {
         let j = ctx.in_cell_y;
         let k = ctx.in_cell_z;
 
-        if i >= 0 && j >= 0 && k >= 0 && i < ctx.cell_width && j < ctx.cell_height && k < ctx.cell_width {
-            self.values[(((ctx.cell_height - 1 - j) * ctx.cell_width + i) * ctx.cell_width + k) as usize]
-        } else {
-            self.inner.sample(at, ctx)
+        match ctx.cell_caches.get(self.index) {
+            Some(values) if i < ctx.cell_width && j < ctx.cell_height && k < ctx.cell_width => {
+                values[(((ctx.cell_height - 1 - j) * ctx.cell_width + i) * ctx.cell_width + k) as usize]
+            }
+            _ => self.inner.sample(at, ctx)
         }
     }
 
@@ -354,6 +381,39 @@ impl DensityFunction for CacheAllInCell {
     }
 
     fn map<M: Mapper>(self, mapper: &M) -> DensityFunctions {
-        todo!()
+        mapper.map(
+            DensityFunctions::CacheAllInCell(
+                Box::new(
+                    Self::new(
+                        self.inner.map(mapper),
+                        self.index
+                    )
+                )
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::density::cache::CacheOnce;
+    use crate::noise::density::density::{DensityFunction, DensityFunctionContext, DensityFunctions};
+    use crate::noise::density::misc::YClampedGradient;
+    use spherix_math::vector::Vector3;
+
+    #[test]
+    fn cache_once_caches_per_context() {
+        let df = CacheOnce::new(DensityFunctions::YClampedGradient(YClampedGradient::new(-64, 320, -1.0, 1.0)));
+        let mut first = DensityFunctionContext::default();
+        let mut second = DensityFunctionContext::default();
+
+        assert_eq!(df.sample(Vector3::new(0, -64, 0), &mut first), -1.0);
+        assert_eq!(df.sample(Vector3::new(0, 320, 0), &mut second), 1.0);
+
+        // The counter has not moved, so the first context keeps its value.
+        assert_eq!(df.sample(Vector3::new(0, 320, 0), &mut first), -1.0);
+
+        first.interpolation_counter += 1;
+        assert_eq!(df.sample(Vector3::new(0, 320, 0), &mut first), 1.0);
     }
 }
@@ -1,5 +1,5 @@
 use crate::noise::blending::blender::{Blender, BlendingOutput};
-use crate::noise::density::cache::{Cache2D, CacheAllInCell, CacheOnce, FlatCache};
+use crate::noise::density::cache::{Cache2D, CacheAllInCell, CacheOnce, CacheOnceState, FlatCache};
 use crate::noise::math::{floor_div, floor_mod};
 use debug_tree::TreeBuilder;
 use spherix_math::vector::Vector3;
@@ -7,7 +7,6 @@ use spherix_world::chunk::pos::ChunkPos;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Pointer};
-use std::rc::Rc;
 use std::sync::Arc;
 
 pub trait Mapper {
@@ -57,18 +56,31 @@ impl Mapper for DebugMapper {
 
 pub struct SetupNoiseMapper<R: RngPos> {
     rng: Arc<R>,
-    noises: RefCell<HashMap<String, Rc<NoiseHolder<DefaultNoise>>>>,
+    /// Seed of the world, when the noise settings use the legacy random
+    /// source. Some noises are then seeded by it directly.
+    legacy_seed: Option<i64>,
+    noises: RefCell<HashMap<String, Arc<NoiseHolder<DefaultNoise>>>>,
 }
 
 impl<R: RngPos> SetupNoiseMapper<R> {
     pub fn new(rng: Arc<R>) -> Self {
         Self {
             rng,
+            legacy_seed: None,
             noises: Default::default(),
         }
     }
 
-    pub fn get_or_create_noise(&self, holder: &NoiseHolder<DefaultNoise>) -> Rc<NoiseHolder<DefaultNoise>> {
+    /// Sets the noises up like vanilla does with `legacy_random_source`.
+    pub fn new_legacy(rng: Arc<R>, seed: i64) -> Self {
+        Self {
+            rng,
+            legacy_seed: Some(seed),
+            noises: Default::default(),
+        }
+    }
+
+    pub fn get_or_create_noise(&self, holder: &NoiseHolder<DefaultNoise>) -> Arc<NoiseHolder<DefaultNoise>> {
         let tag = holder.tag();
         let borrow = self.noises.borrow();
         if borrow.contains_key(tag) {
@@ -76,14 +88,40 @@ impl<R: RngPos> SetupNoiseMapper<R> {
         } else {
             drop(borrow);
 
-            let noise = Rc::new(
-                holder.with_rng(&mut self.rng.by_hash(tag.to_owned()))
+            let noise = Arc::new(
+                self.create_legacy_noise(tag)
+                    .unwrap_or_else(|| holder.with_rng(&mut self.rng.by_hash(tag.to_owned())))
             );
             self.noises.borrow_mut().insert(tag.to_owned(), noise.clone());
 
             noise
         }
     }
+
+    /// Equivalent of the legacy branch of vanilla `NoiseWiringHelper::wrapNew()`.
+    fn create_legacy_noise(&self, tag: &str) -> Option<NoiseHolder<DefaultNoise>> {
+        let seed = self.legacy_seed?;
+
+        let salt = match tag {
+            "minecraft:temperature" => 0,
+            "minecraft:vegetation" => 1,
+            "minecraft:offset" => {
+                let params = MultiOctaveNoiseParameters::new(0, vec![0.0]);
+                let noise = DefaultNoise::create(&mut self.rng.by_hash(tag.to_owned()), &params.amplitudes, params.first_octave);
+
+                return Some(NoiseHolder::new(tag.to_owned(), params, Some(noise)))
+            }
+            _ => return None
+        };
+
+        let params = MultiOctaveNoiseParameters::new(-7, vec![1.0, 1.0]);
+        let noise = DefaultNoise::create_legacy_nether_biome(
+            &mut LegacyRng::new(LcgEntropySrc::new(seed.wrapping_add(salt) as u64)),
+            &params
+        );
+
+        Some(NoiseHolder::new(tag.to_owned(), params, Some(noise)))
+    }
 }
 
 impl<R: RngPos> Mapper for SetupNoiseMapper<R> {
@@ -100,9 +138,10 @@ impl<R: RngPos> Mapper for SetupNoiseMapper<R> {
             }
             DensityFunctions::OldBlendedNoise(x) => {
                 DensityFunctions::OldBlendedNoise(
-                    x.with_new_rng(
-                        &mut self.rng.by_hash("minecraft:terrain".to_owned())
-                    )
+                    match self.legacy_seed {
+                        Some(seed) => x.with_new_rng(&mut LegacyRng::new(LcgEntropySrc::new(seed as u64))),
+                        None => x.with_new_rng(&mut self.rng.by_hash("minecraft:terrain".to_owned())),
+                    }
                 )
             }
             DensityFunctions::ShiftedNoise(df) => {
@@ -146,6 +185,29 @@ impl<R: RngPos> Mapper for SetupNoiseMapper<R> {
     }
 }
 
+/// Seeds the `minecraft:end_islands` functions, which use the world seed
+/// directly instead of a forked random.
+pub struct SetupEndIslandsMapper {
+    end_islands: EndIslands,
+}
+
+impl SetupEndIslandsMapper {
+    pub fn new(seed: i64) -> Self {
+        Self {
+            end_islands: EndIslands::new(seed),
+        }
+    }
+}
+
+impl Mapper for SetupEndIslandsMapper {
+    fn map(&self, df: DensityFunctions) -> DensityFunctions {
+        match df {
+            DensityFunctions::EndIslands(_) => DensityFunctions::EndIslands(self.end_islands.clone()),
+            _ => df
+        }
+    }
+}
+
 pub struct SetupFlatCacheMapper {
     ctx: RefCell<DensityFunctionContext>,
 }
@@ -197,13 +259,11 @@ impl SetupInterpolatedMapper {
 
 impl Mapper for SetupInterpolatedMapper {
     fn map(&self, df: DensityFunctions) -> DensityFunctions {
-        match &df {
+        match df {
             DensityFunctions::InterpolatedInner(inner) => {
                 DensityFunctions::InterpolatedInner(
-                    Rc::new(
-                        RefCell::new(
-                            InterpolatedInner::new(inner.borrow().argument.clone(), self.cell_count_y, self.cell_count_xz)
-                        )
+                    Box::new(
+                        InterpolatedInner::new(inner.argument, self.cell_count_y, self.cell_count_xz)
                     )
                 )
             }
@@ -212,8 +272,11 @@ impl Mapper for SetupInterpolatedMapper {
     }
 }
 
+/// Numbers the interpolators of a noise chunk, so that each of them finds its
+/// state in [`DensityFunctionContext::interpolators`], and collects them in
+/// that order.
 pub struct InterpolatedCollector {
-    pub collected: RefCell<Vec<Rc<RefCell<InterpolatedInner>>>>,
+    pub collected: RefCell<Vec<InterpolatedInner>>,
 }
 
 impl InterpolatedCollector {
@@ -226,38 +289,14 @@ impl InterpolatedCollector {
 
 impl Mapper for InterpolatedCollector {
     fn map(&self, df: DensityFunctions) -> DensityFunctions {
-        match &df {
-            DensityFunctions::InterpolatedInner(inner) => {
-                self
-                    .collected
-                    .borrow_mut()
-                    .push(inner.clone());
-
-                df
-            }
-            _ => df
-        }
-    }
-}
-
-pub struct CacheAllInCellCollector(RefCell<Vec<Rc<RefCell<CacheAllInCell>>>>);
+        match df {
+            DensityFunctions::InterpolatedInner(mut inner) => {
+                let mut collected = self.collected.borrow_mut();
 
-impl CacheAllInCellCollector {
-    pub fn new() -> Self {
-        Self(RefCell::new(Vec::new()))
-    }
-}
+                inner.index = collected.len();
+                collected.push(inner.as_ref().clone());
 
-impl Mapper for CacheAllInCellCollector {
-    fn map(&self, df: DensityFunctions) -> DensityFunctions {
-        match &df {
-            DensityFunctions::CacheAllInCell(cache) => {
-                self
-                    .0
-                    .borrow_mut()
-                    .push(cache.clone());
-
-                df
+                DensityFunctions::InterpolatedInner(inner)
             }
             _ => df
         }
@@ -280,23 +319,26 @@ pub trait DensityFunction: Clone + Debug {
     fn map<M: Mapper>(self, mapper: &M) -> DensityFunctions;
 }
 
+#[derive(Clone)]
 pub enum DensityFunctions {
     Abs(Box<Abs>),
     Add(Box<Add>),
     AddConst(Box<AddConst>),
+    Beardifier(Beardifier),
     BlendAlpha(BlendAlpha),
     BlendDensity(Box<BlendDensity>),
     BlendOffset(BlendOffset),
     Cache2D(Box<Cache2D>),
-    CacheAllInCell(Rc<RefCell<CacheAllInCell>>),
+    CacheAllInCell(Box<CacheAllInCell>),
     CacheOnce(Box<CacheOnce>),
     Clamp(Box<Clamp>),
     Const(Const),
     Spline(Box<Spline>),
     Cube(Box<Cube>),
+    EndIslands(EndIslands),
     FlatCache(Box<FlatCache>),
     HalfNegative(Box<HalfNegative>),
-    InterpolatedInner(Rc<RefCell<InterpolatedInner>>),
+    InterpolatedInner(Box<InterpolatedInner>),
     Interpolated(Box<Interpolated>),
     Marker(Box<Marker>),
     Max(Box<Max>),
@@ -317,67 +359,13 @@ pub enum DensityFunctions {
     Debug(Box<DebugDensityFunction>),
 }
 
-impl Clone for DensityFunctions {
-    fn clone(&self) -> Self {
-        match self {
-            DensityFunctions::Abs(x) => DensityFunctions::Abs(x.clone()),
-            DensityFunctions::Add(x) => DensityFunctions::Add(x.clone()),
-            DensityFunctions::AddConst(x) => DensityFunctions::AddConst(x.clone()),
-            DensityFunctions::BlendAlpha(x) => DensityFunctions::BlendAlpha(x.clone()),
-            DensityFunctions::BlendDensity(x) => DensityFunctions::BlendDensity(x.clone()),
-            DensityFunctions::BlendOffset(x) => DensityFunctions::BlendOffset(x.clone()),
-            DensityFunctions::Cache2D(x) => DensityFunctions::Cache2D(x.clone()),
-            DensityFunctions::CacheAllInCell(x) => DensityFunctions::CacheAllInCell(x.clone()),
-            DensityFunctions::CacheOnce(x) => DensityFunctions::CacheOnce(x.clone()),
-            DensityFunctions::Clamp(x) => DensityFunctions::Clamp(x.clone()),
-            DensityFunctions::Const(x) => DensityFunctions::Const(x.clone()),
-            DensityFunctions::Spline(x) => DensityFunctions::Spline(x.clone()),
-            DensityFunctions::Cube(x) => DensityFunctions::Cube(x.clone()),
-            DensityFunctions::FlatCache(x) => DensityFunctions::FlatCache(x.clone()),
-            DensityFunctions::HalfNegative(x) => DensityFunctions::HalfNegative(x.clone()),
-            DensityFunctions::InterpolatedInner(x) => {
-                let borrow = x.borrow();
-
-                DensityFunctions::InterpolatedInner(
-                    Rc::new(
-                        RefCell::new(
-                            InterpolatedInner::new(
-                                borrow.argument.clone(),
-                                borrow.cell_count_y,
-                                borrow.cell_count_xz,
-                            )
-                        )
-                    )
-                )
-            }
-            DensityFunctions::Interpolated(x) => DensityFunctions::Interpolated(x.clone()),
-            DensityFunctions::Marker(x) => DensityFunctions::Marker(x.clone()),
-            DensityFunctions::Max(x) => DensityFunctions::Max(x.clone()),
-            DensityFunctions::Min(x) => DensityFunctions::Min(x.clone()),
-            DensityFunctions::MulConst(x) => DensityFunctions::MulConst(x.clone()),
-            DensityFunctions::Mul(x) => DensityFunctions::Mul(x.clone()),
-            DensityFunctions::Noise(x) => DensityFunctions::Noise(x.clone()),
-            DensityFunctions::OldBlendedNoise(x) => DensityFunctions::OldBlendedNoise(x.clone()),
-            DensityFunctions::QuarterNegative(x) => DensityFunctions::QuarterNegative(x.clone()),
-            DensityFunctions::RangeChoice(x) => DensityFunctions::RangeChoice(x.clone()),
-            DensityFunctions::ShiftA(x) => DensityFunctions::ShiftA(x.clone()),
-            DensityFunctions::ShiftB(x) => DensityFunctions::ShiftB(x.clone()),
-            DensityFunctions::ShiftedNoise(x) => DensityFunctions::ShiftedNoise(x.clone()),
-            DensityFunctions::Square(x) => DensityFunctions::Square(x.clone()),
-            DensityFunctions::Squeeze(x) => DensityFunctions::Squeeze(x.clone()),
-            DensityFunctions::WeirdScaledSampler(x) => DensityFunctions::WeirdScaledSampler(x.clone()),
-            DensityFunctions::YClampedGradient(x) => DensityFunctions::YClampedGradient(x.clone()),
-            DensityFunctions::Debug(x) => DensityFunctions::Debug(x.clone()),
-        }
-    }
-}
-
 impl Debug for DensityFunctions {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             DensityFunctions::Abs(x) => Debug::fmt(&x, f),
             DensityFunctions::Add(x) => Debug::fmt(&x, f),
             DensityFunctions::AddConst(x) => Debug::fmt(&x, f),
+            DensityFunctions::Beardifier(x) => Debug::fmt(&x, f),
             DensityFunctions::BlendAlpha(x) => Debug::fmt(&x, f),
             DensityFunctions::BlendDensity(x) => Debug::fmt(&x, f),
             DensityFunctions::BlendOffset(x) => Debug::fmt(&x, f),
@@ -388,9 +376,10 @@ impl Debug for DensityFunctions {
             DensityFunctions::Const(x) => Debug::fmt(&x, f),
             DensityFunctions::Spline(x) => Debug::fmt(&x, f),
             DensityFunctions::Cube(x) => Debug::fmt(&x, f),
+            DensityFunctions::EndIslands(x) => Debug::fmt(&x, f),
             DensityFunctions::FlatCache(x) => Debug::fmt(&x, f),
             DensityFunctions::HalfNegative(x) => Debug::fmt(&x, f),
-            DensityFunctions::InterpolatedInner(x) => Debug::fmt(&x.borrow(), f),
+            DensityFunctions::InterpolatedInner(x) => Debug::fmt(&x, f),
             DensityFunctions::Interpolated(x) => Debug::fmt(&x, f),
             DensityFunctions::Marker(x) => Debug::fmt(&x, f),
             DensityFunctions::Max(x) => Debug::fmt(&x, f),
@@ -419,19 +408,21 @@ impl DensityFunction for DensityFunctions {
             DensityFunctions::Abs(x) => x.sample(at, ctx),
             DensityFunctions::Add(x) => x.sample(at, ctx),
             DensityFunctions::AddConst(x) => x.sample(at, ctx),
+            DensityFunctions::Beardifier(x) => x.sample(at, ctx),
             DensityFunctions::BlendAlpha(x) => x.sample(at, ctx),
             DensityFunctions::BlendDensity(x) => x.sample(at, ctx),
             DensityFunctions::BlendOffset(x) => x.sample(at, ctx),
             DensityFunctions::Cache2D(x) => x.sample(at, ctx),
-            DensityFunctions::CacheAllInCell(x) => x.borrow().sample(at, ctx),
+            DensityFunctions::CacheAllInCell(x) => x.sample(at, ctx),
             DensityFunctions::CacheOnce(x) => x.sample(at, ctx),
             DensityFunctions::Clamp(x) => x.sample(at, ctx),
             DensityFunctions::Const(x) => x.sample(at, ctx),
             DensityFunctions::Spline(x) => x.sample(at, ctx),
             DensityFunctions::Cube(x) => x.sample(at, ctx),
+            DensityFunctions::EndIslands(x) => x.sample(at, ctx),
             DensityFunctions::FlatCache(x) => x.sample(at, ctx),
             DensityFunctions::HalfNegative(x) => x.sample(at, ctx),
-            DensityFunctions::InterpolatedInner(x) => x.borrow().sample(at, ctx),
+            DensityFunctions::InterpolatedInner(x) => x.sample(at, ctx),
             DensityFunctions::Interpolated(x) => x.sample(at, ctx),
             DensityFunctions::Marker(x) => x.sample(at, ctx),
             DensityFunctions::Max(x) => x.sample(at, ctx),
@@ -458,19 +449,21 @@ impl DensityFunction for DensityFunctions {
             DensityFunctions::Abs(x) => x.fill_array(arr, ctx),
             DensityFunctions::Add(x) => x.fill_array(arr, ctx),
             DensityFunctions::AddConst(x) => x.fill_array(arr, ctx),
+            DensityFunctions::Beardifier(x) => x.fill_array(arr, ctx),
             DensityFunctions::BlendAlpha(x) => x.fill_array(arr, ctx),
             DensityFunctions::BlendDensity(x) => x.fill_array(arr, ctx),
             DensityFunctions::BlendOffset(x) => x.fill_array(arr, ctx),
             DensityFunctions::Cache2D(x) => x.fill_array(arr, ctx),
-            DensityFunctions::CacheAllInCell(x) => x.borrow().fill_array(arr, ctx),
+            DensityFunctions::CacheAllInCell(x) => x.fill_array(arr, ctx),
             DensityFunctions::CacheOnce(x) => x.fill_array(arr, ctx),
             DensityFunctions::Clamp(x) => x.fill_array(arr, ctx),
             DensityFunctions::Const(x) => x.fill_array(arr, ctx),
             DensityFunctions::Spline(x) => x.fill_array(arr, ctx),
             DensityFunctions::Cube(x) => x.fill_array(arr, ctx),
+            DensityFunctions::EndIslands(x) => x.fill_array(arr, ctx),
             DensityFunctions::FlatCache(x) => x.fill_array(arr, ctx),
             DensityFunctions::HalfNegative(x) => x.fill_array(arr, ctx),
-            DensityFunctions::InterpolatedInner(x) => x.borrow().fill_array(arr, ctx),
+            DensityFunctions::InterpolatedInner(x) => x.fill_array(arr, ctx),
             DensityFunctions::Interpolated(x) => x.fill_array(arr, ctx),
             DensityFunctions::Marker(x) => x.fill_array(arr, ctx),
             DensityFunctions::Max(x) => x.fill_array(arr, ctx),
@@ -497,19 +490,21 @@ impl DensityFunction for DensityFunctions {
             DensityFunctions::Abs(x) => x.min_value(),
             DensityFunctions::Add(x) => x.min_value(),
             DensityFunctions::AddConst(x) => x.min_value(),
+            DensityFunctions::Beardifier(x) => x.min_value(),
             DensityFunctions::BlendAlpha(x) => x.min_value(),
             DensityFunctions::BlendDensity(x) => x.min_value(),
             DensityFunctions::BlendOffset(x) => x.min_value(),
             DensityFunctions::Cache2D(x) => x.min_value(),
-            DensityFunctions::CacheAllInCell(x) => x.borrow().min_value(),
+            DensityFunctions::CacheAllInCell(x) => x.min_value(),
             DensityFunctions::CacheOnce(x) => x.min_value(),
             DensityFunctions::Clamp(x) => x.min_value(),
             DensityFunctions::Const(x) => x.min_value(),
             DensityFunctions::Spline(x) => x.min_value(),
             DensityFunctions::Cube(x) => x.min_value(),
+            DensityFunctions::EndIslands(x) => x.min_value(),
             DensityFunctions::FlatCache(x) => x.min_value(),
             DensityFunctions::HalfNegative(x) => x.min_value(),
-            DensityFunctions::InterpolatedInner(x) => x.borrow().min_value(),
+            DensityFunctions::InterpolatedInner(x) => x.min_value(),
             DensityFunctions::Interpolated(x) => x.min_value(),
             DensityFunctions::Marker(x) => x.min_value(),
             DensityFunctions::Max(x) => x.min_value(),
@@ -536,19 +531,21 @@ impl DensityFunction for DensityFunctions {
             DensityFunctions::Abs(x) => x.max_value(),
             DensityFunctions::Add(x) => x.max_value(),
             DensityFunctions::AddConst(x) => x.max_value(),
+            DensityFunctions::Beardifier(x) => x.max_value(),
             DensityFunctions::BlendAlpha(x) => x.max_value(),
             DensityFunctions::BlendDensity(x) => x.max_value(),
             DensityFunctions::BlendOffset(x) => x.max_value(),
             DensityFunctions::Cache2D(x) => x.max_value(),
-            DensityFunctions::CacheAllInCell(x) => x.borrow().max_value(),
+            DensityFunctions::CacheAllInCell(x) => x.max_value(),
             DensityFunctions::CacheOnce(x) => x.max_value(),
             DensityFunctions::Clamp(x) => x.max_value(),
             DensityFunctions::Const(x) => x.max_value(),
             DensityFunctions::Spline(x) => x.max_value(),
             DensityFunctions::Cube(x) => x.max_value(),
+            DensityFunctions::EndIslands(x) => x.max_value(),
             DensityFunctions::FlatCache(x) => x.max_value(),
             DensityFunctions::HalfNegative(x) => x.max_value(),
-            DensityFunctions::InterpolatedInner(x) => x.borrow().max_value(),
+            DensityFunctions::InterpolatedInner(x) => x.max_value(),
             DensityFunctions::Interpolated(x) => x.max_value(),
             DensityFunctions::Marker(x) => x.max_value(),
             DensityFunctions::Max(x) => x.max_value(),
@@ -575,49 +572,21 @@ impl DensityFunction for DensityFunctions {
             DensityFunctions::Abs(x) => x.map(mapper),
             DensityFunctions::Add(x) => x.map(mapper),
             DensityFunctions::AddConst(x) => x.map(mapper),
+            DensityFunctions::Beardifier(x) => x.map(mapper),
             DensityFunctions::BlendAlpha(x) => x.map(mapper),
             DensityFunctions::BlendDensity(x) => x.map(mapper),
             DensityFunctions::BlendOffset(x) => x.map(mapper),
             DensityFunctions::Cache2D(x) => x.map(mapper),
-            DensityFunctions::CacheAllInCell(x) => {
-                let strong_counter = Rc::strong_count(&x);
-                let inner = Rc::into_inner(x);
-                if inner.is_none() {
-                    panic!(
-                        concat!(
-                        "Rc has {} strong reference(s). To map CacheAllInCell Function ",
-                        "it is required for strong counter of Rc to be 1. Do not clone ",
-                        "CacheAllInCell Density Function reference without urgent need."
-                        ),
-                        strong_counter
-                    )
-                }
-
-                inner.unwrap().into_inner().map(mapper)
-            }
+            DensityFunctions::CacheAllInCell(x) => x.map(mapper),
             DensityFunctions::CacheOnce(x) => x.map(mapper),
             DensityFunctions::Clamp(x) => x.map(mapper),
             DensityFunctions::Const(x) => x.map(mapper),
             DensityFunctions::Spline(x) => x.map(mapper),
             DensityFunctions::Cube(x) => x.map(mapper),
+            DensityFunctions::EndIslands(x) => x.map(mapper),
             DensityFunctions::FlatCache(x) => x.map(mapper),
             DensityFunctions::HalfNegative(x) => x.map(mapper),
-            DensityFunctions::InterpolatedInner(x) => {
-                let strong_counter = Rc::strong_count(&x);
-                let inner = Rc::into_inner(x);
-                if inner.is_none() {
-                    panic!(
-                        concat!(
-                        "Rc has {} strong reference(s). To map Interpolated Density Function ",
-                        "it is required for strong counter of Rc to be 1. Do not clone ",
-                        "Interpolated Density Function reference without urgent need."
-                        ),
-                        strong_counter
-                    )
-                }
-
-                inner.unwrap().into_inner().map(mapper)
-            }
+            DensityFunctions::InterpolatedInner(x) => x.map(mapper),
             DensityFunctions::Interpolated(x) => x.map(mapper),
             DensityFunctions::Marker(x) => x.map(mapper),
             DensityFunctions::Max(x) => x.map(mapper),
@@ -704,6 +673,15 @@ pub struct DensityFunctionContext {
     pub last_blending_data_at: i64,
     pub last_blending_output: BlendingOutput,
     pub debug_tree: Option<DebugTree<f64>>,
+    /// States of the interpolators of the noise chunk, in the order
+    /// [`InterpolatedCollector`] numbers them.
+    pub interpolators: Vec<InterpolatorState>,
+    /// Values of the cell caches of the noise chunk, in the order of
+    /// [`Self::for_index`].
+    pub cell_caches: Vec<Vec<f64>>,
+    /// Last values of the `cache_once` functions, by their ids. Functions are
+    /// shared between threads, so each context caches for itself.
+    cache_once: Vec<CacheOnceState>,
 }
 
 impl Default for DensityFunctionContext {
@@ -735,6 +713,9 @@ impl Default for DensityFunctionContext {
             last_blending_data_at: 0,
             last_blending_output: BlendingOutput { alpha: 0.0, offset: 0.0 },
             debug_tree: None,
+            interpolators: Vec::new(),
+            cell_caches: Vec::new(),
+            cache_once: Vec::new(),
         }
     }
 }
@@ -795,6 +776,16 @@ impl DensityFunctionContext {
         }
     }
 
+    /// State of the `cache_once` function with the id.
+    #[inline]
+    pub(crate) fn cache_once(&mut self, id: usize) -> &mut CacheOnceState {
+        if id >= self.cache_once.len() {
+            self.cache_once.resize_with(id + 1, Default::default);
+        }
+
+        &mut self.cache_once[id]
+    }
+
     pub fn get_or_compute_blending_output(&mut self, x: i32, z: i32) -> BlendingOutput {
         let i: i64 = ChunkPos::new(x, z).into();
 
@@ -816,6 +807,44 @@ impl DensityFunctionContext {
         )
     }
 
+    /// Positions of the array, in the order [`Self::fill_all_directly`]
+    /// samples them. The context is left at the last one, as if it filled
+    /// the array.
+    pub fn collect_positions(&mut self) -> Vec<Vector3> {
+        let mut positions = Vec::new();
+
+        match self.filler {
+            ContextFiller::Default => {
+                self.array_index = 0;
+
+                for i in (0..=self.cell_height - 1).rev() {
+                    self.in_cell_y = i;
+
+                    for j in 0..self.cell_width {
+                        self.in_cell_x = j;
+
+                        for k in 0..self.cell_width {
+                            self.in_cell_z = k;
+                            self.array_index += 1;
+                            positions.push(self.pos());
+                        }
+                    }
+                }
+            }
+            ContextFiller::Slice => {
+                for i in 0..=self.cell_count_y as usize {
+                    self.cell_start_block_y = (i as i32 + self.cell_noise_min_y) * self.cell_height as i32;
+                    self.interpolation_counter += 1;
+                    self.in_cell_y = 0;
+                    self.array_index = i;
+                    positions.push(self.pos());
+                }
+            }
+        }
+
+        positions
+    }
+
     pub fn fill_all_directly<F: DensityFunction>(&mut self, arr: &mut [f64], f: &F) {
         fill_all_directly!(self, f, arr);
     }
@@ -827,14 +856,15 @@ pub enum ContextFiller {
     Slice,
 }
 
-pub(crate) use fill_all_directly;
+use crate::noise::density::beardifier::Beardifier;
 use crate::noise::density::binary::{Add, AddConst, Max, Min, Mul, MulConst};
 use crate::noise::density::blend::{BlendAlpha, BlendOffset};
 use crate::noise::density::debug::DebugDensityFunction;
-use crate::noise::density::maker::{Interpolated, InterpolatedInner, Marker};
-use crate::noise::density::misc::{Clamp, Const, RangeChoice, WeirdScaledSampler, YClampedGradient};
+use crate::noise::density::maker::{Interpolated, InterpolatedInner, InterpolatorState, Marker};
+use crate::noise::density::misc::{Clamp, Const, EndIslands, RangeChoice, WeirdScaledSampler, YClampedGradient};
 use crate::noise::density::noise::{BlendDensity, NoiseDensityFunction, NoiseHolder, OldBlendedNoise, ShiftA, ShiftB, ShiftedNoise};
 use crate::noise::density::spline::Spline;
 use crate::noise::density::unary::{Abs, Cube, HalfNegative, QuarterNegative, Square, Squeeze};
 use crate::noise::perlin::DefaultNoise;
-use crate::rng::RngPos;
+use crate::noise::perlin::octave::{MultiOctaveNoiseFactory, MultiOctaveNoiseParameters};
+use crate::rng::{LcgEntropySrc, LegacyRng, RngPos, U32EntropySrc};
@@ -1,18 +1,112 @@
-use crate::noise::density::density::{fill_all_directly, ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions, Mapper};
+use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions, Mapper};
 use crate::noise::math::{lerp, lerp3};
 use spherix_math::vector::Vector3;
-use std::cell::RefCell;
 use std::fmt::{Debug, Formatter};
 use std::mem;
-use std::rc::Rc;
 
+/// Marker `minecraft:interpolated` as the noise chunk sees it. The function
+/// only defines the interpolator, its slices and the noises of the selected
+/// cell are the [`InterpolatorState`] at `index` in the context.
 #[derive(Clone)]
 pub struct InterpolatedInner {
     pub argument: DensityFunctions,
     pub cell_count_y: usize,
     pub cell_count_xz: usize,
+    /// Index of the state in [`DensityFunctionContext::interpolators`],
+    /// assigned by [`crate::noise::density::density::InterpolatedCollector`].
+    pub index: usize,
+}
+
+impl InterpolatedInner {
+    pub fn new(argument: DensityFunctions, cell_count_y: usize, cell_count_xz: usize) -> Self {
+        Self {
+            argument,
+            cell_count_y,
+            cell_count_xz,
+            index: 0,
+        }
+    }
+
+    /// Empty state of the interpolator for a noise chunk.
+    pub fn state(&self) -> InterpolatorState {
+        InterpolatorState {
+            slice0: InterpolatorState::allocate_slice(self.cell_count_y, self.cell_count_xz),
+            slice1: InterpolatorState::allocate_slice(self.cell_count_y, self.cell_count_xz),
+            ..Default::default()
+        }
+    }
+
+    /// Fills a column of a slice with the argument.
+    pub fn fill_slice(&self, slice: &mut [f64], ctx: &mut DensityFunctionContext) {
+        if ctx.filling_cell {
+            ctx.fill_all_directly(slice, self);
+        } else {
+            self.argument.fill_array(slice, ctx);
+        }
+    }
+}
+
+impl Debug for InterpolatedInner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "InterpolatedInner (index: {})", self.index)
+    }
+}
+
+impl DensityFunction for InterpolatedInner {
+    fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
+        if ctx.filler != ContextFiller::Default {
+            return self.argument.sample(at, ctx)
+        }
+
+        let Some(state) = ctx.interpolators.get(self.index) else {
+            return 0.0
+        };
+
+        if ctx.filling_cell {
+            state.interpolate_in_cell(ctx.in_cell_x, ctx.in_cell_y, ctx.in_cell_z, ctx.cell_width, ctx.cell_height)
+        } else {
+            state.value
+        }
+    }
+
+    fn fill_array(&self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
+        if ctx.filling_cell {
+            ctx.fill_all_directly(arr, self);
+        } else {
+            self.argument.fill_array(arr, ctx);
+        }
+    }
+
+    fn min_value(&self) -> f64 {
+        self.argument.min_value()
+    }
+
+    fn max_value(&self) -> f64 {
+        self.argument.max_value()
+    }
+
+    fn map<M: Mapper>(self, mapper: &M) -> DensityFunctions {
+        mapper.map(
+            DensityFunctions::InterpolatedInner(
+                Box::new(
+                    InterpolatedInner {
+                        argument: self.argument.map(mapper),
+                        cell_count_y: self.cell_count_y,
+                        cell_count_xz: self.cell_count_xz,
+                        index: self.index,
+                    }
+                )
+            )
+        )
+    }
+}
+
+/// Slices of an interpolator, which the noise chunk fills along the x axis,
+/// and the noises of the selected cell interpolated at the current block.
+#[derive(Clone, Default)]
+pub struct InterpolatorState {
     pub slice0: Vec<Vec<f64>>,
-    slice1: Vec<Vec<f64>>,
+    pub slice1: Vec<Vec<f64>>,
     noise000: f64,
     noise001: f64,
     noise100: f64,
@@ -30,32 +124,7 @@ pub struct InterpolatedInner {
     value: f64,
 }
 
-impl InterpolatedInner {
-    pub fn new(argument: DensityFunctions, cell_count_y: usize, cell_count_xz: usize) -> Self {
-        Self {
-            argument,
-            cell_count_y,
-            cell_count_xz,
-            slice0: Self::allocate_slice(cell_count_y, cell_count_xz),
-            slice1: Self::allocate_slice(cell_count_y, cell_count_xz),
-            noise000: 0.0,
-            noise001: 0.0,
-            noise100: 0.0,
-            noise101: 0.0,
-            noise010: 0.0,
-            noise011: 0.0,
-            noise110: 0.0,
-            noise111: 0.0,
-            value_xz00: 0.0,
-            value_xz10: 0.0,
-            value_xz01: 0.0,
-            value_xz11: 0.0,
-            value_z0: 0.0,
-            value_z1: 0.0,
-            value: 0.0,
-        }
-    }
-
+impl InterpolatorState {
     fn allocate_slice(cell_count_y: usize, cell_count_xz: usize) -> Vec<Vec<f64>> {
         let i = cell_count_xz + 1;
         let j = cell_count_y + 1;
@@ -89,32 +158,19 @@ impl InterpolatedInner {
         self.value = lerp(z, self.value_z0, self.value_z1);
     }
 
-    pub fn slice0_mut(&mut self) -> &mut Vec<Vec<f64>> {
-        &mut self.slice0
-    }
-
-    pub fn slice1_mut(&mut self) -> &mut Vec<Vec<f64>> {
-        &mut self.slice1
-    }
-
-    pub fn fill_slice0(&mut self, index: usize, ctx: &mut DensityFunctionContext) {
-        if ctx.filling_cell {
-            // This macro helps to bypass borrow checker restrictions on having
-            // shared and mutable borrows simultaneously
-            fill_all_directly!(ctx, self, self.slice0[index]);
-        } else {
-            self.argument.fill_array(&mut self.slice0[index], ctx);
-        }
-    }
-
-    pub fn fill_slice1(&mut self, index: usize, ctx: &mut DensityFunctionContext) {
-        if ctx.filling_cell {
-            // This macro helps to bypass borrow checker restrictions on having
-            // shared and mutable borrows simultaneously
-            fill_all_directly!(ctx, self, self.slice1[index]);
-        } else {
-            self.argument.fill_array(&mut self.slice1[index], ctx);
-        }
+    /// Interpolates between the noises of the selected cell at the position
+    /// within the cell.
+    #[inline]
+    pub fn interpolate_in_cell(&self, in_cell_x: u32, in_cell_y: u32, in_cell_z: u32, cell_width: u32, cell_height: u32) -> f64 {
+        lerp3(
+            in_cell_x as f64 / cell_width as f64,
+            in_cell_y as f64 / cell_height as f64,
+            in_cell_z as f64 / cell_width as f64,
+            self.noise000, self.noise100,
+            self.noise010, self.noise110,
+            self.noise001, self.noise101,
+            self.noise011, self.noise111,
+        )
     }
 
     pub fn swap_slices(&mut self) {
@@ -122,108 +178,15 @@ impl InterpolatedInner {
     }
 }
 
-impl Debug for InterpolatedInner {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            concat!(
-            "InterpolatedInner (noise000: {}, noise001: {}, noise100: {}, noise101: {}, ",
-            "noise010: {}, noise011: {}, noise110: {}, noise111: {})"
-            ),
-            self.noise000,
-            self.noise001,
-            self.noise100,
-            self.noise101,
-            self.noise010,
-            self.noise011,
-            self.noise110,
-            self.noise111
-        )
-    }
-}
-
-impl DensityFunction for InterpolatedInner {
-    fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
-        if ctx.filler != ContextFiller::Default {
-            self.argument.sample(at, ctx)
-        } else {
-            if ctx.filling_cell {
-                lerp3(
-                    ctx.in_cell_x as f64 / ctx.cell_width as f64,
-                    ctx.in_cell_y as f64 / ctx.cell_height as f64,
-                    ctx.in_cell_z as f64 / ctx.cell_width as f64,
-                    self.noise000, self.noise100,
-                    self.noise010, self.noise110,
-                    self.noise001, self.noise101,
-                    self.noise011, self.noise111,
-                )
-            } else {
-                self.value
-            }
-        }
-    }
-
-    fn fill_array(&self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
-        if ctx.filling_cell {
-            ctx.fill_all_directly(arr, self);
-        } else {
-            self.argument.fill_array(arr, ctx);
-        }
-    }
-
-    fn min_value(&self) -> f64 {
-        self.argument.min_value()
-    }
-
-    fn max_value(&self) -> f64 {
-        self.argument.max_value()
-    }
-
-    fn map<M: Mapper>(self, mapper: &M) -> DensityFunctions {
-        mapper.map(
-            DensityFunctions::InterpolatedInner(
-                Rc::new(
-                    RefCell::new(
-                        InterpolatedInner {
-                            argument: self.argument.map(mapper),
-                            cell_count_y: self.cell_count_y,
-                            cell_count_xz: self.cell_count_xz,
-                            slice0: self.slice0,
-                            slice1: self.slice1,
-                            noise000: self.noise000,
-                            noise001: self.noise001,
-                            noise100: self.noise100,
-                            noise101: self.noise101,
-                            noise010: self.noise010,
-                            noise011: self.noise011,
-                            noise110: self.noise110,
-                            noise111: self.noise111,
-                            value_xz00: self.value_xz00,
-                            value_xz10: self.value_xz10,
-                            value_xz01: self.value_xz01,
-                            value_xz11: self.value_xz11,
-                            value_z0: self.value_z0,
-                            value_z1: self.value_z1,
-                            value: self.value,
-                        }
-                    )
-                )
-            )
-        )
-    }
-}
-
 #[derive(Clone)]
-pub struct Interpolated(DensityFunctions);
+pub struct Interpolated(pub(crate) DensityFunctions);
 
 impl Interpolated {
     pub fn new(argument: DensityFunctions, cell_count_y: usize, cell_count_xz: usize) -> Self {
         Self(
             DensityFunctions::InterpolatedInner(
-                Rc::new(
-                    RefCell::new(
-                        InterpolatedInner::new(argument, cell_count_y, cell_count_xz)
-                    )
+                Box::new(
+                    InterpolatedInner::new(argument, cell_count_y, cell_count_xz)
                 )
             )
         )
@@ -259,7 +222,7 @@ impl DensityFunction for Interpolated {
 #[derive(Clone)]
 pub struct Marker {
     ty: String,
-    inner: DensityFunctions
+    pub(crate) inner: DensityFunctions
 }
 
 impl Marker {
@@ -2,14 +2,16 @@ use crate::noise::density::density::{DensityFunction, DensityFunctionContext, De
 use crate::noise::density::noise::NoiseHolder;
 use crate::noise::math::clamped_map;
 use crate::noise::perlin::noise::{LegacyNoise, SupremumNoise};
+use crate::noise::perlin::simplex::noise::SimplexNoise;
 use crate::noise::perlin::DefaultNoise;
-use spherix_math::vector::Vector3;
+use crate::rng::{LcgEntropySrc, LegacyRng, Rng, U32EntropySrc};
+use spherix_math::vector::{Vector2f, Vector3};
 use std::fmt::{Debug, Display, Formatter};
-use std::rc::Rc;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct Const {
-    val: f64,
+    pub(crate) val: f64,
 }
 
 impl Const {
@@ -96,13 +98,100 @@ impl DensityFunction for YClampedGradient {
     }
 }
 
+/// Falloff of the End islands: the main island around the origin and the
+/// outer islands placed by the simplex noise. The noise depends on the seed
+/// only, so the function from json is seeded with 0 until
+/// `SetupEndIslandsMapper` replaces it.
+#[derive(Clone)]
+pub struct EndIslands {
+    noise: Arc<SimplexNoise>,
+}
+
+impl EndIslands {
+    pub fn new(seed: i64) -> Self {
+        let mut rng = LegacyRng::new(LcgEntropySrc::new(seed as u64));
+        rng.skip(17292);
+
+        Self {
+            noise: Arc::new(SimplexNoise::new(&mut rng)),
+        }
+    }
+
+    /// Equivalent of vanilla `EndIslandDensityFunction::getHeightValue()`.
+    /// Coordinates are in blocks divided by 8.
+    fn height_value(&self, x: i32, z: i32) -> f32 {
+        let i = x / 2;
+        let j = z / 2;
+        let k = x % 2;
+        let l = z % 2;
+
+        let mut height = (100.0 - (x.wrapping_mul(x).wrapping_add(z.wrapping_mul(z)) as f32).sqrt() * 8.0)
+            .clamp(-100.0, 80.0);
+
+        for dx in -12..=12 {
+            for dz in -12..=12 {
+                let island_x = (i + dx) as i64;
+                let island_z = (j + dz) as i64;
+
+                if island_x * island_x + island_z * island_z <= 4096 {
+                    continue
+                }
+
+                // The trait is not imported, since NoiseHolder implements both
+                // Noise and LegacyNoise.
+                let noise = crate::noise::perlin::Noise::sample(
+                    self.noise.as_ref(),
+                    Vector2f::new(island_x as f64, island_z as f64)
+                );
+                if noise >= -0.9f32 as f64 {
+                    continue
+                }
+
+                let falloff = ((island_x as f32).abs() * 3439.0 + (island_z as f32).abs() * 147.0) % 13.0 + 9.0;
+                let offset_x = (k - dx * 2) as f32;
+                let offset_z = (l - dz * 2) as f32;
+                let island_height = (100.0 - (offset_x * offset_x + offset_z * offset_z).sqrt() * falloff)
+                    .clamp(-100.0, 80.0);
+
+                height = height.max(island_height);
+            }
+        }
+
+        height
+    }
+}
+
+impl Debug for EndIslands {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EndIslands")
+    }
+}
+
+impl DensityFunction for EndIslands {
+    fn sample(&self, at: Vector3, _: &mut DensityFunctionContext) -> f64 {
+        (self.height_value(at.x / 8, at.z / 8) as f64 - 8.0) / 128.0
+    }
+
+    fn min_value(&self) -> f64 {
+        -0.84375
+    }
+
+    fn max_value(&self) -> f64 {
+        0.5625
+    }
+
+    fn map<M: Mapper>(self, mapper: &M) -> DensityFunctions {
+        mapper.map(DensityFunctions::EndIslands(self))
+    }
+}
+
 #[derive(Clone)]
 pub struct RangeChoice {
-    input: DensityFunctions,
-    min_inclusive: f64,
-    max_exclusive: f64,
-    when_in_range: DensityFunctions,
-    when_out_of_range: DensityFunctions,
+    pub(crate) input: DensityFunctions,
+    pub(crate) min_inclusive: f64,
+    pub(crate) max_exclusive: f64,
+    pub(crate) when_in_range: DensityFunctions,
+    pub(crate) when_out_of_range: DensityFunctions,
 }
 
 impl RangeChoice {
@@ -181,9 +270,9 @@ impl DensityFunction for RangeChoice {
 
 #[derive(Clone)]
 pub struct Clamp {
-    input: DensityFunctions,
-    min: f64,
-    max: f64,
+    pub(crate) input: DensityFunctions,
+    pub(crate) min: f64,
+    pub(crate) max: f64,
 }
 
 impl Clamp {
@@ -226,30 +315,34 @@ impl DensityFunction for Clamp {
     }
 }
 
+#[derive(Clone)]
 pub struct WeirdScaledSampler {
     pub input: DensityFunctions,
-    pub noise: Rc<NoiseHolder<DefaultNoise>>,
+    pub noise: Arc<NoiseHolder<DefaultNoise>>,
     pub rarity_value: RarityValue,
 }
 
-impl Clone for WeirdScaledSampler {
-    fn clone(&self) -> Self {
-        Self {
-            input: self.input.clone(),
-            noise: Rc::new(self.noise.as_ref().clone()),
-            rarity_value: self.rarity_value.clone(),
-        }
-    }
-}
-
 impl WeirdScaledSampler {
-    pub fn new(input: DensityFunctions, noise: Rc<NoiseHolder<DefaultNoise>>, rarity_value: RarityValue) -> Self {
+    pub fn new(input: DensityFunctions, noise: Arc<NoiseHolder<DefaultNoise>>, rarity_value: RarityValue) -> Self {
         Self {
             input,
             noise,
             rarity_value,
         }
     }
+
+    /// Samples the noise scaled by the rarity of the sampled input.
+    #[inline]
+    pub(crate) fn sample_with_input(
+        noise: &NoiseHolder<DefaultNoise>,
+        rarity_value: &RarityValue,
+        at: Vector3,
+        input: f64,
+    ) -> f64 {
+        let d0 = rarity_value.mapper()(input);
+        let sampled = noise.sample(at / d0, 0.0, 0.0).abs();
+        d0 * sampled
+    }
 }
 
 impl Debug for WeirdScaledSampler {
@@ -262,9 +355,7 @@ impl DensityFunction for WeirdScaledSampler {
     fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
         let p_208441_ = self.input.sample(at, ctx);
 
-        let d0 = self.rarity_value.mapper()(p_208441_);
-        let sampled = self.noise.sample(at / d0, 0.0, 0.0).abs();
-        d0 * sampled
+        Self::sample_with_input(&self.noise, &self.rarity_value, at, p_208441_)
     }
 
     fn min_value(&self) -> f64 {
@@ -344,3 +435,22 @@ impl RarityValue {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::density::density::{DensityFunction, DensityFunctionContext};
+    use crate::noise::density::misc::EndIslands;
+    use spherix_math::vector::Vector3;
+
+    #[test]
+    fn end_islands_main_island() {
+        let end_islands = EndIslands::new(12345);
+        let mut ctx = DensityFunctionContext::default();
+
+        assert_eq!(0.5625, end_islands.sample(Vector3::new(0, 64, 0), &mut ctx));
+        assert_eq!(0.5625, end_islands.sample(Vector3::new(-7, 0, 7), &mut ctx));
+        assert_eq!(0.09375, end_islands.sample(Vector3::new(80, 64, 0), &mut ctx));
+        // Beyond the main island, but too close to the origin for the outer ones.
+        assert_eq!(-0.84375, end_islands.sample(Vector3::new(800, 64, 0), &mut ctx));
+    }
+}
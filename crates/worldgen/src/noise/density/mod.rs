@@ -8,3 +8,5 @@ pub mod noise;
 pub mod cache;
 pub mod blend;
 pub mod debug;
+pub mod beardifier;
+pub mod tape;
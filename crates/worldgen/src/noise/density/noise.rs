@@ -1,5 +1,6 @@
 use crate::noise::density::density::{DensityFunction, DensityFunctionContext, DensityFunctions, Mapper};
 use crate::noise::math::clamped_lerp;
+use crate::noise::perlin::batch::{BatchNoise, Lanes, LANES};
 use crate::noise::perlin::noise::{LegacyNoise, SupremumNoise};
 use crate::noise::perlin::octave::{wrap, MultiOctaveNoiseFactory, MultiOctaveNoiseParameters};
 use crate::noise::perlin::DefaultNoise;
@@ -8,27 +9,18 @@ use crate::rng::RngForkable;
 use spherix_math::vector::{Vector3, Vector3f};
 use std::fmt::{Debug, Formatter};
 use std::hint::black_box;
-use std::rc::Rc;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct NoiseDensityFunction {
-    noise: Rc<NoiseHolder<DefaultNoise>>,
+    pub(crate) noise: Arc<NoiseHolder<DefaultNoise>>,
     xz_scale: f32,
     y_scale: f32,
 }
 
-impl Clone for NoiseDensityFunction {
-    fn clone(&self) -> Self {
-        Self {
-            noise: Rc::new(self.noise.as_ref().clone()),
-            xz_scale: self.xz_scale,
-            y_scale: self.y_scale,
-        }
-    }
-}
-
 impl NoiseDensityFunction {
     pub fn new(
-        noise: Rc<NoiseHolder<DefaultNoise>>,
+        noise: Arc<NoiseHolder<DefaultNoise>>,
         xz_scale: f32,
         y_scale: f32,
     ) -> Self {
@@ -53,6 +45,37 @@ impl NoiseDensityFunction {
     pub fn y_scale(&self) -> f32 {
         self.y_scale
     }
+
+    #[inline]
+    pub(crate) fn scale(at: Vector3, xz_scale: f64, y_scale: f64) -> (f64, f64, f64) {
+        (at.x as f64 * xz_scale, at.y as f64 * y_scale, at.z as f64 * xz_scale)
+    }
+
+    /// Samples the noise at the scaled position.
+    #[inline]
+    pub(crate) fn sample_scaled(noise: &NoiseHolder<DefaultNoise>, at: Vector3, xz_scale: f64, y_scale: f64) -> f64 {
+        let (x, y, z) = Self::scale(at, xz_scale, y_scale);
+
+        noise.sample(Vector3f::new(x, y, z), 0.0, 0.0)
+    }
+
+    /// Samples the noise at the scaled positions, a batch of lanes at a time.
+    pub(crate) fn sample_scaled_batch(
+        noise: &NoiseHolder<DefaultNoise>,
+        at: &[Vector3],
+        xz_scale: f64,
+        y_scale: f64,
+        out: &mut [f64],
+    ) {
+        let mut values = [0.0; LANES];
+
+        for (at, out) in at.chunks(LANES).zip(out.chunks_mut(LANES)) {
+            let lanes = Lanes::from_fn(at.len(), |i| Self::scale(at[i], xz_scale, y_scale));
+
+            noise.sample_lanes(&lanes, 0.0, 0.0, &mut values);
+            out.copy_from_slice(&values[..out.len()]);
+        }
+    }
 }
 
 impl Debug for NoiseDensityFunction {
@@ -63,15 +86,13 @@ impl Debug for NoiseDensityFunction {
 
 impl DensityFunction for NoiseDensityFunction {
     fn sample(&self, at: Vector3, _: &mut DensityFunctionContext) -> f64 {
-        self.noise.sample(
-            Vector3f::new(
-                at.x as f64 * self.xz_scale as f64,
-                at.y as f64 * self.y_scale as f64,
-                at.z as f64 * self.xz_scale as f64,
-            ),
-            0.0,
-            0.0
-        )
+        Self::sample_scaled(&self.noise, at, self.xz_scale as f64, self.y_scale as f64)
+    }
+
+    fn fill_array(&self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
+        let positions = ctx.collect_positions();
+
+        Self::sample_scaled_batch(&self.noise, &positions, self.xz_scale as f64, self.y_scale as f64, arr);
     }
 
     #[inline]
@@ -158,6 +179,80 @@ impl OldBlendedNoise {
     pub fn with_new_rng<R: RngForkable>(&self, rng: &mut R) -> Self {
         Self::create(rng, self.xz_scale, self.y_scale, self.xz_factor, self.y_factor, self.smear_scale_multiplier)
     }
+
+    /// Batched [`DensityFunction::sample`]. Octaves of the limit noises are
+    /// only sampled, if any of the lanes needs them.
+    fn sample_lanes(&self, at: &[Vector3]) -> [f64; LANES] {
+        let len = at.len();
+
+        let d0 = std::array::from_fn::<_, LANES, _>(|i| at.get(i).map_or(0.0, |at| at.x as f64 * self.xz_multiplier));
+        let d1 = std::array::from_fn::<_, LANES, _>(|i| at.get(i).map_or(0.0, |at| at.y as f64 * self.y_multiplier));
+        let d2 = std::array::from_fn::<_, LANES, _>(|i| at.get(i).map_or(0.0, |at| at.z as f64 * self.xz_multiplier));
+
+        let v = Lanes::from_fn(len, |i| (d0[i] / self.xz_factor, d1[i] / self.y_factor, d2[i] / self.xz_factor));
+
+        let d6 = self.y_multiplier * self.smear_scale_multiplier;
+        let d7 = d6 / self.y_factor;
+        let mut d8 = [0.0; LANES];
+        let mut d9 = [0.0; LANES];
+        let mut d10 = [0.0; LANES];
+        let mut d11 = 1.0;
+        let mut values = [0.0; LANES];
+
+        for i in 0..8 {
+            if let Some(oct) = self.main_noise.octave(i) {
+                let y_min = std::array::from_fn(|lane| v.y[lane] * d11);
+                oct.inner().sample_slices(&v.scaled(d11), d7 * d11, &y_min, &mut values);
+
+                for lane in 0..LANES {
+                    d10[lane] += values[lane] / d11;
+                }
+            }
+
+            d11 /= 2.0;
+        }
+
+        let d16 = d10.map(|d10| (d10 / 10.0 + 1.0) / 2.0);
+        let flag1 = d16.map(|d16| d16 >= 1.0);
+        let flag2 = d16.map(|d16| d16 <= 0.0);
+        let any_min = flag1[..len].contains(&false);
+        let any_max = flag2[..len].contains(&false);
+        d11 = 1.0;
+
+        for j in 0..16 {
+            let pos = Lanes::from_fn(len, |i| (wrap(d0[i] * d11), wrap(d1[i] * d11), wrap(d2[i] * d11)));
+            let y_min = std::array::from_fn(|lane| d1[lane] * d11);
+            let d15 = d6 * d11;
+
+            if any_min {
+                if let Some(oct1) = self.min_limit_noise.octave(j) {
+                    oct1.inner().sample_slices(&pos, d15, &y_min, &mut values);
+
+                    for lane in 0..LANES {
+                        if !flag1[lane] {
+                            d8[lane] += values[lane] / d11;
+                        }
+                    }
+                }
+            }
+
+            if any_max {
+                if let Some(oct2) = self.max_limit_noise.octave(j) {
+                    oct2.inner().sample_slices(&pos, d15, &y_min, &mut values);
+
+                    for lane in 0..LANES {
+                        if !flag2[lane] {
+                            d9[lane] += values[lane] / d11;
+                        }
+                    }
+                }
+            }
+
+            d11 /= 2.0;
+        }
+
+        std::array::from_fn(|lane| clamped_lerp(d8[lane] / 512.0, d9[lane] / 512.0, d16[lane]) / 128.0)
+    }
 }
 
 impl Debug for OldBlendedNoise {
@@ -236,6 +331,14 @@ impl DensityFunction for OldBlendedNoise {
         clamped_lerp(d8 / 512.0, d9 / 512.0, d16) / 128.0
     }
 
+    fn fill_array(&self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
+        let positions = ctx.collect_positions();
+
+        for (at, out) in positions.chunks(LANES).zip(arr.chunks_mut(LANES)) {
+            out.copy_from_slice(&self.sample_lanes(at)[..out.len()]);
+        }
+    }
+
     #[inline]
     fn min_value(&self) -> f64 {
         -self.max_value()
@@ -253,7 +356,7 @@ impl DensityFunction for OldBlendedNoise {
 
 #[derive(Clone)]
 pub struct BlendDensity {
-    input: DensityFunctions
+    pub(crate) input: DensityFunctions
 }
 
 impl BlendDensity {
@@ -370,6 +473,18 @@ where
     }
 }
 
+impl<N> BatchNoise for NoiseHolder<N>
+where
+    N: BatchNoise + SupremumNoise + MultiOctaveNoiseFactory + Clone
+{
+    fn sample_lanes(&self, at: &Lanes, y_amp: f64, y_min: f64, out: &mut [f64; LANES]) {
+        match &self.noise {
+            Some(noise) => noise.sample_lanes(at, y_amp, y_min, out),
+            None => *out = [0.0; LANES],
+        }
+    }
+}
+
 impl<N: SupremumNoise + MultiOctaveNoiseFactory + Clone> SupremumNoise for NoiseHolder<N> {
     fn max_value(&self) -> f64 {
         if self.noise.is_some() {
@@ -380,31 +495,19 @@ impl<N: SupremumNoise + MultiOctaveNoiseFactory + Clone> SupremumNoise for Noise
     }
 }
 
+#[derive(Clone)]
 pub struct ShiftedNoise {
-    noise: Rc<NoiseHolder<DefaultNoise>>,
-    shift_x: DensityFunctions,
-    shift_y: DensityFunctions,
-    shift_z: DensityFunctions,
+    pub(crate) noise: Arc<NoiseHolder<DefaultNoise>>,
+    pub(crate) shift_x: DensityFunctions,
+    pub(crate) shift_y: DensityFunctions,
+    pub(crate) shift_z: DensityFunctions,
     xz_scale: f64,
     y_scale: f64,
 }
 
-impl Clone for ShiftedNoise {
-    fn clone(&self) -> Self {
-        Self {
-            noise: Rc::new(self.noise.as_ref().clone()),
-            shift_x: self.shift_x.clone(),
-            shift_y: self.shift_y.clone(),
-            shift_z: self.shift_z.clone(),
-            xz_scale: self.xz_scale,
-            y_scale: self.y_scale,
-        }
-    }
-}
-
 impl ShiftedNoise {
     pub fn new(
-        noise: Rc<NoiseHolder<DefaultNoise>>,
+        noise: Arc<NoiseHolder<DefaultNoise>>,
         shift_x: DensityFunctions,
         shift_y: DensityFunctions,
         shift_z: DensityFunctions,
@@ -440,6 +543,24 @@ impl ShiftedNoise {
     pub fn y_scale(&self) -> f64 {
         self.y_scale
     }
+
+    /// Samples the noise at the scaled position moved by the shifts.
+    #[inline]
+    pub(crate) fn sample_shifted(
+        noise: &NoiseHolder<DefaultNoise>,
+        at: Vector3,
+        xz_scale: f64,
+        y_scale: f64,
+        shift_x: f64,
+        shift_y: f64,
+        shift_z: f64,
+    ) -> f64 {
+        let d0 = at.x() as f64 * xz_scale + shift_x;
+        let d1 = at.y() as f64 * y_scale + shift_y;
+        let d2 = at.z() as f64 * xz_scale + shift_z;
+
+        noise.sample(Vector3f::new(d0, d1, d2), 0.0, 0.0)
+    }
 }
 
 impl Debug for ShiftedNoise {
@@ -456,11 +577,11 @@ impl Debug for ShiftedNoise {
 
 impl DensityFunction for ShiftedNoise {
     fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
-        let d0 = at.x() as f64 * self.xz_scale + self.shift_x.sample(at, ctx);
-        let d1 = at.y() as f64 * self.y_scale + self.shift_y.sample(at, ctx);
-        let d2 = at.z() as f64 * self.xz_scale + self.shift_z.sample(at, ctx);
+        let shift_x = self.shift_x.sample(at, ctx);
+        let shift_y = self.shift_y.sample(at, ctx);
+        let shift_z = self.shift_z.sample(at, ctx);
 
-        let val = self.noise.sample(Vector3f::new(d0, d1, d2), 0.0, 0.0);
+        let val = Self::sample_shifted(&self.noise, at, self.xz_scale, self.y_scale, shift_x, shift_y, shift_z);
 
         black_box(val)
     }
@@ -491,20 +612,13 @@ impl DensityFunction for ShiftedNoise {
     }
 }
 
+#[derive(Clone)]
 pub struct ShiftA {
-    noise: Rc<NoiseHolder<DefaultNoise>>
-}
-
-impl Clone for ShiftA {
-    fn clone(&self) -> Self {
-        Self {
-            noise: Rc::new(self.noise.as_ref().clone()),
-        }
-    }
+    pub(crate) noise: Arc<NoiseHolder<DefaultNoise>>
 }
 
 impl ShiftA {
-    pub fn new(noise: Rc<NoiseHolder<DefaultNoise>>) -> Self {
+    pub fn new(noise: Arc<NoiseHolder<DefaultNoise>>) -> Self {
         Self {
             noise
         }
@@ -514,6 +628,15 @@ impl ShiftA {
     pub fn noise(&self) -> &NoiseHolder<DefaultNoise> {
         &self.noise
     }
+
+    #[inline]
+    pub(crate) fn shift(noise: &NoiseHolder<DefaultNoise>, at: Vector3) -> f64 {
+        noise.sample(
+            Vector3f::new(at.x() as f64 * 0.25, 0.0, at.z() as f64 * 0.25),
+            0.0,
+            0.0
+        ) * 4.0
+    }
 }
 
 impl Debug for ShiftA {
@@ -524,11 +647,7 @@ impl Debug for ShiftA {
 
 impl DensityFunction for ShiftA {
     fn sample(&self, at: Vector3, _: &mut DensityFunctionContext) -> f64 {
-        self.noise.sample(
-            Vector3f::new(at.x() as f64 * 0.25, 0.0, at.z() as f64 * 0.25),
-            0.0,
-            0.0
-        ) * 4.0
+        Self::shift(&self.noise, at)
     }
 
     fn min_value(&self) -> f64 {
@@ -544,20 +663,13 @@ impl DensityFunction for ShiftA {
     }
 }
 
+#[derive(Clone)]
 pub struct ShiftB {
-    noise: Rc<NoiseHolder<DefaultNoise>>
-}
-
-impl Clone for ShiftB {
-    fn clone(&self) -> Self {
-        Self {
-            noise: Rc::new(self.noise.as_ref().clone()),
-        }
-    }
+    pub(crate) noise: Arc<NoiseHolder<DefaultNoise>>
 }
 
 impl ShiftB {
-    pub fn new(noise: Rc<NoiseHolder<DefaultNoise>>) -> Self {
+    pub fn new(noise: Arc<NoiseHolder<DefaultNoise>>) -> Self {
         Self {
             noise
         }
@@ -567,6 +679,15 @@ impl ShiftB {
     pub fn noise(&self) -> &NoiseHolder<DefaultNoise> {
         &self.noise
     }
+
+    #[inline]
+    pub(crate) fn shift(noise: &NoiseHolder<DefaultNoise>, at: Vector3) -> f64 {
+        noise.sample(
+            Vector3f::new(at.z() as f64 * 0.25, at.x() as f64 * 0.25, 0.0),
+            0.0,
+            0.0
+        ) * 4.0
+    }
 }
 
 impl Debug for ShiftB {
@@ -577,11 +698,7 @@ impl Debug for ShiftB {
 
 impl DensityFunction for ShiftB {
     fn sample(&self, at: Vector3, _: &mut DensityFunctionContext) -> f64 {
-        self.noise.sample(
-            Vector3f::new(at.z() as f64 * 0.25, at.x() as f64 * 0.25, 0.0),
-            0.0,
-            0.0
-        ) * 4.0
+        Self::shift(&self.noise, at)
     }
 
     fn min_value(&self) -> f64 {
@@ -599,7 +716,7 @@ impl DensityFunction for ShiftB {
 
 #[cfg(test)]
 mod tests {
-    use crate::noise::density::density::{DensityFunction, DensityFunctionContext};
+    use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext};
     use crate::noise::density::noise::OldBlendedNoise;
     use crate::rng::XoroShiro;
     use spherix_math::vector::Vector3;
@@ -625,4 +742,28 @@ mod tests {
 
         assert_f64_eq!(-0.06628792977026834, s, 10);
     }
+
+    #[test]
+    fn blended_noise_fill_array_matches_sample() {
+        let noise = OldBlendedNoise::create(&mut XoroShiro::new(0x301D04), 0.25, 0.125, 80.0, 160.0, 8.0);
+        let ctx = || DensityFunctionContext {
+            filler: ContextFiller::Slice,
+            cell_height: 8,
+            cell_count_y: 48,
+            cell_noise_min_y: -8,
+            cell_start_block_x: -36,
+            cell_start_block_z: 1024,
+            ..Default::default()
+        };
+
+        let mut actual = vec![0.0; 49];
+        let mut fill_ctx = ctx();
+        noise.fill_array(&mut actual, &mut fill_ctx);
+
+        let mut sample_ctx = ctx();
+        for (at, actual) in sample_ctx.collect_positions().into_iter().zip(actual) {
+            assert_eq!(noise.sample(at, &mut DensityFunctionContext::default()).to_bits(), actual.to_bits());
+        }
+        assert_eq!(sample_ctx.cell_start_block_y, fill_ctx.cell_start_block_y);
+    }
 }
@@ -51,7 +51,7 @@ impl DensityFunction for Spline {
 
 #[derive(Clone)]
 pub struct ConstantSpline {
-    value: f64,
+    pub(crate) value: f64,
 }
 
 impl ConstantSpline {
@@ -99,10 +99,10 @@ impl DensityFunction for ConstantSpline {
 /// Represents multipoint cubic spline
 #[derive(Clone)]
 pub struct MultipointSpline {
-    coordinate: DensityFunctions,
-    locations: Vec<f64>,
-    derivatives: Vec<f64>,
-    values: Vec<DensityFunctions>,
+    pub(crate) coordinate: DensityFunctions,
+    pub(crate) locations: Vec<f64>,
+    pub(crate) derivatives: Vec<f64>,
+    pub(crate) values: Vec<DensityFunctions>,
     min_value: f64,
     max_value: f64,
 }
@@ -203,7 +203,7 @@ impl MultipointSpline {
     /// `index`: The index specifying the position in the locations and derivatives vectors to
     /// be used for the extension.
     #[inline]
-    fn linear_extend(f2: f64, locations: &Vec<f64>, derivatives: &Vec<f64>, value: f64, index: usize) -> f64 {
+    fn linear_extend(f2: f64, locations: &[f64], derivatives: &[f64], value: f64, index: usize) -> f64 {
         let slope = derivatives[index];
         if slope == 0.0 {
             value
@@ -213,55 +213,53 @@ impl MultipointSpline {
     }
 
     #[inline]
-    fn find_interval_start(locations: &[f64], f: f64) -> isize {
+    pub(crate) fn find_interval_start(locations: &[f64], f: f64) -> isize {
         locations.binary_search_by(|a| a.total_cmp(&f)).unwrap_or_else(|index| index) as isize - 1
     }
-}
 
-impl Debug for MultipointSpline {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "MultipointSpline (min_value: {}, max_value: {})", self.min_value, self.max_value)
+    /// Whether the value of the point at `index` is needed to interpolate
+    /// at the coordinate `f`.
+    #[inline]
+    pub(crate) fn needs_value(locations: &[f64], f: f64, index: usize) -> bool {
+        let start_interval_index = Self::find_interval_start(locations, f);
+        let last_interval_index = locations.len() - 1;
+
+        if start_interval_index < 0 {
+            index == 0
+        } else if start_interval_index as usize == last_interval_index {
+            index == last_interval_index
+        } else {
+            index == start_interval_index as usize || index == start_interval_index as usize + 1
+        }
     }
-}
 
-impl DensityFunction for MultipointSpline {
-    fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
-        let f = self.coordinate.sample(at, ctx);
+    /// Interpolates at the coordinate `f`, sampling only the values of the
+    /// points it needs with `value`.
+    #[inline]
+    pub(crate) fn interpolate<F: FnMut(usize) -> f64>(locations: &[f64], derivatives: &[f64], f: f64, mut value: F) -> f64 {
         // Find the index of the interval start for the given input value
-        let start_interval_index = Self::find_interval_start(&self.locations, f);
-        let last_interval_index = self.locations.len() - 1;
-        
+        let start_interval_index = Self::find_interval_start(locations, f);
+        let last_interval_index = locations.len() - 1;
+
         if start_interval_index < 0 {
             // If the input is before the first interval, use linear extension
-            Self::linear_extend(
-                f,
-                &self.locations,
-                &self.derivatives,
-                self.values[0].sample(at, ctx),
-                0,
-            )
+            Self::linear_extend(f, locations, derivatives, value(0), 0)
         } else if start_interval_index as usize == last_interval_index {
             // If the input is after the last interval, use linear extension
-            Self::linear_extend(
-                f,
-                &self.locations,
-                &self.derivatives,
-                self.values[last_interval_index].sample(at, ctx),
-                last_interval_index,
-            )
+            Self::linear_extend(f, locations, derivatives, value(last_interval_index), last_interval_index)
         } else {
             let start_interval_index = start_interval_index as usize;
             // If the input is within the intervals, perform spline interpolation
-            let x1 = self.locations[start_interval_index];
-            let x2 = self.locations[start_interval_index + 1];
+            let x1 = locations[start_interval_index];
+            let x2 = locations[start_interval_index + 1];
             // Calculate the normalized position within the interval
             let normalized_x = (f - x1) / (x2 - x1);
             // Get the slope at the current and next control points
-            let current_slope = self.derivatives[start_interval_index];
-            let next_slope = self.derivatives[start_interval_index + 1];
+            let current_slope = derivatives[start_interval_index];
+            let next_slope = derivatives[start_interval_index + 1];
             // Evaluate the spline at the beginning of the current interval
-            let y1 = self.values[start_interval_index].sample(at, ctx);
-            let y2 = self.values[start_interval_index + 1].sample(at, ctx);
+            let y1 = value(start_interval_index);
+            let y2 = value(start_interval_index + 1);
             // Intermediate values
             let t1 = current_slope * (x2 - x1) - (y2 - y1);
             let t2 = -next_slope * (x2 - x1) + (y2 - y1);
@@ -269,6 +267,20 @@ impl DensityFunction for MultipointSpline {
             lerp(normalized_x, y1, y2) + normalized_x * (1.0 - normalized_x) * lerp(normalized_x, t1, t2)
         }
     }
+}
+
+impl Debug for MultipointSpline {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MultipointSpline (min_value: {}, max_value: {})", self.min_value, self.max_value)
+    }
+}
+
+impl DensityFunction for MultipointSpline {
+    fn sample(&self, at: Vector3, ctx: &mut DensityFunctionContext) -> f64 {
+        let f = self.coordinate.sample(at, ctx);
+
+        Self::interpolate(&self.locations, &self.derivatives, f, |index| self.values[index].sample(at, ctx))
+    }
 
     #[inline]
     fn min_value(&self) -> f64 {
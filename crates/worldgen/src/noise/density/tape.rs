@@ -0,0 +1,1112 @@
+use crate::noise::density::beardifier::Beardifier;
+use crate::noise::density::density::{DensityFunction, DensityFunctionContext, DensityFunctions};
+use crate::noise::density::misc::{RarityValue, WeirdScaledSampler};
+use crate::noise::density::noise::{NoiseDensityFunction, NoiseHolder, ShiftA, ShiftB, ShiftedNoise};
+use crate::noise::density::spline::{MultipointSpline, Spline};
+use crate::noise::density::unary::{HalfNegative, QuarterNegative, Squeeze};
+use crate::noise::perlin::batch::{BatchNoise, Lanes, LANES};
+use crate::noise::perlin::DefaultNoise;
+use anyhow::anyhow;
+use spherix_math::vector::Vector3;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Batch of points a [`Program`] fills.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Cell corners of a column, which fill a slice of an interpolator.
+    /// Interpolated functions sample their arguments.
+    Slice,
+    /// Blocks of the selected cell, in the order of
+    /// [`DensityFunctionContext::for_index`]. Interpolated functions
+    /// interpolate between the noises of the cell.
+    Cell,
+}
+
+#[derive(Clone, Copy)]
+enum Unary {
+    Abs,
+    Square,
+    Cube,
+    HalfNegative,
+    QuarterNegative,
+    Squeeze,
+}
+
+impl Unary {
+    #[inline]
+    fn apply(self, val: f64) -> f64 {
+        match self {
+            Unary::Abs => val.abs(),
+            Unary::Square => val.powf(2.0),
+            Unary::Cube => val.powf(3.0),
+            Unary::HalfNegative => HalfNegative::half_negative(val),
+            Unary::QuarterNegative => QuarterNegative::quarter_negative(val),
+            Unary::Squeeze => Squeeze::squeeze(val),
+        }
+    }
+}
+
+/// Operation of an instruction. Operands are registers of the program, or
+/// node ids while the program is compiled.
+#[derive(Clone)]
+enum Op {
+    Const(f64),
+    Add(usize, usize),
+    AddConst(usize, f64),
+    /// Product, the second operand is only read if the first is not 0.
+    Mul(usize, usize),
+    MulConst(usize, f64),
+    /// Minimum, the second operand is only read if the first is not below
+    /// its minimum value.
+    Min(usize, usize, f64),
+    /// Maximum, the second operand is only read if the first is not above
+    /// its maximum value.
+    Max(usize, usize, f64),
+    /// Maximum sampled point by point, which reads both operands.
+    MaxAll(usize, usize),
+    Unary(Unary, usize),
+    Clamp(usize, f64, f64),
+    RangeChoice {
+        input: usize,
+        min_inclusive: f64,
+        max_exclusive: f64,
+        when_in_range: usize,
+        when_out_of_range: usize,
+    },
+    Noise {
+        noise: Arc<NoiseHolder<DefaultNoise>>,
+        xz_scale: f64,
+        y_scale: f64,
+    },
+    ShiftA(Arc<NoiseHolder<DefaultNoise>>),
+    ShiftB(Arc<NoiseHolder<DefaultNoise>>),
+    ShiftedNoise {
+        noise: Arc<NoiseHolder<DefaultNoise>>,
+        shift_x: usize,
+        shift_y: usize,
+        shift_z: usize,
+        xz_scale: f64,
+        y_scale: f64,
+    },
+    WeirdScaledSampler {
+        input: usize,
+        noise: Arc<NoiseHolder<DefaultNoise>>,
+        rarity_value: RarityValue,
+    },
+    /// Multipoint spline, the value of a point is only read if the
+    /// coordinate needs it.
+    Spline {
+        coordinate: usize,
+        locations: Vec<f64>,
+        derivatives: Vec<f64>,
+        values: Vec<usize>,
+    },
+    BlendDensity(usize),
+    /// Interpolator with the state at the index of the context.
+    Interpolated(usize),
+    /// Beardifier of the chunk, which the program is bound to.
+    Beardifier,
+    /// Leaf, which is sampled through the tree.
+    Sample(DensityFunctions),
+}
+
+impl Op {
+    fn operands_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Op::Add(a, b) | Op::Mul(a, b) | Op::Min(a, b, _) | Op::Max(a, b, _) | Op::MaxAll(a, b) => vec![a, b],
+            Op::AddConst(a, _)
+            | Op::MulConst(a, _)
+            | Op::Unary(_, a)
+            | Op::Clamp(a, _, _)
+            | Op::BlendDensity(a)
+            | Op::WeirdScaledSampler { input: a, .. } => vec![a],
+            Op::RangeChoice { input, when_in_range, when_out_of_range, .. } => {
+                vec![input, when_in_range, when_out_of_range]
+            }
+            Op::ShiftedNoise { shift_x, shift_y, shift_z, .. } => vec![shift_x, shift_y, shift_z],
+            Op::Spline { coordinate, values, .. } => std::iter::once(coordinate).chain(values.iter_mut()).collect(),
+            Op::Const(_)
+            | Op::Noise { .. }
+            | Op::ShiftA(_)
+            | Op::ShiftB(_)
+            | Op::Interpolated(_)
+            | Op::Beardifier
+            | Op::Sample(_) => Vec::new(),
+        }
+    }
+}
+
+/// Condition of the lanes, which evaluate a masked part of the program.
+#[derive(Clone)]
+enum Cond {
+    NonZero(usize),
+    NotBelow(usize, f64),
+    NotAbove(usize, f64),
+    InRange(usize, f64, f64),
+    OutOfRange(usize, f64, f64),
+    SplinePoint {
+        coordinate: usize,
+        locations: Vec<f64>,
+        index: usize,
+    },
+}
+
+impl Cond {
+    /// Comparisons are negated like in the tree, so NaN reads the operand.
+    #[inline]
+    #[allow(clippy::neg_cmp_op_on_partial_ord)]
+    fn holds(&self, regs: &[f64], n: usize, lane: usize) -> bool {
+        match self {
+            Cond::NonZero(a) => regs[a * n + lane] != 0.0,
+            Cond::NotBelow(a, min) => !(regs[a * n + lane] < *min),
+            Cond::NotAbove(a, max) => !(regs[a * n + lane] > *max),
+            Cond::InRange(a, min, max) => {
+                let val = regs[a * n + lane];
+                val >= *min && val < *max
+            }
+            Cond::OutOfRange(a, min, max) => {
+                let val = regs[a * n + lane];
+                !(val >= *min && val < *max)
+            }
+            Cond::SplinePoint { coordinate, locations, index } => {
+                MultipointSpline::needs_value(locations, regs[coordinate * n + lane], *index)
+            }
+        }
+    }
+
+    fn operand_mut(&mut self) -> &mut usize {
+        match self {
+            Cond::NonZero(a)
+            | Cond::NotBelow(a, _)
+            | Cond::NotAbove(a, _)
+            | Cond::InRange(a, _, _)
+            | Cond::OutOfRange(a, _, _)
+            | Cond::SplinePoint { coordinate: a, .. } => a,
+        }
+    }
+}
+
+enum Instr {
+    /// Evaluates the operation for the active lanes. A uniform operation has
+    /// the same value for all the lanes, so it is evaluated once.
+    Eval {
+        dst: usize,
+        uniform: bool,
+        op: Op,
+    },
+    /// Narrows the active lanes to the ones the condition holds for.
+    PushMask(Cond),
+    PopMask,
+}
+
+impl Instr {
+    fn operands_mut(&mut self) -> Vec<&mut usize> {
+        match self {
+            Instr::Eval { op, .. } => op.operands_mut(),
+            Instr::PushMask(cond) => vec![cond.operand_mut()],
+            Instr::PopMask => Vec::new(),
+        }
+    }
+}
+
+/// Density function compiled to a linear tape of instructions, which fills
+/// a whole batch of points an instruction at a time instead of walking the
+/// tree for each point.
+///
+/// Identical subtrees are evaluated once and functions of x and z only are
+/// evaluated once per slice column. The arithmetic is shared with the tree,
+/// so the filled values are identical to the ones of
+/// [`DensityFunction::fill_array`].
+///
+/// The tape does not depend on the chunk, so it is compiled once and shared.
+/// Chunks evaluate it with a [`Program`] of their own.
+pub struct Tape {
+    stage: Stage,
+    code: Vec<Instr>,
+    registers: usize,
+    result: usize,
+    mask_depth: usize,
+}
+
+impl Tape {
+    /// Compiles the function for the stage. Functions, which depend on the
+    /// state of the tree, can not be compiled, so the tree has to be used.
+    pub fn compile(df: &DensityFunctions, stage: Stage) -> anyhow::Result<Self> {
+        let mut compiler = Compiler::new(stage);
+        let root = compiler.intern(df, false)?;
+        let result = compiler.emit(root);
+
+        let (code, registers, result) = allocate_registers(compiler.code, compiler.slots, result);
+
+        let mut depth = 0;
+        let mut max_depth = 0;
+        for instr in &code {
+            match instr {
+                Instr::PushMask(_) => {
+                    depth += 1;
+                    max_depth = max_depth.max(depth);
+                }
+                Instr::PopMask => depth -= 1,
+                Instr::Eval { .. } => {}
+            }
+        }
+
+        Ok(Self {
+            stage,
+            code,
+            registers,
+            result,
+            mask_depth: max_depth,
+        })
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn instruction_count(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn register_count(&self) -> usize {
+        self.registers
+    }
+}
+
+/// Evaluation of a [`Tape`] for a chunk, which keeps the registers between
+/// the fills.
+pub struct Program {
+    tape: Arc<Tape>,
+    beardifier: Beardifier,
+    /// Registers of `n` lanes each, one after another.
+    regs: Vec<f64>,
+    /// Active lanes of each mask depth.
+    masks: Vec<Vec<u32>>,
+    positions: Vec<Vector3>,
+    in_cell: Vec<(u32, u32, u32)>,
+}
+
+impl Program {
+    /// Beardifier of the tape is the given one, whichever the compiled
+    /// function had.
+    pub fn new(tape: Arc<Tape>, beardifier: Beardifier) -> Self {
+        Self {
+            masks: vec![Vec::new(); tape.mask_depth + 1],
+            tape,
+            beardifier,
+            regs: Vec::new(),
+            positions: Vec::new(),
+            in_cell: Vec::new(),
+        }
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.tape.stage
+    }
+
+    /// Fills the points of the stage like [`DensityFunction::fill_array`]
+    /// does, leaving the context in the same state.
+    pub fn fill(&mut self, arr: &mut [f64], ctx: &mut DensityFunctionContext) {
+        let n = arr.len();
+        if n == 0 {
+            return
+        }
+
+        self.place_lanes(n, ctx);
+        self.regs.resize(self.tape.registers * n, 0.0);
+
+        let Self { tape, beardifier, regs, masks, positions, in_cell } = self;
+        let result = tape.result;
+        let points = Points {
+            positions,
+            in_cell,
+            beardifier,
+            cell_width: ctx.cell_width,
+            cell_height: ctx.cell_height,
+        };
+
+        masks[0].clear();
+        masks[0].extend(0..n as u32);
+        let mut depth = 0;
+
+        for instr in tape.code.iter() {
+            match instr {
+                Instr::PushMask(cond) => {
+                    depth += 1;
+
+                    let (outer, inner) = masks.split_at_mut(depth);
+                    inner[0].clear();
+                    inner[0].extend(
+                        outer[depth - 1]
+                            .iter()
+                            .copied()
+                            .filter(|&lane| cond.holds(regs.as_slice(), n, lane as usize))
+                    );
+                }
+                Instr::PopMask => depth -= 1,
+                Instr::Eval { dst, uniform, op } => {
+                    let active = &masks[depth];
+                    let active = if *uniform { &active[..active.len().min(1)] } else { &active[..] };
+
+                    eval(op, active, *dst, regs, n, &points, ctx);
+
+                    if let (true, Some(&lane)) = (*uniform, active.first()) {
+                        let row = &mut regs[dst * n..(dst + 1) * n];
+                        let val = row[lane as usize];
+                        row.fill(val);
+                    }
+                }
+            }
+        }
+
+        arr.copy_from_slice(&regs[result * n..(result + 1) * n]);
+
+        self.finish(n, ctx);
+    }
+
+    /// Positions of the lanes, as the tree visits them.
+    fn place_lanes(&mut self, n: usize, ctx: &DensityFunctionContext) {
+        self.positions.clear();
+        self.in_cell.clear();
+
+        match self.tape.stage {
+            Stage::Slice => {
+                let x = ctx.cell_start_block_x + ctx.in_cell_x as i32;
+                let z = ctx.cell_start_block_z + ctx.in_cell_z as i32;
+
+                self.positions.extend(
+                    (0..n).map(|i| Vector3::new(x, (i as i32 + ctx.cell_noise_min_y) * ctx.cell_height as i32, z))
+                );
+            }
+            Stage::Cell => {
+                let width = ctx.cell_width as usize;
+
+                for i in 0..n {
+                    let j = i / width;
+                    let in_cell_x = (j % width) as u32;
+                    let in_cell_y = ctx.cell_height - 1 - (j / width) as u32;
+                    let in_cell_z = (i % width) as u32;
+
+                    self.in_cell.push((in_cell_x, in_cell_y, in_cell_z));
+                    self.positions.push(Vector3::new(
+                        ctx.cell_start_block_x + in_cell_x as i32,
+                        ctx.cell_start_block_y + in_cell_y as i32,
+                        ctx.cell_start_block_z + in_cell_z as i32,
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Leaves the context at the last point, like the tree does.
+    fn finish(&self, n: usize, ctx: &mut DensityFunctionContext) {
+        match self.tape.stage {
+            Stage::Slice => {
+                ctx.cell_start_block_y = ((n - 1) as i32 + ctx.cell_noise_min_y) * ctx.cell_height as i32;
+                ctx.interpolation_counter += n;
+                ctx.in_cell_y = 0;
+                ctx.array_index = n - 1;
+            }
+            Stage::Cell => {
+                let (in_cell_x, in_cell_y, in_cell_z) = self.in_cell[n - 1];
+                ctx.in_cell_x = in_cell_x;
+                ctx.in_cell_y = in_cell_y;
+                ctx.in_cell_z = in_cell_z;
+                ctx.array_index = n;
+            }
+        }
+    }
+}
+
+struct Points<'a> {
+    positions: &'a [Vector3],
+    in_cell: &'a [(u32, u32, u32)],
+    beardifier: &'a Beardifier,
+    cell_width: u32,
+    cell_height: u32,
+}
+
+macro_rules! for_lanes {
+    ($active:expr, $regs:ident, $n:ident, $dst:ident, |$lane:ident| $val:expr) => {
+        for &$lane in $active {
+            let $lane = $lane as usize;
+            let val = $val;
+            $regs[$dst * $n + $lane] = val;
+        }
+    };
+}
+
+#[inline]
+fn eval(
+    op: &Op,
+    active: &[u32],
+    dst: usize,
+    regs: &mut [f64],
+    n: usize,
+    points: &Points,
+    ctx: &mut DensityFunctionContext,
+) {
+    match op {
+        Op::Const(val) => for_lanes!(active, regs, n, dst, |lane| *val),
+        Op::Add(a, b) => for_lanes!(active, regs, n, dst, |lane| regs[a * n + lane] + regs[b * n + lane]),
+        Op::AddConst(a, argument) => for_lanes!(active, regs, n, dst, |lane| regs[a * n + lane] + argument),
+        Op::Mul(a, b) => for_lanes!(active, regs, n, dst, |lane| {
+            let d0 = regs[a * n + lane];
+            if d0 == 0.0 { 0.0 } else { d0 * regs[b * n + lane] }
+        }),
+        Op::MulConst(a, argument) => for_lanes!(active, regs, n, dst, |lane| regs[a * n + lane] * argument),
+        Op::Min(a, b, min) => for_lanes!(active, regs, n, dst, |lane| {
+            let d0 = regs[a * n + lane];
+            if d0 < *min { d0 } else { d0.min(regs[b * n + lane]) }
+        }),
+        Op::Max(a, b, max) => for_lanes!(active, regs, n, dst, |lane| {
+            let d0 = regs[a * n + lane];
+            if d0 > *max { d0 } else { d0.max(regs[b * n + lane]) }
+        }),
+        Op::MaxAll(a, b) => for_lanes!(active, regs, n, dst, |lane| regs[a * n + lane].max(regs[b * n + lane])),
+        Op::Unary(f, a) => for_lanes!(active, regs, n, dst, |lane| f.apply(regs[a * n + lane])),
+        Op::Clamp(a, min, max) => for_lanes!(active, regs, n, dst, |lane| regs[a * n + lane].clamp(*min, *max)),
+        Op::RangeChoice { input, min_inclusive, max_exclusive, when_in_range, when_out_of_range } => {
+            for_lanes!(active, regs, n, dst, |lane| {
+                let d0 = regs[input * n + lane];
+                if d0 >= *min_inclusive && d0 < *max_exclusive {
+                    regs[when_in_range * n + lane]
+                } else {
+                    regs[when_out_of_range * n + lane]
+                }
+            })
+        }
+        Op::Noise { noise, xz_scale, y_scale } => {
+            let mut values = [0.0; LANES];
+
+            for chunk in active.chunks(LANES) {
+                let at = Lanes::from_fn(chunk.len(), |i| {
+                    NoiseDensityFunction::scale(points.positions[chunk[i] as usize], *xz_scale, *y_scale)
+                });
+                noise.sample_lanes(&at, 0.0, 0.0, &mut values);
+
+                for (&lane, val) in chunk.iter().zip(values) {
+                    regs[dst * n + lane as usize] = val;
+                }
+            }
+        }
+        Op::ShiftA(noise) => for_lanes!(active, regs, n, dst, |lane| ShiftA::shift(noise, points.positions[lane])),
+        Op::ShiftB(noise) => for_lanes!(active, regs, n, dst, |lane| ShiftB::shift(noise, points.positions[lane])),
+        Op::ShiftedNoise { noise, shift_x, shift_y, shift_z, xz_scale, y_scale } => {
+            for_lanes!(active, regs, n, dst, |lane| {
+                ShiftedNoise::sample_shifted(
+                    noise,
+                    points.positions[lane],
+                    *xz_scale,
+                    *y_scale,
+                    regs[shift_x * n + lane],
+                    regs[shift_y * n + lane],
+                    regs[shift_z * n + lane],
+                )
+            })
+        }
+        Op::WeirdScaledSampler { input, noise, rarity_value } => for_lanes!(active, regs, n, dst, |lane| {
+            WeirdScaledSampler::sample_with_input(noise, rarity_value, points.positions[lane], regs[input * n + lane])
+        }),
+        Op::Spline { coordinate, locations, derivatives, values } => for_lanes!(active, regs, n, dst, |lane| {
+            MultipointSpline::interpolate(locations, derivatives, regs[coordinate * n + lane], |index| {
+                regs[values[index] * n + lane]
+            })
+        }),
+        Op::BlendDensity(a) => for_lanes!(active, regs, n, dst, |lane| {
+            ctx.blender.blend_density(points.positions[lane], regs[a * n + lane])
+        }),
+        Op::Interpolated(index) => match ctx.interpolators.get(*index) {
+            Some(state) => for_lanes!(active, regs, n, dst, |lane| {
+                let (in_cell_x, in_cell_y, in_cell_z) = points.in_cell[lane];
+                state.interpolate_in_cell(in_cell_x, in_cell_y, in_cell_z, points.cell_width, points.cell_height)
+            }),
+            None => for_lanes!(active, regs, n, dst, |lane| 0.0),
+        },
+        Op::Beardifier => for_lanes!(active, regs, n, dst, |lane| points.beardifier.sample(points.positions[lane], ctx)),
+        Op::Sample(df) => for_lanes!(active, regs, n, dst, |lane| df.sample(points.positions[lane], ctx)),
+    }
+}
+
+/// Reuses the registers of the values, which are not read anymore.
+fn allocate_registers(mut code: Vec<Instr>, slots: usize, result: usize) -> (Vec<Instr>, usize, usize) {
+    let mut last_read = vec![0; slots];
+    for (i, instr) in code.iter_mut().enumerate() {
+        for slot in instr.operands_mut() {
+            last_read[*slot] = i;
+        }
+    }
+    last_read[result] = usize::MAX;
+
+    let mut registers = vec![usize::MAX; slots];
+    let mut free = Vec::new();
+    let mut count = 0;
+
+    for (i, instr) in code.iter_mut().enumerate() {
+        for slot in instr.operands_mut() {
+            let virtual_slot = *slot;
+            *slot = registers[virtual_slot];
+
+            if last_read[virtual_slot] == i {
+                // Read twice by the same instruction.
+                last_read[virtual_slot] = usize::MAX - 1;
+                free.push(*slot);
+            }
+        }
+
+        if let Instr::Eval { dst, .. } = instr {
+            let register = free.pop().unwrap_or_else(|| {
+                count += 1;
+                count - 1
+            });
+
+            if last_read[*dst] <= i {
+                free.push(register);
+            }
+
+            registers[*dst] = register;
+            *dst = register;
+        }
+    }
+
+    (code, count, registers[result])
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct NodeKey {
+    kind: &'static str,
+    tag: String,
+    params: Vec<u64>,
+    children: Vec<usize>,
+}
+
+impl NodeKey {
+    fn new(kind: &'static str, tag: &str, params: &[f64], children: &[usize]) -> Self {
+        Self {
+            kind,
+            tag: tag.to_owned(),
+            params: params.iter().map(|param| param.to_bits()).collect(),
+            children: children.to_vec(),
+        }
+    }
+}
+
+struct Node {
+    /// Operation with the ids of the child nodes as operands.
+    op: Op,
+    uniform: bool,
+}
+
+/// Compiles the tree in two passes. The tree is interned into a graph first,
+/// where identical subtrees are a single node, then the nodes are emitted
+/// depth first. Subtrees under a mask are only reused within the mask.
+struct Compiler {
+    stage: Stage,
+    nodes: Vec<Node>,
+    keys: HashMap<NodeKey, usize>,
+    constants: HashSet<usize>,
+    unique: usize,
+    code: Vec<Instr>,
+    slots: usize,
+    scopes: Vec<HashMap<usize, usize>>,
+}
+
+impl Compiler {
+    fn new(stage: Stage) -> Self {
+        Self {
+            stage,
+            nodes: Vec::new(),
+            keys: HashMap::new(),
+            constants: HashSet::new(),
+            unique: 0,
+            code: Vec::new(),
+            slots: 0,
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    fn node(&mut self, key: NodeKey, op: Op, uniform: bool) -> usize {
+        if let Some(&id) = self.keys.get(&key) {
+            return id
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node { op, uniform });
+        self.keys.insert(key, id);
+
+        id
+    }
+
+    fn constant(&mut self, val: f64) -> usize {
+        let id = self.node(NodeKey::new("const", "", &[val], &[]), Op::Const(val), true);
+        self.constants.insert(id);
+
+        id
+    }
+
+    /// Leaf sampled through the tree, which is never merged.
+    fn sample(&mut self, df: &DensityFunctions, uniform: bool) -> usize {
+        self.unique += 1;
+
+        self.node(NodeKey::new("sample", "", &[self.unique as f64], &[]), Op::Sample(df.clone()), uniform)
+    }
+
+    fn uniform(&self, ids: &[usize]) -> bool {
+        ids.iter().all(|&id| self.nodes[id].uniform)
+    }
+
+    /// `pointwise` tells whether the tree samples the function point by
+    /// point instead of filling an array with it, which only matters for
+    /// `max`.
+    fn intern(&mut self, df: &DensityFunctions, pointwise: bool) -> anyhow::Result<usize> {
+        // Functions of x and z only are the same for the whole slice column.
+        let planar = self.stage == Stage::Slice;
+
+        Ok(match df {
+            DensityFunctions::Const(x) => self.constant(x.val),
+            DensityFunctions::Spline(x) => match x.as_ref() {
+                Spline::Constant(spline) => self.constant(spline.value),
+                Spline::Multipoint(spline) => {
+                    let mut children = vec![self.intern(&spline.coordinate, true)?];
+                    for value in &spline.values {
+                        children.push(self.intern(value, true)?);
+                    }
+
+                    let params = spline.locations.iter().chain(&spline.derivatives).copied().collect::<Vec<_>>();
+                    let uniform = self.uniform(&children);
+
+                    self.node(
+                        NodeKey::new("spline", "", &params, &children),
+                        Op::Spline {
+                            coordinate: children[0],
+                            locations: spline.locations.clone(),
+                            derivatives: spline.derivatives.clone(),
+                            values: children[1..].to_vec(),
+                        },
+                        uniform,
+                    )
+                }
+            },
+            DensityFunctions::Add(x) => {
+                let a = self.intern(&x.argument1, pointwise)?;
+                let b = self.intern(&x.argument2, pointwise)?;
+
+                self.node(NodeKey::new("add", "", &[], &[a, b]), Op::Add(a, b), self.uniform(&[a, b]))
+            }
+            DensityFunctions::AddConst(x) => {
+                let a = self.intern(&x.input, pointwise)?;
+
+                self.node(NodeKey::new("add_const", "", &[x.argument], &[a]), Op::AddConst(a, x.argument), self.uniform(&[a]))
+            }
+            DensityFunctions::Mul(x) => {
+                let a = self.intern(&x.argument1, pointwise)?;
+                let b = self.intern(&x.argument2, true)?;
+
+                self.node(NodeKey::new("mul", "", &[], &[a, b]), Op::Mul(a, b), self.uniform(&[a, b]))
+            }
+            DensityFunctions::MulConst(x) => {
+                let a = self.intern(&x.input, pointwise)?;
+
+                self.node(NodeKey::new("mul_const", "", &[x.argument], &[a]), Op::MulConst(a, x.argument), self.uniform(&[a]))
+            }
+            DensityFunctions::Min(x) => {
+                let a = self.intern(&x.argument1, pointwise)?;
+                let b = self.intern(&x.argument2, true)?;
+                let min = x.argument2.min_value();
+
+                self.node(NodeKey::new("min", "", &[min], &[a, b]), Op::Min(a, b, min), self.uniform(&[a, b]))
+            }
+            DensityFunctions::Max(x) => {
+                let a = self.intern(&x.argument1, pointwise)?;
+                let b = self.intern(&x.argument2, true)?;
+                let uniform = self.uniform(&[a, b]);
+
+                // Only filling an array short-circuits, which differs, if
+                // the maximum value of the second argument is not exact.
+                if pointwise {
+                    self.node(NodeKey::new("max_all", "", &[], &[a, b]), Op::MaxAll(a, b), uniform)
+                } else {
+                    let max = x.argument2.max_value();
+
+                    self.node(NodeKey::new("max", "", &[max], &[a, b]), Op::Max(a, b, max), uniform)
+                }
+            }
+            DensityFunctions::Abs(x) => self.unary("abs", Unary::Abs, &x.argument)?,
+            DensityFunctions::Square(x) => self.unary("square", Unary::Square, &x.argument)?,
+            DensityFunctions::Cube(x) => self.unary("cube", Unary::Cube, &x.argument)?,
+            DensityFunctions::HalfNegative(x) => self.unary("half_negative", Unary::HalfNegative, &x.argument)?,
+            DensityFunctions::QuarterNegative(x) => self.unary("quarter_negative", Unary::QuarterNegative, &x.argument)?,
+            DensityFunctions::Squeeze(x) => self.unary("squeeze", Unary::Squeeze, &x.argument)?,
+            DensityFunctions::Clamp(x) => {
+                let a = self.intern(&x.input, true)?;
+
+                self.node(NodeKey::new("clamp", "", &[x.min, x.max], &[a]), Op::Clamp(a, x.min, x.max), self.uniform(&[a]))
+            }
+            DensityFunctions::RangeChoice(x) => {
+                let input = self.intern(&x.input, pointwise)?;
+                let when_in_range = self.intern(&x.when_in_range, true)?;
+                let when_out_of_range = self.intern(&x.when_out_of_range, true)?;
+                let children = [input, when_in_range, when_out_of_range];
+
+                self.node(
+                    NodeKey::new("range_choice", "", &[x.min_inclusive, x.max_exclusive], &children),
+                    Op::RangeChoice {
+                        input,
+                        min_inclusive: x.min_inclusive,
+                        max_exclusive: x.max_exclusive,
+                        when_in_range,
+                        when_out_of_range,
+                    },
+                    self.uniform(&children),
+                )
+            }
+            DensityFunctions::Noise(x) => {
+                let xz_scale = x.xz_scale() as f64;
+                let y_scale = x.y_scale() as f64;
+
+                self.node(
+                    NodeKey::new("noise", x.noise().tag(), &[xz_scale, y_scale], &[]),
+                    Op::Noise {
+                        noise: x.noise.clone(),
+                        xz_scale,
+                        y_scale,
+                    },
+                    false,
+                )
+            }
+            DensityFunctions::ShiftA(x) => {
+                self.node(NodeKey::new("shift_a", x.noise().tag(), &[], &[]), Op::ShiftA(x.noise.clone()), planar)
+            }
+            DensityFunctions::ShiftB(x) => {
+                self.node(NodeKey::new("shift_b", x.noise().tag(), &[], &[]), Op::ShiftB(x.noise.clone()), planar)
+            }
+            DensityFunctions::ShiftedNoise(x) => {
+                let shift_x = self.intern(&x.shift_x, true)?;
+                let shift_y = self.intern(&x.shift_y, true)?;
+                let shift_z = self.intern(&x.shift_z, true)?;
+
+                // The scaled y is then 0 or -0, which the shift has to turn
+                // into the same value.
+                let flat_y = x.y_scale() == 0.0 && matches!(
+                    &x.shift_y,
+                    DensityFunctions::Const(shift) if shift.val != 0.0 || shift.val.is_sign_positive()
+                );
+                let uniform = planar && flat_y && self.uniform(&[shift_x, shift_z]);
+
+                self.node(
+                    NodeKey::new("shifted_noise", x.noise().tag(), &[x.xz_scale(), x.y_scale()], &[shift_x, shift_y, shift_z]),
+                    Op::ShiftedNoise {
+                        noise: x.noise.clone(),
+                        shift_x,
+                        shift_y,
+                        shift_z,
+                        xz_scale: x.xz_scale(),
+                        y_scale: x.y_scale(),
+                    },
+                    uniform,
+                )
+            }
+            DensityFunctions::WeirdScaledSampler(x) => {
+                let input = self.intern(&x.input, true)?;
+                let rarity = match x.rarity_value {
+                    RarityValue::Type1 => 1.0,
+                    RarityValue::Type2 => 2.0,
+                };
+
+                self.node(
+                    NodeKey::new("weird_scaled_sampler", x.noise.tag(), &[rarity], &[input]),
+                    Op::WeirdScaledSampler {
+                        input,
+                        noise: x.noise.clone(),
+                        rarity_value: x.rarity_value.clone(),
+                    },
+                    false,
+                )
+            }
+            DensityFunctions::BlendDensity(x) => {
+                let a = self.intern(&x.input, pointwise)?;
+
+                self.node(NodeKey::new("blend_density", "", &[], &[a]), Op::BlendDensity(a), false)
+            }
+            DensityFunctions::EndIslands(_) => {
+                self.node(NodeKey::new("end_islands", "", &[], &[]), Op::Sample(df.clone()), planar)
+            }
+            DensityFunctions::BlendAlpha(_) => {
+                self.node(NodeKey::new("blend_alpha", "", &[], &[]), Op::Sample(df.clone()), planar)
+            }
+            DensityFunctions::BlendOffset(_) => {
+                self.node(NodeKey::new("blend_offset", "", &[], &[]), Op::Sample(df.clone()), planar)
+            }
+            // Beardifiers of a chunk are all the same, it is bound to the
+            // program of the chunk.
+            DensityFunctions::Beardifier(_) => self.node(NodeKey::new("beardifier", "", &[], &[]), Op::Beardifier, false),
+            DensityFunctions::YClampedGradient(_)
+            | DensityFunctions::OldBlendedNoise(_) => self.sample(df, false),
+            DensityFunctions::Marker(x) => self.intern(&x.inner, pointwise)?,
+            DensityFunctions::Interpolated(x) => self.intern(&x.0, true)?,
+            DensityFunctions::Cache2D(x) => self.intern(&x.inner, pointwise)?,
+            DensityFunctions::FlatCache(x) if x.is_empty() => self.intern(&x.argument, true)?,
+            DensityFunctions::InterpolatedInner(x) => match self.stage {
+                Stage::Slice => self.intern(&x.argument, pointwise)?,
+                Stage::Cell => {
+                    self.node(
+                        NodeKey::new("interpolated", "", &[x.index as f64], &[]),
+                        Op::Interpolated(x.index),
+                        false,
+                    )
+                }
+            },
+            DensityFunctions::CacheOnce(x) if self.stage == Stage::Slice => self.intern(&x.inner, pointwise)?,
+            DensityFunctions::CacheAllInCell(x) if self.stage == Stage::Slice => self.intern(&x.inner, true)?,
+            _ => return Err(anyhow!("Can not compile {:?} for {:?}", df, self.stage))
+        })
+    }
+
+    fn unary(&mut self, kind: &'static str, f: Unary, argument: &DensityFunctions) -> anyhow::Result<usize> {
+        let a = self.intern(argument, true)?;
+
+        Ok(self.node(NodeKey::new(kind, "", &[], &[a]), Op::Unary(f, a), self.uniform(&[a])))
+    }
+
+    fn lookup(&self, id: usize) -> Option<usize> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(&id).copied())
+    }
+
+    /// Emits the node and the nodes it reads, which are not emitted yet.
+    /// Returns the slot of its value.
+    fn emit(&mut self, id: usize) -> usize {
+        if let Some(slot) = self.lookup(id) {
+            return slot
+        }
+
+        let mut op = self.nodes[id].op.clone();
+        let uniform = self.nodes[id].uniform;
+
+        match &mut op {
+            Op::Mul(a, b) => {
+                *a = self.emit(*a);
+                *b = self.emit_masked(Cond::NonZero(*a), *b);
+            }
+            Op::Min(a, b, min) => {
+                *a = self.emit(*a);
+                *b = self.emit_masked(Cond::NotBelow(*a, *min), *b);
+            }
+            Op::Max(a, b, max) => {
+                *a = self.emit(*a);
+                *b = self.emit_masked(Cond::NotAbove(*a, *max), *b);
+            }
+            Op::RangeChoice { input, min_inclusive, max_exclusive, when_in_range, when_out_of_range } => {
+                *input = self.emit(*input);
+                *when_in_range = self.emit_masked(Cond::InRange(*input, *min_inclusive, *max_exclusive), *when_in_range);
+                *when_out_of_range = self.emit_masked(
+                    Cond::OutOfRange(*input, *min_inclusive, *max_exclusive),
+                    *when_out_of_range
+                );
+            }
+            Op::Spline { coordinate, locations, values, .. } => {
+                *coordinate = self.emit(*coordinate);
+
+                for (index, value) in values.iter_mut().enumerate() {
+                    let cond = Cond::SplinePoint {
+                        coordinate: *coordinate,
+                        locations: locations.clone(),
+                        index,
+                    };
+
+                    *value = self.emit_masked(cond, *value);
+                }
+            }
+            _ => {
+                for operand in op.operands_mut() {
+                    *operand = self.emit(*operand);
+                }
+            }
+        }
+
+        let slot = self.slots;
+        self.slots += 1;
+
+        self.code.push(Instr::Eval { dst: slot, uniform, op });
+        self.scopes.last_mut().unwrap().insert(id, slot);
+
+        slot
+    }
+
+    /// Emits the node for the lanes, which the condition holds for. Values
+    /// emitted already and constants are not masked.
+    fn emit_masked(&mut self, cond: Cond, id: usize) -> usize {
+        if self.lookup(id).is_some() || self.constants.contains(&id) {
+            return self.emit(id)
+        }
+
+        self.code.push(Instr::PushMask(cond));
+        self.scopes.push(HashMap::new());
+
+        let slot = self.emit(id);
+
+        self.scopes.pop();
+        self.code.push(Instr::PopMask);
+
+        slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::density::beardifier::Beardifier;
+    use crate::noise::density::binary::{Add, Max, Min, Mul, MulConst};
+    use crate::noise::density::density::{ContextFiller, DensityFunction, DensityFunctionContext, DensityFunctions};
+    use crate::noise::density::maker::Interpolated;
+    use crate::noise::density::misc::{Clamp, Const, RangeChoice};
+    use crate::noise::density::noise::{NoiseDensityFunction, NoiseHolder, ShiftA, ShiftB, ShiftedNoise};
+    use crate::noise::density::spline::{MultipointSpline, Spline};
+    use crate::noise::density::tape::{Program, Stage, Tape};
+    use crate::noise::density::unary::{Abs, Square, Squeeze};
+    use crate::noise::perlin::octave::{MultiOctaveNoiseFactory, MultiOctaveNoiseParameters};
+    use crate::noise::perlin::DefaultNoise;
+    use crate::rng::XoroShiro;
+    use std::sync::Arc;
+
+    fn holder(tag: &str, seed: u64) -> Arc<NoiseHolder<DefaultNoise>> {
+        let params = MultiOctaveNoiseParameters::new(-4, vec![1.0, 1.0, 0.5]);
+        let noise = DefaultNoise::create(&mut XoroShiro::new(seed), &params.amplitudes, params.first_octave);
+
+        Arc::new(NoiseHolder::new(tag.to_owned(), params, Some(noise)))
+    }
+
+    fn noise(tag: &str, seed: u64, xz_scale: f32, y_scale: f32) -> DensityFunctions {
+        DensityFunctions::Noise(NoiseDensityFunction::new(holder(tag, seed), xz_scale, y_scale))
+    }
+
+    fn constant(val: f64) -> DensityFunctions {
+        DensityFunctions::Const(Const::new(val))
+    }
+
+    /// Tree with the short-circuiting functions and a 2D spline.
+    fn tree() -> DensityFunctions {
+        let continents = DensityFunctions::ShiftedNoise(Box::new(ShiftedNoise::new(
+            holder("test:continents", 3),
+            DensityFunctions::ShiftA(ShiftA::new(holder("test:offset", 4))),
+            constant(0.0),
+            DensityFunctions::ShiftB(ShiftB::new(holder("test:offset", 4))),
+            0.25,
+            0.0,
+        )));
+        let spline = DensityFunctions::Spline(Box::new(Spline::Multipoint(MultipointSpline::new(
+            continents,
+            vec![-0.3, 0.0, 0.3],
+            vec![0.0, 0.5, 0.0],
+            vec![constant(-0.5), noise("test:a", 1, 1.0, 1.0), constant(0.5)],
+        ))));
+
+        let choice = DensityFunctions::RangeChoice(Box::new(RangeChoice::new(
+            noise("test:a", 1, 1.0, 1.0),
+            -0.1,
+            0.1,
+            DensityFunctions::Mul(Box::new(Mul::new(noise("test:a", 1, 1.0, 1.0), noise("test:b", 2, 0.5, 2.0)))),
+            DensityFunctions::Min(Box::new(Min::new(
+                DensityFunctions::Abs(Box::new(Abs::new(noise("test:b", 2, 0.5, 2.0)))),
+                constant(0.3),
+            ))),
+        )));
+
+        DensityFunctions::Add(Box::new(Add::new(
+            DensityFunctions::Max(Box::new(Max::new(choice, spline))),
+            DensityFunctions::Clamp(Box::new(Clamp::new(
+                DensityFunctions::Square(Box::new(Square::new(noise("test:b", 2, 0.5, 2.0)))),
+                0.0,
+                0.5,
+            ))),
+        )))
+    }
+
+    fn bits(values: &[f64]) -> Vec<u64> {
+        values.iter().map(|val| val.to_bits()).collect()
+    }
+
+    fn slice_ctx(x: i32, z: i32) -> DensityFunctionContext {
+        DensityFunctionContext {
+            filler: ContextFiller::Slice,
+            cell_width: 4,
+            cell_height: 8,
+            cell_count_xz: 4,
+            cell_count_y: 48,
+            cell_noise_min_y: -8,
+            cell_start_block_x: x,
+            cell_start_block_z: z,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn slice_matches_tree() {
+        let df = tree();
+        let mut program = Program::new(Arc::new(Tape::compile(&df, Stage::Slice).unwrap()), Beardifier::default());
+
+        for (x, z) in [(0, 0), (-36, 1024), (4000, -772)] {
+            let mut expected = vec![0.0; 49];
+            let mut ctx = slice_ctx(x, z);
+            df.fill_array(&mut expected, &mut ctx);
+
+            let mut actual = vec![0.0; 49];
+            let mut program_ctx = slice_ctx(x, z);
+            program.fill(&mut actual, &mut program_ctx);
+
+            assert_eq!(bits(&expected), bits(&actual));
+            assert_eq!(ctx.cell_start_block_y, program_ctx.cell_start_block_y);
+        }
+    }
+
+    #[test]
+    fn cell_matches_tree() {
+        let interpolated = Interpolated::new(noise("test:a", 1, 1.0, 1.0), 48, 4);
+        let DensityFunctions::InterpolatedInner(inner) = &interpolated.0 else { unreachable!() };
+        let mut state = inner.state();
+        for (i, val) in [0.3, -0.2, 0.05, 0.7].into_iter().enumerate() {
+            state.slice0[i % 2][i / 2] = val;
+            state.slice1[i % 2][i / 2] = -val;
+        }
+        state.select_cell_yz(0, 0);
+
+        let df = DensityFunctions::Min(Box::new(Min::new(
+            DensityFunctions::Squeeze(Box::new(Squeeze::new(
+                DensityFunctions::MulConst(Box::new(MulConst::new(DensityFunctions::Interpolated(Box::new(interpolated)), 0.64)))
+            ))),
+            tree(),
+        )));
+        let mut program = Program::new(Arc::new(Tape::compile(&df, Stage::Cell).unwrap()), Beardifier::default());
+
+        let cell_ctx = || DensityFunctionContext {
+            filling_cell: true,
+            cell_width: 4,
+            cell_height: 8,
+            cell_start_block_x: 16,
+            cell_start_block_y: -24,
+            cell_start_block_z: -8,
+            interpolators: vec![state.clone()],
+            ..Default::default()
+        };
+
+        let mut expected = vec![0.0; 128];
+        df.fill_array(&mut expected, &mut cell_ctx());
+
+        let mut actual = vec![0.0; 128];
+        program.fill(&mut actual, &mut cell_ctx());
+
+        assert_eq!(bits(&expected), bits(&actual));
+    }
+
+    #[test]
+    fn identical_subtrees_are_evaluated_once() {
+        let df = DensityFunctions::Add(Box::new(Add::new(
+            DensityFunctions::Square(Box::new(Square::new(noise("test:a", 1, 1.0, 1.0)))),
+            DensityFunctions::Square(Box::new(Square::new(noise("test:a", 1, 1.0, 1.0)))),
+        )));
+        let tape = Tape::compile(&df, Stage::Slice).unwrap();
+
+        // Noise, square and add.
+        assert_eq!(3, tape.instruction_count());
+        assert_eq!(2, tape.register_count());
+    }
+}
@@ -4,7 +4,7 @@ use std::fmt::{Debug, Formatter};
 
 #[derive(Clone)]
 pub struct Abs {
-    argument: DensityFunctions,
+    pub(crate) argument: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -57,7 +57,7 @@ impl DensityFunction for Abs {
 
 #[derive(Clone)]
 pub struct Square {
-    argument: DensityFunctions,
+    pub(crate) argument: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -108,7 +108,7 @@ impl DensityFunction for Square {
 
 #[derive(Clone)]
 pub struct Cube {
-    argument: DensityFunctions,
+    pub(crate) argument: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -176,7 +176,7 @@ impl HalfNegative {
     }
 
     #[inline]
-    fn half_negative(val: f64) -> f64 {
+    pub(crate) fn half_negative(val: f64) -> f64 {
         if val > 0.0 { val } else { val * 0.5 }
     }
 }
@@ -215,7 +215,7 @@ impl DensityFunction for HalfNegative {
 
 #[derive(Clone)]
 pub struct QuarterNegative {
-    argument: DensityFunctions,
+    pub(crate) argument: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -234,7 +234,7 @@ impl QuarterNegative {
     }
 
     #[inline]
-    fn quarter_negative(val: f64) -> f64 {
+    pub(crate) fn quarter_negative(val: f64) -> f64 {
         if val > 0.0 { val } else { val * 0.25 }
     }
 }
@@ -273,7 +273,7 @@ impl DensityFunction for QuarterNegative {
 
 #[derive(Clone)]
 pub struct Squeeze {
-    argument: DensityFunctions,
+    pub(crate) argument: DensityFunctions,
     min_value: f64,
     max_value: f64,
 }
@@ -292,7 +292,7 @@ impl Squeeze {
     }
 
     #[inline]
-    fn squeeze(val: f64) -> f64 {
+    pub(crate) fn squeeze(val: f64) -> f64 {
         let val = val.clamp(-1.0, 1.0);
 
         val / 2.0 - val * val * val / 24.0
@@ -1,5 +1,6 @@
+use crate::noise::density::beardifier::Beardifier;
 use crate::noise::density::density::DensityFunctions;
-use crate::noise::density::misc::{Clamp, RangeChoice, WeirdScaledSampler, YClampedGradient};
+use crate::noise::density::misc::{Clamp, EndIslands, RangeChoice, WeirdScaledSampler, YClampedGradient};
 use crate::noise::json::deserializer::Deserializer;
 use crate::noise::json::Resolver;
 use serde_json::Value;
@@ -68,3 +69,19 @@ impl Deserializer<DensityFunctions> for WeirdScaledSamplerDeserializer {
         ))
     }
 }
+
+pub struct BeardifierDeserializer;
+
+impl Deserializer<DensityFunctions> for BeardifierDeserializer {
+    fn deserialize(&self, _: &Value, _: &Resolver<DensityFunctions>) -> anyhow::Result<DensityFunctions> {
+        Ok(DensityFunctions::Beardifier(Beardifier::default()))
+    }
+}
+
+pub struct EndIslandsDeserializer;
+
+impl Deserializer<DensityFunctions> for EndIslandsDeserializer {
+    fn deserialize(&self, _: &Value, _: &Resolver<DensityFunctions>) -> anyhow::Result<DensityFunctions> {
+        Ok(DensityFunctions::EndIslands(EndIslands::new(0)))
+    }
+}
@@ -6,7 +6,7 @@ use std::collections::HashMap;
 
 use crate::noise::json::deserializer::blend::{BlendAlphaDeserializer, BlendOffsetDeserializer};
 use crate::noise::json::deserializer::maker::{InterpolatedDeserializer, MarkerDeserializer};
-use crate::noise::json::deserializer::misc::{ClampDeserializer, RangeChoiceDeserializer, WeirdScaledSamplerDeserializer, YClampedGradientDeserializer};
+use crate::noise::json::deserializer::misc::{BeardifierDeserializer, ClampDeserializer, EndIslandsDeserializer, RangeChoiceDeserializer, WeirdScaledSamplerDeserializer, YClampedGradientDeserializer};
 use crate::noise::json::deserializer::noise::{BlendedNoiseDeserializer, OldBlendedNoiseDeserializer, ShiftADeserializer, ShiftBDeserializer};
 use crate::noise::json::deserializer::spline::SplineDeserializer;
 use crate::noise::json::deserializer::unary::{AbsDeserializer, CubeDeserializer, HalfNegativeDeserializer, QuarterNegativeDeserializer, SquareDeserializer, SqueezeDeserializer};
@@ -48,6 +48,8 @@ pub fn deserializers() -> HashMap<String, Box<dyn Deserializer<DensityFunctions>
         ("minecraft:shift_b".to_owned(), cast(ShiftBDeserializer)),
         ("minecraft:clamp".to_owned(), cast(ClampDeserializer)),
         ("minecraft:weird_scaled_sampler".to_owned(), cast(WeirdScaledSamplerDeserializer)),
+        ("minecraft:beardifier".to_owned(), cast(BeardifierDeserializer)),
+        ("minecraft:end_islands".to_owned(), cast(EndIslandsDeserializer)),
     ])
 }
 
@@ -7,7 +7,7 @@ use crate::noise::perlin::octave::MultiOctaveNoiseParameters;
 use crate::noise::perlin::DefaultNoise;
 use anyhow::anyhow;
 use serde_json::Value;
-use std::rc::Rc;
+use std::sync::Arc;
 
 impl<T> Resolvable<T> for NoiseHolder<DefaultNoise> {
     fn resolve(val: &Value, resolver: &Resolver<T>) -> anyhow::Result<Self> {
@@ -37,9 +37,9 @@ impl<T> Resolvable<T> for (String, MultiOctaveNoiseParameters) {
     }
 }
 
-impl<T> Resolvable<T> for Rc<NoiseHolder<DefaultNoise>> {
+impl<T> Resolvable<T> for Arc<NoiseHolder<DefaultNoise>> {
     fn resolve(val: &Value, resolver: &Resolver<T>) -> anyhow::Result<Self> {
-        Ok(Rc::new(NoiseHolder::resolve(val, resolver)?))
+        Ok(Arc::new(NoiseHolder::resolve(val, resolver)?))
     }
 }
 
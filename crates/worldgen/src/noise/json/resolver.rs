@@ -76,6 +76,15 @@ impl<T> Resolver<T> {
         }
     }
 
+    /// Same as `resolve_field()`, but returns `default` if the field is absent.
+    pub fn resolve_field_or<R: Resolvable<T>, S: AsRef<str>>(&self, json: &Value, field: S, default: R) -> anyhow::Result<R> {
+        match json {
+            Value::String(s) => self.resolve_field_or(&self.resolve_value(s.to_owned())?, field, default),
+            Value::Object(map) if !map.contains_key(field.as_ref()) => Ok(default),
+            _ => self.resolve_field(json, field)
+        }
+    }
+
     pub fn deserialize(&self, name: &String) -> anyhow::Result<T> {
         let value = self.resolve_value(name.clone())?;
 
@@ -1,3 +1,4 @@
+use crate::pack::DataPacks;
 use anyhow::anyhow;
 use serde_json::Value;
 use std::cell::RefCell;
@@ -6,6 +7,7 @@ use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{BufReader, ErrorKind};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub trait ValueResolver {
     fn resolve(&self, name: String) -> anyhow::Result<Value, Error>;
@@ -76,6 +78,32 @@ impl ValueResolver for FilesystemValueResolver {
     }
 }
 
+/// Resolves entries of the registry (e.g. `worldgen/noise`) from the data
+/// packs, so namespaced ids such as `mypack:foo` resolve to the right pack.
+pub struct DataPackValueResolver {
+    packs: Arc<DataPacks>,
+    registry: String,
+}
+
+impl DataPackValueResolver {
+    pub fn new(packs: Arc<DataPacks>, registry: &str) -> Self {
+        Self {
+            packs,
+            registry: registry.to_owned(),
+        }
+    }
+}
+
+impl ValueResolver for DataPackValueResolver {
+    fn resolve(&self, name: String) -> anyhow::Result<Value, Error> {
+        match self.packs.read_json(&self.registry, &name) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(Error::NotFound),
+            Err(err) => Err(Error::Other(anyhow!("Error during resolving \"{}\" value: {}", name, err))),
+        }
+    }
+}
+
 pub struct CascadeValueResolver {
     resolvers: Vec<Box<dyn ValueResolver>>,
 }
@@ -1,5 +1,12 @@
+use lazy_static::lazy_static;
 use std::ops::{Add, BitXor, Rem};
 
+lazy_static! {
+    static ref SIN: Vec<f32> = (0..65536)
+        .map(|i| (i as f64 * std::f64::consts::PI * 2.0 / 65536.0).sin() as f32)
+        .collect();
+}
+
 /// Linear interpolation
 #[inline]
 pub fn lerp(part: f64, from: f64, to: f64) -> f64 {
@@ -81,3 +88,40 @@ pub fn floor(x: f64) -> i32 {
 pub fn positive_mod(a: f64, b: f64) -> f64 {
     (a % b + b) % b
 }
+
+/// Sine looked up in the table of 65536 values, as vanilla does. Carvers depend
+/// on the exact results of this approximation.
+#[inline]
+pub fn sin(x: f32) -> f32 {
+    SIN[((x * 10430.378) as i32 & 0xFFFF) as usize]
+}
+
+/// Cosine looked up in the same table as [`sin`].
+#[inline]
+pub fn cos(x: f32) -> f32 {
+    SIN[((x * 10430.378 + 16384.0) as i32 & 0xFFFF) as usize]
+}
+
+/// Inverse square root approximated with a single Newton iteration, as
+/// vanilla `Mth.fastInvSqrt()` does. The beardifier depends on its results.
+#[inline]
+pub fn fast_inv_sqrt(x: f64) -> f64 {
+    let half = 0.5 * x;
+    let y = f64::from_bits((0x5FE6EB50C7B537AA - (x.to_bits() as i64 >> 1)) as u64);
+
+    y * (1.5 - half * y * y)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::math::{cos, sin};
+
+    #[test]
+    fn sin_table() {
+        assert_eq!(0.0, sin(0.0));
+        assert_eq!(1.0, sin(std::f32::consts::FRAC_PI_2));
+        assert_eq!(1.0, cos(0.0));
+        assert!((sin(1.0) - 1.0f32.sin()).abs() < 1e-4);
+        assert!((cos(-2.5) - (-2.5f32).cos()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::noise::perlin::octave::wrap;
+use spherix_math::vector::Vector3f;
+
+/// Number of positions a batched noise samples at once. Buffers of a batch
+/// are fixed size arrays on the stack, so the loops over them vectorize.
+pub const LANES: usize = 16;
+
+/// Positions of a batch, one coordinate after another. Lanes past the length
+/// are 0.
+#[derive(Clone, Copy)]
+pub struct Lanes {
+    len: usize,
+    pub x: [f64; LANES],
+    pub y: [f64; LANES],
+    pub z: [f64; LANES],
+}
+
+impl Default for Lanes {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            x: [0.0; LANES],
+            y: [0.0; LANES],
+            z: [0.0; LANES],
+        }
+    }
+}
+
+impl Lanes {
+    /// Lanes of the first `len` positions the function returns.
+    #[inline]
+    pub fn from_fn<F: FnMut(usize) -> (f64, f64, f64)>(len: usize, mut f: F) -> Self {
+        assert!(len <= LANES, "Batch of {} positions exceeds {} lanes", len, LANES);
+
+        let mut lanes = Self {
+            len,
+            ..Default::default()
+        };
+
+        for i in 0..len {
+            (lanes.x[i], lanes.y[i], lanes.z[i]) = f(i);
+        }
+
+        lanes
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Positions multiplied by the factor, as `Vector3f * f64` does.
+    #[inline]
+    pub fn scaled(&self, factor: f64) -> Self {
+        let mut lanes = *self;
+
+        for i in 0..LANES {
+            lanes.x[i] *= factor;
+            lanes.y[i] *= factor;
+            lanes.z[i] *= factor;
+        }
+
+        lanes
+    }
+
+    /// Positions multiplied by the factor and wrapped, as octaves sample them.
+    #[inline]
+    pub fn wrapped(&self, factor: f64) -> Self {
+        let mut lanes = *self;
+
+        for i in 0..LANES {
+            lanes.x[i] = wrap(self.x[i] * factor);
+            lanes.y[i] = wrap(self.y[i] * factor);
+            lanes.z[i] = wrap(self.z[i] * factor);
+        }
+
+        lanes
+    }
+}
+
+/// Noise, which samples a batch of positions at once. The values are
+/// identical to the ones of [`crate::noise::perlin::LegacyNoise::sample`]
+/// at each of the positions.
+pub trait BatchNoise {
+    /// Samples the lanes. Values of the lanes past the length are unspecified.
+    fn sample_lanes(&self, at: &Lanes, y_amp: f64, y_min: f64, out: &mut [f64; LANES]);
+
+    /// Samples the positions a batch of lanes at a time.
+    fn sample_batch(&self, at: &[Vector3f], y_amp: f64, y_min: f64, out: &mut [f64]) {
+        let mut values = [0.0; LANES];
+
+        for (at, out) in at.chunks(LANES).zip(out.chunks_mut(LANES)) {
+            let lanes = Lanes::from_fn(at.len(), |i| (at[i].x, at[i].y, at[i].z));
+
+            self.sample_lanes(&lanes, y_amp, y_min, &mut values);
+            out.copy_from_slice(&values[..out.len()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::perlin::batch::{BatchNoise, Lanes, LANES};
+    use crate::noise::perlin::noise::LegacyNoise;
+    use crate::noise::perlin::octave::MultiOctaveNoiseFactory;
+    use crate::noise::perlin::{DefaultNoise, GridNoise};
+    use crate::rng::XoroShiro;
+    use spherix_math::vector::Vector3f;
+
+    fn positions() -> Vec<Vector3f> {
+        (0..37)
+            .map(|i| Vector3f::new(i as f64 * 3.7 - 60.0, (i % 7) as f64 * -0.6, 1.0E7 - i as f64 * 0.013))
+            .collect()
+    }
+
+    #[test]
+    fn grid_noise_batch_matches_scalar() {
+        let noise = GridNoise::new(&mut XoroShiro::new(0xD128FF383ED163EB));
+        let positions = positions();
+
+        for (y_amp, y_min) in [(0.0, 0.0), (0.25, 0.1), (1.5, -2.0)] {
+            let mut actual = vec![0.0; positions.len()];
+            noise.sample_batch(&positions, y_amp, y_min, &mut actual);
+
+            for (at, actual) in positions.iter().zip(actual) {
+                assert_eq!(noise.sample(*at, y_amp, y_min).to_bits(), actual.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn grid_noise_slices_match_scalar() {
+        let noise = GridNoise::new(&mut XoroShiro::new(0xA809293));
+        let positions = positions();
+        let lanes = Lanes::from_fn(LANES, |i| (positions[i].x, positions[i].y, positions[i].z));
+        let y_min = std::array::from_fn(|i| i as f64 * 0.07 - 0.3);
+
+        let mut actual = [0.0; LANES];
+        noise.sample_slices(&lanes, 0.4, &y_min, &mut actual);
+
+        for i in 0..LANES {
+            assert_eq!(noise.sample(positions[i], 0.4, y_min[i]).to_bits(), actual[i].to_bits());
+        }
+    }
+
+    #[test]
+    fn double_noise_batch_matches_scalar() {
+        let noise = DefaultNoise::create(&mut XoroShiro::new(0x8C190F14101), &vec![0.64, 0.21, 0.0, 0.58, 1.0], -5);
+        let positions = positions();
+
+        let mut actual = vec![0.0; positions.len()];
+        noise.sample_batch(&positions, 0.0, 0.0, &mut actual);
+
+        for (at, actual) in positions.iter().zip(actual) {
+            assert_eq!(noise.sample(*at, 0.0, 0.0).to_bits(), actual.to_bits());
+        }
+    }
+}
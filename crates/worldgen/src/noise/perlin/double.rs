@@ -1,5 +1,8 @@
+use crate::noise::perlin::batch::{BatchNoise, Lanes, LANES};
+use crate::noise::perlin::grid::LegacyMultiOctaveGridNoise;
 use crate::noise::perlin::noise::{LegacyNoise, Noise, SupremumNoise};
-use crate::noise::perlin::octave::MultiOctaveNoiseFactory;
+use crate::noise::perlin::octave::{MultiOctaveNoise, MultiOctaveNoiseFactory, MultiOctaveNoiseParameters};
+use crate::noise::perlin::GridNoise;
 use crate::rng::{Rng, RngForkable};
 use spherix_math::vector::Vector3f;
 
@@ -36,6 +39,22 @@ where
     }
 }
 
+impl<N> BatchNoise for DoubleMultiOctavePerlinNoise<N>
+where
+    N: BatchNoise + SupremumNoise + Clone
+{
+    fn sample_lanes(&self, at: &Lanes, y_amp: f64, y_min: f64, out: &mut [f64; LANES]) {
+        let mut second = [0.0; LANES];
+
+        self.first.sample_lanes(at, y_amp, y_min, out);
+        self.second.sample_lanes(&at.scaled(Self::POS_MULTIPLIER), y_amp, y_min, &mut second);
+
+        for i in 0..LANES {
+            out[i] = (out[i] + second[i]) * self.value_factor;
+        }
+    }
+}
+
 impl<N> SupremumNoise for DoubleMultiOctavePerlinNoise<N>
 where
     N: SupremumNoise + Clone
@@ -53,22 +72,19 @@ where
         let first = N::create(rng, amplitudes, first_octave);
         let second = N::create(rng, amplitudes, first_octave);
 
-        let mut j = i32::MAX;
-        let mut k = i32::MIN;
+        Self::new(first, second, Self::value_factor(amplitudes))
+    }
+}
 
-        for (i, amp) in amplitudes.iter().enumerate() {
-            if *amp != 0.0 {
-                let i = i as i32;
-                j = j.min(i);
-                k = k.max(i);
-            }
-        }
+impl DoubleMultiOctavePerlinNoise<MultiOctaveNoise<GridNoise>> {
+    /// Equivalent of vanilla `NormalNoise::createLegacyNetherBiome()`. Octaves
+    /// are created by the legacy factory, which the legacy random source uses
+    /// for the temperature and vegetation noises.
+    pub fn create_legacy_nether_biome<R: RngForkable>(rng: &mut R, params: &MultiOctaveNoiseParameters) -> Self {
+        let first = LegacyMultiOctaveGridNoise::create(rng, &params.amplitudes, params.first_octave);
+        let second = LegacyMultiOctaveGridNoise::create(rng, &params.amplitudes, params.first_octave);
 
-        Self::new(
-            first,
-            second,
-            0.16666666666666666 / Self::expected_deviation(k - j)
-        )
+        Self::new(first.into(), second.into(), Self::value_factor(&params.amplitudes))
     }
 }
 
@@ -87,6 +103,22 @@ where
         }
     }
 
+    fn value_factor(amplitudes: &[f64]) -> f64 {
+        let mut j = i32::MAX;
+        let mut k = i32::MIN;
+
+        for (i, amp) in amplitudes.iter().enumerate() {
+            if *amp != 0.0 {
+                let i = i as i32;
+                j = j.min(i);
+                k = k.max(i);
+            }
+        }
+
+        // Without octaves the difference overflows, as it does in vanilla.
+        0.16666666666666666 / Self::expected_deviation(k.wrapping_sub(j))
+    }
+
     fn expected_deviation(x: i32) -> f64 {
         0.1 * (1.0 + 1.0 / (x + 1) as f64)
     }
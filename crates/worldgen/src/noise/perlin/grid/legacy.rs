@@ -1,7 +1,7 @@
 use crate::noise::perlin::grid::noise::GridNoise;
 use crate::noise::perlin::noise::{LegacyNoise, Noise, SupremumNoise};
 use crate::noise::perlin::octave;
-use crate::noise::perlin::octave::{MultiOctaveNoiseFactory, NoiseOctave, NoiseOctaves};
+use crate::noise::perlin::octave::{MultiOctaveNoise, MultiOctaveNoiseFactory, NoiseOctave, NoiseOctaves};
 use crate::rng::{Rng, RngForkable};
 use core::panic;
 use spherix_math::vector::Vector3f;
@@ -107,6 +107,17 @@ impl LegacyMultiOctaveGridNoise {
     }
 }
 
+/// Legacy octaves differ in their creation only, so the noise samples like
+/// the regular one.
+impl From<LegacyMultiOctaveGridNoise> for MultiOctaveNoise<GridNoise> {
+    fn from(noise: LegacyMultiOctaveGridNoise) -> Self {
+        Self {
+            octaves: noise.octaves,
+            max_value: noise.max_value,
+        }
+    }
+}
+
 impl Noise<Vector3f> for LegacyMultiOctaveGridNoise {
     fn sample(&self, at: Vector3f) -> f64 {
         self.octaves
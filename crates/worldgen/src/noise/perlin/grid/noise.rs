@@ -1,4 +1,5 @@
 use crate::noise::math::lerp3;
+use crate::noise::perlin::batch::{BatchNoise, Lanes, LANES};
 use crate::noise::perlin::inner::NoiseInner;
 use crate::noise::perlin::{LegacyNoise, Noise};
 use crate::rng::Rng;
@@ -21,27 +22,45 @@ impl GridNoise {
         Self(NoiseInner::new(rng))
     }
 
-    /// Sample the noise at the 8 corners of a cube and interpolate between them
-    fn sample_and_lerp(&self, floor_x: i32, floor_y: i32, floor_z: i32, frac_x: f64, frac_y: f64, frac_z: f64, frac_y_orig: f64) -> f64 {
+    /// Permutations of the 8 corners of a cube, in the order 000, 100, 010,
+    /// 110, 001, 101, 011 and 111.
+    #[inline]
+    fn corner_hashes(&self, floor_x: i32, floor_y: i32, floor_z: i32) -> [i32; 8] {
         let perm_x = self.0.permutation(floor_x);
         let perm_x1 = self.0.permutation(floor_x + 1);
-        
+
         let perm_xy = self.0.permutation(perm_x + floor_y);
         let perm_xy1 = self.0.permutation(perm_x + floor_y + 1);
 
         let perm_x1_y = self.0.permutation(perm_x1 + floor_y);
         let perm_x1_y_inc = self.0.permutation(perm_x1 + floor_y + 1);
 
+        [
+            self.0.permutation(perm_xy + floor_z),
+            self.0.permutation(perm_x1_y + floor_z),
+            self.0.permutation(perm_xy1 + floor_z),
+            self.0.permutation(perm_x1_y_inc + floor_z),
+            self.0.permutation(perm_xy + floor_z + 1),
+            self.0.permutation(perm_x1_y + floor_z + 1),
+            self.0.permutation(perm_xy1 + floor_z + 1),
+            self.0.permutation(perm_x1_y_inc + floor_z + 1),
+        ]
+    }
+
+    /// Sample the noise at the 8 corners of a cube and interpolate between them
+    fn sample_and_lerp(&self, floor_x: i32, floor_y: i32, floor_z: i32, frac_x: f64, frac_y: f64, frac_z: f64, frac_y_orig: f64) -> f64 {
+        let hashes = self.corner_hashes(floor_x, floor_y, floor_z);
+
         // Calculate the dot products at each corner of the cube
-        let value000 = Self::gradient_dot(self.0.permutation(perm_xy + floor_z), frac_x, frac_y, frac_z);
-        let value100 = Self::gradient_dot(self.0.permutation(perm_x1_y + floor_z), frac_x - 1.0, frac_y, frac_z);
-        let value010 = Self::gradient_dot(self.0.permutation(perm_xy1 + floor_z), frac_x, frac_y - 1.0, frac_z);
-        let value110 = Self::gradient_dot(self.0.permutation(perm_x1_y_inc + floor_z), frac_x - 1.0, frac_y - 1.0, frac_z);
+        let value000 = Self::gradient_dot(hashes[0], frac_x, frac_y, frac_z);
+        let value100 = Self::gradient_dot(hashes[1], frac_x - 1.0, frac_y, frac_z);
+        let value010 = Self::gradient_dot(hashes[2], frac_x, frac_y - 1.0, frac_z);
+        let value110 = Self::gradient_dot(hashes[3], frac_x - 1.0, frac_y - 1.0, frac_z);
 
-        let value001 = Self::gradient_dot(self.0.permutation(perm_xy + floor_z + 1), frac_x, frac_y, frac_z - 1.0);
-        let value101 = Self::gradient_dot(self.0.permutation(perm_x1_y + floor_z + 1), frac_x - 1.0, frac_y, frac_z - 1.0);
-        let value011 = Self::gradient_dot(self.0.permutation(perm_xy1 + floor_z + 1), frac_x, frac_y - 1.0, frac_z - 1.0);
-        let value111 = Self::gradient_dot(self.0.permutation(perm_x1_y_inc + floor_z + 1), frac_x - 1.0, frac_y - 1.0, frac_z - 1.0);
+        let value001 = Self::gradient_dot(hashes[4], frac_x, frac_y, frac_z - 1.0);
+        let value101 = Self::gradient_dot(hashes[5], frac_x - 1.0, frac_y, frac_z - 1.0);
+        let value011 = Self::gradient_dot(hashes[6], frac_x, frac_y - 1.0, frac_z - 1.0);
+        let value111 = Self::gradient_dot(hashes[7], frac_x - 1.0, frac_y - 1.0, frac_z - 1.0);
 
         // Smooth the interpolation parameters using smoothstep function
         let smooth_x = Self::smooth_step_improved(frac_x);
@@ -52,6 +71,80 @@ impl GridNoise {
         lerp3(smooth_x, smooth_y, smooth_z, value000, value100, value010, value110, value001, value101, value011, value111)
     }
 
+    /// Batched [`LegacyNoise::sample`] with the vertical slicing minimum of
+    /// each lane. The permutations are looked up first, so the arithmetic
+    /// runs over plain arrays.
+    pub fn sample_slices(&self, at: &Lanes, y_amp: f64, y_min: &[f64; LANES], out: &mut [f64; LANES]) {
+        let mut floor = [(0, 0, 0); LANES];
+        let mut x_frac = [0.0; LANES];
+        let mut y_frac = [0.0; LANES];
+        let mut z_frac = [0.0; LANES];
+
+        for i in 0..LANES {
+            let shifted_x = at.x[i] + self.0.x_o;
+            let shifted_y = at.y[i] + self.0.y_o;
+            let shifted_z = at.z[i] + self.0.z_o;
+
+            let floor_x = shifted_x.floor() as i32;
+            let floor_y = shifted_y.floor() as i32;
+            let floor_z = shifted_z.floor() as i32;
+
+            floor[i] = (floor_x, floor_y, floor_z);
+            x_frac[i] = shifted_x - floor_x as f64;
+            y_frac[i] = shifted_y - floor_y as f64;
+            z_frac[i] = shifted_z - floor_z as f64;
+        }
+
+        let mut slice_value = [0.0; LANES];
+        if y_amp != 0.0 {
+            for i in 0..LANES {
+                let effective_y = if y_min[i] >= 0.0 && y_min[i] < y_frac[i] { y_min[i] } else { y_frac[i] };
+
+                slice_value[i] = (effective_y / y_amp + Self::EPS).floor() * y_amp;
+            }
+        }
+
+        let mut gradients = [[(0.0, 0.0, 0.0); LANES]; 8];
+        for i in 0..LANES {
+            let (floor_x, floor_y, floor_z) = floor[i];
+            let hashes = self.corner_hashes(floor_x, floor_y, floor_z);
+
+            for (corner, hash) in hashes.into_iter().enumerate() {
+                gradients[corner][i] = NoiseInner::GRADIENTS[hash as usize & 15];
+            }
+        }
+
+        for i in 0..LANES {
+            let frac_x = x_frac[i];
+            let frac_y = y_frac[i] - slice_value[i];
+            let frac_z = z_frac[i];
+
+            let value000 = NoiseInner::dot(gradients[0][i], frac_x, frac_y, frac_z);
+            let value100 = NoiseInner::dot(gradients[1][i], frac_x - 1.0, frac_y, frac_z);
+            let value010 = NoiseInner::dot(gradients[2][i], frac_x, frac_y - 1.0, frac_z);
+            let value110 = NoiseInner::dot(gradients[3][i], frac_x - 1.0, frac_y - 1.0, frac_z);
+
+            let value001 = NoiseInner::dot(gradients[4][i], frac_x, frac_y, frac_z - 1.0);
+            let value101 = NoiseInner::dot(gradients[5][i], frac_x - 1.0, frac_y, frac_z - 1.0);
+            let value011 = NoiseInner::dot(gradients[6][i], frac_x, frac_y - 1.0, frac_z - 1.0);
+            let value111 = NoiseInner::dot(gradients[7][i], frac_x - 1.0, frac_y - 1.0, frac_z - 1.0);
+
+            out[i] = lerp3(
+                Self::smooth_step_improved(frac_x),
+                Self::smooth_step_improved(y_frac[i]),
+                Self::smooth_step_improved(frac_z),
+                value000,
+                value100,
+                value010,
+                value110,
+                value001,
+                value101,
+                value011,
+                value111,
+            );
+        }
+    }
+
     /// Calculate the dot product of a gradient vector and a coordinate vector
     #[inline]
     pub fn gradient_dot(index: i32, x: f64, y: f64, z: f64) -> f64 {
@@ -114,6 +207,12 @@ impl LegacyNoise<Vector3f> for GridNoise {
     }
 }
 
+impl BatchNoise for GridNoise {
+    fn sample_lanes(&self, at: &Lanes, y_amp: f64, y_min: f64, out: &mut [f64; LANES]) {
+        self.sample_slices(at, y_amp, &[y_min; LANES], out);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::noise::perlin::grid::noise::GridNoise;
@@ -1,3 +1,4 @@
+use crate::noise::perlin::batch::{BatchNoise, Lanes, LANES};
 use crate::noise::perlin::grid::noise::GridNoise;
 use crate::noise::perlin::noise::{LegacyNoise, Noise};
 use crate::noise::perlin::octave;
@@ -63,6 +64,28 @@ impl LegacyNoise<Vector3f> for MultiOctaveNoise<GridNoise> {
     }
 }
 
+impl BatchNoise for MultiOctaveNoise<GridNoise> {
+    fn sample_lanes(&self, at: &Lanes, y_amp: f64, y_min: f64, out: &mut [f64; LANES]) {
+        // The scalar sampling sums the octaves with `Iterator::sum`, which
+        // starts at -0.0, so the lanes keep the sign of a zero sum the same.
+        *out = [-0.0; LANES];
+        let mut values = [0.0; LANES];
+
+        for octave in &self.octaves {
+            octave.noise.sample_lanes(
+                &at.wrapped(octave.lacunarity),
+                y_amp * octave.lacunarity,
+                y_min * octave.lacunarity,
+                &mut values,
+            );
+
+            for i in 0..LANES {
+                out[i] += octave.amplitude * values[i];
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::noise::perlin::noise::Noise;
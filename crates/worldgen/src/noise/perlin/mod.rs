@@ -4,7 +4,9 @@ pub mod double;
 pub mod inner;
 pub mod simplex;
 pub mod grid;
+pub mod batch;
 
+pub use batch::{BatchNoise, Lanes, LANES};
 pub use double::DoubleMultiOctavePerlinNoise;
 pub use grid::{GridNoise, LegacyMultiOctaveGridNoise};
 pub use noise::{LegacyNoise, Noise};
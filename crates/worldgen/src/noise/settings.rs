@@ -16,6 +16,8 @@ pub struct NoiseSettings {
     pub default_block: Arc<BlockState>,
     pub default_fluid: Arc<BlockState>,
     pub sea_level: i32,
+    pub aquifers_enabled: bool,
+    pub ore_veins_enabled: bool,
     pub noise_height: u32,
     pub noise_min_y: i32,
     pub noise_size_horizontal: i32,
@@ -99,6 +101,42 @@ impl NoiseSettings {
             return Err(anyhow!("No \"sea_level\" key"))
         };
 
+        let aquifers_enabled = if map.contains_key("aquifers_enabled") {
+            let aquifers_enabled = map.get("aquifers_enabled").unwrap();
+
+            let Value::Bool(aquifers_enabled) = aquifers_enabled else {
+                return Err(anyhow!("Expected boolean, found {:?}", json))
+            };
+
+            *aquifers_enabled
+        } else {
+            return Err(anyhow!("No \"aquifers_enabled\" key"))
+        };
+
+        let use_legacy_random_source = if map.contains_key("legacy_random_source") {
+            let legacy_random_source = map.get("legacy_random_source").unwrap();
+
+            let Value::Bool(legacy_random_source) = legacy_random_source else {
+                return Err(anyhow!("Expected boolean, found {:?}", json))
+            };
+
+            *legacy_random_source
+        } else {
+            return Err(anyhow!("No \"legacy_random_source\" key"))
+        };
+
+        let ore_veins_enabled = if map.contains_key("ore_veins_enabled") {
+            let ore_veins_enabled = map.get("ore_veins_enabled").unwrap();
+
+            let Value::Bool(ore_veins_enabled) = ore_veins_enabled else {
+                return Err(anyhow!("Expected boolean, found {:?}", json))
+            };
+
+            *ore_veins_enabled
+        } else {
+            return Err(anyhow!("No \"ore_veins_enabled\" key"))
+        };
+
         let noise = if map.contains_key("noise") {
             let noise = map.get("noise").unwrap();
 
@@ -161,10 +199,12 @@ impl NoiseSettings {
 
         Ok(Self {
             router: NoiseRouter::from_json(noise_router_json, resolver)?,
-            use_legacy_random_source: false, // TODO: deserialize this field!
+            use_legacy_random_source,
             default_block: palette.get_default_obj_by_index(&default_block).unwrap(),
             default_fluid: palette.get_default_obj_by_index(&default_fluid).unwrap(),
             sea_level,
+            aquifers_enabled,
+            ore_veins_enabled,
             noise_height,
             noise_min_y,
             noise_size_horizontal,
@@ -180,3 +220,17 @@ impl NoiseSettings {
         quart_pos_to_block(self.noise_size_horizontal) as u32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::generator::NoiseBasedChunkGenerator;
+    use crate::noise::settings::NoiseSettings;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn settings_are_shared_between_threads() {
+        assert_send_sync::<NoiseSettings>();
+        assert_send_sync::<NoiseBasedChunkGenerator>();
+    }
+}
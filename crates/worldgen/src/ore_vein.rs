@@ -0,0 +1,222 @@
+use crate::material::BlockStateFiller;
+use crate::noise::density::density::{DensityFunction, DensityFunctionContext, DensityFunctions};
+use crate::noise::math::clamped_map;
+use crate::rng::{Rng, RngPos, WorldgenRngPos};
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::sync::Arc;
+
+struct VeinType {
+    ore: Arc<BlockState>,
+    raw_ore_block: Arc<BlockState>,
+    filler: Arc<BlockState>,
+    min_y: i32,
+    max_y: i32,
+}
+
+impl VeinType {
+    fn new(palette: &BlockGlobalPalette, ore: &'static Block, raw_ore_block: &'static Block, filler: &'static Block, min_y: i32, max_y: i32) -> Self {
+        Self {
+            ore: palette.get_default_obj_by_index(&ore).unwrap(),
+            raw_ore_block: palette.get_default_obj_by_index(&raw_ore_block).unwrap(),
+            filler: palette.get_default_obj_by_index(&filler).unwrap(),
+            min_y,
+            max_y,
+        }
+    }
+}
+
+/// Generates large ore veins: copper veins in granite above y=0 and iron veins
+/// in tuff deep below.
+///
+/// The `vein_toggle` density function selects the type of the vein (positive
+/// values for copper, negative for iron) and its absolute value determines
+/// whether the block belongs to a vein at all. The `vein_ridged` function shapes
+/// veins, while `vein_gap` makes holes in the ore.
+pub struct OreVeinifier {
+    vein_toggle: DensityFunctions,
+    vein_ridged: DensityFunctions,
+    vein_gap: DensityFunctions,
+    rng: Arc<WorldgenRngPos>,
+    copper: VeinType,
+    iron: VeinType,
+}
+
+impl OreVeinifier {
+    const VEININESS_THRESHOLD: f64 = 0.4f32 as f64;
+    const EDGE_ROUNDOFF_BEGIN: f64 = 20.0;
+    const MAX_EDGE_ROUNDOFF: f64 = 0.2;
+    const VEIN_SOLIDNESS: f32 = 0.7;
+    const MIN_RICHNESS: f64 = 0.1f32 as f64;
+    const MAX_RICHNESS: f64 = 0.3f32 as f64;
+    const MAX_RICHNESS_THRESHOLD: f64 = 0.6f32 as f64;
+    const CHANCE_OF_RAW_ORE_BLOCK: f32 = 0.02;
+    const SKIP_ORE_IF_GAP_NOISE_IS_BELOW: f64 = -0.3f32 as f64;
+
+    pub fn new(
+        vein_toggle: DensityFunctions,
+        vein_ridged: DensityFunctions,
+        vein_gap: DensityFunctions,
+        rng: Arc<WorldgenRngPos>,
+        palette: &BlockGlobalPalette,
+    ) -> Self {
+        Self {
+            vein_toggle,
+            vein_ridged,
+            vein_gap,
+            rng,
+            copper: VeinType::new(palette, Block::COPPER_ORE, Block::RAW_COPPER_BLOCK, Block::GRANITE, 0, 50),
+            iron: VeinType::new(palette, Block::DEEPSLATE_IRON_ORE, Block::RAW_IRON_BLOCK, Block::TUFF, -60, -8),
+        }
+    }
+}
+
+impl BlockStateFiller for OreVeinifier {
+    fn calculate(&self, ctx: &mut DensityFunctionContext) -> Option<Arc<BlockState>> {
+        let pos = ctx.pos();
+
+        let toggle = self.vein_toggle.sample(pos, ctx);
+        let vein_type = if toggle > 0.0 {
+            &self.copper
+        } else {
+            &self.iron
+        };
+
+        let veininess = toggle.abs();
+        let distance_to_top = vein_type.max_y - pos.y;
+        let distance_to_bottom = pos.y - vein_type.min_y;
+        if distance_to_bottom < 0 || distance_to_top < 0 {
+            return None
+        }
+
+        // Veins are thinning out near the bounds of the height range.
+        let distance_to_edge = distance_to_top.min(distance_to_bottom);
+        let edge_roundoff = clamped_map(distance_to_edge as f64, 0.0, Self::EDGE_ROUNDOFF_BEGIN, -Self::MAX_EDGE_ROUNDOFF, 0.0);
+        if veininess + edge_roundoff < Self::VEININESS_THRESHOLD {
+            return None
+        }
+
+        let mut rng = self.rng.at(pos);
+        if rng.next_f32() > Self::VEIN_SOLIDNESS {
+            return None
+        }
+
+        if self.vein_ridged.sample(pos, ctx) >= 0.0 {
+            return None
+        }
+
+        let richness = clamped_map(
+            veininess,
+            Self::VEININESS_THRESHOLD,
+            Self::MAX_RICHNESS_THRESHOLD,
+            Self::MIN_RICHNESS,
+            Self::MAX_RICHNESS
+        );
+
+        if (rng.next_f32() as f64) < richness && self.vein_gap.sample(pos, ctx) > Self::SKIP_ORE_IF_GAP_NOISE_IS_BELOW {
+            if rng.next_f32() < Self::CHANCE_OF_RAW_ORE_BLOCK {
+                Some(vein_type.raw_ore_block.clone())
+            } else {
+                Some(vein_type.ore.clone())
+            }
+        } else {
+            Some(vein_type.filler.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::BlockStateFiller;
+    use crate::noise::density::density::{DensityFunctionContext, DensityFunctions};
+    use crate::noise::density::misc::Const;
+    use crate::ore_vein::OreVeinifier;
+    use crate::rng::{RngForkable, WorldgenRng};
+    use spherix_world::block::block::Block;
+    use spherix_world::block::state::BlockState;
+    use spherix_world::block::variant::VariantVec;
+    use spherix_world::chunk::palette::global::GlobalId;
+    use spherix_world::chunk::palette::BlockGlobalPalette;
+    use std::sync::Arc;
+
+    fn palette() -> BlockGlobalPalette {
+        let mut palette = BlockGlobalPalette::new(6);
+        let blocks = [
+            Block::COPPER_ORE,
+            Block::RAW_COPPER_BLOCK,
+            Block::GRANITE,
+            Block::DEEPSLATE_IRON_ORE,
+            Block::RAW_IRON_BLOCK,
+            Block::TUFF
+        ];
+
+        for (i, block) in blocks.into_iter().enumerate() {
+            palette.insert(GlobalId(i as u16), BlockState::new(block, true, VariantVec::empty()));
+        }
+
+        palette
+    }
+
+    fn veinifier(toggle: f64, ridged: f64) -> OreVeinifier {
+        OreVeinifier::new(
+            DensityFunctions::Const(Const::new(toggle)),
+            DensityFunctions::Const(Const::new(ridged)),
+            DensityFunctions::Const(Const::new(1.0)),
+            Arc::new(WorldgenRng::new(42, false).fork_pos()),
+            &palette()
+        )
+    }
+
+    /// Collects blocks of the veinifier in the 16x16 layer at the given Y.
+    fn layer(veinifier: &OreVeinifier, y: i32) -> Vec<Option<&'static Block>> {
+        let mut ctx = DensityFunctionContext {
+            cell_start_block_y: y,
+            ..Default::default()
+        };
+
+        let mut blocks = Vec::new();
+        for x in 0..16 {
+            for z in 0..16 {
+                ctx.cell_start_block_x = x;
+                ctx.cell_start_block_z = z;
+
+                blocks.push(veinifier.calculate(&mut ctx).map(|state| state.block()));
+            }
+        }
+
+        blocks
+    }
+
+    #[test]
+    fn copper_vein() {
+        let blocks = layer(&veinifier(1.0, -1.0), 25);
+
+        assert!(blocks.iter().all(|block| match block {
+            None => true,
+            Some(block) => [Block::COPPER_ORE, Block::RAW_COPPER_BLOCK, Block::GRANITE].contains(block)
+        }));
+        assert!(blocks.contains(&Some(Block::GRANITE)));
+        assert!(blocks.contains(&Some(Block::COPPER_ORE)));
+        assert!(blocks.contains(&None));
+
+        // Out of the copper height range.
+        assert!(layer(&veinifier(1.0, -1.0), 60).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn iron_vein() {
+        let blocks = layer(&veinifier(-1.0, -1.0), -30);
+
+        assert!(blocks.iter().all(|block| match block {
+            None => true,
+            Some(block) => [Block::DEEPSLATE_IRON_ORE, Block::RAW_IRON_BLOCK, Block::TUFF].contains(block)
+        }));
+        assert!(blocks.contains(&Some(Block::TUFF)));
+
+        // Ridges are outside of veins.
+        assert!(layer(&veinifier(-1.0, 1.0), -30).iter().all(Option::is_none));
+        // Iron veins don't reach the surface.
+        assert!(layer(&veinifier(-1.0, -1.0), 0).iter().all(Option::is_none));
+    }
+}
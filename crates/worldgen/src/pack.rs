@@ -0,0 +1,364 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// Data pack, i.e. a folder or a zip archive with `pack.mcmeta` next to the
+/// `data` folder. Paths within the pack are separated by `/`.
+pub enum DataPack {
+    Folder(PathBuf),
+    Zip {
+        path: PathBuf,
+        archive: Mutex<ZipArchive<File>>,
+    },
+}
+
+impl DataPack {
+    /// Opens the folder or the `.zip` file.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let pack = if path.is_dir() {
+            DataPack::Folder(path.to_path_buf())
+        } else if path.extension().is_some_and(|extension| extension == "zip") {
+            let file = File::open(path).map_err(|e| anyhow!("Unable to open {}: {}", path.display(), e))?;
+
+            DataPack::Zip {
+                path: path.to_path_buf(),
+                archive: Mutex::new(
+                    ZipArchive::new(file).map_err(|e| anyhow!("Unable to read {}: {}", path.display(), e))?
+                ),
+            }
+        } else {
+            return Err(anyhow!("Data pack {} is neither a folder nor a zip file", path.display()))
+        };
+
+        if pack.read("pack.mcmeta")?.is_none() {
+            return Err(anyhow!("No pack.mcmeta in data pack {}", path.display()))
+        }
+
+        Ok(pack)
+    }
+
+    /// Output of the vanilla data generator, which has no `pack.mcmeta`.
+    pub fn vanilla(path: PathBuf) -> Self {
+        DataPack::Folder(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            DataPack::Folder(path) => path,
+            DataPack::Zip { path, .. } => path,
+        }
+    }
+
+    /// Contents of the file, if the pack has it.
+    pub fn read(&self, path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut bytes = Vec::new();
+
+        match self {
+            DataPack::Folder(root) => match File::open(root.join(path)) {
+                Ok(mut file) => {
+                    file.read_to_end(&mut bytes)?;
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(anyhow!("Unable to open {} in {}: {}", path, root.display(), e))
+            },
+            DataPack::Zip { path: zip_path, archive } => match archive.lock().unwrap().by_name(path) {
+                Ok(mut file) => {
+                    file.read_to_end(&mut bytes)?;
+                }
+                Err(ZipError::FileNotFound) => return Ok(None),
+                Err(e) => return Err(anyhow!("Unable to open {} in {}: {}", path, zip_path.display(), e))
+            },
+        }
+
+        Ok(Some(bytes))
+    }
+
+    /// Paths of all the files within the folder of the pack, relative to it.
+    pub fn list(&self, dir: &str) -> anyhow::Result<Vec<String>> {
+        match self {
+            DataPack::Folder(root) => {
+                let mut files = Vec::new();
+                list_folder(&root.join(dir), "", &mut files)?;
+
+                Ok(files)
+            }
+            DataPack::Zip { archive, .. } => {
+                let prefix = format!("{}/", dir.trim_end_matches('/'));
+                let archive = archive.lock().unwrap();
+
+                Ok(archive
+                    .file_names()
+                    .filter(|name| !name.ends_with('/'))
+                    .filter_map(|name| name.strip_prefix(&prefix))
+                    .map(str::to_owned)
+                    .collect())
+            }
+        }
+    }
+
+    /// Namespaces of the `data` folder.
+    pub fn namespaces(&self) -> anyhow::Result<BTreeSet<String>> {
+        match self {
+            DataPack::Folder(root) => {
+                let dir = root.join("data");
+                let entries = match std::fs::read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeSet::new()),
+                    Err(e) => return Err(anyhow!("Unable to read {}: {}", dir.display(), e))
+                };
+
+                let mut namespaces = BTreeSet::new();
+                for entry in entries {
+                    let entry = entry?;
+
+                    if entry.file_type()?.is_dir() {
+                        namespaces.extend(entry.file_name().to_str().map(str::to_owned));
+                    }
+                }
+
+                Ok(namespaces)
+            }
+            DataPack::Zip { archive, .. } => {
+                let archive = archive.lock().unwrap();
+
+                Ok(archive
+                    .file_names()
+                    .filter_map(|name| name.strip_prefix("data/")?.split_once('/'))
+                    .map(|(namespace, _)| namespace.to_owned())
+                    .collect())
+            }
+        }
+    }
+}
+
+fn list_folder(dir: &Path, prefix: &str, files: &mut Vec<String>) -> anyhow::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(anyhow!("Unable to read {}: {}", dir.display(), e))
+    };
+
+    for entry in entries {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue
+        };
+
+        if entry.file_type()?.is_dir() {
+            list_folder(&entry.path(), &format!("{}{}/", prefix, name), files)?;
+        } else {
+            files.push(format!("{}{}", prefix, name));
+        }
+    }
+
+    Ok(())
+}
+
+/// Data packs layered over each other. Files of the later packs take
+/// precedence, except for tags, whose values are merged, unless the tag
+/// replaces them.
+pub struct DataPacks {
+    packs: Vec<DataPack>,
+}
+
+impl DataPacks {
+    pub fn new(packs: Vec<DataPack>) -> Self {
+        Self {
+            packs,
+        }
+    }
+
+    /// The vanilla data followed by the packs at the given paths.
+    pub fn load(vanilla: PathBuf, paths: &[PathBuf]) -> anyhow::Result<Self> {
+        let mut packs = vec![DataPack::vanilla(vanilla)];
+
+        for path in paths {
+            packs.push(DataPack::open(path)?);
+        }
+
+        Ok(Self::new(packs))
+    }
+
+    pub fn packs(&self) -> &[DataPack] {
+        &self.packs
+    }
+
+    /// Contents of the entry of the registry, e.g. `worldgen/noise` and
+    /// `mypack:foo`, which is `data/mypack/worldgen/noise/foo.json`, from
+    /// the topmost pack, which has it.
+    pub fn read(&self, registry: &str, id: &str, extension: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = resource_path(registry, id, extension);
+
+        for pack in self.packs.iter().rev() {
+            if let Some(bytes) = pack.read(&path)? {
+                return Ok(Some(bytes))
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn read_json(&self, registry: &str, id: &str) -> anyhow::Result<Option<Value>> {
+        self.read(registry, id, "json")?
+            .map(|bytes| serde_json::from_slice(&bytes).map_err(|e| anyhow!("Unable to read {} {}: {}", registry, id, e)))
+            .transpose()
+    }
+
+    /// Same as [`DataPacks::read_json()`], but the entry is required.
+    pub fn json(&self, registry: &str, id: &str) -> anyhow::Result<Value> {
+        self.read_json(registry, id)?
+            .ok_or_else(|| anyhow!("No {} {} in data packs", registry, id))
+    }
+
+    /// Ids of all the entries of the registry in all the packs. They are
+    /// ordered by path, then by namespace, as vanilla orders them.
+    pub fn ids(&self, registry: &str, extension: &str) -> anyhow::Result<Vec<String>> {
+        let suffix = format!(".{}", extension);
+        let mut ids = BTreeSet::new();
+
+        for pack in &self.packs {
+            for namespace in pack.namespaces()? {
+                for path in pack.list(&format!("data/{}/{}", namespace, registry))? {
+                    if let Some(path) = path.strip_suffix(&suffix) {
+                        ids.insert((path.to_owned(), namespace.clone()));
+                    }
+                }
+            }
+        }
+
+        Ok(ids
+            .into_iter()
+            .map(|(path, namespace)| format!("{}:{}", namespace, path))
+            .collect())
+    }
+
+    /// Entries of the tag of the registry (e.g. `tags/blocks`) along with
+    /// whether they are required. Tag files of all the packs are merged from
+    /// the bottom up, a file with `"replace": true` discards the entries of
+    /// the packs below.
+    pub fn tag_entries(&self, registry: &str, id: &str) -> anyhow::Result<Vec<(String, bool)>> {
+        let path = resource_path(registry, id, "json");
+        let mut entries: Vec<(String, bool)> = Vec::new();
+        let mut found = false;
+
+        for pack in &self.packs {
+            let Some(bytes) = pack.read(&path)? else {
+                continue
+            };
+
+            let json: Value = serde_json::from_slice(&bytes)
+                .map_err(|e| anyhow!("Unable to read tag {} of {}: {}", id, pack.path().display(), e))?;
+
+            if json.get("replace").and_then(Value::as_bool).unwrap_or(false) {
+                entries.clear();
+            }
+
+            for entry in tag_values(&json, id)? {
+                if !entries.contains(&entry) {
+                    entries.push(entry);
+                }
+            }
+
+            found = true;
+        }
+
+        if !found {
+            return Err(anyhow!("Unable to find tag {}", id))
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Path of the resource within a pack. Ids without a namespace are in the
+/// `minecraft` one.
+pub fn resource_path(registry: &str, id: &str, extension: &str) -> String {
+    let (namespace, path) = id.split_once(':').unwrap_or(("minecraft", id));
+
+    format!("data/{}/{}/{}.{}", namespace, registry, path, extension)
+}
+
+fn tag_values(json: &Value, id: &str) -> anyhow::Result<Vec<(String, bool)>> {
+    let Some(Value::Array(values)) = json.get("values") else {
+        return Err(anyhow!("No \"values\" key in tag {}", id))
+    };
+
+    values
+        .iter()
+        .map(|value| match value {
+            Value::String(id) => Ok((id.clone(), true)),
+            // Optional entries are written as objects.
+            Value::Object(entry) => match entry.get("id") {
+                Some(Value::String(id)) => Ok((
+                    id.clone(),
+                    entry.get("required").and_then(Value::as_bool).unwrap_or(true)
+                )),
+                _ => Err(anyhow!("No \"id\" key in tag entry {:?}", value))
+            },
+            _ => Err(anyhow!("Expected string or object, found {:?}", value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pack::{resource_path, DataPack, DataPacks};
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn write(root: &PathBuf, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn resource_paths() {
+        assert_eq!("data/minecraft/worldgen/noise/ore_gap.json", resource_path("worldgen/noise", "minecraft:ore_gap", "json"));
+        assert_eq!("data/mypack/worldgen/noise/foo/bar.json", resource_path("worldgen/noise", "mypack:foo/bar", "json"));
+        assert_eq!("data/minecraft/structures/igloo/top.nbt", resource_path("structures", "igloo/top", "nbt"));
+    }
+
+    #[test]
+    fn layered_folders() {
+        let root = std::env::temp_dir().join(format!("spherix-packs-{}", std::process::id()));
+        let vanilla = root.join("vanilla");
+        let pack = root.join("pack");
+
+        write(&vanilla, "data/minecraft/worldgen/noise/a.json", "1");
+        write(&vanilla, "data/minecraft/worldgen/noise/b.json", "2");
+        write(&vanilla, "data/minecraft/tags/blocks/t.json", r#"{"values": ["minecraft:stone"]}"#);
+        write(&pack, "pack.mcmeta", "{}");
+        write(&pack, "data/minecraft/worldgen/noise/b.json", "3");
+        write(&pack, "data/mypack/worldgen/noise/sub/a.json", "4");
+        write(&pack, "data/minecraft/tags/blocks/t.json", r#"{"values": ["minecraft:dirt"]}"#);
+
+        let packs = DataPacks::new(vec![DataPack::vanilla(vanilla), DataPack::open(&pack).unwrap()]);
+
+        assert_eq!(Some(serde_json::json!(1)), packs.read_json("worldgen/noise", "minecraft:a").unwrap());
+        assert_eq!(Some(serde_json::json!(3)), packs.read_json("worldgen/noise", "minecraft:b").unwrap());
+        assert_eq!(Some(serde_json::json!(4)), packs.read_json("worldgen/noise", "mypack:sub/a").unwrap());
+        assert_eq!(None, packs.read_json("worldgen/noise", "mypack:a").unwrap());
+        assert_eq!(
+            vec!["minecraft:a", "minecraft:b", "mypack:sub/a"],
+            packs.ids("worldgen/noise", "json").unwrap()
+        );
+        assert_eq!(
+            vec![("minecraft:stone".to_owned(), true), ("minecraft:dirt".to_owned(), true)],
+            packs.tag_entries("tags/blocks", "minecraft:t").unwrap()
+        );
+
+        write(&pack, "data/minecraft/tags/blocks/t.json", r#"{"replace": true, "values": ["minecraft:dirt"]}"#);
+        assert_eq!(vec![("minecraft:dirt".to_owned(), true)], packs.tag_entries("tags/blocks", "minecraft:t").unwrap());
+
+        assert!(DataPack::open(&root.join("vanilla")).is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
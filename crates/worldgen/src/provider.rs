@@ -0,0 +1,431 @@
+use crate::noise::json::resolvable::Resolvable;
+use crate::noise::json::Resolver;
+use crate::rng::Rng;
+use crate::surface::context::WorldGenerationContext;
+use anyhow::anyhow;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Height, which is defined relatively to the generated part of the dimension,
+/// e.g. `{"above_bottom": 8}`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalAnchor {
+    Absolute(i32),
+    AboveBottom(i32),
+    BelowTop(i32),
+}
+
+impl VerticalAnchor {
+    pub fn resolve_y(&self, ctx: &WorldGenerationContext) -> i32 {
+        match self {
+            VerticalAnchor::Absolute(y) => *y,
+            VerticalAnchor::AboveBottom(offset) => ctx.min_y + offset,
+            VerticalAnchor::BelowTop(offset) => ctx.min_y + ctx.height - 1 - offset,
+        }
+    }
+}
+
+/// Provides random height. Bare anchor is treated as a constant height.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum HeightProvider {
+    Anchor(VerticalAnchor),
+    Typed(TypedHeightProvider),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum TypedHeightProvider {
+    #[serde(rename = "minecraft:constant")]
+    Constant {
+        value: VerticalAnchor,
+    },
+    #[serde(rename = "minecraft:uniform")]
+    Uniform {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+    },
+    #[serde(rename = "minecraft:trapezoid")]
+    Trapezoid {
+        min_inclusive: VerticalAnchor,
+        max_inclusive: VerticalAnchor,
+        #[serde(default)]
+        plateau: i32,
+    },
+}
+
+impl HeightProvider {
+    pub fn sample<R: Rng>(&self, rng: &mut R, ctx: &WorldGenerationContext) -> i32 {
+        let provider = match self {
+            HeightProvider::Anchor(anchor) => return anchor.resolve_y(ctx),
+            HeightProvider::Typed(provider) => provider,
+        };
+
+        match provider {
+            TypedHeightProvider::Constant { value } => value.resolve_y(ctx),
+            TypedHeightProvider::Uniform { min_inclusive, max_inclusive } => {
+                let min = min_inclusive.resolve_y(ctx);
+                let max = max_inclusive.resolve_y(ctx);
+
+                // Vanilla only warns about the empty range.
+                if min > max {
+                    return min
+                }
+
+                random_between_inclusive(rng, min, max)
+            }
+            TypedHeightProvider::Trapezoid { min_inclusive, max_inclusive, plateau } => {
+                let min = min_inclusive.resolve_y(ctx);
+                let max = max_inclusive.resolve_y(ctx);
+
+                if min > max {
+                    return min
+                }
+
+                let range = max - min;
+                if *plateau >= range {
+                    return random_between_inclusive(rng, min, max)
+                }
+
+                let slope = (range - plateau) / 2;
+
+                min + random_between_inclusive(rng, 0, range - slope) + random_between_inclusive(rng, 0, slope)
+            }
+        }
+    }
+}
+
+/// Provides random float. Bare number is treated as a constant.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum FloatProvider {
+    Constant(f32),
+    Typed(TypedFloatProvider),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum TypedFloatProvider {
+    #[serde(rename = "minecraft:constant")]
+    Constant(f32),
+    #[serde(rename = "minecraft:uniform")]
+    Uniform {
+        min_inclusive: f32,
+        max_exclusive: f32,
+    },
+    #[serde(rename = "minecraft:trapezoid")]
+    Trapezoid {
+        min: f32,
+        max: f32,
+        plateau: f32,
+    },
+}
+
+impl FloatProvider {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f32 {
+        let provider = match self {
+            FloatProvider::Constant(value) => return *value,
+            FloatProvider::Typed(provider) => provider,
+        };
+
+        match provider {
+            TypedFloatProvider::Constant(value) => *value,
+            TypedFloatProvider::Uniform { min_inclusive, max_exclusive } => {
+                random_between(rng, *min_inclusive, *max_exclusive)
+            }
+            TypedFloatProvider::Trapezoid { min, max, plateau } => {
+                let range = max - min;
+                let slope = (range - plateau) / 2.0;
+                let rest = range - slope;
+
+                min + rng.next_f32() * rest + rng.next_f32() * slope
+            }
+        }
+    }
+}
+
+/// Provides random integer. Bare number is treated as a constant.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntProvider {
+    Constant(i32),
+    Uniform {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    BiasedToBottom {
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    Clamped {
+        source: Box<IntProvider>,
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    ClampedNormal {
+        mean: f32,
+        deviation: f32,
+        min_inclusive: i32,
+        max_inclusive: i32,
+    },
+    WeightedList(Vec<(IntProvider, u32)>),
+}
+
+impl IntProvider {
+    pub fn from_json(json: &Value) -> anyhow::Result<Self> {
+        if let Some(value) = json.as_i64() {
+            return Ok(IntProvider::Constant(value as i32))
+        }
+
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("Expected number or object with \"type\" key, found {:?}", json))
+        };
+
+        // Depending on the codec of the provider, its parameters are either
+        // inlined or wrapped into "value".
+        let params = match json.get("value") {
+            Some(value) if value.is_object() => value,
+            _ => json,
+        };
+
+        let int = |key: &str| {
+            params
+                .get(key)
+                .and_then(Value::as_i64)
+                .map(|value| value as i32)
+                .ok_or_else(|| anyhow!("No integer \"{}\" key in {:?}", key, json))
+        };
+        let float = |key: &str| {
+            params
+                .get(key)
+                .and_then(Value::as_f64)
+                .map(|value| value as f32)
+                .ok_or_else(|| anyhow!("No number \"{}\" key in {:?}", key, json))
+        };
+
+        Ok(match ty.as_str() {
+            "minecraft:constant" => IntProvider::Constant(int("value")?),
+            "minecraft:uniform" => IntProvider::Uniform {
+                min_inclusive: int("min_inclusive")?,
+                max_inclusive: int("max_inclusive")?,
+            },
+            "minecraft:biased_to_bottom" => IntProvider::BiasedToBottom {
+                min_inclusive: int("min_inclusive")?,
+                max_inclusive: int("max_inclusive")?,
+            },
+            "minecraft:clamped" => IntProvider::Clamped {
+                source: Box::new(IntProvider::from_json(
+                    params.get("source").ok_or_else(|| anyhow!("No \"source\" key in {:?}", json))?
+                )?),
+                min_inclusive: int("min_inclusive")?,
+                max_inclusive: int("max_inclusive")?,
+            },
+            "minecraft:clamped_normal" => IntProvider::ClampedNormal {
+                mean: float("mean")?,
+                deviation: float("deviation")?,
+                min_inclusive: int("min_inclusive")?,
+                max_inclusive: int("max_inclusive")?,
+            },
+            "minecraft:weighted_list" => {
+                let Some(Value::Array(distribution)) = params.get("distribution") else {
+                    return Err(anyhow!("No \"distribution\" key in {:?}", json))
+                };
+
+                IntProvider::WeightedList(
+                    distribution
+                        .iter()
+                        .map(|entry| {
+                            let data = entry.get("data").ok_or_else(|| anyhow!("No \"data\" key in {:?}", entry))?;
+                            let weight = entry.get("weight").and_then(Value::as_u64).unwrap_or(1);
+
+                            Ok((IntProvider::from_json(data)?, weight as u32))
+                        })
+                        .collect::<anyhow::Result<_>>()?
+                )
+            }
+            ty => return Err(anyhow!("Unknown int provider type {}", ty))
+        })
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> i32 {
+        match self {
+            IntProvider::Constant(value) => *value,
+            IntProvider::Uniform { min_inclusive, max_inclusive } => {
+                random_between_inclusive(rng, *min_inclusive, *max_inclusive)
+            }
+            IntProvider::BiasedToBottom { min_inclusive, max_inclusive } => {
+                let bound = rng.next_u32((max_inclusive - min_inclusive + 1) as u32) + 1;
+
+                min_inclusive + rng.next_u32(bound) as i32
+            }
+            IntProvider::Clamped { source, min_inclusive, max_inclusive } => {
+                source.sample(rng).clamp(*min_inclusive, *max_inclusive)
+            }
+            IntProvider::ClampedNormal { mean, deviation, min_inclusive, max_inclusive } => {
+                let value = mean + next_gaussian(rng) as f32 * deviation;
+
+                value.clamp(*min_inclusive as f32, *max_inclusive as f32) as i32
+            }
+            IntProvider::WeightedList(distribution) => {
+                let total: u32 = distribution.iter().map(|(_, weight)| weight).sum();
+                if total == 0 {
+                    return 0
+                }
+
+                let mut target = rng.next_u32(total) as i64;
+                for (provider, weight) in distribution {
+                    target -= *weight as i64;
+
+                    if target < 0 {
+                        return provider.sample(rng)
+                    }
+                }
+
+                unreachable!()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IntProvider {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = Value::deserialize(deserializer)?;
+
+        IntProvider::from_json(&json).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<T> Resolvable<T> for IntProvider {
+    fn resolve(val: &Value, _: &Resolver<T>) -> anyhow::Result<Self> {
+        IntProvider::from_json(val)
+    }
+}
+
+impl<T> Resolvable<T> for HeightProvider {
+    fn resolve(val: &Value, _: &Resolver<T>) -> anyhow::Result<Self> {
+        Ok(serde_json::from_value(val.clone())?)
+    }
+}
+
+impl<T> Resolvable<T> for FloatProvider {
+    fn resolve(val: &Value, _: &Resolver<T>) -> anyhow::Result<Self> {
+        Ok(serde_json::from_value(val.clone())?)
+    }
+}
+
+#[inline]
+pub fn random_between<R: Rng>(rng: &mut R, min: f32, max: f32) -> f32 {
+    rng.next_f32() * (max - min) + min
+}
+
+#[inline]
+pub fn random_between_inclusive<R: Rng>(rng: &mut R, min: i32, max: i32) -> i32 {
+    rng.next_u32((max - min + 1) as u32) as i32 + min
+}
+
+/// Normally distributed value, obtained with the polar method. Unlike vanilla,
+/// the second value of the pair is not kept for the next call.
+pub fn next_gaussian<R: Rng>(rng: &mut R) -> f64 {
+    loop {
+        let x = 2.0 * rng.next_f64() - 1.0;
+        let y = 2.0 * rng.next_f64() - 1.0;
+        let s = x * x + y * y;
+
+        if s < 1.0 && s != 0.0 {
+            return x * (-2.0 * s.ln() / s).sqrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::provider::{FloatProvider, HeightProvider, IntProvider, VerticalAnchor};
+    use crate::rng::{LcgEntropySrc, U32EntropySrc, U32EntropySrcRng};
+    use crate::surface::context::WorldGenerationContext;
+
+    const OVERWORLD: WorldGenerationContext = WorldGenerationContext {
+        height: 384,
+        min_y: -64,
+    };
+
+    #[test]
+    fn vertical_anchor() {
+        let above_bottom: VerticalAnchor = serde_json::from_str(r#"{"above_bottom": 8}"#).unwrap();
+        let below_top: VerticalAnchor = serde_json::from_str(r#"{"below_top": 1}"#).unwrap();
+
+        assert_eq!(-56, above_bottom.resolve_y(&OVERWORLD));
+        assert_eq!(318, below_top.resolve_y(&OVERWORLD));
+        assert_eq!(10, VerticalAnchor::Absolute(10).resolve_y(&OVERWORLD));
+    }
+
+    #[test]
+    fn height_provider() {
+        let mut rng = U32EntropySrcRng::new(LcgEntropySrc::new(42));
+
+        let constant: HeightProvider = serde_json::from_str(r#"{"absolute": 47}"#).unwrap();
+        assert_eq!(47, constant.sample(&mut rng, &OVERWORLD));
+
+        let uniform: HeightProvider = serde_json::from_str(r#"{
+            "type": "minecraft:uniform",
+            "min_inclusive": {"above_bottom": 8},
+            "max_inclusive": {"absolute": 180}
+        }"#).unwrap();
+
+        for _ in 0..100 {
+            assert!((-56..=180).contains(&uniform.sample(&mut rng, &OVERWORLD)));
+        }
+    }
+
+    #[test]
+    fn float_provider() {
+        let mut rng = U32EntropySrcRng::new(LcgEntropySrc::new(42));
+
+        let constant: FloatProvider = serde_json::from_str("3.0").unwrap();
+        assert_eq!(3.0, constant.sample(&mut rng));
+
+        let uniform: FloatProvider = serde_json::from_str(r#"{
+            "type": "minecraft:uniform",
+            "value": {"min_inclusive": -1.0, "max_exclusive": -0.4}
+        }"#).unwrap();
+        let trapezoid: FloatProvider = serde_json::from_str(r#"{
+            "type": "minecraft:trapezoid",
+            "value": {"min": 0.0, "max": 6.0, "plateau": 2.0}
+        }"#).unwrap();
+
+        for _ in 0..100 {
+            assert!((-1.0..-0.4).contains(&uniform.sample(&mut rng)));
+            assert!((0.0..6.0).contains(&trapezoid.sample(&mut rng)));
+        }
+    }
+
+    #[test]
+    fn int_provider() {
+        let mut rng = U32EntropySrcRng::new(LcgEntropySrc::new(42));
+
+        let constant: IntProvider = serde_json::from_str("7").unwrap();
+        assert_eq!(7, constant.sample(&mut rng));
+
+        let uniform: IntProvider = serde_json::from_str(r#"{
+            "type": "minecraft:uniform",
+            "value": {"min_inclusive": 2, "max_inclusive": 3}
+        }"#).unwrap();
+        let clamped: IntProvider = serde_json::from_str(r#"{
+            "type": "minecraft:clamped",
+            "value": {
+                "source": {"type": "minecraft:uniform", "value": {"min_inclusive": -5, "max_inclusive": 5}},
+                "min_inclusive": 0,
+                "max_inclusive": 1
+            }
+        }"#).unwrap();
+        let weighted: IntProvider = serde_json::from_str(r#"{
+            "type": "minecraft:weighted_list",
+            "distribution": [{"data": 0, "weight": 0}, {"data": 9, "weight": 1}]
+        }"#).unwrap();
+
+        for _ in 0..100 {
+            assert!((2..=3).contains(&uniform.sample(&mut rng)));
+            assert!((0..=1).contains(&clamped.sample(&mut rng)));
+            assert_eq!(9, weighted.sample(&mut rng));
+        }
+    }
+}
@@ -332,6 +332,14 @@ where
     fn next_bool(&mut self) -> bool {
         self.src.next(1) != 0
     }
+
+    /// Vanilla skips a single `nextInt()` per value, i.e. a single step of
+    /// the entropy source.
+    fn skip(&mut self, n: usize) {
+        for _ in 0..n {
+            self.src.next(32);
+        }
+    }
 }
 
 impl RngForkable for U32EntropySrcRng<XoroShiroU32EntropySrc> {
@@ -384,6 +392,117 @@ impl RngPos for U32EntropySrcRngPos
     }
 }
 
+/// Java `Random`-compatible generator, i.e. vanilla `LegacyRandomSource`.
+pub type LegacyRng = U32EntropySrcRng<LcgEntropySrc>;
+
+/// Random of the world generation. Noise settings pick the legacy one by
+/// `legacy_random_source`, as the nether and the end do.
+pub enum WorldgenRng {
+    XoroShiro(XoroShiro),
+    Legacy(LegacyRng),
+}
+
+impl WorldgenRng {
+    pub fn new(seed: i64, legacy: bool) -> Self {
+        if legacy {
+            WorldgenRng::Legacy(LegacyRng::new(LcgEntropySrc::new(seed as u64)))
+        } else {
+            WorldgenRng::XoroShiro(XoroShiro::new(seed as u64))
+        }
+    }
+}
+
+impl Rng for WorldgenRng {
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            WorldgenRng::XoroShiro(rng) => rng.next_u64(),
+            WorldgenRng::Legacy(rng) => rng.next_u64(),
+        }
+    }
+
+    fn next_u32(&mut self, max_value: u32) -> u32 {
+        match self {
+            WorldgenRng::XoroShiro(rng) => rng.next_u32(max_value),
+            WorldgenRng::Legacy(rng) => rng.next_u32(max_value),
+        }
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        match self {
+            WorldgenRng::XoroShiro(rng) => rng.next_f64(),
+            WorldgenRng::Legacy(rng) => rng.next_f64(),
+        }
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        match self {
+            WorldgenRng::XoroShiro(rng) => rng.next_f32(),
+            WorldgenRng::Legacy(rng) => rng.next_f32(),
+        }
+    }
+
+    fn next_bool(&mut self) -> bool {
+        match self {
+            WorldgenRng::XoroShiro(rng) => rng.next_bool(),
+            WorldgenRng::Legacy(rng) => rng.next_bool(),
+        }
+    }
+
+    fn skip(&mut self, n: usize) {
+        match self {
+            WorldgenRng::XoroShiro(rng) => rng.skip(n),
+            WorldgenRng::Legacy(rng) => rng.skip(n),
+        }
+    }
+}
+
+impl RngForkable for WorldgenRng {
+    type Pos = WorldgenRngPos;
+
+    fn fork_pos(&mut self) -> Self::Pos {
+        match self {
+            WorldgenRng::XoroShiro(rng) => WorldgenRngPos::XoroShiro(rng.fork_pos()),
+            WorldgenRng::Legacy(rng) => WorldgenRngPos::Legacy(rng.fork_pos()),
+        }
+    }
+}
+
+/// [`RngPos`] that is yielded by [`WorldgenRng`].
+pub enum WorldgenRngPos {
+    XoroShiro(XoroShiroPos),
+    Legacy(U32EntropySrcRngPos),
+}
+
+impl WorldgenRngPos {
+    pub fn is_legacy(&self) -> bool {
+        matches!(self, WorldgenRngPos::Legacy(_))
+    }
+}
+
+impl RngPos for WorldgenRngPos {
+    type Item = WorldgenRng;
+
+    /// The kind of the random is unknown here, so the XoroShiro one is
+    /// created. The legacy one is only forked from [`WorldgenRng`].
+    fn from_rng<R: Rng>(rng: &mut R) -> Self {
+        WorldgenRngPos::XoroShiro(XoroShiroPos::from_rng(rng))
+    }
+
+    fn at(&self, pos: Vector3) -> Self::Item {
+        match self {
+            WorldgenRngPos::XoroShiro(rng) => WorldgenRng::XoroShiro(rng.at(pos)),
+            WorldgenRngPos::Legacy(rng) => WorldgenRng::Legacy(rng.at(pos)),
+        }
+    }
+
+    fn by_hash(&self, s: String) -> Self::Item {
+        match self {
+            WorldgenRngPos::XoroShiro(rng) => WorldgenRng::XoroShiro(rng.by_hash(s)),
+            WorldgenRngPos::Legacy(rng) => WorldgenRng::Legacy(rng.by_hash(s)),
+        }
+    }
+}
+
 /// A custom hasher that mimics the behavior of Java's `String.hashCode()` for ASCII strings.
 /// More precisely, it behaves like `StringLatin1.hashCode()` method.
 /// This implementation uses a simple multiplicative hash function with a multiplier of 31.
@@ -586,6 +705,14 @@ mod tests {
         assert_eq!(517, xoro.next_u32_inclusive(256, 1024));
     }
     
+    #[test]
+    fn lcg_skip() {
+        // java.util.Random(0): nextInt() yields -1155484576, -723955400, 1033096058.
+        let mut rng = U32EntropySrcRng::new(LcgEntropySrc::new(0));
+        rng.skip(2);
+        assert_eq!(1033096058, rng.src.next(32));
+    }
+
     #[test]
     fn lcg_pos_at() {
         let mut rng = U32EntropySrcRng::new(LcgEntropySrc::new(0xDF64D427));
@@ -0,0 +1,164 @@
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::pos::ChunkPos;
+
+/// Axis-aligned box of blocks. Both corners are inclusive, as in vanilla
+/// `BoundingBox`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+    pub max_z: i32,
+}
+
+impl BoundingBox {
+    pub fn new(min_x: i32, min_y: i32, min_z: i32, max_x: i32, max_y: i32, max_z: i32) -> Self {
+        Self {
+            min_x: min_x.min(max_x),
+            min_y: min_y.min(max_y),
+            min_z: min_z.min(max_z),
+            max_x: min_x.max(max_x),
+            max_y: min_y.max(max_y),
+            max_z: min_z.max(max_z),
+        }
+    }
+
+    pub fn from_corners(a: Vector3, b: Vector3) -> Self {
+        Self::new(a.x, a.y, a.z, b.x, b.y, b.z)
+    }
+
+    /// Blocks of the chunk between `min_y` and `max_y`.
+    pub fn for_chunk(pos: &ChunkPos, min_y: i32, max_y: i32) -> Self {
+        Self::new(
+            pos.get_min_block_x(),
+            min_y,
+            pos.get_min_block_z(),
+            pos.get_min_block_x() + 15,
+            max_y,
+            pos.get_min_block_z() + 15,
+        )
+    }
+
+    /// The smallest box containing all the boxes.
+    pub fn encapsulating<I>(boxes: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = BoundingBox>
+    {
+        boxes.into_iter().reduce(|a, b| a.encapsulate(&b))
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.max_x >= other.min_x
+            && self.min_x <= other.max_x
+            && self.max_z >= other.min_z
+            && self.min_z <= other.max_z
+            && self.max_y >= other.min_y
+            && self.min_y <= other.max_y
+    }
+
+    /// Whether the box intersects the column range, regardless of y.
+    pub fn intersects_xz(&self, min_x: i32, min_z: i32, max_x: i32, max_z: i32) -> bool {
+        self.max_x >= min_x && self.min_x <= max_x && self.max_z >= min_z && self.min_z <= max_z
+    }
+
+    pub fn is_inside(&self, pos: Vector3) -> bool {
+        pos.x >= self.min_x
+            && pos.x <= self.max_x
+            && pos.z >= self.min_z
+            && pos.z <= self.max_z
+            && pos.y >= self.min_y
+            && pos.y <= self.max_y
+    }
+
+    /// Whether the other box lies within this one.
+    pub fn contains(&self, other: &BoundingBox) -> bool {
+        other.min_x >= self.min_x
+            && other.max_x <= self.max_x
+            && other.min_y >= self.min_y
+            && other.max_y <= self.max_y
+            && other.min_z >= self.min_z
+            && other.max_z <= self.max_z
+    }
+
+    pub fn encapsulate(&self, other: &BoundingBox) -> Self {
+        Self {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            min_z: self.min_z.min(other.min_z),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+            max_z: self.max_z.max(other.max_z),
+        }
+    }
+
+    pub fn encapsulate_pos(&self, pos: Vector3) -> Self {
+        self.encapsulate(&Self::from_corners(pos, pos))
+    }
+
+    pub fn moved(&self, x: i32, y: i32, z: i32) -> Self {
+        Self {
+            min_x: self.min_x + x,
+            min_y: self.min_y + y,
+            min_z: self.min_z + z,
+            max_x: self.max_x + x,
+            max_y: self.max_y + y,
+            max_z: self.max_z + z,
+        }
+    }
+
+    pub fn inflated_by(&self, value: i32) -> Self {
+        Self {
+            min_x: self.min_x - value,
+            min_y: self.min_y - value,
+            min_z: self.min_z - value,
+            max_x: self.max_x + value,
+            max_y: self.max_y + value,
+            max_z: self.max_z + value,
+        }
+    }
+
+    pub fn center(&self) -> Vector3 {
+        Vector3::new(
+            self.min_x + (self.max_x - self.min_x + 1) / 2,
+            self.min_y + (self.max_y - self.min_y + 1) / 2,
+            self.min_z + (self.max_z - self.min_z + 1) / 2,
+        )
+    }
+
+    pub fn y_span(&self) -> i32 {
+        self.max_y - self.min_y + 1
+    }
+
+    /// Written as `[min_x, min_y, min_z, max_x, max_y, max_z]`, as vanilla
+    /// writes the `BB` tag of structure pieces.
+    pub fn to_nbt(&self) -> nbt::Value {
+        nbt::Value::IntArray(vec![self.min_x, self.min_y, self.min_z, self.max_x, self.max_y, self.max_z])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::bounding_box::BoundingBox;
+    use spherix_math::vector::Vector3;
+
+    #[test]
+    fn intersection_and_containment() {
+        let a = BoundingBox::new(0, 0, 0, 15, 10, 15);
+        let b = BoundingBox::from_corners(Vector3::new(20, 5, 15), Vector3::new(15, 0, 20));
+
+        assert_eq!(BoundingBox::new(15, 0, 15, 20, 5, 20), b);
+        assert!(a.intersects(&b));
+        assert!(!a.intersects(&b.moved(1, 0, 0)));
+        assert!(a.is_inside(Vector3::new(15, 10, 15)));
+        assert!(!a.is_inside(Vector3::new(15, 11, 15)));
+        assert!(a.contains(&BoundingBox::new(1, 1, 1, 2, 2, 2)));
+        assert!(!a.contains(&b));
+
+        assert_eq!(BoundingBox::new(0, 0, 0, 20, 10, 20), a.encapsulate(&b));
+        assert_eq!(Vector3::new(8, 5, 8), a.center());
+        assert_eq!(11, a.y_span());
+        assert_eq!(BoundingBox::new(-12, -12, -12, 27, 22, 27), a.inflated_by(12));
+    }
+}
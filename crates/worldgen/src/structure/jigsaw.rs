@@ -0,0 +1,453 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::{Direction, FeatureRng};
+use crate::provider::HeightProvider;
+use crate::structure::bounding_box::BoundingBox;
+use crate::structure::pool::{PoolElement, PoolRegistry, Projection, EMPTY_POOL};
+use crate::structure::template::offset;
+use crate::structure::{heightmap_from_name, Rotation, StructureContext, StructureRng};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::heightmap::HeightmapType;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Structure assembled out of template pools by matching jigsaws, e.g.
+/// villages.
+pub struct JigsawStructure {
+    pub start_pool: String,
+    /// Pieces are attached at most this many times in a row.
+    pub max_depth: i32,
+    pub start_height: HeightProvider,
+    /// Name of the jigsaw of the start piece, which is placed at the start
+    /// position, instead of the corner of the piece.
+    pub start_jigsaw_name: Option<String>,
+    pub project_start_to_heightmap: Option<HeightmapType>,
+    pub max_distance_from_center: i32,
+    /// Reserves room above pieces for the pieces attached to them, as
+    /// villages did before 1.14.
+    pub use_expansion_hack: bool,
+}
+
+impl JigsawStructure {
+    pub fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let Some(Value::String(start_pool)) = json.get("start_pool") else {
+            return Err(anyhow!("No \"start_pool\" key in {:?}", json))
+        };
+
+        let start_height = json.get("start_height").ok_or_else(|| anyhow!("No \"start_height\" key in {:?}", json))?;
+
+        Ok(Self {
+            start_pool: start_pool.clone(),
+            max_depth: json
+                .get("size")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| anyhow!("No \"size\" key in {:?}", json))? as i32,
+            start_height: serde_json::from_value(start_height.clone())?,
+            start_jigsaw_name: json.get("start_jigsaw_name").and_then(Value::as_str).map(str::to_owned),
+            project_start_to_heightmap: json
+                .get("project_start_to_heightmap")
+                .and_then(Value::as_str)
+                .map(heightmap_from_name)
+                .transpose()?,
+            max_distance_from_center: json.get("max_distance_from_center").and_then(Value::as_i64).unwrap_or(80) as i32,
+            use_expansion_hack: json
+                .get("use_expansion_hack")
+                .and_then(Value::as_bool)
+                .ok_or_else(|| anyhow!("No \"use_expansion_hack\" key in {:?}", json))?,
+        })
+    }
+
+    /// Equivalent of vanilla `JigsawStructure::findGenerationPoint()`. Picks
+    /// the start piece and moves it to the surface, if the structure asks so.
+    pub fn find_generation_point(
+        &self,
+        ctx: &StructureContext,
+        pools: &PoolRegistry,
+        rng: &mut StructureRng,
+        chunk: &ChunkPos,
+    ) -> Option<GenerationStub> {
+        let y = self.start_height.sample(rng, &ctx.gen_ctx);
+        let pos = Vector3::new(chunk.get_min_block_x(), y, chunk.get_min_block_z());
+
+        let rotation = Rotation::random(rng);
+        let pool = pools.pool(&self.start_pool)?;
+        if pool.is_empty() {
+            return None
+        }
+
+        let element = pool.random_template(rng);
+        if matches!(element.as_ref(), PoolElement::Empty) {
+            return None
+        }
+
+        let jigsaw_pos = match &self.start_jigsaw_name {
+            Some(name) => element
+                .shuffled_jigsaws(pos, rotation, rng)
+                .into_iter()
+                .find(|jigsaw| jigsaw.jigsaw.name == *name)?
+                .pos,
+            None => pos,
+        };
+
+        let jigsaw_offset = Vector3::new(jigsaw_pos.x - pos.x, jigsaw_pos.y - pos.y, jigsaw_pos.z - pos.z);
+        let piece_pos = Vector3::new(pos.x - jigsaw_offset.x, pos.y - jigsaw_offset.y, pos.z - jigsaw_offset.z);
+
+        let mut piece = PoolElementPiece::new(element, piece_pos, rotation);
+        let center_x = (piece.bounding_box.max_x + piece.bounding_box.min_x) / 2;
+        let center_z = (piece.bounding_box.max_z + piece.bounding_box.min_z) / 2;
+
+        let y = match self.project_start_to_heightmap {
+            Some(ty) => pos.y + (ctx.base_height)(center_x, center_z, ty),
+            None => piece_pos.y,
+        };
+
+        piece.move_by(0, y - (piece.bounding_box.min_y + piece.ground_level_delta), 0);
+
+        Some(GenerationStub {
+            position: Vector3::new(center_x, y + jigsaw_offset.y, center_z),
+            start: piece,
+        })
+    }
+
+    /// Attaches pieces to the start piece, while there is room for them within
+    /// `max_distance_from_center` and `max_depth` is not reached.
+    pub fn assemble(
+        &self,
+        ctx: &StructureContext,
+        pools: &PoolRegistry,
+        rng: &mut StructureRng,
+        stub: GenerationStub,
+    ) -> Vec<PoolElementPiece> {
+        let GenerationStub { position, start } = stub;
+
+        if self.max_depth <= 0 {
+            return vec![start]
+        }
+
+        let distance = self.max_distance_from_center;
+        let free = FreeSpace {
+            bounds: BoundingBox::new(
+                position.x - distance,
+                position.y - distance,
+                position.z - distance,
+                position.x + distance,
+                position.y + distance,
+                position.z + distance,
+            ),
+            occupied: vec![start.bounding_box],
+        };
+
+        let mut assembler = Assembler {
+            ctx,
+            pools,
+            rng,
+            max_depth: self.max_depth,
+            use_expansion_hack: self.use_expansion_hack,
+            pieces: vec![start],
+        };
+
+        let mut placing = VecDeque::from([(0, Rc::new(RefCell::new(free)), 0)]);
+
+        while let Some((piece, free, depth)) = placing.pop_front() {
+            placing.extend(assembler.place_children(piece, free, depth));
+        }
+
+        assembler.pieces
+    }
+}
+
+/// Start piece of the structure, which is assembled only if the biome at
+/// `position` suits the structure.
+pub struct GenerationStub {
+    pub position: Vector3,
+    pub start: PoolElementPiece,
+}
+
+/// Room left for pieces: the cells of `bounds`, which are not occupied.
+struct FreeSpace {
+    bounds: BoundingBox,
+    occupied: Vec<BoundingBox>,
+}
+
+impl FreeSpace {
+    fn fits(&self, bounding_box: &BoundingBox) -> bool {
+        self.bounds.contains(bounding_box) && !self.occupied.iter().any(|occupied| occupied.intersects(bounding_box))
+    }
+}
+
+type SharedFreeSpace = Rc<RefCell<FreeSpace>>;
+
+/// Vanilla `JigsawPlacement.Placer`.
+struct Assembler<'a, 'b> {
+    ctx: &'a StructureContext<'b>,
+    pools: &'a PoolRegistry,
+    rng: &'a mut StructureRng,
+    max_depth: i32,
+    use_expansion_hack: bool,
+    pieces: Vec<PoolElementPiece>,
+}
+
+impl Assembler<'_, '_> {
+    /// Equivalent of vanilla `Placer::tryPlacingChildren()`. Returns the placed
+    /// children, whose children are to be placed next.
+    fn place_children(&mut self, index: usize, free: SharedFreeSpace, depth: i32) -> Vec<(usize, SharedFreeSpace, i32)> {
+        let element = self.pieces[index].element.clone();
+        let position = self.pieces[index].position;
+        let rotation = self.pieces[index].rotation;
+        let bounding_box = self.pieces[index].bounding_box;
+        let ground_level_delta = self.pieces[index].ground_level_delta;
+
+        let rigid = element.projection() == Projection::Rigid;
+        let min_y = bounding_box.min_y;
+        // Free space within the piece, for the children placed inside of it.
+        let mut inner_free: Option<SharedFreeSpace> = None;
+        let mut children = Vec::new();
+
+        'jigsaws: for jigsaw in element.shuffled_jigsaws(position, rotation, self.rng) {
+            let front = jigsaw.front;
+            let attach_pos = front.relative(jigsaw.pos);
+            let jigsaw_y = jigsaw.pos.y - min_y;
+            let mut surface = None;
+
+            let Some(pool) = self.pools.pool(&jigsaw.jigsaw.pool) else {
+                continue
+            };
+
+            if pool.is_empty() && pool.name != EMPTY_POOL {
+                continue
+            }
+
+            let Some(fallback) = self.pools.pool(&pool.fallback) else {
+                continue
+            };
+
+            if fallback.is_empty() && fallback.name != EMPTY_POOL {
+                continue
+            }
+
+            let free = if bounding_box.is_inside(attach_pos) {
+                inner_free
+                    .get_or_insert_with(|| {
+                        Rc::new(RefCell::new(FreeSpace {
+                            bounds: bounding_box,
+                            occupied: Vec::new(),
+                        }))
+                    })
+                    .clone()
+            } else {
+                free.clone()
+            };
+
+            let mut candidates = Vec::new();
+            if depth != self.max_depth {
+                candidates.extend(pool.shuffled_templates(self.rng));
+            }
+            candidates.extend(fallback.shuffled_templates(self.rng));
+
+            for candidate in candidates {
+                if matches!(candidate.as_ref(), PoolElement::Empty) {
+                    break
+                }
+
+                for candidate_rotation in Rotation::shuffled(self.rng) {
+                    let origin = Vector3::new(0, 0, 0);
+                    let candidate_jigsaws = candidate.shuffled_jigsaws(origin, candidate_rotation, self.rng);
+                    let candidate_box = candidate.bounding_box(origin, candidate_rotation);
+
+                    let expansion = if self.use_expansion_hack && candidate_box.y_span() <= 16 {
+                        candidate_jigsaws
+                            .iter()
+                            .filter(|other| candidate_box.is_inside(other.front.relative(other.pos)))
+                            .map(|other| {
+                                let pool = self.pools.pool(&other.jigsaw.pool);
+                                let fallback = pool.and_then(|pool| self.pools.pool(&pool.fallback));
+
+                                pool.map_or(0, |pool| pool.max_size()).max(fallback.map_or(0, |pool| pool.max_size()))
+                            })
+                            .max()
+                            .unwrap_or(0)
+                    } else {
+                        0
+                    };
+
+                    for other in candidate_jigsaws.iter().filter(|other| jigsaw.can_attach(other)) {
+                        let candidate_pos = Vector3::new(
+                            attach_pos.x - other.pos.x,
+                            attach_pos.y - other.pos.y,
+                            attach_pos.z - other.pos.z,
+                        );
+                        let placed_box = candidate.bounding_box(candidate_pos, candidate_rotation);
+                        let candidate_rigid = candidate.projection() == Projection::Rigid;
+                        let other_y = other.pos.y;
+                        let delta_y = jigsaw_y - other_y + front_step_y(front);
+
+                        let y = if rigid && candidate_rigid {
+                            min_y + delta_y
+                        } else {
+                            *surface.get_or_insert_with(|| self.surface(jigsaw.pos)) - other_y
+                        };
+
+                        let move_y = y - placed_box.min_y;
+                        let mut moved_box = placed_box.moved(0, move_y, 0);
+                        let moved_pos = offset(candidate_pos, Vector3::new(0, move_y, 0));
+
+                        if expansion > 0 {
+                            let height = (expansion + 1).max(moved_box.max_y - moved_box.min_y);
+                            moved_box = moved_box.encapsulate_pos(Vector3::new(moved_box.min_x, moved_box.min_y + height, moved_box.min_z));
+                        }
+
+                        if !free.borrow().fits(&moved_box) {
+                            continue
+                        }
+
+                        free.borrow_mut().occupied.push(moved_box);
+
+                        let candidate_ground_level_delta = if candidate_rigid {
+                            ground_level_delta - delta_y
+                        } else {
+                            candidate.ground_level_delta()
+                        };
+
+                        let junction_y = if rigid {
+                            min_y + jigsaw_y
+                        } else if candidate_rigid {
+                            y + other_y
+                        } else {
+                            *surface.get_or_insert_with(|| self.surface(jigsaw.pos)) + delta_y / 2
+                        };
+
+                        self.pieces[index].junctions.push(JigsawJunction {
+                            source_x: attach_pos.x,
+                            source_ground_y: junction_y - jigsaw_y + ground_level_delta,
+                            source_z: attach_pos.z,
+                            delta_y,
+                            dest_projection: candidate.projection(),
+                        });
+
+                        self.pieces.push(PoolElementPiece {
+                            element: candidate.clone(),
+                            position: moved_pos,
+                            rotation: candidate_rotation,
+                            ground_level_delta: candidate_ground_level_delta,
+                            bounding_box: moved_box,
+                            junctions: vec![JigsawJunction {
+                                source_x: jigsaw.pos.x,
+                                source_ground_y: junction_y - other_y + candidate_ground_level_delta,
+                                source_z: jigsaw.pos.z,
+                                delta_y: -delta_y,
+                                dest_projection: element.projection(),
+                            }],
+                        });
+
+                        if depth < self.max_depth {
+                            children.push((self.pieces.len() - 1, free, depth + 1));
+                        }
+
+                        continue 'jigsaws
+                    }
+                }
+            }
+        }
+
+        children
+    }
+
+    /// The first free y above the surface at the jigsaw.
+    fn surface(&self, pos: Vector3) -> i32 {
+        (self.ctx.base_height)(pos.x, pos.z, HeightmapType::WorldSurfaceWg)
+    }
+}
+
+fn front_step_y(front: Direction) -> i32 {
+    match front {
+        Direction::Up => 1,
+        Direction::Down => -1,
+        _ => 0,
+    }
+}
+
+/// Where two pieces are attached. The beardifier smooths terrain around
+/// junctions of terrain matching pieces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JigsawJunction {
+    pub source_x: i32,
+    pub source_ground_y: i32,
+    pub source_z: i32,
+    pub delta_y: i32,
+    pub dest_projection: Projection,
+}
+
+impl JigsawJunction {
+    pub fn to_nbt(&self) -> nbt::Value {
+        nbt::Value::Compound(HashMap::from([
+            ("source_x".to_owned(), nbt::Value::Int(self.source_x)),
+            ("source_ground_y".to_owned(), nbt::Value::Int(self.source_ground_y)),
+            ("source_z".to_owned(), nbt::Value::Int(self.source_z)),
+            ("delta_y".to_owned(), nbt::Value::Int(self.delta_y)),
+            ("dest_proj".to_owned(), nbt::Value::String(self.dest_projection.name().to_owned())),
+        ]))
+    }
+}
+
+/// Piece of a jigsaw structure, i.e. a pool element placed at some position
+/// with some rotation.
+pub struct PoolElementPiece {
+    pub element: Arc<PoolElement>,
+    pub position: Vector3,
+    pub rotation: Rotation,
+    pub ground_level_delta: i32,
+    pub bounding_box: BoundingBox,
+    pub junctions: Vec<JigsawJunction>,
+}
+
+impl PoolElementPiece {
+    pub fn new(element: Arc<PoolElement>, position: Vector3, rotation: Rotation) -> Self {
+        Self {
+            bounding_box: element.bounding_box(position, rotation),
+            ground_level_delta: element.ground_level_delta(),
+            element,
+            position,
+            rotation,
+            junctions: Vec::new(),
+        }
+    }
+
+    pub fn move_by(&mut self, x: i32, y: i32, z: i32) {
+        self.position = offset(self.position, Vector3::new(x, y, z));
+        self.bounding_box = self.bounding_box.moved(x, y, z);
+    }
+
+    pub fn place(
+        &self,
+        level: &mut WorldGenLevel,
+        rng: &mut FeatureRng,
+        palette: &BlockGlobalPalette,
+        pivot: Vector3,
+        chunk_box: &BoundingBox,
+    ) -> bool {
+        self.element.place(level, rng, palette, self.position, pivot, self.rotation, chunk_box)
+    }
+
+    /// The piece in the form vanilla stores it in the `Children` tag of the
+    /// structure start.
+    pub fn to_nbt(&self) -> nbt::Value {
+        nbt::Value::Compound(HashMap::from([
+            ("id".to_owned(), nbt::Value::String("minecraft:jigsaw".to_owned())),
+            ("GD".to_owned(), nbt::Value::Int(0)),
+            ("O".to_owned(), nbt::Value::Int(-1)),
+            ("BB".to_owned(), self.bounding_box.to_nbt()),
+            ("PosX".to_owned(), nbt::Value::Int(self.position.x)),
+            ("PosY".to_owned(), nbt::Value::Int(self.position.y)),
+            ("PosZ".to_owned(), nbt::Value::Int(self.position.z)),
+            ("ground_level_delta".to_owned(), nbt::Value::Int(self.ground_level_delta)),
+            ("pool_element".to_owned(), self.element.to_nbt()),
+            ("rotation".to_owned(), nbt::Value::String(self.rotation.name().to_owned())),
+            ("junctions".to_owned(), nbt::Value::List(self.junctions.iter().map(JigsawJunction::to_nbt).collect())),
+        ]))
+    }
+}
@@ -0,0 +1,472 @@
+pub mod bounding_box;
+pub mod jigsaw;
+pub mod placement;
+pub mod pool;
+pub mod processor;
+pub mod registry;
+pub mod template;
+
+use crate::biome::sampler::BiomeSampler;
+use crate::carver::large_feature_rng;
+use crate::feature::level::WorldGenLevel;
+use crate::feature::state::{property, with_properties};
+use crate::feature::{Direction, FeatureRng};
+use crate::noise::density::cache::quart_pos_from_block;
+use crate::rng::{LcgEntropySrc, Rng, U32EntropySrc, U32EntropySrcRng};
+use crate::structure::bounding_box::BoundingBox;
+use crate::structure::jigsaw::{JigsawStructure, PoolElementPiece};
+use crate::structure::pool::PoolRegistry;
+use crate::surface::context::WorldGenerationContext;
+use crate::tag::{BiomeSet, BiomeTags};
+use anyhow::anyhow;
+use lru::LruCache;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::heightmap::HeightmapType;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+/// Structures still use the legacy (Java) random, like carvers.
+pub type StructureRng = U32EntropySrcRng<LcgEntropySrc>;
+
+pub fn legacy_rng(seed: i64) -> StructureRng {
+    StructureRng::new(LcgEntropySrc::new(seed as u64))
+}
+
+/// Equivalent of vanilla `WorldgenRandom::setLargeFeatureWithSalt()`.
+pub fn large_feature_with_salt_rng(seed: i64, x: i32, z: i32, salt: i32) -> StructureRng {
+    legacy_rng(
+        (x as i64)
+            .wrapping_mul(341873128712)
+            .wrapping_add((z as i64).wrapping_mul(132897987541))
+            .wrapping_add(seed)
+            .wrapping_add(salt as i64)
+    )
+}
+
+/// Vanilla `Util.shuffle()`.
+pub fn shuffle<T, R: Rng>(list: &mut [T], rng: &mut R) {
+    for i in (2..=list.len()).rev() {
+        list.swap(i - 1, rng.next_u32(i as u32) as usize);
+    }
+}
+
+pub fn heightmap_from_name(name: &str) -> anyhow::Result<HeightmapType> {
+    HeightmapType::ALL
+        .into_iter()
+        .find(|ty| ty.name() == name)
+        .ok_or_else(|| anyhow!("Unknown heightmap type {}", name))
+}
+
+/// Rotation of structure pieces around the vertical axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90,
+}
+
+impl Rotation {
+    /// Rotations in the order of vanilla `Rotation.values()`.
+    pub const ALL: [Rotation; 4] = [
+        Rotation::None,
+        Rotation::Clockwise90,
+        Rotation::Clockwise180,
+        Rotation::CounterClockwise90,
+    ];
+
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        Self::ALL[rng.next_u32(4) as usize]
+    }
+
+    pub fn shuffled<R: Rng>(rng: &mut R) -> [Rotation; 4] {
+        let mut rotations = Self::ALL;
+        shuffle(&mut rotations, rng);
+
+        rotations
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rotation::None => "NONE",
+            Rotation::Clockwise90 => "CLOCKWISE_90",
+            Rotation::Clockwise180 => "CLOCKWISE_180",
+            Rotation::CounterClockwise90 => "COUNTERCLOCKWISE_90",
+        }
+    }
+
+    fn quarter_turns(&self) -> u32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::CounterClockwise90 => 3,
+        }
+    }
+
+    /// Rotates the position of a template block around the origin of the
+    /// template.
+    pub fn transform(&self, pos: Vector3) -> Vector3 {
+        match self {
+            Rotation::None => pos,
+            Rotation::Clockwise90 => Vector3::new(-pos.z, pos.y, pos.x),
+            Rotation::Clockwise180 => Vector3::new(-pos.x, pos.y, -pos.z),
+            Rotation::CounterClockwise90 => Vector3::new(pos.z, pos.y, -pos.x),
+        }
+    }
+
+    pub fn rotate(&self, direction: Direction) -> Direction {
+        (0..self.quarter_turns()).fold(direction, |direction, _| direction.clockwise())
+    }
+
+    /// Equivalent of vanilla `BlockState::rotate()`. Blocks override it in
+    /// vanilla, here the rotation is derived from the properties of the state:
+    /// horizontal facing, axis, 16-step rotation, connections to the sides,
+    /// jigsaw orientation and rail shape.
+    pub fn rotate_state(&self, state: &Arc<BlockState>, palette: &BlockGlobalPalette) -> Arc<BlockState> {
+        if *self == Rotation::None {
+            return state.clone()
+        }
+
+        let mut changes = Vec::new();
+
+        if let Some(facing) = property(state, "facing").and_then(|facing| Direction::from_name(&facing)) {
+            changes.push(("facing", self.rotate(facing).name().to_owned()));
+        }
+
+        if let Some(axis) = property(state, "axis") {
+            let axis = match (axis.as_str(), self.quarter_turns() % 2) {
+                ("x", 1) => "z".to_owned(),
+                ("z", 1) => "x".to_owned(),
+                _ => axis,
+            };
+
+            changes.push(("axis", axis));
+        }
+
+        if let Some(rotation) = property(state, "rotation").and_then(|rotation| rotation.parse::<u32>().ok()) {
+            changes.push(("rotation", ((rotation + 4 * self.quarter_turns()) % 16).to_string()));
+        }
+
+        let sides = Direction::HORIZONTAL
+            .iter()
+            .filter_map(|side| property(state, side.name()).map(|value| (self.rotate(*side).name(), value)))
+            .collect::<Vec<_>>();
+
+        if sides.len() == Direction::HORIZONTAL.len() {
+            changes.extend(sides);
+        }
+
+        if let Some(orientation) = property(state, "orientation") {
+            let rotated = orientation
+                .split('_')
+                .map(|direction| Direction::from_name(direction).map_or(direction, |direction| self.rotate(direction).name()))
+                .collect::<Vec<_>>()
+                .join("_");
+
+            changes.push(("orientation", rotated));
+        }
+
+        if state.name().ends_with("rail") {
+            if let Some(shape) = property(state, "shape").and_then(|shape| self.rotate_rail_shape(&shape)) {
+                changes.push(("shape", shape));
+            }
+        }
+
+        with_properties(state, &changes, palette).unwrap_or_else(|| state.clone())
+    }
+
+    /// Rail shapes are either `ascending_<direction>` or two directions, north
+    /// or south going first.
+    fn rotate_rail_shape(&self, shape: &str) -> Option<String> {
+        if let Some(direction) = shape.strip_prefix("ascending_") {
+            return Direction::from_name(direction).map(|direction| format!("ascending_{}", self.rotate(direction).name()))
+        }
+
+        let (a, b) = shape.split_once('_')?;
+        let a = self.rotate(Direction::from_name(a)?);
+        let b = self.rotate(Direction::from_name(b)?);
+
+        let shape = match (a, b) {
+            (Direction::North | Direction::South, Direction::North | Direction::South) => "north_south".to_owned(),
+            (Direction::East | Direction::West, Direction::East | Direction::West) => "east_west".to_owned(),
+            (Direction::North | Direction::South, _) => format!("{}_{}", a.name(), b.name()),
+            _ => format!("{}_{}", b.name(), a.name()),
+        };
+
+        Some(shape)
+    }
+}
+
+/// How the terrain around the structure is adapted to it by the
+/// [`Beardifier`](crate::noise::density::beardifier::Beardifier).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerrainAdjustment {
+    None,
+    Bury,
+    BeardThin,
+    BeardBox,
+    Encapsulate,
+}
+
+impl TerrainAdjustment {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(TerrainAdjustment::None),
+            "bury" => Some(TerrainAdjustment::Bury),
+            "beard_thin" => Some(TerrainAdjustment::BeardThin),
+            "beard_box" => Some(TerrainAdjustment::BeardBox),
+            "encapsulate" => Some(TerrainAdjustment::Encapsulate),
+            _ => None
+        }
+    }
+}
+
+/// Decoration steps in the order of vanilla `GenerationStep.Decoration`.
+pub const DECORATION_STEPS: [&str; 11] = [
+    "raw_generation",
+    "lakes",
+    "local_modifications",
+    "underground_structures",
+    "surface_structures",
+    "strongholds",
+    "underground_ores",
+    "underground_decoration",
+    "fluid_springs",
+    "vegetal_decoration",
+    "top_layer_modification",
+];
+
+/// What is known about the world while structure starts are created. Starts
+/// are created before the noise of their chunks, so heights are sampled from
+/// the noise router.
+pub struct StructureContext<'a> {
+    pub seed: i64,
+    pub gen_ctx: WorldGenerationContext,
+    pub biome_sampler: &'a BiomeSampler,
+    /// Vanilla `ChunkGenerator::getBaseHeight()`, i.e. the first free y of the
+    /// block column of the given heightmap type.
+    pub base_height: &'a dyn Fn(i32, i32, HeightmapType) -> i32,
+}
+
+pub enum StructureType {
+    Jigsaw(JigsawStructure),
+}
+
+/// Structure from `worldgen/structure/*.json`.
+pub struct Structure {
+    pub name: String,
+    pub biomes: BiomeSet,
+    /// Decoration step the pieces are placed at.
+    pub step: usize,
+    pub terrain_adaptation: TerrainAdjustment,
+    pub ty: StructureType,
+}
+
+impl Structure {
+    pub fn from_json(name: &str, json: &Value, biome_tags: &BiomeTags) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let ty = match ty.as_str() {
+            "minecraft:jigsaw" => StructureType::Jigsaw(JigsawStructure::from_json(json)?),
+            _ => return Err(anyhow!("Unsupported structure type {}", ty))
+        };
+
+        Ok(Self {
+            name: name.to_owned(),
+            biomes: biome_tags.resolve_holder_set(
+                json.get("biomes").ok_or_else(|| anyhow!("No \"biomes\" key in {:?}", json))?
+            )?,
+            step: step_from_json(json)?,
+            terrain_adaptation: match json.get("terrain_adaptation") {
+                Some(Value::String(name)) => TerrainAdjustment::from_name(name)
+                    .ok_or_else(|| anyhow!("Unknown terrain adaptation {}", name))?,
+                _ => TerrainAdjustment::None,
+            },
+            ty,
+        })
+    }
+
+    /// Equivalent of vanilla `Structure::generate()`. The start is dropped, if
+    /// the biome at its position does not suit the structure.
+    pub fn generate(self: &Arc<Self>, ctx: &StructureContext, pools: &PoolRegistry, chunk: &ChunkPos) -> Option<StructureStart> {
+        let mut rng = large_feature_rng(ctx.seed, chunk.x(), chunk.z());
+
+        let pieces = match &self.ty {
+            StructureType::Jigsaw(jigsaw) => {
+                let stub = jigsaw.find_generation_point(ctx, pools, &mut rng, chunk)?;
+
+                let biome = ctx.biome_sampler.sample(&Vector3::new(
+                    quart_pos_from_block(stub.position.x),
+                    quart_pos_from_block(stub.position.y),
+                    quart_pos_from_block(stub.position.z),
+                ));
+
+                if !self.biomes.contains(biome.name_ref()) {
+                    return None
+                }
+
+                jigsaw.assemble(ctx, pools, &mut rng, stub)
+            }
+        };
+
+        StructureStart::new(self.clone(), chunk.clone(), pieces)
+    }
+}
+
+/// Reads the decoration step of the structure as its index.
+pub fn step_from_json(json: &Value) -> anyhow::Result<usize> {
+    let Some(Value::String(step)) = json.get("step") else {
+        return Err(anyhow!("No \"step\" key in {:?}", json))
+    };
+
+    DECORATION_STEPS
+        .iter()
+        .position(|name| name == step)
+        .ok_or_else(|| anyhow!("Unknown decoration step {}", step))
+}
+
+/// Structure generated at the chunk, i.e. all its pieces. Pieces are placed
+/// chunk by chunk during the decoration of the chunks they intersect.
+pub struct StructureStart {
+    pub structure: Arc<Structure>,
+    pub chunk: ChunkPos,
+    pub pieces: Vec<PoolElementPiece>,
+    bounding_box: BoundingBox,
+}
+
+impl StructureStart {
+    /// Returns `None` for no pieces, as vanilla considers such starts invalid.
+    pub fn new(structure: Arc<Structure>, chunk: ChunkPos, pieces: Vec<PoolElementPiece>) -> Option<Self> {
+        let bounding_box = BoundingBox::encapsulating(pieces.iter().map(|piece| piece.bounding_box))?;
+
+        // Leaves room for the terrain adapted to the structure.
+        let bounding_box = match structure.terrain_adaptation {
+            TerrainAdjustment::None => bounding_box,
+            _ => bounding_box.inflated_by(12),
+        };
+
+        Some(Self {
+            structure,
+            chunk,
+            pieces,
+            bounding_box,
+        })
+    }
+
+    pub fn bounding_box(&self) -> &BoundingBox {
+        &self.bounding_box
+    }
+
+    /// Places the pieces intersecting the writable part of the decorated
+    /// chunk. Pieces are rotated around the bottom center of the first one.
+    pub fn place_in_chunk(
+        &self,
+        level: &mut WorldGenLevel,
+        rng: &mut FeatureRng,
+        palette: &BlockGlobalPalette,
+        chunk_box: &BoundingBox,
+    ) {
+        let Some(first) = self.pieces.first() else {
+            return
+        };
+
+        let center = first.bounding_box.center();
+        let pivot = Vector3::new(center.x, first.bounding_box.min_y, center.z);
+
+        for piece in self.pieces.iter().filter(|piece| piece.bounding_box.intersects(chunk_box)) {
+            piece.place(level, rng, palette, pivot, chunk_box);
+        }
+    }
+
+    /// The start in the form vanilla stores it in the `structures.starts` tag
+    /// of the chunk.
+    pub fn to_nbt(&self) -> nbt::Value {
+        nbt::Value::Compound(HashMap::from([
+            ("id".to_owned(), nbt::Value::String(self.structure.name.clone())),
+            ("ChunkX".to_owned(), nbt::Value::Int(self.chunk.x())),
+            ("ChunkZ".to_owned(), nbt::Value::Int(self.chunk.z())),
+            ("references".to_owned(), nbt::Value::Int(0)),
+            ("Children".to_owned(), nbt::Value::List(self.pieces.iter().map(PoolElementPiece::to_nbt).collect())),
+        ]))
+    }
+}
+
+pub type StructureStarts = Arc<[Arc<StructureStart>]>;
+
+/// Starts of the chunks, created on demand. Chunks are generated
+/// independently, so starts of the chunks around are needed by every chunk
+/// intersected by their pieces.
+pub struct StructureStartCache {
+    cache: Mutex<LruCache<ChunkPos, StructureStarts>>,
+}
+
+impl StructureStartCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new(LruCache::new(NonZeroUsize::new(capacity).unwrap())),
+        }
+    }
+
+    /// Cached starts of the chunk. The lock is not held while the starts are
+    /// created, so other threads may create them at the same time, but the
+    /// result is the same.
+    pub fn get_or_create<F>(&self, pos: &ChunkPos, create: F) -> StructureStarts
+    where
+        F: FnOnce() -> StructureStarts
+    {
+        if let Some(starts) = self.cache.lock().unwrap().get(pos) {
+            return starts.clone()
+        }
+
+        let starts = create();
+        self.cache.lock().unwrap().put(pos.clone(), starts.clone());
+
+        starts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::Direction;
+    use crate::rng::Rng;
+    use crate::structure::{large_feature_with_salt_rng, Rotation};
+    use spherix_math::vector::Vector3;
+
+    #[test]
+    fn rotation() {
+        let pos = Vector3::new(1, 2, 3);
+
+        assert_eq!(Vector3::new(-3, 2, 1), Rotation::Clockwise90.transform(pos));
+        assert_eq!(Vector3::new(-1, 2, -3), Rotation::Clockwise180.transform(pos));
+        assert_eq!(Vector3::new(3, 2, -1), Rotation::CounterClockwise90.transform(pos));
+
+        assert_eq!(Direction::East, Rotation::Clockwise90.rotate(Direction::North));
+        assert_eq!(Direction::West, Rotation::CounterClockwise90.rotate(Direction::North));
+        assert_eq!(Direction::Up, Rotation::Clockwise180.rotate(Direction::Up));
+
+        assert_eq!(Some("east_west".to_owned()), Rotation::Clockwise90.rotate_rail_shape("north_south"));
+        assert_eq!(Some("south_west".to_owned()), Rotation::Clockwise90.rotate_rail_shape("south_east"));
+        assert_eq!(Some("north_east".to_owned()), Rotation::Clockwise90.rotate_rail_shape("north_west"));
+        assert_eq!(Some("ascending_south".to_owned()), Rotation::Clockwise90.rotate_rail_shape("ascending_east"));
+    }
+
+    #[test]
+    fn salted_large_feature_seed() {
+        // Village spacing 34 and separation 8.
+        let mut rng = large_feature_with_salt_rng(12345, 3, -7, 10387312);
+        assert_eq!(14, rng.next_u32(26));
+        assert_eq!(1, rng.next_u32(26));
+
+        let mut rng = large_feature_with_salt_rng(12345, -2, 5, 10387312);
+        assert_eq!(18, rng.next_u32(26));
+        assert_eq!(23, rng.next_u32(26));
+    }
+}
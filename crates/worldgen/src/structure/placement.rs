@@ -0,0 +1,307 @@
+use crate::carver::large_feature_rng;
+use crate::noise::density::cache::quart_pos_from_block;
+use crate::noise::math::floor_div;
+use crate::rng::Rng;
+use crate::structure::{large_feature_with_salt_rng, legacy_rng, StructureContext};
+use crate::tag::{BiomeSet, BiomeTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::chunk::pos::ChunkPos;
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::sync::{Arc, OnceLock};
+
+/// Placement of a structure set, i.e. the chunks its structures may start at.
+pub struct StructurePlacement {
+    pub salt: i32,
+    pub frequency: f32,
+    pub frequency_reduction: FrequencyReduction,
+    /// The set is not placed within the given number of chunks around the
+    /// chunks of the other set.
+    pub exclusion_zone: Option<(String, i32)>,
+    pub kind: PlacementKind,
+}
+
+pub enum PlacementKind {
+    RandomSpread(RandomSpread),
+    ConcentricRings(ConcentricRings),
+}
+
+impl StructurePlacement {
+    pub fn from_json(json: &Value, biome_tags: &BiomeTags) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("type") else {
+            return Err(anyhow!("No \"type\" key in {:?}", json))
+        };
+
+        let int = |name: &str| {
+            json.get(name)
+                .and_then(Value::as_i64)
+                .map(|value| value as i32)
+                .ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json))
+        };
+
+        let kind = match ty.as_str() {
+            "minecraft:random_spread" => {
+                let spacing = int("spacing")?;
+                let separation = int("separation")?;
+                if spacing <= separation {
+                    return Err(anyhow!("Spacing {} has to be greater than separation {}", spacing, separation))
+                }
+
+                PlacementKind::RandomSpread(RandomSpread {
+                    spacing,
+                    separation,
+                    triangular: match json.get("spread_type").and_then(Value::as_str) {
+                        None | Some("linear") => false,
+                        Some("triangular") => true,
+                        Some(spread) => return Err(anyhow!("Unknown spread type {}", spread))
+                    },
+                })
+            }
+            "minecraft:concentric_rings" => PlacementKind::ConcentricRings(ConcentricRings {
+                distance: int("distance")?,
+                spread: int("spread")?,
+                count: int("count")?,
+                preferred_biomes: biome_tags.resolve_holder_set(
+                    json.get("preferred_biomes").ok_or_else(|| anyhow!("No \"preferred_biomes\" key in {:?}", json))?
+                )?,
+                positions: OnceLock::new(),
+            }),
+            _ => return Err(anyhow!("Unknown structure placement type {}", ty))
+        };
+
+        let exclusion_zone = match json.get("exclusion_zone") {
+            Some(zone) => match (zone.get("other_set"), zone.get("chunk_count").and_then(Value::as_i64)) {
+                (Some(Value::String(other_set)), Some(chunk_count)) => Some((other_set.clone(), chunk_count as i32)),
+                _ => return Err(anyhow!("Invalid exclusion zone {:?}", zone))
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            salt: int("salt")?,
+            frequency: json.get("frequency").and_then(Value::as_f64).unwrap_or(1.0) as f32,
+            frequency_reduction: match json.get("frequency_reduction_method").and_then(Value::as_str) {
+                Some(name) => FrequencyReduction::from_name(name)
+                    .ok_or_else(|| anyhow!("Unknown frequency reduction method {}", name))?,
+                None => FrequencyReduction::Default,
+            },
+            exclusion_zone,
+            kind,
+        })
+    }
+
+    /// Equivalent of vanilla `StructurePlacement::isStructureChunk()`. Sets
+    /// of exclusion zones are looked up in `placements`.
+    pub fn is_structure_chunk(
+        &self,
+        ctx: &StructureContext,
+        x: i32,
+        z: i32,
+        placements: &HashMap<String, Arc<StructurePlacement>>,
+    ) -> bool {
+        let placement_chunk = match &self.kind {
+            PlacementKind::RandomSpread(spread) => spread.potential_chunk(ctx.seed, self.salt, x, z) == ChunkPos::new(x, z),
+            PlacementKind::ConcentricRings(rings) => rings.positions(ctx).contains(&ChunkPos::new(x, z)),
+        };
+
+        if !placement_chunk {
+            return false
+        }
+
+        if self.frequency < 1.0 && !self.frequency_reduction.should_generate(ctx.seed, self.salt, x, z, self.frequency) {
+            return false
+        }
+
+        let Some((other_set, chunk_count)) = &self.exclusion_zone else {
+            return true
+        };
+
+        let Some(other) = placements.get(other_set) else {
+            return true
+        };
+
+        !((x - chunk_count)..=(x + chunk_count))
+            .any(|i| ((z - chunk_count)..=(z + chunk_count)).any(|j| other.is_structure_chunk(ctx, i, j, placements)))
+    }
+}
+
+/// One structure per grid cell of `spacing` chunks, at a random chunk of the
+/// cell, which keeps `separation` chunks to the next cell.
+pub struct RandomSpread {
+    pub spacing: i32,
+    pub separation: i32,
+    /// Triangular spread prefers the middle of the cell.
+    pub triangular: bool,
+}
+
+impl RandomSpread {
+    pub fn potential_chunk(&self, seed: i64, salt: i32, x: i32, z: i32) -> ChunkPos {
+        let i = floor_div(x, self.spacing);
+        let j = floor_div(z, self.spacing);
+
+        let mut rng = large_feature_with_salt_rng(seed, i, j, salt);
+        let bound = (self.spacing - self.separation) as u32;
+
+        let mut evaluate = || {
+            if self.triangular {
+                (rng.next_u32(bound) as i32 + rng.next_u32(bound) as i32) / 2
+            } else {
+                rng.next_u32(bound) as i32
+            }
+        };
+
+        let k = evaluate();
+        let l = evaluate();
+
+        ChunkPos::new(i * self.spacing + k, j * self.spacing + l)
+    }
+}
+
+/// Structures on rings around the origin, e.g. strongholds. Positions are
+/// moved to the preferred biomes nearby.
+pub struct ConcentricRings {
+    pub distance: i32,
+    pub spread: i32,
+    pub count: i32,
+    pub preferred_biomes: BiomeSet,
+    positions: OnceLock<HashSet<ChunkPos>>,
+}
+
+impl ConcentricRings {
+    /// Vanilla does not look further for the preferred biome.
+    const BIOME_SEARCH_RADIUS: i32 = 112;
+
+    /// Positions of all the rings. They are computed once, on the first call.
+    pub fn positions(&self, ctx: &StructureContext) -> &HashSet<ChunkPos> {
+        self.positions.get_or_init(|| self.generate_positions(ctx))
+    }
+
+    /// Equivalent of vanilla `ChunkGeneratorStructureState::generateRingPositions()`.
+    fn generate_positions(&self, ctx: &StructureContext) -> HashSet<ChunkPos> {
+        let mut positions = HashSet::new();
+        let mut rng = legacy_rng(ctx.seed);
+
+        let mut angle = rng.next_f64() * PI * 2.0;
+        let mut spread = self.spread;
+        let mut on_ring = 0;
+        let mut ring = 0;
+
+        for i in 0..self.count {
+            let distance = (4 * self.distance + self.distance * ring * 6) as f64
+                + (rng.next_f64() - 0.5) * self.distance as f64 * 2.5;
+            // Java Math.round().
+            let x = (angle.cos() * distance + 0.5).floor() as i32;
+            let z = (angle.sin() * distance + 0.5).floor() as i32;
+
+            let mut fork = legacy_rng(rng.next_u64() as i64);
+            let pos = self
+                .find_preferred_biome(ctx, (x << 4) + 8, (z << 4) + 8, &mut fork)
+                .map_or_else(|| ChunkPos::new(x, z), |pos| ChunkPos::new(pos.x >> 4, pos.z >> 4));
+
+            positions.insert(pos);
+
+            angle += PI * 2.0 / spread as f64;
+            on_ring += 1;
+
+            if on_ring == spread {
+                ring += 1;
+                on_ring = 0;
+                spread += 2 * spread / (ring + 1);
+                spread = spread.min(self.count - i);
+                angle += rng.next_f64() * PI * 2.0;
+            }
+        }
+
+        positions
+    }
+
+    /// Equivalent of vanilla `BiomeSource::findBiomeHorizontal()`, which scans
+    /// the whole square and picks one of the suitable positions at random.
+    fn find_preferred_biome<R: Rng>(&self, ctx: &StructureContext, x: i32, z: i32, rng: &mut R) -> Option<Vector3> {
+        let center_x = quart_pos_from_block(x);
+        let center_z = quart_pos_from_block(z);
+        let radius = quart_pos_from_block(Self::BIOME_SEARCH_RADIUS);
+
+        let mut found = None;
+        let mut count = 0;
+
+        for dz in -radius..=radius {
+            for dx in -radius..=radius {
+                let quart = Vector3::new(center_x + dx, 0, center_z + dz);
+
+                if !self.preferred_biomes.contains(ctx.biome_sampler.sample(&quart).name_ref()) {
+                    continue
+                }
+
+                if found.is_none() || rng.next_u32(count + 1) == 0 {
+                    found = Some(Vector3::new(quart.x << 2, 0, quart.z << 2));
+                }
+
+                count += 1;
+            }
+        }
+
+        found
+    }
+}
+
+/// How the chunks of the set are thinned out, when its frequency is below 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencyReduction {
+    Default,
+    /// Pillager outposts before 1.18.
+    LegacyType1,
+    LegacyType2,
+    LegacyType3,
+}
+
+impl FrequencyReduction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(FrequencyReduction::Default),
+            "legacy_type_1" => Some(FrequencyReduction::LegacyType1),
+            "legacy_type_2" => Some(FrequencyReduction::LegacyType2),
+            "legacy_type_3" => Some(FrequencyReduction::LegacyType3),
+            _ => None
+        }
+    }
+
+    pub fn should_generate(&self, seed: i64, salt: i32, x: i32, z: i32, frequency: f32) -> bool {
+        match self {
+            // Vanilla passes the salt in place of x, x in place of z and z in
+            // place of the salt.
+            FrequencyReduction::Default => large_feature_with_salt_rng(seed, salt, x, z).next_f32() < frequency,
+            FrequencyReduction::LegacyType1 => {
+                let mut rng = legacy_rng(((x >> 4) ^ ((z >> 4) << 4)) as i64 ^ seed);
+                // Skips the unbounded nextInt(), a single step of the random.
+                rng.next_f32();
+
+                rng.next_u32((1.0 / frequency) as u32) == 0
+            }
+            FrequencyReduction::LegacyType2 => large_feature_with_salt_rng(seed, x, z, 10387320).next_f32() < frequency,
+            FrequencyReduction::LegacyType3 => large_feature_rng(seed, x, z).next_f64() < frequency as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::placement::RandomSpread;
+    use spherix_world::chunk::pos::ChunkPos;
+
+    #[test]
+    fn random_spread_potential_chunk() {
+        let villages = RandomSpread {
+            spacing: 34,
+            separation: 8,
+            triangular: false,
+        };
+
+        // See the salted large feature seed test.
+        assert_eq!(ChunkPos::new(3 * 34 + 14, -7 * 34 + 1), villages.potential_chunk(12345, 10387312, 3 * 34, -7 * 34));
+        assert_eq!(ChunkPos::new(3 * 34 + 14, -7 * 34 + 1), villages.potential_chunk(12345, 10387312, 3 * 34 + 33, -7 * 34 + 5));
+        assert_eq!(ChunkPos::new(-2 * 34 + 18, 5 * 34 + 23), villages.potential_chunk(12345, 10387312, -2 * 34 + 20, 5 * 34));
+    }
+}
@@ -0,0 +1,499 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::placement::PlacementModifiers;
+use crate::feature::{ConfiguredFeature, Direction, FeatureRng, PlacedFeature};
+use crate::noise::json::Resolver;
+use crate::pack::DataPacks;
+use crate::rng::Rng;
+use crate::structure::bounding_box::BoundingBox;
+use crate::structure::processor::{GravityProcessor, ProcessorList, StructureProcessors};
+use crate::structure::template::{Jigsaw, PlacedJigsaw, StructureTemplate};
+use crate::structure::{shuffle, Rotation};
+use crate::tag::BlockTags;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::block::Block;
+use spherix_world::chunk::heightmap::HeightmapType;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// Name of the pool, which vanilla registers as an empty pool.
+pub const EMPTY_POOL: &str = "minecraft:empty";
+
+/// How the piece follows the terrain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// The piece is placed as it is.
+    Rigid,
+    /// Every block column of the piece is moved to the surface, e.g. streets.
+    TerrainMatching,
+}
+
+impl Projection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rigid" => Some(Projection::Rigid),
+            "terrain_matching" => Some(Projection::TerrainMatching),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Rigid => "rigid",
+            Projection::TerrainMatching => "terrain_matching",
+        }
+    }
+
+    fn processors(&self) -> ProcessorList {
+        match self {
+            Projection::Rigid => Vec::new(),
+            Projection::TerrainMatching => vec![Arc::new(StructureProcessors::Gravity(GravityProcessor {
+                heightmap: HeightmapType::WorldSurfaceWg,
+                offset: -1,
+            }))],
+        }
+    }
+}
+
+/// Element of a template pool, i.e. what a jigsaw piece is made of.
+pub enum PoolElement {
+    Single(SinglePoolElement),
+    List(Vec<PoolElement>, Projection),
+    Feature(FeaturePoolElement),
+    Empty,
+}
+
+impl PoolElement {
+    pub fn projection(&self) -> Projection {
+        match self {
+            PoolElement::Single(x) => x.projection,
+            PoolElement::List(_, projection) => *projection,
+            PoolElement::Feature(x) => x.projection,
+            PoolElement::Empty => Projection::Rigid,
+        }
+    }
+
+    pub fn ground_level_delta(&self) -> i32 {
+        1
+    }
+
+    /// Jigsaws of the element placed at `pos`. Lists expose the jigsaws of
+    /// their first element only.
+    pub fn jigsaws(&self, pos: Vector3, rotation: Rotation) -> Vec<PlacedJigsaw> {
+        match self {
+            PoolElement::Single(x) => x.template.jigsaws(pos, rotation),
+            PoolElement::List(elements, _) => elements
+                .first()
+                .map(|element| element.jigsaws(pos, rotation))
+                .unwrap_or_default(),
+            PoolElement::Feature(x) => vec![PlacedJigsaw {
+                pos,
+                front: Direction::Down,
+                top: Direction::South,
+                jigsaw: x.jigsaw.clone(),
+            }],
+            PoolElement::Empty => Vec::new(),
+        }
+    }
+
+    pub fn shuffled_jigsaws<R: Rng>(&self, pos: Vector3, rotation: Rotation, rng: &mut R) -> Vec<PlacedJigsaw> {
+        let mut jigsaws = self.jigsaws(pos, rotation);
+        shuffle(&mut jigsaws, rng);
+
+        jigsaws
+    }
+
+    pub fn bounding_box(&self, pos: Vector3, rotation: Rotation) -> BoundingBox {
+        match self {
+            PoolElement::Single(x) => x.template.bounding_box(pos, rotation),
+            PoolElement::List(elements, _) => BoundingBox::encapsulating(
+                elements
+                    .iter()
+                    .filter(|element| !matches!(element, PoolElement::Empty))
+                    .map(|element| element.bounding_box(pos, rotation))
+            ).unwrap_or_else(|| BoundingBox::from_corners(pos, pos)),
+            PoolElement::Feature(_) | PoolElement::Empty => BoundingBox::from_corners(pos, pos),
+        }
+    }
+
+    /// Places the part of the element within `chunk_box`. `pivot` is the
+    /// bottom center of the first piece of the structure.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place(
+        &self,
+        level: &mut WorldGenLevel,
+        rng: &mut FeatureRng,
+        palette: &BlockGlobalPalette,
+        pos: Vector3,
+        pivot: Vector3,
+        rotation: Rotation,
+        chunk_box: &BoundingBox,
+    ) -> bool {
+        match self {
+            PoolElement::Single(x) => x.template.place(level, palette, pos, pivot, rotation, &x.processors, chunk_box),
+            PoolElement::List(elements, _) => elements
+                .iter()
+                .all(|element| element.place(level, rng, palette, pos, pivot, rotation, chunk_box)),
+            PoolElement::Feature(x) => x.feature.place(level, rng, pos),
+            PoolElement::Empty => true,
+        }
+    }
+
+    /// The element in the form of the `pool_element` tag of jigsaw pieces.
+    pub fn to_nbt(&self) -> nbt::Value {
+        let mut nbt = HashMap::new();
+
+        match self {
+            PoolElement::Single(x) => {
+                let ty = if x.legacy { "minecraft:legacy_single_pool_element" } else { "minecraft:single_pool_element" };
+
+                nbt.insert("element_type".to_owned(), nbt::Value::String(ty.to_owned()));
+                nbt.insert("location".to_owned(), nbt::Value::String(x.location.clone()));
+                // Vanilla data refers to processor lists by name only.
+                let processors = match &x.processors_name {
+                    Some(name) => nbt::Value::String(name.clone()),
+                    None => nbt::Value::Compound(HashMap::from([
+                        ("processors".to_owned(), nbt::Value::List(Vec::new())),
+                    ])),
+                };
+                nbt.insert("processors".to_owned(), processors);
+                nbt.insert("projection".to_owned(), nbt::Value::String(x.projection.name().to_owned()));
+            }
+            PoolElement::List(elements, projection) => {
+                nbt.insert("element_type".to_owned(), nbt::Value::String("minecraft:list_pool_element".to_owned()));
+                nbt.insert("elements".to_owned(), nbt::Value::List(elements.iter().map(PoolElement::to_nbt).collect()));
+                nbt.insert("projection".to_owned(), nbt::Value::String(projection.name().to_owned()));
+            }
+            PoolElement::Feature(x) => {
+                nbt.insert("element_type".to_owned(), nbt::Value::String("minecraft:feature_pool_element".to_owned()));
+                nbt.insert("feature".to_owned(), nbt::Value::String(x.name.clone()));
+                nbt.insert("projection".to_owned(), nbt::Value::String(x.projection.name().to_owned()));
+            }
+            PoolElement::Empty => {
+                nbt.insert("element_type".to_owned(), nbt::Value::String("minecraft:empty_pool_element".to_owned()));
+            }
+        }
+
+        nbt::Value::Compound(nbt)
+    }
+}
+
+/// Element made of a structure template.
+pub struct SinglePoolElement {
+    pub location: String,
+    /// Legacy elements do not place air of the template.
+    pub legacy: bool,
+    pub template: Arc<StructureTemplate>,
+    pub processors_name: Option<String>,
+    pub projection: Projection,
+    /// Processors of the element along with the ones vanilla adds around them.
+    processors: ProcessorList,
+}
+
+/// Element, which places a feature, e.g. a tree or a lamp post in a village.
+pub struct FeaturePoolElement {
+    pub name: String,
+    pub feature: Arc<PlacedFeature>,
+    pub projection: Projection,
+    /// The single jigsaw of the element, which points down.
+    jigsaw: Arc<Jigsaw>,
+}
+
+/// Template pool from `worldgen/template_pool/*.json`. Elements are repeated
+/// by their weight, so that they are picked uniformly.
+pub struct StructureTemplatePool {
+    pub name: String,
+    pub fallback: String,
+    templates: Vec<Arc<PoolElement>>,
+    /// The greatest height of the elements, see
+    /// [`StructureTemplatePool::max_size()`].
+    max_size: i32,
+}
+
+impl StructureTemplatePool {
+    pub fn new(name: String, fallback: String, templates: Vec<Arc<PoolElement>>) -> Self {
+        let max_size = templates
+            .iter()
+            .filter(|element| !matches!(element.as_ref(), PoolElement::Empty))
+            .map(|element| element.bounding_box(Vector3::new(0, 0, 0), Rotation::None).y_span())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            name,
+            fallback,
+            templates,
+            max_size,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(EMPTY_POOL.to_owned(), EMPTY_POOL.to_owned(), Vec::new())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Height the assembler reserves for pieces of the pool, when it is not
+    /// able to tell the height of the surface.
+    pub fn max_size(&self) -> i32 {
+        self.max_size
+    }
+
+    pub fn random_template<R: Rng>(&self, rng: &mut R) -> Arc<PoolElement> {
+        self.templates[rng.next_u32(self.templates.len() as u32) as usize].clone()
+    }
+
+    pub fn shuffled_templates<R: Rng>(&self, rng: &mut R) -> Vec<Arc<PoolElement>> {
+        let mut templates = self.templates.clone();
+        shuffle(&mut templates, rng);
+
+        templates
+    }
+}
+
+/// Template pools reachable from the start pools of the structures. Pools,
+/// which fail to load, are absent, as vanilla skips unknown pools while
+/// assembling.
+pub struct PoolRegistry {
+    pools: HashMap<String, Arc<StructureTemplatePool>>,
+    failed: Vec<(String, String)>,
+}
+
+impl PoolRegistry {
+    /// Loads the start pools and all pools their jigsaws refer to, directly or
+    /// as fallbacks. Templates are read from `structures/*.nbt` of the data packs.
+    pub fn load<I>(
+        packs: &DataPacks,
+        start_pools: I,
+        tags: &BlockTags,
+        palette: &BlockGlobalPalette,
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = String>
+    {
+        let mut loader = PoolLoader {
+            packs,
+            tags,
+            palette,
+            features,
+            placements,
+            templates: HashMap::new(),
+            processor_lists: HashMap::new(),
+        };
+
+        let mut pools = HashMap::new();
+        let mut failed = Vec::new();
+        let mut queue = start_pools.into_iter().collect::<VecDeque<_>>();
+
+        while let Some(name) = queue.pop_front() {
+            if pools.contains_key(&name) || failed.iter().any(|(failed, _)| *failed == name) {
+                continue
+            }
+
+            match loader.pool(&name) {
+                Ok(pool) => {
+                    queue.push_back(pool.fallback.clone());
+                    queue.extend(
+                        pool.templates
+                            .iter()
+                            .flat_map(|element| element.jigsaws(Vector3::new(0, 0, 0), Rotation::None))
+                            .map(|jigsaw| jigsaw.jigsaw.pool.clone())
+                    );
+
+                    pools.insert(name, Arc::new(pool));
+                }
+                Err(e) => failed.push((name, e.to_string())),
+            }
+        }
+
+        Self { pools, failed }
+    }
+
+    pub fn pool(&self, name: &str) -> Option<&Arc<StructureTemplatePool>> {
+        self.pools.get(name)
+    }
+
+    /// Names of the pools, which failed to load, with the reasons.
+    pub fn failed(&self) -> &[(String, String)] {
+        &self.failed
+    }
+}
+
+struct PoolLoader<'a> {
+    packs: &'a DataPacks,
+    tags: &'a BlockTags,
+    palette: &'a BlockGlobalPalette,
+    features: &'a Resolver<ConfiguredFeature>,
+    placements: &'a Resolver<PlacementModifiers>,
+    templates: HashMap<String, Arc<StructureTemplate>>,
+    processor_lists: HashMap<String, ProcessorList>,
+}
+
+impl PoolLoader<'_> {
+    fn pool(&mut self, name: &str) -> anyhow::Result<StructureTemplatePool> {
+        let json = match self.packs.read_json("worldgen/template_pool", name)? {
+            Some(json) => json,
+            // Vanilla registers the empty pool in code.
+            None if name == EMPTY_POOL => return Ok(StructureTemplatePool::empty()),
+            None => return Err(anyhow!("No pool {} in data packs", name)),
+        };
+
+        let fallback = match json.get("fallback") {
+            Some(Value::String(fallback)) => fallback.clone(),
+            _ => return Err(anyhow!("No \"fallback\" key in pool {}", name))
+        };
+
+        let Some(Value::Array(elements)) = json.get("elements") else {
+            return Err(anyhow!("No \"elements\" key in pool {}", name))
+        };
+
+        let mut templates = Vec::new();
+        for entry in elements {
+            let weight = entry
+                .get("weight")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| anyhow!("No \"weight\" key in {:?}", entry))?;
+
+            let element = Arc::new(self.element(
+                entry.get("element").ok_or_else(|| anyhow!("No \"element\" key in {:?}", entry))?,
+                None
+            )?);
+
+            templates.extend((0..weight).map(|_| element.clone()));
+        }
+
+        Ok(StructureTemplatePool::new(name.to_owned(), fallback, templates))
+    }
+
+    /// Reads the element. Lists force their projection on their elements.
+    fn element(&mut self, json: &Value, projection: Option<Projection>) -> anyhow::Result<PoolElement> {
+        let Some(Value::String(ty)) = json.get("element_type") else {
+            return Err(anyhow!("No \"element_type\" key in {:?}", json))
+        };
+
+        if ty == "minecraft:empty_pool_element" {
+            return Ok(PoolElement::Empty)
+        }
+
+        let projection = match (projection, json.get("projection")) {
+            (Some(projection), _) => projection,
+            (None, Some(Value::String(name))) => Projection::from_name(name)
+                .ok_or_else(|| anyhow!("Unknown projection {}", name))?,
+            _ => return Err(anyhow!("No \"projection\" key in {:?}", json))
+        };
+
+        let element = match ty.as_str() {
+            "minecraft:single_pool_element" | "minecraft:legacy_single_pool_element" => {
+                let legacy = ty == "minecraft:legacy_single_pool_element";
+
+                let Some(Value::String(location)) = json.get("location") else {
+                    return Err(anyhow!("No \"location\" key in {:?}", json))
+                };
+
+                let (processors_name, list) = match json.get("processors") {
+                    Some(Value::String(name)) => (Some(name.clone()), self.processor_list(name)?),
+                    Some(list) => (None, StructureProcessors::list_from_json(list, self.tags, self.palette)?),
+                    None => return Err(anyhow!("No \"processors\" key in {:?}", json))
+                };
+
+                // Vanilla SinglePoolElement::getSettings().
+                let mut processors = Vec::new();
+                if !legacy {
+                    processors.push(Arc::new(StructureProcessors::ignore(&[Block::STRUCTURE_BLOCK])));
+                }
+                processors.push(Arc::new(StructureProcessors::JigsawReplacement));
+                processors.extend(list);
+                processors.extend(projection.processors());
+                if legacy {
+                    processors.push(Arc::new(StructureProcessors::ignore(&[Block::STRUCTURE_BLOCK, Block::AIR])));
+                }
+
+                PoolElement::Single(SinglePoolElement {
+                    location: location.clone(),
+                    legacy,
+                    template: self.template(location)?,
+                    processors_name,
+                    projection,
+                    processors,
+                })
+            }
+            "minecraft:list_pool_element" => {
+                let Some(Value::Array(elements)) = json.get("elements") else {
+                    return Err(anyhow!("No \"elements\" key in {:?}", json))
+                };
+
+                let elements = elements
+                    .iter()
+                    .map(|element| self.element(element, Some(projection)))
+                    .collect::<anyhow::Result<_>>()?;
+
+                PoolElement::List(elements, projection)
+            }
+            "minecraft:feature_pool_element" => {
+                let Some(Value::String(name)) = json.get("feature") else {
+                    return Err(anyhow!("No \"feature\" key in {:?}", json))
+                };
+
+                let air = self.palette
+                    .get_default_obj_by_index(Block::AIR)
+                    .ok_or_else(|| anyhow!("No air state"))?;
+
+                PoolElement::Feature(FeaturePoolElement {
+                    name: name.clone(),
+                    feature: Arc::new(PlacedFeature::from_json(&Value::String(name.clone()), self.features, self.placements)?),
+                    projection,
+                    jigsaw: Arc::new(Jigsaw {
+                        name: "minecraft:bottom".to_owned(),
+                        target: EMPTY_POOL.to_owned(),
+                        pool: EMPTY_POOL.to_owned(),
+                        rollable: true,
+                        final_state: Some(air),
+                    }),
+                })
+            }
+            _ => return Err(anyhow!("Unsupported pool element type {}", ty))
+        };
+
+        Ok(element)
+    }
+
+    fn template(&mut self, location: &str) -> anyhow::Result<Arc<StructureTemplate>> {
+        if let Some(template) = self.templates.get(location) {
+            return Ok(template.clone())
+        }
+
+        let bytes = self.packs
+            .read("structures", location, "nbt")?
+            .ok_or_else(|| anyhow!("No template {} in data packs", location))?;
+        let template = Arc::new(
+            StructureTemplate::from_gzip(bytes.as_slice(), self.palette)
+                .map_err(|e| anyhow!("Unable to read template {}: {}", location, e))?
+        );
+        self.templates.insert(location.to_owned(), template.clone());
+
+        Ok(template)
+    }
+
+    fn processor_list(&mut self, name: &str) -> anyhow::Result<ProcessorList> {
+        if let Some(list) = self.processor_lists.get(name) {
+            return Ok(list.clone())
+        }
+
+        let list = match self.packs.read_json("worldgen/processor_list", name)? {
+            Some(json) => StructureProcessors::list_from_json(&json, self.tags, self.palette)
+                .map_err(|e| anyhow!("Unable to read processor list {}: {}", name, e))?,
+            None if name == EMPTY_POOL => Vec::new(),
+            None => return Err(anyhow!("No processor list {} in data packs", name)),
+        };
+
+        self.processor_lists.insert(name.to_owned(), list.clone());
+
+        Ok(list)
+    }
+}
@@ -0,0 +1,357 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::rule_test::{RuleTest, RuleTests};
+use crate::feature::state::block_state_from_json;
+use crate::rng::Rng;
+use crate::structure::template::{Jigsaw, TemplateBlock};
+use crate::structure::{heightmap_from_name, legacy_rng, StructureRng};
+use crate::tag::{BlockSet, BlockTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::heightmap::HeightmapType;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Block of a template on its way through the processors, already moved to
+/// its position in the world.
+#[derive(Clone)]
+pub struct ProcessedBlock {
+    pub pos: Vector3,
+    pub state: Arc<BlockState>,
+    pub jigsaw: Option<Arc<Jigsaw>>,
+}
+
+pub type ProcessorList = Vec<Arc<StructureProcessors>>;
+
+/// Processors change blocks of templates while they are placed, e.g. age
+/// cobblestone of zombie villages or drop the structure blocks.
+pub enum StructureProcessors {
+    BlockIgnore(BlockSet),
+    BlockRot(BlockRotProcessor),
+    Gravity(GravityProcessor),
+    Rule(Vec<ProcessorRule>),
+    ProtectedBlocks(BlockSet),
+    JigsawReplacement,
+    Nop,
+}
+
+impl StructureProcessors {
+    pub fn ignore(blocks: &[&'static Block]) -> Self {
+        StructureProcessors::BlockIgnore(Arc::new(blocks.iter().copied().collect()))
+    }
+
+    /// Reads the processor list, either given inline or as a name of
+    /// `worldgen/processor_list/*.json`.
+    pub fn list_from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<ProcessorList> {
+        let Some(Value::Array(processors)) = json.get("processors") else {
+            return Err(anyhow!("No \"processors\" key in {:?}", json))
+        };
+
+        processors
+            .iter()
+            .map(|processor| Self::from_json(processor, tags, palette).map(Arc::new))
+            .collect()
+    }
+
+    pub fn from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("processor_type") else {
+            return Err(anyhow!("No \"processor_type\" key in {:?}", json))
+        };
+
+        let processor = match ty.as_str() {
+            "minecraft:block_ignore" => {
+                let Some(Value::Array(states)) = json.get("blocks") else {
+                    return Err(anyhow!("No \"blocks\" key in {:?}", json))
+                };
+
+                let blocks = states
+                    .iter()
+                    .map(|state| block_state_from_json(state, palette).map(|state| state.block()))
+                    .collect::<anyhow::Result<HashSet<_>>>()?;
+
+                StructureProcessors::BlockIgnore(Arc::new(blocks))
+            }
+            "minecraft:block_rot" => StructureProcessors::BlockRot(BlockRotProcessor {
+                integrity: json
+                    .get("integrity")
+                    .and_then(Value::as_f64)
+                    .map(|integrity| integrity as f32)
+                    .ok_or_else(|| anyhow!("No \"integrity\" key in {:?}", json))?,
+                rottable_blocks: json
+                    .get("rottable_blocks")
+                    .map(|blocks| tags.resolve_holder_set(blocks))
+                    .transpose()?,
+            }),
+            "minecraft:gravity" => StructureProcessors::Gravity(GravityProcessor {
+                heightmap: match json.get("heightmap") {
+                    Some(Value::String(name)) => heightmap_from_name(name)?,
+                    _ => HeightmapType::WorldSurfaceWg,
+                },
+                offset: json.get("offset").and_then(Value::as_i64).unwrap_or(0) as i32,
+            }),
+            "minecraft:rule" => {
+                let Some(Value::Array(rules)) = json.get("rules") else {
+                    return Err(anyhow!("No \"rules\" key in {:?}", json))
+                };
+
+                StructureProcessors::Rule(
+                    rules
+                        .iter()
+                        .map(|rule| ProcessorRule::from_json(rule, tags, palette))
+                        .collect::<anyhow::Result<_>>()?
+                )
+            }
+            "minecraft:protected_blocks" => StructureProcessors::ProtectedBlocks(
+                tags.resolve_holder_set(json.get("value").ok_or_else(|| anyhow!("No \"value\" key in {:?}", json))?)?
+            ),
+            "minecraft:jigsaw_replacement" => StructureProcessors::JigsawReplacement,
+            "minecraft:nop" => StructureProcessors::Nop,
+            _ => return Err(anyhow!("Unsupported structure processor type {}", ty))
+        };
+
+        Ok(processor)
+    }
+
+    /// Equivalent of vanilla `StructureProcessor::processBlock()`. `original`
+    /// is the block as the template has it, `current` is the block as the
+    /// previous processors left it. `None` drops the block.
+    pub fn process(
+        &self,
+        level: &WorldGenLevel,
+        pivot: Vector3,
+        original: &TemplateBlock,
+        current: ProcessedBlock,
+    ) -> Option<ProcessedBlock> {
+        match self {
+            StructureProcessors::BlockIgnore(blocks) => (!blocks.contains(current.state.block())).then_some(current),
+            StructureProcessors::BlockRot(x) => x.process(original, current),
+            StructureProcessors::Gravity(x) => Some(x.process(level, original, current)),
+            StructureProcessors::Rule(rules) => {
+                let mut rng = legacy_rng(current.pos.seed());
+                let existing = level.block_state(current.pos);
+
+                let output = rules
+                    .iter()
+                    .find(|rule| rule.test(current.pos, pivot, &current.state, &existing, &mut rng))
+                    .map(|rule| rule.output.clone());
+
+                match output {
+                    Some(state) => Some(ProcessedBlock { state, ..current }),
+                    None => Some(current),
+                }
+            }
+            StructureProcessors::ProtectedBlocks(blocks) => {
+                (!blocks.contains(level.block_state(current.pos).block())).then_some(current)
+            }
+            StructureProcessors::JigsawReplacement => {
+                if current.state.block() != Block::JIGSAW {
+                    return Some(current)
+                }
+
+                let state = current.jigsaw.as_ref()?.final_state.clone()?;
+
+                Some(ProcessedBlock { state, jigsaw: None, ..current })
+            }
+            StructureProcessors::Nop => Some(current),
+        }
+    }
+}
+
+/// Removes blocks at random, e.g. from ruined portals.
+pub struct BlockRotProcessor {
+    pub integrity: f32,
+    /// Only these blocks rot, if given.
+    pub rottable_blocks: Option<BlockSet>,
+}
+
+impl BlockRotProcessor {
+    fn process(&self, original: &TemplateBlock, current: ProcessedBlock) -> Option<ProcessedBlock> {
+        let mut rng = legacy_rng(current.pos.seed());
+        let rottable = self
+            .rottable_blocks
+            .as_ref()
+            .is_none_or(|blocks| blocks.contains(original.state.block()));
+
+        if rottable && rng.next_f32() > self.integrity {
+            return None
+        }
+
+        Some(current)
+    }
+}
+
+/// Moves blocks to the surface, keeping their height within the template.
+/// Used by terrain matching pieces, e.g. village streets.
+pub struct GravityProcessor {
+    pub heightmap: HeightmapType,
+    pub offset: i32,
+}
+
+impl GravityProcessor {
+    fn process(&self, level: &WorldGenLevel, original: &TemplateBlock, current: ProcessedBlock) -> ProcessedBlock {
+        let y = level.height(self.heightmap, current.pos.x, current.pos.z) + self.offset + original.pos.y;
+
+        ProcessedBlock {
+            pos: Vector3::new(current.pos.x, y, current.pos.z),
+            ..current
+        }
+    }
+}
+
+/// Replaces the block with `output`, if the block, the block it replaces and
+/// its position pass the tests.
+pub struct ProcessorRule {
+    pub input: RuleTests,
+    pub location: RuleTests,
+    pub position: PosRuleTests,
+    pub output: Arc<BlockState>,
+}
+
+impl ProcessorRule {
+    fn from_json(json: &Value, tags: &BlockTags, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let field = |name: &str| json.get(name).ok_or_else(|| anyhow!("No \"{}\" key in {:?}", name, json));
+
+        Ok(Self {
+            input: RuleTests::from_json(field("input_predicate")?, tags, palette)?,
+            location: RuleTests::from_json(field("location_predicate")?, tags, palette)?,
+            position: match json.get("position_predicate") {
+                Some(position) => PosRuleTests::from_json(position)?,
+                None => PosRuleTests::AlwaysTrue,
+            },
+            output: block_state_from_json(field("output_state")?, palette)?,
+        })
+    }
+
+    fn test(&self, pos: Vector3, pivot: Vector3, state: &BlockState, existing: &BlockState, rng: &mut StructureRng) -> bool {
+        self.input.test(state, rng) && self.location.test(existing, rng) && self.position.test(pos, pivot, rng)
+    }
+}
+
+/// Tests of the block position within the structure. The chance grows
+/// linearly with the distance from the pivot of the structure.
+pub enum PosRuleTests {
+    AlwaysTrue,
+    LinearPos(LinearPosTest),
+    AxisAlignedLinearPos(LinearPosTest, Axis),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl PosRuleTests {
+    fn from_json(json: &Value) -> anyhow::Result<Self> {
+        let Some(Value::String(ty)) = json.get("predicate_type") else {
+            return Err(anyhow!("No \"predicate_type\" key in {:?}", json))
+        };
+
+        let float = |name: &str| json.get(name).and_then(Value::as_f64).unwrap_or(0.0) as f32;
+        let int = |name: &str| json.get(name).and_then(Value::as_i64).unwrap_or(0) as i32;
+
+        let linear = || LinearPosTest {
+            min_chance: float("min_chance"),
+            max_chance: float("max_chance"),
+            min_dist: int("min_dist"),
+            max_dist: int("max_dist"),
+        };
+
+        let test = match ty.as_str() {
+            "minecraft:always_true" => PosRuleTests::AlwaysTrue,
+            "minecraft:linear_pos" => PosRuleTests::LinearPos(linear()),
+            "minecraft:axis_aligned_linear_pos" => {
+                let axis = match json.get("axis").and_then(Value::as_str).unwrap_or("y") {
+                    "x" => Axis::X,
+                    "y" => Axis::Y,
+                    "z" => Axis::Z,
+                    axis => return Err(anyhow!("Unknown axis {}", axis))
+                };
+
+                PosRuleTests::AxisAlignedLinearPos(linear(), axis)
+            }
+            _ => return Err(anyhow!("Unknown position rule test type {}", ty))
+        };
+
+        Ok(test)
+    }
+
+    /// `pivot` is the position the structure is rotated around.
+    fn test(&self, relative: Vector3, pivot: Vector3, rng: &mut StructureRng) -> bool {
+        match self {
+            PosRuleTests::AlwaysTrue => true,
+            PosRuleTests::LinearPos(x) => {
+                let distance = (relative.x - pivot.x).abs() + (relative.y - pivot.y).abs() + (relative.z - pivot.z).abs();
+
+                x.test(distance, rng)
+            }
+            PosRuleTests::AxisAlignedLinearPos(x, axis) => {
+                let distance = match axis {
+                    Axis::X => relative.x - pivot.x,
+                    Axis::Y => relative.y - pivot.y,
+                    Axis::Z => relative.z - pivot.z,
+                };
+
+                x.test(distance.abs(), rng)
+            }
+        }
+    }
+}
+
+pub struct LinearPosTest {
+    pub min_chance: f32,
+    pub max_chance: f32,
+    pub min_dist: i32,
+    pub max_dist: i32,
+}
+
+impl LinearPosTest {
+    /// Vanilla computes the chance with floats, so does this. Equal distances
+    /// give NaN, which never passes.
+    fn test(&self, distance: i32, rng: &mut StructureRng) -> bool {
+        let part = (distance - self.min_dist) as f32 / (self.max_dist - self.min_dist) as f32;
+        let chance = if part < 0.0 {
+            self.min_chance
+        } else if part > 1.0 {
+            self.max_chance
+        } else {
+            self.min_chance + part * (self.max_chance - self.min_chance)
+        };
+
+        rng.next_f32() <= chance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structure::legacy_rng;
+    use crate::structure::processor::LinearPosTest;
+
+    #[test]
+    fn linear_pos_chance() {
+        let test = LinearPosTest {
+            min_chance: 0.0,
+            max_chance: 1.0,
+            min_dist: 10,
+            max_dist: 20,
+        };
+
+        let mut rng = legacy_rng(42);
+        assert!(!test.test(5, &mut rng));
+        assert!(test.test(25, &mut rng));
+
+        // Nothing passes, when the distances are not given.
+        let test = LinearPosTest {
+            min_chance: 0.0,
+            max_chance: 0.0,
+            min_dist: 0,
+            max_dist: 0,
+        };
+
+        assert!(!test.test(0, &mut rng));
+    }
+}
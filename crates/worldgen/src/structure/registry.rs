@@ -0,0 +1,252 @@
+use crate::carver::large_feature_rng;
+use crate::feature::placement::PlacementModifiers;
+use crate::feature::ConfiguredFeature;
+use crate::noise::json::Resolver;
+use crate::pack::DataPacks;
+use crate::rng::Rng;
+use crate::structure::placement::StructurePlacement;
+use crate::structure::pool::PoolRegistry;
+use crate::structure::{step_from_json, Structure, StructureContext, StructureStart, StructureStarts, StructureType};
+use crate::tag::{BiomeTags, BlockTags};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use spherix_world::chunk::pos::ChunkPos;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Structure set from `worldgen/structure_set/*.json`. At most one structure
+/// of the set starts at a placement chunk.
+pub struct StructureSet {
+    pub name: String,
+    /// Structures with their weights.
+    pub structures: Vec<(String, u32)>,
+    pub placement: Arc<StructurePlacement>,
+}
+
+/// Structures and structure sets of the data, along with the template pools
+/// of jigsaw structures.
+pub struct StructureRegistry {
+    structures: HashMap<String, Arc<Structure>>,
+    sets: Vec<StructureSet>,
+    placements: HashMap<String, Arc<StructurePlacement>>,
+    /// Names of all the structures of each decoration step, including the
+    /// unsupported ones, since the index within the step seeds the placement.
+    steps: Vec<Vec<String>>,
+    pools: PoolRegistry,
+    failed: Vec<(String, String)>,
+}
+
+impl StructureRegistry {
+    /// Loads the structures of all namespaces of the data packs. Entries are
+    /// read in the order of their ids, as vanilla registers them. Sets, none of which structures may start in the
+    /// `possible_biomes` of the dimension, are skipped.
+    #[allow(clippy::too_many_arguments)]
+    pub fn load(
+        packs: &DataPacks,
+        block_tags: &BlockTags,
+        biome_tags: &BiomeTags,
+        possible_biomes: &[String],
+        palette: &BlockGlobalPalette,
+        features: &Resolver<ConfiguredFeature>,
+        placements: &Resolver<PlacementModifiers>,
+    ) -> anyhow::Result<Self> {
+        let mut failed = Vec::new();
+        let mut structures = HashMap::new();
+        let mut steps = Vec::new();
+
+        for (name, json) in read_all(packs, "worldgen/structure")? {
+            let step = match step_from_json(&json) {
+                Ok(step) => step,
+                Err(e) => {
+                    failed.push((name, e.to_string()));
+                    continue
+                }
+            };
+
+            if steps.len() <= step {
+                steps.resize_with(step + 1, Vec::new);
+            }
+            steps[step].push(name.clone());
+
+            match Structure::from_json(&name, &json, biome_tags) {
+                Ok(structure) => {
+                    structures.insert(name, Arc::new(structure));
+                }
+                Err(e) => failed.push((name, e.to_string())),
+            }
+        }
+
+        let mut sets = Vec::new();
+        let mut set_placements = HashMap::new();
+
+        for (name, json) in read_all(packs, "worldgen/structure_set")? {
+            match Self::set_from_json(&name, &json, biome_tags) {
+                Ok(set) => {
+                    set_placements.insert(name, set.placement.clone());
+
+                    let possible = set.structures.iter().any(|(name, _)| {
+                        structures
+                            .get(name)
+                            .is_some_and(|structure| possible_biomes.iter().any(|biome| structure.biomes.contains(biome)))
+                    });
+
+                    if possible {
+                        sets.push(set);
+                    }
+                }
+                Err(e) => failed.push((name, e.to_string())),
+            }
+        }
+
+        let start_pools = structures
+            .values()
+            .map(|structure| match &structure.ty {
+                StructureType::Jigsaw(jigsaw) => jigsaw.start_pool.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        let pools = PoolRegistry::load(packs, start_pools, block_tags, palette, features, placements);
+
+        Ok(Self {
+            structures,
+            sets,
+            placements: set_placements,
+            steps,
+            pools,
+            failed,
+        })
+    }
+
+    fn set_from_json(name: &str, json: &Value, biome_tags: &BiomeTags) -> anyhow::Result<StructureSet> {
+        let Some(Value::Array(entries)) = json.get("structures") else {
+            return Err(anyhow!("No \"structures\" key in {:?}", json))
+        };
+
+        let structures = entries
+            .iter()
+            .map(|entry| match (entry.get("structure"), entry.get("weight").and_then(Value::as_u64)) {
+                (Some(Value::String(structure)), Some(weight)) => Ok((structure.clone(), weight as u32)),
+                _ => Err(anyhow!("Invalid structure set entry {:?}", entry))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(StructureSet {
+            name: name.to_owned(),
+            structures,
+            placement: Arc::new(StructurePlacement::from_json(
+                json.get("placement").ok_or_else(|| anyhow!("No \"placement\" key in {:?}", json))?,
+                biome_tags
+            )?),
+        })
+    }
+
+    /// Equivalent of vanilla `ChunkGenerator::createStructures()`. Sets are
+    /// tried one by one, a set with several structures picks one of them by
+    /// weight and tries the others, if the picked one does not generate.
+    pub fn create_starts(&self, ctx: &StructureContext, pos: &ChunkPos) -> StructureStarts {
+        let mut starts = Vec::new();
+
+        for set in &self.sets {
+            // Nothing would be generated, so the placement is not computed.
+            if !set.structures.iter().any(|(name, _)| self.structures.contains_key(name)) {
+                continue
+            }
+
+            if !set.placement.is_structure_chunk(ctx, pos.x(), pos.z(), &self.placements) {
+                continue
+            }
+
+            if let [(name, _)] = set.structures.as_slice() {
+                starts.extend(self.generate(ctx, name, pos));
+                continue
+            }
+
+            let mut candidates = set.structures.clone();
+            let mut total = candidates.iter().map(|(_, weight)| *weight).sum::<u32>();
+            let mut rng = large_feature_rng(ctx.seed, pos.x(), pos.z());
+
+            while !candidates.is_empty() && total > 0 {
+                let mut picked = rng.next_u32(total) as i64;
+                let index = candidates
+                    .iter()
+                    .position(|(_, weight)| {
+                        picked -= *weight as i64;
+                        picked < 0
+                    })
+                    .unwrap_or(candidates.len() - 1);
+
+                let (name, weight) = candidates.remove(index);
+
+                // Vanilla would try to generate the unsupported structure,
+                // whose outcome is unknown, so the rest are not tried.
+                if !self.structures.contains_key(&name) {
+                    break
+                }
+
+                if let Some(start) = self.generate(ctx, &name, pos) {
+                    starts.push(start);
+                    break
+                }
+
+                total -= weight;
+            }
+        }
+
+        starts.into()
+    }
+
+    fn generate(&self, ctx: &StructureContext, name: &str, pos: &ChunkPos) -> Option<Arc<StructureStart>> {
+        self.structures
+            .get(name)?
+            .generate(ctx, &self.pools, pos)
+            .map(Arc::new)
+    }
+
+    pub fn structure(&self, name: &str) -> Option<&Arc<Structure>> {
+        self.structures.get(name)
+    }
+
+    pub fn sets(&self) -> &[StructureSet] {
+        &self.sets
+    }
+
+    /// Keeps only the sets, which are placed, e.g. the structure overrides of
+    /// a superflat world. Exclusion zones still see all the sets.
+    pub fn retain_sets<F: Fn(&StructureSet) -> bool>(&mut self, f: F) {
+        self.sets.retain(f);
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Names of the structures placed at the decoration step, in the order
+    /// they are placed.
+    pub fn structures_in_step(&self, step: usize) -> &[String] {
+        self.steps.get(step).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn pools(&self) -> &PoolRegistry {
+        &self.pools
+    }
+
+    /// Names of the structures, structure sets and template pools, which
+    /// failed to load, with the reasons.
+    pub fn failed(&self) -> Vec<(String, String)> {
+        self.failed.iter().chain(self.pools.failed()).cloned().collect()
+    }
+}
+
+/// Reads all JSON entries of the registry, ordered by their ids.
+fn read_all(packs: &DataPacks, registry: &str) -> anyhow::Result<Vec<(String, Value)>> {
+    packs
+        .ids(registry, "json")?
+        .into_iter()
+        .map(|id| {
+            let json = packs.json(registry, &id)?;
+
+            Ok((id, json))
+        })
+        .collect()
+}
@@ -0,0 +1,410 @@
+use crate::feature::level::WorldGenLevel;
+use crate::feature::predicate::{fluid, is_face_sturdy};
+use crate::feature::state::{block_state_from_json, property, with_property};
+use crate::feature::Direction;
+use crate::rng::Rng;
+use crate::structure::bounding_box::BoundingBox;
+use crate::structure::processor::{ProcessedBlock, StructureProcessors};
+use crate::structure::{legacy_rng, Rotation};
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_math::vector::Vector3;
+use spherix_util::nbt::blob_to_compound;
+use spherix_world::block::block::Block;
+use spherix_world::block::state::BlockState;
+use spherix_world::chunk::palette::BlockGlobalPalette;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
+
+/// Jigsaw block of a template, i.e. the point where other pieces attach.
+#[derive(Debug)]
+pub struct Jigsaw {
+    pub name: String,
+    pub target: String,
+    pub pool: String,
+    /// Whether the attached piece may be turned around the front of the
+    /// jigsaw, see [`PlacedJigsaw::can_attach()`].
+    pub rollable: bool,
+    /// State the jigsaw is replaced with. `None` for structure void, i.e.
+    /// nothing is placed instead of the jigsaw.
+    pub final_state: Option<Arc<BlockState>>,
+}
+
+impl Jigsaw {
+    /// Reads the block entity data of the jigsaw. `front` of the jigsaw block
+    /// decides the joint, if it is not given.
+    pub fn from_nbt(nbt: &HashMap<String, nbt::Value>, front: Direction, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let string = |name: &str| match nbt.get(name) {
+            Some(nbt::Value::String(value)) => Ok(value.clone()),
+            _ => Err(anyhow!("No \"{}\" key in jigsaw", name))
+        };
+
+        let rollable = match nbt.get("joint") {
+            Some(nbt::Value::String(joint)) if joint == "rollable" => true,
+            Some(nbt::Value::String(joint)) if joint == "aligned" => false,
+            _ => matches!(front, Direction::Up | Direction::Down),
+        };
+
+        let final_state = parse_block_state(&string("final_state")?, palette)?;
+
+        Ok(Self {
+            name: string("name")?,
+            target: string("target")?,
+            pool: string("pool")?,
+            rollable,
+            final_state: (final_state.block() != Block::STRUCTURE_VOID).then_some(final_state),
+        })
+    }
+}
+
+/// Jigsaw of a template placed at some position with some rotation.
+#[derive(Debug, Clone)]
+pub struct PlacedJigsaw {
+    pub pos: Vector3,
+    pub front: Direction,
+    pub top: Direction,
+    pub jigsaw: Arc<Jigsaw>,
+}
+
+impl PlacedJigsaw {
+    /// Equivalent of vanilla `JigsawBlock::canAttach()`: jigsaws face each
+    /// other and the target of this one is the name of the other one.
+    pub fn can_attach(&self, other: &PlacedJigsaw) -> bool {
+        self.front == other.front.opposite()
+            && (self.jigsaw.rollable || self.top == other.top)
+            && self.jigsaw.target == other.jigsaw.name
+    }
+}
+
+/// Block of a template relatively to its origin.
+pub struct TemplateBlock {
+    pub pos: Vector3,
+    pub state: Arc<BlockState>,
+    pub jigsaw: Option<Arc<Jigsaw>>,
+}
+
+/// Structure template from `structures/*.nbt`. Templates with several
+/// palettes (e.g. shipwrecks) pick one of them by the position.
+pub struct StructureTemplate {
+    pub size: Vector3,
+    /// Blocks of each palette: full blocks first, then the rest, then the
+    /// blocks with block entities, each group ordered by y, x and z.
+    palettes: Vec<Vec<TemplateBlock>>,
+}
+
+impl StructureTemplate {
+    /// Reads the gzipped template.
+    pub fn from_gzip<R: Read>(mut reader: R, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let blob = nbt::Blob::from_gzip_reader(&mut reader)?;
+
+        Self::from_nbt(&blob_to_compound(&blob), palette)
+    }
+
+    pub fn from_nbt(nbt: &HashMap<String, nbt::Value>, palette: &BlockGlobalPalette) -> anyhow::Result<Self> {
+        let size = match nbt.get("size").map(int_list).transpose()?.as_deref() {
+            Some([x, y, z]) => Vector3::new(*x, *y, *z),
+            _ => return Err(anyhow!("No \"size\" key in template"))
+        };
+
+        let Some(nbt::Value::List(blocks)) = nbt.get("blocks") else {
+            return Err(anyhow!("No \"blocks\" key in template"))
+        };
+
+        let palettes = match (nbt.get("palettes"), nbt.get("palette")) {
+            (Some(nbt::Value::List(palettes)), _) => palettes.iter().map(list).collect::<anyhow::Result<Vec<_>>>()?,
+            (_, Some(nbt::Value::List(states))) => vec![states],
+            _ => return Err(anyhow!("No \"palette\" key in template"))
+        };
+
+        let palettes = palettes
+            .into_iter()
+            .map(|states| {
+                let states = states
+                    .iter()
+                    .map(|state| state_from_nbt(state, palette))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+
+                Self::read_blocks(blocks, &states, palette)
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            size,
+            palettes,
+        })
+    }
+
+    fn read_blocks(
+        blocks: &[nbt::Value],
+        states: &[Arc<BlockState>],
+        palette: &BlockGlobalPalette,
+    ) -> anyhow::Result<Vec<TemplateBlock>> {
+        let mut full = Vec::new();
+        let mut other = Vec::new();
+        let mut with_nbt = Vec::new();
+
+        for block in blocks {
+            let nbt::Value::Compound(block) = block else {
+                return Err(anyhow!("Expected compound, found {:?}", block))
+            };
+
+            let pos = match block.get("pos").map(int_list).transpose()?.as_deref() {
+                Some([x, y, z]) => Vector3::new(*x, *y, *z),
+                _ => return Err(anyhow!("No \"pos\" key in template block"))
+            };
+
+            let state = match block.get("state") {
+                Some(nbt::Value::Int(index)) => states
+                    .get(*index as usize)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No state {} in template palette", index))?,
+                _ => return Err(anyhow!("No \"state\" key in template block"))
+            };
+
+            let block_entity = match block.get("nbt") {
+                Some(nbt::Value::Compound(block_entity)) => Some(block_entity),
+                _ => None,
+            };
+
+            let jigsaw = match block_entity {
+                Some(block_entity) if state.block() == Block::JIGSAW => {
+                    let (front, _) = orientation(&state)?;
+                    Some(Arc::new(Jigsaw::from_nbt(block_entity, front, palette)?))
+                }
+                _ => None,
+            };
+
+            let block = TemplateBlock { pos, state, jigsaw };
+
+            if block_entity.is_some() {
+                with_nbt.push(block);
+            } else if is_face_sturdy(&block.state) {
+                full.push(block);
+            } else {
+                other.push(block);
+            }
+        }
+
+        for list in [&mut full, &mut other, &mut with_nbt] {
+            list.sort_by_key(|block| (block.pos.y, block.pos.x, block.pos.z));
+        }
+
+        Ok(full.into_iter().chain(other).chain(with_nbt).collect())
+    }
+
+    /// Blocks of the palette picked for the template placed at `pos`.
+    pub fn blocks(&self, pos: Vector3) -> &[TemplateBlock] {
+        match self.palettes.len() {
+            0 => &[],
+            len => &self.palettes[legacy_rng(pos.seed()).next_u32(len as u32) as usize],
+        }
+    }
+
+    pub fn bounding_box(&self, pos: Vector3, rotation: Rotation) -> BoundingBox {
+        let min = rotation.transform(Vector3::new(0, 0, 0));
+        let max = rotation.transform(Vector3::new(self.size.x - 1, self.size.y - 1, self.size.z - 1));
+
+        BoundingBox::from_corners(min, max).moved(pos.x, pos.y, pos.z)
+    }
+
+    /// Jigsaws of the template placed at `pos`, in the order of the palette.
+    pub fn jigsaws(&self, pos: Vector3, rotation: Rotation) -> Vec<PlacedJigsaw> {
+        self.blocks(pos)
+            .iter()
+            .filter_map(|block| {
+                let jigsaw = block.jigsaw.clone()?;
+                let (front, top) = orientation(&block.state).ok()?;
+
+                Some(PlacedJigsaw {
+                    pos: offset(rotation.transform(block.pos), pos),
+                    front: rotation.rotate(front),
+                    top: rotation.rotate(top),
+                    jigsaw,
+                })
+            })
+            .collect()
+    }
+
+    /// Equivalent of vanilla `StructureTemplate::placeInWorld()`. Blocks are
+    /// run through the processors, rotated and placed, if they are within
+    /// `chunk_box`. Water at the position is kept by waterlogging the block.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place(
+        &self,
+        level: &mut WorldGenLevel,
+        palette: &BlockGlobalPalette,
+        pos: Vector3,
+        pivot: Vector3,
+        rotation: Rotation,
+        processors: &[Arc<StructureProcessors>],
+        chunk_box: &BoundingBox,
+    ) -> bool {
+        let blocks = self.blocks(pos);
+        if blocks.is_empty() || self.size.x < 1 || self.size.y < 1 || self.size.z < 1 {
+            return false
+        }
+
+        for block in blocks {
+            let mut processed = Some(ProcessedBlock {
+                pos: offset(rotation.transform(block.pos), pos),
+                state: block.state.clone(),
+                jigsaw: block.jigsaw.clone(),
+            });
+
+            for processor in processors {
+                let Some(current) = processed else {
+                    break
+                };
+
+                processed = processor.process(level, pivot, block, current);
+            }
+
+            let Some(processed) = processed else {
+                continue
+            };
+
+            if !chunk_box.is_inside(processed.pos) {
+                continue
+            }
+
+            let keeps_water = is_water_source(&level.block_state(processed.pos));
+            let mut state = rotation.rotate_state(&processed.state, palette);
+
+            if keeps_water && property(&state, "waterlogged").is_some_and(|waterlogged| waterlogged == "false") {
+                state = with_property(&state, "waterlogged", "true", palette).unwrap_or(state);
+            }
+
+            level.set_block_state(processed.pos, state);
+        }
+
+        true
+    }
+}
+
+/// Front and top of the jigsaw block, e.g. `north_up`.
+fn orientation(state: &BlockState) -> anyhow::Result<(Direction, Direction)> {
+    property(state, "orientation")
+        .and_then(|orientation| {
+            let (front, top) = orientation.split_once('_')?;
+
+            Some((Direction::from_name(front)?, Direction::from_name(top)?))
+        })
+        .ok_or_else(|| anyhow!("No orientation of jigsaw {:?}", state))
+}
+
+/// Whether the block is, or is waterlogged with, water source.
+fn is_water_source(state: &BlockState) -> bool {
+    if state.block() == Block::WATER {
+        return property(state, "level").is_some_and(|level| level == "0")
+    }
+
+    fluid(state) == Some("minecraft:water")
+}
+
+/// Parses the block state as commands write it, e.g.
+/// `minecraft:oak_log[axis=y]`.
+pub fn parse_block_state(input: &str, palette: &BlockGlobalPalette) -> anyhow::Result<Arc<BlockState>> {
+    let (name, properties) = match input.split_once('[') {
+        Some((name, properties)) => (name, properties.trim_end_matches(']')),
+        None => (input, ""),
+    };
+
+    let name = if name.contains(':') {
+        name.to_owned()
+    } else {
+        format!("minecraft:{}", name)
+    };
+
+    let properties = properties
+        .split(',')
+        .filter_map(|property| property.split_once('='))
+        .map(|(name, value)| (name.trim().to_owned(), Value::String(value.trim().to_owned())))
+        .collect::<serde_json::Map<_, _>>();
+
+    block_state_from_json(&serde_json::json!({"Name": name, "Properties": properties}), palette)
+}
+
+fn state_from_nbt(nbt: &nbt::Value, palette: &BlockGlobalPalette) -> anyhow::Result<Arc<BlockState>> {
+    let nbt::Value::Compound(state) = nbt else {
+        return Err(anyhow!("Expected compound, found {:?}", nbt))
+    };
+
+    let Some(nbt::Value::String(name)) = state.get("Name") else {
+        return Err(anyhow!("No \"Name\" key in {:?}", state))
+    };
+
+    let properties = match state.get("Properties") {
+        Some(nbt::Value::Compound(properties)) => properties
+            .iter()
+            .filter_map(|(name, value)| match value {
+                nbt::Value::String(value) => Some((name.clone(), Value::String(value.clone()))),
+                _ => None,
+            })
+            .collect(),
+        _ => serde_json::Map::new(),
+    };
+
+    block_state_from_json(&serde_json::json!({"Name": name, "Properties": properties}), palette)
+}
+
+fn list(nbt: &nbt::Value) -> anyhow::Result<&Vec<nbt::Value>> {
+    match nbt {
+        nbt::Value::List(list) => Ok(list),
+        _ => Err(anyhow!("Expected list, found {:?}", nbt))
+    }
+}
+
+fn int_list(nbt: &nbt::Value) -> anyhow::Result<Vec<i32>> {
+    list(nbt)?
+        .iter()
+        .map(|value| match value {
+            nbt::Value::Int(value) => Ok(*value),
+            _ => Err(anyhow!("Expected int, found {:?}", value))
+        })
+        .collect()
+}
+
+pub fn offset(pos: Vector3, by: Vector3) -> Vector3 {
+    Vector3::new(pos.x + by.x, pos.y + by.y, pos.z + by.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::Direction;
+    use crate::structure::template::{Jigsaw, PlacedJigsaw};
+    use spherix_math::vector::Vector3;
+    use std::sync::Arc;
+
+    fn jigsaw(front: Direction, top: Direction, name: &str, target: &str, rollable: bool) -> PlacedJigsaw {
+        PlacedJigsaw {
+            pos: Vector3::new(0, 0, 0),
+            front,
+            top,
+            jigsaw: Arc::new(Jigsaw {
+                name: name.to_owned(),
+                target: target.to_owned(),
+                pool: "minecraft:empty".to_owned(),
+                rollable,
+                final_state: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn jigsaw_attachment() {
+        let street = jigsaw(Direction::North, Direction::Up, "minecraft:street", "minecraft:building_entrance", false);
+        let house = jigsaw(Direction::South, Direction::Up, "minecraft:building_entrance", "minecraft:street", false);
+
+        assert!(street.can_attach(&house));
+        assert!(house.can_attach(&street));
+        assert!(!street.can_attach(&jigsaw(Direction::North, Direction::Up, "minecraft:building_entrance", "", false)));
+        assert!(!street.can_attach(&jigsaw(Direction::South, Direction::Up, "minecraft:other", "", false)));
+
+        let down = jigsaw(Direction::Down, Direction::North, "minecraft:bottom", "minecraft:top", true);
+        let up = jigsaw(Direction::Up, Direction::East, "minecraft:top", "minecraft:bottom", false);
+
+        assert!(down.can_attach(&up));
+        assert!(!up.can_attach(&down));
+    }
+}
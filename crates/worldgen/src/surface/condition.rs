@@ -2,7 +2,7 @@ use crate::biome::temperature::cold_enough_to_snow;
 use crate::noise::math::map;
 use crate::noise::perlin::noise::Noise;
 use crate::noise::perlin::DefaultNoise;
-use crate::rng::{Rng, RngPos, XoroShiroPos};
+use crate::rng::{Rng, RngPos, WorldgenRngPos};
 use crate::surface::context::{Context, WorldGenerationContext};
 use spherix_math::vector::{Vector3, Vector3f};
 use spherix_world::chunk::vector::block::Vector2BlockSection;
@@ -243,7 +243,7 @@ impl VerticalAnchor {
 }
 
 pub struct VerticalGradientCondition {
-    pub random: Arc<XoroShiroPos>,
+    pub random: Arc<WorldgenRngPos>,
     pub true_at_and_below: i32,
     pub false_at_and_above: i32
 }
@@ -3,7 +3,7 @@ use crate::noise::density::density::DebugTree;
 use crate::noise::density::noise::NoiseHolder;
 use crate::noise::perlin::octave::{MultiOctaveNoiseFactory, MultiOctaveNoiseParameters};
 use crate::noise::perlin::DefaultNoise;
-use crate::rng::{RngForkable, RngPos, XoroShiroPos};
+use crate::rng::{RngForkable, RngPos, WorldgenRngPos};
 use crate::surface::level::SurfaceLevel;
 use spherix_world::chunk::heightmap::Heightmaps;
 use std::cell::OnceCell;
@@ -63,20 +63,21 @@ where
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct WorldGenerationContext {
     pub height: i32,
     pub min_y: i32
 }
 
 pub struct EntropyBag {
-    pub rng: Arc<XoroShiroPos>,
+    pub rng: Arc<WorldgenRngPos>,
     pub noises: Noises,
     noise_cache: Mutex<HashMap<String, Arc<DefaultNoise>>>,
-    pos_cache: Mutex<HashMap<String, Arc<XoroShiroPos>>>,
+    pos_cache: Mutex<HashMap<String, Arc<WorldgenRngPos>>>,
 }
 
 impl EntropyBag {
-    pub fn new(rng: Arc<XoroShiroPos>, noises: Noises) -> Self {
+    pub fn new(rng: Arc<WorldgenRngPos>, noises: Noises) -> Self {
         Self {
             rng,
             noises,
@@ -105,7 +106,7 @@ impl EntropyBag {
             .clone()
     }
 
-    pub fn get_or_create_rng_pos(&self, tag: String) -> Arc<XoroShiroPos> {
+    pub fn get_or_create_rng_pos(&self, tag: String) -> Arc<WorldgenRngPos> {
         self.pos_cache
             .lock()
             .unwrap()
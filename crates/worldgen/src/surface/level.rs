@@ -5,7 +5,7 @@ use crate::noise::density::noise::NoiseHolder;
 use crate::noise::math::{floor, lerp2};
 use crate::noise::perlin::{DefaultNoise, Noise};
 use crate::noise::settings::NoiseSettings;
-use crate::rng::{Rng, RngPos, XoroShiroPos};
+use crate::rng::{Rng, RngPos, WorldgenRngPos};
 use gxhash::GxBuildHasher;
 use spherix_math::vector::{Vector2, Vector3, Vector3f};
 use spherix_world::chunk::pos::ChunkPos;
@@ -13,7 +13,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 pub struct SurfaceLevel<'a> {
-    noise_chunk: NoiseChunk,
+    noise_chunk: &'a mut NoiseChunk,
     noise_settings: &'a NoiseSettings,
     pub block: Vector3,
     cell_height: usize,
@@ -31,7 +31,7 @@ pub struct SurfaceLevel<'a> {
     last_surface_depth2update: i64,
     surface_noise: Arc<NoiseHolder<DefaultNoise>>,
     preliminary_surface_level_cache: HashMap<i64, i32, GxBuildHasher>,
-    noise_rng: Arc<XoroShiroPos>,
+    noise_rng: Arc<WorldgenRngPos>,
 }
 
 impl<'a> SurfaceLevel<'a> {
@@ -42,7 +42,7 @@ impl<'a> SurfaceLevel<'a> {
     const SURFACE_CELL_SIZE: f32 = 16.0;
     const SURFACE_CELL_MASK: i32 = 15;
 
-    pub fn new(noise_chunk: NoiseChunk, noise_settings: &'a NoiseSettings, surface_noise: Arc<NoiseHolder<DefaultNoise>>, noise_rng: Arc<XoroShiroPos>) -> Self {
+    pub fn new(noise_chunk: &'a mut NoiseChunk, noise_settings: &'a NoiseSettings, surface_noise: Arc<NoiseHolder<DefaultNoise>>, noise_rng: Arc<WorldgenRngPos>) -> Self {
         Self {
             noise_chunk,
             noise_settings,
@@ -9,7 +9,7 @@ use crate::noise::settings::NoiseSettings;
 use crate::rng::{Rng, RngPos};
 use crate::surface::context::{Context, EntropyBag, WorldGenerationContext};
 use crate::surface::level::SurfaceLevel;
-use crate::surface::rule::Rule;
+use crate::surface::rule::{Rule, Rules};
 use crate::surface::rule_factory::{RuleFactories, RuleFactory};
 use num_traits::abs;
 use spherix_math::vector::{Vector3, Vector3f};
@@ -25,6 +25,7 @@ use spherix_world::chunk::vector::block::Vector2BlockSection;
 use spherix_world::chunk::vector::Vector3BlockColumn;
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct SurfaceMaterializer {
     palette: Arc<BlockGlobalPalette>
 }
@@ -41,8 +42,8 @@ impl SurfaceMaterializer {
         noise_settings: &NoiseSettings,
         entropy_bag: Arc<EntropyBag>,
         rule_factory: Arc<RuleFactories>,
-        noise_chunk: NoiseChunk,
-        biome_accessor: BiomeAccessor,
+        noise_chunk: &mut NoiseChunk,
+        biome_accessor: BiomeAccessor<'_>,
         chunk_column: &mut ChunkColumn,
     ) {
         let mut col_accessor = ColumnAccessor { horizontal_pos: Vector2BlockSection::origin() };
@@ -51,13 +52,15 @@ impl SurfaceMaterializer {
             &biome_accessor,
         );
         let cached_biome_gradient = LazyCachedBiomeGradient::new(&biome_gradient);
+        // The rules read the heightmaps while the chunk is written.
+        let heightmaps = chunk_column.inner().heightmaps.clone();
 
         let mut ctx = Context::new(
             WorldGenerationContext {
                 height: noise_settings.noise_height as i32,
                 min_y: noise_settings.noise_min_y,
             },
-            unsafe {&*(&chunk_column.inner().heightmaps as *const Heightmaps)},
+            &heightmaps,
             SurfaceLevel::new(
                 noise_chunk,
                 noise_settings,
@@ -148,7 +151,9 @@ impl SurfaceMaterializer {
                         
                         ctx.update_y(stone_depth_above, stone_depth_below, water_height, y, &cached_biome_gradient);
 
-                        if block == Block::STONE {
+                        // Only the default block of the dimension is replaced,
+                        // e.g. stone in the overworld or netherrack in the nether.
+                        if block == noise_settings.default_block.block() {
                             let block_state = rule.apply(Vector3::new(x, y, z), &mut ctx);
                             if block_state.is_some() {
                                 col_accessor.set_to(y, block_state.unwrap(), chunk_column);
@@ -325,6 +330,60 @@ impl SurfaceMaterializer {
     }
 }
 
+/// Equivalent of vanilla `SurfaceSystem.topMaterial()`. Evaluates the surface
+/// rules for single blocks, which carvers expose after the surface is built.
+pub struct TopMaterial<'a> {
+    ctx: Context<'a>,
+    rule: Rules,
+    biome_gradient: &'a LazyCachedBiomeGradient<'a>,
+}
+
+impl<'a> TopMaterial<'a> {
+    pub fn new(
+        noise_settings: &'a NoiseSettings,
+        entropy_bag: Arc<EntropyBag>,
+        rule_factory: &RuleFactories,
+        noise_chunk: &'a mut NoiseChunk,
+        heightmaps: &'a Heightmaps,
+        biome_gradient: &'a LazyCachedBiomeGradient<'a>,
+    ) -> Self {
+        let mut ctx = Context::new(
+            WorldGenerationContext {
+                height: noise_settings.noise_height as i32,
+                min_y: noise_settings.noise_min_y,
+            },
+            heightmaps,
+            SurfaceLevel::new(
+                noise_chunk,
+                noise_settings,
+                entropy_bag.noises.surface.clone(),
+                entropy_bag.rng.clone(),
+            ),
+            entropy_bag
+        );
+
+        let rule = rule_factory.create_rule(&mut ctx);
+
+        Self {
+            ctx,
+            rule,
+            biome_gradient,
+        }
+    }
+
+    /// The block is treated as the top one of a one block deep stone layer,
+    /// which is under water if `under_fluid` is set.
+    pub fn at(&mut self, at: Vector3, under_fluid: bool) -> Option<Arc<BlockState>> {
+        self.ctx.update_xz(at.x, at.z);
+        self.biome_gradient.at(at);
+
+        let water_height = if under_fluid { at.y + 1 } else { i32::MIN };
+        self.ctx.update_y(1, 1, water_height, at.y, self.biome_gradient);
+
+        self.rule.apply(at, &mut self.ctx)
+    }
+}
+
 struct ColumnAccessor {
     horizontal_pos: Vector2BlockSection
 }
@@ -337,15 +396,11 @@ impl ColumnAccessor {
 
     #[inline]
     fn get_from(&self, y: i32, from: &ChunkColumn) -> Arc<BlockState> {
-        unsafe {
-            from.with_unsafe().block_state(Vector3BlockColumn::new(self.horizontal_pos.x(), y, self.horizontal_pos.z()))
-        }
+        from.with_safe().block_state(Vector3BlockColumn::new(self.horizontal_pos.x(), y, self.horizontal_pos.z()))
     }
 
     #[inline]
     fn set_to(&self, y: i32, block: Arc<BlockState>, to: &mut ChunkColumn) {
-        unsafe {
-            to.with_unsafe_mut().set_block_state(Vector3BlockColumn::new(self.horizontal_pos.x(), y, self.horizontal_pos.z()), block);
-        }
+        to.with_safe_mut().set_block_state(Vector3BlockColumn::new(self.horizontal_pos.x(), y, self.horizontal_pos.z()), block);
     }
 }
@@ -0,0 +1,166 @@
+use crate::pack::DataPacks;
+use anyhow::anyhow;
+use serde_json::Value;
+use spherix_world::block::block::{Block, BLOCKS};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+pub type BlockSet = Arc<HashSet<&'static Block>>;
+
+/// Resolves block tags (e.g. `#minecraft:overworld_carver_replaceables`) from
+/// the data packs. Tags may refer to other tags, all of them are flattened
+/// into sets of blocks.
+pub struct BlockTags {
+    packs: Arc<DataPacks>,
+    cache: RefCell<HashMap<String, BlockSet>>,
+}
+
+impl BlockTags {
+    pub fn new(packs: Arc<DataPacks>) -> Self {
+        Self {
+            packs,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Resolves the list of blocks as it is written in worldgen configs: either
+    /// a tag prefixed with `#`, a single block or a list of blocks.
+    pub fn resolve_holder_set(&self, json: &Value) -> anyhow::Result<BlockSet> {
+        match json {
+            Value::String(name) => match name.strip_prefix('#') {
+                Some(tag) => self.tag(tag),
+                None => Ok(Arc::new(HashSet::from([block(name)?]))),
+            },
+            Value::Array(names) => {
+                let mut blocks = HashSet::new();
+                for name in names {
+                    let Value::String(name) = name else {
+                        return Err(anyhow!("Expected string, found {:?}", name))
+                    };
+
+                    blocks.insert(block(name)?);
+                }
+
+                Ok(Arc::new(blocks))
+            }
+            _ => Err(anyhow!("Expected string or array, found {:?}", json))
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> anyhow::Result<BlockSet> {
+        if let Some(blocks) = self.cache.borrow().get(name) {
+            return Ok(blocks.clone())
+        }
+
+        let mut blocks = HashSet::new();
+        for (id, required) in self.packs.tag_entries("tags/blocks", name)? {
+            let resolved = match id.strip_prefix('#') {
+                Some(tag) => self.tag(tag).map(|tag| tag.iter().copied().collect()),
+                None => block(&id).map(|block| vec![block]),
+            };
+
+            match resolved {
+                Ok(resolved) => blocks.extend(resolved),
+                Err(e) if required => return Err(e),
+                Err(_) => {}
+            }
+        }
+
+        let blocks = Arc::new(blocks);
+        self.cache.borrow_mut().insert(name.to_owned(), blocks.clone());
+
+        Ok(blocks)
+    }
+}
+
+pub type BiomeSet = Arc<HashSet<String>>;
+
+/// Resolves biome tags (e.g. `#minecraft:has_structure/village_plains`) the
+/// same way [`BlockTags`] resolves block tags. Biomes are kept by name.
+pub struct BiomeTags {
+    packs: Arc<DataPacks>,
+    cache: RefCell<HashMap<String, BiomeSet>>,
+}
+
+impl BiomeTags {
+    pub fn new(packs: Arc<DataPacks>) -> Self {
+        Self {
+            packs,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Resolves the list of biomes: either a tag prefixed with `#`, a single
+    /// biome or a list of biomes.
+    pub fn resolve_holder_set(&self, json: &Value) -> anyhow::Result<BiomeSet> {
+        match json {
+            Value::String(name) => match name.strip_prefix('#') {
+                Some(tag) => self.tag(tag),
+                None => Ok(Arc::new(HashSet::from([name.clone()]))),
+            },
+            Value::Array(names) => names
+                .iter()
+                .map(|name| name.as_str().map(str::to_owned).ok_or_else(|| anyhow!("Expected string, found {:?}", name)))
+                .collect::<anyhow::Result<HashSet<_>>>()
+                .map(Arc::new),
+            _ => Err(anyhow!("Expected string or array, found {:?}", json))
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> anyhow::Result<BiomeSet> {
+        if let Some(biomes) = self.cache.borrow().get(name) {
+            return Ok(biomes.clone())
+        }
+
+        let mut biomes = HashSet::new();
+        for (id, required) in self.packs.tag_entries("tags/worldgen/biome", name)? {
+            match id.strip_prefix('#') {
+                Some(tag) => match self.tag(tag) {
+                    Ok(tag) => biomes.extend(tag.iter().cloned()),
+                    Err(e) if required => return Err(e),
+                    Err(_) => {}
+                },
+                None => {
+                    biomes.insert(id);
+                }
+            }
+        }
+
+        let biomes = Arc::new(biomes);
+        self.cache.borrow_mut().insert(name.to_owned(), biomes.clone());
+
+        Ok(biomes)
+    }
+}
+
+fn block(name: &str) -> anyhow::Result<&'static Block> {
+    BLOCKS
+        .get(name)
+        .copied()
+        .ok_or_else(|| anyhow!("Unknown block {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pack::DataPacks;
+    use crate::tag::BlockTags;
+    use spherix_world::block::block::Block;
+    use std::sync::Arc;
+
+    #[test]
+    fn resolve_holder_set() {
+        let tags = BlockTags::new(Arc::new(DataPacks::new(Vec::new())));
+
+        let blocks = tags.resolve_holder_set(&serde_json::json!(["minecraft:stone", "minecraft:dirt"])).unwrap();
+        assert!(blocks.contains(Block::STONE));
+        assert!(blocks.contains(Block::DIRT));
+        assert!(!blocks.contains(Block::GRASS_BLOCK));
+
+        let blocks = tags.resolve_holder_set(&serde_json::json!("minecraft:netherrack")).unwrap();
+        assert_eq!(1, blocks.len());
+
+        assert!(tags.resolve_holder_set(&serde_json::json!("minecraft:unknown")).is_err());
+        assert!(tags.resolve_holder_set(&serde_json::json!("#minecraft:unknown")).is_err());
+    }
+}
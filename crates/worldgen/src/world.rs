@@ -0,0 +1,747 @@
+use crate::biome::accessor::BiomeAccessor;
+use crate::biome::climate::json::{create_biome_index_from_json, possible_biomes_from_json};
+use crate::biome::source::{BiomeSource, END_BIOMES};
+use crate::carver::registry::CarverRegistry;
+use crate::chunk::column::{ChunkColumn as WorldgenChunkColumn, Neighbour};
+use crate::chunk::flat::{FlatChunkGenerator, FlatSettings};
+use crate::chunk::generator::{ChunkGenerator, NoiseBasedChunkGenerator};
+use crate::chunk::step::{ChunkStep, CHUNK_STEPS};
+use crate::chunk::void::VoidChunkGenerator;
+use crate::feature::json::{feature_deserializers, placement_deserializers, predicate_deserializers};
+use crate::feature::registry::FeatureRegistry;
+use crate::noise::density::noise::NoiseHolder;
+use crate::noise::json::resolvable::Resolvable;
+use crate::noise::json::value_resolver::{CachedValueResolver, CascadeValueResolver, DataPackValueResolver, NoReturnValueResolver};
+use crate::noise::json::{deserializers, Resolver};
+use crate::noise::perlin::DefaultNoise;
+use crate::noise::settings::NoiseSettings;
+use crate::pack::DataPacks;
+use crate::rng::{RngForkable, RngPos, WorldgenRng};
+use crate::structure::registry::StructureRegistry;
+use crate::structure::{StructureContext, StructureStartCache};
+use crate::surface::bands::generate_bands;
+use crate::surface::condition_factory::ConditionFactories;
+use crate::surface::context::{EntropyBag, Noises, WorldGenerationContext};
+use crate::surface::json::{condition_deserializers, rule_deserializers};
+use crate::surface::rule_factory::RuleFactories;
+use crate::tag::{BiomeTags, BlockTags};
+use anyhow::anyhow;
+use serde_json::{json, Value};
+use spherix_world::chunk::column::ChunkColumn;
+use spherix_world::chunk::heightmap::HeightmapType;
+use spherix_world::chunk::palette::{create_biome_global_palette_from_json, create_block_global_palette_from_json, BiomeGlobalPalette, BlockGlobalPalette};
+use spherix_world::chunk::pos::ChunkPos;
+use spherix_world::chunk::status::ChunkStatus;
+use spherix_world::dimension::DimensionKind;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Generator of the chunks of a dimension along with everything it is set up
+/// from: the noise settings, the surface rule and the registries of the data
+/// packs.
+///
+/// ```no_run
+/// use spherix_world::chunk::pos::ChunkPos;
+/// use spherix_world::dimension::DimensionKind;
+/// use spherix_worldgen::world::WorldGenerator;
+///
+/// let generator = WorldGenerator::builder()
+///     .data_dir("./generated")
+///     .seed(1)
+///     .dimension(DimensionKind::Overworld)
+///     .build()?;
+///
+/// let chunk = generator.generate_chunk(ChunkPos::new(0, 0));
+/// # Ok::<(), spherix_worldgen::world::Error>(())
+/// ```
+pub struct WorldGenerator {
+    block_global_palette: Arc<BlockGlobalPalette>,
+    biome_global_palette: Arc<BiomeGlobalPalette>,
+    generator: Box<dyn ChunkGenerator + Send + Sync>,
+    noise_settings: NoiseSettings,
+    entropy_bag: Arc<EntropyBag>,
+    rule_factory: Arc<RuleFactories>,
+    carvers: CarverRegistry,
+    features: FeatureRegistry,
+    structures: StructureRegistry,
+    structure_starts: StructureStartCache,
+    seed: i64,
+}
+
+impl WorldGenerator {
+    pub fn builder() -> WorldGeneratorBuilder {
+        WorldGeneratorBuilder::default()
+    }
+
+    pub fn seed(&self) -> i64 {
+        self.seed
+    }
+
+    pub fn block_global_palette(&self) -> &Arc<BlockGlobalPalette> {
+        &self.block_global_palette
+    }
+
+    pub fn biome_global_palette(&self) -> &Arc<BiomeGlobalPalette> {
+        &self.biome_global_palette
+    }
+
+    pub fn noise_settings(&self) -> &NoiseSettings {
+        &self.noise_settings
+    }
+
+    pub fn features(&self) -> &FeatureRegistry {
+        &self.features
+    }
+
+    pub fn structures(&self) -> &StructureRegistry {
+        &self.structures
+    }
+
+    /// Empty chunk, which the steps of the generation are run on.
+    pub fn empty_chunk(&self, pos: ChunkPos) -> WorldgenChunkColumn {
+        WorldgenChunkColumn::new(ChunkColumn::empty(
+            pos,
+            self.block_global_palette.clone(),
+            self.biome_global_palette.clone(),
+        ))
+    }
+
+    /// Generates the chunk from scratch, along with the chunks around it,
+    /// which its steps read (see [`ChunkStep::dependency_radius()`]). The
+    /// chunks around are dropped, so generating many adjacent chunks this way
+    /// repeats a lot of work, which a scheduler sharing them avoids.
+    pub fn generate_chunk(&self, pos: ChunkPos) -> ChunkColumn {
+        let mut required = HashMap::new();
+        require(&mut required, pos.clone(), ChunkStatus::Full);
+
+        // Ordered, so the chunks are generated the same way every time.
+        let mut required = required.into_iter().collect::<Vec<_>>();
+        required.sort_by_key(|(pos, _)| (pos.x(), pos.z()));
+
+        let mut chunks = required
+            .iter()
+            .map(|(pos, _)| (pos.clone(), self.empty_chunk(pos.clone())))
+            .collect::<HashMap<_, _>>();
+
+        for step in CHUNK_STEPS.iter() {
+            for (at, status) in &required {
+                if status.ordinal() < step.status.ordinal() {
+                    continue
+                }
+
+                let mut chunk = chunks.remove(at).unwrap();
+                let neighbours = chunks
+                    .iter_mut()
+                    .filter(|(neighbour, _)| distance(at, neighbour) <= step.radius)
+                    .map(|(neighbour, chunk)| (neighbour.clone(), Neighbour::Writable(chunk)))
+                    .collect();
+
+                self.promote(step, neighbours, &mut chunk);
+                chunks.insert(at.clone(), chunk);
+            }
+        }
+
+        chunks.remove(&pos).unwrap().into_inner()
+    }
+
+    /// Runs the step of the generation on the chunk. The neighbours are the
+    /// chunks within the radius of the step, which have reached its neighbour
+    /// status. Only features write into the neighbours.
+    pub fn promote(
+        &self,
+        step: &ChunkStep,
+        neighbours: HashMap<ChunkPos, Neighbour>,
+        chunk: &mut WorldgenChunkColumn,
+    ) {
+        let noise_settings = &self.noise_settings;
+        let pos = chunk.pos();
+        let biome_sampler = self.generator.biome_sampler(noise_settings);
+
+        let base_height = |x, z, ty: HeightmapType| self.generator.base_height(noise_settings, x, z, ty);
+        let structure_ctx = StructureContext {
+            seed: self.seed,
+            gen_ctx: WorldGenerationContext {
+                height: noise_settings.noise_height as i32,
+                min_y: noise_settings.noise_min_y,
+            },
+            biome_sampler: &biome_sampler,
+            base_height: &base_height,
+        };
+        let starts = |pos: &ChunkPos| {
+            self.structure_starts.get_or_create(pos, || self.structures.create_starts(&structure_ctx, pos))
+        };
+
+        match step.status {
+            ChunkStatus::Biomes => {
+                self.generator.create_structures(&starts(&pos), chunk);
+                self.generator.create_references(&starts, chunk);
+                self.generator.fill_biomes(&biome_sampler, chunk);
+            }
+            ChunkStatus::Carvers => {
+                let neighbours = neighbours
+                    .iter()
+                    .map(|(at, neighbour)| (at.clone(), neighbour.get()))
+                    .collect::<HashMap<_, _>>();
+
+                let beardifier = self.generator.beardifier(&starts, chunk);
+                let noise_chunk = self.generator.fill_noise(noise_settings, beardifier, chunk);
+
+                // Without the noise there is nothing to build the surface on and carve.
+                if let Some(mut noise_chunk) = noise_chunk {
+                    self.generator.build_surface(
+                        noise_settings,
+                        self.entropy_bag.clone(),
+                        self.rule_factory.clone(),
+                        &mut noise_chunk,
+                        BiomeAccessor {
+                            neighbours: neighbours.clone(),
+                            sampler: biome_sampler.clone(),
+                        },
+                        chunk,
+                    );
+
+                    self.generator.carve(
+                        self.seed,
+                        noise_settings,
+                        self.entropy_bag.clone(),
+                        self.rule_factory.clone(),
+                        &self.carvers,
+                        &mut noise_chunk,
+                        BiomeAccessor {
+                            neighbours,
+                            sampler: biome_sampler.clone(),
+                        },
+                        chunk
+                    );
+                }
+            }
+            ChunkStatus::Features => self.generator.decorate(
+                self.seed,
+                noise_settings,
+                &self.features,
+                &self.structures,
+                &starts,
+                &biome_sampler,
+                neighbours,
+                chunk
+            ),
+            ChunkStatus::Full => {}
+            status => unreachable!("{} is not a status of a generation step", status.name())
+        }
+
+        // Generators skip the stages they have nothing to do at, but the chunk
+        // still reaches the status.
+        chunk.inner_mut().status = step.status;
+    }
+}
+
+/// Marks the statuses the chunks around have to reach before the chunk
+/// reaches the status.
+fn require(required: &mut HashMap<ChunkPos, ChunkStatus>, pos: ChunkPos, status: ChunkStatus) {
+    if required.get(&pos).is_some_and(|known| known.ordinal() >= status.ordinal()) {
+        return
+    }
+
+    required.insert(pos.clone(), status);
+
+    for step in CHUNK_STEPS.iter().filter(|step| step.status.ordinal() <= status.ordinal()) {
+        for x in pos.x() - step.radius..=pos.x() + step.radius {
+            for z in pos.z() - step.radius..=pos.z() + step.radius {
+                let neighbour = ChunkPos::new(x, z);
+
+                if neighbour != pos {
+                    require(required, neighbour, step.neighbour_status);
+                }
+            }
+        }
+    }
+}
+
+fn distance(a: &ChunkPos, b: &ChunkPos) -> i32 {
+    (a.x() - b.x()).abs().max((a.z() - b.z()).abs())
+}
+
+/// Generator kind of a dimension, which has no `dimension/*.json` in the data
+/// packs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneratorKind {
+    Noise,
+    /// Superflat world of the `worldgen/flat_level_generator_preset`.
+    Flat { preset: String },
+    Void,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// Option of the builder, which has to be set.
+    Missing(&'static str),
+    /// File of the data directory, which could not be read.
+    Read(PathBuf, anyhow::Error),
+    /// Invalid or unsupported data of the part of the generator.
+    Data(&'static str, anyhow::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Missing(option) => write!(f, "{} of the world generator is not set", option),
+            Error::Read(path, err) => write!(f, "unable to read {}: {}", path.display(), err),
+            Error::Data(what, err) => write!(f, "invalid {}: {}", what, err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Missing(_) => None,
+            Error::Read(_, err) | Error::Data(_, err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Options of the [`WorldGenerator`]. Only the data directory, i.e. the output
+/// of the vanilla data generator, is required. The palettes and the data packs
+/// are read from it, unless they are already loaded by the caller.
+pub struct WorldGeneratorBuilder {
+    data_dir: Option<PathBuf>,
+    data_packs: Vec<PathBuf>,
+    seed: i64,
+    dimension: DimensionKind,
+    kind: GeneratorKind,
+    palettes: Option<(Arc<BlockGlobalPalette>, Arc<BiomeGlobalPalette>)>,
+    packs: Option<Arc<DataPacks>>,
+}
+
+impl Default for WorldGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            data_packs: Vec::new(),
+            seed: 0,
+            dimension: DimensionKind::Overworld,
+            kind: GeneratorKind::Noise,
+            palettes: None,
+            packs: None,
+        }
+    }
+}
+
+impl WorldGeneratorBuilder {
+    pub fn data_dir<P: Into<PathBuf>>(mut self, data_dir: P) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// Data packs layered over the vanilla data, see [`DataPacks::load()`].
+    pub fn data_packs(mut self, data_packs: Vec<PathBuf>) -> Self {
+        self.data_packs = data_packs;
+        self
+    }
+
+    pub fn seed(mut self, seed: i64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn dimension(mut self, dimension: DimensionKind) -> Self {
+        self.dimension = dimension;
+        self
+    }
+
+    pub fn kind(mut self, kind: GeneratorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn palettes(mut self, blocks: Arc<BlockGlobalPalette>, biomes: Arc<BiomeGlobalPalette>) -> Self {
+        self.palettes = Some((blocks, biomes));
+        self
+    }
+
+    /// Already loaded data packs, which replace the ones of the data directory.
+    pub fn packs(mut self, packs: Arc<DataPacks>) -> Self {
+        self.packs = Some(packs);
+        self
+    }
+
+    pub fn build(self) -> Result<WorldGenerator, Error> {
+        let data_dir = self.data_dir.ok_or(Error::Missing("data_dir"))?;
+
+        let (palette, biome_global_palette) = match self.palettes {
+            Some(palettes) => palettes,
+            None => (
+                Arc::new(create_block_global_palette_from_json(read_json(&data_dir.join("reports/blocks.json"))?)),
+                Arc::new(read_biome_palette(&data_dir.join("registry_codec.json"))?),
+            ),
+        };
+
+        let packs = match self.packs {
+            Some(packs) => packs,
+            None => Arc::new(
+                DataPacks::load(data_dir.clone(), &self.data_packs).map_err(|e| Error::Data("data packs", e))?
+            ),
+        };
+
+        let seed = self.seed;
+
+        let mut df_resolver = Resolver::new(
+            deserializers(),
+            Box::new(
+                CachedValueResolver::new(
+                    CascadeValueResolver::new(
+                        vec![
+                            Box::new(DataPackValueResolver::new(packs.clone(), "worldgen/density_function")),
+                            Box::new(DataPackValueResolver::new(packs.clone(), "worldgen/noise"))
+                        ]
+                    )
+                )
+            )
+        );
+
+        let dimension = DimensionGenerator::new(self.dimension, &packs, &self.kind)
+            .map_err(|e| Error::Data("dimension", e))?;
+
+        let json = match dimension.noise_settings() {
+            Value::String(name) => packs.json("worldgen/noise_settings", name).map_err(|e| Error::Data("noise settings", e))?,
+            settings => settings.clone(),
+        };
+
+        // Noise settings define the height of the dimension for all the
+        // generators, though only the noise based one samples the router.
+        let noise_settings = NoiseSettings::from_json(&json, &mut df_resolver, palette.clone())
+            .map_err(|e| Error::Data("noise settings", e))?;
+
+        let mut rng = WorldgenRng::new(seed, noise_settings.use_legacy_random_source);
+        let forked = Arc::new(rng.fork_pos());
+
+        let (gen, noise_settings, possible_biomes, structure_sets) = match dimension {
+            DimensionGenerator::Noise { biome_source, .. } => {
+                let (biome_source, possible_biomes) = biome_source_from_json(&biome_source, &data_dir)
+                    .map_err(|e| Error::Data("biome source", e))?;
+
+                let (gen, noise_settings) = NoiseBasedChunkGenerator::new(
+                    noise_settings,
+                    palette.clone(),
+                    biome_global_palette.clone(),
+                    biome_source,
+                    seed,
+                    forked.clone()
+                );
+
+                (Box::new(gen) as Box<dyn ChunkGenerator + Send + Sync>, noise_settings, possible_biomes, None)
+            }
+            DimensionGenerator::Flat { settings, .. } => {
+                let gen = FlatSettings::from_json(&settings, &palette)
+                    .and_then(|settings| FlatChunkGenerator::new(settings, palette.clone(), biome_global_palette.clone()))
+                    .map_err(|e| Error::Data("flat settings", e))?;
+                let possible_biomes = vec![gen.settings().biome.clone()];
+                let structure_sets = gen.settings().structure_overrides.clone();
+
+                (Box::new(gen) as Box<dyn ChunkGenerator + Send + Sync>, noise_settings, possible_biomes, structure_sets)
+            }
+            DimensionGenerator::Void { .. } => (
+                Box::new(VoidChunkGenerator::new(biome_global_palette.clone())) as Box<dyn ChunkGenerator + Send + Sync>,
+                noise_settings,
+                vec![VoidChunkGenerator::BIOME.to_owned()],
+                Some(Vec::new())
+            ),
+        };
+
+        let condition_resolver = Resolver::new(
+            condition_deserializers(),
+            Box::new(DataPackValueResolver::new(packs.clone(), "worldgen/noise"))
+        );
+
+        let noise = |name: &str| deserialize_noise(&forked, &condition_resolver, name)
+            .map_err(|e| Error::Data("surface noises", e));
+
+        let entropy_bag = EntropyBag::new(
+            forked.clone(),
+            Noises {
+                clay_bands_offset: noise("minecraft:clay_bands_offset")?,
+                badlands_pillar: noise("minecraft:badlands_pillar")?,
+                badlands_pillar_roof: noise("minecraft:badlands_pillar_roof")?,
+                badlands_surface: noise("minecraft:badlands_surface")?,
+                iceberg_pillar: noise("minecraft:iceberg_pillar")?,
+                iceberg_pillar_roof: noise("minecraft:iceberg_pillar_roof")?,
+                iceberg_surface: noise("minecraft:iceberg_surface")?,
+                surface: noise("minecraft:surface")?,
+                surface_secondary: noise("minecraft:surface_secondary")?,
+            }
+        );
+
+        let surface_resolver = Resolver::new(
+            rule_deserializers(
+                condition_resolver,
+                palette.clone(),
+                generate_bands(&mut forked.by_hash("minecraft:clay_bands".to_owned()), palette.clone())
+            ),
+            Box::new(NoReturnValueResolver)
+        );
+
+        let surface_rule = json
+            .get("surface_rule")
+            .ok_or_else(|| Error::Data("surface rule", anyhow!("No \"surface_rule\" key in {:?}", json)))?;
+        let surface_rule_factory = surface_resolver
+            .resolve(surface_rule)
+            .map_err(|e| Error::Data("surface rule", e))?;
+
+        let tags = Rc::new(BlockTags::new(packs.clone()));
+
+        let carvers = CarverRegistry::load(&packs, &tags).map_err(|e| Error::Data("carvers", e))?;
+
+        let predicate_resolver = Rc::new(Resolver::new(
+            predicate_deserializers(tags.clone(), palette.clone()),
+            Box::new(NoReturnValueResolver)
+        ));
+
+        let placement_resolver = Rc::new(Resolver::new(
+            placement_deserializers(predicate_resolver.clone()),
+            Box::new(DataPackValueResolver::new(packs.clone(), "worldgen/placed_feature"))
+        ));
+
+        let feature_resolver = Resolver::new(
+            feature_deserializers(tags.clone(), palette.clone(), predicate_resolver, placement_resolver.clone()),
+            Box::new(DataPackValueResolver::new(packs.clone(), "worldgen/configured_feature"))
+        );
+
+        let features = FeatureRegistry::load(
+            &packs,
+            &possible_biomes,
+            &feature_resolver,
+            &placement_resolver
+        ).map_err(|e| Error::Data("features", e))?;
+
+        let mut structures = StructureRegistry::load(
+            &packs,
+            &tags,
+            &BiomeTags::new(packs.clone()),
+            &possible_biomes,
+            &palette,
+            &feature_resolver,
+            &placement_resolver
+        ).map_err(|e| Error::Data("structures", e))?;
+
+        if let Some(structure_sets) = structure_sets {
+            structures.retain_sets(|set| structure_sets.contains(&set.name));
+        }
+
+        Ok(WorldGenerator {
+            block_global_palette: palette,
+            biome_global_palette,
+            generator: gen,
+            noise_settings,
+            entropy_bag: Arc::new(entropy_bag),
+            rule_factory: Arc::new(surface_rule_factory),
+            carvers,
+            features,
+            structures,
+            structure_starts: StructureStartCache::new(4096),
+            seed,
+        })
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value, Error> {
+    let read = || -> anyhow::Result<Value> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    };
+
+    read().map_err(|e| Error::Read(path.to_path_buf(), e))
+}
+
+/// Biomes of the registry codec, which the server sends to the clients.
+fn read_biome_palette(path: &Path) -> Result<BiomeGlobalPalette, Error> {
+    let json = read_json(path)?;
+    let biomes = json
+        .get("minecraft:worldgen/biome")
+        .and_then(|registry| registry.get("value"))
+        .ok_or_else(|| Error::Read(path.to_path_buf(), anyhow!("No \"minecraft:worldgen/biome\" registry")))?;
+
+    Ok(create_biome_global_palette_from_json(biomes))
+}
+
+/// Generator of the dimension. A `dimension/*.json` of the data packs takes
+/// precedence over the generator kind of the builder.
+enum DimensionGenerator {
+    /// Noise settings are either a name or inline settings.
+    Noise { settings: Value, biome_source: Value },
+    Flat { settings: Value, noise_settings: Value },
+    Void { noise_settings: Value },
+}
+
+impl DimensionGenerator {
+    fn new(dim: DimensionKind, packs: &DataPacks, kind: &GeneratorKind) -> anyhow::Result<Self> {
+        let (name, settings_name) = match dim {
+            DimensionKind::Overworld => ("minecraft:overworld", "minecraft:overworld"),
+            DimensionKind::TheNether => ("minecraft:the_nether", "minecraft:nether"),
+            DimensionKind::TheEnd => ("minecraft:the_end", "minecraft:end"),
+        };
+        let noise_settings = Value::String(settings_name.to_owned());
+
+        if let Some(json) = packs.read_json("dimension", name)? {
+            let generator = json.get("generator").ok_or_else(|| anyhow!("No \"generator\" key in {:?}", json))?;
+            let Some(Value::String(ty)) = generator.get("type") else {
+                return Err(anyhow!("No \"type\" key in {:?}", generator))
+            };
+            let settings = generator
+                .get("settings")
+                .cloned()
+                .ok_or_else(|| anyhow!("No \"settings\" key in {:?}", generator));
+
+            return match ty.as_str() {
+                "minecraft:noise" => Ok(Self::Noise {
+                    settings: settings?,
+                    biome_source: generator
+                        .get("biome_source")
+                        .cloned()
+                        .ok_or_else(|| anyhow!("No \"biome_source\" key in {:?}", generator))?,
+                }),
+                "minecraft:flat" => Ok(Self::Flat { settings: settings?, noise_settings }),
+                _ => Err(anyhow!("Unsupported generator type {} of dimension {}", ty, name))
+            }
+        }
+
+        // The End has no biome parameters, its biomes are picked by the island
+        // falloff.
+        let biome_source = match dim {
+            DimensionKind::Overworld => json!({"type": "minecraft:multi_noise", "preset": "minecraft:overworld"}),
+            DimensionKind::TheNether => json!({"type": "minecraft:multi_noise", "preset": "minecraft:nether"}),
+            DimensionKind::TheEnd => json!({"type": "minecraft:the_end"}),
+        };
+
+        Ok(match kind {
+            GeneratorKind::Noise => Self::Noise { settings: noise_settings, biome_source },
+            GeneratorKind::Flat { preset } => {
+                let preset = packs.json("worldgen/flat_level_generator_preset", preset)?;
+                let settings = preset
+                    .get("settings")
+                    .cloned()
+                    .ok_or_else(|| anyhow!("No \"settings\" key in {:?}", preset))?;
+
+                Self::Flat { settings, noise_settings }
+            }
+            GeneratorKind::Void => Self::Void { noise_settings },
+        })
+    }
+
+    fn noise_settings(&self) -> &Value {
+        match self {
+            Self::Noise { settings, .. } => settings,
+            Self::Flat { noise_settings, .. } | Self::Void { noise_settings } => noise_settings,
+        }
+    }
+}
+
+/// Reads the biome source of a noise generator along with its possible
+/// biomes. Multi noise presets are read from the reports of the data directory.
+fn biome_source_from_json(json: &Value, data_dir: &Path) -> anyhow::Result<(BiomeSource, Vec<String>)> {
+    let Some(Value::String(ty)) = json.get("type") else {
+        return Err(anyhow!("No \"type\" key in {:?}", json))
+    };
+
+    match ty.as_str() {
+        "minecraft:multi_noise" => {
+            let parameters = match json.get("preset") {
+                Some(Value::String(preset)) => {
+                    let (namespace, path) = preset.split_once(':').unwrap_or(("minecraft", preset));
+                    let path = data_dir.join(format!("reports/biome_parameters/{}/{}.json", namespace, path));
+
+                    std::fs::read_to_string(&path).map_err(|e| anyhow!("Unable to read {}: {}", path.display(), e))?
+                }
+                _ => serde_json::to_string(json)?
+            };
+
+            Ok((
+                BiomeSource::MultiNoise(Arc::new(create_biome_index_from_json(parameters.clone())?)),
+                possible_biomes_from_json(&parameters)?
+            ))
+        }
+        "minecraft:the_end" => Ok((
+            BiomeSource::TheEnd,
+            END_BIOMES.iter().map(|biome| biome.to_string()).collect()
+        )),
+        "minecraft:fixed" => {
+            let Some(Value::String(biome)) = json.get("biome") else {
+                return Err(anyhow!("No \"biome\" key in {:?}", json))
+            };
+
+            Ok((BiomeSource::Fixed(biome.clone()), vec![biome.clone()]))
+        }
+        _ => Err(anyhow!("Unsupported biome source type {}", ty))
+    }
+}
+
+fn deserialize_noise<R: RngPos, F: AsRef<R>>(
+    rng: F,
+    resolver: &Resolver<ConditionFactories>,
+    name: &str
+) -> anyhow::Result<Arc<NoiseHolder<DefaultNoise>>> {
+    resolver.contextual_name.set(Some(name.to_owned()));
+
+    let noise = NoiseHolder::resolve(&resolver.resolve_value(name.to_owned())?, resolver)?;
+
+    Ok(noise
+        .with_rng(&mut rng.as_ref().by_hash(name.to_owned()))
+        .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chunk::step::ChunkStep;
+    use crate::world::{require, Error, WorldGenerator};
+    use spherix_world::block::block::Block;
+    use spherix_world::chunk::pos::ChunkPos;
+    use spherix_world::chunk::status::ChunkStatus;
+    use spherix_world::chunk::vector::Vector3BlockColumn;
+    use spherix_world::dimension::DimensionKind;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    #[test]
+    fn chunk_requires_its_dependencies() {
+        let mut required = HashMap::new();
+        require(&mut required, ChunkPos::new(3, -2), ChunkStatus::Features);
+
+        let radius = ChunkStep::dependency_radius(ChunkStatus::Features);
+        assert_eq!(((2 * radius + 1) * (2 * radius + 1)) as usize, required.len());
+
+        assert_eq!(ChunkStatus::Features, required[&ChunkPos::new(3, -2)]);
+        assert_eq!(ChunkStatus::Carvers, required[&ChunkPos::new(4, -1)]);
+        assert_eq!(ChunkStatus::Biomes, required[&ChunkPos::new(5, -4)]);
+    }
+
+    #[test]
+    fn data_dir_is_required() {
+        assert!(matches!(WorldGenerator::builder().seed(1).build(), Err(Error::Missing("data_dir"))));
+    }
+
+    #[test]
+    #[ignore = "needs the vanilla data generated into ./generated"]
+    fn nether_has_a_lava_sea() {
+        let generator = WorldGenerator::builder()
+            .data_dir(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../generated"))
+            .seed(1)
+            .dimension(DimensionKind::TheNether)
+            .build()
+            .unwrap();
+
+        let noise_settings = generator.noise_settings();
+        assert_eq!(32, noise_settings.sea_level);
+        assert_eq!(Block::NETHERRACK, noise_settings.default_block.block());
+        assert_eq!(Block::LAVA, noise_settings.default_fluid.block());
+
+        let chunk = generator.generate_chunk(ChunkPos::new(0, 0));
+        let blocks = (0..16u32)
+            .flat_map(|x| (0..16u32).flat_map(move |z| (0..128).map(move |y| (x, y, z))))
+            .map(|(x, y, z)| (y, chunk.block_state(Vector3BlockColumn::new(x, y, z)).unwrap().block()))
+            .collect::<Vec<_>>();
+
+        assert!(blocks.iter().any(|(_, block)| *block == Block::NETHERRACK));
+        assert!(blocks.iter().any(|(y, block)| *y <= 31 && *block == Block::LAVA));
+    }
+}